@@ -1,3 +1,4 @@
+use crate::control::KinematicPlatform;
 use crate::dynamics::RigidBodySet;
 use crate::geometry::{ColliderHandle, ColliderSet, ContactManifold, Shape, ShapeCastHit};
 use crate::math::{Isometry, Point, Real, UnitVector, Vector};
@@ -211,7 +212,6 @@ impl KinematicCharacterController {
     }
 
     /// Computes the possible movement for a shape.
-    #[profiling::function]
     pub fn move_shape(
         &self,
         dt: Real,
@@ -222,6 +222,40 @@ impl KinematicCharacterController {
         character_pos: &Isometry<Real>,
         desired_translation: Vector<Real>,
         filter: QueryFilter,
+        events: impl FnMut(CharacterCollision),
+    ) -> EffectiveCharacterMovement {
+        self.move_shape_on_platforms(
+            dt,
+            bodies,
+            colliders,
+            queries,
+            character_shape,
+            character_pos,
+            desired_translation,
+            filter,
+            &[],
+            events,
+        )
+    }
+
+    /// Computes the possible movement for a shape, carried along by the given moving platforms.
+    ///
+    /// This is the same as [`Self::move_shape`], except that the velocity of the colliders
+    /// attached to the rigid-body of one of the `platforms` is given by
+    /// [`KinematicPlatform::velocity_at_point`] instead of the velocity of their rigid-body.
+    /// This lets the character follow platforms whose pose is set directly every frame.
+    #[profiling::function]
+    pub fn move_shape_on_platforms(
+        &self,
+        dt: Real,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        queries: &QueryPipeline,
+        character_shape: &dyn Shape,
+        character_pos: &Isometry<Real>,
+        desired_translation: Vector<Real>,
+        filter: QueryFilter,
+        platforms: &[KinematicPlatform],
         mut events: impl FnMut(CharacterCollision),
     ) -> EffectiveCharacterMovement {
         let mut result = EffectiveCharacterMovement {
@@ -245,6 +279,7 @@ impl KinematicCharacterController {
             character_pos,
             &dims,
             filter,
+            platforms,
             None,
             None,
         );
@@ -331,6 +366,7 @@ impl KinematicCharacterController {
                     &(Translation::from(result.translation) * character_pos),
                     &dims,
                     filter,
+                    platforms,
                     None,
                     None,
                 );
@@ -345,6 +381,7 @@ impl KinematicCharacterController {
                 &(Translation::from(result.translation) * character_pos),
                 &dims,
                 filter,
+                platforms,
                 Some(&mut kinematic_friction_translation),
                 Some(&mut translation_remaining),
             );
@@ -365,6 +402,7 @@ impl KinematicCharacterController {
                 &(Translation::from(result.translation) * character_pos),
                 &dims,
                 filter,
+                platforms,
                 None,
                 None,
             );
@@ -442,6 +480,7 @@ impl KinematicCharacterController {
         character_pos: &Isometry<Real>,
         dims: &Vector2<Real>,
         filter: QueryFilter,
+        platforms: &[KinematicPlatform],
         mut kinematic_friction_translation: Option<&mut Vector<Real>>,
         mut translation_remaining: Option<&mut Vector<Real>>,
     ) -> bool {
@@ -458,6 +497,10 @@ impl KinematicCharacterController {
         let mut grounded = false;
 
         queries.colliders_with_aabb_intersecting_aabb(&character_aabb, |handle| {
+            if !queries.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
             if let Some(collider) = colliders.get(*handle) {
                 if filter.test(bodies, *handle, collider) {
                     manifolds.clear();
@@ -476,17 +519,29 @@ impl KinematicCharacterController {
                         translation_remaining.as_deref_mut(),
                     ) {
                         let init_kinematic_friction_translation = *kinematic_friction_translation;
-                        let kinematic_parent = collider
-                            .parent
-                            .and_then(|p| bodies.get(p.handle))
+                        let parent = collider.parent.map(|p| p.handle);
+                        let platform = parent
+                            .and_then(|handle| platforms.iter().find(|pl| pl.body() == handle));
+                        let kinematic_parent = parent
+                            .and_then(|handle| bodies.get(handle))
                             .filter(|rb| rb.is_kinematic());
+                        let is_moving_parent = platform.is_some() || kinematic_parent.is_some();
+                        let parent_velocity_at_point = |point: &Point<Real>| {
+                            if let Some(platform) = platform {
+                                platform.velocity_at_point(point)
+                            } else {
+                                kinematic_parent
+                                    .map(|rb| rb.velocity_at_point(point))
+                                    .unwrap_or_else(Vector::zeros)
+                            }
+                        };
 
                         for m in &manifolds {
                             if self.is_grounded_at_contact_manifold(m, character_pos, dims) {
                                 grounded = true;
                             }
 
-                            if let Some(kinematic_parent) = kinematic_parent {
+                            if is_moving_parent {
                                 let mut num_active_contacts = 0;
                                 let mut manifold_center = Point::origin();
                                 let normal = -(character_pos * m.local_n1);
@@ -495,8 +550,7 @@ impl KinematicCharacterController {
                                     if contact.dist <= prediction {
                                         num_active_contacts += 1;
                                         let contact_point = collider.position() * contact.local_p2;
-                                        let target_vel =
-                                            kinematic_parent.velocity_at_point(&contact_point);
+                                        let target_vel = parent_velocity_at_point(&contact_point);
 
                                         let normal_target_mvt = target_vel.dot(&normal) * dt;
                                         let normal_current_mvt = translation_remaining.dot(&normal);
@@ -508,7 +562,7 @@ impl KinematicCharacterController {
                                 }
 
                                 if num_active_contacts > 0 {
-                                    let target_vel = kinematic_parent.velocity_at_point(
+                                    let target_vel = parent_velocity_at_point(
                                         &(manifold_center / num_active_contacts as Real),
                                     );
                                     let tangent_platform_mvt =
@@ -877,6 +931,10 @@ impl KinematicCharacterController {
             .loosened(prediction);
 
         queries.colliders_with_aabb_intersecting_aabb(&character_aabb, |handle| {
+            if !queries.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
             if let Some(collider) = colliders.get(*handle) {
                 if let Some(parent) = collider.parent {
                     if filter.test(bodies, *handle, collider) {
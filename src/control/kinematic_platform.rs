@@ -0,0 +1,203 @@
+use crate::dynamics::{RigidBodyHandle, RigidBodyPosition, RigidBodySet, RigidBodyVelocity};
+use crate::math::{Isometry, Point, Real, Vector};
+use crate::pipeline::ContactModificationContext;
+use crate::utils;
+use std::collections::VecDeque;
+
+/// A pose of a [`KinematicPlatform`], recorded at a given time.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlatformPose {
+    /// The time at which this pose was recorded.
+    pub time: Real,
+    /// The world-space pose of the platform at `time`.
+    pub pose: Isometry<Real>,
+}
+
+/// A moving platform, e.g., an elevator or a rotating disk, estimating its velocity from the
+/// history of its poses.
+///
+/// Platforms animated by setting the pose of their rigid-body directly every frame, e.g., from
+/// an animation track or a network replication, don’t have any meaningful rigid-body velocity.
+/// Recording their poses with [`KinematicPlatform::record`] lets every system carrying
+/// objects along with the platform use the same velocity, including its angular contribution:
+/// - the [`KinematicCharacterController`](crate::control::KinematicCharacterController)
+///   through [`KinematicCharacterController::move_shape_on_platforms`](crate::control::KinematicCharacterController::move_shape_on_platforms),
+/// - the contact constraints through [`KinematicPlatform::modify_solver_contacts`].
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct KinematicPlatform {
+    body: RigidBodyHandle,
+    history: VecDeque<PlatformPose>,
+    max_history_len: usize,
+}
+
+impl KinematicPlatform {
+    /// Creates a platform for the given rigid-body, keeping at most the `max_history_len` latest
+    /// recorded poses.
+    ///
+    /// At least the two latest poses are always kept since they are needed to estimate the
+    /// platform’s velocity.
+    pub fn new(body: RigidBodyHandle, max_history_len: usize) -> Self {
+        let max_history_len = max_history_len.max(2);
+        Self {
+            body,
+            history: VecDeque::with_capacity(max_history_len),
+            max_history_len,
+        }
+    }
+
+    /// The rigid-body moved as this platform.
+    pub fn body(&self) -> RigidBodyHandle {
+        self.body
+    }
+
+    /// The recorded poses, from the oldest to the latest.
+    pub fn history(&self) -> impl ExactSizeIterator<Item = &PlatformPose> + '_ {
+        self.history.iter()
+    }
+
+    /// The latest recorded pose.
+    pub fn latest_pose(&self) -> Option<&PlatformPose> {
+        self.history.back()
+    }
+
+    /// Forgets all the recorded poses, e.g., after teleporting the platform.
+    ///
+    /// The platform has a zero velocity until two new poses are recorded.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Records the pose of the platform at the given time.
+    ///
+    /// The poses must be recorded with increasing times.
+    pub fn record_pose(&mut self, time: Real, pose: Isometry<Real>) {
+        if self.history.len() == self.max_history_len {
+            let _ = self.history.pop_front();
+        }
+
+        self.history.push_back(PlatformPose { time, pose });
+    }
+
+    /// Records the current position of the platform’s rigid-body at the given time.
+    ///
+    /// This is typically called once per frame, right after the platform’s rigid-body was moved.
+    pub fn record(&mut self, bodies: &RigidBodySet, time: Real) {
+        if let Some(rb) = bodies.get(self.body) {
+            self.record_pose(time, *rb.position());
+        }
+    }
+
+    /// The velocity of the platform, estimated from its two latest recorded poses.
+    ///
+    /// The linear velocity is the velocity of the origin of the platform’s local frame. This is
+    /// zero if less than two poses were recorded.
+    pub fn velocity(&self) -> RigidBodyVelocity {
+        let len = self.history.len();
+
+        if len < 2 {
+            return RigidBodyVelocity::zero();
+        }
+
+        let prev = &self.history[len - 2];
+        let last = &self.history[len - 1];
+        let dt = last.time - prev.time;
+
+        if dt <= 0.0 {
+            return RigidBodyVelocity::zero();
+        }
+
+        RigidBodyPosition {
+            position: prev.pose,
+            next_position: last.pose,
+        }
+        .interpolate_velocity(utils::inv(dt), &Point::origin())
+    }
+
+    /// The velocity of the given world-space point attached to the platform.
+    pub fn velocity_at_point(&self, point: &Point<Real>) -> Vector<Real> {
+        let Some(last) = self.history.back() else {
+            return Vector::zeros();
+        };
+
+        self.velocity()
+            .velocity_at_point(point, &Point::from(last.pose.translation.vector))
+    }
+
+    /// Sets the tangent velocities of the solver contacts involving the platform so that the
+    /// bodies touching it are dragged along by friction.
+    ///
+    /// This is intended to be called from [`PhysicsHooks::modify_solver_contacts`](crate::pipeline::PhysicsHooks::modify_solver_contacts)
+    /// for the colliders attached to the platform, which need the
+    /// [`ActiveHooks::MODIFY_SOLVER_CONTACTS`](crate::pipeline::ActiveHooks::MODIFY_SOLVER_CONTACTS)
+    /// flag. Only the part of the platform’s velocity not already accounted for by the
+    /// velocity of its rigid-body is added, so this also works for
+    /// kinematic bodies with a non-zero velocity. Nothing is changed if neither body involved
+    /// in the contact is this platform.
+    pub fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+        // The tangent velocity is the desired velocity of the second body relative to the first.
+        let sign = if context.rigid_body1 == Some(self.body) {
+            1.0
+        } else if context.rigid_body2 == Some(self.body) {
+            -1.0
+        } else {
+            return;
+        };
+
+        let rb = context.bodies.get(self.body);
+        let normal = *context.normal;
+
+        for contact in context.solver_contacts.iter_mut() {
+            let mut vel = self.velocity_at_point(&contact.point);
+
+            if let Some(rb) = rb {
+                vel -= rb.velocity_at_point(&contact.point);
+            }
+
+            let tangent_vel = vel - normal * normal.dot(&vel);
+            contact.tangent_velocity += tangent_vel * sign;
+        }
+    }
+}
+
+#[cfg(all(feature = "dim3", feature = "f32"))]
+#[cfg(test)]
+mod test {
+    use super::KinematicPlatform;
+    use crate::prelude::*;
+
+    #[test]
+    fn platform_velocity_includes_rotation() {
+        let mut bodies = RigidBodySet::new();
+        let body = bodies.insert(RigidBodyBuilder::kinematic_position_based());
+        let mut platform = KinematicPlatform::new(body, 4);
+
+        platform.record(&bodies, 0.0);
+        assert_eq!(
+            platform.velocity_at_point(&point![1.0, 0.0, 0.0]),
+            Vector::zeros()
+        );
+
+        // Translate along X and rotate around Y at 1 unit/s and 1 rad/s.
+        bodies[body].set_position(
+            Isometry::new(vector![0.1, 0.0, 0.0], vector![0.0, 0.1, 0.0]),
+            true,
+        );
+        platform.record(&bodies, 0.1);
+
+        let vel = platform.velocity_at_point(&point![1.1, 0.0, 0.0]);
+        assert!((vel - vector![1.0, 0.0, -1.0]).norm() < 1.0e-4);
+
+        for i in 2..10 {
+            platform.record(&bodies, i as Real * 0.1);
+        }
+
+        // The platform stopped moving, and only the latest poses are kept.
+        assert_eq!(platform.history().len(), 4);
+        assert_eq!(
+            platform.velocity_at_point(&point![1.1, 0.0, 0.0]),
+            Vector::zeros()
+        );
+    }
+}
@@ -4,11 +4,13 @@ pub use self::character_controller::{
     CharacterAutostep, CharacterCollision, CharacterLength, EffectiveCharacterMovement,
     KinematicCharacterController,
 };
+pub use self::kinematic_platform::{KinematicPlatform, PlatformPose};
 
 #[cfg(feature = "dim3")]
 pub use self::ray_cast_vehicle_controller::{DynamicRayCastVehicleController, Wheel, WheelTuning};
 
 mod character_controller;
+mod kinematic_platform;
 
 #[cfg(feature = "dim3")]
 mod ray_cast_vehicle_controller;
@@ -10,6 +10,21 @@ pub struct CollisionDetectionCounters {
     pub broad_phase_time: Timer,
     /// Time spent for the narrow-phase of the collision detection.
     pub narrow_phase_time: Timer,
+    /// Number of contact pairs with contacts recomputed by the narrow-phase.
+    pub narrow_phase_updated_pairs: usize,
+    /// The longest time, in milliseconds, spent computing the contacts of a single contact pair.
+    ///
+    /// This is only measured if the `profiler` feature is enabled. If this is close to the
+    /// [`Self::narrow_phase_time`], a few expensive pairs (e.g. involving large triangle meshes)
+    /// dominate the narrow-phase and can’t be balanced across threads.
+    pub narrow_phase_max_pair_time: f64,
+    /// The time, in milliseconds, spent computing the contacts of all the contact pairs, summed
+    /// over all the threads.
+    ///
+    /// This is only measured if the `profiler` feature is enabled. Dividing it by the
+    /// [`Self::narrow_phase_time`] gives the average number of threads kept busy by the
+    /// narrow-phase.
+    pub narrow_phase_total_pair_time: f64,
 }
 
 impl CollisionDetectionCounters {
@@ -19,6 +34,9 @@ impl CollisionDetectionCounters {
             ncontact_pairs: 0,
             broad_phase_time: Timer::new(),
             narrow_phase_time: Timer::new(),
+            narrow_phase_updated_pairs: 0,
+            narrow_phase_max_pair_time: 0.0,
+            narrow_phase_total_pair_time: 0.0,
         }
     }
 
@@ -27,6 +45,9 @@ impl CollisionDetectionCounters {
         self.ncontact_pairs = 0;
         self.broad_phase_time.reset();
         self.narrow_phase_time.reset();
+        self.narrow_phase_updated_pairs = 0;
+        self.narrow_phase_max_pair_time = 0.0;
+        self.narrow_phase_total_pair_time = 0.0;
     }
 }
 
@@ -34,6 +55,21 @@ impl Display for CollisionDetectionCounters {
     fn fmt(&self, f: &mut Formatter) -> Result {
         writeln!(f, "Number of contact pairs: {}", self.ncontact_pairs)?;
         writeln!(f, "Broad-phase time: {}", self.broad_phase_time)?;
-        writeln!(f, "Narrow-phase time: {}", self.narrow_phase_time)
+        writeln!(f, "Narrow-phase time: {}", self.narrow_phase_time)?;
+        writeln!(
+            f,
+            "Narrow-phase updated pairs: {}",
+            self.narrow_phase_updated_pairs
+        )?;
+        writeln!(
+            f,
+            "Narrow-phase max pair time: {}",
+            self.narrow_phase_max_pair_time
+        )?;
+        writeln!(
+            f,
+            "Narrow-phase total pair time: {}",
+            self.narrow_phase_total_pair_time
+        )
     }
 }
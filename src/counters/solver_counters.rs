@@ -8,6 +8,13 @@ pub struct SolverCounters {
     pub nconstraints: usize,
     /// Number of contacts found.
     pub ncontacts: usize,
+    /// Total number of PGS iterations run by all the islands, over all the substeps.
+    ///
+    /// With [`IntegrationParameters::velocity_convergence`](crate::dynamics::IntegrationParameters::velocity_convergence),
+    /// this is the number of iterations actually run before the islands converged.
+    pub num_pgs_iterations: usize,
+    /// The largest number of PGS iterations run by a single island, over all the substeps.
+    pub max_island_pgs_iterations: usize,
     /// Time spent for the resolution of the constraints (force computation).
     pub velocity_resolution_time: Timer,
     /// Time spent for the assembly of all the velocity constraints.
@@ -24,6 +31,8 @@ impl SolverCounters {
         SolverCounters {
             nconstraints: 0,
             ncontacts: 0,
+            num_pgs_iterations: 0,
+            max_island_pgs_iterations: 0,
             velocity_assembly_time: Timer::new(),
             velocity_resolution_time: Timer::new(),
             velocity_update_time: Timer::new(),
@@ -35,6 +44,8 @@ impl SolverCounters {
     pub fn reset(&mut self) {
         self.nconstraints = 0;
         self.ncontacts = 0;
+        self.num_pgs_iterations = 0;
+        self.max_island_pgs_iterations = 0;
         self.velocity_resolution_time.reset();
         self.velocity_assembly_time.reset();
         self.velocity_update_time.reset();
@@ -46,6 +57,12 @@ impl Display for SolverCounters {
     fn fmt(&self, f: &mut Formatter) -> Result {
         writeln!(f, "Number of contacts: {}", self.ncontacts)?;
         writeln!(f, "Number of constraints: {}", self.nconstraints)?;
+        writeln!(f, "Number of PGS iterations: {}", self.num_pgs_iterations)?;
+        writeln!(
+            f,
+            "Max. PGS iterations per island: {}",
+            self.max_island_pgs_iterations
+        )?;
         writeln!(f, "Velocity assembly time: {}", self.velocity_assembly_time)?;
         writeln!(
             f,
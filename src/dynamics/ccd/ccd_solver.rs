@@ -92,7 +92,10 @@ impl CCDSolver {
         for handle in islands.active_dynamic_bodies() {
             let rb = bodies.index_mut_internal(*handle);
 
-            if rb.ccd.ccd_enabled {
+            if rb.ccd.ccd_enabled && rb.is_debris() {
+                // Debris are never affected by CCD.
+                rb.ccd.ccd_active = false;
+            } else if rb.ccd.ccd_enabled {
                 let forces = if include_forces {
                     Some(&rb.forces)
                 } else {
@@ -1,16 +1,68 @@
+use crate::geometry::NarrowPhase;
 use crate::math::Real;
 use na::RealField;
+use std::borrow::Cow;
 use std::num::NonZeroUsize;
 
 #[cfg(doc)]
 use super::RigidBodyActivation;
 
-// TODO: enabling the block solver in 3d introduces a lot of jitters in
-//       the 3D domino demo. So for now we dont enable it in 3D.
-pub(crate) static BLOCK_SOLVER_ENABLED: bool = cfg!(feature = "dim2");
+/// The strategy used to correct the penetrations of the contacts.
+///
+/// Each strategy has different artifacts, so the best one depends on the simulated scene. This
+/// doesn’t affect the joints which are always stabilized with [`Self::Baumgarte`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum PositionCorrectionMode {
+    /// The penetrations are corrected by a velocity bias added to the contact constraints at
+    /// each substep (default).
+    ///
+    /// The velocity added by the bias is mostly removed by the stabilization iterations, but
+    /// deep penetrations can still make bodies pop out with some extra energy.
+    #[default]
+    Baumgarte,
+    /// The velocities are solved without bias, and the penetrations are then corrected separately
+    /// with [`IntegrationParameters::num_position_iterations`] iterations on pseudo-velocities
+    /// which only move the bodies.
+    ///
+    /// This doesn’t inject any energy, but the penetrations are only corrected once per step,
+    /// which makes deep contacts, e.g., at the bottom of tall stacks, sink more.
+    SplitImpulse,
+    /// The velocities are solved without bias, and the penetrations are then corrected directly
+    /// on the positions with [`IntegrationParameters::num_position_iterations`] iterations of
+    /// nonlinear Gauss-Seidel.
+    ///
+    /// Unlike [`Self::SplitImpulse`], the penetration depths are re-evaluated from the updated
+    /// positions at each iteration, which converges better for large rotations and deep
+    /// penetrations, at the price of updating the contact constraints at each iteration.
+    NonlinearGaussSeidel,
+}
+
+/// How the normal and friction impulses of the contact points are solved relative to each other.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum ContactFrictionMode {
+    /// At each solver iteration, the normal impulses of all the contact constraints are solved
+    /// first, then their friction impulses are solved given these normal impulses (default).
+    ///
+    /// This is the cheapest mode, but the friction always lags one pass behind the normal
+    /// impulses, which is visible on bodies sliding under large loads.
+    #[default]
+    Staggered,
+    /// The normal and friction impulses of each contact point are solved together, as a small
+    /// cone complementarity problem.
+    ///
+    /// The impulses are first solved without bounds with the whole mass matrix of the contact
+    /// point, then projected onto the friction cone, the normal impulse being recomputed for the
+    /// projected friction impulse. This makes sliding contacts, e.g., granular flows or drifting
+    /// vehicles, converge faster, at the price of a more expensive solve for each contact point.
+    /// The contacts involving multibody links are solved normal first, then friction, one
+    /// constraint after the other.
+    Coupled,
+}
 
 /// Parameters for a time-step of the physics engine.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct IntegrationParameters {
     /// The timestep length (default: `1.0 / 60.0`).
@@ -56,6 +108,14 @@ pub struct IntegrationParameters {
     /// (default `1.0`).
     pub joint_damping_ratio: Real,
 
+    /// The strategy used to correct the penetrations of the contacts (default:
+    /// [`PositionCorrectionMode::Baumgarte`]).
+    pub position_correction_mode: PositionCorrectionMode,
+    /// The number of position correction iterations run at the end of each step with the
+    /// [`PositionCorrectionMode::SplitImpulse`] and [`PositionCorrectionMode::NonlinearGaussSeidel`]
+    /// modes (default: `4`).
+    pub num_position_iterations: usize,
+
     /// The coefficient in `[0, 1]` applied to warmstart impulses, i.e., impulses that are used as the
     /// initial solution (instead of 0) at the next simulation step.
     ///
@@ -91,18 +151,345 @@ pub struct IntegrationParameters {
     ///
     /// This value is implicitly scaled by [`IntegrationParameters::length_unit`].
     pub normalized_prediction_distance: Real,
+    /// The distance a collider can move relative to a fixed heightfield before the heightfield
+    /// cells it touches are queried again (default: `0.0`, disabling the cache).
+    ///
+    /// While the collider moves by less than this distance, the contacts computed at the last
+    /// query are only moved along with it, and their distances updated, instead of querying and
+    /// colliding against the heightfield cells again. This speeds up the narrow-phase for
+    /// crowds of bodies resting on terrain. The contacts being queried with an additional
+    /// prediction distance equal to this distance, large values generate more contacts.
+    ///
+    /// This value is implicitly scaled by [`IntegrationParameters::length_unit`].
+    pub normalized_heightfield_contact_cache_distance: Real,
     /// The number of solver iterations run by the constraints solver for calculating forces (default: `4`).
+    ///
+    /// Each iteration is a substep of length `dt / num_solver_iterations`. The constant forces
+    /// added to the rigid-bodies, and their step impulses, are integrated at every substep so the
+    /// total impulse they apply during a step doesn’t depend on this number. The impulses applied
+    /// with [`RigidBody::apply_impulse`](crate::dynamics::RigidBody::apply_impulse) change the
+    /// velocities before the step starts instead.
     pub num_solver_iterations: NonZeroUsize,
     /// Number of addition friction resolution iteration run during the last solver sub-step (default: `0`).
     pub num_additional_friction_iterations: usize,
     /// Number of internal Project Gauss Seidel (PGS) iterations run at each solver iteration (default: `1`).
     pub num_internal_pgs_iterations: usize,
+    /// The criterion letting the islands stop their PGS iterations once they converged
+    /// (default: `None`).
+    ///
+    /// If `None`, exactly [`Self::num_internal_pgs_iterations`] PGS iterations are run at each
+    /// substep. See [`VelocityConvergenceCriterion`] for details.
+    pub velocity_convergence: Option<VelocityConvergenceCriterion>,
+    /// Solve the normal constraints of the contact points of each contact manifold by pairs, as a
+    /// coupled 2x2 LCP, instead of one after the other (default: `true` in 2D, `false` in 3D).
+    ///
+    /// This improves the convergence of contact manifolds with several contact points, e.g.,
+    /// for stacks of boxes solved with few iterations. It is disabled by default in 3D because it
+    /// introduces a lot of jitter in scenes like the 3D domino demo.
+    ///
+    /// This is ignored with [`ContactFrictionMode::Coupled`], which always solves the normal and
+    /// friction impulses of each contact point together, one point after the other.
+    pub contact_block_solver: bool,
+    /// How the normal and friction impulses of the contact points are solved relative to each
+    /// other (default: [`ContactFrictionMode::Staggered`]).
+    ///
+    /// [`Self::contact_block_solver`] is ignored with [`ContactFrictionMode::Coupled`].
+    pub contact_friction_mode: ContactFrictionMode,
     /// The number of stabilization iterations run at each solver iterations (default: `2`).
     pub num_internal_stabilization_iterations: usize,
+    /// Number of additional stabilization iterations run during the last solver sub-step
+    /// (default: `0`).
+    ///
+    /// These relaxation iterations solve the constraints without their position-correction bias,
+    /// which removes the residual velocities, e.g. the small bounces of resting stacks, at a
+    /// lower cost than additional substeps.
+    pub num_additional_stabilization_iterations: usize,
     /// Minimum number of dynamic bodies in each active island (default: `128`).
     pub min_island_size: usize,
+    /// Generate and solve the constraints of each island in an order sorted by the handles of
+    /// the rigid-bodies and joints involved (default: `false`).
+    ///
+    /// By default, the bodies and constraints of an island are ordered by the traversal of the
+    /// interaction graph building the island, which depends on the history of the simulation
+    /// (e.g. on which bodies were awake). Enabling this makes the result of a step only depend
+    /// on the state of the simulation and its handles, which is needed for bit-exact replays,
+    /// including when the `parallel` feature is enabled. Sorting has a small cost at each step.
+    pub deterministic_constraint_ordering: bool,
+    /// Reuse the contact constraints of an island from the previous step if none of their
+    /// inputs changed (default: `false`).
+    ///
+    /// This saves the constraints generation for islands whose contacts and bodies are exactly
+    /// the same as during the previous step, e.g., resting bodies kept awake by a kinematic body
+    /// touching them. The inputs and the constraints of every island are then copied at each
+    /// step so they can be compared and restored, which is wasted when the islands keep moving.
+    pub contact_constraints_caching: bool,
     /// Maximum number of substeps performed by the  solver (default: `1`).
     pub max_ccd_substeps: usize,
+    /// Minimum number of active bodies solved by each task when the islands are solved in
+    /// parallel (default: `None`).
+    ///
+    /// Small islands are batched together until they reach this number of bodies, so that each
+    /// task has enough work to amortize its scheduling cost. If `None`, the batch size is picked
+    /// automatically from the number of active bodies and the number of worker threads.
+    ///
+    /// This is only used if the `parallel` feature is enabled.
+    pub parallel_island_batch_size: Option<NonZeroUsize>,
+    /// Minimum number of contact constraints in an island for them to be solved in parallel
+    /// (default: `2048`).
+    ///
+    /// The contact constraints of such islands are colored so that constraints sharing a body
+    /// get different colors. Each color is then solved in parallel, so that a single huge island
+    /// doesn’t serialize the whole solver. Set this to `usize::MAX` to disable it.
+    ///
+    /// This is only used if the `parallel` feature is enabled.
+    pub parallel_island_split_size: usize,
+    /// The maximum number of contact pairs processed by a single task of the parallel
+    /// narrow-phase (default: `None`).
+    ///
+    /// If `None`, the contact pairs are split adaptively between the worker threads, which works
+    /// well when all the pairs have a similar cost. Smaller chunks distribute the work better
+    /// when a few pairs are much more expensive than the others, e.g., in scenes mixing large
+    /// triangle meshes with simple balls, at the price of a larger scheduling overhead. See
+    /// [`CollisionDetectionCounters`](crate::counters::CollisionDetectionCounters) for measuring
+    /// the load imbalance.
+    ///
+    /// This is only used if the `parallel` feature is enabled.
+    pub parallel_narrow_phase_chunk_size: Option<NonZeroUsize>,
+    /// Thresholds beyond which a dynamic rigid-body is considered unstable and quarantined
+    /// (default: `None`).
+    ///
+    /// If `None`, no stability check is performed. See [`QuarantineThresholds`] for details.
+    pub quarantine_thresholds: Option<QuarantineThresholds>,
+}
+
+/// Thresholds used to detect unstable dynamic rigid-bodies.
+///
+/// When set as [`IntegrationParameters::quarantine_thresholds`], the velocity and next position
+/// of every active dynamic rigid-body are checked after the velocity solver and the CCD motion
+/// clamping, before the bodies are moved, and their final position is checked again once they
+/// moved. A body with a NaN or infinite velocity or position, or exceeding one of these
+/// thresholds, is quarantined: it is moved back to its last position that passed these checks
+/// (or to the origin if there is none), its velocity and user forces are reset, it is put to
+/// sleep, and
+/// [`EventHandler::handle_quarantine_event`](crate::pipeline::EventHandler::handle_quarantine_event)
+/// is called. This prevents a single unstable constraint from spreading NaNs through its island.
+///
+/// Rigid-bodies that are part of a multibody are not checked.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct QuarantineThresholds {
+    /// The maximum linear velocity magnitude of a dynamic rigid-body (default: `1.0e4`).
+    ///
+    /// This value is implicitly scaled by [`IntegrationParameters::length_unit`].
+    pub normalized_max_linear_velocity: Real,
+    /// The maximum angular velocity magnitude, in radians per second, of a dynamic rigid-body
+    /// (default: `1.0e4`).
+    pub max_angular_velocity: Real,
+    /// The maximum distance between a dynamic rigid-body and the origin (default: `1.0e7`).
+    ///
+    /// This value is implicitly scaled by [`IntegrationParameters::length_unit`].
+    pub normalized_max_distance_from_origin: Real,
+}
+
+impl Default for QuarantineThresholds {
+    fn default() -> Self {
+        Self {
+            normalized_max_linear_velocity: 1.0e4,
+            max_angular_velocity: 1.0e4,
+            normalized_max_distance_from_origin: 1.0e7,
+        }
+    }
+}
+
+/// The criterion deciding when the PGS iterations of an island converged.
+///
+/// When set as [`IntegrationParameters::velocity_convergence`], each island runs PGS iterations
+/// at each substep until its residuals fall below their thresholds, with at least one iteration
+/// and at most [`Self::max_internal_pgs_iterations`]. Two residuals are measured, each over one
+/// iteration: the largest change of the velocity of the bodies, and the largest change of the
+/// contact impulses computed by the normal and friction constraints. Setting a threshold to
+/// `Real::MAX` disables the corresponding residual, and skips its computation.
+///
+/// Islands at rest exit after their first iteration while the islands that are hard to solve,
+/// e.g. tall stacks, can run more iterations than
+/// [`IntegrationParameters::num_internal_pgs_iterations`]. The number of iterations actually
+/// run is reported by [`SolverCounters`](crate::counters::SolverCounters).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct VelocityConvergenceCriterion {
+    /// The largest change of velocity, during one iteration, of the bodies of a converged island
+    /// (default: `1.0e-4`).
+    ///
+    /// This value is implicitly scaled by [`IntegrationParameters::length_unit`].
+    pub normalized_max_residual: Real,
+    /// The largest change of a contact impulse, during one iteration, of a converged island
+    /// (default: `Real::MAX`, i.e., not checked).
+    ///
+    /// Unlike the velocity residual, this depends on the masses of the bodies in contact.
+    pub max_impulse_delta: Real,
+    /// The maximum number of PGS iterations an island runs at each substep (default: `4`).
+    pub max_internal_pgs_iterations: usize,
+}
+
+impl Default for VelocityConvergenceCriterion {
+    fn default() -> Self {
+        Self {
+            normalized_max_residual: 1.0e-4,
+            max_impulse_delta: Real::MAX,
+            max_internal_pgs_iterations: 4,
+        }
+    }
+}
+
+/// The spring-like parameters regularizing the contact constraints.
+///
+/// The global contact softness is given by [`IntegrationParameters::contact_natural_frequency`]
+/// and [`IntegrationParameters::contact_damping_ratio`]. It can be overridden for specific
+/// colliders with [`Collider::set_contact_softness`](crate::geometry::Collider::set_contact_softness).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ContactSoftness {
+    /// > 0: the natural frequency, in Hertz, of the contact springs.
+    ///
+    /// Smaller values make the contacts softer, allowing more visible penetrations.
+    pub natural_frequency: Real,
+    /// > 0: the damping ratio of the contact springs.
+    ///
+    /// Larger values make the contacts more compliant.
+    pub damping_ratio: Real,
+    /// >= 0: the compliance, in meters per Newton, of the contacts.
+    ///
+    /// If non-zero, this replaces the spring regularization given by [`Self::natural_frequency`]
+    /// and [`Self::damping_ratio`]: a contact resisting a force `f` settles at a penetration
+    /// depth of `compliance * f`, on top of [`IntegrationParameters::allowed_linear_error`],
+    /// regardless of the timestep length. This makes the stiffness of, e.g., tires or foam
+    /// pads predictable.
+    pub compliance: Real,
+}
+
+impl Default for ContactSoftness {
+    fn default() -> Self {
+        Self {
+            natural_frequency: 30.0,
+            damping_ratio: 5.0,
+            compliance: 0.0,
+        }
+    }
+}
+
+impl ContactSoftness {
+    /// A contact softness with the given [`Self::compliance`], in meters per Newton.
+    pub fn with_compliance(compliance: Real) -> Self {
+        Self {
+            compliance,
+            ..Self::default()
+        }
+    }
+
+    /// Combines the contact softness of two colliders in contact.
+    ///
+    /// If both are set, the softest spring, i.e., the one with the smallest natural frequency, is
+    /// selected, and the compliances are added together like two springs in series.
+    pub fn combine(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let mut result = if b.natural_frequency < a.natural_frequency {
+                    b
+                } else {
+                    a
+                };
+                result.compliance = a.compliance + b.compliance;
+                Some(result)
+            }
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+
+    /// The contact’s spring angular frequency for constraints regularization.
+    pub fn angular_frequency(&self) -> Real {
+        self.natural_frequency * Real::two_pi()
+    }
+
+    /// The [`Self::erp`] coefficient, multiplied by the inverse timestep length `1 / dt`.
+    pub fn erp_inv_dt(&self, dt: Real) -> Real {
+        let ang_freq = self.angular_frequency();
+        ang_freq / (dt * ang_freq + 2.0 * self.damping_ratio)
+    }
+
+    /// The effective Error Reduction Parameter applied for calculating regularization forces
+    /// on contacts, for a substep of length `dt`.
+    pub fn erp(&self, dt: Real) -> Real {
+        dt * self.erp_inv_dt(dt)
+    }
+
+    /// The CFM factor to be used in the constraint resolution, for a substep of length `dt`.
+    pub fn cfm_factor(&self, dt: Real) -> Real {
+        // Compute CFM assuming a critically damped spring multiplied by the damping ratio.
+        // The logic is similar to [`IntegrationParameters::joint_cfm_coeff`].
+        let contact_erp = self.erp(dt);
+        if contact_erp == 0.0 {
+            return 0.0;
+        }
+        let inv_erp_minus_one = 1.0 / contact_erp - 1.0;
+
+        // let stiffness = 4.0 * damping_ratio * damping_ratio * projected_mass
+        //     / (dt * dt * inv_erp_minus_one * inv_erp_minus_one);
+        // let damping = 4.0 * damping_ratio * damping_ratio * projected_mass
+        //     / (dt * inv_erp_minus_one);
+        // let cfm = 1.0 / (dt * dt * stiffness + dt * damping);
+        // NOTE: This simplifies to cfm = cfm_coeff / projected_mass:
+        let cfm_coeff = inv_erp_minus_one * inv_erp_minus_one
+            / ((1.0 + inv_erp_minus_one) * 4.0 * self.damping_ratio * self.damping_ratio);
+
+        // Furthermore, we use this coefficient inside of the impulse resolution.
+        // Surprisingly, several simplifications happen there.
+        // Let `m` the projected mass of the constraint.
+        // Let `m’` the projected mass that includes CFM: `m’ = 1 / (1 / m + cfm_coeff / m) = m / (1 + cfm_coeff)`
+        // We have:
+        // new_impulse = old_impulse - m’ (delta_vel - cfm * old_impulse)
+        //             = old_impulse - m / (1 + cfm_coeff) * (delta_vel - cfm_coeff / m * old_impulse)
+        //             = old_impulse * (1 - cfm_coeff / (1 + cfm_coeff)) - m / (1 + cfm_coeff) * delta_vel
+        //             = old_impulse / (1 + cfm_coeff) - m * delta_vel / (1 + cfm_coeff)
+        //             = 1 / (1 + cfm_coeff) * (old_impulse - m * delta_vel)
+        // So, setting cfm_factor = 1 / (1 + cfm_coeff).
+        // We obtain:
+        // new_impulse = cfm_factor * (old_impulse - m * delta_vel)
+        //
+        // The value returned by this function is this cfm_factor that can be used directly
+        // in the constraint solver.
+        1.0 / (1.0 + cfm_coeff)
+    }
+}
+
+/// The spring-like parameters regularizing the constraints of an impulse joint.
+///
+/// The global joint softness is given by [`IntegrationParameters::joint_natural_frequency`]
+/// and [`IntegrationParameters::joint_damping_ratio`]. It can be overridden for specific
+/// joints with [`ImpulseJoint::softness`](crate::dynamics::ImpulseJoint::softness), e.g., to
+/// make a cable much more compliant than a hinge. Together with the substep length, these
+/// determine the ERP and CFM coefficients of the joint, see [`IntegrationParameters::joint_erp`]
+/// and [`IntegrationParameters::joint_cfm_coeff`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct JointSoftness {
+    /// > 0: the natural frequency, in Hertz, of the joint springs.
+    ///
+    /// Smaller values make the joint softer, fixing its drift more slowly.
+    pub natural_frequency: Real,
+    /// The fraction of critical damping of the joint springs.
+    ///
+    /// Larger values make the joint more compliant.
+    pub damping_ratio: Real,
+}
+
+impl Default for JointSoftness {
+    fn default() -> Self {
+        Self {
+            natural_frequency: 1.0e6,
+            damping_ratio: 1.0,
+        }
+    }
 }
 
 impl IntegrationParameters {
@@ -138,15 +525,51 @@ impl IntegrationParameters {
         }
     }
 
+    /// Replaces these parameters by `new_params`, adapting the state retained by the simulation
+    /// from the previous steps so that changing the parameters at runtime doesn’t cause pops.
+    ///
+    /// The contact warmstart impulses are applied during a single solver substep of length
+    /// `dt / num_solver_iterations`. If that length changes, they are rescaled so that they
+    /// apply the same forces at the next step. Otherwise, e.g., halving `dt` would make resting
+    /// stacks jump since the warmstart would push twice too hard. The additional solver
+    /// iterations of the rigid-bodies and islands aren’t accounted for.
+    ///
+    /// The other parameters are read at each step and take effect without any adjustment.
+    pub fn transition_to(
+        &mut self,
+        new_params: &IntegrationParameters,
+        narrow_phase: &mut NarrowPhase,
+    ) {
+        let substep_dt =
+            |params: &IntegrationParameters| params.dt / params.num_solver_iterations.get() as Real;
+        let old_substep_dt = substep_dt(self);
+        let new_substep_dt = substep_dt(new_params);
+
+        if old_substep_dt > 0.0 && new_substep_dt != old_substep_dt {
+            narrow_phase.scale_warmstart_impulses(new_substep_dt / old_substep_dt);
+        }
+
+        *self = *new_params;
+    }
+
+    /// The global contact softness, built from [`Self::contact_natural_frequency`] and
+    /// [`Self::contact_damping_ratio`].
+    pub fn contact_softness(&self) -> ContactSoftness {
+        ContactSoftness {
+            natural_frequency: self.contact_natural_frequency,
+            damping_ratio: self.contact_damping_ratio,
+            compliance: 0.0,
+        }
+    }
+
     /// The contact’s spring angular frequency for constraints regularization.
     pub fn contact_angular_frequency(&self) -> Real {
-        self.contact_natural_frequency * Real::two_pi()
+        self.contact_softness().angular_frequency()
     }
 
     /// The [`Self::contact_erp`] coefficient, multiplied by the inverse timestep length.
     pub fn contact_erp_inv_dt(&self) -> Real {
-        let ang_freq = self.contact_angular_frequency();
-        ang_freq / (self.dt * ang_freq + 2.0 * self.contact_damping_ratio)
+        self.contact_softness().erp_inv_dt(self.dt)
     }
 
     /// The effective Error Reduction Parameter applied for calculating regularization forces
@@ -158,6 +581,27 @@ impl IntegrationParameters {
         self.dt * self.contact_erp_inv_dt()
     }
 
+    /// The global joint softness, built from [`Self::joint_natural_frequency`] and
+    /// [`Self::joint_damping_ratio`].
+    pub fn joint_softness(&self) -> JointSoftness {
+        JointSoftness {
+            natural_frequency: self.joint_natural_frequency,
+            damping_ratio: self.joint_damping_ratio,
+        }
+    }
+
+    /// These parameters, with the global joint softness replaced by `softness` if it is set.
+    pub(crate) fn with_joint_softness(&self, softness: Option<&JointSoftness>) -> Cow<'_, Self> {
+        match softness {
+            Some(softness) => Cow::Owned(Self {
+                joint_natural_frequency: softness.natural_frequency,
+                joint_damping_ratio: softness.damping_ratio,
+                ..*self
+            }),
+            None => Cow::Borrowed(self),
+        }
+    }
+
     /// The joint’s spring angular frequency for constraint regularization.
     pub fn joint_angular_frequency(&self) -> Real {
         self.joint_natural_frequency * Real::two_pi()
@@ -183,43 +627,7 @@ impl IntegrationParameters {
     /// This parameter is computed automatically from [`Self::contact_natural_frequency`],
     /// [`Self::contact_damping_ratio`] and the substep length.
     pub fn contact_cfm_factor(&self) -> Real {
-        // Compute CFM assuming a critically damped spring multiplied by the damping ratio.
-        // The logic is similar to [`Self::joint_cfm_coeff`].
-        let contact_erp = self.contact_erp();
-        if contact_erp == 0.0 {
-            return 0.0;
-        }
-        let inv_erp_minus_one = 1.0 / contact_erp - 1.0;
-
-        // let stiffness = 4.0 * damping_ratio * damping_ratio * projected_mass
-        //     / (dt * dt * inv_erp_minus_one * inv_erp_minus_one);
-        // let damping = 4.0 * damping_ratio * damping_ratio * projected_mass
-        //     / (dt * inv_erp_minus_one);
-        // let cfm = 1.0 / (dt * dt * stiffness + dt * damping);
-        // NOTE: This simplifies to cfm = cfm_coeff / projected_mass:
-        let cfm_coeff = inv_erp_minus_one * inv_erp_minus_one
-            / ((1.0 + inv_erp_minus_one)
-                * 4.0
-                * self.contact_damping_ratio
-                * self.contact_damping_ratio);
-
-        // Furthermore, we use this coefficient inside of the impulse resolution.
-        // Surprisingly, several simplifications happen there.
-        // Let `m` the projected mass of the constraint.
-        // Let `m’` the projected mass that includes CFM: `m’ = 1 / (1 / m + cfm_coeff / m) = m / (1 + cfm_coeff)`
-        // We have:
-        // new_impulse = old_impulse - m’ (delta_vel - cfm * old_impulse)
-        //             = old_impulse - m / (1 + cfm_coeff) * (delta_vel - cfm_coeff / m * old_impulse)
-        //             = old_impulse * (1 - cfm_coeff / (1 + cfm_coeff)) - m / (1 + cfm_coeff) * delta_vel
-        //             = old_impulse / (1 + cfm_coeff) - m * delta_vel / (1 + cfm_coeff)
-        //             = 1 / (1 + cfm_coeff) * (old_impulse - m * delta_vel)
-        // So, setting cfm_factor = 1 / (1 + cfm_coeff).
-        // We obtain:
-        // new_impulse = cfm_factor * (old_impulse - m * delta_vel)
-        //
-        // The value returned by this function is this cfm_factor that can be used directly
-        // in the constraint solver.
-        1.0 / (1.0 + cfm_coeff)
+        self.contact_softness().cfm_factor(self.dt)
     }
 
     /// The CFM (constraints force mixing) coefficient applied to all joints for constraints regularization.
@@ -241,6 +649,29 @@ impl IntegrationParameters {
                 * self.joint_damping_ratio)
     }
 
+    /// The CFM factor and ERP (multiplied by the inverse substep length) applied to contacts
+    /// with the given softness.
+    ///
+    /// The spring of the softness is ignored by compliant contacts, for which the penetration is
+    /// controlled by [`Self::contact_compliance_gain`] instead.
+    pub(crate) fn contact_cfm_factor_and_erp_inv_dt(
+        &self,
+        softness: &ContactSoftness,
+    ) -> (Real, Real) {
+        if softness.compliance > 0.0 {
+            return (1.0, crate::utils::inv(self.dt));
+        }
+
+        (softness.cfm_factor(self.dt), softness.erp_inv_dt(self.dt))
+    }
+
+    /// The velocity error, per unit of impulse, added to contacts with the given softness so
+    /// they yield according to their [`ContactSoftness::compliance`] for a substep of length
+    /// [`Self::dt`].
+    pub(crate) fn contact_compliance_gain(&self, softness: &ContactSoftness) -> Real {
+        softness.compliance * crate::utils::inv(self.dt * self.dt)
+    }
+
     /// Amount of penetration the engine won’t attempt to correct (default: `0.001` multiplied by
     /// [`Self::length_unit`]).
     pub fn allowed_linear_error(&self) -> Real {
@@ -265,6 +696,14 @@ impl IntegrationParameters {
         self.normalized_prediction_distance * self.length_unit
     }
 
+    /// The distance a collider can move relative to a fixed heightfield before its contacts with
+    /// the heightfield are queried again (default: `0.0`, disabling the cache).
+    ///
+    /// See [`Self::normalized_heightfield_contact_cache_distance`] for details.
+    pub fn heightfield_contact_cache_distance(&self) -> Real {
+        self.normalized_heightfield_contact_cache_distance * self.length_unit
+    }
+
     /// Initialize the simulation parameters with settings matching the TGS-soft solver
     /// with warmstarting.
     ///
@@ -277,9 +716,15 @@ impl IntegrationParameters {
             contact_damping_ratio: 5.0,
             joint_natural_frequency: 1.0e6,
             joint_damping_ratio: 1.0,
+            position_correction_mode: PositionCorrectionMode::Baumgarte,
+            num_position_iterations: 4,
             warmstart_coefficient: 1.0,
             num_internal_pgs_iterations: 1,
+            velocity_convergence: None,
+            contact_block_solver: cfg!(feature = "dim2"),
+            contact_friction_mode: ContactFrictionMode::Staggered,
             num_internal_stabilization_iterations: 2,
+            num_additional_stabilization_iterations: 0,
             num_additional_friction_iterations: 0,
             num_solver_iterations: NonZeroUsize::new(4).unwrap(),
             // TODO: what is the optimal value for min_island_size?
@@ -288,11 +733,18 @@ impl IntegrationParameters {
             // However we don't want it to be too small and end up with
             // tons of islands, reducing SIMD parallelism opportunities.
             min_island_size: 128,
+            deterministic_constraint_ordering: false,
+            contact_constraints_caching: false,
             normalized_allowed_linear_error: 0.001,
             normalized_max_corrective_velocity: 10.0,
             normalized_prediction_distance: 0.002,
+            normalized_heightfield_contact_cache_distance: 0.0,
             max_ccd_substeps: 1,
             length_unit: 1.0,
+            parallel_island_batch_size: None,
+            parallel_island_split_size: 2048,
+            parallel_narrow_phase_chunk_size: None,
+            quarantine_thresholds: None,
         }
     }
 
@@ -327,3 +779,95 @@ impl Default for IntegrationParameters {
         Self::tgs_soft()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use super::IntegrationParameters;
+    use crate::dynamics::{ContactSoftness, RigidBodyBuilder};
+    use crate::geometry::{ColliderBuilder, NarrowPhase};
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn compliant_contacts_penetrate_proportionally_to_the_load() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let compliance = 1.0e-3;
+        let _ = world.colliders.insert(
+            ColliderBuilder::halfspace(Vector::y_axis())
+                .contact_softness(ContactSoftness::with_compliance(compliance)),
+        );
+        // Two balls resting on the ground, the second one being twice as heavy.
+        let balls = [1.0, 2.0].map(|mass| {
+            let ball = world.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(Vector::x() * mass * 2.0 + Vector::y() * 0.5),
+            );
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5).mass(mass),
+                ball,
+                &mut world.bodies,
+            );
+            (ball, mass)
+        });
+        for _ in 0..240 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        for (ball, mass) in balls {
+            let penetration = 0.5 - world.bodies[ball].translation().y;
+            let expected =
+                world.integration_parameters.allowed_linear_error() + compliance * mass * 9.81;
+            assert!((penetration - expected).abs() < 2.0e-3);
+        }
+    }
+
+    #[test]
+    fn transitioning_parameters_rescales_warmstart_impulses() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let total_warmstart =
+            |nf: &NarrowPhase| -> Real { nf.warmstart_impulses().map(|i| i.impulse).sum() };
+        let before = total_warmstart(&world.narrow_phase);
+        assert!(before > 0.0);
+
+        // Twice as many substeps, each half as long.
+        let params = &mut world.integration_parameters;
+        let new_params = IntegrationParameters {
+            num_solver_iterations: params
+                .num_solver_iterations
+                .saturating_mul(NonZeroUsize::new(2).unwrap()),
+            ..*params
+        };
+        params.transition_to(&new_params, &mut world.narrow_phase);
+        assert_eq!(
+            params.num_solver_iterations,
+            new_params.num_solver_iterations
+        );
+        assert!((total_warmstart(&world.narrow_phase) - before * 0.5).abs() <= before * 1.0e-5);
+    }
+}
@@ -1,10 +1,56 @@
+use crate::dynamics::solver::UserConstraintSet;
 use crate::dynamics::{
-    ImpulseJointSet, MultibodyJointSet, RigidBodyActivation, RigidBodyChanges, RigidBodyColliders,
-    RigidBodyHandle, RigidBodyIds, RigidBodySet, RigidBodyType, RigidBodyVelocity,
+    ImpulseJointSet, MultibodyJointSet, RigidBody, RigidBodyActivation, RigidBodyChanges,
+    RigidBodyColliders, RigidBodyHandle, RigidBodyIds, RigidBodySet, RigidBodyType,
+    RigidBodyVelocity,
 };
 use crate::geometry::{ColliderSet, NarrowPhase};
 use crate::math::Real;
 use crate::utils::SimdDot;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// The maximum time a debris waits with a low velocity before falling asleep.
+const DEBRIS_MAX_TIME_UNTIL_SLEEP: Real = 0.5;
+
+/// The numbers of iterations the constraints solver runs for a single island.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IslandSolverIterations {
+    /// The number of substeps, replacing [`IntegrationParameters::num_solver_iterations`](crate::dynamics::IntegrationParameters::num_solver_iterations)
+    /// and the additional iterations of the island’s bodies.
+    pub num_solver_iterations: NonZeroUsize,
+    /// The number of velocity iterations per substep, replacing
+    /// [`IntegrationParameters::num_internal_pgs_iterations`](crate::dynamics::IntegrationParameters::num_internal_pgs_iterations).
+    pub num_internal_pgs_iterations: usize,
+    /// The number of stabilization iterations per substep, replacing
+    /// [`IntegrationParameters::num_internal_stabilization_iterations`](crate::dynamics::IntegrationParameters::num_internal_stabilization_iterations).
+    pub num_internal_stabilization_iterations: usize,
+}
+
+/// The description of an island given to the heuristic set with
+/// [`IslandManager::set_solver_iterations_heuristic`].
+pub struct IslandSolverContext<'a> {
+    /// The set of all the rigid-bodies.
+    pub bodies: &'a RigidBodySet,
+    /// The dynamic rigid-bodies of the island.
+    pub island: &'a [RigidBodyHandle],
+    /// The number of contact manifolds solved in the island.
+    pub num_contact_manifolds: usize,
+    /// The number of impulse joints in the island.
+    pub num_joints: usize,
+    /// The largest penetration depth among the contacts of the island, or zero if none of
+    /// them penetrate.
+    pub max_penetration: Real,
+    /// Does this island only contain debris?
+    pub is_debris: bool,
+    /// The iterations the solver would run for this island without any heuristic.
+    pub default_iterations: IslandSolverIterations,
+}
+
+/// A heuristic choosing the solver iterations of an island (see
+/// [`IslandManager::set_solver_iterations_heuristic`]).
+pub type SolverIterationsHeuristic =
+    dyn Fn(&IslandSolverContext) -> IslandSolverIterations + Send + Sync;
 
 /// Structure responsible for maintaining the set of active rigid-bodies, and
 /// putting non-moving rigid-bodies to sleep to save computation times.
@@ -15,11 +61,19 @@ pub struct IslandManager {
     pub(crate) active_kinematic_set: Vec<RigidBodyHandle>,
     pub(crate) active_islands: Vec<usize>,
     pub(crate) active_islands_additional_solver_iterations: Vec<usize>,
+    pub(crate) active_islands_debris: Vec<bool>,
     active_set_timestamp: u32,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     can_sleep: Vec<RigidBodyHandle>, // Workspace.
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     stack: Vec<RigidBodyHandle>, // Workspace.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    assembly: Vec<RigidBodyHandle>, // Workspace.
+    assembly_sleeping: bool,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) debris_fallen_asleep: Vec<RigidBodyHandle>,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    solver_iterations_heuristic: Option<Arc<SolverIterationsHeuristic>>,
 }
 
 impl IslandManager {
@@ -30,12 +84,58 @@ impl IslandManager {
             active_kinematic_set: vec![],
             active_islands: vec![],
             active_islands_additional_solver_iterations: vec![],
+            active_islands_debris: vec![],
             active_set_timestamp: 0,
             can_sleep: vec![],
             stack: vec![],
+            assembly: vec![],
+            assembly_sleeping: false,
+            debris_fallen_asleep: vec![],
+            solver_iterations_heuristic: None,
         }
     }
 
+    /// Sets the heuristic choosing the solver iterations of each active island.
+    ///
+    /// It is called once per island and per step, before its constraints are solved, and can
+    /// e.g. give more iterations to tall stacks and fewer to isolated bodies. The iterations
+    /// returned by the heuristic replace the ones from the
+    /// [`IntegrationParameters`](crate::dynamics::IntegrationParameters), the additional
+    /// solver iterations of the rigid-bodies, and the single iteration of debris islands.
+    ///
+    /// The heuristic isn’t serialized, and it is shared by the clones of this island manager.
+    pub fn set_solver_iterations_heuristic(
+        &mut self,
+        heuristic: impl Fn(&IslandSolverContext) -> IslandSolverIterations + Send + Sync + 'static,
+    ) {
+        self.solver_iterations_heuristic = Some(Arc::new(heuristic));
+    }
+
+    /// Removes the heuristic set with [`IslandManager::set_solver_iterations_heuristic`].
+    pub fn clear_solver_iterations_heuristic(&mut self) {
+        self.solver_iterations_heuristic = None;
+    }
+
+    /// Enables or disables the sleeping of jointed assemblies as a whole.
+    ///
+    /// When enabled, the dynamic rigid-bodies connected by enabled impulse or multibody joints
+    /// are put to sleep based on the mass-weighted average of their kinetic energies (relative
+    /// to their own sleep thresholds) instead of their individual velocities. This lets chains
+    /// and ragdolls fall asleep even if one of their links keeps jittering slightly above its
+    /// threshold. All the bodies of an assembly also fall asleep together. Disabled by default.
+    pub fn set_assembly_sleeping(&mut self, enabled: bool) {
+        self.assembly_sleeping = enabled;
+    }
+
+    /// Are jointed assemblies put to sleep as a whole (see [`IslandManager::set_assembly_sleeping`])?
+    pub fn assembly_sleeping(&self) -> bool {
+        self.assembly_sleeping
+    }
+
+    pub(crate) fn solver_iterations_heuristic(&self) -> Option<&SolverIterationsHeuristic> {
+        self.solver_iterations_heuristic.as_deref()
+    }
+
     pub(crate) fn num_islands(&self) -> usize {
         self.active_islands.len() - 1
     }
@@ -133,6 +233,11 @@ impl IslandManager {
         self.active_islands_additional_solver_iterations[island_id]
     }
 
+    /// Does the given active island only contain debris?
+    pub(crate) fn active_island_is_debris(&self, island_id: usize) -> bool {
+        self.active_islands_debris[island_id]
+    }
+
     #[inline(always)]
     pub(crate) fn iter_active_bodies(&self) -> impl Iterator<Item = RigidBodyHandle> + '_ {
         self.active_dynamic_set
@@ -156,6 +261,7 @@ impl IslandManager {
         narrow_phase: &NarrowPhase,
         impulse_joints: &ImpulseJointSet,
         multibody_joints: &MultibodyJointSet,
+        user_constraints: &UserConstraintSet,
         min_island_size: usize,
     ) {
         assert!(
@@ -166,10 +272,27 @@ impl IslandManager {
         // Update the energy of every rigid body and
         // keep only those that may not sleep.
         //        let t = instant::now();
-        self.active_set_timestamp += 1;
         self.stack.clear();
         self.can_sleep.clear();
 
+        // The bodies belonging to an assembly are marked with this timestamp, and have
+        // their sleep timers already updated.
+        let assembly_timestamp = if self.assembly_sleeping {
+            self.active_set_timestamp += 1;
+            self.update_assemblies_energy(
+                length_unit,
+                bodies,
+                impulse_joints,
+                multibody_joints,
+                dt,
+            );
+            Some(self.active_set_timestamp)
+        } else {
+            None
+        };
+
+        self.active_set_timestamp += 1;
+
         // NOTE: the `.rev()` is here so that two successive timesteps preserve
         // the order of the bodies in the `active_dynamic_set` vec. This reversal
         // does not seem to affect performances nor stability. However it makes
@@ -179,12 +302,21 @@ impl IslandManager {
             let stack = &mut self.stack;
 
             let rb = bodies.index_mut_internal(h);
-            let sq_linvel = rb.vels.linvel.norm_squared();
-            let sq_angvel = rb.vels.angvel.gdot(rb.vels.angvel);
+            let (mut sq_linvel, mut sq_angvel) = sq_velocities(rb);
+            let mut time_until_sleep = rb.activation.time_until_sleep;
+
+            if rb.is_debris() {
+                // Debris can sleep at twice the velocity thresholds, and sooner.
+                sq_linvel *= 0.25;
+                sq_angvel *= 0.25;
+                time_until_sleep = time_until_sleep.min(DEBRIS_MAX_TIME_UNTIL_SLEEP);
+            }
 
-            update_energy(length_unit, &mut rb.activation, sq_linvel, sq_angvel, dt);
+            if Some(rb.ids.active_set_timestamp) != assembly_timestamp {
+                update_energy(length_unit, &mut rb.activation, sq_linvel, sq_angvel, dt);
+            }
 
-            if rb.activation.time_since_can_sleep >= rb.activation.time_until_sleep {
+            if rb.activation.time_since_can_sleep >= time_until_sleep {
                 // Mark them as sleeping for now. This will
                 // be set to false during the graph traversal
                 // if it should not be put to sleep.
@@ -241,6 +373,7 @@ impl IslandManager {
         // Propagation of awake state and awake island computation through the
         // traversal of the interaction graph.
         self.active_islands_additional_solver_iterations.clear();
+        self.active_islands_debris.clear();
         self.active_islands.clear();
         self.active_islands.push(0);
 
@@ -254,6 +387,7 @@ impl IslandManager {
         //       iterations on top of the stack (and other bodies on the back). Not sure it’s
         //       worth it though.
         let mut additional_solver_iterations = 0;
+        let mut island_is_debris = true;
 
         while let Some(handle) = self.stack.pop() {
             let rb = bodies.index_mut_internal(handle);
@@ -265,15 +399,22 @@ impl IslandManager {
             }
 
             if self.stack.len() < island_marker {
+                let island_len =
+                    self.active_dynamic_set.len() - *self.active_islands.last().unwrap();
+
+                // NOTE: debris and non-debris islands are kept separate so that the debris
+                //       islands can be solved with a lower quality.
                 if additional_solver_iterations != rb.additional_solver_iterations
-                    || self.active_dynamic_set.len() - *self.active_islands.last().unwrap()
-                        >= min_island_size
+                    || (island_len > 0 && island_is_debris != rb.is_debris())
+                    || island_len >= min_island_size
                 {
                     // We are starting a new island.
                     self.active_islands_additional_solver_iterations
                         .push(additional_solver_iterations);
+                    self.active_islands_debris.push(island_is_debris);
                     self.active_islands.push(self.active_dynamic_set.len());
                     additional_solver_iterations = 0;
+                    island_is_debris = true;
                 }
 
                 island_marker = self.stack.len();
@@ -281,6 +422,7 @@ impl IslandManager {
 
             additional_solver_iterations =
                 additional_solver_iterations.max(rb.additional_solver_iterations);
+            island_is_debris = island_is_debris && rb.is_debris();
 
             // Transmit the active state to all the rigid-bodies with colliders
             // in contact or joined with this collider.
@@ -295,6 +437,12 @@ impl IslandManager {
                 self.stack.push(other);
             }
 
+            for other in user_constraints.attached_bodies(handle) {
+                if bodies.contains(other) {
+                    self.stack.push(other);
+                }
+            }
+
             rb.activation.wake_up(false);
             rb.ids.active_island_id = self.active_islands.len() - 1;
             rb.ids.active_set_id = self.active_dynamic_set.len();
@@ -307,6 +455,7 @@ impl IslandManager {
 
         self.active_islands_additional_solver_iterations
             .push(additional_solver_iterations);
+        self.active_islands_debris.push(island_is_debris);
         self.active_islands.push(self.active_dynamic_set.len());
         //        println!(
         //            "Extraction: {}, num islands: {}",
@@ -320,11 +469,140 @@ impl IslandManager {
             if rb.activation.sleeping {
                 rb.vels = RigidBodyVelocity::zero();
                 rb.activation.sleep();
+
+                if rb.is_debris() {
+                    self.debris_fallen_asleep.push(*handle);
+                }
+            }
+        }
+    }
+
+    /// Sorts the bodies of each active island by handle, so that their solver ids don’t depend
+    /// on the order in which the islands were traversed.
+    pub(crate) fn sort_active_islands(&mut self, bodies: &mut RigidBodySet) {
+        for island_id in 0..self.num_islands() {
+            let range = self.active_islands[island_id]..self.active_islands[island_id + 1];
+            let island = &mut self.active_dynamic_set[range.clone()];
+            island.sort_unstable_by_key(|handle| handle.0);
+
+            for (offset, handle) in island.iter().enumerate() {
+                let rb = bodies.index_mut_internal(*handle);
+                rb.ids.active_set_id = range.start + offset;
+                rb.ids.active_set_offset = offset;
+            }
+        }
+    }
+
+    /// Updates the sleep timers of the bodies of every assembly of active dynamic bodies
+    /// connected by joints, and marks them with the current active set timestamp.
+    ///
+    /// The timers of all the bodies of an assembly are set to the same value, based on the
+    /// mass-weighted average of their squared velocities relative to their squared thresholds.
+    fn update_assemblies_energy(
+        &mut self,
+        length_unit: Real,
+        bodies: &mut RigidBodySet,
+        impulse_joints: &ImpulseJointSet,
+        multibody_joints: &MultibodyJointSet,
+        dt: Real,
+    ) {
+        for i in 0..self.active_dynamic_set.len() {
+            let root = self.active_dynamic_set[i];
+            if bodies[root].ids.active_set_timestamp == self.active_set_timestamp {
+                continue;
+            }
+
+            self.assembly.clear();
+            self.stack.clear();
+            self.stack.push(root);
+
+            while let Some(handle) = self.stack.pop() {
+                let Some(rb) = bodies.get_mut_internal(handle) else {
+                    continue;
+                };
+
+                if rb.ids.active_set_timestamp == self.active_set_timestamp
+                    || !rb.is_dynamic()
+                    || !rb.is_enabled()
+                    || rb.activation.sleeping
+                {
+                    continue;
+                }
+
+                rb.ids.active_set_timestamp = self.active_set_timestamp;
+                self.assembly.push(handle);
+
+                for inter in impulse_joints.attached_enabled_joints(handle) {
+                    let other = crate::utils::select_other((inter.0, inter.1), handle);
+                    self.stack.push(other);
+                }
+
+                for other in multibody_joints.bodies_attached_with_enabled_joint(handle) {
+                    self.stack.push(other);
+                }
+            }
+
+            if self.assembly.len() == 1 {
+                // Not an assembly, this body will be handled individually.
+                bodies.index_mut_internal(root).ids.active_set_timestamp = 0;
+                continue;
+            }
+
+            let mut total_weight = 0.0;
+            let mut linear_energy = 0.0;
+            let mut angular_energy = 0.0;
+            let mut can_sleep = true;
+            let mut time_since_can_sleep = Real::MAX;
+
+            for handle in &self.assembly {
+                let rb = &bodies[*handle];
+                let (mut sq_linvel, mut sq_angvel) = sq_velocities(rb);
+                if rb.is_debris() {
+                    sq_linvel *= 0.25;
+                    sq_angvel *= 0.25;
+                }
+
+                let linear_threshold = rb.activation.normalized_linear_threshold * length_unit;
+                let angular_threshold = rb.activation.angular_threshold;
+                if linear_threshold <= 0.0 || angular_threshold <= 0.0 {
+                    // This body is not allowed to sleep, so neither is its assembly.
+                    can_sleep = false;
+                    break;
+                }
+
+                let weight = if rb.mass() > 0.0 { rb.mass() } else { 1.0 };
+                total_weight += weight;
+                linear_energy += weight * sq_linvel / (linear_threshold * linear_threshold);
+                angular_energy += weight * sq_angvel / (angular_threshold * angular_threshold);
+                time_since_can_sleep = time_since_can_sleep.min(rb.activation.time_since_can_sleep);
+            }
+
+            let time_since_can_sleep =
+                if can_sleep && linear_energy < total_weight && angular_energy < total_weight {
+                    time_since_can_sleep + dt
+                } else {
+                    0.0
+                };
+
+            for handle in &self.assembly {
+                bodies
+                    .index_mut_internal(*handle)
+                    .activation
+                    .time_since_can_sleep = time_since_can_sleep;
             }
         }
+
+        self.stack.clear();
     }
 }
 
+fn sq_velocities(rb: &RigidBody) -> (Real, Real) {
+    (
+        rb.vels.linvel.norm_squared(),
+        rb.vels.angvel.gdot(rb.vels.angvel),
+    )
+}
+
 fn update_energy(
     length_unit: Real,
     activation: &mut RigidBodyActivation,
@@ -341,3 +619,153 @@ fn update_energy(
         activation.time_since_can_sleep = 0.0;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::IslandManager;
+    use crate::dynamics::solver::UserConstraintSet;
+    use crate::dynamics::{
+        FixedJointBuilder, ImpulseJointSet, IslandSolverIterations, MultibodyJointSet,
+        RigidBodyBuilder, RigidBodyQuality, RigidBodySet,
+    };
+    use crate::geometry::{ColliderBuilder, ColliderSet, NarrowPhase};
+    use crate::math::Vector;
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn debris_fall_asleep_sooner() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Two balls resting on the ground, far from each other.
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let debris = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(Vector::y() * 0.5 + Vector::x() * -5.0)
+                .quality(RigidBodyQuality::Debris),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            debris,
+            &mut world.bodies,
+        );
+        let full = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5 + Vector::x() * 5.0));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), full, &mut world.bodies);
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        assert!(world.bodies[debris].is_sleeping());
+        assert!(!world.bodies[full].is_sleeping());
+    }
+
+    #[test]
+    fn solver_iterations_heuristic_is_applied_per_island() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        world.integration_parameters.min_island_size = 1;
+
+        // Two balls resting on the ground, far enough from each other to be in separate islands.
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let unsolved = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(Vector::y() * 0.5 - Vector::x() * 5.0)
+                .user_data(1),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            unsolved,
+            &mut world.bodies,
+        );
+        let solved = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5 + Vector::x() * 5.0));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            solved,
+            &mut world.bodies,
+        );
+
+        // Don’t run any velocity iteration for the island of the first ball.
+        world.islands.set_solver_iterations_heuristic(|ctx| {
+            if ctx.island.iter().any(|h| ctx.bodies[*h].user_data == 1) {
+                IslandSolverIterations {
+                    num_internal_pgs_iterations: 0,
+                    num_internal_stabilization_iterations: 0,
+                    ..ctx.default_iterations
+                }
+            } else {
+                ctx.default_iterations
+            }
+        });
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        assert!(world.bodies[unsolved].translation().y < 0.0);
+        assert!((world.bodies[solved].translation().y - 0.5).abs() < 0.05);
+    }
+
+    fn jittering_chain_sleeps(assembly_sleeping: bool) -> bool {
+        let mut bodies = RigidBodySet::new();
+        let colliders = ColliderSet::new();
+        let narrow_phase = NarrowPhase::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let multibody_joints = MultibodyJointSet::new();
+        let mut islands = IslandManager::new();
+        islands.set_assembly_sleeping(assembly_sleeping);
+
+        let links: Vec<_> = (0..3)
+            .map(|_| bodies.insert(RigidBodyBuilder::dynamic()))
+            .collect();
+        for pair in links.windows(2) {
+            impulse_joints.insert(pair[0], pair[1], FixedJointBuilder::new(), true);
+        }
+        for link in &links {
+            islands.wake_up(&mut bodies, *link, true);
+        }
+
+        // The middle link jitters slightly above its linear sleep threshold.
+        let dt = 1.0 / 60.0;
+        for i in 0..300 {
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            if !bodies[links[1]].is_sleeping() {
+                bodies[links[1]].set_linvel(Vector::x() * (0.5 * sign), false);
+            }
+            islands.update_active_set_with_contacts(
+                dt,
+                1.0,
+                &mut bodies,
+                &colliders,
+                &narrow_phase,
+                &impulse_joints,
+                &multibody_joints,
+                &UserConstraintSet::new(),
+                1,
+            );
+        }
+
+        links.iter().all(|link| bodies[*link].is_sleeping())
+    }
+
+    #[test]
+    fn jittering_assemblies_sleep_as_a_whole() {
+        assert!(!jittering_chain_sleeps(false));
+        assert!(jittering_chain_sleeps(true));
+    }
+}
@@ -59,6 +59,18 @@ impl FixedJoint {
         self
     }
 
+    /// Sets the joint’s frames from a frame expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_frame(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_frame: &Isometry<Real>,
+    ) -> &mut Self {
+        self.data.set_world_frame(body_pos1, body_pos2, world_frame);
+        self
+    }
+
     /// The joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(&self) -> Point<Real> {
@@ -82,6 +94,19 @@ impl FixedJoint {
         self.data.set_local_anchor2(anchor2);
         self
     }
+
+    /// Sets the joint’s anchors from an anchor expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_anchor(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.data
+            .set_world_anchor(body_pos1, body_pos2, world_anchor);
+        self
+    }
 }
 
 impl From<FixedJoint> for GenericJoint {
@@ -108,6 +133,13 @@ impl FixedJointBuilder {
         self
     }
 
+    /// An arbitrary user-defined 128-bit integer associated to the joints built by this builder.
+    #[must_use]
+    pub fn user_data(mut self, data: u128) -> Self {
+        self.0.data.user_data = data;
+        self
+    }
+
     /// Sets the joint’s frame, expressed in the first rigid-body’s local-space.
     #[must_use]
     pub fn local_frame1(mut self, local_frame: Isometry<Real>) -> Self {
@@ -389,6 +389,56 @@ impl GenericJoint {
         self
     }
 
+    /// Sets the joint’s frames from a frame expressed in world-space.
+    ///
+    /// Both local frames are computed from the given current positions of the attached
+    /// rigid-bodies, so that they coincide with `world_frame`. The current relative position of
+    /// the rigid-bodies thus becomes the joint’s rest configuration, e.g., the zero angle of a
+    /// revolute joint.
+    pub fn set_world_frame(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_frame: &Isometry<Real>,
+    ) -> &mut Self {
+        self.local_frame1 = body_pos1.inv_mul(world_frame);
+        self.local_frame2 = body_pos2.inv_mul(world_frame);
+        self
+    }
+
+    /// Sets the joint’s anchors from an anchor expressed in world-space.
+    ///
+    /// Both local anchors are computed from the given current positions of the attached
+    /// rigid-bodies, so that they coincide with `world_anchor`.
+    pub fn set_world_anchor(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.set_local_anchor1(body_pos1.inverse_transform_point(&world_anchor));
+        self.set_local_anchor2(body_pos2.inverse_transform_point(&world_anchor));
+        self
+    }
+
+    /// Sets the joint’s principal axes from an axis expressed in world-space.
+    ///
+    /// Both local axes are computed from the given current positions of the attached
+    /// rigid-bodies, so that they coincide with `world_axis`. The rest of the local frames
+    /// is chosen so that the current relative orientation of the rigid-bodies becomes the
+    /// joint’s rest orientation.
+    pub fn set_world_axis(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_axis: UnitVector<Real>,
+    ) -> &mut Self {
+        let world_rot = Self::complete_ang_frame(world_axis);
+        self.local_frame1.rotation = body_pos1.rotation.inverse() * world_rot;
+        self.local_frame2.rotation = body_pos2.rotation.inverse() * world_rot;
+        self
+    }
+
     /// Are contacts between the attached rigid-bodies enabled?
     pub fn contacts_enabled(&self) -> bool {
         self.contacts_enabled
@@ -1,5 +1,7 @@
-use crate::dynamics::{GenericJoint, ImpulseJointHandle, RigidBodyHandle};
-use crate::math::{Real, SpacialVector};
+use crate::dynamics::{
+    GenericJoint, ImpulseJointHandle, JointAxesMask, JointSoftness, RigidBodyHandle, RigidBodySet,
+};
+use crate::math::{AngVector, Isometry, Point, Real, SpacialVector, UnitVector, Vector, DIM};
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq)]
@@ -13,9 +15,238 @@ pub struct ImpulseJoint {
     /// The joint’s description.
     pub data: GenericJoint,
 
+    /// The softness of this joint’s constraints, overriding the global one given by the
+    /// [`IntegrationParameters`](crate::dynamics::IntegrationParameters) if set.
+    pub softness: Option<JointSoftness>,
+
     /// The impulses applied by this joint.
+    ///
+    /// These are the impulses applied along the locked axes during the last substep of the
+    /// last simulation step. See [`ImpulseJoint::reaction`] for the corresponding forces.
     pub impulses: SpacialVector<Real>,
 
+    /// The length of the substep during which the `impulses` were applied.
+    pub(crate) impulses_dt: Real,
+
     // A joint needs to know its handle to simplify its removal.
     pub(crate) handle: ImpulseJointHandle,
 }
+
+/// The force and torque applied by a joint to the first rigid-body it is attached to.
+///
+/// The second rigid-body is subject to the opposite force and torque.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct JointReaction {
+    /// The force applied at the joint’s anchor on the first rigid-body.
+    pub force: Vector<Real>,
+    /// The torque applied around the joint’s anchor on the first rigid-body.
+    pub torque: AngVector<Real>,
+}
+
+impl ImpulseJoint {
+    /// Sets the joint’s frames from a frame expressed in world-space, using the current
+    /// positions of the attached rigid-bodies.
+    ///
+    /// See [`GenericJoint::set_world_frame`] for details.
+    pub fn set_world_frame(
+        &mut self,
+        bodies: &RigidBodySet,
+        world_frame: &Isometry<Real>,
+    ) -> &mut Self {
+        self.data.set_world_frame(
+            bodies[self.body1].position(),
+            bodies[self.body2].position(),
+            world_frame,
+        );
+        self
+    }
+
+    /// Sets the joint’s anchors from an anchor expressed in world-space, using the current
+    /// positions of the attached rigid-bodies.
+    ///
+    /// See [`GenericJoint::set_world_anchor`] for details.
+    pub fn set_world_anchor(
+        &mut self,
+        bodies: &RigidBodySet,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.data.set_world_anchor(
+            bodies[self.body1].position(),
+            bodies[self.body2].position(),
+            world_anchor,
+        );
+        self
+    }
+
+    /// Sets the joint’s principal axes from an axis expressed in world-space, using the current
+    /// positions of the attached rigid-bodies.
+    ///
+    /// See [`GenericJoint::set_world_axis`] for details.
+    pub fn set_world_axis(
+        &mut self,
+        bodies: &RigidBodySet,
+        world_axis: UnitVector<Real>,
+    ) -> &mut Self {
+        self.data.set_world_axis(
+            bodies[self.body1].position(),
+            bodies[self.body2].position(),
+            world_axis,
+        );
+        self
+    }
+
+    /// The force and torque applied by this joint during the last simulation step, expressed in
+    /// the joint’s frame attached to the first rigid-body.
+    ///
+    /// This accounts for the locked axes, as well as for the limits and motors of the joint.
+    /// Since the joint is solved again at each substep, this is the reaction during the last
+    /// substep, which is the one matching the final state of the step. Comparing its magnitude
+    /// with a threshold is enough to implement breakable joints.
+    pub fn reaction(&self) -> JointReaction {
+        if self.impulses_dt == 0.0 {
+            return JointReaction::default();
+        }
+
+        let mut impulses = SpacialVector::zeros();
+
+        for (i, impulse) in impulses.iter_mut().enumerate() {
+            let axis = JointAxesMask::from_bits_truncate(1 << i);
+
+            if self.data.locked_axes.contains(axis) {
+                *impulse += self.impulses[i];
+            }
+            if self.data.limit_axes.contains(axis) {
+                *impulse += self.data.limits[i].impulse;
+            }
+            if self.data.motor_axes.contains(axis) {
+                *impulse += self.data.motors[i].impulse;
+            }
+        }
+
+        let forces = impulses / self.impulses_dt;
+
+        JointReaction {
+            force: forces.fixed_rows::<DIM>(0).into_owned(),
+            #[cfg(feature = "dim2")]
+            torque: forces[DIM],
+            #[cfg(feature = "dim3")]
+            torque: forces.fixed_rows::<3>(DIM).into_owned(),
+        }
+    }
+
+    /// The force and torque applied by this joint during the last simulation step, expressed in
+    /// world-space.
+    ///
+    /// See [`ImpulseJoint::reaction`] for details.
+    pub fn world_reaction(&self, bodies: &RigidBodySet) -> JointReaction {
+        let local = self.reaction();
+        let Some(rb1) = bodies.get(self.body1) else {
+            return local;
+        };
+        let rotation = rb1.rotation() * self.data.local_frame1.rotation;
+
+        JointReaction {
+            force: rotation * local.force,
+            #[cfg(feature = "dim2")]
+            torque: local.torque,
+            #[cfg(feature = "dim3")]
+            torque: rotation * local.torque,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{FixedJointBuilder, JointSoftness, RigidBodyBuilder};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn joint_reaction_balances_the_hanging_weight() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let h = world
+            .bodies
+            .insert(RigidBodyBuilder::fixed().rotation(Vector::z() * 0.5));
+        let h_dynamic = world.bodies.insert(RigidBodyBuilder::dynamic());
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            h_dynamic,
+            &mut world.bodies,
+        );
+        let weight = world.bodies[h_dynamic].mass() * 9.81;
+        let joint = world
+            .impulse_joints
+            .insert(h, h_dynamic, FixedJointBuilder::new(), true);
+        assert_eq!(
+            world.impulse_joints.get(joint).unwrap().reaction(),
+            Default::default()
+        );
+        for _ in 0..20 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The hanging body pulls the fixed body downward.
+        let joint = world.impulse_joints.get(joint).unwrap();
+        let reaction = joint.world_reaction(&world.bodies);
+        assert!((reaction.force + Vector::y() * weight).norm() < weight * 0.05);
+        assert!(reaction.torque.norm() < weight * 0.05);
+        // The local reaction is expressed in the rotated frame of the fixed body.
+        assert!((joint.reaction().force.norm() - weight).abs() < weight * 0.05);
+        assert!(joint.reaction().force.x.abs() > weight * 0.1);
+    }
+
+    #[test]
+    fn soft_joints_drift_more_than_stiff_ones() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Two bodies hanging from the ground, one with a much softer joint.
+        let ground = world.bodies.insert(RigidBodyBuilder::fixed());
+        let mut insert_hanging_body = |x: Real, softness: Option<JointSoftness>| {
+            let anchor = Vector::x() * x - Vector::y();
+            let body = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(anchor));
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.1),
+                body,
+                &mut world.bodies,
+            );
+            let joint = FixedJointBuilder::new().local_anchor1(anchor.into());
+            let handle = world.impulse_joints.insert(ground, body, joint, true);
+            world
+                .impulse_joints
+                .get_mut(handle, false)
+                .unwrap()
+                .softness = softness;
+            body
+        };
+        let stiff = insert_hanging_body(-2.0, None);
+        let soft = insert_hanging_body(
+            2.0,
+            Some(JointSoftness {
+                natural_frequency: 1.0,
+                damping_ratio: 1.0,
+            }),
+        );
+        for _ in 0..120 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let stiff_drift = (world.bodies[stiff].translation().y + 1.0).abs();
+        let soft_drift = (world.bodies[soft].translation().y + 1.0).abs();
+        assert!(stiff_drift < 1.0e-3, "stiff drift: {stiff_drift}");
+        assert!(soft_drift > 0.05, "soft drift: {soft_drift}");
+    }
+}
@@ -165,6 +165,22 @@ impl ImpulseJointSet {
         joint
     }
 
+    /// Enables or disables the joint with the given handle, without removing it.
+    ///
+    /// A disabled joint keeps its configuration and its impulses, which are used for
+    /// warmstarting the solver again once the joint is re-enabled. The bodies attached to the
+    /// joint are woken up.
+    ///
+    /// Returns `false` if the handle is invalid.
+    pub fn set_enabled(&mut self, handle: ImpulseJointHandle, enabled: bool) -> bool {
+        if let Some(joint) = self.get_mut(handle, true) {
+            joint.data.set_enabled(enabled);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Gets the joint with the given handle without a known generation.
     ///
     /// This is useful when you know you want the joint at index `i` but
@@ -258,7 +274,9 @@ impl ImpulseJointSet {
             body1,
             body2,
             data,
+            softness: None,
             impulses: na::zero(),
+            impulses_dt: 0.0,
             handle: ImpulseJointHandle(handle),
         };
 
@@ -405,3 +423,46 @@ impl ImpulseJointSet {
         deleted
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{FixedJointBuilder, RigidBodyBuilder};
+    use crate::math::Vector;
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn disabled_joint_keeps_its_impulses() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let h = world.bodies.insert(RigidBodyBuilder::fixed());
+        let h_dynamic = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().additional_mass(1.0));
+        let joint = world
+            .impulse_joints
+            .insert(h, h_dynamic, FixedJointBuilder::new(), true);
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let impulses = world.impulse_joints.get(joint).unwrap().impulses;
+        assert!(impulses.norm() > 0.0);
+        assert!(world.impulse_joints.set_enabled(joint, false));
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The body falls freely, but the joint’s warmstart impulses are preserved.
+        let joints = &mut world.impulse_joints;
+        assert!(world.bodies[h_dynamic].translation().y < -0.1);
+        assert_eq!(joints.get(joint).unwrap().impulses, impulses);
+        assert!(!joints.get(joint).unwrap().data.is_enabled());
+
+        assert!(joints.set_enabled(joint, true));
+        assert!(joints.get(joint).unwrap().data.is_enabled());
+    }
+}
@@ -1,4 +1,4 @@
-pub use self::impulse_joint::ImpulseJoint;
+pub use self::impulse_joint::{ImpulseJoint, JointReaction};
 pub use self::impulse_joint_set::{ImpulseJointHandle, ImpulseJointSet};
 pub(crate) use self::impulse_joint_set::{JointGraphEdge, JointIndex};
 
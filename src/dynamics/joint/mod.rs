@@ -2,6 +2,7 @@ pub use self::fixed_joint::*;
 pub use self::generic_joint::*;
 pub use self::impulse_joint::*;
 pub use self::motor_model::MotorModel;
+pub use self::mouse_joint::*;
 pub use self::multibody_joint::*;
 pub use self::prismatic_joint::*;
 pub use self::revolute_joint::*;
@@ -15,6 +16,7 @@ mod fixed_joint;
 mod generic_joint;
 mod impulse_joint;
 mod motor_model;
+mod mouse_joint;
 mod multibody_joint;
 mod prismatic_joint;
 mod revolute_joint;
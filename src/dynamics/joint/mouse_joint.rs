@@ -0,0 +1,245 @@
+use crate::dynamics::joint::{GenericJoint, GenericJointBuilder, JointAxesMask};
+use crate::dynamics::{JointAxis, MotorModel};
+use crate::math::{Isometry, Point, Real};
+
+#[cfg(feature = "dim2")]
+const LIN_AXES: [JointAxis; 2] = [JointAxis::LinX, JointAxis::LinY];
+#[cfg(feature = "dim3")]
+const LIN_AXES: [JointAxis; 3] = [JointAxis::LinX, JointAxis::LinY, JointAxis::LinZ];
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(transparent)]
+/// A mouse joint, pulls a point of the second rigid-body toward a target point, e.g., for
+/// dragging an object with the mouse cursor or a VR controller.
+///
+/// The target is the joint’s first anchor. It is typically attached to a fixed rigid-body and
+/// moved every frame with [`MouseJoint::set_target`]. The second anchor is the grabbed point,
+/// expressed in the local-space of the dragged rigid-body. The dragged body rotates freely
+/// around its grabbed point.
+///
+/// The body is pulled along each axis by a force-based spring with the given stiffness and
+/// damping, clamped to the given maximum force. Unlike a rigid attachment, this lets the
+/// dragged body be stopped by obstacles instead of pushing through them.
+pub struct MouseJoint {
+    /// The underlying joint data.
+    pub data: GenericJoint,
+}
+
+impl MouseJoint {
+    /// Creates a new mouse joint pulling the grabbed point toward the target with the given
+    /// spring constants, and a force no greater than `max_force` along each axis.
+    pub fn new(stiffness: Real, damping: Real, max_force: Real) -> Self {
+        let mut builder = GenericJointBuilder::new(JointAxesMask::empty());
+
+        for axis in LIN_AXES {
+            builder = builder
+                .motor_position(axis, 0.0, stiffness, damping)
+                .motor_model(axis, MotorModel::ForceBased)
+                .motor_max_force(axis, max_force);
+        }
+
+        Self {
+            data: builder.build(),
+        }
+    }
+
+    /// The underlying generic joint.
+    pub fn data(&self) -> &GenericJoint {
+        &self.data
+    }
+
+    /// Are contacts between the attached rigid-bodies enabled?
+    pub fn contacts_enabled(&self) -> bool {
+        self.data.contacts_enabled
+    }
+
+    /// Sets whether contacts between the attached rigid-bodies are enabled.
+    pub fn set_contacts_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.data.set_contacts_enabled(enabled);
+        self
+    }
+
+    /// The target point, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_target(&self) -> Point<Real> {
+        self.data.local_anchor1()
+    }
+
+    /// Sets the target point, expressed in the local-space of the first rigid-body.
+    pub fn set_local_target(&mut self, target: Point<Real>) -> &mut Self {
+        self.data.set_local_anchor1(target);
+        self
+    }
+
+    /// Sets the target point, expressed in world-space, given the current position of the
+    /// first rigid-body.
+    pub fn set_target(&mut self, body_pos1: &Isometry<Real>, target: Point<Real>) -> &mut Self {
+        self.data
+            .set_local_anchor1(body_pos1.inverse_transform_point(&target));
+        self
+    }
+
+    /// The grabbed point, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_grab_point(&self) -> Point<Real> {
+        self.data.local_anchor2()
+    }
+
+    /// Sets the grabbed point, expressed in the local-space of the second rigid-body.
+    pub fn set_local_grab_point(&mut self, grab_point: Point<Real>) -> &mut Self {
+        self.data.set_local_anchor2(grab_point);
+        self
+    }
+
+    /// Sets the spring constants pulling the grabbed point toward the target.
+    pub fn set_stiffness_and_damping(&mut self, stiffness: Real, damping: Real) -> &mut Self {
+        for axis in LIN_AXES {
+            self.data.set_motor_position(axis, 0.0, stiffness, damping);
+        }
+        self
+    }
+
+    /// Sets the maximum force applied along each axis for pulling the grabbed point.
+    pub fn set_max_force(&mut self, max_force: Real) -> &mut Self {
+        for axis in LIN_AXES {
+            self.data.set_motor_max_force(axis, max_force);
+        }
+        self
+    }
+}
+
+impl From<MouseJoint> for GenericJoint {
+    fn from(val: MouseJoint) -> GenericJoint {
+        val.data
+    }
+}
+
+/// A [MouseJoint] joint using the builder pattern.
+///
+/// This builds a mouse joint which pulls a point of a rigid-body toward a target point.
+/// See the documentation of [MouseJoint] for more information on its behavior.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MouseJointBuilder(pub MouseJoint);
+
+impl MouseJointBuilder {
+    /// Creates a new builder for mouse joints.
+    pub fn new(stiffness: Real, damping: Real, max_force: Real) -> Self {
+        Self(MouseJoint::new(stiffness, damping, max_force))
+    }
+
+    /// Sets whether contacts between the attached rigid-bodies are enabled.
+    #[must_use]
+    pub fn contacts_enabled(mut self, enabled: bool) -> Self {
+        self.0.set_contacts_enabled(enabled);
+        self
+    }
+
+    /// An arbitrary user-defined 128-bit integer associated to the joints built by this builder.
+    #[must_use]
+    pub fn user_data(mut self, data: u128) -> Self {
+        self.0.data.user_data = data;
+        self
+    }
+
+    /// Sets the target point, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_target(mut self, target: Point<Real>) -> Self {
+        self.0.set_local_target(target);
+        self
+    }
+
+    /// Sets the grabbed point, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_grab_point(mut self, grab_point: Point<Real>) -> Self {
+        self.0.set_local_grab_point(grab_point);
+        self
+    }
+
+    /// Builds the mouse joint.
+    #[must_use]
+    pub fn build(self) -> MouseJoint {
+        self.0
+    }
+}
+
+impl From<MouseJointBuilder> for GenericJoint {
+    fn from(val: MouseJointBuilder) -> GenericJoint {
+        val.0.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MouseJointBuilder, LIN_AXES};
+    use crate::dynamics::{JointAxesMask, MotorModel, RigidBodyBuilder};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Isometry, Point, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn mouse_joint_drives_the_linear_axes_only() {
+        let joint = MouseJointBuilder::new(10.0, 2.0, 50.0).build();
+
+        assert!(joint.data.locked_axes.is_empty());
+        assert_eq!(joint.data.motor_axes, JointAxesMask::LIN_AXES);
+
+        for axis in LIN_AXES {
+            let motor = joint.data.motor(axis).unwrap();
+            assert_eq!(motor.target_pos, 0.0);
+            assert_eq!(motor.stiffness, 10.0);
+            assert_eq!(motor.damping, 2.0);
+            assert_eq!(motor.max_force, 50.0);
+            assert_eq!(motor.model, MotorModel::ForceBased);
+        }
+    }
+
+    #[test]
+    fn mouse_joint_target_is_expressed_in_the_first_body_frame() {
+        let mut joint = MouseJointBuilder::new(10.0, 2.0, 50.0).build();
+        #[cfg(feature = "dim2")]
+        let body_pos1 = Isometry::translation(1.0, 2.0);
+        #[cfg(feature = "dim3")]
+        let body_pos1 = Isometry::translation(1.0, 2.0, 0.0);
+        let target = Point::from(Vector::x() * 3.0);
+
+        joint.set_target(&body_pos1, target);
+
+        assert_eq!(body_pos1 * joint.local_target(), target);
+    }
+
+    #[test]
+    fn mouse_joint_drags_body_to_target() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let ground = world.bodies.insert(RigidBodyBuilder::fixed());
+        let h = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 5.0));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), h, &mut world.bodies);
+        let mass = world.bodies[h].mass();
+
+        // Grab the ball 0.5 units to the right of its center.
+        let grab_point = Point::from(Vector::x() * 0.5);
+        let joint = MouseJointBuilder::new(100.0 * mass, 20.0 * mass, 1000.0 * mass)
+            .local_grab_point(grab_point)
+            .local_target(Point::from(Vector::x() * 2.0));
+        let _ = world.impulse_joints.insert(ground, h, joint, true);
+
+        for _ in 0..180 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The grabbed point hangs slightly below the target because of gravity.
+        let grabbed = world.bodies[h].position() * grab_point;
+        assert!((grabbed.coords - Vector::x() * 2.0).norm() < 0.2);
+    }
+}
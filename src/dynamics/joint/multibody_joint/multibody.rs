@@ -262,6 +262,57 @@ impl Multibody {
         self.workspace.resize(self.links.len(), self.ndofs);
     }
 
+    /// Enables or disables the joint of the `link_id`-th link.
+    ///
+    /// A disabled joint doesn’t lock any axis, so its link gets all the degrees of freedom and
+    /// keeps its current pose and velocity relative to its parent. Once re-enabled, the link is
+    /// moved back onto the free axes of the joint and only keeps the velocities along them.
+    pub(crate) fn set_link_joint_enabled(&mut self, link_id: usize, enabled: bool) {
+        let link = &mut self.links[link_id];
+        let assembly_id = link.assembly_id;
+        let prev_ndofs = link.joint.ndofs();
+        let rel_vel = link
+            .joint
+            .jacobian_mul_coordinates(&self.velocities.as_slice()[assembly_id..]);
+
+        link.joint.data.set_enabled(enabled);
+        link.joint.project_coords_onto_free_axes();
+        let ndofs = link.joint.ndofs();
+
+        if ndofs != prev_ndofs {
+            self.velocities = self
+                .velocities
+                .clone()
+                .remove_rows(assembly_id, prev_ndofs)
+                .insert_rows(assembly_id, ndofs, 0.0);
+            self.damping = self
+                .damping
+                .clone()
+                .remove_rows(assembly_id, prev_ndofs)
+                .insert_rows(assembly_id, ndofs, 0.0);
+            self.accelerations = self
+                .accelerations
+                .clone()
+                .remove_rows(assembly_id, prev_ndofs)
+                .insert_rows(assembly_id, ndofs, 0.0);
+
+            for link in &mut self.links[link_id + 1..] {
+                link.assembly_id = link.assembly_id + ndofs - prev_ndofs;
+            }
+
+            self.ndofs = self.velocities.len();
+            self.workspace.resize(self.links.len(), self.ndofs);
+            self.links[link_id]
+                .joint
+                .default_damping(&mut self.damping.rows_mut(assembly_id, ndofs));
+        }
+
+        self.links[link_id].joint.coordinates_from_velocity(
+            &rel_vel,
+            &mut self.velocities.as_mut_slice()[assembly_id..assembly_id + ndofs],
+        );
+    }
+
     /// Whether self-contacts are enabled on this multibody.
     ///
     /// If set to `false` no two link from this multibody can generate contacts, even
@@ -1375,8 +1426,10 @@ impl IndexSequence {
 #[cfg(test)]
 mod test {
     use super::IndexSequence;
-    use crate::dynamics::{ImpulseJointSet, IslandManager};
-    use crate::math::{Real, SPATIAL_DIM};
+    use crate::dynamics::{ImpulseJointSet, IslandManager, RevoluteJointBuilder};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Real, Vector, SPATIAL_DIM};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
     use crate::prelude::{
         ColliderSet, MultibodyJointHandle, MultibodyJointSet, RevoluteJoint, RigidBodyBuilder,
         RigidBodySet,
@@ -1566,4 +1619,59 @@ mod test {
             DVector::from(vec![0.0, 10.0, 0.0, 0.0, 0.0, 50.0, 60.0, 0.0, 80.0, 0.0])
         );
     }
+
+    #[test]
+    fn disabled_joint_frees_its_link() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let anchor = world.bodies.insert(RigidBodyBuilder::fixed());
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::x()));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.1), body, &mut world.bodies);
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJointBuilder::new();
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJointBuilder::new(Vector::z_axis());
+        let joint = joint.local_anchor2((-Vector::x()).into());
+        let handle = world
+            .multibody_joints
+            .insert(anchor, body, joint, true)
+            .unwrap();
+        let distance = |world: &PhysicsWorld| world.bodies[body].translation().norm();
+
+        // The pendulum swings around its anchor.
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        assert!((distance(&world) - 1.0).abs() < 1.0e-3);
+
+        // Once disabled, the body gets all the degrees of freedom and falls.
+        assert!(world.multibody_joints.set_enabled(handle, false));
+        assert_eq!(
+            world.multibody_joints.get(handle).unwrap().0.ndofs(),
+            SPATIAL_DIM
+        );
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        assert!(distance(&world) > 1.5);
+
+        // Once re-enabled, the body is moved back onto the joint.
+        assert!(world.multibody_joints.set_enabled(handle, true));
+        assert_eq!(world.multibody_joints.get(handle).unwrap().0.ndofs(), 1);
+        world.step(&mut pipeline, None, &(), &());
+        assert!((distance(&world) - 1.0).abs() < 1.0e-3);
+
+        // The root isn’t attached by any joint.
+        let root = MultibodyJointHandle(anchor.0);
+        assert!(!world.multibody_joints.set_enabled(root, false));
+    }
 }
@@ -61,19 +61,86 @@ impl MultibodyJoint {
     //     &self.joint_rot
     // }
 
+    /// The axes locked by this joint, as bits.
+    ///
+    /// A disabled joint doesn’t lock any axis, giving all the degrees of freedom to its link.
+    fn locked_bits(&self) -> u8 {
+        if self.data.is_enabled() {
+            self.data.locked_axes.bits()
+        } else {
+            0
+        }
+    }
+
+    /// Resets the coordinates of the locked axes, so that the link is moved back onto the free
+    /// axes of this joint, e.g., after it was re-enabled.
+    pub(crate) fn project_coords_onto_free_axes(&mut self) {
+        let locked_bits = self.locked_bits();
+
+        for i in 0..SPATIAL_DIM {
+            if (locked_bits & (1 << i)) != 0 {
+                self.coords[i] = 0.0;
+            }
+        }
+
+        let locked_ang_bits = locked_bits >> DIM;
+        let num_free_ang_dofs = ANG_DIM - locked_ang_bits.count_ones() as usize;
+        match num_free_ang_dofs {
+            0 => self.joint_rot = Rotation::identity(),
+            #[cfg(feature = "dim3")]
+            1 => {
+                // Only keep the twist of the joint rotation around its free axis.
+                let dof_id = (!locked_ang_bits).trailing_zeros() as usize;
+                let angle = 2.0 * self.joint_rot.imag()[dof_id].atan2(self.joint_rot.w);
+                self.coords[DIM + dof_id] = angle;
+                self.joint_rot = Rotation::from_axis_angle(&Vector::ith_axis(dof_id), angle);
+            }
+            _ => { /* The rotation is already fully described by the free dofs. */ }
+        }
+    }
+
+    /// Sets in `out` the generalized velocities of this joint matching the given relative
+    /// velocity of its link, projected onto its free axes.
+    ///
+    /// This is the inverse of [`Self::jacobian_mul_coordinates`] for the velocities that this
+    /// joint allows.
+    pub(crate) fn coordinates_from_velocity(&self, vel: &RigidBodyVelocity, out: &mut [Real]) {
+        let locked_bits = self.locked_bits();
+        let mut curr_free_dof = 0;
+
+        for i in 0..DIM {
+            if (locked_bits & (1 << i)) == 0 {
+                out[curr_free_dof] = vel.linvel[i];
+                curr_free_dof += 1;
+            }
+        }
+
+        #[cfg(feature = "dim2")]
+        if (locked_bits & (1 << DIM)) == 0 {
+            out[curr_free_dof] = vel.angvel;
+        }
+        #[cfg(feature = "dim3")]
+        for i in 0..ANG_DIM {
+            if (locked_bits & (1 << (DIM + i))) == 0 {
+                out[curr_free_dof] = vel.angvel[i];
+                curr_free_dof += 1;
+            }
+        }
+    }
+
     fn num_free_lin_dofs(&self) -> usize {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         DIM - (locked_bits & ((1 << DIM) - 1)).count_ones() as usize
     }
 
     /// The number of degrees of freedom allowed by the multibody_joint.
     pub fn ndofs(&self) -> usize {
-        SPATIAL_DIM - self.data.locked_axes.bits().count_ones() as usize
+        SPATIAL_DIM - self.locked_bits().count_ones() as usize
     }
 
     /// The position of the multibody link containing this multibody_joint relative to its parent.
     pub fn body_to_parent(&self) -> Isometry<Real> {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let mut transform = self.joint_rot * self.data.local_frame2.inverse();
 
         for i in 0..DIM {
@@ -88,7 +155,7 @@ impl MultibodyJoint {
     /// Integrate the position of this multibody_joint.
     #[profiling::function]
     pub fn integrate(&mut self, dt: Real, vels: &[Real]) {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let mut curr_free_dof = 0;
 
         for i in 0..DIM {
@@ -140,7 +207,7 @@ impl MultibodyJoint {
 
     /// Sets in `out` the non-zero entries of the multibody_joint jacobian transformed by `transform`.
     pub fn jacobian(&self, transform: &Rotation<Real>, out: &mut JacobianViewMut<Real>) {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let mut curr_free_dof = 0;
 
         for i in 0..DIM {
@@ -186,7 +253,7 @@ impl MultibodyJoint {
     /// Multiply the multibody_joint jacobian by generalized velocities to obtain the
     /// relative velocity of the multibody link containing this multibody_joint.
     pub fn jacobian_mul_coordinates(&self, acc: &[Real]) -> RigidBodyVelocity {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let mut result = RigidBodyVelocity::zero();
         let mut curr_free_dof = 0;
 
@@ -227,7 +294,7 @@ impl MultibodyJoint {
 
     /// Fill `out` with the non-zero entries of a damping that can be applied by default to ensure a good stability of the multibody_joint.
     pub fn default_damping(&self, out: &mut DVectorViewMut<Real>) {
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let mut curr_free_dof = self.num_free_lin_dofs();
 
         // A default damping only for the angular dofs
@@ -242,7 +309,11 @@ impl MultibodyJoint {
 
     /// Maximum number of velocity constrains that can be generated by this multibody_joint.
     pub fn num_velocity_constraints(&self) -> usize {
-        let locked_bits = self.data.locked_axes.bits();
+        if !self.data.is_enabled() {
+            return 0;
+        }
+
+        let locked_bits = self.locked_bits();
         let limit_bits = self.data.limit_axes.bits();
         let motor_bits = self.data.motor_axes.bits();
         let mut num_constraints = 0;
@@ -271,8 +342,12 @@ impl MultibodyJoint {
         jacobians: &mut DVector<Real>,
         constraints: &mut [JointGenericOneBodyConstraint],
     ) -> usize {
+        if !self.data.is_enabled() {
+            return 0;
+        }
+
         let j_id = &mut j_id;
-        let locked_bits = self.data.locked_axes.bits();
+        let locked_bits = self.locked_bits();
         let limit_bits = self.data.limit_axes.bits();
         let motor_bits = self.data.motor_axes.bits();
         let mut num_constraints = 0;
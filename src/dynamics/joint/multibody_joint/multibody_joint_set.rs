@@ -347,6 +347,43 @@ impl MultibodyJointSet {
         Some((multibody, link.id))
     }
 
+    /// Enables or disables the multibody joint with the given handle, without removing it.
+    ///
+    /// A disabled multibody joint doesn’t lock any axis and doesn’t apply its limits and motors,
+    /// so the attached body moves freely while staying part of the multibody. Its configuration
+    /// is preserved: once re-enabled, the attached body is moved back onto the joint’s free axes
+    /// at the next step. The bodies attached to the joint are woken up.
+    ///
+    /// Returns `false` if the handle is invalid or identifies the root of a multibody, which
+    /// isn’t attached by any joint.
+    pub fn set_enabled(&mut self, handle: MultibodyJointHandle, enabled: bool) -> bool {
+        let Some((multibody, link_id)) = self.get_mut(handle) else {
+            return false;
+        };
+
+        if link_id == 0 {
+            return false;
+        }
+
+        let link = multibody.link(link_id).unwrap();
+        if link.joint.data.is_enabled() != enabled {
+            multibody.set_link_joint_enabled(link_id, enabled);
+        }
+
+        let link = multibody.link(link_id).unwrap();
+        let rb = link.rigid_body;
+        let parent_rb = link
+            .parent_id()
+            .map(|parent_id| multibody.link(parent_id).unwrap().rigid_body);
+
+        self.to_wake_up.insert(rb, ());
+        if let Some(parent_rb) = parent_rb {
+            self.to_wake_up.insert(parent_rb, ());
+        }
+
+        true
+    }
+
     /// Gets a mutable reference to the multibody identified by its `handle`.
     ///
     /// This method will bypass any modification-detection automatically done by the MultibodyJointSet.
@@ -1,6 +1,6 @@
 use crate::dynamics::joint::{GenericJoint, GenericJointBuilder, JointAxesMask};
 use crate::dynamics::{JointAxis, MotorModel};
-use crate::math::{Point, Real, UnitVector};
+use crate::math::{Isometry, Point, Real, UnitVector};
 
 use super::{JointLimits, JointMotor};
 
@@ -65,6 +65,19 @@ impl PrismaticJoint {
         self
     }
 
+    /// Sets the joint’s anchors from an anchor expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_anchor(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.data
+            .set_world_anchor(body_pos1, body_pos2, world_anchor);
+        self
+    }
+
     /// The principal axis of the joint, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_axis1(&self) -> UnitVector<Real> {
@@ -89,6 +102,18 @@ impl PrismaticJoint {
         self
     }
 
+    /// Sets the joint’s principal axes from an axis expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_axis(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_axis: UnitVector<Real>,
+    ) -> &mut Self {
+        self.data.set_world_axis(body_pos1, body_pos2, world_axis);
+        self
+    }
+
     /// The motor affecting the joint’s translational degree of freedom.
     #[must_use]
     pub fn motor(&self) -> Option<&JointMotor> {
@@ -180,6 +205,13 @@ impl PrismaticJointBuilder {
         self
     }
 
+    /// An arbitrary user-defined 128-bit integer associated to the joints built by this builder.
+    #[must_use]
+    pub fn user_data(mut self, data: u128) -> Self {
+        self.0.data.user_data = data;
+        self
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Point<Real>) -> Self {
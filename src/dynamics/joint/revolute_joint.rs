@@ -1,6 +1,6 @@
 use crate::dynamics::joint::{GenericJoint, GenericJointBuilder, JointAxesMask};
 use crate::dynamics::{JointAxis, JointLimits, JointMotor, MotorModel};
-use crate::math::{Point, Real, Rotation};
+use crate::math::{Isometry, Point, Real, Rotation};
 
 #[cfg(feature = "dim3")]
 use crate::math::UnitVector;
@@ -75,6 +75,34 @@ impl RevoluteJoint {
         self
     }
 
+    /// Sets the joint’s anchors from an anchor expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_anchor(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.data
+            .set_world_anchor(body_pos1, body_pos2, world_anchor);
+        self
+    }
+
+    /// Sets the joint’s principal axes from an axis expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    ///
+    /// The current relative orientation of the rigid-bodies becomes the joint’s zero angle.
+    #[cfg(feature = "dim3")]
+    pub fn set_world_axis(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_axis: UnitVector<Real>,
+    ) -> &mut Self {
+        self.data.set_world_axis(body_pos1, body_pos2, world_axis);
+        self
+    }
+
     /// The angle along the free degree of freedom of this revolute joint in `[-π, π]`.
     ///
     /// # Parameters
@@ -197,6 +225,13 @@ impl RevoluteJointBuilder {
         self
     }
 
+    /// An arbitrary user-defined 128-bit integer associated to the joints built by this builder.
+    #[must_use]
+    pub fn user_data(mut self, data: u128) -> Self {
+        self.0.data.user_data = data;
+        self
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Point<Real>) -> Self {
@@ -316,4 +351,49 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_revolute_joint_world_anchor() {
+        use crate::math::{Isometry, Vector};
+        use crate::na::point;
+
+        #[cfg(feature = "dim2")]
+        let (mut revolute, pos1, pos2, anchor) = (
+            super::RevoluteJointBuilder::new().build(),
+            Isometry::new(Vector::new(1.0, 2.0), 0.5),
+            Isometry::new(Vector::new(-3.0, 0.0), -1.0),
+            point![0.5, 1.5],
+        );
+        #[cfg(feature = "dim3")]
+        let (mut revolute, pos1, pos2, anchor) = (
+            super::RevoluteJointBuilder::new(Vector::z_axis()).build(),
+            Isometry::new(Vector::new(1.0, 2.0, 3.0), Vector::new(0.5, 0.0, 0.2)),
+            Isometry::new(Vector::new(-3.0, 0.0, 1.0), Vector::new(0.0, -1.0, 0.3)),
+            point![0.5, 1.5, -2.0],
+        );
+
+        revolute.set_world_anchor(&pos1, &pos2, anchor);
+        approx::assert_relative_eq!(pos1 * revolute.local_anchor1(), anchor, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(pos2 * revolute.local_anchor2(), anchor, epsilon = 1.0e-5);
+
+        #[cfg(feature = "dim3")]
+        {
+            revolute.set_world_axis(&pos1, &pos2, Vector::y_axis());
+            approx::assert_relative_eq!(
+                (pos1 * revolute.data.local_axis1()).into_inner(),
+                Vector::y(),
+                epsilon = 1.0e-5
+            );
+            approx::assert_relative_eq!(
+                (pos2 * revolute.data.local_axis2()).into_inner(),
+                Vector::y(),
+                epsilon = 1.0e-5
+            );
+            approx::assert_relative_eq!(
+                revolute.angle(&pos1.rotation, &pos2.rotation),
+                0.0,
+                epsilon = 1.0e-5
+            );
+        }
+    }
 }
@@ -66,6 +66,19 @@ impl SphericalJoint {
         self
     }
 
+    /// Sets the joint’s anchors from an anchor expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_anchor(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_anchor: Point<Real>,
+    ) -> &mut Self {
+        self.data
+            .set_world_anchor(body_pos1, body_pos2, world_anchor);
+        self
+    }
+
     /// Gets both the joint anchor and the joint’s reference orientation relative to the first
     /// rigid-body’s local-space.
     #[must_use]
@@ -94,6 +107,18 @@ impl SphericalJoint {
         self
     }
 
+    /// Sets the joint’s frames from a frame expressed in world-space, given the current
+    /// positions of the attached rigid-bodies.
+    pub fn set_world_frame(
+        &mut self,
+        body_pos1: &Isometry<Real>,
+        body_pos2: &Isometry<Real>,
+        world_frame: &Isometry<Real>,
+    ) -> &mut Self {
+        self.data.set_world_frame(body_pos1, body_pos2, world_frame);
+        self
+    }
+
     /// The motor affecting the joint’s rotational degree of freedom along the specified axis.
     #[must_use]
     pub fn motor(&self, axis: JointAxis) -> Option<&JointMotor> {
@@ -194,6 +219,13 @@ impl SphericalJointBuilder {
         self
     }
 
+    /// An arbitrary user-defined 128-bit integer associated to the joints built by this builder.
+    #[must_use]
+    pub fn user_data(mut self, data: u128) -> Self {
+        self.0.data.user_data = data;
+        self
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Point<Real>) -> Self {
@@ -2,12 +2,21 @@
 
 pub use self::ccd::CCDSolver;
 pub use self::coefficient_combine_rule::CoefficientCombineRule;
-pub use self::integration_parameters::IntegrationParameters;
-pub use self::island_manager::IslandManager;
+pub use self::integration_parameters::{
+    ContactFrictionMode, ContactSoftness, IntegrationParameters, JointSoftness,
+    PositionCorrectionMode, QuarantineThresholds, VelocityConvergenceCriterion,
+};
+pub use self::island_manager::{
+    IslandManager, IslandSolverContext, IslandSolverIterations, SolverIterationsHeuristic,
+};
 pub(crate) use self::joint::JointGraphEdge;
 pub(crate) use self::joint::JointIndex;
 pub use self::joint::*;
+pub use self::quantization::{QuantizedBodyState, QuantizedRotation, Quantizer, StateQuantization};
 pub use self::rigid_body_components::*;
+pub use self::solver::{
+    UserConstraint, UserConstraintHandle, UserConstraintRow, UserConstraintSet,
+};
 // #[cfg(not(feature = "parallel"))]
 pub(crate) use self::solver::IslandSolver;
 // #[cfg(feature = "parallel")]
@@ -22,8 +31,9 @@ mod coefficient_combine_rule;
 mod integration_parameters;
 mod island_manager;
 mod joint;
+mod quantization;
 mod rigid_body_components;
-mod solver;
+pub mod solver;
 
 mod rigid_body;
 mod rigid_body_set;
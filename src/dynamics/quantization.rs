@@ -0,0 +1,318 @@
+use crate::dynamics::{RigidBody, RigidBodyVelocity};
+use crate::math::{AngVector, Isometry, Real, Rotation, Translation, Vector, ANG_DIM, DIM};
+
+/// A fixed-point quantization of scalar values in the range `[-range, range]`.
+///
+/// Values are rounded to the closest of `2^bits - 1` evenly spaced levels, and values outside of
+/// the range are clamped. Zero is always represented exactly.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quantizer {
+    /// The largest magnitude of the quantized values.
+    pub range: Real,
+    /// The number of bits of each quantized value, in `[2, 32]`.
+    pub bits: u32,
+}
+
+impl Quantizer {
+    /// Creates a quantizer for values in `[-range, range]` encoded on the given number of bits.
+    ///
+    /// Panics if `bits` isn’t in `[2, 32]` or if `range` isn’t positive.
+    pub fn new(range: Real, bits: u32) -> Self {
+        assert!(
+            (2..=32).contains(&bits),
+            "The number of bits must be in [2, 32]."
+        );
+        assert!(range > 0.0, "The quantization range must be positive.");
+        Self { range, bits }
+    }
+
+    /// The number of quantization levels on each side of zero.
+    fn levels(&self) -> u32 {
+        (1 << (self.bits - 1)) - 1
+    }
+
+    /// The distance between two consecutive quantized values.
+    ///
+    /// The quantization error of values within the range is at most half this step.
+    pub fn step(&self) -> Real {
+        self.range / self.levels() as Real
+    }
+
+    /// Encodes a value into its quantized representation.
+    pub fn encode(&self, value: Real) -> u32 {
+        let levels = self.levels() as i64;
+        let normalized = value.clamp(-self.range, self.range) / self.range;
+        // NOTE: the clamp is needed since `levels` may be rounded up when converted to `Real`.
+        let signed_level = ((normalized * levels as Real).round() as i64).clamp(-levels, levels);
+        (signed_level + levels) as u32
+    }
+
+    /// Decodes a value quantized by [`Quantizer::encode`].
+    pub fn decode(&self, quantized: u32) -> Real {
+        let levels = self.levels();
+        let signed_level = quantized.min(2 * levels) as i64 - levels as i64;
+        signed_level as Real / levels as Real * self.range
+    }
+
+    /// The closest quantized value of `value`, i.e., its encoding followed by its decoding.
+    pub fn quantize(&self, value: Real) -> Real {
+        self.decode(self.encode(value))
+    }
+}
+
+/// A quantized rotation.
+///
+/// The rotation is represented by a unit quaternion in 3D, and by a unit complex number in 2D.
+/// Its largest component isn’t stored since it can be deduced from the others.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuantizedRotation {
+    /// The index of the largest component.
+    ///
+    /// In 2D, the second bit is set if this component is negative. In 3D, the unit quaternion
+    /// is negated if needed so that its largest component is positive.
+    pub largest: u8,
+    /// The other components, quantized in `[-1 / √2, 1 / √2]`.
+    #[cfg(feature = "dim2")]
+    pub components: [u32; 1],
+    /// The other components, quantized in `[-1 / √2, 1 / √2]`.
+    #[cfg(feature = "dim3")]
+    pub components: [u32; 3],
+}
+
+/// The quantized pose and velocity of a rigid-body, obtained with [`StateQuantization::encode`].
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QuantizedBodyState {
+    /// The quantized translation of the rigid-body.
+    pub translation: [u32; DIM],
+    /// The quantized rotation of the rigid-body.
+    pub rotation: QuantizedRotation,
+    /// The quantized linear velocity of the rigid-body.
+    pub linvel: [u32; DIM],
+    /// The quantized angular velocity of the rigid-body.
+    pub angvel: [u32; ANG_DIM],
+}
+
+/// The precision of the quantized states of rigid-bodies, e.g., for sending them over the
+/// network.
+///
+/// Decoding a quantized state only relies on IEEE 754 basic operations and square roots, so all
+/// the peers obtain bit-exactly the same pose and velocity from the same quantized state. In order
+/// to get the exact same simulation on all the peers, the peer sending the states must apply the
+/// quantized states to its own rigid-bodies too, e.g., with [`StateQuantization::quantize`]. The
+/// simulation can then be re-simulated deterministically from this snapshot on each peer.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StateQuantization {
+    /// The quantization of each translation component (default: range of `1.0e4`, and `32` bits).
+    pub translation: Quantizer,
+    /// The number of bits of each stored rotation component, in `[2, 32]` (default: `16`).
+    pub rotation_bits: u32,
+    /// The quantization of each linear velocity component (default: range of `1.0e3`, and `16`
+    /// bits).
+    pub linvel: Quantizer,
+    /// The quantization of each angular velocity component (default: range of `1.0e2`, and `16`
+    /// bits).
+    pub angvel: Quantizer,
+}
+
+impl Default for StateQuantization {
+    fn default() -> Self {
+        Self {
+            translation: Quantizer::new(1.0e4, 32),
+            rotation_bits: 16,
+            linvel: Quantizer::new(1.0e3, 16),
+            angvel: Quantizer::new(1.0e2, 16),
+        }
+    }
+}
+
+impl StateQuantization {
+    fn rotation_quantizer(&self) -> Quantizer {
+        Quantizer::new(std::f64::consts::FRAC_1_SQRT_2 as Real, self.rotation_bits)
+    }
+
+    /// Quantizes the pose and velocity of a rigid-body.
+    pub fn encode(&self, rb: &RigidBody) -> QuantizedBodyState {
+        let pos = rb.position();
+        QuantizedBodyState {
+            translation: std::array::from_fn(|i| {
+                self.translation.encode(pos.translation.vector[i])
+            }),
+            rotation: self.encode_rotation(&pos.rotation),
+            linvel: std::array::from_fn(|i| self.linvel.encode(rb.linvel()[i])),
+            angvel: self.encode_angvel(&rb.vels.angvel),
+        }
+    }
+
+    /// Decodes the pose and velocity of a rigid-body quantized by [`StateQuantization::encode`].
+    pub fn decode(&self, state: &QuantizedBodyState) -> (Isometry<Real>, RigidBodyVelocity) {
+        let translation = Vector::from_fn(|i, _| self.translation.decode(state.translation[i]));
+        let pose = Isometry::from_parts(
+            Translation::from(translation),
+            self.decode_rotation(&state.rotation),
+        );
+        let vels = RigidBodyVelocity {
+            linvel: Vector::from_fn(|i, _| self.linvel.decode(state.linvel[i])),
+            angvel: self.decode_angvel(&state.angvel),
+        };
+        (pose, vels)
+    }
+
+    /// Sets the pose and velocity of a rigid-body to the ones of a quantized state.
+    pub fn apply(&self, state: &QuantizedBodyState, rb: &mut RigidBody, wake_up: bool) {
+        let (pose, vels) = self.decode(state);
+        rb.set_position(pose, wake_up);
+        rb.set_linvel(vels.linvel, wake_up);
+        rb.set_angvel(vels.angvel, wake_up);
+    }
+
+    /// Rounds the pose and velocity of a rigid-body to their quantized values, and returns its
+    /// quantized state.
+    ///
+    /// The rigid-body then has exactly the state obtained by the peers applying the returned
+    /// quantized state with [`StateQuantization::apply`].
+    pub fn quantize(&self, rb: &mut RigidBody, wake_up: bool) -> QuantizedBodyState {
+        let state = self.encode(rb);
+        self.apply(&state, rb, wake_up);
+        state
+    }
+
+    #[cfg(feature = "dim2")]
+    fn encode_rotation(&self, rotation: &Rotation<Real>) -> QuantizedRotation {
+        let components = [rotation.re, rotation.im];
+        let largest = if components[1].abs() > components[0].abs() {
+            1
+        } else {
+            0
+        };
+        let sign_bit = if components[largest] < 0.0 { 2 } else { 0 };
+
+        QuantizedRotation {
+            largest: largest as u8 | sign_bit,
+            components: [self.rotation_quantizer().encode(components[1 - largest])],
+        }
+    }
+
+    #[cfg(feature = "dim2")]
+    fn decode_rotation(&self, rotation: &QuantizedRotation) -> Rotation<Real> {
+        let largest = (rotation.largest & 1) as usize;
+        let other = self.rotation_quantizer().decode(rotation.components[0]);
+        let mut largest_value = (1.0 - other * other).max(0.0).sqrt();
+
+        if rotation.largest & 2 != 0 {
+            largest_value = -largest_value;
+        }
+
+        let mut components = [0.0; 2];
+        components[largest] = largest_value;
+        components[1 - largest] = other;
+        Rotation::from_complex(na::Complex::new(components[0], components[1]))
+    }
+
+    #[cfg(feature = "dim3")]
+    fn encode_rotation(&self, rotation: &Rotation<Real>) -> QuantizedRotation {
+        let mut coords = rotation.coords;
+        let largest = coords.iamax();
+
+        // The quaternions `q` and `-q` represent the same rotation.
+        if coords[largest] < 0.0 {
+            coords = -coords;
+        }
+
+        let quantizer = self.rotation_quantizer();
+        let mut others = (0..4).filter(|i| *i != largest);
+        QuantizedRotation {
+            largest: largest as u8,
+            components: std::array::from_fn(|_| quantizer.encode(coords[others.next().unwrap()])),
+        }
+    }
+
+    #[cfg(feature = "dim3")]
+    fn decode_rotation(&self, rotation: &QuantizedRotation) -> Rotation<Real> {
+        let largest = (rotation.largest as usize).min(3);
+        let quantizer = self.rotation_quantizer();
+        let mut coords = na::Vector4::zeros();
+        let mut sq_norm = 0.0;
+
+        for (i, component) in (0..4)
+            .filter(|i| *i != largest)
+            .zip(rotation.components.iter())
+        {
+            coords[i] = quantizer.decode(*component);
+            sq_norm += coords[i] * coords[i];
+        }
+
+        coords[largest] = (1.0 - sq_norm).max(0.0).sqrt();
+        Rotation::new_normalize(na::Quaternion::from(coords))
+    }
+
+    #[cfg(feature = "dim2")]
+    fn encode_angvel(&self, angvel: &AngVector<Real>) -> [u32; ANG_DIM] {
+        [self.angvel.encode(*angvel)]
+    }
+
+    #[cfg(feature = "dim2")]
+    fn decode_angvel(&self, angvel: &[u32; ANG_DIM]) -> AngVector<Real> {
+        self.angvel.decode(angvel[0])
+    }
+
+    #[cfg(feature = "dim3")]
+    fn encode_angvel(&self, angvel: &AngVector<Real>) -> [u32; ANG_DIM] {
+        std::array::from_fn(|i| self.angvel.encode(angvel[i]))
+    }
+
+    #[cfg(feature = "dim3")]
+    fn decode_angvel(&self, angvel: &[u32; ANG_DIM]) -> AngVector<Real> {
+        AngVector::from_fn(|i, _| self.angvel.decode(angvel[i]))
+    }
+}
+
+#[cfg(all(feature = "dim3", feature = "f32"))]
+#[cfg(test)]
+mod test {
+    use super::{Quantizer, StateQuantization};
+    use crate::prelude::*;
+
+    #[test]
+    fn quantizer_represents_zero_and_bounds_exactly() {
+        let quantizer = Quantizer::new(10.0, 16);
+        assert_eq!(quantizer.quantize(0.0), 0.0);
+        assert_eq!(quantizer.quantize(10.0), 10.0);
+        assert_eq!(quantizer.quantize(-25.0), -10.0);
+        assert!((quantizer.quantize(1.2345) - 1.2345).abs() <= quantizer.step() / 2.0);
+    }
+
+    #[test]
+    fn quantized_state_is_reapplied_bit_exactly() {
+        let quantization = StateQuantization::default();
+        let mut bodies = RigidBodySet::new();
+        let rotation = Rotation::from_scaled_axis(vector![0.3, -1.2, 2.1]);
+        let sender = bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .position(Isometry::from_parts(
+                    Translation::new(12.345, -0.678, 9.1011),
+                    rotation,
+                ))
+                .linvel(vector![1.5, 0.0, -3.25])
+                .angvel(vector![0.1, 0.2, -0.3]),
+        );
+        let receiver = bodies.insert(RigidBodyBuilder::dynamic());
+
+        let state = quantization.quantize(&mut bodies[sender], true);
+        quantization.apply(&state, &mut bodies[receiver], true);
+
+        let (sender, receiver) = (&bodies[sender], &bodies[receiver]);
+        assert_eq!(sender.position(), receiver.position());
+        assert_eq!(sender.linvel(), receiver.linvel());
+        assert_eq!(sender.angvel(), receiver.angvel());
+        assert_eq!(quantization.encode(receiver), state);
+
+        // Zero velocity components remain exactly zero.
+        assert_eq!(receiver.linvel().y, 0.0);
+        assert!(receiver.rotation().angle_to(&rotation) < 1.0e-3);
+        assert!((receiver.translation() - vector![12.345, -0.678, 9.1011]).norm() < 1.0e-3);
+    }
+}
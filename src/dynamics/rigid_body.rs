@@ -1,10 +1,16 @@
+#[cfg(feature = "dim3")]
+use crate::dynamics::AllowedRotationAxis;
+#[cfg(feature = "dim2")]
+use crate::dynamics::AngularInertiaOverride;
 use crate::dynamics::{
-    LockedAxes, MassProperties, RigidBodyActivation, RigidBodyAdditionalMassProps, RigidBodyCcd,
-    RigidBodyChanges, RigidBodyColliders, RigidBodyDamping, RigidBodyDominance, RigidBodyForces,
-    RigidBodyIds, RigidBodyMassProps, RigidBodyPosition, RigidBodyType, RigidBodyVelocity,
+    AppliedForce, LockedAxes, MassProperties, RigidBodyActivation, RigidBodyAdditionalMassProps,
+    RigidBodyCcd, RigidBodyChanges, RigidBodyColliders, RigidBodyDamping, RigidBodyDominance,
+    RigidBodyForces, RigidBodyIds, RigidBodyMassProps, RigidBodyPosition, RigidBodyQuality,
+    RigidBodyType, RigidBodyVelocity,
 };
 use crate::geometry::{
-    ColliderHandle, ColliderMassProps, ColliderParent, ColliderPosition, ColliderSet, ColliderShape,
+    Aabb, ColliderHandle, ColliderMassProps, ColliderParent, ColliderPosition, ColliderSet,
+    ColliderShape,
 };
 use crate::math::{AngVector, Isometry, Point, Real, Rotation, Vector};
 use crate::utils::SimdCross;
@@ -40,6 +46,18 @@ pub struct RigidBody {
     pub(crate) dominance: RigidBodyDominance,
     pub(crate) enabled: bool,
     pub(crate) additional_solver_iterations: usize,
+    pub(crate) max_solved_contact_manifolds: usize,
+    pub(crate) quality: RigidBodyQuality,
+    #[cfg(feature = "dim3")]
+    pub(crate) gyroscopic_forces_enabled: bool,
+    /// The union of the broad-phase AABBs of the colliders, updated by the physics pipeline.
+    pub(crate) bounding_aabb: Option<Aabb>,
+    /// The world-space motion applied by `RigidBodySet::teleport` since the last narrow-phase
+    /// update, if the contacts of this rigid-body must follow it.
+    pub(crate) teleport_motion: Option<Isometry<Real>>,
+    /// The last position of this rigid-body that passed the stability checks, restored if it is
+    /// quarantined.
+    pub(crate) stable_position: Option<Isometry<Real>>,
     /// User-defined data associated to this rigid-body.
     pub user_data: u128,
 }
@@ -69,6 +87,13 @@ impl RigidBody {
             enabled: true,
             user_data: 0,
             additional_solver_iterations: 0,
+            max_solved_contact_manifolds: usize::MAX,
+            quality: RigidBodyQuality::Full,
+            #[cfg(feature = "dim3")]
+            gyroscopic_forces_enabled: false,
+            bounding_aabb: None,
+            teleport_motion: None,
+            stable_position: None,
         }
     }
 
@@ -112,6 +137,13 @@ impl RigidBody {
             dominance,
             enabled,
             additional_solver_iterations,
+            max_solved_contact_manifolds,
+            quality,
+            #[cfg(feature = "dim3")]
+            gyroscopic_forces_enabled,
+            bounding_aabb: _bounding_aabb, // Depends on the colliders which aren’t copied.
+            teleport_motion: _teleport_motion, // Only meaningful for the body that was teleported.
+            stable_position: _stable_position, // Will be updated from the copied pose.
             user_data,
         } = other;
 
@@ -127,6 +159,12 @@ impl RigidBody {
         self.dominance = *dominance;
         self.enabled = *enabled;
         self.additional_solver_iterations = *additional_solver_iterations;
+        self.max_solved_contact_manifolds = *max_solved_contact_manifolds;
+        self.quality = *quality;
+        #[cfg(feature = "dim3")]
+        {
+            self.gyroscopic_forces_enabled = *gyroscopic_forces_enabled;
+        }
         self.user_data = *user_data;
 
         self.changes = RigidBodyChanges::all();
@@ -153,6 +191,81 @@ impl RigidBody {
         self.additional_solver_iterations = additional_iterations;
     }
 
+    /// The maximum number of contact manifolds involving this rigid-body that are solved at
+    /// each step.
+    ///
+    /// See [`Self::set_max_solved_contact_manifolds`] for additional information.
+    pub fn max_solved_contact_manifolds(&self) -> usize {
+        self.max_solved_contact_manifolds
+    }
+
+    /// Sets the maximum number of contact manifolds involving this rigid-body that are solved at
+    /// each step.
+    ///
+    /// If more manifolds involve this rigid-body, only the ones with the deepest penetrations are
+    /// given to the constraints solver. The others are ignored for this step, as if the
+    /// contact was disabled by the physics hooks. This prevents a single body buried into a
+    /// large number of small objects, e.g., debris, from dominating the cost of the solver, at the
+    /// expense of some extra penetrations.
+    ///
+    /// The default value is `usize::MAX`, meaning all the contact manifolds are solved.
+    pub fn set_max_solved_contact_manifolds(&mut self, max_manifolds: usize) {
+        self.max_solved_contact_manifolds = max_manifolds;
+    }
+
+    /// The simulation quality tier of this rigid-body.
+    pub fn quality(&self) -> RigidBodyQuality {
+        self.quality
+    }
+
+    /// Sets the simulation quality tier of this rigid-body.
+    ///
+    /// See [`RigidBodyQuality`] for the effects of each tier.
+    pub fn set_quality(&mut self, quality: RigidBodyQuality) {
+        self.quality = quality;
+    }
+
+    pub(crate) fn is_debris(&self) -> bool {
+        self.quality == RigidBodyQuality::Debris
+    }
+
+    /// Is the gyroscopic torque integrated for this rigid-body?
+    ///
+    /// See [`Self::enable_gyroscopic_forces`] for additional information.
+    #[cfg(feature = "dim3")]
+    pub fn is_gyroscopic_forces_enabled(&self) -> bool {
+        self.gyroscopic_forces_enabled
+    }
+
+    /// Enables or disables the integration of the gyroscopic torque `-ω × (I ω)` of this
+    /// rigid-body.
+    ///
+    /// When disabled, the angular velocity of a spinning rigid-body is constant in the absence of
+    /// external torques, even if its angular momentum isn’t aligned with it. Enabling it makes
+    /// asymmetric rigid-bodies precess and tumble realistically, e.g., a spinning top or a thrown
+    /// tool rotating about its intermediate axis (the Dzhanibekov effect). The torque is integrated
+    /// implicitly (see [`RigidBodyVelocity::angvel_with_gyroscopic_forces`]) so this stays stable
+    /// for fast spinning rigid-bodies.
+    ///
+    /// This is disabled by default.
+    #[cfg(feature = "dim3")]
+    pub fn enable_gyroscopic_forces(&mut self, enabled: bool) {
+        self.gyroscopic_forces_enabled = enabled;
+    }
+
+    /// The world-space AABB containing all the enabled colliders attached to this rigid-body.
+    ///
+    /// This is the union of the AABBs of the colliders given to the broad-phase, i.e., enlarged
+    /// by their contact skin and prediction distance. It is updated by the physics pipeline each
+    /// time the colliders move, so it reflects the positions of the colliders at the end of the
+    /// last simulation step, and doesn’t take into account the changes made by the user since.
+    ///
+    /// Returns `None` before the first step after the rigid-body was inserted, or if it doesn’t
+    /// have any enabled collider.
+    pub fn bounding_aabb(&self) -> Option<Aabb> {
+        self.bounding_aabb
+    }
+
     /// The activation status of this rigid-body.
     pub fn activation(&self) -> &RigidBodyActivation {
         &self.activation
@@ -210,6 +323,36 @@ impl RigidBody {
         self.damping.angular_damping = damping
     }
 
+    /// The maximum magnitude of the linear velocity of this rigid-body.
+    #[inline]
+    pub fn max_linear_velocity(&self) -> Real {
+        self.damping.max_linear_velocity
+    }
+
+    /// Sets the maximum magnitude of the linear velocity of this rigid-body.
+    ///
+    /// The velocity computed by the solver is clamped to this limit before being integrated,
+    /// preventing a body hit by an excessive impulse from tunneling through the whole scene.
+    /// This is not applied to multibody links. Unlimited by default.
+    #[inline]
+    pub fn set_max_linear_velocity(&mut self, max_linvel: Real) {
+        self.damping.max_linear_velocity = max_linvel;
+    }
+
+    /// The maximum magnitude of the angular velocity of this rigid-body.
+    #[inline]
+    pub fn max_angular_velocity(&self) -> Real {
+        self.damping.max_angular_velocity
+    }
+
+    /// Sets the maximum magnitude of the angular velocity of this rigid-body.
+    ///
+    /// See [`Self::set_max_linear_velocity`] for additional information.
+    #[inline]
+    pub fn set_max_angular_velocity(&mut self, max_angvel: Real) {
+        self.damping.max_angular_velocity = max_angvel;
+    }
+
     /// The type of this rigid-body.
     pub fn body_type(&self) -> RigidBodyType {
         self.body_type
@@ -231,6 +374,42 @@ impl RigidBody {
         }
     }
 
+    /// Sets the type of this rigid-body, carrying over the velocity it is currently moving at.
+    ///
+    /// This is typically used for releasing a grabbed object, i.e., switching it from
+    /// position-based kinematic to dynamic. If this rigid-body is a position-based kinematic body
+    /// with a pending next kinematic position, its velocity is set to the velocity needed to
+    /// reach it within `dt`, and the pending position is discarded: the body keeps moving from its
+    /// current position instead of stopping, or jumping to its target. Otherwise, the velocity
+    /// derived from the last kinematic motion is kept.
+    ///
+    /// The active sets, islands, and contacts are updated during the next timestep, before its
+    /// velocities are solved.
+    pub fn set_body_type_with_velocity_transfer(
+        &mut self,
+        status: RigidBodyType,
+        dt: Real,
+        wake_up: bool,
+    ) {
+        if status == self.body_type {
+            return;
+        }
+
+        if self.body_type == RigidBodyType::KinematicPositionBased
+            && self.pos.next_position != self.pos.position
+            && dt > 0.0
+        {
+            self.vels = self
+                .pos
+                .interpolate_velocity(1.0 / dt, &self.mprops.local_mprops.local_com);
+        }
+
+        // The positions of position-based kinematic bodies are only controlled
+        // by their next kinematic position, so don’t let them jump to a stale target.
+        self.pos.next_position = self.pos.position;
+        self.set_body_type(status, wake_up);
+    }
+
     /// The world-space center-of-mass of this rigid-body.
     #[inline]
     pub fn center_of_mass(&self) -> &Point<Real> {
@@ -271,6 +450,34 @@ impl RigidBody {
         self.mprops.flags
     }
 
+    /// Restricts the rotations of this rigid-body to the given axis, or removes that restriction
+    /// if `axis` is `None`.
+    ///
+    /// While set, this takes precedence over the rotation flags of the [locked axes](Self::locked_axes).
+    /// The part of the current angular velocity not along the axis is removed.
+    #[cfg(feature = "dim3")]
+    pub fn set_allowed_rotation_axis(&mut self, axis: Option<AllowedRotationAxis>, wake_up: bool) {
+        if axis != self.mprops.allowed_rotation_axis {
+            if self.is_dynamic() && wake_up {
+                self.wake_up(true);
+            }
+
+            self.mprops.allowed_rotation_axis = axis;
+            self.update_world_mass_properties();
+
+            if let Some(axis) = axis {
+                let axis = axis.world_axis(&self.pos.position.rotation);
+                self.vels.angvel = *axis * axis.dot(&self.vels.angvel);
+            }
+        }
+    }
+
+    /// The only axis this rigid-body can rotate about, if any.
+    #[cfg(feature = "dim3")]
+    pub fn allowed_rotation_axis(&self) -> Option<AllowedRotationAxis> {
+        self.mprops.allowed_rotation_axis
+    }
+
     #[inline]
     /// Locks or unlocks all the rotations of this rigid-body.
     pub fn lock_rotations(&mut self, locked: bool, wake_up: bool) {
@@ -549,6 +756,33 @@ impl RigidBody {
         )
     }
 
+    /// Replaces the angular inertia computed from the colliders of this rigid-body, or restores it
+    /// if `inertia` is `None`.
+    ///
+    /// As for the additional mass-properties, this is taken into account at the next physics step
+    /// or by [`Self::recompute_mass_properties_from_colliders`].
+    #[cfg(feature = "dim2")]
+    pub fn set_angular_inertia_override(
+        &mut self,
+        inertia: Option<AngularInertiaOverride>,
+        wake_up: bool,
+    ) {
+        if self.mprops.angular_inertia_override != inertia {
+            self.changes.insert(RigidBodyChanges::LOCAL_MASS_PROPERTIES);
+            self.mprops.angular_inertia_override = inertia;
+
+            if self.is_dynamic() && wake_up {
+                self.wake_up(true);
+            }
+        }
+    }
+
+    /// The angular inertia replacing the one computed from the colliders of this rigid-body, if any.
+    #[cfg(feature = "dim2")]
+    pub fn angular_inertia_override(&self) -> Option<AngularInertiaOverride> {
+        self.mprops.angular_inertia_override
+    }
+
     fn do_set_additional_mass_properties(
         &mut self,
         props: RigidBodyAdditionalMassProps,
@@ -665,6 +899,8 @@ impl RigidBody {
         if let Some(i) = self.colliders.0.iter().position(|e| *e == handle) {
             self.changes.set(RigidBodyChanges::COLLIDERS, true);
             self.colliders.0.swap_remove(i);
+            // Recomputed by the pipeline from the remaining colliders, if any.
+            self.bounding_aabb = None;
         }
     }
 
@@ -1009,6 +1245,115 @@ impl RigidBody {
             }
         }
     }
+
+    /// Adds to this rigid-body a constant force at the given world-space point of this
+    /// rigid-body, and returns the force and torque it contributes to the motion of this body.
+    ///
+    /// The returned force and torque, relative to the center-of-mass, exclude their components
+    /// along the locked axes of this rigid-body since these don’t affect its motion.
+    /// This does nothing, and returns zero, on non-dynamic bodies.
+    pub fn add_force_at_point_with_split(
+        &mut self,
+        force: Vector<Real>,
+        point: Point<Real>,
+        wake_up: bool,
+    ) -> AppliedForce {
+        if self.body_type != RigidBodyType::Dynamic {
+            return AppliedForce::default();
+        }
+
+        self.add_force_at_point(force, point, wake_up);
+        let torque = (point - self.mprops.world_com).gcross(force);
+        self.mprops
+            .without_locked_axes(&self.pos.position.rotation, force, torque)
+    }
+
+    /// Adds to this rigid-body an impulse at its center-of-mass, spread evenly across the
+    /// substeps of the next step.
+    ///
+    /// Contrary to [`RigidBody::apply_impulse`] which changes the velocity right away, this acts
+    /// like a force `impulse / dt` applied during the next step only, so the other constraints
+    /// see it progressively at each substep. The resulting motion doesn’t depend on
+    /// [`IntegrationParameters::num_solver_iterations`].
+    ///
+    /// This does nothing on non-dynamic bodies.
+    pub fn add_step_impulse(&mut self, impulse: Vector<Real>, wake_up: bool) {
+        if !impulse.is_zero() && self.body_type == RigidBodyType::Dynamic {
+            self.forces.step_impulse += impulse;
+
+            if wake_up {
+                self.wake_up(true);
+            }
+        }
+    }
+
+    /// Adds to this rigid-body a torque impulse, spread evenly across the substeps of the next
+    /// step.
+    ///
+    /// See [`RigidBody::add_step_impulse`] for details. This does nothing on non-dynamic bodies.
+    #[cfg(feature = "dim2")]
+    pub fn add_step_torque_impulse(&mut self, torque_impulse: Real, wake_up: bool) {
+        if !torque_impulse.is_zero() && self.body_type == RigidBodyType::Dynamic {
+            self.forces.step_torque_impulse += torque_impulse;
+
+            if wake_up {
+                self.wake_up(true);
+            }
+        }
+    }
+
+    /// Adds to this rigid-body a torque impulse, spread evenly across the substeps of the next
+    /// step.
+    ///
+    /// See [`RigidBody::add_step_impulse`] for details. This does nothing on non-dynamic bodies.
+    #[cfg(feature = "dim3")]
+    pub fn add_step_torque_impulse(&mut self, torque_impulse: Vector<Real>, wake_up: bool) {
+        if !torque_impulse.is_zero() && self.body_type == RigidBodyType::Dynamic {
+            self.forces.step_torque_impulse += torque_impulse;
+
+            if wake_up {
+                self.wake_up(true);
+            }
+        }
+    }
+
+    /// Adds to this rigid-body an impulse at the given world-space point, spread evenly across
+    /// the substeps of the next step.
+    ///
+    /// See [`RigidBody::add_step_impulse`] for details. This does nothing on non-dynamic bodies.
+    pub fn add_step_impulse_at_point(
+        &mut self,
+        impulse: Vector<Real>,
+        point: Point<Real>,
+        wake_up: bool,
+    ) {
+        if !impulse.is_zero() && self.body_type == RigidBodyType::Dynamic {
+            self.forces.step_impulse += impulse;
+            self.forces.step_torque_impulse += (point - self.mprops.world_com).gcross(impulse);
+
+            if wake_up {
+                self.wake_up(true);
+            }
+        }
+    }
+
+    /// The total external force and torque applied to this rigid-body during the last step.
+    ///
+    /// This is the sum of the constant forces added by the user, of the step impulses divided by
+    /// the timestep length, and of the gravity, excluding their components along the locked axes
+    /// of this rigid-body. It is only updated by the
+    /// steps where this rigid-body is awake, and is zero for non-dynamic bodies.
+    pub fn applied_external_force(&self) -> AppliedForce {
+        if self.body_type == RigidBodyType::Dynamic {
+            self.mprops.without_locked_axes(
+                &self.pos.position.rotation,
+                self.forces.force,
+                self.forces.torque,
+            )
+        } else {
+            AppliedForce::default()
+        }
+    }
 }
 
 /// ## Applying impulses and angular impulses
@@ -1073,6 +1418,28 @@ impl RigidBody {
         self.apply_torque_impulse(torque_impulse, wake_up);
     }
 
+    /// Applies an impulse at the given world-space point of this rigid-body, and returns the
+    /// linear and angular impulses it contributes to the motion of this body.
+    ///
+    /// The returned impulses, relative to the center-of-mass, exclude their components along
+    /// the locked axes of this rigid-body. This does nothing, and returns zero, on non-dynamic
+    /// bodies.
+    pub fn apply_impulse_at_point_with_split(
+        &mut self,
+        impulse: Vector<Real>,
+        point: Point<Real>,
+        wake_up: bool,
+    ) -> AppliedForce {
+        if self.body_type != RigidBodyType::Dynamic {
+            return AppliedForce::default();
+        }
+
+        self.apply_impulse_at_point(impulse, point, wake_up);
+        let torque_impulse = (point - self.mprops.world_com).gcross(impulse);
+        self.mprops
+            .without_locked_axes(&self.pos.position.rotation, impulse, torque_impulse)
+    }
+
     /// Retrieves the constant force(s) that the user has added to the body.
     ///
     /// Returns zero if the rigid-body isn’t dynamic.
@@ -1139,15 +1506,33 @@ pub struct RigidBodyBuilder {
     pub linear_damping: Real,
     /// Damping factor for gradually slowing down the angular motion of the rigid-body, `0.0` by default.
     pub angular_damping: Real,
+    /// The maximum magnitude of the linear velocity of the rigid-body to be built, unlimited by default.
+    pub max_linear_velocity: Real,
+    /// The maximum magnitude of the angular velocity of the rigid-body to be built, unlimited by default.
+    pub max_angular_velocity: Real,
     /// The type of rigid-body being constructed.
     pub body_type: RigidBodyType,
     mprops_flags: LockedAxes,
+    #[cfg(feature = "dim3")]
+    allowed_rotation_axis: Option<AllowedRotationAxis>,
+    #[cfg(feature = "dim2")]
+    angular_inertia_override: Option<AngularInertiaOverride>,
     /// The additional mass-properties of the rigid-body being built. See [`RigidBodyBuilder::additional_mass_properties`] for more information.
     additional_mass_properties: RigidBodyAdditionalMassProps,
     /// Whether the rigid-body to be created can sleep if it reaches a dynamic equilibrium.
     pub can_sleep: bool,
     /// Whether the rigid-body is to be created asleep.
     pub sleeping: bool,
+    /// The linear velocity below which the rigid-body to be created can fall asleep, multiplied
+    /// by [`IntegrationParameters::length_unit`].
+    ///
+    /// See [`RigidBodyActivation::normalized_linear_threshold`] for additional information.
+    pub normalized_linear_sleep_threshold: Real,
+    /// The angular velocity below which the rigid-body to be created can fall asleep.
+    pub angular_sleep_threshold: Real,
+    /// The amount of time the rigid-body to be created must remain below its sleep thresholds
+    /// to be put to sleep.
+    pub time_until_sleep: Real,
     /// Whether Continuous Collision-Detection is enabled for the rigid-body to be built.
     ///
     /// CCD prevents tunneling, but may still allow limited interpenetration of colliders.
@@ -1174,6 +1559,18 @@ pub struct RigidBodyBuilder {
     ///
     /// See [`RigidBody::set_additional_solver_iterations`] for additional information.
     pub additional_solver_iterations: usize,
+    /// The maximum number of contact manifolds involving this rigid-body that are solved at
+    /// each step.
+    ///
+    /// See [`RigidBody::set_max_solved_contact_manifolds`] for additional information.
+    pub max_solved_contact_manifolds: usize,
+    /// The simulation quality tier of the rigid-body to be built.
+    pub quality: RigidBodyQuality,
+    /// Whether the gyroscopic torque is integrated for the rigid-body to be built.
+    ///
+    /// See [`RigidBody::enable_gyroscopic_forces`] for additional information.
+    #[cfg(feature = "dim3")]
+    pub gyroscopic_forces_enabled: bool,
 }
 
 impl Default for RigidBodyBuilder {
@@ -1192,17 +1589,31 @@ impl RigidBodyBuilder {
             gravity_scale: 1.0,
             linear_damping: 0.0,
             angular_damping: 0.0,
+            max_linear_velocity: Real::MAX,
+            max_angular_velocity: Real::MAX,
             body_type,
             mprops_flags: LockedAxes::empty(),
+            #[cfg(feature = "dim3")]
+            allowed_rotation_axis: None,
+            #[cfg(feature = "dim2")]
+            angular_inertia_override: None,
             additional_mass_properties: RigidBodyAdditionalMassProps::default(),
             can_sleep: true,
             sleeping: false,
+            normalized_linear_sleep_threshold:
+                RigidBodyActivation::default_normalized_linear_threshold(),
+            angular_sleep_threshold: RigidBodyActivation::default_angular_threshold(),
+            time_until_sleep: RigidBodyActivation::default_time_until_sleep(),
             ccd_enabled: false,
             soft_ccd_prediction: 0.0,
             dominance_group: 0,
             enabled: true,
             user_data: 0,
             additional_solver_iterations: 0,
+            max_solved_contact_manifolds: usize::MAX,
+            quality: RigidBodyQuality::Full,
+            #[cfg(feature = "dim3")]
+            gyroscopic_forces_enabled: false,
         }
     }
 
@@ -1251,6 +1662,32 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets the maximum number of contact manifolds involving the rigid-body that are solved at
+    /// each step.
+    ///
+    /// See [`RigidBody::set_max_solved_contact_manifolds`] for additional information.
+    pub fn max_solved_contact_manifolds(mut self, max_manifolds: usize) -> Self {
+        self.max_solved_contact_manifolds = max_manifolds;
+        self
+    }
+
+    /// Sets the simulation quality tier of the rigid-body to be built.
+    ///
+    /// See [`RigidBodyQuality`] for the effects of each tier.
+    pub fn quality(mut self, quality: RigidBodyQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Sets whether the gyroscopic torque is integrated for the rigid-body to be built.
+    ///
+    /// See [`RigidBody::enable_gyroscopic_forces`] for additional information.
+    #[cfg(feature = "dim3")]
+    pub fn gyroscopic_forces_enabled(mut self, enabled: bool) -> Self {
+        self.gyroscopic_forces_enabled = enabled;
+        self
+    }
+
     /// Sets the scale applied to the gravity force affecting the rigid-body to be created.
     pub fn gravity_scale(mut self, scale_factor: Real) -> Self {
         self.gravity_scale = scale_factor;
@@ -1325,6 +1762,35 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets the angular inertia of the rigid-body being built, around its center-of-mass.
+    ///
+    /// This replaces the angular inertia computed from the attached colliders and the additional
+    /// mass-properties, while their mass and center-of-mass are kept.
+    #[cfg(feature = "dim2")]
+    pub fn angular_inertia(mut self, inertia: Real) -> Self {
+        self.angular_inertia_override = Some(AngularInertiaOverride::Inertia(inertia));
+        self
+    }
+
+    /// Sets the radius of gyration of the rigid-body being built.
+    ///
+    /// Its angular inertia will be `mass * radius²`, replacing the one computed from the attached
+    /// colliders and the additional mass-properties.
+    #[cfg(feature = "dim2")]
+    pub fn gyration_radius(mut self, radius: Real) -> Self {
+        self.angular_inertia_override = Some(AngularInertiaOverride::GyrationRadius(radius));
+        self
+    }
+
+    /// Gives an infinite angular inertia to the rigid-body being built.
+    ///
+    /// See [`AngularInertiaOverride::Infinite`] for the difference with [`Self::lock_rotations`].
+    #[cfg(feature = "dim2")]
+    pub fn infinite_angular_inertia(mut self) -> Self {
+        self.angular_inertia_override = Some(AngularInertiaOverride::Infinite);
+        self
+    }
+
     /// Sets the axes along which this rigid-body cannot translate or rotate.
     pub fn locked_axes(mut self, locked_axes: LockedAxes) -> Self {
         self.mprops_flags = locked_axes;
@@ -1407,6 +1873,16 @@ impl RigidBodyBuilder {
         self.enabled_rotations(allow_rotations_x, allow_rotations_y, allow_rotations_z)
     }
 
+    /// Only allow rotations of this rigid-body about the given axis.
+    ///
+    /// The axis doesn’t have to be a coordinate axis. This takes precedence over the rotations
+    /// allowed by [`Self::enabled_rotations`] and [`Self::lock_rotations`].
+    #[cfg(feature = "dim3")]
+    pub fn allowed_rotation_axis(mut self, axis: AllowedRotationAxis) -> Self {
+        self.allowed_rotation_axis = Some(axis);
+        self
+    }
+
     /// Sets the damping factor for the linear part of the rigid-body motion.
     ///
     /// The higher the linear damping factor is, the more quickly the rigid-body
@@ -1425,6 +1901,22 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets the maximum magnitude of the linear velocity of the rigid-body to be created.
+    ///
+    /// See [`RigidBody::set_max_linear_velocity`] for additional information.
+    pub fn max_linear_velocity(mut self, max_linvel: Real) -> Self {
+        self.max_linear_velocity = max_linvel;
+        self
+    }
+
+    /// Sets the maximum magnitude of the angular velocity of the rigid-body to be created.
+    ///
+    /// See [`RigidBody::set_max_linear_velocity`] for additional information.
+    pub fn max_angular_velocity(mut self, max_angvel: Real) -> Self {
+        self.max_angular_velocity = max_angvel;
+        self
+    }
+
     /// Sets the initial linear velocity of the rigid-body to be created.
     pub fn linvel(mut self, linvel: Vector<Real>) -> Self {
         self.linvel = linvel;
@@ -1443,6 +1935,29 @@ impl RigidBodyBuilder {
         self
     }
 
+    /// Sets the linear and angular velocities below which the rigid-body to be created can fall
+    /// asleep.
+    ///
+    /// The linear threshold is multiplied by [`IntegrationParameters::length_unit`]. Giving
+    /// large thresholds to small debris lets them sleep aggressively, while tiny thresholds keep
+    /// important bodies awake as long as they move, even slowly.
+    pub fn sleep_thresholds(
+        mut self,
+        normalized_linear_threshold: Real,
+        angular_threshold: Real,
+    ) -> Self {
+        self.normalized_linear_sleep_threshold = normalized_linear_threshold;
+        self.angular_sleep_threshold = angular_threshold;
+        self
+    }
+
+    /// Sets the amount of time the rigid-body to be created must remain below its sleep
+    /// thresholds to be put to sleep.
+    pub fn time_until_sleep(mut self, time_until_sleep: Real) -> Self {
+        self.time_until_sleep = time_until_sleep;
+        self
+    }
+
     /// Sets whether Continuous Collision-Detection is enabled for this rigid-body.
     ///
     /// CCD prevents tunneling, but may still allow limited interpenetration of colliders.
@@ -1488,6 +2003,12 @@ impl RigidBodyBuilder {
         rb.body_type = self.body_type;
         rb.user_data = self.user_data;
         rb.additional_solver_iterations = self.additional_solver_iterations;
+        rb.max_solved_contact_manifolds = self.max_solved_contact_manifolds;
+        rb.quality = self.quality;
+        #[cfg(feature = "dim3")]
+        {
+            rb.gyroscopic_forces_enabled = self.gyroscopic_forces_enabled;
+        }
 
         if self.additional_mass_properties
             != RigidBodyAdditionalMassProps::MassProps(MassProperties::zero())
@@ -1497,13 +2018,28 @@ impl RigidBodyBuilder {
         }
 
         rb.mprops.flags = self.mprops_flags;
+        #[cfg(feature = "dim2")]
+        {
+            rb.mprops.angular_inertia_override = self.angular_inertia_override;
+        }
+        #[cfg(feature = "dim3")]
+        if let Some(axis) = self.allowed_rotation_axis {
+            rb.mprops.allowed_rotation_axis = Some(axis);
+            let axis = axis.world_axis(&rb.pos.position.rotation);
+            rb.vels.angvel = *axis * axis.dot(&rb.vels.angvel);
+        }
         rb.damping.linear_damping = self.linear_damping;
         rb.damping.angular_damping = self.angular_damping;
+        rb.damping.max_linear_velocity = self.max_linear_velocity;
+        rb.damping.max_angular_velocity = self.max_angular_velocity;
         rb.forces.gravity_scale = self.gravity_scale;
         rb.dominance = RigidBodyDominance(self.dominance_group);
         rb.enabled = self.enabled;
         rb.enable_ccd(self.ccd_enabled);
         rb.set_soft_ccd_prediction(self.soft_ccd_prediction);
+        rb.activation.normalized_linear_threshold = self.normalized_linear_sleep_threshold;
+        rb.activation.angular_threshold = self.angular_sleep_threshold;
+        rb.activation.time_until_sleep = self.time_until_sleep;
 
         if self.can_sleep && self.sleeping {
             rb.sleep();
@@ -1523,3 +2059,302 @@ impl From<RigidBodyBuilder> for RigidBody {
         val.build()
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "dim3")]
+    use na::{point, vector};
+
+    use super::RigidBodyBuilder;
+    use crate::dynamics::RigidBodyType;
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn released_kinematic_body_keeps_its_velocity() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+        let dt = world.integration_parameters.dt;
+
+        let h = world
+            .bodies
+            .insert(RigidBodyBuilder::kinematic_position_based());
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), h, &mut world.bodies);
+
+        // Release the body while it is being moved along +X.
+        world.bodies[h].set_next_kinematic_translation(Vector::x() * 0.1);
+        world.bodies[h].set_body_type_with_velocity_transfer(RigidBodyType::Dynamic, dt, true);
+        world.step(&mut pipeline, None, &(), &());
+
+        approx::assert_relative_eq!(world.bodies[h].linvel().x, 0.1 / dt, epsilon = 1.0e-3);
+        approx::assert_relative_eq!(world.bodies[h].translation().x, 0.1, epsilon = 1.0e-3);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn applied_forces_exclude_locked_axes() {
+        use crate::dynamics::LockedAxes;
+
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .locked_axes(LockedAxes::TRANSLATION_LOCKED_Y | LockedAxes::ROTATION_LOCKED_Z),
+        );
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+
+        let split = world.bodies[body].add_force_at_point_with_split(
+            vector![1.0, 2.0, 0.0],
+            point![0.0, 1.0, 1.0],
+            true,
+        );
+        assert_eq!(split.force, vector![1.0, 0.0, 0.0]);
+        assert_eq!(split.torque, vector![-2.0, 1.0, 0.0]);
+
+        world.step(&mut pipeline, None, &(), &());
+
+        // The gravity and the user force along the locked `Y` axis aren’t reported.
+        assert_eq!(world.bodies[body].applied_external_force(), split);
+    }
+
+    #[test]
+    fn step_impulses_do_not_depend_on_substeps() {
+        let velocity_after_step_impulse = |num_solver_iterations| {
+            let mut world = PhysicsWorld::default();
+            let mut pipeline = PhysicsPipeline::new();
+            world.integration_parameters.num_solver_iterations =
+                std::num::NonZeroUsize::new(num_solver_iterations).unwrap();
+
+            let body = world.bodies.insert(RigidBodyBuilder::dynamic());
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5),
+                body,
+                &mut world.bodies,
+            );
+            let mass = world.bodies[body].mass();
+            world.bodies[body].add_step_impulse(Vector::x() * mass, true);
+
+            // The step impulse is only applied once.
+            for _ in 0..2 {
+                world.step(&mut pipeline, None, &(), &());
+            }
+            world.bodies[body].linvel().x
+        };
+
+        assert!((velocity_after_step_impulse(1) - 1.0).abs() < 1.0e-5);
+        assert!((velocity_after_step_impulse(8) - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn rotations_are_restricted_to_the_allowed_axis() {
+        use crate::dynamics::AllowedRotationAxis;
+
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        let axis = na::Unit::new_normalize(vector![1.0, 1.0, 0.0]);
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .rotation(vector![0.0, 0.3, 0.0])
+                .allowed_rotation_axis(AllowedRotationAxis::Local(axis)),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.5, 1.0, 2.0),
+            body,
+            &mut world.bodies,
+        );
+        let world_axis = (world.bodies[body].rotation() * axis).into_inner();
+
+        let applied = world.bodies[body].apply_impulse_at_point_with_split(
+            vector![0.0, 0.0, 1.0],
+            point![0.0, 1.0, 0.0],
+            true,
+        );
+        assert!(applied.torque.cross(&world_axis).norm() < 1.0e-5);
+
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let rb = &world.bodies[body];
+        assert!(rb.angvel().norm() > 1.0e-3);
+        assert!(rb.angvel().cross(&world_axis).norm() < 1.0e-5);
+        assert!(((rb.rotation() * axis).into_inner() - world_axis).norm() < 1.0e-5);
+    }
+
+    #[test]
+    #[cfg(feature = "dim2")]
+    fn angular_inertia_overrides_replace_the_colliders_inertia() {
+        use crate::dynamics::AngularInertiaOverride;
+
+        let mut world = PhysicsWorld::default();
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().angular_inertia(2.0));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.5, 0.5),
+            body,
+            &mut world.bodies,
+        );
+
+        let rb = &mut world.bodies[body];
+        let mass = rb.mass();
+        assert!((rb.mass_properties().local_mprops.principal_inertia() - 2.0).abs() < 1.0e-5);
+
+        rb.set_angular_inertia_override(Some(AngularInertiaOverride::GyrationRadius(3.0)), true);
+        rb.recompute_mass_properties_from_colliders(&world.colliders);
+        assert_eq!(rb.mass(), mass);
+        assert!(
+            (rb.mass_properties().local_mprops.principal_inertia() - mass * 9.0).abs() < 1.0e-3
+        );
+
+        // An infinite inertia prevents rotations without locking them.
+        rb.set_angular_inertia_override(Some(AngularInertiaOverride::Infinite), true);
+        rb.recompute_mass_properties_from_colliders(&world.colliders);
+        rb.apply_torque_impulse(1.0, true);
+        assert_eq!(rb.angvel(), 0.0);
+        assert!(rb.locked_axes().is_empty());
+    }
+
+    #[test]
+    fn velocities_are_clamped_to_the_body_limits() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+        let max_linvel: Real = 10.0;
+
+        let h = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .linvel(Vector::x() * 1.0e6)
+                .max_linear_velocity(max_linvel),
+        );
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), h, &mut world.bodies);
+        world.step(&mut pipeline, None, &(), &());
+
+        let dt = world.integration_parameters.dt;
+        assert!(world.bodies[h].linvel().norm() <= max_linvel * 1.0001);
+        // The position was integrated with the clamped velocity too.
+        assert!(world.bodies[h].translation().norm() <= max_linvel * dt * 1.0001);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn gyroscopic_forces_flip_bodies_spinning_about_their_intermediate_axis() {
+        use crate::dynamics::RigidBodySet;
+        use crate::utils::SimdAngularInertia;
+
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        // The y axis is the intermediate principal axis of this cuboid.
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .angvel(vector![0.05, 5.0, 0.05])
+                .gyroscopic_forces_enabled(true),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.2, 0.5, 1.0),
+            body,
+            &mut world.bodies,
+        );
+
+        let angular_momentum = |bodies: &RigidBodySet| {
+            let rb = &bodies[body];
+            rb.mass_properties()
+                .effective_angular_inertia()
+                .transform_vector(*rb.angvel())
+        };
+        let mut initial_momentum = None;
+        let mut flipped = false;
+
+        for _ in 0..300 {
+            world.step(&mut pipeline, None, &(), &());
+
+            let rb = &world.bodies[body];
+            let local_angvel = rb.position().rotation.inverse_transform_vector(rb.angvel());
+            flipped |= local_angvel.y < 0.0;
+            let _ = initial_momentum.get_or_insert_with(|| angular_momentum(&world.bodies));
+        }
+
+        // Without the gyroscopic torque, the local angular velocity would stay constant.
+        assert!(flipped);
+        // The world-space angular momentum is conserved.
+        let initial_momentum = initial_momentum.unwrap();
+        let final_momentum = angular_momentum(&world.bodies);
+        assert!((final_momentum - initial_momentum).norm() < initial_momentum.norm() * 0.2);
+    }
+
+    #[test]
+    fn bounding_aabbs_follow_the_colliders_of_the_bodies() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        let body = world.bodies.insert(RigidBodyBuilder::dynamic());
+        for x in [-2.0, 2.0] {
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5).translation(Vector::x() * x),
+                body,
+                &mut world.bodies,
+            );
+        }
+        assert!(world.bodies[body].bounding_aabb().is_none());
+
+        world.step(&mut pipeline, None, &(), &());
+        let aabb = world.bodies[body].bounding_aabb().unwrap();
+        assert!(aabb.mins.x <= -2.5 && aabb.maxs.x >= 2.5);
+        assert!(aabb.mins.y <= -0.5 && aabb.maxs.y >= 0.5);
+        assert!(aabb.mins.x > -2.6 && aabb.maxs.x < 2.6);
+
+        world.bodies[body].set_translation(Vector::y() * 10.0, true);
+        world.step(&mut pipeline, None, &(), &());
+        let aabb = world.bodies[body].bounding_aabb().unwrap();
+        assert!(aabb.mins.y <= 9.5 && aabb.maxs.y >= 10.5);
+        assert!(aabb.mins.y > 9.4);
+    }
+
+    #[test]
+    fn rigid_bodies_sleep_with_their_own_thresholds() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        let mut insert = |rb: RigidBodyBuilder, x: Real| {
+            let body = world.bodies.insert(rb.translation(Vector::x() * x));
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5),
+                body,
+                &mut world.bodies,
+            );
+            body
+        };
+        let default = insert(RigidBodyBuilder::dynamic(), 0.0);
+        let debris = insert(RigidBodyBuilder::dynamic().time_until_sleep(0.2), 5.0);
+        let important = insert(
+            RigidBodyBuilder::dynamic()
+                .time_until_sleep(0.2)
+                .sleep_thresholds(0.01, 0.01)
+                .linvel(Vector::y() * 0.1),
+            10.0,
+        );
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        assert!(!world.bodies[default].is_sleeping());
+        assert!(world.bodies[debris].is_sleeping());
+        assert!(!world.bodies[important].is_sleeping());
+    }
+}
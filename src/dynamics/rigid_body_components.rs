@@ -4,7 +4,7 @@ use crate::geometry::{
     ColliderSet, ColliderShape,
 };
 use crate::math::{
-    AngVector, AngularInertia, Isometry, Point, Real, Rotation, Translation, Vector,
+    AngVector, AngularInertia, Isometry, Point, Real, Rotation, Translation, Vector, DIM,
 };
 use crate::parry::partitioning::IndexedData;
 use crate::utils::{SimdAngularInertia, SimdCross, SimdDot};
@@ -12,6 +12,8 @@ use num::Zero;
 
 #[cfg(doc)]
 use super::IntegrationParameters;
+#[cfg(doc)]
+use crate::pipeline::EventHandler;
 
 /// The unique handle of a rigid body added to a `RigidBodySet`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
@@ -231,6 +233,54 @@ bitflags::bitflags! {
     }
 }
 
+/// The only axis a rigid-body is allowed to rotate about.
+///
+/// Contrary to the rotation flags of [`LockedAxes`], this axis doesn’t have to be one of the
+/// coordinate axes, which makes it possible to simulate a tilted hinge without attaching the
+/// rigid-body to the ground with a joint.
+#[cfg(feature = "dim3")]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AllowedRotationAxis {
+    /// An axis fixed in world-space.
+    World(na::Unit<Vector<Real>>),
+    /// An axis expressed in the local-space of the rigid-body.
+    ///
+    /// Since the rigid-body can only rotate about this axis, it stays fixed in world-space too.
+    Local(na::Unit<Vector<Real>>),
+}
+
+#[cfg(feature = "dim3")]
+impl AllowedRotationAxis {
+    /// This axis expressed in world-space, for a rigid-body with the given orientation.
+    pub fn world_axis(&self, rotation: &Rotation<Real>) -> na::Unit<Vector<Real>> {
+        match self {
+            Self::World(axis) => *axis,
+            Self::Local(axis) => rotation * *axis,
+        }
+    }
+}
+
+/// An angular inertia replacing the one computed from the colliders of a 2D rigid-body.
+///
+/// The mass and center-of-mass of the rigid-body are still computed from its colliders and
+/// additional mass-properties.
+#[cfg(feature = "dim2")]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AngularInertiaOverride {
+    /// The angular inertia, around the center-of-mass.
+    Inertia(Real),
+    /// The radius of gyration `r`, giving the angular inertia `mass * r²`.
+    GyrationRadius(Real),
+    /// An infinite angular inertia.
+    ///
+    /// The rigid-body no longer rotates because of contacts, forces or joints, but contrary to
+    /// [`LockedAxes::ROTATION_LOCKED_Z`] its rotation isn’t a locked axis: the torques applied to it
+    /// are still reported by [`RigidBody::applied_external_force`](crate::dynamics::RigidBody::applied_external_force).
+    Infinite,
+}
+
 /// Mass and angular inertia added to a rigid-body on top of its attached colliders’ contributions.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -265,6 +315,14 @@ pub struct RigidBodyMassProps {
     /// The square-root of the world-space inverse angular inertia tensor of the rigid-body,
     /// taking into account rotation locking.
     pub effective_world_inv_inertia_sqrt: AngularInertia<Real>,
+    /// The only axis this rigid-body can rotate about, if any.
+    ///
+    /// If set, this takes precedence over the rotation flags of [`Self::flags`].
+    #[cfg(feature = "dim3")]
+    pub allowed_rotation_axis: Option<AllowedRotationAxis>,
+    /// The angular inertia replacing the one computed from the colliders, if any.
+    #[cfg(feature = "dim2")]
+    pub angular_inertia_override: Option<AngularInertiaOverride>,
 }
 
 impl Default for RigidBodyMassProps {
@@ -276,6 +334,10 @@ impl Default for RigidBodyMassProps {
             world_com: Point::origin(),
             effective_inv_mass: Vector::zero(),
             effective_world_inv_inertia_sqrt: AngularInertia::zero(),
+            #[cfg(feature = "dim3")]
+            allowed_rotation_axis: None,
+            #[cfg(feature = "dim2")]
+            angular_inertia_override: None,
         }
     }
 }
@@ -316,6 +378,23 @@ impl RigidBodyMassProps {
     /// this rigid-body.
     #[must_use]
     pub fn effective_angular_inertia_sqrt(&self) -> AngularInertia<Real> {
+        #[cfg(feature = "dim3")]
+        if self.allowed_rotation_axis.is_some() {
+            // The effective inverse inertia is `c * a * aᵀ` with `c` its trace, so its
+            // pseudo-inverse is itself divided by `c²`.
+            let m = &self.effective_world_inv_inertia_sqrt;
+            let trace = m.m11 + m.m22 + m.m33;
+            let scale = crate::utils::inv(trace * trace);
+            return AngularInertia {
+                m11: m.m11 * scale,
+                m12: m.m12 * scale,
+                m13: m.m13 * scale,
+                m22: m.m22 * scale,
+                m23: m.m23 * scale,
+                m33: m.m33 * scale,
+            };
+        }
+
         #[allow(unused_mut)] // mut needed in 3D.
         let mut ang_inertia = self.effective_world_inv_inertia_sqrt;
 
@@ -399,6 +478,18 @@ impl RigidBodyMassProps {
             }
         }
 
+        #[cfg(feature = "dim2")]
+        if let Some(inertia) = self.angular_inertia_override {
+            let inertia = match inertia {
+                AngularInertiaOverride::Inertia(inertia) => inertia,
+                AngularInertiaOverride::GyrationRadius(radius) => {
+                    self.local_mprops.mass() * radius * radius
+                }
+                AngularInertiaOverride::Infinite => Real::INFINITY,
+            };
+            self.local_mprops.inv_principal_inertia_sqrt = crate::utils::inv(inertia.sqrt());
+        }
+
         self.update_world_mass_properties(position);
     }
 
@@ -430,7 +521,12 @@ impl RigidBodyMassProps {
             }
         }
         #[cfg(feature = "dim3")]
-        {
+        if let Some(axis) = self.allowed_rotation_axis {
+            self.effective_world_inv_inertia_sqrt = self.inv_inertia_sqrt_about_axis(
+                &position.rotation,
+                &axis.world_axis(&position.rotation),
+            );
+        } else {
             if self.flags.contains(LockedAxes::ROTATION_LOCKED_X) {
                 self.effective_world_inv_inertia_sqrt.m11 = 0.0;
                 self.effective_world_inv_inertia_sqrt.m12 = 0.0;
@@ -449,6 +545,63 @@ impl RigidBodyMassProps {
             }
         }
     }
+
+    /// The square root of the inverse angular inertia of this rigid-body when it can only rotate
+    /// about the given world-space axis.
+    #[cfg(feature = "dim3")]
+    fn inv_inertia_sqrt_about_axis(
+        &self,
+        rotation: &Rotation<Real>,
+        axis: &na::Unit<Vector<Real>>,
+    ) -> AngularInertia<Real> {
+        let frame = rotation * self.local_mprops.principal_inertia_local_frame;
+        let local_axis = frame.inverse_transform_vector(axis);
+        let inv_principal_inertia_sqrt = self.local_mprops.inv_principal_inertia_sqrt;
+
+        // The angular inertia about the axis, i.e., `aᵀ * I * a`.
+        let mut inertia = 0.0;
+
+        for i in 0..3 {
+            if inv_principal_inertia_sqrt[i] != 0.0 {
+                inertia += (local_axis[i] / inv_principal_inertia_sqrt[i]).powi(2);
+            } else if local_axis[i].abs() > 1.0e-6 {
+                // The rigid-body can’t rotate about this principal axis at all.
+                return AngularInertia::zero();
+            }
+        }
+
+        // The effective inverse inertia is `a * aᵀ / inertia`, and `a * aᵀ` is its own square.
+        let scaled_axis = axis.into_inner() * crate::utils::inv(inertia).sqrt().sqrt();
+        AngularInertia {
+            m11: scaled_axis.x * scaled_axis.x,
+            m12: scaled_axis.x * scaled_axis.y,
+            m13: scaled_axis.x * scaled_axis.z,
+            m22: scaled_axis.y * scaled_axis.y,
+            m23: scaled_axis.y * scaled_axis.z,
+            m33: scaled_axis.z * scaled_axis.z,
+        }
+    }
+
+    /// The part of `force` and `torque` that affects the motion of this rigid-body, given its
+    /// locked axes and allowed rotation axis.
+    pub(crate) fn without_locked_axes(
+        &self,
+        #[allow(unused_variables)] // Only needed in 3D.
+        rotation: &Rotation<Real>,
+        force: Vector<Real>,
+        torque: AngVector<Real>,
+    ) -> AppliedForce {
+        #[cfg(feature = "dim3")]
+        if let Some(axis) = self.allowed_rotation_axis {
+            let axis = axis.world_axis(rotation);
+            let flags = self.flags - LockedAxes::ROTATION_LOCKED;
+            let mut result = AppliedForce::without_locked_axes(force, torque, flags);
+            result.torque = *axis * axis.dot(&torque);
+            return result;
+        }
+
+        AppliedForce::without_locked_axes(force, torque, self.flags)
+    }
 }
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -573,6 +726,41 @@ impl RigidBodyVelocity {
         }
     }
 
+    /// The angular velocity obtained after integrating, during `dt`, the gyroscopic torque
+    /// `-ω × (I ω)` of a rigid-body with the given mass properties and orientation.
+    ///
+    /// The torque is integrated implicitly, with a single Newton iteration in the principal inertia
+    /// frame of the rigid-body. Unlike an explicit integration, this remains stable for fast
+    /// spinning rigid-bodies, at the cost of a slight loss of kinetic energy while they tumble.
+    ///
+    /// The angular velocity is returned unchanged if some rotations of the rigid-body are locked.
+    #[must_use]
+    #[cfg(feature = "dim3")]
+    pub fn angvel_with_gyroscopic_forces(
+        &self,
+        mprops: &RigidBodyMassProps,
+        rotation: &Rotation<Real>,
+        dt: Real,
+    ) -> AngVector<Real> {
+        if mprops.allowed_rotation_axis.is_some()
+            || mprops.flags.intersects(LockedAxes::ROTATION_LOCKED)
+        {
+            return self.angvel;
+        }
+
+        let frame = rotation * mprops.local_mprops.principal_inertia_local_frame;
+        let inertia = na::Matrix3::from_diagonal(&mprops.local_mprops.principal_inertia());
+        let w = frame.inverse_transform_vector(&self.angvel);
+        let iw = inertia * w;
+        let residual = w.cross(&iw) * dt;
+        let jacobian = inertia + (w.cross_matrix() * inertia - iw.cross_matrix()) * dt;
+
+        match jacobian.try_inverse() {
+            Some(inv_jacobian) => frame * (w - inv_jacobian * residual),
+            None => self.angvel,
+        }
+    }
+
     /// The approximate kinetic energy of this rigid-body.
     ///
     /// This approximation does not take the rigid-body's mass and angular inertia
@@ -584,11 +772,19 @@ impl RigidBodyVelocity {
     }
 
     /// Returns the update velocities after applying the given damping.
+    ///
+    /// The damped velocities are then clamped to the velocity limits of `damping`.
     #[must_use]
     pub fn apply_damping(&self, dt: Real, damping: &RigidBodyDamping) -> Self {
+        let linvel = self.linvel * (1.0 / (1.0 + dt * damping.linear_damping));
+        let angvel = self.angvel * (1.0 / (1.0 + dt * damping.angular_damping));
+
         RigidBodyVelocity {
-            linvel: self.linvel * (1.0 / (1.0 + dt * damping.linear_damping)),
-            angvel: self.angvel * (1.0 / (1.0 + dt * damping.angular_damping)),
+            linvel: linvel.cap_magnitude(damping.max_linear_velocity),
+            #[cfg(feature = "dim2")]
+            angvel: angvel.clamp(-damping.max_angular_velocity, damping.max_angular_velocity),
+            #[cfg(feature = "dim3")]
+            angvel: angvel.cap_magnitude(damping.max_angular_velocity),
         }
     }
 
@@ -722,12 +918,16 @@ impl std::ops::AddAssign<RigidBodyVelocity> for RigidBodyVelocity {
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, Copy, PartialEq)]
-/// Damping factors to progressively slow down a rigid-body.
+/// Damping factors and velocity limits to progressively slow down a rigid-body.
 pub struct RigidBodyDamping {
     /// Damping factor for gradually slowing down the translational motion of the rigid-body.
     pub linear_damping: Real,
     /// Damping factor for gradually slowing down the angular motion of the rigid-body.
     pub angular_damping: Real,
+    /// The maximum magnitude of the linear velocity of the rigid-body.
+    pub max_linear_velocity: Real,
+    /// The maximum magnitude of the angular velocity of the rigid-body.
+    pub max_angular_velocity: Real,
 }
 
 impl Default for RigidBodyDamping {
@@ -735,6 +935,8 @@ impl Default for RigidBodyDamping {
         Self {
             linear_damping: 0.0,
             angular_damping: 0.0,
+            max_linear_velocity: Real::MAX,
+            max_angular_velocity: Real::MAX,
         }
     }
 }
@@ -754,6 +956,10 @@ pub struct RigidBodyForces {
     pub user_force: Vector<Real>,
     /// Torque applied by the user.
     pub user_torque: AngVector<Real>,
+    /// Impulse applied by the user and spread across the substeps of the next step.
+    pub step_impulse: Vector<Real>,
+    /// Torque impulse applied by the user and spread across the substeps of the next step.
+    pub step_torque_impulse: AngVector<Real>,
 }
 
 impl Default for RigidBodyForces {
@@ -764,6 +970,8 @@ impl Default for RigidBodyForces {
             gravity_scale: 1.0,
             user_force: na::zero(),
             user_torque: na::zero(),
+            step_impulse: na::zero(),
+            step_torque_impulse: na::zero(),
         }
     }
 }
@@ -798,6 +1006,16 @@ impl RigidBodyForces {
         self.torque = self.user_torque;
     }
 
+    /// Adds to `self.force` and `self.torque` the step impulses, spread over a step of
+    /// length `dt`, and resets them to zero.
+    pub(crate) fn consume_step_impulses(&mut self, dt: Real) {
+        let inv_dt = crate::utils::inv(dt);
+        self.force += self.step_impulse * inv_dt;
+        self.torque += self.step_torque_impulse * inv_dt;
+        self.step_impulse = na::zero();
+        self.step_torque_impulse = na::zero();
+    }
+
     /// Applies a force at the given world-space point of the rigid-body with the given mass properties.
     pub fn apply_force_at_point(
         &mut self,
@@ -810,6 +1028,59 @@ impl RigidBodyForces {
     }
 }
 
+/// A force and a torque applied to a rigid-body, restricted to its unlocked degrees of freedom.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AppliedForce {
+    /// The force applied at the center-of-mass of the rigid-body.
+    pub force: Vector<Real>,
+    /// The torque applied around the center-of-mass of the rigid-body.
+    pub torque: AngVector<Real>,
+}
+
+impl AppliedForce {
+    /// The part of `force` and `torque` along the axes not locked by `locked_axes`.
+    pub fn without_locked_axes(
+        mut force: Vector<Real>,
+        mut torque: AngVector<Real>,
+        locked_axes: LockedAxes,
+    ) -> Self {
+        let translation_locks = [
+            LockedAxes::TRANSLATION_LOCKED_X,
+            LockedAxes::TRANSLATION_LOCKED_Y,
+            LockedAxes::TRANSLATION_LOCKED_Z,
+        ];
+
+        for (i, lock) in translation_locks.iter().enumerate().take(DIM) {
+            if locked_axes.contains(*lock) {
+                force[i] = 0.0;
+            }
+        }
+
+        #[cfg(feature = "dim2")]
+        if locked_axes.contains(LockedAxes::ROTATION_LOCKED_Z) {
+            torque = 0.0;
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            let rotation_locks = [
+                LockedAxes::ROTATION_LOCKED_X,
+                LockedAxes::ROTATION_LOCKED_Y,
+                LockedAxes::ROTATION_LOCKED_Z,
+            ];
+
+            for (i, lock) in rotation_locks.iter().enumerate() {
+                if locked_axes.contains(*lock) {
+                    torque[i] = 0.0;
+                }
+            }
+        }
+
+        Self { force, torque }
+    }
+}
+
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, Copy, PartialEq)]
 /// Information used for Continuous-Collision-Detection.
@@ -994,6 +1265,28 @@ impl RigidBodyDominance {
     }
 }
 
+/// The simulation quality tier of a rigid-body.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum RigidBodyQuality {
+    /// The rigid-body is simulated with the quality set by the [`IntegrationParameters`].
+    #[default]
+    Full,
+    /// The rigid-body is a cosmetic piece of debris, simulated at a lower quality so that
+    /// large amounts of them can coexist with the other bodies:
+    ///
+    /// - Islands containing only debris are solved with a single solver iteration, and without
+    ///   additional friction iterations.
+    /// - Debris never push non-debris dynamic bodies: contacts between them behave as if the
+    ///   non-debris body was in a higher dominance group.
+    /// - Debris can fall asleep at twice the usual velocity thresholds, after at most
+    ///   half a second.
+    /// - Debris are never affected by CCD, including soft-CCD.
+    /// - [`EventHandler::handle_debris_sleep`] is called whenever a debris falls asleep,
+    ///   e.g., to despawn it.
+    Debris,
+}
+
 /// The rb_activation status of a body.
 ///
 /// This controls whether a body is sleeping or not.
@@ -3,6 +3,7 @@ use crate::dynamics::{
     ImpulseJointSet, IslandManager, MultibodyJointSet, RigidBody, RigidBodyChanges, RigidBodyHandle,
 };
 use crate::geometry::ColliderSet;
+use crate::math::{Isometry, Real};
 use std::ops::{Index, IndexMut};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -199,6 +200,38 @@ impl RigidBodySet {
         Some(result)
     }
 
+    /// Teleports the rigid-body with the given handle to `new_pose`, and wakes it up.
+    ///
+    /// If `preserve_contacts` is `false`, this is the same as [`RigidBody::set_position`]: the
+    /// contacts involving this rigid-body are recomputed from scratch at its new pose by the next
+    /// simulation step, and lose the impulses accumulated by the solver during the previous steps.
+    ///
+    /// If `preserve_contacts` is `true`, the contacts of this rigid-body are moved along with it,
+    /// as if the colliders it touches were teleported by the same motion. The contacts computed at
+    /// the new pose by the next simulation step then keep the impulses of the contacts they match,
+    /// so the solver is warm-started as if nothing happened. This avoids the pop caused by contacts
+    /// settling again, e.g., when a whole vehicle resting on the ground is moved by a few meters
+    /// for lag correction. Rigid-bodies teleported by the same motion before the next simulation
+    /// step, e.g., all the parts of that vehicle, keep their contacts with each other unchanged.
+    ///
+    /// Does nothing if the handle doesn’t match any rigid-body.
+    pub fn teleport(
+        &mut self,
+        handle: RigidBodyHandle,
+        new_pose: Isometry<Real>,
+        preserve_contacts: bool,
+    ) {
+        if let Some(rb) = self.get_mut_internal_with_modification_tracking(handle) {
+            let motion = new_pose * rb.pos.position.inverse();
+            rb.teleport_motion = if preserve_contacts {
+                Some(motion * rb.teleport_motion.unwrap_or_else(Isometry::identity))
+            } else {
+                None
+            };
+            rb.set_position(new_pose, true);
+        }
+    }
+
     /// Iterates through all the rigid-bodies on this set.
     pub fn iter(&self) -> impl Iterator<Item = (RigidBodyHandle, &RigidBody)> {
         self.bodies.iter().map(|(h, b)| (RigidBodyHandle(h), b))
@@ -260,3 +293,75 @@ impl IndexMut<RigidBodyHandle> for RigidBodySet {
         rb
     }
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn teleport_preserving_contacts_keeps_impulses() {
+        use crate::dynamics::RigidBodyBuilder;
+        use crate::geometry::ColliderBuilder;
+        use crate::math::Vector;
+        use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+        use na::point;
+
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Two identical quads side by side, so teleporting by the width of a quad changes the
+        // triangles touched without changing the contact geometry.
+        let vertices = vec![
+            point![-2.0, 0.0, -1.0],
+            point![0.0, 0.0, -1.0],
+            point![0.0, 0.0, 1.0],
+            point![-2.0, 0.0, 1.0],
+            point![2.0, 0.0, -1.0],
+            point![2.0, 0.0, 1.0],
+        ];
+        let indices = vec![[0, 2, 1], [0, 3, 2], [1, 5, 4], [1, 2, 5]];
+        let ground = world
+            .colliders
+            .insert(ColliderBuilder::trimesh(vertices, indices));
+
+        let mut insert_box = |z| {
+            let body = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::new(-1.0, 0.25, z)));
+            let collider = world.colliders.insert_with_parent(
+                ColliderBuilder::cuboid(0.25, 0.25, 0.25),
+                body,
+                &mut world.bodies,
+            );
+            (body, collider)
+        };
+        let (preserved, preserved_collider) = insert_box(-0.5);
+        let (discarded, discarded_collider) = insert_box(0.5);
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        for (body, preserve_contacts) in [(preserved, true), (discarded, false)] {
+            let mut pose = *world.bodies[body].position();
+            pose.translation.vector.x += 2.0;
+            world.bodies.teleport(body, pose, preserve_contacts);
+        }
+        world.step(&mut pipeline, None, &(), &());
+
+        let solver_contacts = |collider| {
+            let pair = world.narrow_phase.contact_pair(ground, collider).unwrap();
+            pair.manifolds
+                .iter()
+                .flat_map(|m| m.data.solver_contacts.iter().copied())
+                .collect::<Vec<_>>()
+        };
+
+        let preserved_contacts = solver_contacts(preserved_collider);
+        let discarded_contacts = solver_contacts(discarded_collider);
+        assert!(!preserved_contacts.is_empty());
+        assert!(preserved_contacts.iter().all(|c| !c.is_new));
+        assert!(discarded_contacts.iter().all(|c| c.is_new));
+    }
+}
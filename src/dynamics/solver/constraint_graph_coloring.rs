@@ -0,0 +1,281 @@
+use crate::dynamics::{
+    ImpulseJointHandle, ImpulseJointSet, MultibodyIndex, MultibodyJointSet, RigidBodyHandle,
+    RigidBodySet,
+};
+use crate::geometry::{ColliderHandle, ColliderSet, NarrowPhase};
+use parry::utils::hashmap::HashMap;
+
+/// A constraint partitioned by a [`ConstraintGraphColoring`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColoredConstraint {
+    /// All the contact constraints between two colliders.
+    ContactPair {
+        /// The first collider of the contact pair.
+        collider1: ColliderHandle,
+        /// The second collider of the contact pair.
+        collider2: ColliderHandle,
+    },
+    /// The constraints of an impulse joint.
+    ImpulseJoint(ImpulseJointHandle),
+}
+
+/// The solver velocities a constraint writes to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum ColoringNode {
+    Body(RigidBodyHandle),
+    // All the links of a multibody share the same generalized velocities.
+    Multibody(MultibodyIndex),
+}
+
+/// A partition of constraints into conflict-free batches, called colors.
+///
+/// Two constraints with the same color never involve the same dynamic rigid-body, nor two links
+/// of the same multibody. So all the constraints of a color can be solved concurrently (e.g. by
+/// a Jacobi or GPU solver) without data races, the colors themselves being solved one after the
+/// other. Fixed and kinematic rigid-bodies aren’t modified by the constraints, so they never
+/// cause conflicts.
+///
+/// The coloring is greedy: each constraint gets the first color not already used by one of its
+/// bodies, in the order they are given. This is independent from the constraints solver of the
+/// physics pipeline.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintGraphColoring {
+    node_ids: HashMap<ColoringNode, usize>, // Workspace.
+    node_colors: Vec<Vec<u64>>,             // Workspace.
+    constraint_colors: Vec<(ColoredConstraint, usize)>, // Workspace.
+    constraints: Vec<ColoredConstraint>,
+    colors: Vec<usize>,
+}
+
+impl ConstraintGraphColoring {
+    /// Creates a new empty coloring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of colors of the last coloring.
+    pub fn num_colors(&self) -> usize {
+        self.colors.len().saturating_sub(1)
+    }
+
+    /// The constraints with the given color.
+    ///
+    /// Panics if `color >= self.num_colors()`.
+    pub fn color(&self, color: usize) -> &[ColoredConstraint] {
+        &self.constraints[self.colors[color]..self.colors[color + 1]]
+    }
+
+    /// Iterates through the constraints of each color, in increasing color order.
+    pub fn colors(&self) -> impl ExactSizeIterator<Item = &[ColoredConstraint]> {
+        (0..self.num_colors()).map(|color| self.color(color))
+    }
+
+    /// All the colored constraints, sorted by color.
+    pub fn constraints(&self) -> &[ColoredConstraint] {
+        &self.constraints
+    }
+
+    /// Colors the constraints the physics pipeline would solve at its next step: the contact
+    /// pairs with active contacts, and the enabled impulse joints, that involve at least one
+    /// awake dynamic rigid-body.
+    ///
+    /// The constraints of different islands never share a body, so this also colors each
+    /// active island independently.
+    pub fn color_active_constraints(
+        &mut self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        narrow_phase: &NarrowPhase,
+        impulse_joints: &ImpulseJointSet,
+        multibody_joints: &MultibodyJointSet,
+    ) {
+        let is_active = |handle: Option<RigidBodyHandle>| {
+            handle
+                .and_then(|h| bodies.get(h))
+                .map(|rb| rb.is_dynamic() && !rb.is_sleeping())
+                .unwrap_or(false)
+        };
+
+        let contacts = narrow_phase
+            .contact_pairs()
+            .filter(|pair| pair.has_any_active_contact)
+            .filter_map(|pair| {
+                let body1 = colliders.get(pair.collider1)?.parent();
+                let body2 = colliders.get(pair.collider2)?.parent();
+                (is_active(body1) || is_active(body2)).then_some((
+                    ColoredConstraint::ContactPair {
+                        collider1: pair.collider1,
+                        collider2: pair.collider2,
+                    },
+                    [body1, body2],
+                ))
+            });
+        let joints = impulse_joints
+            .iter()
+            .filter(|(_, joint)| joint.data.is_enabled())
+            .filter(|(_, joint)| is_active(Some(joint.body1)) || is_active(Some(joint.body2)))
+            .map(|(handle, joint)| {
+                (
+                    ColoredConstraint::ImpulseJoint(handle),
+                    [Some(joint.body1), Some(joint.body2)],
+                )
+            });
+
+        self.color_constraints(bodies, multibody_joints, contacts.chain(joints));
+    }
+
+    /// Colors the given constraints, each given with the rigid-bodies it involves.
+    ///
+    /// The constraints are kept in the given order within each color.
+    pub fn color_constraints(
+        &mut self,
+        bodies: &RigidBodySet,
+        multibody_joints: &MultibodyJointSet,
+        constraints: impl IntoIterator<Item = (ColoredConstraint, [Option<RigidBodyHandle>; 2])>,
+    ) {
+        self.node_ids.clear();
+        self.node_colors
+            .iter_mut()
+            .for_each(|colors| colors.clear());
+        self.constraint_colors.clear();
+        self.constraints.clear();
+        self.colors.clear();
+
+        let mut color_len: Vec<usize> = vec![];
+
+        for (constraint, constraint_bodies) in constraints {
+            let mut nodes = [None; 2];
+            for (node, body) in nodes.iter_mut().zip(constraint_bodies) {
+                *node = body.and_then(|h| self.node_id(bodies, multibody_joints, h));
+            }
+
+            // Find the first color used by none of the nodes.
+            let words1 = nodes[0].map_or(&[][..], |i| &self.node_colors[i][..]);
+            let words2 = nodes[1].map_or(&[][..], |i| &self.node_colors[i][..]);
+            let mut color = 0;
+            loop {
+                let word_id = color / 64;
+                let used = words1.get(word_id).copied().unwrap_or(0)
+                    | words2.get(word_id).copied().unwrap_or(0);
+                if used == u64::MAX {
+                    color += 64;
+                } else {
+                    color += (!used).trailing_zeros() as usize;
+                    break;
+                }
+            }
+
+            for node in nodes.into_iter().flatten() {
+                let node_colors = &mut self.node_colors[node];
+                if node_colors.len() <= color / 64 {
+                    node_colors.resize(color / 64 + 1, 0);
+                }
+                node_colors[color / 64] |= 1 << (color % 64);
+            }
+
+            if color_len.len() <= color {
+                color_len.resize(color + 1, 0);
+            }
+            color_len[color] += 1;
+            self.constraint_colors.push((constraint, color));
+        }
+
+        // Sort the constraints by color.
+        let mut offsets = Vec::with_capacity(color_len.len());
+        let mut last_offset = 0;
+        for len in &color_len {
+            self.colors.push(last_offset);
+            offsets.push(last_offset);
+            last_offset += len;
+        }
+        self.colors.push(last_offset);
+
+        if let Some((first, _)) = self.constraint_colors.first() {
+            self.constraints.resize(last_offset, *first);
+        }
+
+        for (constraint, color) in self.constraint_colors.drain(..) {
+            self.constraints[offsets[color]] = constraint;
+            offsets[color] += 1;
+        }
+    }
+
+    /// The index of the coloring node written by the constraints involving `body`, or `None`
+    /// if they don’t write to it.
+    fn node_id(
+        &mut self,
+        bodies: &RigidBodySet,
+        multibody_joints: &MultibodyJointSet,
+        body: RigidBodyHandle,
+    ) -> Option<usize> {
+        if !bodies.get(body)?.is_dynamic() {
+            return None;
+        }
+
+        let node = match multibody_joints.rigid_body_link(body) {
+            Some(link) => ColoringNode::Multibody(link.multibody),
+            None => ColoringNode::Body(body),
+        };
+        let num_nodes = self.node_ids.len();
+        let id = *self.node_ids.entry(node).or_insert(num_nodes);
+
+        if id == self.node_colors.len() {
+            self.node_colors.push(vec![]);
+        }
+
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColoredConstraint, ConstraintGraphColoring};
+    use crate::dynamics::{
+        FixedJointBuilder, ImpulseJointSet, MultibodyJointSet, RigidBodyBuilder, RigidBodySet,
+    };
+    use crate::geometry::{ColliderSet, NarrowPhase};
+
+    #[test]
+    fn chains_are_colored_with_two_colors() {
+        let mut bodies = RigidBodySet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let multibody_joints = MultibodyJointSet::new();
+
+        let ground = bodies.insert(RigidBodyBuilder::fixed());
+        let links: Vec<_> = (0..5)
+            .map(|_| bodies.insert(RigidBodyBuilder::dynamic()))
+            .collect();
+        impulse_joints.insert(ground, links[0], FixedJointBuilder::new(), true);
+        for pair in links.windows(2) {
+            impulse_joints.insert(pair[0], pair[1], FixedJointBuilder::new(), true);
+        }
+
+        let mut coloring = ConstraintGraphColoring::new();
+        coloring.color_active_constraints(
+            &bodies,
+            &ColliderSet::new(),
+            &NarrowPhase::new(),
+            &impulse_joints,
+            &multibody_joints,
+        );
+
+        assert_eq!(coloring.num_colors(), 2);
+        assert_eq!(coloring.constraints().len(), 5);
+
+        for color in coloring.colors() {
+            let mut used = vec![];
+            for constraint in color {
+                let ColoredConstraint::ImpulseJoint(handle) = constraint else {
+                    unreachable!()
+                };
+                let joint = &impulse_joints[*handle];
+                for body in [joint.body1, joint.body2] {
+                    if body != ground {
+                        assert!(!used.contains(&body));
+                        used.push(body);
+                    }
+                }
+            }
+        }
+    }
+}
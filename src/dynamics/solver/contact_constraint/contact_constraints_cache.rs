@@ -0,0 +1,414 @@
+use crate::dynamics::solver::contact_constraint::{
+    ContactConstraintsSet, OneBodyConstraint, TwoBodyConstraint,
+};
+#[cfg(feature = "simd-is-enabled")]
+use crate::dynamics::solver::contact_constraint::{OneBodyConstraintSimd, TwoBodyConstraintSimd};
+use crate::dynamics::{
+    ContactSoftness, IntegrationParameters, MultibodyJointSet, RigidBodyHandle, RigidBodySet,
+    RigidBodyVelocity,
+};
+#[cfg(feature = "dim3")]
+use crate::geometry::ContactAnisotropicFriction;
+use crate::geometry::{ContactManifold, ContactManifoldIndex, SolverContact};
+use crate::math::{AngularInertia, Isometry, Point, Real, Vector};
+
+/// The state of a rigid-body, as read by the contact constraints construction.
+#[derive(Copy, Clone, PartialEq)]
+struct BodyInputs {
+    handle: RigidBodyHandle,
+    solver_vel: usize,
+    is_multibody_link: bool,
+    position: Isometry<Real>,
+    vels: RigidBodyVelocity,
+    local_com: Point<Real>,
+    world_com: Point<Real>,
+    effective_inv_mass: Vector<Real>,
+    effective_world_inv_inertia_sqrt: AngularInertia<Real>,
+}
+
+impl BodyInputs {
+    fn new(
+        bodies: &RigidBodySet,
+        multibodies: &MultibodyJointSet,
+        handle: Option<RigidBodyHandle>,
+    ) -> Option<Self> {
+        let handle = handle?;
+        let rb = &bodies[handle];
+        Some(Self {
+            handle,
+            solver_vel: rb.ids.active_set_offset,
+            is_multibody_link: multibodies.rigid_body_link(handle).is_some(),
+            position: rb.pos.position,
+            vels: rb.vels,
+            local_com: rb.mprops.local_mprops.local_com,
+            world_com: rb.mprops.world_com,
+            effective_inv_mass: rb.mprops.effective_inv_mass,
+            effective_world_inv_inertia_sqrt: rb.mprops.effective_world_inv_inertia_sqrt,
+        })
+    }
+}
+
+/// The part of a contact manifold read by the contact constraints construction.
+#[derive(Copy, Clone, PartialEq)]
+struct ManifoldInputs {
+    index: ContactManifoldIndex,
+    bodies: [Option<BodyInputs>; 2],
+    normal: Vector<Real>,
+    relative_dominance: i16,
+    softness: ContactSoftness,
+    rolling_friction: Real,
+    #[cfg(feature = "dim3")]
+    twist_friction: Real,
+    #[cfg(feature = "dim3")]
+    anisotropic_friction: Option<ContactAnisotropicFriction>,
+    num_solver_contacts: usize,
+}
+
+/// Caches the contact constraints of an island so they can be reused by the next step if
+/// none of their inputs changed.
+///
+/// The contact constraints only depend on the island’s contact manifolds, on the integration
+/// parameters, and on the position, velocity, and mass properties of the bodies they involve. If all of these
+/// are bit-for-bit identical to those of the last step (which is typical of resting bodies kept
+/// awake, e.g., by a kinematic body touching them), regenerating the constraints would yield the
+/// exact same result. In that case, the constraints generated at the last step are restored instead.
+/// This is only used if [`IntegrationParameters::contact_constraints_caching`] is enabled.
+///
+/// Constraints involving multibodies are never cached since they rely on jacobians indexing
+/// the multibodies’ generalized coordinates.
+#[derive(Default)]
+pub(crate) struct ContactConstraintsCache {
+    valid: bool,
+    params: IntegrationParameters,
+    manifolds: Vec<ManifoldInputs>,
+    solver_contacts: Vec<SolverContact>,
+    velocity_constraints: Vec<TwoBodyConstraint>,
+    velocity_one_body_constraints: Vec<OneBodyConstraint>,
+    #[cfg(feature = "simd-is-enabled")]
+    simd_velocity_constraints: Vec<TwoBodyConstraintSimd>,
+    #[cfg(feature = "simd-is-enabled")]
+    simd_velocity_one_body_constraints: Vec<OneBodyConstraintSimd>,
+}
+
+impl ContactConstraintsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restores the cached constraints into `constraints` if the given manifolds and bodies
+    /// match exactly the inputs of the cached constraints.
+    ///
+    /// Returns `false` (and leaves `constraints` untouched) if the constraints need to be
+    /// regenerated.
+    pub fn try_restore(
+        &self,
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        multibodies: &MultibodyJointSet,
+        manifolds: &[&mut ContactManifold],
+        manifold_indices: &[ContactManifoldIndex],
+        constraints: &mut ContactConstraintsSet,
+    ) -> bool {
+        if !self.valid || self.params != *params || manifold_indices.len() != self.manifolds.len() {
+            return false;
+        }
+
+        let mut contacts = &self.solver_contacts[..];
+
+        for (cached, manifold_i) in self.manifolds.iter().zip(manifold_indices.iter()) {
+            let manifold = &manifolds[*manifold_i];
+            let num_solver_contacts = manifold.data.solver_contacts.len();
+
+            if num_solver_contacts > contacts.len()
+                || manifold.data.solver_contacts[..] != contacts[..num_solver_contacts]
+                || *cached
+                    != Self::manifold_inputs(params, bodies, multibodies, manifold, *manifold_i)
+            {
+                return false;
+            }
+
+            contacts = &contacts[num_solver_contacts..];
+        }
+
+        constraints
+            .velocity_constraints
+            .clone_from(&self.velocity_constraints);
+        constraints
+            .velocity_one_body_constraints
+            .clone_from(&self.velocity_one_body_constraints);
+        #[cfg(feature = "simd-is-enabled")]
+        {
+            constraints
+                .simd_velocity_constraints
+                .clone_from(&self.simd_velocity_constraints);
+            constraints
+                .simd_velocity_one_body_constraints
+                .clone_from(&self.simd_velocity_one_body_constraints);
+        }
+
+        true
+    }
+
+    /// Records the freshly generated `constraints` together with the inputs they were
+    /// generated from.
+    pub fn save(
+        &mut self,
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        multibodies: &MultibodyJointSet,
+        manifolds: &[&mut ContactManifold],
+        manifold_indices: &[ContactManifoldIndex],
+        constraints: &ContactConstraintsSet,
+    ) {
+        self.manifolds.clear();
+        self.solver_contacts.clear();
+        self.params = *params;
+        self.valid = constraints.generic_velocity_constraints.is_empty()
+            && constraints.generic_velocity_one_body_constraints.is_empty();
+
+        if !self.valid {
+            return;
+        }
+
+        for manifold_i in manifold_indices {
+            let manifold = &manifolds[*manifold_i];
+            self.manifolds.push(Self::manifold_inputs(
+                params,
+                bodies,
+                multibodies,
+                manifold,
+                *manifold_i,
+            ));
+            self.solver_contacts
+                .extend_from_slice(&manifold.data.solver_contacts);
+        }
+
+        self.velocity_constraints
+            .clone_from(&constraints.velocity_constraints);
+        self.velocity_one_body_constraints
+            .clone_from(&constraints.velocity_one_body_constraints);
+        #[cfg(feature = "simd-is-enabled")]
+        {
+            self.simd_velocity_constraints
+                .clone_from(&constraints.simd_velocity_constraints);
+            self.simd_velocity_one_body_constraints
+                .clone_from(&constraints.simd_velocity_one_body_constraints);
+        }
+    }
+
+    fn manifold_inputs(
+        params: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        multibodies: &MultibodyJointSet,
+        manifold: &ContactManifold,
+        index: ContactManifoldIndex,
+    ) -> ManifoldInputs {
+        ManifoldInputs {
+            index,
+            bodies: [
+                BodyInputs::new(bodies, multibodies, manifold.data.rigid_body1),
+                BodyInputs::new(bodies, multibodies, manifold.data.rigid_body2),
+            ],
+            normal: manifold.data.normal,
+            relative_dominance: manifold.data.relative_dominance,
+            softness: manifold
+                .data
+                .contact_softness
+                .unwrap_or_else(|| params.contact_softness()),
+            rolling_friction: manifold.data.rolling_friction,
+            #[cfg(feature = "dim3")]
+            twist_friction: manifold.data.twist_friction,
+            #[cfg(feature = "dim3")]
+            anisotropic_friction: manifold.data.anisotropic_friction,
+            num_solver_contacts: manifold.data.solver_contacts.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ContactConstraintsCache;
+    use crate::dynamics::solver::contact_constraint::ContactConstraintsSet;
+    use crate::dynamics::{
+        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        RigidBodyBuilder, RigidBodySet,
+    };
+    use crate::geometry::{
+        ColliderBuilder, ColliderHandle, ColliderSet, ContactManifold, DefaultBroadPhase,
+        NarrowPhase,
+    };
+    use crate::math::Vector;
+    use crate::pipeline::PhysicsPipeline;
+
+    // A ball resting on a fixed ground, after one step.
+    fn resting_ball() -> (
+        RigidBodySet,
+        NarrowPhase,
+        ColliderHandle,
+        ColliderHandle,
+        IntegrationParameters,
+    ) {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let params = IntegrationParameters::default();
+        let ground = colliders.insert(ColliderBuilder::ball(0.5));
+        let ball = bodies.insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.99));
+        let ball = colliders.insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut bodies);
+        let mut narrow_phase = NarrowPhase::new();
+
+        PhysicsPipeline::new().step(
+            &Vector::zeros(),
+            &params,
+            &mut IslandManager::new(),
+            &mut DefaultBroadPhase::new(),
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut ImpulseJointSet::new(),
+            &mut MultibodyJointSet::new(),
+            &mut CCDSolver::new(),
+            None,
+            &(),
+            &(),
+        );
+
+        (bodies, narrow_phase, ground, ball, params)
+    }
+
+    fn manifolds<'a>(
+        narrow_phase: &'a mut NarrowPhase,
+        ground: ColliderHandle,
+        ball: ColliderHandle,
+    ) -> Vec<&'a mut ContactManifold> {
+        let pair = narrow_phase.contact_pair_mut(ground, ball).unwrap();
+        assert!(pair.has_any_active_contact);
+        pair.manifolds.iter_mut().collect()
+    }
+
+    #[test]
+    fn cache_is_restored_for_identical_inputs() {
+        let (bodies, mut narrow_phase, ground, ball, params) = resting_ball();
+        let multibodies = MultibodyJointSet::new();
+        let manifolds = manifolds(&mut narrow_phase, ground, ball);
+        let mut constraints = ContactConstraintsSet::new();
+        let mut cache = ContactConstraintsCache::new();
+
+        assert!(!cache.try_restore(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &mut constraints
+        ));
+        cache.save(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &constraints,
+        );
+        assert!(cache.try_restore(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &mut constraints
+        ));
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_a_timestep_change() {
+        let (bodies, mut narrow_phase, ground, ball, mut params) = resting_ball();
+        let multibodies = MultibodyJointSet::new();
+        let manifolds = manifolds(&mut narrow_phase, ground, ball);
+        let mut constraints = ContactConstraintsSet::new();
+        let mut cache = ContactConstraintsCache::new();
+
+        cache.save(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &constraints,
+        );
+        params.dt /= 2.0;
+        assert!(!cache.try_restore(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &mut constraints
+        ));
+    }
+
+    #[test]
+    fn cache_is_invalidated_by_a_friction_change() {
+        let (bodies, mut narrow_phase, ground, ball, params) = resting_ball();
+        let multibodies = MultibodyJointSet::new();
+        let mut manifolds = manifolds(&mut narrow_phase, ground, ball);
+        let mut constraints = ContactConstraintsSet::new();
+        let mut cache = ContactConstraintsCache::new();
+
+        cache.save(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &constraints,
+        );
+        manifolds[0].data.rolling_friction = 0.5;
+        assert!(!cache.try_restore(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &mut constraints
+        ));
+
+        cache.save(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &constraints,
+        );
+        manifolds[0].data.solver_contacts[0].friction *= 2.0;
+        assert!(!cache.try_restore(
+            &params,
+            &bodies,
+            &multibodies,
+            &manifolds,
+            &[0],
+            &mut constraints
+        ));
+
+        #[cfg(feature = "dim3")]
+        {
+            cache.save(
+                &params,
+                &bodies,
+                &multibodies,
+                &manifolds,
+                &[0],
+                &constraints,
+            );
+            manifolds[0].data.twist_friction = 0.5;
+            assert!(!cache.try_restore(
+                &params,
+                &bodies,
+                &multibodies,
+                &manifolds,
+                &[0],
+                &mut constraints
+            ));
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use crate::dynamics::solver::contact_constraint::{ContactConstraintTypes, ContactConstraintsSet};
+use crate::dynamics::solver::{AnyConstraintMut, ConstraintIndex, SolverVel};
+use crate::math::Real;
+use na::DVector;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+/// The maximum number of colors supported by the coloring.
+const MAX_NUM_COLORS: usize = 128;
+
+/// A coloring of the contact constraints graph of an island, enabling their parallel resolution.
+///
+/// Constraints with the same color don’t share any body, so they can be solved concurrently
+/// without data races. The colors themselves are solved one after the other.
+///
+/// The constraints are identified by their index in the concatenation of the two-body,
+/// one-body, and (if SIMD is enabled) SIMD two-body and one-body constraint vectors of the
+/// `ContactConstraintsSet`.
+///
+/// Constraints involving multibodies are not supported since they all write to the
+/// generalized velocities vector.
+pub(crate) struct ContactConstraintsColoring {
+    body_colors: Vec<u128>,                 // Workspace.
+    constraint_colors: Vec<(usize, usize)>, // Workspace.
+    sorted_constraints: Vec<usize>,
+    colors: Vec<usize>,
+}
+
+impl ContactConstraintsColoring {
+    pub fn new() -> Self {
+        Self {
+            body_colors: Vec::new(),
+            constraint_colors: Vec::new(),
+            sorted_constraints: Vec::new(),
+            colors: Vec::new(),
+        }
+    }
+
+    /// The number of colors of the last successful coloring.
+    ///
+    /// This is zero if the coloring was cleared or if the last coloring failed.
+    pub fn num_colors(&self) -> usize {
+        self.colors.len().saturating_sub(1)
+    }
+
+    /// Discards the current coloring.
+    pub fn clear(&mut self) {
+        self.sorted_constraints.clear();
+        self.colors.clear();
+    }
+
+    /// Colors the given contact constraints, touching `num_solver_vels` solver velocities.
+    ///
+    /// If the constraints have a `solve_order`, each group of constraints with the same priority
+    /// gets colors of its own, placed after the colors of the lower priorities. This way, the
+    /// highest-priority constraints are still solved last.
+    ///
+    /// Returns `false` if the constraints can’t be colored, either because some of them
+    /// involve multibodies, or because more than 128 colors would be needed.
+    pub fn color(&mut self, num_solver_vels: usize, constraints: &ContactConstraintsSet) -> bool {
+        self.body_colors.clear();
+        self.constraint_colors.clear();
+        self.sorted_constraints.clear();
+        self.colors.clear();
+
+        if !constraints.generic_velocity_constraints.is_empty()
+            || !constraints.generic_velocity_one_body_constraints.is_empty()
+        {
+            return false;
+        }
+
+        self.body_colors.resize(num_solver_vels, 0);
+
+        let num_two_bodies = constraints.velocity_constraints.len();
+        let num_one_body = constraints.velocity_one_body_constraints.len();
+        #[cfg(feature = "simd-is-enabled")]
+        let num_simd_two_bodies = constraints.simd_velocity_constraints.len();
+
+        // Returns the identifier of the constraint, and pushes the solver velocities it involves.
+        let constraint_vels = |id: ConstraintIndex, vels: &mut Vec<usize>| -> usize {
+            vels.clear();
+            match id {
+                ConstraintIndex::TwoBodies(i) => {
+                    let c = &constraints.velocity_constraints[i];
+                    vels.extend([c.solver_vel1, c.solver_vel2]);
+                    i
+                }
+                ConstraintIndex::OneBody(i) => {
+                    vels.push(constraints.velocity_one_body_constraints[i].solver_vel2);
+                    num_two_bodies + i
+                }
+                #[cfg(feature = "simd-is-enabled")]
+                ConstraintIndex::SimdTwoBodies(i) => {
+                    let c = &constraints.simd_velocity_constraints[i];
+                    vels.extend_from_slice(&c.solver_vel1);
+                    vels.extend_from_slice(&c.solver_vel2);
+                    num_two_bodies + num_one_body + i
+                }
+                #[cfg(feature = "simd-is-enabled")]
+                ConstraintIndex::SimdOneBody(i) => {
+                    let c = &constraints.simd_velocity_one_body_constraints[i];
+                    vels.extend_from_slice(&c.solver_vel2);
+                    num_two_bodies + num_one_body + num_simd_two_bodies + i
+                }
+                ConstraintIndex::GenericTwoBodies(_) | ConstraintIndex::GenericOneBody(_) => {
+                    unreachable!("Generic constraints are never colored.")
+                }
+            }
+        };
+
+        let default_order: Vec<_>;
+        let default_groups: [usize; 1];
+        let (order, groups) = if constraints.solve_order.is_empty() {
+            let order = (0..num_two_bodies)
+                .map(ConstraintIndex::TwoBodies)
+                .chain((0..num_one_body).map(ConstraintIndex::OneBody));
+            #[cfg(feature = "simd-is-enabled")]
+            let order = order
+                .chain((0..num_simd_two_bodies).map(ConstraintIndex::SimdTwoBodies))
+                .chain(
+                    (0..constraints.simd_velocity_one_body_constraints.len())
+                        .map(ConstraintIndex::SimdOneBody),
+                );
+            default_order = order.collect();
+            default_groups = [default_order.len()];
+            (&default_order[..], &default_groups[..])
+        } else {
+            (
+                &constraints.solve_order[..],
+                &constraints.solve_order_groups[..],
+            )
+        };
+
+        let mut color_len = [0; MAX_NUM_COLORS];
+        let mut vels = vec![];
+        // The first color available to the current group of constraints.
+        let mut first_color = 0;
+        let mut group_start = 0;
+
+        for group_end in groups {
+            if first_color != 0 {
+                self.body_colors.fill(0);
+            }
+
+            let mut next_first_color = first_color;
+
+            for id in &order[group_start..*group_end] {
+                let id = constraint_vels(*id, &mut vels);
+                let mut used = if first_color == 0 {
+                    0
+                } else {
+                    u128::MAX >> (MAX_NUM_COLORS - first_color)
+                };
+
+                for i in &vels {
+                    used |= self.body_colors[*i];
+                }
+
+                if used == u128::MAX {
+                    return false;
+                }
+
+                let color = (!used).trailing_zeros() as usize;
+                for i in &vels {
+                    self.body_colors[*i] |= 1 << color;
+                }
+
+                color_len[color] += 1;
+                self.constraint_colors.push((id, color));
+                next_first_color = next_first_color.max(color + 1);
+            }
+
+            first_color = next_first_color;
+            group_start = *group_end;
+        }
+
+        let mut sort_offsets = [0; MAX_NUM_COLORS];
+        let mut last_offset = 0;
+
+        for i in 0..MAX_NUM_COLORS {
+            if color_len[i] != 0 {
+                self.colors.push(last_offset);
+                sort_offsets[i] = last_offset;
+                last_offset += color_len[i];
+            }
+        }
+
+        self.colors.push(last_offset);
+        self.sorted_constraints.resize(last_offset, 0);
+
+        for (id, color) in self.constraint_colors.drain(..) {
+            self.sorted_constraints[sort_offsets[color]] = id;
+            sort_offsets[color] += 1;
+        }
+
+        true
+    }
+
+    /// Applies `solve` to all the colored constraints, in parallel.
+    ///
+    /// The colors are processed one after the other, and each task processes at least
+    /// `min_task_size` constraints of the same color.
+    pub fn solve(
+        &self,
+        constraints: &mut ContactConstraintsSet,
+        solver_vels: &mut [SolverVel<Real>],
+        min_task_size: usize,
+        solve: impl Fn(
+                &mut AnyConstraintMut<ContactConstraintTypes>,
+                &DVector<Real>,
+                &mut [SolverVel<Real>],
+                &mut DVector<Real>,
+            ) + Sync,
+    ) {
+        let jac = &constraints.generic_jacobians;
+
+        // Borrow each constraint mutably once, in the order of the colors, so each color
+        // is a contiguous range of disjoint mutable borrows.
+        let mut unsorted: Vec<Option<AnyConstraintMut<ContactConstraintTypes>>> = constraints
+            .velocity_constraints
+            .iter_mut()
+            .map(AnyConstraintMut::TwoBodies)
+            .chain(
+                constraints
+                    .velocity_one_body_constraints
+                    .iter_mut()
+                    .map(AnyConstraintMut::OneBody),
+            )
+            .map(Some)
+            .collect();
+        #[cfg(feature = "simd-is-enabled")]
+        unsorted.extend(
+            constraints
+                .simd_velocity_constraints
+                .iter_mut()
+                .map(AnyConstraintMut::SimdTwoBodies)
+                .chain(
+                    constraints
+                        .simd_velocity_one_body_constraints
+                        .iter_mut()
+                        .map(AnyConstraintMut::SimdOneBody),
+                )
+                .map(Some),
+        );
+        let mut sorted: Vec<_> = self
+            .sorted_constraints
+            .iter()
+            .map(|id| {
+                unsorted[*id]
+                    .take()
+                    .expect("Each constraint is colored once.")
+            })
+            .collect();
+
+        let num_solver_vels = solver_vels.len();
+        let solver_vels = &AtomicPtr::new(solver_vels.as_mut_ptr());
+
+        for color in 0..self.num_colors() {
+            let range = self.colors[color]..self.colors[color + 1];
+
+            sorted[range]
+                .par_iter_mut()
+                .with_min_len(min_task_size.max(1))
+                .for_each(|constraint| {
+                    // SAFETY: constraints of the same color don’t share any solver velocity, so
+                    //         the tasks of this color never access the same solver velocities.
+                    let solver_vels: &mut [SolverVel<Real>] = unsafe {
+                        std::slice::from_raw_parts_mut(
+                            solver_vels.load(Ordering::Relaxed),
+                            num_solver_vels,
+                        )
+                    };
+                    // NOTE: this is never accessed since there are no multibody constraints.
+                    let mut generic_solver_vels = DVector::zeros(0);
+                    solve(constraint, jac, solver_vels, &mut generic_solver_vels);
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ContactConstraintsColoring;
+    use crate::dynamics::solver::contact_constraint::{ContactConstraintsSet, TwoBodyConstraint};
+    use crate::dynamics::solver::{AnyConstraintMut, ConstraintIndex, SolverVel};
+    use crate::dynamics::{
+        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+    };
+    use crate::geometry::{ColliderBuilder, ColliderSet, DefaultBroadPhase, NarrowPhase};
+    use crate::math::{Real, Vector};
+    use crate::pipeline::PhysicsPipeline;
+    use std::sync::Mutex;
+
+    // Simulates three stacks of boxes, and returns the final positions of the boxes.
+    fn simulate_stacks(params: &IntegrationParameters) -> Vec<(RigidBodyHandle, Vector<Real>)> {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        #[cfg(feature = "dim2")]
+        let (ground, cube) = (
+            ColliderBuilder::cuboid(10.0, 0.5),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (ground, cube) = (
+            ColliderBuilder::cuboid(10.0, 0.5, 10.0),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let _ = colliders.insert(ground.translation(-Vector::y() * 0.5));
+
+        for i in 0..3 {
+            for j in 0..4 {
+                let handle = bodies.insert(RigidBodyBuilder::dynamic().translation(
+                    Vector::x() * (i as Real * 2.0) + Vector::y() * (j as Real + 0.5),
+                ));
+                let _ = colliders.insert_with_parent(cube.clone(), handle, &mut bodies);
+            }
+        }
+
+        let mut pipeline = PhysicsPipeline::new();
+        let mut islands = IslandManager::new();
+        let mut broad_phase = DefaultBroadPhase::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut ccd_solver = CCDSolver::new();
+
+        for _ in 0..200 {
+            pipeline.step(
+                &(Vector::y() * -9.81),
+                params,
+                &mut islands,
+                &mut broad_phase,
+                &mut narrow_phase,
+                &mut bodies,
+                &mut colliders,
+                &mut impulse_joints,
+                &mut multibody_joints,
+                &mut ccd_solver,
+                None,
+                &(),
+                &(),
+            );
+        }
+
+        bodies
+            .iter()
+            .map(|(handle, rb)| (handle, *rb.translation()))
+            .collect()
+    }
+
+    #[test]
+    fn colored_constraints_match_sequential_constraints() {
+        let colored = simulate_stacks(&IntegrationParameters {
+            parallel_island_split_size: 1,
+            ..IntegrationParameters::default()
+        });
+        let sequential = simulate_stacks(&IntegrationParameters {
+            parallel_island_split_size: usize::MAX,
+            ..IntegrationParameters::default()
+        });
+
+        for ((handle, colored_pos), (_, sequential_pos)) in colored.iter().zip(sequential.iter()) {
+            // The constraints aren’t solved in the same order, but both stacks must be at rest
+            // at the same place.
+            assert!(
+                (colored_pos - sequential_pos).norm() < 1.0e-2,
+                "body {handle:?}: {colored_pos} != {sequential_pos}"
+            );
+        }
+    }
+
+    // The first solver velocity of each constraint, in the order they are solved.
+    fn colored_solve_order(constraints: &mut ContactConstraintsSet) -> Vec<usize> {
+        let mut coloring = ContactConstraintsColoring::new();
+        assert!(coloring.color(5, constraints));
+
+        let solved = Mutex::new(vec![]);
+        coloring.solve(
+            constraints,
+            &mut [SolverVel::zero(); 5],
+            1,
+            |constraint, _, _, _| {
+                if let AnyConstraintMut::TwoBodies(c) = constraint {
+                    solved.lock().unwrap().push(c.solver_vel1);
+                }
+            },
+        );
+        solved.into_inner().unwrap()
+    }
+
+    #[test]
+    fn colors_follow_the_contact_priorities() {
+        let mut constraints = ContactConstraintsSet::new();
+        for (solver_vel1, solver_vel2) in [(0, 1), (1, 2), (3, 4)] {
+            let mut constraint = TwoBodyConstraint::invalid();
+            constraint.solver_vel1 = solver_vel1;
+            constraint.solver_vel2 = solver_vel2;
+            constraints.velocity_constraints.push(constraint);
+        }
+
+        // Without priorities, the first and last constraints share the first color.
+        let order = colored_solve_order(&mut constraints);
+        assert_eq!(order[2], 1);
+
+        // The first constraint has the highest priority, so its color is solved last.
+        constraints.solve_order = vec![
+            ConstraintIndex::TwoBodies(1),
+            ConstraintIndex::TwoBodies(2),
+            ConstraintIndex::TwoBodies(0),
+        ];
+        constraints.solve_order_groups = vec![2, 3];
+        let order = colored_solve_order(&mut constraints);
+        assert_eq!(order[2], 0);
+    }
+}
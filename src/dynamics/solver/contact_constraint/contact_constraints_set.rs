@@ -4,9 +4,11 @@ use crate::dynamics::solver::contact_constraint::{
     GenericTwoBodyConstraintBuilder, OneBodyConstraint, OneBodyConstraintBuilder,
     TwoBodyConstraint, TwoBodyConstraintBuilder,
 };
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::solver_vel::SolverVel;
-use crate::dynamics::solver::{reset_buffer, ConstraintTypes, SolverConstraintsSet};
+use crate::dynamics::solver::{
+    reset_buffer, ConstraintIndex, ConstraintTypes, SolverConstraintsSet,
+};
 use crate::dynamics::{
     ImpulseJoint, IntegrationParameters, IslandManager, JointAxesMask, MultibodyJointSet,
     RigidBodySet,
@@ -143,6 +145,7 @@ impl ContactConstraintsSet {
 
     pub fn init(
         &mut self,
+        params: &IntegrationParameters,
         island_id: usize,
         islands: &IslandManager,
         bodies: &RigidBodySet,
@@ -166,17 +169,24 @@ impl ContactConstraintsSet {
 
         #[cfg(feature = "simd-is-enabled")]
         {
-            self.simd_compute_constraints(bodies, manifolds);
+            self.simd_compute_constraints(params, bodies, manifolds);
         }
-        self.compute_constraints(bodies, manifolds);
-        self.compute_generic_constraints(bodies, multibody_joints, manifolds, &mut jacobian_id);
+        self.compute_constraints(params, bodies, manifolds);
+        self.compute_generic_constraints(
+            params,
+            bodies,
+            multibody_joints,
+            manifolds,
+            &mut jacobian_id,
+        );
 
         #[cfg(feature = "simd-is-enabled")]
         {
-            self.simd_compute_one_body_constraints(bodies, manifolds);
+            self.simd_compute_one_body_constraints(params, bodies, manifolds);
         }
-        self.compute_one_body_constraints(bodies, manifolds);
+        self.compute_one_body_constraints(params, bodies, manifolds);
         self.compute_generic_one_body_constraints(
+            params,
             bodies,
             multibody_joints,
             manifolds,
@@ -187,6 +197,7 @@ impl ContactConstraintsSet {
     #[cfg(feature = "simd-is-enabled")]
     fn simd_compute_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         manifolds_all: &[&mut ContactManifold],
     ) {
@@ -218,6 +229,7 @@ impl ContactConstraintsSet {
             let manifolds = gather![|ii| &*manifolds_all[manifolds_i[ii]]];
 
             TwoBodyConstraintBuilderSimd::generate(
+                params,
                 manifold_id,
                 manifolds,
                 bodies,
@@ -233,6 +245,7 @@ impl ContactConstraintsSet {
 
     fn compute_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         manifolds_all: &[&mut ContactManifold],
     ) {
@@ -258,6 +271,7 @@ impl ContactConstraintsSet {
             let num_to_add = ConstraintsCounts::from_contacts(manifold).num_constraints;
 
             TwoBodyConstraintBuilder::generate(
+                params,
                 *manifold_i,
                 manifold,
                 bodies,
@@ -272,6 +286,7 @@ impl ContactConstraintsSet {
 
     fn compute_generic_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         multibody_joints: &MultibodyJointSet,
         manifolds_all: &[&mut ContactManifold],
@@ -297,6 +312,7 @@ impl ContactConstraintsSet {
             let num_to_add = ConstraintsCounts::from_contacts(manifold).num_constraints;
 
             GenericTwoBodyConstraintBuilder::generate(
+                params,
                 *manifold_i,
                 manifold,
                 bodies,
@@ -315,6 +331,7 @@ impl ContactConstraintsSet {
 
     fn compute_generic_one_body_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         multibody_joints: &MultibodyJointSet,
         manifolds_all: &[&mut ContactManifold],
@@ -339,6 +356,7 @@ impl ContactConstraintsSet {
             let num_to_add = ConstraintsCounts::from_contacts(manifold).num_constraints;
 
             GenericOneBodyConstraintBuilder::generate(
+                params,
                 *manifold_i,
                 manifold,
                 bodies,
@@ -357,6 +375,7 @@ impl ContactConstraintsSet {
     #[cfg(feature = "simd-is-enabled")]
     fn simd_compute_one_body_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         manifolds_all: &[&mut ContactManifold],
     ) {
@@ -390,6 +409,7 @@ impl ContactConstraintsSet {
             let manifold_id = gather![|ii| manifolds_i[ii]];
             let manifolds = gather![|ii| &*manifolds_all[manifolds_i[ii]]];
             SimdOneBodyConstraintBuilder::generate(
+                params,
                 manifold_id,
                 manifolds,
                 bodies,
@@ -403,6 +423,7 @@ impl ContactConstraintsSet {
 
     fn compute_one_body_constraints(
         &mut self,
+        params: &IntegrationParameters,
         bodies: &RigidBodySet,
         manifolds_all: &[&mut ContactManifold],
     ) {
@@ -431,6 +452,7 @@ impl ContactConstraintsSet {
             let num_to_add = ConstraintsCounts::from_contacts(manifold).num_constraints;
 
             OneBodyConstraintBuilder::generate(
+                params,
                 *manifold_i,
                 manifold,
                 bodies,
@@ -443,15 +465,103 @@ impl ContactConstraintsSet {
         assert_eq!(curr_start, total_num_constraints);
     }
 
+    /// Sorts the constraints by increasing priority of their contact manifolds, so the
+    /// highest-priority contacts are solved last, and the most accurately.
+    ///
+    /// The constraints are solved in their default order if all the manifolds have the same
+    /// priority.
+    pub fn update_solve_order(
+        &mut self,
+        manifolds: &[&mut ContactManifold],
+        manifold_indices: &[ContactManifoldIndex],
+    ) {
+        self.solve_order.clear();
+        self.solve_order_groups.clear();
+
+        let mut priorities = manifold_indices.iter().map(|i| manifolds[*i].data.priority);
+        let Some(first_priority) = priorities.next() else {
+            return;
+        };
+
+        if priorities.all(|priority| priority == first_priority) {
+            return;
+        }
+
+        let priority = |manifold_id: ContactManifoldIndex| manifolds[manifold_id].data.priority;
+        let mut order: Vec<(i8, ConstraintIndex)> = vec![];
+
+        order.extend(
+            self.velocity_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (priority(c.manifold_id), ConstraintIndex::TwoBodies(i))),
+        );
+        order.extend(
+            self.generic_velocity_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    (
+                        priority(c.inner.manifold_id),
+                        ConstraintIndex::GenericTwoBodies(i),
+                    )
+                }),
+        );
+        #[cfg(feature = "simd-is-enabled")]
+        order.extend(
+            self.simd_velocity_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let max_priority = c.manifold_id.iter().map(|id| priority(*id)).max();
+                    (max_priority.unwrap_or(0), ConstraintIndex::SimdTwoBodies(i))
+                }),
+        );
+        order.extend(
+            self.velocity_one_body_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (priority(c.manifold_id), ConstraintIndex::OneBody(i))),
+        );
+        order.extend(
+            self.generic_velocity_one_body_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    (
+                        priority(c.inner.manifold_id),
+                        ConstraintIndex::GenericOneBody(i),
+                    )
+                }),
+        );
+        #[cfg(feature = "simd-is-enabled")]
+        order.extend(
+            self.simd_velocity_one_body_constraints
+                .iter()
+                .enumerate()
+                .map(|(i, c)| {
+                    let max_priority = c.manifold_id.iter().map(|id| priority(*id)).max();
+                    (max_priority.unwrap_or(0), ConstraintIndex::SimdOneBody(i))
+                }),
+        );
+
+        // NOTE: the sort is stable so the default order is kept between constraints with the
+        //       same priority.
+        order.sort_by_key(|(priority, _)| *priority);
+        self.solve_order_groups
+            .extend((1..order.len()).filter(|i| order[*i].0 != order[*i - 1].0));
+        self.solve_order_groups.push(order.len());
+        self.solve_order.extend(order.into_iter().map(|(_, id)| id));
+    }
+
     pub fn warmstart(
         &mut self,
         solver_vels: &mut [SolverVel<Real>],
         generic_solver_vels: &mut DVector<Real>,
     ) {
-        let (jac, constraints) = self.iter_constraints_mut();
-        for mut c in constraints {
+        self.for_each_constraint_mut(|jac, mut c| {
             c.warmstart(jac, solver_vels, generic_solver_vels);
-        }
+        });
     }
 
     #[profiling::function]
@@ -459,11 +569,11 @@ impl ContactConstraintsSet {
         &mut self,
         solver_vels: &mut [SolverVel<Real>],
         generic_solver_vels: &mut DVector<Real>,
+        block_solver: bool,
     ) {
-        let (jac, constraints) = self.iter_constraints_mut();
-        for mut c in constraints {
-            c.solve_restitution(jac, solver_vels, generic_solver_vels);
-        }
+        self.for_each_constraint_mut(|jac, mut c| {
+            c.solve_restitution(jac, solver_vels, generic_solver_vels, block_solver);
+        });
     }
 
     #[profiling::function]
@@ -471,12 +581,12 @@ impl ContactConstraintsSet {
         &mut self,
         solver_vels: &mut [SolverVel<Real>],
         generic_solver_vels: &mut DVector<Real>,
+        block_solver: bool,
     ) {
-        let (jac, constraints) = self.iter_constraints_mut();
-        for mut c in constraints {
+        self.for_each_constraint_mut(|jac, mut c| {
             c.remove_bias();
-            c.solve_restitution(jac, solver_vels, generic_solver_vels);
-        }
+            c.solve_restitution(jac, solver_vels, generic_solver_vels, block_solver);
+        });
     }
 
     #[profiling::function]
@@ -485,9 +595,39 @@ impl ContactConstraintsSet {
         solver_vels: &mut [SolverVel<Real>],
         generic_solver_vels: &mut DVector<Real>,
     ) {
-        let (jac, constraints) = self.iter_constraints_mut();
-        for mut c in constraints {
+        self.for_each_constraint_mut(|jac, mut c| {
             c.solve_friction(jac, solver_vels, generic_solver_vels);
+        });
+    }
+
+    #[profiling::function]
+    pub fn solve_coupled(
+        &mut self,
+        solver_vels: &mut [SolverVel<Real>],
+        generic_solver_vels: &mut DVector<Real>,
+    ) {
+        self.for_each_constraint_mut(|jac, mut c| {
+            c.solve_coupled(jac, solver_vels, generic_solver_vels);
+        });
+    }
+
+    #[profiling::function]
+    pub fn solve_coupled_wo_bias(
+        &mut self,
+        solver_vels: &mut [SolverVel<Real>],
+        generic_solver_vels: &mut DVector<Real>,
+    ) {
+        self.for_each_constraint_mut(|jac, mut c| {
+            c.remove_bias();
+            c.solve_coupled(jac, solver_vels, generic_solver_vels);
+        });
+    }
+
+    /// Keeps only the penetration bias of the constraints, to solve them on pseudo-velocities.
+    pub fn keep_only_position_bias(&mut self) {
+        let (_, constraints) = self.iter_constraints_mut();
+        for mut c in constraints {
+            c.keep_only_position_bias();
         }
     }
 
@@ -498,13 +638,66 @@ impl ContactConstraintsSet {
         }
     }
 
+    /// Writes the current impulses of all the contact constraints into `out`.
+    ///
+    /// The impulses are always written in the same order, so the outputs of two calls can be
+    /// compared element-wise as long as the constraints aren’t regenerated in-between.
+    pub fn gather_impulses(&self, out: &mut Vec<Real>) {
+        out.clear();
+
+        macro_rules! gather_impulses(
+            ($constraint: expr) => {{
+                let c = $constraint;
+                for elt in &c.elements[..c.num_contacts as usize] {
+                    out.push(elt.normal_part.impulse);
+                    out.extend(elt.tangent_part.impulse.iter().copied());
+                }
+                out.extend(c.angular_friction.impulse.iter().copied());
+            }}
+        );
+
+        for c in &self.velocity_constraints {
+            gather_impulses!(c);
+        }
+        for c in &self.velocity_one_body_constraints {
+            gather_impulses!(c);
+        }
+        for c in &self.generic_velocity_constraints {
+            gather_impulses!(&c.inner);
+        }
+        for c in &self.generic_velocity_one_body_constraints {
+            gather_impulses!(&c.inner);
+        }
+
+        #[cfg(feature = "simd-is-enabled")]
+        {
+            macro_rules! gather_simd_impulses(
+                ($constraints: expr) => {
+                    for c in $constraints {
+                        for elt in &c.elements[..c.num_contacts as usize] {
+                            let impulses: [Real; SIMD_WIDTH] = elt.normal_part.impulse.into();
+                            out.extend(impulses);
+                            for impulse in elt.tangent_part.impulse.iter() {
+                                let impulses: [Real; SIMD_WIDTH] = (*impulse).into();
+                                out.extend(impulses);
+                            }
+                        }
+                    }
+                }
+            );
+
+            gather_simd_impulses!(&self.simd_velocity_constraints);
+            gather_simd_impulses!(&self.simd_velocity_one_body_constraints);
+        }
+    }
+
     #[profiling::function]
     pub fn update(
         &mut self,
         params: &IntegrationParameters,
         small_step_id: usize,
         multibodies: &MultibodyJointSet,
-        solver_bodies: &[SolverBody],
+        solver_bodies: &SolverBodies,
     ) {
         macro_rules! update_contacts(
             ($builders: ident, $constraints: ident) => {
@@ -6,8 +6,8 @@ use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::math::{Point, Real, DIM, MAX_MANIFOLD_POINTS};
 use crate::utils::SimdCross;
 
-use super::{OneBodyConstraintElement, OneBodyConstraintNormalPart};
-use crate::dynamics::solver::solver_body::SolverBody;
+use super::{ContactAngularFrictionPart, OneBodyConstraintElement, OneBodyConstraintNormalPart};
+use crate::dynamics::solver::solver_body::{SolverBodies, SolverBody};
 use crate::dynamics::solver::{ContactPointInfos, OneBodyConstraintBuilder};
 #[cfg(feature = "dim2")]
 use crate::utils::SimdBasis;
@@ -30,6 +30,7 @@ impl GenericOneBodyConstraintBuilder {
     }
 
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: ContactManifoldIndex,
         manifold: &ContactManifold,
         bodies: &RigidBodySet,
@@ -97,6 +98,10 @@ impl GenericOneBodyConstraintBuilder {
 
             builder.inner.rb1 = rb1;
             builder.inner.vels1 = vels1;
+            builder.inner.softness = manifold
+                .data
+                .contact_softness
+                .unwrap_or_else(|| params.contact_softness());
 
             constraint.inner.dir1 = force_dir1;
             constraint.inner.im2 = mprops2.effective_inv_mass;
@@ -106,7 +111,10 @@ impl GenericOneBodyConstraintBuilder {
             #[cfg(feature = "dim3")]
             {
                 constraint.inner.tangent1 = tangents1[0];
+                constraint.inner.friction_scale = na::Vector2::repeat(1.0);
             }
+            // The rolling, twist, and anisotropic friction aren’t supported by multibody contacts.
+            constraint.inner.angular_friction = ContactAngularFrictionPart::zero();
 
             for k in 0..manifold_points.len() {
                 let manifold_point = &manifold_points[k];
@@ -137,7 +145,8 @@ impl GenericOneBodyConstraintBuilder {
                         )
                         .0;
 
-                    let r = crate::utils::inv(inv_r2);
+                    let compliance = params.contact_compliance_gain(&builder.inner.softness);
+                    let r = crate::utils::inv(inv_r2 + compliance);
 
                     let is_bouncy = manifold_point.is_bouncy() as u32 as Real;
 
@@ -155,6 +164,7 @@ impl GenericOneBodyConstraintBuilder {
                         impulse: na::zero(),
                         impulse_accumulator: na::zero(),
                         r,
+                        compliance,
                         r_mat_elts: [0.0; 2],
                     };
                 }
@@ -221,7 +231,7 @@ impl GenericOneBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        _solver_bodies: &[SolverBody],
+        _solver_bodies: &SolverBodies,
         multibodies: &MultibodyJointSet,
         constraint: &mut GenericOneBodyConstraint,
     ) {
@@ -304,4 +314,8 @@ impl GenericOneBodyConstraint {
     pub fn remove_cfm_and_bias_from_rhs(&mut self) {
         self.inner.remove_cfm_and_bias_from_rhs();
     }
+
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.inner.keep_only_position_bias_in_rhs();
+    }
 }
@@ -134,7 +134,8 @@ impl OneBodyConstraintNormalPart<Real> {
         let dvel = jacobians
             .rows(j_id2, ndofs2)
             .dot(&solver_vels.rows(solver_vel2, ndofs2))
-            + self.rhs;
+            + self.rhs
+            + self.compliance * self.impulse;
 
         let new_impulse = cfm_factor * (self.impulse - self.r * dvel).max(0.0);
         let dlambda = new_impulse - self.impulse;
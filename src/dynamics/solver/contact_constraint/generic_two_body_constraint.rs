@@ -4,8 +4,11 @@ use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::math::{Real, DIM, MAX_MANIFOLD_POINTS};
 use crate::utils::{SimdAngularInertia, SimdCross, SimdDot};
 
-use super::{TwoBodyConstraintBuilder, TwoBodyConstraintElement, TwoBodyConstraintNormalPart};
-use crate::dynamics::solver::solver_body::SolverBody;
+use super::{
+    ContactAngularFrictionPart, TwoBodyConstraintBuilder, TwoBodyConstraintElement,
+    TwoBodyConstraintNormalPart,
+};
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::{ContactPointInfos, SolverVel};
 use crate::prelude::RigidBodyHandle;
 #[cfg(feature = "dim2")]
@@ -31,6 +34,7 @@ impl GenericTwoBodyConstraintBuilder {
     }
 
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: ContactManifoldIndex,
         manifold: &ContactManifold,
         bodies: &RigidBodySet,
@@ -98,6 +102,10 @@ impl GenericTwoBodyConstraintBuilder {
 
             let builder = &mut out_builders[l];
             let constraint = &mut out_constraints[l];
+            builder.inner.softness = manifold
+                .data
+                .contact_softness
+                .unwrap_or_else(|| params.contact_softness());
             constraint.inner.dir1 = force_dir1;
             constraint.inner.im1 = if type1.is_dynamic() {
                 mprops1.effective_inv_mass
@@ -116,7 +124,10 @@ impl GenericTwoBodyConstraintBuilder {
             #[cfg(feature = "dim3")]
             {
                 constraint.inner.tangent1 = tangents1[0];
+                constraint.inner.friction_scale = na::Vector2::repeat(1.0);
             }
+            // The rolling, twist, and anisotropic friction aren’t supported by multibody contacts.
+            constraint.inner.angular_friction = ContactAngularFrictionPart::zero();
 
             for k in 0..manifold_points.len() {
                 let manifold_point = &manifold_points[k];
@@ -189,7 +200,8 @@ impl GenericTwoBodyConstraintBuilder {
                         0.0
                     };
 
-                    let r = crate::utils::inv(inv_r1 + inv_r2);
+                    let compliance = params.contact_compliance_gain(&builder.inner.softness);
+                    let r = crate::utils::inv(inv_r1 + inv_r2 + compliance);
 
                     let is_bouncy = manifold_point.is_bouncy() as u32 as Real;
 
@@ -204,6 +216,7 @@ impl GenericTwoBodyConstraintBuilder {
                         impulse_accumulator: na::zero(),
                         impulse: manifold_point.warmstart_impulse,
                         r,
+                        compliance,
                         r_mat_elts: [0.0; 2],
                     };
                 }
@@ -328,7 +341,7 @@ impl GenericTwoBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         multibodies: &MultibodyJointSet,
         constraint: &mut GenericTwoBodyConstraint,
     ) {
@@ -336,11 +349,11 @@ impl GenericTwoBodyConstraintBuilder {
         let pos1 = multibodies
             .rigid_body_link(self.handle1)
             .map(|m| &multibodies[m.multibody].link(m.id).unwrap().local_to_world)
-            .unwrap_or_else(|| &bodies[constraint.inner.solver_vel1].position);
+            .unwrap_or_else(|| &bodies.positions[constraint.inner.solver_vel1]);
         let pos2 = multibodies
             .rigid_body_link(self.handle2)
             .map(|m| &multibodies[m.multibody].link(m.id).unwrap().local_to_world)
-            .unwrap_or_else(|| &bodies[constraint.inner.solver_vel2].position);
+            .unwrap_or_else(|| &bodies.positions[constraint.inner.solver_vel2]);
 
         self.inner
             .update_with_positions(params, solved_dt, pos1, pos2, &mut constraint.inner);
@@ -470,4 +483,8 @@ impl GenericTwoBodyConstraint {
     pub fn remove_cfm_and_bias_from_rhs(&mut self) {
         self.inner.remove_cfm_and_bias_from_rhs();
     }
+
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.inner.keep_only_position_bias_in_rhs();
+    }
 }
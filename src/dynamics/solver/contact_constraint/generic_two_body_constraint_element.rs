@@ -388,7 +388,8 @@ impl TwoBodyConstraintNormalPart<Real> {
 
         let dvel = solver_vel1.dvel(j_id1, ndofs1, jacobians, dir1, &self.gcross1, solver_vels)
             + solver_vel2.dvel(j_id2, ndofs2, jacobians, &-dir1, &self.gcross2, solver_vels)
-            + self.rhs;
+            + self.rhs
+            + self.compliance * self.impulse;
 
         let new_impulse = cfm_factor * (self.impulse - self.r * dvel).max(0.0);
         let dlambda = new_impulse - self.impulse;
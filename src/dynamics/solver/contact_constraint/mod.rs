@@ -1,5 +1,8 @@
 pub(crate) use generic_one_body_constraint::*;
 // pub(crate) use generic_one_body_constraint_element::*;
+pub(crate) use contact_constraints_cache::ContactConstraintsCache;
+#[cfg(feature = "parallel")]
+pub(crate) use contact_constraints_coloring::ContactConstraintsColoring;
 pub(crate) use contact_constraints_set::{
     ConstraintsCounts, ContactConstraintTypes, ContactConstraintsSet,
 };
@@ -14,6 +17,9 @@ pub(crate) use two_body_constraint_element::*;
 #[cfg(feature = "simd-is-enabled")]
 pub(crate) use two_body_constraint_simd::*;
 
+mod contact_constraints_cache;
+#[cfg(feature = "parallel")]
+mod contact_constraints_coloring;
 mod contact_constraints_set;
 mod generic_one_body_constraint;
 mod generic_one_body_constraint_element;
@@ -1,15 +1,16 @@
-use super::{OneBodyConstraintElement, OneBodyConstraintNormalPart};
-use crate::math::{Point, Real, Vector, DIM, MAX_MANIFOLD_POINTS};
+use super::{ContactAngularFrictionPart, OneBodyConstraintElement, OneBodyConstraintNormalPart};
+use crate::math::{Point, Real, Vector, ANG_DIM, DIM, MAX_MANIFOLD_POINTS};
 #[cfg(feature = "dim2")]
 use crate::utils::SimdBasis;
 use crate::utils::{self, SimdAngularInertia, SimdCross, SimdDot, SimdRealCopy};
-use na::Matrix2;
+use na::{Matrix2, Vector2};
 use parry::math::Isometry;
 
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::{SolverBodies, SolverBody};
 use crate::dynamics::solver::SolverVel;
-use crate::dynamics::{IntegrationParameters, MultibodyJointSet, RigidBodySet, RigidBodyVelocity};
+use crate::dynamics::{
+    ContactSoftness, IntegrationParameters, MultibodyJointSet, RigidBodySet, RigidBodyVelocity,
+};
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
 
 // TODO: move this struct somewhere else.
@@ -40,6 +41,7 @@ pub(crate) struct OneBodyConstraintBuilder {
     pub rb1: SolverBody,
     pub vels1: RigidBodyVelocity,
     pub infos: [ContactPointInfos<Real>; MAX_MANIFOLD_POINTS],
+    pub softness: ContactSoftness,
 }
 
 impl OneBodyConstraintBuilder {
@@ -48,10 +50,12 @@ impl OneBodyConstraintBuilder {
             rb1: SolverBody::default(),
             vels1: RigidBodyVelocity::zero(),
             infos: [ContactPointInfos::default(); MAX_MANIFOLD_POINTS],
+            softness: ContactSoftness::default(),
         }
     }
 
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: ContactManifoldIndex,
         manifold: &ContactManifold,
         bodies: &RigidBodySet,
@@ -87,8 +91,12 @@ impl OneBodyConstraintBuilder {
         #[cfg(feature = "dim2")]
         let tangents1 = force_dir1.orthonormal_basis();
         #[cfg(feature = "dim3")]
-        let tangents1 =
-            super::compute_tangent_contact_directions(&force_dir1, &vels1.linvel, &vels2.linvel);
+        let (tangents1, friction_scale) = super::compute_friction_directions(
+            &force_dir1,
+            &vels1.linvel,
+            &vels2.linvel,
+            manifold.data.anisotropic_friction.as_ref(),
+        );
 
         let solver_vel2 = rb2.ids.active_set_offset;
 
@@ -102,7 +110,12 @@ impl OneBodyConstraintBuilder {
             let constraint = &mut out_constraints[l];
 
             builder.rb1 = rb1;
+            builder.softness = manifold
+                .data
+                .contact_softness
+                .unwrap_or_else(|| params.contact_softness());
             builder.vels1 = vels1;
+            let compliance = params.contact_compliance_gain(&builder.softness);
 
             constraint.dir1 = force_dir1;
             constraint.im2 = mprops2.effective_inv_mass;
@@ -112,8 +125,25 @@ impl OneBodyConstraintBuilder {
             #[cfg(feature = "dim3")]
             {
                 constraint.tangent1 = tangents1[0];
+                constraint.friction_scale = friction_scale;
             }
 
+            // The angular friction is only applied once per manifold.
+            constraint.angular_friction = if l == 0 {
+                ContactAngularFrictionPart::new(
+                    &manifold.data,
+                    #[cfg(feature = "dim3")]
+                    &force_dir1,
+                    #[cfg(feature = "dim3")]
+                    &tangents1[0],
+                    None,
+                    &vels1.angvel,
+                    &mprops2.effective_world_inv_inertia_sqrt,
+                )
+            } else {
+                ContactAngularFrictionPart::zero()
+            };
+
             for k in 0..manifold_points.len() {
                 let manifold_point = &manifold_points[k];
 
@@ -136,7 +166,8 @@ impl OneBodyConstraintBuilder {
                         force_dir1.dot(&mprops2.effective_inv_mass.component_mul(&force_dir1));
                     let projected_ang_mass = gcross2.gdot(gcross2);
 
-                    let projected_mass = utils::inv(projected_lin_mass + projected_ang_mass);
+                    let projected_mass =
+                        utils::inv(projected_lin_mass + projected_ang_mass + compliance);
 
                     let is_bouncy = manifold_point.is_bouncy() as u32 as Real;
 
@@ -154,6 +185,7 @@ impl OneBodyConstraintBuilder {
                         impulse: manifold_point.warmstart_impulse,
                         impulse_accumulator: na::zero(),
                         r: projected_mass,
+                        compliance,
                         r_mat_elts: [0.0; 2],
                     };
                 }
@@ -211,7 +243,7 @@ impl OneBodyConstraintBuilder {
                 }
             }
 
-            if BLOCK_SOLVER_ENABLED {
+            if params.contact_block_solver {
                 // Coupling between consecutive pairs.
                 for k in 0..manifold_points.len() / 2 {
                     let k0 = k * 2;
@@ -255,12 +287,12 @@ impl OneBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         _multibodies: &MultibodyJointSet,
         constraint: &mut OneBodyConstraint,
     ) {
-        let rb2 = &bodies[constraint.solver_vel2];
-        self.update_with_positions(params, solved_dt, &rb2.position, constraint)
+        let pos2 = &bodies.positions[constraint.solver_vel2];
+        self.update_with_positions(params, solved_dt, pos2, constraint)
     }
 
     // TODO: this code is SOOOO similar to TwoBodyConstraint::update.
@@ -272,10 +304,12 @@ impl OneBodyConstraintBuilder {
         rb2_pos: &Isometry<Real>,
         constraint: &mut OneBodyConstraint,
     ) {
-        let cfm_factor = params.contact_cfm_factor();
+        let (cfm_factor, erp_inv_dt) = params.contact_cfm_factor_and_erp_inv_dt(&self.softness);
+        let compliance = params.contact_compliance_gain(&self.softness);
         let inv_dt = params.inv_dt();
-        let erp_inv_dt = params.contact_erp_inv_dt();
 
+        // The warmstart impulses aren’t part of this step’s impulses yet at the first substep.
+        let accumulate_impulses = solved_dt != 0.0;
         let all_infos = &self.infos[..constraint.num_contacts as usize];
         let all_elements = &mut constraint.elements[..constraint.num_contacts as usize];
         let rb1 = &self.rb1;
@@ -307,13 +341,18 @@ impl OneBodyConstraintBuilder {
 
                 element.normal_part.rhs_wo_bias = rhs_wo_bias;
                 element.normal_part.rhs = new_rhs;
-                element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                element.normal_part.compliance = compliance;
+                if accumulate_impulses {
+                    element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                }
                 element.normal_part.impulse *= params.warmstart_coefficient;
             }
 
             // Tangent part.
             {
-                element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                if accumulate_impulses {
+                    element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                }
                 element.tangent_part.impulse *= params.warmstart_coefficient;
 
                 for j in 0..DIM - 1 {
@@ -323,6 +362,8 @@ impl OneBodyConstraintBuilder {
             }
         }
 
+        // The angular friction isn’t warmstarted.
+        constraint.angular_friction.impulse = [0.0; ANG_DIM];
         constraint.cfm_factor = cfm_factor;
     }
 }
@@ -333,10 +374,13 @@ pub(crate) struct OneBodyConstraint {
     pub dir1: Vector<Real>, // Non-penetration force direction for the first body.
     #[cfg(feature = "dim3")]
     pub tangent1: Vector<Real>, // One of the friction force directions.
+    #[cfg(feature = "dim3")]
+    pub friction_scale: Vector2<Real>, // The anisotropic friction scale along each tangent.
     pub im2: Vector<Real>,
     pub cfm_factor: Real,
     pub limit: Real,
     pub elements: [OneBodyConstraintElement<Real>; MAX_MANIFOLD_POINTS],
+    pub angular_friction: ContactAngularFrictionPart,
 
     pub manifold_id: ContactManifoldIndex,
     pub manifold_contact_id: [u8; MAX_MANIFOLD_POINTS],
@@ -350,10 +394,13 @@ impl OneBodyConstraint {
             dir1: Vector::zeros(),
             #[cfg(feature = "dim3")]
             tangent1: Vector::zeros(),
+            #[cfg(feature = "dim3")]
+            friction_scale: Vector2::repeat(1.0),
             im2: Vector::zeros(),
             cfm_factor: 0.0,
             limit: 0.0,
             elements: [OneBodyConstraintElement::zero(); MAX_MANIFOLD_POINTS],
+            angular_friction: ContactAngularFrictionPart::zero(),
             manifold_id: ContactManifoldIndex::MAX,
             manifold_contact_id: [u8::MAX; MAX_MANIFOLD_POINTS],
             num_contacts: u8::MAX,
@@ -380,6 +427,7 @@ impl OneBodyConstraint {
         solver_vels: &mut [SolverVel<Real>],
         solve_normal: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) {
         let mut solver_vel2 = solver_vels[self.solver_vel2];
 
@@ -391,11 +439,49 @@ impl OneBodyConstraint {
             &self.tangent1,
             &self.im2,
             self.limit,
+            #[cfg(feature = "dim3")]
+            Some(&self.friction_scale),
             &mut solver_vel2,
             solve_normal,
             solve_friction,
+            block_solver,
+        );
+
+        if solve_friction {
+            let normal_impulse = self.elements[..self.num_contacts as usize]
+                .iter()
+                .map(|e| e.normal_part.impulse)
+                .sum();
+            self.angular_friction
+                .solve(normal_impulse, None, &mut solver_vel2);
+        }
+
+        solver_vels[self.solver_vel2] = solver_vel2;
+    }
+
+    pub fn solve_coupled(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        let mut solver_vel2 = solver_vels[self.solver_vel2];
+
+        OneBodyConstraintElement::solve_group_coupled(
+            self.cfm_factor,
+            &mut self.elements[..self.num_contacts as usize],
+            &self.dir1,
+            #[cfg(feature = "dim3")]
+            &self.tangent1,
+            &self.im2,
+            self.limit,
+            #[cfg(feature = "dim3")]
+            Some(&self.friction_scale),
+            &mut solver_vel2,
         );
 
+        let normal_impulse = self.elements[..self.num_contacts as usize]
+            .iter()
+            .map(|e| e.normal_part.impulse)
+            .sum();
+        self.angular_friction
+            .solve(normal_impulse, None, &mut solver_vel2);
+
         solver_vels[self.solver_vel2] = solver_vel2;
     }
 
@@ -418,7 +504,19 @@ impl OneBodyConstraint {
         self.cfm_factor = 1.0;
         for elt in &mut self.elements {
             elt.normal_part.rhs = elt.normal_part.rhs_wo_bias;
+            elt.normal_part.compliance = 0.0;
             elt.tangent_part.rhs = elt.tangent_part.rhs_wo_bias;
         }
     }
+
+    /// Prepares this constraint for a position correction pass by keeping only the penetration
+    /// bias in its right-hand side, and resetting its impulses.
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.cfm_factor = 1.0;
+        for elt in &mut self.elements {
+            elt.normal_part.rhs -= elt.normal_part.rhs_wo_bias;
+            elt.normal_part.impulse = 0.0;
+            elt.normal_part.compliance = 0.0;
+        }
+    }
 }
@@ -1,5 +1,8 @@
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
-use crate::dynamics::solver::contact_constraint::TwoBodyConstraintNormalPart;
+#[cfg(feature = "dim3")]
+use crate::dynamics::solver::contact_constraint::cap_friction_impulse;
+use crate::dynamics::solver::contact_constraint::{
+    solve_coupled_contact_point, TwoBodyConstraintNormalPart,
+};
 use crate::dynamics::solver::SolverVel;
 use crate::math::{AngVector, TangentImpulse, Vector, DIM};
 use crate::utils::{SimdBasis, SimdDot, SimdRealCopy};
@@ -67,6 +70,7 @@ impl<N: SimdRealCopy> OneBodyConstraintTangentPart<N> {
         tangents1: [&Vector<N>; DIM - 1],
         im2: &Vector<N>,
         limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
         solver_vel2: &mut SolverVel<N>,
     ) where
         AngVector<N>: SimdDot<AngVector<N>, Result = N>,
@@ -106,7 +110,7 @@ impl<N: SimdRealCopy> OneBodyConstraintTangentPart<N> {
                 let _disable_fe_except =
                     crate::utils::DisableFloatingPointExceptionsFlags::
                     disable_floating_point_exceptions();
-                new_impulse.simd_cap_magnitude(limit)
+                cap_friction_impulse(new_impulse, limit, friction_scale)
             };
             let dlambda = new_impulse - self.impulse;
             self.impulse = new_impulse;
@@ -126,6 +130,8 @@ pub(crate) struct OneBodyConstraintNormalPart<N: SimdRealCopy> {
     pub impulse: N,
     pub impulse_accumulator: N,
     pub r: N,
+    // The velocity error per unit of impulse of compliant contacts. `r` already accounts for it.
+    pub compliance: N,
     pub r_mat_elts: [N; 2],
 }
 
@@ -138,6 +144,7 @@ impl<N: SimdRealCopy> OneBodyConstraintNormalPart<N> {
             impulse: na::zero(),
             impulse_accumulator: na::zero(),
             r: na::zero(),
+            compliance: na::zero(),
             r_mat_elts: [N::zero(); 2],
         }
     }
@@ -164,8 +171,10 @@ impl<N: SimdRealCopy> OneBodyConstraintNormalPart<N> {
     ) where
         AngVector<N>: SimdDot<AngVector<N>, Result = N>,
     {
-        let dvel =
-            -dir1.dot(&solver_vel2.linear) + self.gcross2.gdot(solver_vel2.angular) + self.rhs;
+        let dvel = -dir1.dot(&solver_vel2.linear)
+            + self.gcross2.gdot(solver_vel2.angular)
+            + self.rhs
+            + self.compliance * self.impulse;
         let new_impulse = cfm_factor * (self.impulse - self.r * dvel).simd_max(N::zero());
         let dlambda = new_impulse - self.impulse;
         self.impulse = new_impulse;
@@ -187,10 +196,12 @@ impl<N: SimdRealCopy> OneBodyConstraintNormalPart<N> {
     {
         let dvel_a = -dir1.dot(&solver_vel2.linear)
             + constraint_a.gcross2.gdot(solver_vel2.angular)
-            + constraint_a.rhs;
+            + constraint_a.rhs
+            + constraint_a.compliance * constraint_a.impulse;
         let dvel_b = -dir1.dot(&solver_vel2.linear)
             + constraint_b.gcross2.gdot(solver_vel2.angular)
-            + constraint_b.rhs;
+            + constraint_b.rhs
+            + constraint_b.compliance * constraint_b.impulse;
 
         let prev_impulse = Vector2::new(constraint_a.impulse, constraint_b.impulse);
         let new_impulse = TwoBodyConstraintNormalPart::solve_mlcp_two_constraints(
@@ -254,9 +265,11 @@ impl<N: SimdRealCopy> OneBodyConstraintElement<N> {
         #[cfg(feature = "dim3")] tangent1: &Vector<N>,
         im2: &Vector<N>,
         limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
         solver_vel2: &mut SolverVel<N>,
         solve_normal: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) where
         Vector<N>: SimdBasis,
         AngVector<N>: SimdDot<AngVector<N>, Result = N>,
@@ -268,7 +281,7 @@ impl<N: SimdRealCopy> OneBodyConstraintElement<N> {
 
         // Solve penetration.
         if solve_normal {
-            if BLOCK_SOLVER_ENABLED {
+            if block_solver {
                 for elements in elements.chunks_exact_mut(2) {
                     let [element_a, element_b] = elements else {
                         unreachable!()
@@ -304,7 +317,89 @@ impl<N: SimdRealCopy> OneBodyConstraintElement<N> {
             for element in elements.iter_mut() {
                 let limit = limit * element.normal_part.impulse;
                 let part = &mut element.tangent_part;
-                part.solve(tangents1, im2, limit, solver_vel2);
+                part.solve(
+                    tangents1,
+                    im2,
+                    limit,
+                    #[cfg(feature = "dim3")]
+                    friction_scale,
+                    solver_vel2,
+                );
+            }
+        }
+    }
+
+    /// Solves the normal and friction impulses of each contact point together.
+    ///
+    /// See [`solve_coupled_contact_point`].
+    #[inline]
+    pub fn solve_group_coupled(
+        cfm_factor: N,
+        elements: &mut [Self],
+        dir1: &Vector<N>,
+        #[cfg(feature = "dim3")] tangent1: &Vector<N>,
+        im2: &Vector<N>,
+        limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
+        solver_vel2: &mut SolverVel<N>,
+    ) where
+        Vector<N>: SimdBasis,
+        AngVector<N>: SimdDot<AngVector<N>, Result = N>,
+    {
+        #[cfg(feature = "dim3")]
+        let dirs = [*dir1, *tangent1, dir1.cross(tangent1)];
+        #[cfg(feature = "dim2")]
+        let dirs = [*dir1, dir1.orthonormal_vector()];
+
+        for element in elements.iter_mut() {
+            let normal = &mut element.normal_part;
+            let tangent = &mut element.tangent_part;
+
+            #[cfg(feature = "dim3")]
+            let (gcross2, rhs) = (
+                [normal.gcross2, tangent.gcross2[0], tangent.gcross2[1]],
+                [normal.rhs, tangent.rhs[0], tangent.rhs[1]],
+            );
+            #[cfg(feature = "dim2")]
+            let (gcross2, rhs) = (
+                [normal.gcross2, tangent.gcross2[0]],
+                [normal.rhs, tangent.rhs[0]],
+            );
+
+            let mut w: [[N; DIM]; DIM] = std::array::from_fn(|j| {
+                std::array::from_fn(|k| {
+                    dirs[j].dot(&dirs[k].component_mul(im2)) + gcross2[j].gdot(gcross2[k])
+                })
+            });
+            w[0][0] += normal.compliance;
+            let mut dvel: [N; DIM] = std::array::from_fn(|j| {
+                -dirs[j].dot(&solver_vel2.linear) + gcross2[j].gdot(solver_vel2.angular) + rhs[j]
+            });
+            dvel[0] += normal.compliance * normal.impulse;
+
+            let (new_normal, new_tangent) = solve_coupled_contact_point(
+                w,
+                dvel,
+                normal.impulse,
+                tangent.impulse,
+                cfm_factor,
+                limit,
+                #[cfg(feature = "dim3")]
+                friction_scale,
+            );
+            let dnormal = new_normal - normal.impulse;
+            let dtangent = new_tangent - tangent.impulse;
+            normal.impulse = new_normal;
+            tangent.impulse = new_tangent;
+
+            #[cfg(feature = "dim3")]
+            let dlambdas = [dnormal, dtangent[0], dtangent[1]];
+            #[cfg(feature = "dim2")]
+            let dlambdas = [dnormal, dtangent[0]];
+
+            for (j, dlambda) in dlambdas.into_iter().enumerate() {
+                solver_vel2.linear += dirs[j].component_mul(im2) * -dlambda;
+                solver_vel2.angular += gcross2[j] * dlambda;
             }
         }
     }
@@ -1,10 +1,9 @@
 use super::{OneBodyConstraintElement, OneBodyConstraintNormalPart};
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::{SolverBodies, SolverBody};
 use crate::dynamics::solver::{ContactPointInfos, SolverVel};
 use crate::dynamics::{
-    IntegrationParameters, MultibodyJointSet, RigidBodyIds, RigidBodyMassProps, RigidBodySet,
-    RigidBodyVelocity,
+    ContactSoftness, IntegrationParameters, MultibodyJointSet, RigidBodyIds, RigidBodyMassProps,
+    RigidBodySet, RigidBodyVelocity,
 };
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::math::{
@@ -24,10 +23,12 @@ pub(crate) struct SimdOneBodyConstraintBuilder {
     rb1: [SolverBody; SIMD_WIDTH],
     vels1: [RigidBodyVelocity; SIMD_WIDTH],
     infos: [ContactPointInfos<SimdReal>; MAX_MANIFOLD_POINTS],
+    softness: [ContactSoftness; SIMD_WIDTH],
 }
 
 impl SimdOneBodyConstraintBuilder {
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: [ContactManifoldIndex; SIMD_WIDTH],
         manifolds: [&ContactManifold; SIMD_WIDTH],
         bodies: &RigidBodySet,
@@ -93,6 +94,13 @@ impl SimdOneBodyConstraintBuilder {
         #[cfg(feature = "dim3")]
         let tangents1 = super::compute_tangent_contact_directions(&force_dir1, &linvel1, &linvel2);
 
+        let softness = gather![|ii| manifolds[ii]
+            .data
+            .contact_softness
+            .unwrap_or_else(|| params.contact_softness())];
+        let compliance =
+            SimdReal::from(gather![|ii| params.contact_compliance_gain(&softness[ii])]);
+
         for l in (0..num_active_contacts).step_by(MAX_MANIFOLD_POINTS) {
             let manifold_points = gather![|ii| &manifolds[ii].data.solver_contacts[l..]];
             let num_points = manifold_points[0].len().min(MAX_MANIFOLD_POINTS);
@@ -102,6 +110,7 @@ impl SimdOneBodyConstraintBuilder {
 
             builder.rb1 = rb1;
             builder.vels1 = vels1;
+            builder.softness = softness;
 
             constraint.dir1 = force_dir1;
             constraint.im2 = im2;
@@ -146,7 +155,9 @@ impl SimdOneBodyConstraintBuilder {
                     let gcross2 = ii2.transform_vector(dp2.gcross(-force_dir1));
 
                     let projected_mass = utils::simd_inv(
-                        force_dir1.dot(&im2.component_mul(&force_dir1)) + gcross2.gdot(gcross2),
+                        force_dir1.dot(&im2.component_mul(&force_dir1))
+                            + gcross2.gdot(gcross2)
+                            + compliance,
                     );
 
                     let projected_vel1 = vel1.dot(&force_dir1);
@@ -162,6 +173,7 @@ impl SimdOneBodyConstraintBuilder {
                         impulse: warmstart_impulse,
                         impulse_accumulator: na::zero(),
                         r: projected_mass,
+                        compliance,
                         r_mat_elts: [SimdReal::zero(); 2],
                     };
                 }
@@ -208,7 +220,7 @@ impl SimdOneBodyConstraintBuilder {
                 }
             }
 
-            if BLOCK_SOLVER_ENABLED {
+            if params.contact_block_solver {
                 // Coupling between consecutive pairs.
                 for k in 0..num_points / 2 {
                     let k0 = k * 2;
@@ -257,19 +269,23 @@ impl SimdOneBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         _multibodies: &MultibodyJointSet,
         constraint: &mut OneBodyConstraintSimd,
     ) {
-        let cfm_factor = SimdReal::splat(params.contact_cfm_factor());
+        let cfm_and_erp =
+            gather![|ii| params.contact_cfm_factor_and_erp_inv_dt(&self.softness[ii])];
+        let cfm_factor = SimdReal::from(gather![|ii| cfm_and_erp[ii].0]);
         let inv_dt = SimdReal::splat(params.inv_dt());
         let allowed_lin_err = SimdReal::splat(params.allowed_linear_error());
-        let erp_inv_dt = SimdReal::splat(params.contact_erp_inv_dt());
+        let erp_inv_dt = SimdReal::from(gather![|ii| cfm_and_erp[ii].1]);
+        let compliance = SimdReal::from(gather![
+            |ii| params.contact_compliance_gain(&self.softness[ii])
+        ]);
         let max_corrective_velocity = SimdReal::splat(params.max_corrective_velocity());
         let warmstart_coeff = SimdReal::splat(params.warmstart_coefficient);
 
-        let rb2 = gather![|ii| &bodies[constraint.solver_vel2[ii]]];
-        let poss2 = Isometry::from(gather![|ii| rb2[ii].position]);
+        let poss2 = Isometry::from(gather![|ii| bodies.positions[constraint.solver_vel2[ii]]]);
 
         let all_infos = &self.infos[..constraint.num_contacts as usize];
         let all_elements = &mut constraint.elements[..constraint.num_contacts as usize];
@@ -289,6 +305,8 @@ impl SimdOneBodyConstraintBuilder {
             constraint.dir1.cross(&constraint.tangent1),
         ];
 
+        // The warmstart impulses aren’t part of this step’s impulses yet at the first substep.
+        let accumulate_impulses = solved_dt != 0.0;
         let solved_dt = SimdReal::splat(solved_dt);
 
         for (info, element) in all_infos.iter().zip(all_elements.iter_mut()) {
@@ -307,13 +325,18 @@ impl SimdOneBodyConstraintBuilder {
 
                 element.normal_part.rhs_wo_bias = rhs_wo_bias;
                 element.normal_part.rhs = new_rhs;
-                element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                element.normal_part.compliance = compliance;
+                if accumulate_impulses {
+                    element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                }
                 element.normal_part.impulse *= warmstart_coeff;
             }
 
             // tangent parts.
             {
-                element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                if accumulate_impulses {
+                    element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                }
                 element.tangent_part.impulse *= warmstart_coeff;
 
                 for j in 0..DIM - 1 {
@@ -369,6 +392,7 @@ impl OneBodyConstraintSimd {
         solver_vels: &mut [SolverVel<Real>],
         solve_normal: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) {
         let mut solver_vel2 = SolverVel {
             linear: Vector::from(gather![|ii| solver_vels[self.solver_vel2[ii]].linear]),
@@ -383,9 +407,37 @@ impl OneBodyConstraintSimd {
             &self.tangent1,
             &self.im2,
             self.limit,
+            #[cfg(feature = "dim3")]
+            None,
             &mut solver_vel2,
             solve_normal,
             solve_friction,
+            block_solver,
+        );
+
+        for ii in 0..SIMD_WIDTH {
+            solver_vels[self.solver_vel2[ii]].linear = solver_vel2.linear.extract(ii);
+            solver_vels[self.solver_vel2[ii]].angular = solver_vel2.angular.extract(ii);
+        }
+    }
+
+    pub fn solve_coupled(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        let mut solver_vel2 = SolverVel {
+            linear: Vector::from(gather![|ii| solver_vels[self.solver_vel2[ii]].linear]),
+            angular: AngVector::from(gather![|ii| solver_vels[self.solver_vel2[ii]].angular]),
+        };
+
+        OneBodyConstraintElement::solve_group_coupled(
+            self.cfm_factor,
+            &mut self.elements[..self.num_contacts as usize],
+            &self.dir1,
+            #[cfg(feature = "dim3")]
+            &self.tangent1,
+            &self.im2,
+            self.limit,
+            #[cfg(feature = "dim3")]
+            None,
+            &mut solver_vel2,
         );
 
         for ii in 0..SIMD_WIDTH {
@@ -420,7 +472,19 @@ impl OneBodyConstraintSimd {
         self.cfm_factor = SimdReal::splat(1.0);
         for elt in &mut self.elements {
             elt.normal_part.rhs = elt.normal_part.rhs_wo_bias;
+            elt.normal_part.compliance = SimdReal::zero();
             elt.tangent_part.rhs = elt.tangent_part.rhs_wo_bias;
         }
     }
+
+    /// Prepares this constraint for a position correction pass by keeping only the penetration
+    /// bias in its right-hand side, and resetting its impulses.
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.cfm_factor = SimdReal::splat(1.0);
+        for elt in &mut self.elements {
+            elt.normal_part.rhs -= elt.normal_part.rhs_wo_bias;
+            elt.normal_part.impulse = SimdReal::zero();
+            elt.normal_part.compliance = SimdReal::zero();
+        }
+    }
 }
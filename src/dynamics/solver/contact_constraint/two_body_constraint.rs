@@ -1,15 +1,16 @@
 use super::{ContactConstraintTypes, ContactPointInfos};
 use crate::dynamics::solver::SolverVel;
-use crate::dynamics::solver::{AnyConstraintMut, SolverBody};
+use crate::dynamics::solver::{AnyConstraintMut, SolverBodies};
 
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
-use crate::dynamics::{IntegrationParameters, MultibodyJointSet, RigidBodySet};
+use crate::dynamics::{ContactSoftness, IntegrationParameters, MultibodyJointSet, RigidBodySet};
+#[cfg(feature = "dim3")]
+use crate::geometry::ContactAnisotropicFriction;
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
-use crate::math::{Isometry, Real, Vector, DIM, MAX_MANIFOLD_POINTS};
+use crate::math::{Isometry, Real, Vector, ANG_DIM, DIM, MAX_MANIFOLD_POINTS};
 use crate::utils::{self, SimdAngularInertia, SimdBasis, SimdCross, SimdDot};
-use na::{DVector, Matrix2};
+use na::{DVector, Matrix2, Vector2};
 
-use super::{TwoBodyConstraintElement, TwoBodyConstraintNormalPart};
+use super::{ContactAngularFrictionPart, TwoBodyConstraintElement, TwoBodyConstraintNormalPart};
 
 impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
     pub fn remove_bias(&mut self) {
@@ -24,6 +25,18 @@ impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
             Self::SimdTwoBodies(c) => c.remove_cfm_and_bias_from_rhs(),
         }
     }
+    pub fn keep_only_position_bias(&mut self) {
+        match self {
+            Self::OneBody(c) => c.keep_only_position_bias_in_rhs(),
+            Self::TwoBodies(c) => c.keep_only_position_bias_in_rhs(),
+            Self::GenericOneBody(c) => c.keep_only_position_bias_in_rhs(),
+            Self::GenericTwoBodies(c) => c.keep_only_position_bias_in_rhs(),
+            #[cfg(feature = "simd-is-enabled")]
+            Self::SimdOneBody(c) => c.keep_only_position_bias_in_rhs(),
+            #[cfg(feature = "simd-is-enabled")]
+            Self::SimdTwoBodies(c) => c.keep_only_position_bias_in_rhs(),
+        }
+    }
     pub fn warmstart(
         &mut self,
         generic_jacobians: &DVector<Real>,
@@ -49,10 +62,11 @@ impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
         generic_jacobians: &DVector<Real>,
         solver_vels: &mut [SolverVel<Real>],
         generic_solver_vels: &mut DVector<Real>,
+        block_solver: bool,
     ) {
         match self {
-            Self::OneBody(c) => c.solve(solver_vels, true, false),
-            Self::TwoBodies(c) => c.solve(solver_vels, true, false),
+            Self::OneBody(c) => c.solve(solver_vels, true, false, block_solver),
+            Self::TwoBodies(c) => c.solve(solver_vels, true, false, block_solver),
             Self::GenericOneBody(c) => c.solve(generic_jacobians, generic_solver_vels, true, false),
             Self::GenericTwoBodies(c) => c.solve(
                 generic_jacobians,
@@ -62,9 +76,9 @@ impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
                 false,
             ),
             #[cfg(feature = "simd-is-enabled")]
-            Self::SimdOneBody(c) => c.solve(solver_vels, true, false),
+            Self::SimdOneBody(c) => c.solve(solver_vels, true, false, block_solver),
             #[cfg(feature = "simd-is-enabled")]
-            Self::SimdTwoBodies(c) => c.solve(solver_vels, true, false),
+            Self::SimdTwoBodies(c) => c.solve(solver_vels, true, false, block_solver),
         }
     }
 
@@ -75,8 +89,8 @@ impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
         generic_solver_vels: &mut DVector<Real>,
     ) {
         match self {
-            Self::OneBody(c) => c.solve(solver_vels, false, true),
-            Self::TwoBodies(c) => c.solve(solver_vels, false, true),
+            Self::OneBody(c) => c.solve(solver_vels, false, true, false),
+            Self::TwoBodies(c) => c.solve(solver_vels, false, true, false),
             Self::GenericOneBody(c) => c.solve(generic_jacobians, generic_solver_vels, false, true),
             Self::GenericTwoBodies(c) => c.solve(
                 generic_jacobians,
@@ -86,9 +100,37 @@ impl<'a> AnyConstraintMut<'a, ContactConstraintTypes> {
                 true,
             ),
             #[cfg(feature = "simd-is-enabled")]
-            Self::SimdOneBody(c) => c.solve(solver_vels, false, true),
+            Self::SimdOneBody(c) => c.solve(solver_vels, false, true, false),
             #[cfg(feature = "simd-is-enabled")]
-            Self::SimdTwoBodies(c) => c.solve(solver_vels, false, true),
+            Self::SimdTwoBodies(c) => c.solve(solver_vels, false, true, false),
+        }
+    }
+
+    /// Solves the normal and friction impulses of the contact points together, for
+    /// [`ContactFrictionMode::Coupled`](crate::dynamics::ContactFrictionMode::Coupled).
+    ///
+    /// The generic constraints solve their normal impulses first, then their friction.
+    pub fn solve_coupled(
+        &mut self,
+        generic_jacobians: &DVector<Real>,
+        solver_vels: &mut [SolverVel<Real>],
+        generic_solver_vels: &mut DVector<Real>,
+    ) {
+        match self {
+            Self::OneBody(c) => c.solve_coupled(solver_vels),
+            Self::TwoBodies(c) => c.solve_coupled(solver_vels),
+            Self::GenericOneBody(c) => c.solve(generic_jacobians, generic_solver_vels, true, true),
+            Self::GenericTwoBodies(c) => c.solve(
+                generic_jacobians,
+                solver_vels,
+                generic_solver_vels,
+                true,
+                true,
+            ),
+            #[cfg(feature = "simd-is-enabled")]
+            Self::SimdOneBody(c) => c.solve_coupled(solver_vels),
+            #[cfg(feature = "simd-is-enabled")]
+            Self::SimdTwoBodies(c) => c.solve_coupled(solver_vels),
         }
     }
 
@@ -111,6 +153,8 @@ pub(crate) struct TwoBodyConstraint {
     pub dir1: Vector<Real>, // Non-penetration force direction for the first body.
     #[cfg(feature = "dim3")]
     pub tangent1: Vector<Real>, // One of the friction force directions.
+    #[cfg(feature = "dim3")]
+    pub friction_scale: Vector2<Real>, // The anisotropic friction scale along each tangent.
     pub im1: Vector<Real>,
     pub im2: Vector<Real>,
     pub cfm_factor: Real,
@@ -121,6 +165,7 @@ pub(crate) struct TwoBodyConstraint {
     pub manifold_contact_id: [u8; MAX_MANIFOLD_POINTS],
     pub num_contacts: u8,
     pub elements: [TwoBodyConstraintElement<Real>; MAX_MANIFOLD_POINTS],
+    pub angular_friction: ContactAngularFrictionPart,
 }
 
 impl TwoBodyConstraint {
@@ -129,6 +174,8 @@ impl TwoBodyConstraint {
             dir1: Vector::zeros(),
             #[cfg(feature = "dim3")]
             tangent1: Vector::zeros(),
+            #[cfg(feature = "dim3")]
+            friction_scale: Vector2::repeat(1.0),
             im1: Vector::zeros(),
             im2: Vector::zeros(),
             cfm_factor: 0.0,
@@ -139,6 +186,7 @@ impl TwoBodyConstraint {
             manifold_contact_id: [u8::MAX; MAX_MANIFOLD_POINTS],
             num_contacts: u8::MAX,
             elements: [TwoBodyConstraintElement::zero(); MAX_MANIFOLD_POINTS],
+            angular_friction: ContactAngularFrictionPart::zero(),
         }
     }
 }
@@ -146,16 +194,19 @@ impl TwoBodyConstraint {
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct TwoBodyConstraintBuilder {
     pub infos: [ContactPointInfos<Real>; MAX_MANIFOLD_POINTS],
+    pub softness: ContactSoftness,
 }
 
 impl TwoBodyConstraintBuilder {
     pub fn invalid() -> Self {
         Self {
             infos: [ContactPointInfos::default(); MAX_MANIFOLD_POINTS],
+            softness: ContactSoftness::default(),
         }
     }
 
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: ContactManifoldIndex,
         manifold: &ContactManifold,
         bodies: &RigidBodySet,
@@ -175,12 +226,21 @@ impl TwoBodyConstraintBuilder {
         let solver_vel1 = rb1.ids.active_set_offset;
         let solver_vel2 = rb2.ids.active_set_offset;
         let force_dir1 = -manifold.data.normal;
+        let softness = manifold
+            .data
+            .contact_softness
+            .unwrap_or_else(|| params.contact_softness());
+        let compliance = params.contact_compliance_gain(&softness);
 
         #[cfg(feature = "dim2")]
         let tangents1 = force_dir1.orthonormal_basis();
         #[cfg(feature = "dim3")]
-        let tangents1 =
-            super::compute_tangent_contact_directions(&force_dir1, &vels1.linvel, &vels2.linvel);
+        let (tangents1, friction_scale) = super::compute_friction_directions(
+            &force_dir1,
+            &vels1.linvel,
+            &vels2.linvel,
+            manifold.data.anisotropic_friction.as_ref(),
+        );
 
         for (l, manifold_points) in manifold
             .data
@@ -190,6 +250,7 @@ impl TwoBodyConstraintBuilder {
         {
             let builder = &mut out_builders[l];
             let constraint = &mut out_constraints[l];
+            builder.softness = softness;
             constraint.dir1 = force_dir1;
             constraint.im1 = mprops1.effective_inv_mass;
             constraint.im2 = mprops2.effective_inv_mass;
@@ -200,8 +261,25 @@ impl TwoBodyConstraintBuilder {
             #[cfg(feature = "dim3")]
             {
                 constraint.tangent1 = tangents1[0];
+                constraint.friction_scale = friction_scale;
             }
 
+            // The angular friction is only applied once per manifold.
+            constraint.angular_friction = if l == 0 {
+                ContactAngularFrictionPart::new(
+                    &manifold.data,
+                    #[cfg(feature = "dim3")]
+                    &force_dir1,
+                    #[cfg(feature = "dim3")]
+                    &tangents1[0],
+                    Some(&mprops1.effective_world_inv_inertia_sqrt),
+                    &vels1.angvel,
+                    &mprops2.effective_world_inv_inertia_sqrt,
+                )
+            } else {
+                ContactAngularFrictionPart::zero()
+            };
+
             for k in 0..manifold_points.len() {
                 let manifold_point = &manifold_points[k];
                 let point = manifold_point.point;
@@ -229,7 +307,8 @@ impl TwoBodyConstraintBuilder {
                     let projected_mass = utils::inv(
                         force_dir1.dot(&imsum.component_mul(&force_dir1))
                             + gcross1.gdot(gcross1)
-                            + gcross2.gdot(gcross2),
+                            + gcross2.gdot(gcross2)
+                            + compliance,
                     );
 
                     let is_bouncy = manifold_point.is_bouncy() as u32 as Real;
@@ -245,6 +324,7 @@ impl TwoBodyConstraintBuilder {
                         impulse: manifold_point.warmstart_impulse,
                         impulse_accumulator: na::zero(),
                         r: projected_mass,
+                        compliance,
                         r_mat_elts: [0.0; 2],
                     };
                 }
@@ -307,7 +387,7 @@ impl TwoBodyConstraintBuilder {
                 constraint.manifold_contact_id[k] = manifold_point.contact_id;
             }
 
-            if BLOCK_SOLVER_ENABLED {
+            if params.contact_block_solver {
                 // Coupling between consecutive pairs.
                 for k in 0..manifold_points.len() / 2 {
                     let k0 = k * 2;
@@ -355,13 +435,13 @@ impl TwoBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         _multibodies: &MultibodyJointSet,
         constraint: &mut TwoBodyConstraint,
     ) {
-        let rb1 = &bodies[constraint.solver_vel1];
-        let rb2 = &bodies[constraint.solver_vel2];
-        self.update_with_positions(params, solved_dt, &rb1.position, &rb2.position, constraint)
+        let pos1 = &bodies.positions[constraint.solver_vel1];
+        let pos2 = &bodies.positions[constraint.solver_vel2];
+        self.update_with_positions(params, solved_dt, pos1, pos2, constraint)
     }
 
     // Used by both generic and non-generic builders..
@@ -373,10 +453,13 @@ impl TwoBodyConstraintBuilder {
         rb2_pos: &Isometry<Real>,
         constraint: &mut TwoBodyConstraint,
     ) {
-        let cfm_factor = params.contact_cfm_factor();
+        let (cfm_factor, erp_inv_dt) = params.contact_cfm_factor_and_erp_inv_dt(&self.softness);
+        let compliance = params.contact_compliance_gain(&self.softness);
         let inv_dt = params.inv_dt();
-        let erp_inv_dt = params.contact_erp_inv_dt();
 
+        // At the first substep, the impulses are still the warmstart impulses of the previous
+        // step: they haven’t been applied during this step so they must not be accumulated.
+        let accumulate_impulses = solved_dt != 0.0;
         let all_infos = &self.infos[..constraint.num_contacts as usize];
         let all_elements = &mut constraint.elements[..constraint.num_contacts as usize];
 
@@ -403,13 +486,18 @@ impl TwoBodyConstraintBuilder {
 
                 element.normal_part.rhs_wo_bias = rhs_wo_bias;
                 element.normal_part.rhs = new_rhs;
-                element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                element.normal_part.compliance = compliance;
+                if accumulate_impulses {
+                    element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                }
                 element.normal_part.impulse *= params.warmstart_coefficient;
             }
 
             // Tangent part.
             {
-                element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                if accumulate_impulses {
+                    element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                }
                 element.tangent_part.impulse *= params.warmstart_coefficient;
 
                 for j in 0..DIM - 1 {
@@ -419,6 +507,8 @@ impl TwoBodyConstraintBuilder {
             }
         }
 
+        // The angular friction isn’t warmstarted.
+        constraint.angular_friction.impulse = [0.0; ANG_DIM];
         constraint.cfm_factor = cfm_factor;
     }
 }
@@ -448,6 +538,7 @@ impl TwoBodyConstraint {
         solver_vels: &mut [SolverVel<Real>],
         solve_normal: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) {
         let mut solver_vel1 = solver_vels[self.solver_vel1];
         let mut solver_vel2 = solver_vels[self.solver_vel2];
@@ -461,12 +552,54 @@ impl TwoBodyConstraint {
             &self.im1,
             &self.im2,
             self.limit,
+            #[cfg(feature = "dim3")]
+            Some(&self.friction_scale),
             &mut solver_vel1,
             &mut solver_vel2,
             solve_normal,
             solve_friction,
+            block_solver,
         );
 
+        if solve_friction {
+            let normal_impulse = self.elements[..self.num_contacts as usize]
+                .iter()
+                .map(|e| e.normal_part.impulse)
+                .sum();
+            self.angular_friction
+                .solve(normal_impulse, Some(&mut solver_vel1), &mut solver_vel2);
+        }
+
+        solver_vels[self.solver_vel1] = solver_vel1;
+        solver_vels[self.solver_vel2] = solver_vel2;
+    }
+
+    pub fn solve_coupled(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        let mut solver_vel1 = solver_vels[self.solver_vel1];
+        let mut solver_vel2 = solver_vels[self.solver_vel2];
+
+        TwoBodyConstraintElement::solve_group_coupled(
+            self.cfm_factor,
+            &mut self.elements[..self.num_contacts as usize],
+            &self.dir1,
+            #[cfg(feature = "dim3")]
+            &self.tangent1,
+            &self.im1,
+            &self.im2,
+            self.limit,
+            #[cfg(feature = "dim3")]
+            Some(&self.friction_scale),
+            &mut solver_vel1,
+            &mut solver_vel2,
+        );
+
+        let normal_impulse = self.elements[..self.num_contacts as usize]
+            .iter()
+            .map(|e| e.normal_part.impulse)
+            .sum();
+        self.angular_friction
+            .solve(normal_impulse, Some(&mut solver_vel1), &mut solver_vel2);
+
         solver_vels[self.solver_vel1] = solver_vel1;
         solver_vels[self.solver_vel2] = solver_vel2;
     }
@@ -488,12 +621,24 @@ impl TwoBodyConstraint {
         self.cfm_factor = 1.0;
         for elt in &mut self.elements {
             elt.normal_part.rhs = elt.normal_part.rhs_wo_bias;
+            elt.normal_part.compliance = 0.0;
             // elt.normal_part.impulse = elt.normal_part.total_impulse;
 
             elt.tangent_part.rhs = elt.tangent_part.rhs_wo_bias;
             // elt.tangent_part.impulse = elt.tangent_part.total_impulse;
         }
     }
+
+    /// Prepares this constraint for a position correction pass by keeping only the penetration
+    /// bias in its right-hand side, and resetting its impulses.
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.cfm_factor = 1.0;
+        for elt in &mut self.elements {
+            elt.normal_part.rhs -= elt.normal_part.rhs_wo_bias;
+            elt.normal_part.impulse = 0.0;
+            elt.normal_part.compliance = 0.0;
+        }
+    }
 }
 
 #[inline(always)]
@@ -531,3 +676,171 @@ where
 
     [tangent1, bitangent1]
 }
+
+/// Computes the friction directions of a contact manifold, along with the friction scale along
+/// each of them.
+///
+/// If the manifold has an anisotropic friction, the first tangent is the projection of its
+/// friction direction onto the contact plane. Otherwise, this is the same as
+/// [`compute_tangent_contact_directions`] with a unit scale.
+#[cfg(feature = "dim3")]
+pub(crate) fn compute_friction_directions(
+    force_dir1: &Vector<Real>,
+    linvel1: &Vector<Real>,
+    linvel2: &Vector<Real>,
+    anisotropic_friction: Option<&ContactAnisotropicFriction>,
+) -> ([Vector<Real>; DIM - 1], Vector2<Real>) {
+    let Some(anisotropic_friction) = anisotropic_friction else {
+        return (
+            compute_tangent_contact_directions(force_dir1, linvel1, linvel2),
+            Vector2::repeat(1.0),
+        );
+    };
+
+    let direction = anisotropic_friction.direction;
+    let tangent1 = direction - force_dir1 * force_dir1.dot(&direction);
+
+    if let Some(tangent1) = tangent1.try_normalize(1.0e-6) {
+        (
+            [tangent1, force_dir1.cross(&tangent1)],
+            Vector2::new(
+                anisotropic_friction.along_scale,
+                anisotropic_friction.across_scale,
+            ),
+        )
+    } else {
+        // The friction direction is orthogonal to the contact plane, so the sliding is
+        // always across it.
+        (
+            compute_tangent_contact_directions(force_dir1, linvel1, linvel2),
+            Vector2::repeat(anisotropic_friction.across_scale),
+        )
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "dim3")]
+mod test {
+    use na::{vector, Vector2};
+
+    use super::compute_friction_directions;
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::geometry::{ColliderBuilder, ContactAnisotropicFriction};
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn rolling_and_twist_friction_slow_down_balls() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let mut insert_ball = |z: Real, linvel, angvel, rolling_friction, twist_friction| {
+            let body = world.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(vector![0.0, 0.5, z])
+                    .linvel(linvel)
+                    .angvel(angvel)
+                    .can_sleep(false),
+            );
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5)
+                    .rolling_friction(rolling_friction)
+                    .twist_friction(twist_friction),
+                body,
+                &mut world.bodies,
+            );
+            body
+        };
+
+        // The first two balls roll without slipping, the last two spin around the normal.
+        let rolling = vector![2.0, 0.0, 0.0];
+        let rolling_angvel = vector![0.0, 0.0, -4.0];
+        let spinning_angvel = vector![0.0, 5.0, 0.0];
+        let free_rolling = insert_ball(-6.0, rolling, rolling_angvel, 0.0, 0.0);
+        let resisted_rolling = insert_ball(-2.0, rolling, rolling_angvel, 0.05, 0.0);
+        let free_spinning = insert_ball(2.0, Vector::zeros(), spinning_angvel, 0.0, 0.0);
+        let resisted_spinning = insert_ball(6.0, Vector::zeros(), spinning_angvel, 0.0, 0.05);
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let free_speed = world.bodies[free_rolling].linvel().x;
+        let resisted_speed = world.bodies[resisted_rolling].linvel().x;
+        assert!(free_speed > 1.9);
+        assert!(resisted_speed > 0.0 && resisted_speed < free_speed - 0.3);
+
+        assert!(world.bodies[free_spinning].angvel().y > 4.9);
+        assert!(world.bodies[resisted_spinning].angvel().y.abs() < 1.0);
+    }
+
+    #[test]
+    fn anisotropic_friction_direction_is_projected_onto_the_contact_plane() {
+        let normal = Vector::y();
+        let friction = ContactAnisotropicFriction {
+            direction: vector![1.0, 1.0, 0.0],
+            along_scale: 0.5,
+            across_scale: 1.0,
+        };
+
+        let ([tangent, bitangent], scale) = compute_friction_directions(
+            &normal,
+            &Vector::zeros(),
+            &Vector::zeros(),
+            Some(&friction),
+        );
+        assert!((tangent - Vector::x()).norm() < 1.0e-6);
+        assert!(bitangent.dot(&normal).abs() < 1.0e-6 && bitangent.dot(&tangent).abs() < 1.0e-6);
+        assert_eq!(scale, Vector2::new(0.5, 1.0));
+
+        // A friction direction along the normal only leaves sliding across it.
+        let friction = ContactAnisotropicFriction {
+            direction: normal,
+            ..friction
+        };
+        let (_, scale) = compute_friction_directions(
+            &normal,
+            &Vector::zeros(),
+            &Vector::zeros(),
+            Some(&friction),
+        );
+        assert_eq!(scale, Vector2::repeat(1.0));
+    }
+
+    #[test]
+    fn anisotropic_friction_only_resists_sliding_across_its_direction() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()).friction(0.0));
+        // A ski sliding without friction along the X axis.
+        let ski = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![0.0, 0.1, 0.0])
+                .linvel(vector![2.0, 0.0, 2.0])
+                .can_sleep(false),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(1.0, 0.1, 0.2).anisotropic_friction(Vector::x_axis(), 0.0, 2.0),
+            ski,
+            &mut world.bodies,
+        );
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let linvel = world.bodies[ski].linvel();
+        assert!(linvel.x > 1.9);
+        assert!(linvel.z.abs() < 0.05);
+    }
+}
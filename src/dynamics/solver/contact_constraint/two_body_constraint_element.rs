@@ -1,7 +1,7 @@
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
 use crate::dynamics::solver::SolverVel;
-use crate::math::{AngVector, TangentImpulse, Vector, DIM};
-use crate::utils::{SimdBasis, SimdDot, SimdRealCopy};
+use crate::geometry::ContactManifoldData;
+use crate::math::{AngVector, AngularInertia, Real, TangentImpulse, Vector, ANG_DIM, DIM};
+use crate::utils::{self, SimdAngularInertia, SimdBasis, SimdDot, SimdRealCopy};
 use na::Vector2;
 use simba::simd::SimdValue;
 
@@ -88,6 +88,7 @@ impl<N: SimdRealCopy> TwoBodyConstraintTangentPart<N> {
         im1: &Vector<N>,
         im2: &Vector<N>,
         limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
         solver_vel1: &mut SolverVel<N>,
         solver_vel2: &mut SolverVel<N>,
     ) where
@@ -137,7 +138,7 @@ impl<N: SimdRealCopy> TwoBodyConstraintTangentPart<N> {
                 let _disable_fe_except =
                         crate::utils::DisableFloatingPointExceptionsFlags::
                         disable_floating_point_exceptions();
-                new_impulse.simd_cap_magnitude(limit)
+                cap_friction_impulse(new_impulse, limit, friction_scale)
             };
 
             let dlambda = new_impulse - self.impulse;
@@ -154,6 +155,120 @@ impl<N: SimdRealCopy> TwoBodyConstraintTangentPart<N> {
     }
 }
 
+/// Caps a friction impulse to the disk of radius `limit`, or to the ellipse with the semi-axes
+/// `limit * friction_scale` for anisotropic friction.
+#[cfg(feature = "dim3")]
+#[inline]
+pub(crate) fn cap_friction_impulse<N: SimdRealCopy>(
+    impulse: Vector2<N>,
+    limit: N,
+    friction_scale: Option<&Vector2<N>>,
+) -> Vector2<N> {
+    match friction_scale {
+        Some(scale) => {
+            // A zero scale cancels the friction along its axis.
+            let inv_scale = Vector2::new(utils::simd_inv(scale.x), utils::simd_inv(scale.y));
+            impulse
+                .component_mul(&inv_scale)
+                .simd_cap_magnitude(limit)
+                .component_mul(scale)
+        }
+        None => impulse.simd_cap_magnitude(limit),
+    }
+}
+
+/// Caps a friction impulse to `limit`, the friction coefficient times the normal impulse.
+#[inline]
+fn project_friction_impulse<N: SimdRealCopy>(
+    impulse: TangentImpulse<N>,
+    limit: N,
+    #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
+) -> TangentImpulse<N> {
+    #[cfg(feature = "dim2")]
+    return na::Vector1::new(impulse.x.simd_clamp(-limit, limit));
+    #[cfg(feature = "dim3")]
+    return {
+        let _disable_fe_except =
+            crate::utils::DisableFloatingPointExceptionsFlags::disable_floating_point_exceptions();
+        cap_friction_impulse(impulse, limit, friction_scale)
+    };
+}
+
+/// Solves the normal and friction impulses of a single contact point together, for
+/// [`ContactFrictionMode::Coupled`](crate::dynamics::ContactFrictionMode::Coupled).
+///
+/// The rows of the mass matrix `w` and of the velocity error `dvel` are ordered with the normal
+/// first, then the tangents. Returns the new normal and friction impulses.
+#[inline]
+pub(crate) fn solve_coupled_contact_point<N: SimdRealCopy>(
+    w: [[N; DIM]; DIM],
+    dvel: [N; DIM],
+    normal_impulse: N,
+    tangent_impulse: TangentImpulse<N>,
+    cfm_factor: N,
+    friction_coefficient: N,
+    #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
+) -> (N, TangentImpulse<N>) {
+    // The tangent rows are slightly regularized so the matrix stays invertible if the bodies
+    // can’t move along a tangent, e.g., because of locked axes.
+    #[cfg(feature = "dim2")]
+    let delta = {
+        let [[a, b], [_, d]] = w;
+        let d = d + (a + d) * N::splat(1.0e-5);
+        let inv_det = utils::simd_inv(a * d - b * b);
+        [
+            (d * dvel[0] - b * dvel[1]) * inv_det,
+            (a * dvel[1] - b * dvel[0]) * inv_det,
+        ]
+    };
+    #[cfg(feature = "dim3")]
+    let delta = {
+        let [[a, b, c], [_, d, e], [_, _, f]] = w;
+        let eps = (a + d + f) * N::splat(1.0e-5);
+        let (d, f) = (d + eps, f + eps);
+        // The cofactors of the symmetric matrix.
+        let (c00, c01, c02) = (d * f - e * e, c * e - b * f, b * e - c * d);
+        let (c11, c12, c22) = (a * f - c * c, b * c - a * e, a * d - b * b);
+        let inv_det = utils::simd_inv(a * c00 + b * c01 + c * c02);
+        [
+            (c00 * dvel[0] + c01 * dvel[1] + c02 * dvel[2]) * inv_det,
+            (c01 * dvel[0] + c11 * dvel[1] + c12 * dvel[2]) * inv_det,
+            (c02 * dvel[0] + c12 * dvel[1] + c22 * dvel[2]) * inv_det,
+        ]
+    };
+
+    #[cfg(feature = "dim2")]
+    let unbounded_tangent = tangent_impulse - na::Vector1::new(delta[1]);
+    #[cfg(feature = "dim3")]
+    let unbounded_tangent = tangent_impulse - Vector2::new(delta[1], delta[2]);
+
+    let normal = cfm_factor * (normal_impulse - delta[0]).simd_max(N::zero());
+    let tangent = project_friction_impulse(
+        unbounded_tangent,
+        friction_coefficient * normal,
+        #[cfg(feature = "dim3")]
+        friction_scale,
+    );
+
+    // The friction impulse may have been moved to the boundary of the friction cone, so the
+    // normal impulse is recomputed for this projected friction impulse.
+    let dtangent = tangent - tangent_impulse;
+    #[cfg(feature = "dim2")]
+    let coupling = w[0][1] * dtangent.x;
+    #[cfg(feature = "dim3")]
+    let coupling = w[0][1] * dtangent.x + w[0][2] * dtangent.y;
+    let normal = cfm_factor
+        * (normal_impulse - (dvel[0] + coupling) * utils::simd_inv(w[0][0])).simd_max(N::zero());
+    let tangent = project_friction_impulse(
+        tangent,
+        friction_coefficient * normal,
+        #[cfg(feature = "dim3")]
+        friction_scale,
+    );
+
+    (normal, tangent)
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct TwoBodyConstraintNormalPart<N: SimdRealCopy> {
     pub gcross1: AngVector<N>,
@@ -163,6 +278,8 @@ pub(crate) struct TwoBodyConstraintNormalPart<N: SimdRealCopy> {
     pub impulse: N,
     pub impulse_accumulator: N,
     pub r: N,
+    // The velocity error per unit of impulse of compliant contacts. `r` already accounts for it.
+    pub compliance: N,
     // For coupled constraint pairs, even constraints store the
     // diagonal of the projected mass matrix. Odd constraints
     // store the off-diagonal element of the projected mass matrix,
@@ -180,6 +297,7 @@ impl<N: SimdRealCopy> TwoBodyConstraintNormalPart<N> {
             impulse: na::zero(),
             impulse_accumulator: na::zero(),
             r: na::zero(),
+            compliance: na::zero(),
             r_mat_elts: [N::zero(); 2],
         }
     }
@@ -221,7 +339,8 @@ impl<N: SimdRealCopy> TwoBodyConstraintNormalPart<N> {
         let dvel = dir1.dot(&solver_vel1.linear) + self.gcross1.gdot(solver_vel1.angular)
             - dir1.dot(&solver_vel2.linear)
             + self.gcross2.gdot(solver_vel2.angular)
-            + self.rhs;
+            + self.rhs
+            + self.compliance * self.impulse;
         let new_impulse = cfm_factor * (self.impulse - self.r * dvel).simd_max(N::zero());
         let dlambda = new_impulse - self.impulse;
         self.impulse = new_impulse;
@@ -282,11 +401,13 @@ impl<N: SimdRealCopy> TwoBodyConstraintNormalPart<N> {
         let dvel_a = dvel_lin
             + constraint_a.gcross1.gdot(solver_vel1.angular)
             + constraint_a.gcross2.gdot(solver_vel2.angular)
-            + constraint_a.rhs;
+            + constraint_a.rhs
+            + constraint_a.compliance * constraint_a.impulse;
         let dvel_b = dvel_lin
             + constraint_b.gcross1.gdot(solver_vel1.angular)
             + constraint_b.gcross2.gdot(solver_vel2.angular)
-            + constraint_b.rhs;
+            + constraint_b.rhs
+            + constraint_b.compliance * constraint_b.impulse;
 
         let prev_impulse = Vector2::new(constraint_a.impulse, constraint_b.impulse);
         let new_impulse = Self::solve_mlcp_two_constraints(
@@ -359,16 +480,18 @@ impl<N: SimdRealCopy> TwoBodyConstraintElement<N> {
         im1: &Vector<N>,
         im2: &Vector<N>,
         limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
         solver_vel1: &mut SolverVel<N>,
         solver_vel2: &mut SolverVel<N>,
         solve_restitution: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) where
         Vector<N>: SimdBasis,
         AngVector<N>: SimdDot<AngVector<N>, Result = N>,
     {
         if solve_restitution {
-            if BLOCK_SOLVER_ENABLED {
+            if block_solver {
                 for elements in elements.chunks_exact_mut(2) {
                     let [element_a, element_b] = elements else {
                         unreachable!()
@@ -411,7 +534,263 @@ impl<N: SimdRealCopy> TwoBodyConstraintElement<N> {
             for element in elements.iter_mut() {
                 let limit = limit * element.normal_part.impulse;
                 let part = &mut element.tangent_part;
-                part.solve(tangents1, im1, im2, limit, solver_vel1, solver_vel2);
+                part.solve(
+                    tangents1,
+                    im1,
+                    im2,
+                    limit,
+                    #[cfg(feature = "dim3")]
+                    friction_scale,
+                    solver_vel1,
+                    solver_vel2,
+                );
+            }
+        }
+    }
+
+    /// Solves the normal and friction impulses of each contact point together.
+    ///
+    /// See [`solve_coupled_contact_point`].
+    #[inline]
+    pub fn solve_group_coupled(
+        cfm_factor: N,
+        elements: &mut [Self],
+        dir1: &Vector<N>,
+        #[cfg(feature = "dim3")] tangent1: &Vector<N>,
+        im1: &Vector<N>,
+        im2: &Vector<N>,
+        limit: N,
+        #[cfg(feature = "dim3")] friction_scale: Option<&Vector2<N>>,
+        solver_vel1: &mut SolverVel<N>,
+        solver_vel2: &mut SolverVel<N>,
+    ) where
+        Vector<N>: SimdBasis,
+        AngVector<N>: SimdDot<AngVector<N>, Result = N>,
+    {
+        #[cfg(feature = "dim3")]
+        let dirs = [*dir1, *tangent1, dir1.cross(tangent1)];
+        #[cfg(feature = "dim2")]
+        let dirs = [*dir1, dir1.orthonormal_vector()];
+        let im = im1 + im2;
+
+        for element in elements.iter_mut() {
+            let normal = &mut element.normal_part;
+            let tangent = &mut element.tangent_part;
+
+            #[cfg(feature = "dim3")]
+            let (gcross1, gcross2, rhs) = (
+                [normal.gcross1, tangent.gcross1[0], tangent.gcross1[1]],
+                [normal.gcross2, tangent.gcross2[0], tangent.gcross2[1]],
+                [normal.rhs, tangent.rhs[0], tangent.rhs[1]],
+            );
+            #[cfg(feature = "dim2")]
+            let (gcross1, gcross2, rhs) = (
+                [normal.gcross1, tangent.gcross1[0]],
+                [normal.gcross2, tangent.gcross2[0]],
+                [normal.rhs, tangent.rhs[0]],
+            );
+
+            let mut w: [[N; DIM]; DIM] = std::array::from_fn(|j| {
+                std::array::from_fn(|k| {
+                    dirs[j].dot(&dirs[k].component_mul(&im))
+                        + gcross1[j].gdot(gcross1[k])
+                        + gcross2[j].gdot(gcross2[k])
+                })
+            });
+            w[0][0] += normal.compliance;
+            let mut dvel: [N; DIM] = std::array::from_fn(|j| {
+                dirs[j].dot(&solver_vel1.linear) + gcross1[j].gdot(solver_vel1.angular)
+                    - dirs[j].dot(&solver_vel2.linear)
+                    + gcross2[j].gdot(solver_vel2.angular)
+                    + rhs[j]
+            });
+            dvel[0] += normal.compliance * normal.impulse;
+
+            let (new_normal, new_tangent) = solve_coupled_contact_point(
+                w,
+                dvel,
+                normal.impulse,
+                tangent.impulse,
+                cfm_factor,
+                limit,
+                #[cfg(feature = "dim3")]
+                friction_scale,
+            );
+            let dnormal = new_normal - normal.impulse;
+            let dtangent = new_tangent - tangent.impulse;
+            normal.impulse = new_normal;
+            tangent.impulse = new_tangent;
+
+            #[cfg(feature = "dim3")]
+            let dlambdas = [dnormal, dtangent[0], dtangent[1]];
+            #[cfg(feature = "dim2")]
+            let dlambdas = [dnormal, dtangent[0]];
+
+            for (j, dlambda) in dlambdas.into_iter().enumerate() {
+                solver_vel1.linear += dirs[j].component_mul(im1) * dlambda;
+                solver_vel1.angular += gcross1[j] * dlambda;
+                solver_vel2.linear += dirs[j].component_mul(im2) * -dlambda;
+                solver_vel2.angular += gcross2[j] * dlambda;
+            }
+        }
+    }
+}
+
+/// The angular rows of a contact manifold, resisting the rolling and, in 3D, the twisting of the
+/// two bodies relative to each other.
+///
+/// Both rows are attached to the whole manifold rather than to each contact point, and their
+/// impulses are bounded by the sum of the normal impulses of the manifold’s contact points.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ContactAngularFrictionPart {
+    pub gcross1: [AngVector<Real>; ANG_DIM],
+    pub gcross2: [AngVector<Real>; ANG_DIM],
+    pub rhs: [Real; ANG_DIM],
+    pub r: [Real; ANG_DIM],
+    pub impulse: [Real; ANG_DIM],
+    pub rolling_friction: Real,
+    #[cfg(feature = "dim3")]
+    pub twist_friction: Real,
+}
+
+impl ContactAngularFrictionPart {
+    pub fn zero() -> Self {
+        Self {
+            gcross1: [na::zero(); ANG_DIM],
+            gcross2: [na::zero(); ANG_DIM],
+            rhs: [0.0; ANG_DIM],
+            r: [0.0; ANG_DIM],
+            impulse: [0.0; ANG_DIM],
+            rolling_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            twist_friction: 0.0,
+        }
+    }
+
+    /// Initializes the angular rows of the given manifold.
+    ///
+    /// If the first body isn’t solved (`inv_inertia_sqrt1` is `None`), its angular velocity
+    /// `angvel1` is moved to the right-hand side.
+    pub fn new(
+        data: &ContactManifoldData,
+        #[cfg(feature = "dim3")] dir1: &Vector<Real>,
+        #[cfg(feature = "dim3")] tangent1: &Vector<Real>,
+        inv_inertia_sqrt1: Option<&AngularInertia<Real>>,
+        angvel1: &AngVector<Real>,
+        inv_inertia_sqrt2: &AngularInertia<Real>,
+    ) -> Self {
+        let mut result = Self::zero();
+        result.rolling_friction = data.rolling_friction;
+        #[cfg(feature = "dim3")]
+        {
+            result.twist_friction = data.twist_friction;
+        }
+
+        if !data.has_angular_friction() {
+            return result;
+        }
+
+        // The first two axes are the rolling axes, the last one is the twist axis.
+        #[cfg(feature = "dim3")]
+        let axes = [*tangent1, dir1.cross(tangent1), *dir1];
+        #[cfg(feature = "dim2")]
+        let axes = [1.0];
+
+        for (j, axis) in axes.iter().enumerate() {
+            let gcross1 = inv_inertia_sqrt1
+                .map(|ii| ii.transform_vector(*axis))
+                .unwrap_or_else(na::zero);
+            let gcross2 = inv_inertia_sqrt2.transform_vector(-*axis);
+
+            result.gcross1[j] = gcross1;
+            result.gcross2[j] = gcross2;
+            result.rhs[j] = if inv_inertia_sqrt1.is_some() {
+                0.0
+            } else {
+                axis.gdot(*angvel1)
+            };
+            result.r[j] = utils::inv(gcross1.gdot(gcross1) + gcross2.gdot(gcross2));
+        }
+
+        result
+    }
+
+    #[inline]
+    fn solve_row(
+        &self,
+        j: usize,
+        solver_vel1: &Option<&mut SolverVel<Real>>,
+        solver_vel2: &SolverVel<Real>,
+    ) -> Real {
+        let dvel1 = solver_vel1
+            .as_ref()
+            .map(|vel| self.gcross1[j].gdot(vel.angular))
+            .unwrap_or(0.0);
+        let dvel = dvel1 + self.gcross2[j].gdot(solver_vel2.angular) + self.rhs[j];
+        self.impulse[j] - self.r[j] * dvel
+    }
+
+    #[inline]
+    fn apply_row(
+        &self,
+        j: usize,
+        dlambda: Real,
+        solver_vel1: &mut Option<&mut SolverVel<Real>>,
+        solver_vel2: &mut SolverVel<Real>,
+    ) {
+        if let Some(vel) = solver_vel1 {
+            vel.angular += self.gcross1[j] * dlambda;
+        }
+        solver_vel2.angular += self.gcross2[j] * dlambda;
+    }
+
+    /// Solves the angular rows, given the sum of the normal impulses of the manifold.
+    ///
+    /// Pass `None` as `solver_vel1` if the first body isn’t solved.
+    #[inline]
+    pub fn solve(
+        &mut self,
+        normal_impulse: Real,
+        mut solver_vel1: Option<&mut SolverVel<Real>>,
+        solver_vel2: &mut SolverVel<Real>,
+    ) {
+        let rolling_limit = self.rolling_friction * normal_impulse;
+
+        #[cfg(feature = "dim2")]
+        {
+            let new_impulse = self
+                .solve_row(0, &solver_vel1, solver_vel2)
+                .clamp(-rolling_limit, rolling_limit);
+            let dlambda = new_impulse - self.impulse[0];
+            self.impulse[0] = new_impulse;
+            self.apply_row(0, dlambda, &mut solver_vel1, solver_vel2);
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            if self.rolling_friction > 0.0 {
+                // The rolling resistance is bounded by a disk, like the Coulomb friction.
+                let new_impulse = Vector2::new(
+                    self.solve_row(0, &solver_vel1, solver_vel2),
+                    self.solve_row(1, &solver_vel1, solver_vel2),
+                )
+                .cap_magnitude(rolling_limit);
+
+                for j in 0..2 {
+                    let dlambda = new_impulse[j] - self.impulse[j];
+                    self.impulse[j] = new_impulse[j];
+                    self.apply_row(j, dlambda, &mut solver_vel1, solver_vel2);
+                }
+            }
+
+            if self.twist_friction > 0.0 {
+                let twist_limit = self.twist_friction * normal_impulse;
+                let new_impulse = self
+                    .solve_row(2, &solver_vel1, solver_vel2)
+                    .clamp(-twist_limit, twist_limit);
+                let dlambda = new_impulse - self.impulse[2];
+                self.impulse[2] = new_impulse;
+                self.apply_row(2, dlambda, &mut solver_vel1, solver_vel2);
             }
         }
     }
@@ -1,10 +1,9 @@
 use super::{TwoBodyConstraintElement, TwoBodyConstraintNormalPart};
-use crate::dynamics::integration_parameters::BLOCK_SOLVER_ENABLED;
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::{ContactPointInfos, SolverVel};
 use crate::dynamics::{
-    IntegrationParameters, MultibodyJointSet, RigidBodyIds, RigidBodyMassProps, RigidBodySet,
-    RigidBodyVelocity,
+    ContactSoftness, IntegrationParameters, MultibodyJointSet, RigidBodyIds, RigidBodyMassProps,
+    RigidBodySet, RigidBodyVelocity,
 };
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::math::{
@@ -21,10 +20,12 @@ use simba::simd::{SimdPartialOrd, SimdValue};
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct TwoBodyConstraintBuilderSimd {
     infos: [super::ContactPointInfos<SimdReal>; MAX_MANIFOLD_POINTS],
+    softness: [ContactSoftness; SIMD_WIDTH],
 }
 
 impl TwoBodyConstraintBuilderSimd {
     pub fn generate(
+        params: &IntegrationParameters,
         manifold_id: [ContactManifoldIndex; SIMD_WIDTH],
         manifolds: [&ContactManifold; SIMD_WIDTH],
         bodies: &RigidBodySet,
@@ -76,6 +77,13 @@ impl TwoBodyConstraintBuilderSimd {
         #[cfg(feature = "dim3")]
         let tangents1 = super::compute_tangent_contact_directions(&force_dir1, &linvel1, &linvel2);
 
+        let softness = gather![|ii| manifolds[ii]
+            .data
+            .contact_softness
+            .unwrap_or_else(|| params.contact_softness())];
+        let compliance =
+            SimdReal::from(gather![|ii| params.contact_compliance_gain(&softness[ii])]);
+
         for l in (0..num_active_contacts).step_by(MAX_MANIFOLD_POINTS) {
             let manifold_points =
                 gather![|ii| &manifolds[ii].data.solver_contacts[l..num_active_contacts]];
@@ -83,6 +91,7 @@ impl TwoBodyConstraintBuilderSimd {
 
             let constraint = &mut out_constraints[l / MAX_MANIFOLD_POINTS];
             let builder = &mut out_builders[l / MAX_MANIFOLD_POINTS];
+            builder.softness = softness;
 
             constraint.dir1 = force_dir1;
             constraint.im1 = im1;
@@ -132,7 +141,8 @@ impl TwoBodyConstraintBuilderSimd {
                     let projected_mass = utils::simd_inv(
                         force_dir1.dot(&imsum.component_mul(&force_dir1))
                             + gcross1.gdot(gcross1)
-                            + gcross2.gdot(gcross2),
+                            + gcross2.gdot(gcross2)
+                            + compliance,
                     );
 
                     let projected_velocity = (vel1 - vel2).dot(&force_dir1);
@@ -146,6 +156,7 @@ impl TwoBodyConstraintBuilderSimd {
                         impulse: warmstart_impulse,
                         impulse_accumulator: SimdReal::splat(0.0),
                         r: projected_mass,
+                        compliance,
                         r_mat_elts: [SimdReal::zero(); 2],
                     };
                 }
@@ -194,7 +205,7 @@ impl TwoBodyConstraintBuilderSimd {
                 builder.infos[k] = infos;
             }
 
-            if BLOCK_SOLVER_ENABLED {
+            if params.contact_block_solver {
                 // Coupling between consecutive pairs.
                 for k in 0..num_points / 2 {
                     let k0 = k * 2;
@@ -246,22 +257,24 @@ impl TwoBodyConstraintBuilderSimd {
         &self,
         params: &IntegrationParameters,
         solved_dt: Real,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         _multibodies: &MultibodyJointSet,
         constraint: &mut TwoBodyConstraintSimd,
     ) {
-        let cfm_factor = SimdReal::splat(params.contact_cfm_factor());
+        let cfm_and_erp =
+            gather![|ii| params.contact_cfm_factor_and_erp_inv_dt(&self.softness[ii])];
+        let cfm_factor = SimdReal::from(gather![|ii| cfm_and_erp[ii].0]);
         let inv_dt = SimdReal::splat(params.inv_dt());
         let allowed_lin_err = SimdReal::splat(params.allowed_linear_error());
-        let erp_inv_dt = SimdReal::splat(params.contact_erp_inv_dt());
+        let erp_inv_dt = SimdReal::from(gather![|ii| cfm_and_erp[ii].1]);
+        let compliance = SimdReal::from(gather![
+            |ii| params.contact_compliance_gain(&self.softness[ii])
+        ]);
         let max_corrective_velocity = SimdReal::splat(params.max_corrective_velocity());
         let warmstart_coeff = SimdReal::splat(params.warmstart_coefficient);
 
-        let rb1 = gather![|ii| &bodies[constraint.solver_vel1[ii]]];
-        let rb2 = gather![|ii| &bodies[constraint.solver_vel2[ii]]];
-
-        let poss1 = Isometry::from(gather![|ii| rb1[ii].position]);
-        let poss2 = Isometry::from(gather![|ii| rb2[ii].position]);
+        let poss1 = Isometry::from(gather![|ii| bodies.positions[constraint.solver_vel1[ii]]]);
+        let poss2 = Isometry::from(gather![|ii| bodies.positions[constraint.solver_vel2[ii]]]);
 
         let all_infos = &self.infos[..constraint.num_contacts as usize];
         let all_elements = &mut constraint.elements[..constraint.num_contacts as usize];
@@ -274,6 +287,8 @@ impl TwoBodyConstraintBuilderSimd {
             constraint.dir1.cross(&constraint.tangent1),
         ];
 
+        // The warmstart impulses aren’t part of this step’s impulses yet at the first substep.
+        let accumulate_impulses = solved_dt != 0.0;
         let solved_dt = SimdReal::splat(solved_dt);
 
         for (info, element) in all_infos.iter().zip(all_elements.iter_mut()) {
@@ -292,13 +307,18 @@ impl TwoBodyConstraintBuilderSimd {
 
                 element.normal_part.rhs_wo_bias = rhs_wo_bias;
                 element.normal_part.rhs = new_rhs;
-                element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                element.normal_part.compliance = compliance;
+                if accumulate_impulses {
+                    element.normal_part.impulse_accumulator += element.normal_part.impulse;
+                }
                 element.normal_part.impulse *= warmstart_coeff;
             }
 
             // tangent parts.
             {
-                element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                if accumulate_impulses {
+                    element.tangent_part.impulse_accumulator += element.tangent_part.impulse;
+                }
                 element.tangent_part.impulse *= warmstart_coeff;
 
                 for j in 0..DIM - 1 {
@@ -367,6 +387,7 @@ impl TwoBodyConstraintSimd {
         solver_vels: &mut [SolverVel<Real>],
         solve_normal: bool,
         solve_friction: bool,
+        block_solver: bool,
     ) {
         let mut solver_vel1 = SolverVel {
             linear: Vector::from(gather![|ii| solver_vels[self.solver_vel1[ii]].linear]),
@@ -387,10 +408,49 @@ impl TwoBodyConstraintSimd {
             &self.im1,
             &self.im2,
             self.limit,
+            #[cfg(feature = "dim3")]
+            None,
             &mut solver_vel1,
             &mut solver_vel2,
             solve_normal,
             solve_friction,
+            block_solver,
+        );
+
+        for ii in 0..SIMD_WIDTH {
+            solver_vels[self.solver_vel1[ii]].linear = solver_vel1.linear.extract(ii);
+            solver_vels[self.solver_vel1[ii]].angular = solver_vel1.angular.extract(ii);
+        }
+        for ii in 0..SIMD_WIDTH {
+            solver_vels[self.solver_vel2[ii]].linear = solver_vel2.linear.extract(ii);
+            solver_vels[self.solver_vel2[ii]].angular = solver_vel2.angular.extract(ii);
+        }
+    }
+
+    pub fn solve_coupled(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        let mut solver_vel1 = SolverVel {
+            linear: Vector::from(gather![|ii| solver_vels[self.solver_vel1[ii]].linear]),
+            angular: AngVector::from(gather![|ii| solver_vels[self.solver_vel1[ii]].angular]),
+        };
+
+        let mut solver_vel2 = SolverVel {
+            linear: Vector::from(gather![|ii| solver_vels[self.solver_vel2[ii]].linear]),
+            angular: AngVector::from(gather![|ii| solver_vels[self.solver_vel2[ii]].angular]),
+        };
+
+        TwoBodyConstraintElement::solve_group_coupled(
+            self.cfm_factor,
+            &mut self.elements[..self.num_contacts as usize],
+            &self.dir1,
+            #[cfg(feature = "dim3")]
+            &self.tangent1,
+            &self.im1,
+            &self.im2,
+            self.limit,
+            #[cfg(feature = "dim3")]
+            None,
+            &mut solver_vel1,
+            &mut solver_vel2,
         );
 
         for ii in 0..SIMD_WIDTH {
@@ -427,7 +487,19 @@ impl TwoBodyConstraintSimd {
         self.cfm_factor = SimdReal::splat(1.0);
         for elt in &mut self.elements {
             elt.normal_part.rhs = elt.normal_part.rhs_wo_bias;
+            elt.normal_part.compliance = SimdReal::zero();
             elt.tangent_part.rhs = elt.tangent_part.rhs_wo_bias;
         }
     }
+
+    /// Prepares this constraint for a position correction pass by keeping only the penetration
+    /// bias in its right-hand side, and resetting its impulses.
+    pub fn keep_only_position_bias_in_rhs(&mut self) {
+        self.cfm_factor = SimdReal::splat(1.0);
+        for elt in &mut self.elements {
+            elt.normal_part.rhs -= elt.normal_part.rhs_wo_bias;
+            elt.normal_part.impulse = SimdReal::zero();
+            elt.normal_part.compliance = SimdReal::zero();
+        }
+    }
 }
@@ -264,8 +264,9 @@ impl InteractionGroups {
                 continue;
             }
 
-            if !interaction.data.supports_simd_constraints() {
-                // This joint does not support simd constraints yet.
+            if !interaction.data.supports_simd_constraints() || interaction.softness.is_some() {
+                // This joint does not support simd constraints yet, or the lanes of a
+                // simd constraint would need a different softness.
                 self.nongrouped_interactions.push(*interaction_i);
                 continue;
             }
@@ -413,6 +414,13 @@ impl InteractionGroups {
                     continue;
                 }
 
+                // The rolling, twist, and anisotropic friction are only solved by the non-SIMD
+                // constraints.
+                if interaction.data.needs_scalar_constraints() {
+                    self.nongrouped_interactions.push(*interaction_i);
+                    continue;
+                }
+
                 let (status1, active_set_offset1) = if let Some(rb1) = interaction.data.rigid_body1
                 {
                     let rb1 = &bodies[rb1];
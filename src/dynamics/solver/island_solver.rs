@@ -1,15 +1,21 @@
-use super::{JointConstraintsSet, VelocitySolver};
+use super::{
+    JointConstraintsSet, UserConstraintHandle, UserConstraintRows, UserConstraintSet,
+    VelocitySolver,
+};
 use crate::counters::Counters;
-use crate::dynamics::solver::contact_constraint::ContactConstraintsSet;
-use crate::dynamics::IslandManager;
+use crate::dynamics::solver::contact_constraint::{ContactConstraintsCache, ContactConstraintsSet};
 use crate::dynamics::{IntegrationParameters, JointGraphEdge, JointIndex, RigidBodySet};
+use crate::dynamics::{IslandManager, IslandSolverContext, IslandSolverIterations};
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
 use crate::prelude::MultibodyJointSet;
 use parry::math::Real;
+use std::num::NonZeroUsize;
 
 pub struct IslandSolver {
     contact_constraints: ContactConstraintsSet,
     joint_constraints: JointConstraintsSet,
+    user_constraints: UserConstraintRows,
+    contact_constraints_cache: ContactConstraintsCache,
     velocity_solver: VelocitySolver,
 }
 
@@ -20,10 +26,27 @@ impl Default for IslandSolver {
 }
 
 impl IslandSolver {
+    /// The number of PGS iterations run during the last call to `Self::init_and_solve`, over
+    /// all the substeps.
+    pub fn num_pgs_iterations(&self) -> usize {
+        self.velocity_solver.num_pgs_iterations
+    }
+
+    /// Stores the impulses of the user constraints solved during the last call to
+    /// `Self::init_and_solve` for warmstarting them at the next step.
+    ///
+    /// This is separate from `Self::init_and_solve` so the islands can be solved in parallel
+    /// with a shared `UserConstraintSet`.
+    pub fn writeback_user_constraint_impulses(&self, user_constraints: &mut UserConstraintSet) {
+        self.user_constraints.writeback_impulses(user_constraints);
+    }
+
     pub fn new() -> Self {
         Self {
             contact_constraints: ContactConstraintsSet::new(),
             joint_constraints: JointConstraintsSet::new(),
+            user_constraints: UserConstraintRows::new(),
+            contact_constraints_cache: ContactConstraintsCache::new(),
             velocity_solver: VelocitySolver::new(),
         }
     }
@@ -41,13 +64,51 @@ impl IslandSolver {
         impulse_joints: &mut [JointGraphEdge],
         joint_indices: &[JointIndex],
         multibodies: &mut MultibodyJointSet,
+        user_constraints: &UserConstraintSet,
+        user_constraint_handles: &[UserConstraintHandle],
     ) {
         counters.solver.velocity_assembly_time.resume();
-        let num_solver_iterations = base_params.num_solver_iterations.get()
-            + islands.active_island_additional_solver_iterations(island_id);
+        let is_debris = islands.active_island_is_debris(island_id);
+        let mut iterations = IslandSolverIterations {
+            num_solver_iterations: if is_debris {
+                NonZeroUsize::MIN
+            } else {
+                base_params
+                    .num_solver_iterations
+                    .saturating_add(islands.active_island_additional_solver_iterations(island_id))
+            },
+            num_internal_pgs_iterations: base_params.num_internal_pgs_iterations,
+            num_internal_stabilization_iterations: base_params
+                .num_internal_stabilization_iterations,
+        };
 
+        if let Some(heuristic) = islands.solver_iterations_heuristic() {
+            let max_penetration = manifold_indices
+                .iter()
+                .flat_map(|i| manifolds[*i].points.iter())
+                .fold(0.0, |max: Real, pt| max.max(-pt.dist));
+            iterations = heuristic(&IslandSolverContext {
+                bodies,
+                island: islands.active_island(island_id),
+                num_contact_manifolds: manifold_indices.len(),
+                num_joints: joint_indices.len(),
+                max_penetration,
+                is_debris,
+                default_iterations: iterations,
+            });
+        }
+
+        let num_solver_iterations = iterations.num_solver_iterations.get();
         let mut params = *base_params;
         params.dt /= num_solver_iterations as Real;
+        params.num_internal_pgs_iterations = iterations.num_internal_pgs_iterations;
+        params.num_internal_stabilization_iterations =
+            iterations.num_internal_stabilization_iterations;
+
+        if is_debris {
+            params.num_additional_friction_iterations = 0;
+            params.num_additional_stabilization_iterations = 0;
+        }
 
         /*
          *
@@ -63,17 +124,61 @@ impl IslandSolver {
                 bodies,
                 multibodies,
             );
-        self.velocity_solver.init_constraints(
-            island_id,
-            islands,
+        // If nothing changed since the last step for the contacts of this island, reuse
+        // the contact constraints from the last step instead of regenerating them.
+        if params.contact_constraints_caching
+            && self.contact_constraints_cache.try_restore(
+                &params,
+                bodies,
+                multibodies,
+                manifolds,
+                manifold_indices,
+                &mut self.contact_constraints,
+            )
+        {
+            self.joint_constraints.init(
+                island_id,
+                islands,
+                bodies,
+                multibodies,
+                impulse_joints,
+                joint_indices,
+            );
+        } else {
+            self.velocity_solver.init_constraints(
+                &params,
+                island_id,
+                islands,
+                bodies,
+                multibodies,
+                manifolds,
+                manifold_indices,
+                impulse_joints,
+                joint_indices,
+                &mut self.contact_constraints,
+                &mut self.joint_constraints,
+            );
+
+            if params.contact_constraints_caching {
+                self.contact_constraints_cache.save(
+                    &params,
+                    bodies,
+                    multibodies,
+                    manifolds,
+                    manifold_indices,
+                    &self.contact_constraints,
+                );
+            }
+        }
+        self.contact_constraints
+            .update_solve_order(manifolds, manifold_indices);
+        self.user_constraints.init(
+            base_params,
+            num_solver_iterations,
             bodies,
             multibodies,
-            manifolds,
-            manifold_indices,
-            impulse_joints,
-            joint_indices,
-            &mut self.contact_constraints,
-            &mut self.joint_constraints,
+            user_constraints,
+            user_constraint_handles,
         );
         counters.solver.velocity_assembly_time.pause();
 
@@ -86,13 +191,25 @@ impl IslandSolver {
             multibodies,
             &mut self.contact_constraints,
             &mut self.joint_constraints,
+            &mut self.user_constraints,
         );
         counters.solver.velocity_resolution_time.pause();
 
         // WRITEBACK
         counters.solver.velocity_writeback_time.resume();
         self.joint_constraints.writeback_impulses(impulse_joints);
+        for joint_id in joint_indices {
+            impulse_joints[*joint_id].weight.impulses_dt = params.dt;
+        }
         self.contact_constraints.writeback_impulses(manifolds);
+        // The position correction pass runs once for the whole step.
+        let mut position_params = params;
+        position_params.dt = base_params.dt;
+        self.velocity_solver.solve_position_errors(
+            &position_params,
+            multibodies,
+            &mut self.contact_constraints,
+        );
         self.velocity_solver.writeback_bodies(
             base_params,
             num_solver_iterations,
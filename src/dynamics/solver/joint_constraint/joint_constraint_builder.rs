@@ -2,10 +2,12 @@ use crate::dynamics::solver::joint_constraint::joint_velocity_constraint::{
     JointFixedSolverBody, JointOneBodyConstraint, JointTwoBodyConstraint, WritebackId,
 };
 use crate::dynamics::solver::joint_constraint::JointSolverBody;
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::ConstraintsCounts;
 use crate::dynamics::solver::MotorParameters;
-use crate::dynamics::{GenericJoint, ImpulseJoint, IntegrationParameters, JointIndex};
+use crate::dynamics::{
+    GenericJoint, ImpulseJoint, IntegrationParameters, JointIndex, JointSoftness,
+};
 use crate::math::{AngVector, Isometry, Matrix, Point, Real, Rotation, Vector, ANG_DIM, DIM};
 use crate::prelude::RigidBodySet;
 use crate::utils;
@@ -16,13 +18,17 @@ use na::SMatrix;
 use crate::utils::{SimdBasis, SimdQuat};
 
 #[cfg(feature = "simd-is-enabled")]
-use crate::math::{SimdReal, SIMD_WIDTH};
+use {
+    crate::math::{SimdReal, SIMD_WIDTH},
+    na::SimdValue,
+};
 
 pub struct JointTwoBodyConstraintBuilder {
     body1: usize,
     body2: usize,
     joint_id: JointIndex,
     joint: GenericJoint,
+    softness: Option<JointSoftness>,
     constraint_id: usize,
 }
 
@@ -41,6 +47,7 @@ impl JointTwoBodyConstraintBuilder {
             body2: rb2.ids.active_set_offset,
             joint_id,
             joint: joint.data,
+            softness: joint.softness,
             constraint_id: *out_constraint_id,
         };
 
@@ -51,32 +58,32 @@ impl JointTwoBodyConstraintBuilder {
     pub fn update(
         &self,
         params: &IntegrationParameters,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         out: &mut [JointTwoBodyConstraint<Real, 1>],
     ) {
         // NOTE: right now, the "update", is basically reconstructing all the
         //       constraints. Could we make this more incremental?
 
-        let rb1 = &bodies[self.body1];
-        let rb2 = &bodies[self.body2];
-        let frame1 = rb1.position * self.joint.local_frame1;
-        let frame2 = rb2.position * self.joint.local_frame2;
+        let params = params.with_joint_softness(self.softness.as_ref());
+
+        let frame1 = bodies.positions[self.body1] * self.joint.local_frame1;
+        let frame2 = bodies.positions[self.body2] * self.joint.local_frame2;
 
         let joint_body1 = JointSolverBody {
-            im: rb1.im,
-            sqrt_ii: rb1.sqrt_ii,
-            world_com: rb1.world_com,
+            im: bodies.ims[self.body1],
+            sqrt_ii: bodies.sqrt_iis[self.body1],
+            world_com: bodies.world_coms[self.body1],
             solver_vel: [self.body1],
         };
         let joint_body2 = JointSolverBody {
-            im: rb2.im,
-            sqrt_ii: rb2.sqrt_ii,
-            world_com: rb2.world_com,
+            im: bodies.ims[self.body2],
+            sqrt_ii: bodies.sqrt_iis[self.body2],
+            world_com: bodies.world_coms[self.body2],
             solver_vel: [self.body2],
         };
 
         JointTwoBodyConstraint::<Real, 1>::lock_axes(
-            params,
+            &params,
             self.joint_id,
             &joint_body1,
             &joint_body2,
@@ -148,18 +155,18 @@ impl JointTwoBodyConstraintBuilderSimd {
     pub fn update(
         &mut self,
         params: &IntegrationParameters,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         out: &mut [JointTwoBodyConstraint<SimdReal, SIMD_WIDTH>],
     ) {
         // NOTE: right now, the "update", is basically reconstructing all the
         //       constraints. Could we make this more incremental?
 
-        let rb1 = gather![|ii| &bodies[self.body1[ii]]];
-        let rb2 = gather![|ii| &bodies[self.body2[ii]]];
-        let frame1 = Isometry::from(gather![|ii| rb1[ii].position]) * self.local_frame1;
-        let frame2 = Isometry::from(gather![|ii| rb2[ii].position]) * self.local_frame2;
-        self.joint_body1.world_com = gather![|ii| rb1[ii].world_com].into();
-        self.joint_body2.world_com = gather![|ii| rb2[ii].world_com].into();
+        let frame1 =
+            Isometry::from(gather![|ii| bodies.positions[self.body1[ii]]]) * self.local_frame1;
+        let frame2 =
+            Isometry::from(gather![|ii| bodies.positions[self.body2[ii]]]) * self.local_frame2;
+        self.joint_body1.world_com = gather![|ii| bodies.world_coms[self.body1[ii]]].into();
+        self.joint_body2.world_com = gather![|ii| bodies.world_coms[self.body2[ii]]].into();
 
         JointTwoBodyConstraint::<SimdReal, SIMD_WIDTH>::lock_axes(
             params,
@@ -180,6 +187,7 @@ pub struct JointOneBodyConstraintBuilder {
     body2: usize,
     joint_id: JointIndex,
     joint: GenericJoint,
+    softness: Option<JointSoftness>,
     constraint_id: usize,
 }
 
@@ -217,6 +225,7 @@ impl JointOneBodyConstraintBuilder {
             body2: rb2.ids.active_set_offset,
             joint_id,
             joint: joint_data,
+            softness: joint.softness,
             constraint_id: *out_constraint_id,
         };
 
@@ -227,28 +236,31 @@ impl JointOneBodyConstraintBuilder {
     pub fn update(
         &self,
         params: &IntegrationParameters,
-        bodies: &[SolverBody],
+        solved_dt: Real,
+        bodies: &SolverBodies,
         out: &mut [JointOneBodyConstraint<Real, 1>],
     ) {
         // NOTE: right now, the "update", is basically reconstructing all the
         //       constraints. Could we make this more incremental?
 
-        let rb2 = &bodies[self.body2];
-        let frame2 = rb2.position * self.joint.local_frame2;
+        let params = params.with_joint_softness(self.softness.as_ref());
+        // Move the frame of the fixed body, if it’s kinematic, to its position at this substep.
+        let (body1, frame1) = self.body1.integrate(solved_dt, &self.frame1);
+        let frame2 = bodies.positions[self.body2] * self.joint.local_frame2;
 
         let joint_body2 = JointSolverBody {
-            im: rb2.im,
-            sqrt_ii: rb2.sqrt_ii,
-            world_com: rb2.world_com,
+            im: bodies.ims[self.body2],
+            sqrt_ii: bodies.sqrt_iis[self.body2],
+            world_com: bodies.world_coms[self.body2],
             solver_vel: [self.body2],
         };
 
         JointOneBodyConstraint::<Real, 1>::lock_axes(
-            params,
+            &params,
             self.joint_id,
-            &self.body1,
+            &body1,
             &joint_body2,
-            &self.frame1,
+            &frame1,
             &frame2,
             &self.joint,
             &mut out[self.constraint_id..],
@@ -313,28 +325,33 @@ impl JointOneBodyConstraintBuilderSimd {
     pub fn update(
         &self,
         params: &IntegrationParameters,
-        bodies: &[SolverBody],
+        solved_dt: Real,
+        bodies: &SolverBodies,
         out: &mut [JointOneBodyConstraint<SimdReal, SIMD_WIDTH>],
     ) {
         // NOTE: right now, the "update", is basically reconstructing all the
         //       constraints. Could we make this more incremental?
 
-        let rb2 = gather![|ii| &bodies[self.body2[ii]]];
-        let frame2 = Isometry::from(gather![|ii| rb2[ii].position]) * self.local_frame2;
+        let (body1, frame1) = self
+            .body1
+            .integrate(SimdReal::splat(solved_dt), &self.frame1);
+
+        let frame2 =
+            Isometry::from(gather![|ii| bodies.positions[self.body2[ii]]]) * self.local_frame2;
 
         let joint_body2 = JointSolverBody {
-            im: gather![|ii| rb2[ii].im].into(),
-            sqrt_ii: gather![|ii| rb2[ii].sqrt_ii].into(),
-            world_com: gather![|ii| rb2[ii].world_com].into(),
+            im: gather![|ii| bodies.ims[self.body2[ii]]].into(),
+            sqrt_ii: gather![|ii| bodies.sqrt_iis[self.body2[ii]]].into(),
+            world_com: gather![|ii| bodies.world_coms[self.body2[ii]]].into(),
             solver_vel: self.body2,
         };
 
         JointOneBodyConstraint::<SimdReal, SIMD_WIDTH>::lock_axes(
             params,
             self.joint_id,
-            &self.body1,
+            &body1,
             &joint_body2,
-            &self.frame1,
+            &frame1,
             &frame2,
             self.locked_axes,
             &mut out[self.constraint_id..],
@@ -1,5 +1,5 @@
 use crate::dynamics::solver::categorization::categorize_joints;
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::solver_vel::SolverVel;
 use crate::dynamics::solver::{
     reset_buffer, JointConstraintTypes, JointGenericOneBodyConstraint,
@@ -396,9 +396,12 @@ impl JointConstraintsSet {
     pub fn update(
         &mut self,
         params: &IntegrationParameters,
+        small_step_id: usize,
         multibodies: &MultibodyJointSet,
-        solver_bodies: &[SolverBody],
+        solver_bodies: &SolverBodies,
     ) {
+        let solved_dt = small_step_id as Real * params.dt;
+
         for builder in &mut self.generic_velocity_constraints_builder {
             builder.update(
                 params,
@@ -431,6 +434,7 @@ impl JointConstraintsSet {
         for builder in &mut self.velocity_one_body_constraints_builder {
             builder.update(
                 params,
+                solved_dt,
                 solver_bodies,
                 &mut self.velocity_one_body_constraints,
             );
@@ -440,6 +444,7 @@ impl JointConstraintsSet {
         for builder in &mut self.simd_velocity_one_body_constraints_builder {
             builder.update(
                 params,
+                solved_dt,
                 solver_bodies,
                 &mut self.simd_velocity_one_body_constraints,
             );
@@ -7,8 +7,8 @@ use crate::dynamics::solver::joint_constraint::joint_velocity_constraint::{
 use crate::dynamics::solver::joint_constraint::{JointSolverBody, JointTwoBodyConstraintHelper};
 use crate::dynamics::solver::MotorParameters;
 use crate::dynamics::{
-    GenericJoint, ImpulseJoint, IntegrationParameters, JointIndex, Multibody, MultibodyJointSet,
-    MultibodyLinkId, RigidBodySet,
+    GenericJoint, ImpulseJoint, IntegrationParameters, JointIndex, JointSoftness, Multibody,
+    MultibodyJointSet, MultibodyLinkId, RigidBodySet,
 };
 use crate::math::{Real, Vector, ANG_DIM, DIM, SPATIAL_DIM};
 use crate::utils;
@@ -16,7 +16,7 @@ use crate::utils::IndexMut2;
 use crate::utils::SimdDot;
 use na::{DVector, SVector};
 
-use crate::dynamics::solver::solver_body::SolverBody;
+use crate::dynamics::solver::solver_body::SolverBodies;
 use crate::dynamics::solver::ConstraintsCounts;
 #[cfg(feature = "dim3")]
 use crate::utils::SimdAngularInertia;
@@ -36,6 +36,7 @@ pub struct JointGenericTwoBodyConstraintBuilder {
     link2: LinkOrBody,
     joint_id: JointIndex,
     joint: GenericJoint,
+    softness: Option<JointSoftness>,
     j_id: usize,
     // These are solver body for both joints, except that
     // the world_com is actually in local-space.
@@ -52,6 +53,7 @@ impl JointGenericTwoBodyConstraintBuilder {
             link2: LinkOrBody::Body(usize::MAX),
             joint_id: JointIndex::MAX,
             joint: GenericJoint::default(),
+            softness: None,
             j_id: usize::MAX,
             local_body1: JointSolverBody::invalid(),
             local_body2: JointSolverBody::invalid(),
@@ -134,6 +136,7 @@ impl JointGenericTwoBodyConstraintBuilder {
             link2,
             joint_id,
             joint: joint.data,
+            softness: joint.softness,
             j_id: starting_j_id,
             local_body1,
             local_body2,
@@ -148,7 +151,7 @@ impl JointGenericTwoBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         multibodies: &MultibodyJointSet,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         jacobians: &mut DVector<Real>,
         out: &mut [JointGenericTwoBodyConstraint],
     ) {
@@ -171,7 +174,7 @@ impl JointGenericTwoBodyConstraintBuilder {
                 mb1 = Some((mb, link.id));
             }
             LinkOrBody::Body(body1) => {
-                pos1 = &bodies[body1].position;
+                pos1 = &bodies.positions[body1];
                 mb1 = None;
             }
         };
@@ -182,13 +185,14 @@ impl JointGenericTwoBodyConstraintBuilder {
                 mb2 = Some((mb, link.id));
             }
             LinkOrBody::Body(body2) => {
-                pos2 = &bodies[body2].position;
+                pos2 = &bodies.positions[body2];
                 mb2 = None;
             }
         };
 
         let frame1 = pos1 * self.joint.local_frame1;
         let frame2 = pos2 * self.joint.local_frame2;
+        let params = params.with_joint_softness(self.softness.as_ref());
 
         let joint_body1 = JointSolverBody {
             world_com: pos1 * self.local_body1.world_com, // the world_com was stored in local-space.
@@ -202,7 +206,7 @@ impl JointGenericTwoBodyConstraintBuilder {
         let mut j_id = self.j_id;
 
         JointGenericTwoBodyConstraint::lock_axes(
-            params,
+            &params,
             self.joint_id,
             &joint_body1,
             &joint_body2,
@@ -242,7 +246,7 @@ impl JointGenericOneBodyConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         multibodies: &MultibodyJointSet,
-        bodies: &[SolverBody],
+        bodies: &SolverBodies,
         jacobians: &mut DVector<Real>,
         out: &mut [JointGenericOneBodyConstraint],
     ) {
@@ -318,6 +322,7 @@ pub struct JointGenericVelocityOneBodyExternalConstraintBuilder {
     link2: MultibodyLinkId,
     joint_id: JointIndex,
     joint: GenericJoint,
+    softness: Option<JointSoftness>,
     j_id: usize,
     constraint_id: usize,
     // These are solver body for both joints, except that
@@ -393,6 +398,7 @@ impl JointGenericVelocityOneBodyExternalConstraintBuilder {
             link2,
             joint_id,
             joint: joint_data,
+            softness: joint.softness,
             j_id: starting_j_id,
             frame1,
             local_body2,
@@ -406,7 +412,7 @@ impl JointGenericVelocityOneBodyExternalConstraintBuilder {
         &self,
         params: &IntegrationParameters,
         multibodies: &MultibodyJointSet,
-        _bodies: &[SolverBody],
+        _bodies: &SolverBodies,
         jacobians: &mut DVector<Real>,
         out: &mut [JointGenericOneBodyConstraint],
     ) {
@@ -415,6 +421,7 @@ impl JointGenericVelocityOneBodyExternalConstraintBuilder {
         let mb2 = &multibodies[self.link2.multibody];
         let pos2 = &mb2.link(self.link2.id).unwrap().local_to_world;
         let frame2 = pos2 * self.joint.local_frame2;
+        let params = params.with_joint_softness(self.softness.as_ref());
 
         let joint_body2 = JointSolverBody {
             world_com: pos2 * self.local_body2.world_com, // the world_com was stored in local-space.
@@ -424,7 +431,7 @@ impl JointGenericVelocityOneBodyExternalConstraintBuilder {
         let mut j_id = self.j_id;
 
         JointGenericOneBodyConstraint::lock_axes(
-            params,
+            &params,
             self.joint_id,
             &self.body1,
             &joint_body2,
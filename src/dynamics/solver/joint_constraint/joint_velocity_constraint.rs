@@ -3,7 +3,9 @@ use crate::dynamics::solver::SolverVel;
 use crate::dynamics::{
     GenericJoint, IntegrationParameters, JointAxesMask, JointGraphEdge, JointIndex,
 };
-use crate::math::{AngVector, AngularInertia, Isometry, Point, Real, Vector, DIM, SPATIAL_DIM};
+use crate::math::{
+    AngVector, AngularInertia, Isometry, Point, Real, Translation, Vector, DIM, SPATIAL_DIM,
+};
 use crate::num::Zero;
 use crate::utils::{SimdDot, SimdRealCopy};
 
@@ -82,6 +84,18 @@ impl<N: SimdRealCopy> JointFixedSolverBody<N> {
             world_com: Point::origin(),
         }
     }
+
+    /// This body and `frame`, attached to it, after moving for `dt` at the body’s velocity.
+    pub fn integrate(&self, dt: N, frame: &Isometry<N>) -> (Self, Isometry<N>) {
+        let shift = Translation::from(self.world_com.coords);
+        let new_frame =
+            shift * Isometry::new(self.linvel * dt, self.angvel * dt) * shift.inverse() * frame;
+        let new_body = Self {
+            world_com: self.world_com + self.linvel * dt,
+            ..*self
+        };
+        (new_body, new_frame)
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
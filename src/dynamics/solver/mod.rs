@@ -1,5 +1,11 @@
+//! The constraints solver, and the tools it uses to partition the constraints.
+
 // #[cfg(not(feature = "parallel"))]
+pub use self::constraint_graph_coloring::{ColoredConstraint, ConstraintGraphColoring};
 pub(crate) use self::island_solver::IslandSolver;
+pub use self::user_constraint::{
+    UserConstraint, UserConstraintHandle, UserConstraintRow, UserConstraintSet,
+};
 // #[cfg(feature = "parallel")]
 // pub(crate) use self::parallel_island_solver::{ParallelIslandSolver, ThreadContext};
 // #[cfg(feature = "parallel")]
@@ -14,12 +20,14 @@ use self::velocity_solver::VelocitySolver;
 use contact_constraint::*;
 use interaction_groups::*;
 pub(crate) use joint_constraint::MotorParameters;
-pub use joint_constraint::*;
-use solver_body::SolverBody;
-use solver_constraints_set::{AnyConstraintMut, ConstraintTypes};
+pub(crate) use joint_constraint::*;
+use solver_body::SolverBodies;
+use solver_constraints_set::{AnyConstraintMut, ConstraintIndex, ConstraintTypes};
 use solver_vel::SolverVel;
+use user_constraint::UserConstraintRows;
 
 mod categorization;
+mod constraint_graph_coloring;
 mod contact_constraint;
 mod interaction_groups;
 // #[cfg(not(feature = "parallel"))]
@@ -35,11 +43,12 @@ mod solver_body;
 // #[cfg(not(feature = "parallel"))]
 mod solver_constraints_set;
 mod solver_vel;
+mod user_constraint;
 // #[cfg(not(feature = "parallel"))]
 mod velocity_solver;
 
 // TODO: SAFETY: restrict with bytemuck::AnyBitPattern to make this safe.
-pub unsafe fn reset_buffer<T>(buffer: &mut Vec<T>, len: usize) {
+pub(crate) unsafe fn reset_buffer<T>(buffer: &mut Vec<T>, len: usize) {
     buffer.clear();
     buffer.reserve(len);
     buffer.as_mut_ptr().write_bytes(u8::MAX, len);
@@ -5,6 +5,10 @@ use crate::prelude::RigidBodyDamping;
 #[cfg(feature = "dim2")]
 use crate::num::Zero;
 
+/// The state of the fixed (or kinematic) body of a one-body contact constraint, captured when
+/// building the constraint.
+///
+/// The bodies actually solved are stored in [`SolverBodies`] instead.
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct SolverBody {
     pub position: Isometry<Real>,
@@ -45,15 +49,60 @@ impl SolverBody {
             local_com: rb.mprops.local_mprops.local_com,
         }
     }
+}
+
+/// The solver bodies of an island, stored as a structure-of-arrays.
+///
+/// Each field is indexed by the `active_set_offset` of the corresponding rigid-body. Splitting
+/// the fields this way lets the constraints updates (that only read the positions, centers of
+/// mass and mass properties) and the position integration stream through contiguous memory
+/// instead of skipping over the data they don’t need.
+///
+/// The velocities solved by the constraints aren’t part of this set: they are still stored as
+/// one `SolverVel` per body, since their linear and angular parts are always read and written
+/// together by the constraints. Neither these arrays nor the `SolverVel` are padded to cache lines.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolverBodies {
+    pub positions: Vec<Isometry<Real>>,
+    pub world_coms: Vec<Point<Real>>,
+    pub ims: Vec<Vector<Real>>,
+    pub sqrt_iis: Vec<AngularInertia<Real>>,
+    pub integrated_vels: Vec<RigidBodyVelocity>,
+    pub dampings: Vec<RigidBodyDamping>,
+    pub local_coms: Vec<Point<Real>>,
+}
+
+impl SolverBodies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clears this set and resizes it to `len` default solver bodies.
+    pub fn reset(&mut self, len: usize) {
+        self.positions.clear();
+        self.positions.resize(len, Isometry::identity());
+        self.world_coms.clear();
+        self.world_coms.resize(len, Point::origin());
+        self.ims.clear();
+        self.ims.resize(len, na::zero());
+        self.sqrt_iis.clear();
+        self.sqrt_iis.resize(len, AngularInertia::zero());
+        self.integrated_vels.clear();
+        self.integrated_vels.resize(len, RigidBodyVelocity::zero());
+        self.dampings.clear();
+        self.dampings.resize(len, RigidBodyDamping::default());
+        self.local_coms.clear();
+        self.local_coms.resize(len, Point::origin());
+    }
 
-    pub fn copy_from(&mut self, rb: &RigidBody) {
-        self.position = rb.pos.position;
-        self.integrated_vels = RigidBodyVelocity::zero();
-        self.im = rb.mprops.effective_inv_mass;
-        self.sqrt_ii = rb.mprops.effective_world_inv_inertia_sqrt;
-        self.world_com = rb.mprops.world_com;
-        self.ccd_thickness = rb.ccd.ccd_thickness;
-        self.damping = rb.damping;
-        self.local_com = rb.mprops.local_mprops.local_com;
+    /// Sets the `i`-th solver body from the given rigid-body.
+    pub fn copy_from(&mut self, i: usize, rb: &RigidBody) {
+        self.positions[i] = rb.pos.position;
+        self.world_coms[i] = rb.mprops.world_com;
+        self.ims[i] = rb.mprops.effective_inv_mass;
+        self.sqrt_iis[i] = rb.mprops.effective_world_inv_inertia_sqrt;
+        self.integrated_vels[i] = RigidBodyVelocity::zero();
+        self.dampings[i] = rb.damping;
+        self.local_coms[i] = rb.mprops.local_mprops.local_com;
     }
 }
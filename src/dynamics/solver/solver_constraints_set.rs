@@ -37,8 +37,27 @@ pub enum AnyConstraintMut<'a, Constraints: ConstraintTypes> {
     SimdTwoBodies(&'a mut Constraints::SimdTwoBodies),
 }
 
+/// The index of a constraint in one of the constraint vectors of a `SolverConstraintsSet`.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum ConstraintIndex {
+    OneBody(usize),
+    TwoBodies(usize),
+    GenericOneBody(usize),
+    GenericTwoBodies(usize),
+    #[cfg(feature = "simd-is-enabled")]
+    SimdOneBody(usize),
+    #[cfg(feature = "simd-is-enabled")]
+    SimdTwoBodies(usize),
+}
+
 pub(crate) struct SolverConstraintsSet<Constraints: ConstraintTypes> {
     pub generic_jacobians: DVector<Real>,
+    /// The order the constraints are solved in. If empty, they are solved in the order of the
+    /// constraint vectors.
+    pub solve_order: Vec<ConstraintIndex>,
+    /// The end of each group of consecutive constraints of `solve_order` sharing the same
+    /// priority.
+    pub solve_order_groups: Vec<usize>,
     pub two_body_interactions: Vec<usize>,
     pub one_body_interactions: Vec<usize>,
     pub generic_two_body_interactions: Vec<usize>,
@@ -69,6 +88,8 @@ impl<Constraints: ConstraintTypes> SolverConstraintsSet<Constraints> {
     pub fn new() -> Self {
         Self {
             generic_jacobians: DVector::zeros(0),
+            solve_order: vec![],
+            solve_order_groups: vec![],
             two_body_interactions: vec![],
             one_body_interactions: vec![],
             generic_two_body_interactions: vec![],
@@ -173,6 +194,8 @@ impl<Constraints: ConstraintTypes> SolverConstraintsSet<Constraints> {
 
     pub fn clear_constraints(&mut self) {
         self.generic_jacobians.fill(0.0);
+        self.solve_order.clear();
+        self.solve_order_groups.clear();
         self.velocity_constraints.clear();
         self.velocity_one_body_constraints.clear();
         self.generic_velocity_constraints.clear();
@@ -239,4 +262,44 @@ impl<Constraints: ConstraintTypes> SolverConstraintsSet<Constraints> {
         #[cfg(not(feature = "simd-is-enabled"))]
         return (jac, a.chain(b).chain(d).chain(e));
     }
+
+    /// Calls `f` on each constraint, following `self.solve_order` if it isn’t empty.
+    pub fn for_each_constraint_mut(
+        &mut self,
+        mut f: impl FnMut(&DVector<Real>, AnyConstraintMut<Constraints>),
+    ) {
+        if self.solve_order.is_empty() {
+            let (jac, constraints) = self.iter_constraints_mut();
+            for c in constraints {
+                f(jac, c);
+            }
+            return;
+        }
+
+        for id in &self.solve_order {
+            let c = match *id {
+                ConstraintIndex::OneBody(i) => {
+                    AnyConstraintMut::OneBody(&mut self.velocity_one_body_constraints[i])
+                }
+                ConstraintIndex::TwoBodies(i) => {
+                    AnyConstraintMut::TwoBodies(&mut self.velocity_constraints[i])
+                }
+                ConstraintIndex::GenericOneBody(i) => AnyConstraintMut::GenericOneBody(
+                    &mut self.generic_velocity_one_body_constraints[i],
+                ),
+                ConstraintIndex::GenericTwoBodies(i) => {
+                    AnyConstraintMut::GenericTwoBodies(&mut self.generic_velocity_constraints[i])
+                }
+                #[cfg(feature = "simd-is-enabled")]
+                ConstraintIndex::SimdOneBody(i) => {
+                    AnyConstraintMut::SimdOneBody(&mut self.simd_velocity_one_body_constraints[i])
+                }
+                #[cfg(feature = "simd-is-enabled")]
+                ConstraintIndex::SimdTwoBodies(i) => {
+                    AnyConstraintMut::SimdTwoBodies(&mut self.simd_velocity_constraints[i])
+                }
+            };
+            f(&self.generic_jacobians, c);
+        }
+    }
 }
@@ -0,0 +1,602 @@
+use crate::data::arena::Arena;
+use crate::dynamics::solver::SolverVel;
+use crate::dynamics::{
+    IntegrationParameters, IslandManager, MultibodyJointSet, RigidBody, RigidBodyHandle,
+    RigidBodySet,
+};
+use crate::math::{AngVector, Real, Vector};
+use crate::utils::{SimdAngularInertia, SimdDot};
+use parry::utils::hashmap::HashMap;
+use std::sync::Arc;
+
+/// The unique identifier of a user-defined constraint added to a [`UserConstraintSet`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct UserConstraintHandle(pub crate::data::arena::Index);
+
+impl UserConstraintHandle {
+    /// Converts this handle into its (index, generation) components.
+    pub fn into_raw_parts(self) -> (u32, u32) {
+        self.0.into_raw_parts()
+    }
+
+    /// Reconstructs an handle from its (index, generation) components.
+    pub fn from_raw_parts(id: u32, generation: u32) -> Self {
+        Self(crate::data::arena::Index::from_raw_parts(id, generation))
+    }
+}
+
+/// A scalar velocity constraint between two rigid-bodies, generated by a [`UserConstraint`].
+///
+/// The solver looks for the impulse `λ` within [`Self::impulse_bounds`] such that
+/// `lin_jac1 · linvel1 + ang_jac1 · angvel1 + lin_jac2 · linvel2 + ang_jac2 · angvel2 = rhs`.
+/// The first body then receives the linear impulse `λ * lin_jac1` and the angular impulse
+/// `λ * ang_jac1` (at its center of mass), and similarly for the second body.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UserConstraintRow {
+    /// The part of the jacobian applied to the linear velocity of the first body.
+    pub lin_jac1: Vector<Real>,
+    /// The part of the jacobian applied to the angular velocity of the first body.
+    pub ang_jac1: AngVector<Real>,
+    /// The part of the jacobian applied to the linear velocity of the second body.
+    pub lin_jac2: Vector<Real>,
+    /// The part of the jacobian applied to the angular velocity of the second body.
+    pub ang_jac2: AngVector<Real>,
+    /// The target value of the jacobian times the velocities of the bodies, without
+    /// [`Self::bias`].
+    pub rhs: Real,
+    /// The part of the target velocity correcting position errors, e.g., `-erp * error / dt`.
+    ///
+    /// It is added to [`Self::rhs`], except during the stabilization iterations of the solver
+    /// which remove the velocities it introduced.
+    pub bias: Real,
+    /// The minimum and maximum impulses this row can apply during the whole timestep.
+    pub impulse_bounds: [Real; 2],
+}
+
+impl Default for UserConstraintRow {
+    fn default() -> Self {
+        Self {
+            lin_jac1: Vector::zeros(),
+            ang_jac1: na::zero(),
+            lin_jac2: Vector::zeros(),
+            ang_jac2: na::zero(),
+            rhs: 0.0,
+            bias: 0.0,
+            impulse_bounds: [-Real::MAX, Real::MAX],
+        }
+    }
+}
+
+/// A user-defined constraint, solved together with the contacts and joints of its island.
+///
+/// At each timestep, the constraint generates its [`UserConstraintRow`]s from the current state
+/// of its two rigid-bodies. Each row is then solved by every PGS and stabilization iteration of
+/// every substep, right after the joints.
+///
+/// The impulses of the rows are warmstarted from the previous substep, and from the last substep
+/// of the previous timestep if the constraint generates the same number of rows again. User
+/// constraints don’t support multibody links: the constraints attached to one are ignored.
+pub trait UserConstraint: Send + Sync {
+    /// Appends to `rows` the velocity constraints to solve during this timestep.
+    ///
+    /// This is called once per timestep, before the velocities are solved. `params.dt` is the
+    /// length of the whole timestep.
+    fn rows(
+        &self,
+        params: &IntegrationParameters,
+        body1: &RigidBody,
+        body2: &RigidBody,
+        rows: &mut Vec<UserConstraintRow>,
+    );
+}
+
+#[derive(Clone)]
+struct UserConstraintEntry {
+    body1: RigidBodyHandle,
+    body2: RigidBodyHandle,
+    constraint: Arc<dyn UserConstraint>,
+    // The impulses of the rows during the last substep, for warmstarting.
+    impulses: Vec<Real>,
+}
+
+/// A set of user-defined constraints, solved by
+/// [`PhysicsPipeline::step_with_user_constraints`](crate::pipeline::PhysicsPipeline::step_with_user_constraints).
+///
+/// Like joints, user constraints link the islands of their bodies, so that both their bodies
+/// are solved and put to sleep together. The constraints attached to a rigid-body removed from
+/// its [`RigidBodySet`] are ignored until they are removed from this set.
+///
+/// Cloning this set shares the constraints, which are immutable, between both sets.
+#[derive(Clone, Default)]
+pub struct UserConstraintSet {
+    constraints: Arena<UserConstraintEntry>,
+    body_constraints: HashMap<RigidBodyHandle, Vec<UserConstraintHandle>>,
+    pub(crate) to_wake_up: Vec<RigidBodyHandle>,
+}
+
+impl UserConstraintSet {
+    /// Creates a new empty set of user constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of constraints in this set.
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// `true` if there are no constraints in this set.
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    /// Adds a constraint between two rigid-bodies.
+    ///
+    /// If `wake_up` is set to `true`, then the bodies attached to this constraint will be
+    /// automatically woken up during the next timestep.
+    pub fn insert(
+        &mut self,
+        body1: RigidBodyHandle,
+        body2: RigidBodyHandle,
+        constraint: impl UserConstraint + 'static,
+        wake_up: bool,
+    ) -> UserConstraintHandle {
+        let handle = UserConstraintHandle(self.constraints.insert(UserConstraintEntry {
+            body1,
+            body2,
+            constraint: Arc::new(constraint),
+            impulses: vec![],
+        }));
+
+        for body in [body1, body2] {
+            self.body_constraints.entry(body).or_default().push(handle);
+        }
+
+        if wake_up {
+            self.to_wake_up.extend([body1, body2]);
+        }
+
+        handle
+    }
+
+    /// Removes a constraint from this set.
+    ///
+    /// If `wake_up` is set to `true`, then the bodies attached to this constraint will be
+    /// automatically woken up during the next timestep.
+    pub fn remove(
+        &mut self,
+        handle: UserConstraintHandle,
+        wake_up: bool,
+    ) -> Option<Arc<dyn UserConstraint>> {
+        let entry = self.constraints.remove(handle.0)?;
+
+        for body in [entry.body1, entry.body2] {
+            if let Some(constraints) = self.body_constraints.get_mut(&body) {
+                constraints.retain(|h| *h != handle);
+                if constraints.is_empty() {
+                    let _ = self.body_constraints.remove(&body);
+                }
+            }
+        }
+
+        if wake_up {
+            self.to_wake_up.extend([entry.body1, entry.body2]);
+        }
+
+        Some(entry.constraint)
+    }
+
+    /// The constraint identified by `handle`.
+    pub fn get(&self, handle: UserConstraintHandle) -> Option<&dyn UserConstraint> {
+        self.constraints
+            .get(handle.0)
+            .map(|entry| &*entry.constraint)
+    }
+
+    /// The two rigid-bodies attached to the constraint identified by `handle`.
+    pub fn bodies(&self, handle: UserConstraintHandle) -> Option<[RigidBodyHandle; 2]> {
+        self.constraints
+            .get(handle.0)
+            .map(|entry| [entry.body1, entry.body2])
+    }
+
+    /// Iterates through all the constraints of this set and the rigid-bodies they are attached to.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            UserConstraintHandle,
+            [RigidBodyHandle; 2],
+            &dyn UserConstraint,
+        ),
+    > + '_ {
+        self.constraints.iter().map(|(id, entry)| {
+            (
+                UserConstraintHandle(id),
+                [entry.body1, entry.body2],
+                &*entry.constraint,
+            )
+        })
+    }
+
+    /// The handles of the constraints attached to the given rigid-body.
+    pub fn attached_constraints(
+        &self,
+        body: RigidBodyHandle,
+    ) -> impl Iterator<Item = UserConstraintHandle> + '_ {
+        self.body_constraints
+            .get(&body)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// The rigid-bodies linked to `body` by a constraint.
+    pub(crate) fn attached_bodies(
+        &self,
+        body: RigidBodyHandle,
+    ) -> impl Iterator<Item = RigidBodyHandle> + '_ {
+        self.attached_constraints(body).map(move |handle| {
+            let entry = &self.constraints[handle.0];
+            crate::utils::select_other((entry.body1, entry.body2), body)
+        })
+    }
+
+    /// Assigns each constraint involving an awake dynamic rigid-body to its active island.
+    pub(crate) fn select_active_constraints(
+        &self,
+        islands: &IslandManager,
+        bodies: &RigidBodySet,
+        out: &mut [Vec<UserConstraintHandle>],
+    ) {
+        for out_island in &mut out[..islands.num_islands()] {
+            out_island.clear();
+        }
+
+        if self.is_empty() {
+            return;
+        }
+
+        for (id, entry) in self.constraints.iter() {
+            let (Some(rb1), Some(rb2)) = (bodies.get(entry.body1), bodies.get(entry.body2)) else {
+                continue;
+            };
+
+            if (rb1.is_dynamic() || rb2.is_dynamic())
+                && (!rb1.is_dynamic() || !rb1.is_sleeping())
+                && (!rb2.is_dynamic() || !rb2.is_sleeping())
+            {
+                let island_index = if !rb1.is_dynamic() {
+                    rb2.ids.active_island_id
+                } else {
+                    rb1.ids.active_island_id
+                };
+
+                out[island_index].push(UserConstraintHandle(id));
+            }
+        }
+    }
+}
+
+/// The body-related part of a row, in the solver’s velocity space.
+#[derive(Copy, Clone, Debug)]
+struct SolverUserConstraintPart {
+    // `usize::MAX` if the body isn’t moved by the solver.
+    solver_vel: usize,
+    lin_jac: Vector<Real>,
+    im_lin_jac: Vector<Real>,
+    ii_ang_jac: AngVector<Real>,
+}
+
+impl SolverUserConstraintPart {
+    fn new(
+        rb: &RigidBody,
+        lin_jac: Vector<Real>,
+        ang_jac: AngVector<Real>,
+        rhs: &mut Real,
+    ) -> Self {
+        if rb.is_dynamic() {
+            Self {
+                solver_vel: rb.ids.active_set_offset,
+                lin_jac,
+                im_lin_jac: lin_jac.component_mul(&rb.mprops.effective_inv_mass),
+                ii_ang_jac: rb
+                    .mprops
+                    .effective_world_inv_inertia_sqrt
+                    .transform_vector(ang_jac),
+            }
+        } else {
+            // The velocity of this body isn’t affected by the solver.
+            *rhs -= lin_jac.dot(&rb.vels.linvel) + ang_jac.gdot(rb.vels.angvel);
+            Self {
+                solver_vel: usize::MAX,
+                lin_jac: Vector::zeros(),
+                im_lin_jac: Vector::zeros(),
+                ii_ang_jac: na::zero(),
+            }
+        }
+    }
+
+    fn velocity(&self, solver_vels: &[SolverVel<Real>]) -> Real {
+        if self.solver_vel == usize::MAX {
+            return 0.0;
+        }
+
+        let vels = &solver_vels[self.solver_vel];
+        self.lin_jac.dot(&vels.linear) + self.ii_ang_jac.gdot(vels.angular)
+    }
+
+    fn apply_impulse(&self, impulse: Real, solver_vels: &mut [SolverVel<Real>]) {
+        if self.solver_vel == usize::MAX {
+            return;
+        }
+
+        let vels = &mut solver_vels[self.solver_vel];
+        vels.linear += self.im_lin_jac * impulse;
+        vels.angular += self.ii_ang_jac * impulse;
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct SolverUserConstraintRow {
+    handle: UserConstraintHandle,
+    parts: [SolverUserConstraintPart; 2],
+    inv_lhs: Real,
+    rhs_wo_bias: Real,
+    rhs: Real,
+    // The bounds of the impulse of one substep.
+    impulse_bounds: [Real; 2],
+    impulse: Real,
+}
+
+/// The rows of the user constraints of an island, ready to be solved.
+#[derive(Default)]
+pub(crate) struct UserConstraintRows {
+    rows: Vec<UserConstraintRow>, // Workspace.
+    solver_rows: Vec<SolverUserConstraintRow>,
+}
+
+impl UserConstraintRows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generates the rows of the given constraints of an island.
+    ///
+    /// `params.dt` must be the length of the whole timestep, split into `num_substeps` substeps.
+    pub fn init(
+        &mut self,
+        params: &IntegrationParameters,
+        num_substeps: usize,
+        bodies: &RigidBodySet,
+        multibodies: &MultibodyJointSet,
+        user_constraints: &UserConstraintSet,
+        constraint_handles: &[UserConstraintHandle],
+    ) {
+        self.solver_rows.clear();
+        let substep_fraction = 1.0 / num_substeps as Real;
+
+        for handle in constraint_handles {
+            let Some(entry) = user_constraints.constraints.get(handle.0) else {
+                continue;
+            };
+            let rb1 = &bodies[entry.body1];
+            let rb2 = &bodies[entry.body2];
+
+            if multibodies.rigid_body_link(entry.body1).is_some()
+                || multibodies.rigid_body_link(entry.body2).is_some()
+            {
+                continue;
+            }
+
+            self.rows.clear();
+            entry.constraint.rows(params, rb1, rb2, &mut self.rows);
+            let warmstart = entry.impulses.len() == self.rows.len();
+
+            for (i, row) in self.rows.iter().enumerate() {
+                let mut rhs = row.rhs;
+                let parts = [
+                    SolverUserConstraintPart::new(rb1, row.lin_jac1, row.ang_jac1, &mut rhs),
+                    SolverUserConstraintPart::new(rb2, row.lin_jac2, row.ang_jac2, &mut rhs),
+                ];
+                let lhs: Real = parts
+                    .iter()
+                    .map(|part| {
+                        part.lin_jac.dot(&part.im_lin_jac) + part.ii_ang_jac.gdot(part.ii_ang_jac)
+                    })
+                    .sum();
+
+                let impulse_bounds = row.impulse_bounds.map(|bound| bound * substep_fraction);
+                let impulse = if warmstart {
+                    entry.impulses[i].clamp(impulse_bounds[0], impulse_bounds[1])
+                } else {
+                    0.0
+                };
+
+                self.solver_rows.push(SolverUserConstraintRow {
+                    handle: *handle,
+                    parts,
+                    inv_lhs: crate::utils::inv(lhs),
+                    rhs_wo_bias: rhs,
+                    rhs: rhs + row.bias,
+                    impulse_bounds,
+                    impulse,
+                });
+            }
+        }
+    }
+
+    /// Scales the impulses of the previous substep, at the start of a substep, by the warmstart
+    /// coefficient.
+    pub fn update(&mut self, params: &IntegrationParameters) {
+        for row in &mut self.solver_rows {
+            row.impulse *= params.warmstart_coefficient;
+        }
+    }
+
+    /// Applies the impulses of the previous substep at the start of a substep.
+    pub fn warmstart(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        for row in &self.solver_rows {
+            let [part1, part2] = &row.parts;
+            part1.apply_impulse(row.impulse, solver_vels);
+            part2.apply_impulse(row.impulse, solver_vels);
+        }
+    }
+
+    pub fn solve(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        self.solve_rows(solver_vels, true)
+    }
+
+    pub fn solve_wo_bias(&mut self, solver_vels: &mut [SolverVel<Real>]) {
+        self.solve_rows(solver_vels, false)
+    }
+
+    fn solve_rows(&mut self, solver_vels: &mut [SolverVel<Real>], with_bias: bool) {
+        for row in &mut self.solver_rows {
+            let [part1, part2] = &row.parts;
+            let rhs = if with_bias { row.rhs } else { row.rhs_wo_bias };
+            let dvel = part1.velocity(solver_vels) + part2.velocity(solver_vels) - rhs;
+            let new_impulse = (row.impulse - row.inv_lhs * dvel)
+                .clamp(row.impulse_bounds[0], row.impulse_bounds[1]);
+            let dimpulse = new_impulse - row.impulse;
+            row.impulse = new_impulse;
+
+            part1.apply_impulse(dimpulse, solver_vels);
+            part2.apply_impulse(dimpulse, solver_vels);
+        }
+    }
+
+    /// Stores the impulses of the last substep into the constraints for the next timestep.
+    pub fn writeback_impulses(&self, user_constraints: &mut UserConstraintSet) {
+        let mut rows = &self.solver_rows[..];
+
+        while let Some(first) = rows.first() {
+            let num_rows = rows
+                .iter()
+                .take_while(|row| row.handle == first.handle)
+                .count();
+            if let Some(entry) = user_constraints.constraints.get_mut(first.handle.0) {
+                entry.impulses.clear();
+                entry
+                    .impulses
+                    .extend(rows[..num_rows].iter().map(|row| row.impulse));
+            }
+            rows = &rows[num_rows..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{UserConstraint, UserConstraintRow, UserConstraintSet};
+    use crate::dynamics::{IntegrationParameters, RigidBody, RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    // Prevents the first body from moving downward, within an impulse budget.
+    struct VerticalSupport {
+        max_impulse: Real,
+    }
+
+    impl UserConstraint for VerticalSupport {
+        fn rows(
+            &self,
+            _: &IntegrationParameters,
+            _: &RigidBody,
+            _: &RigidBody,
+            rows: &mut Vec<UserConstraintRow>,
+        ) {
+            rows.push(UserConstraintRow {
+                lin_jac1: Vector::y(),
+                impulse_bounds: [0.0, self.max_impulse],
+                ..UserConstraintRow::default()
+            });
+        }
+    }
+
+    #[test]
+    fn removed_constraints_are_detached_from_their_bodies() {
+        let mut bodies = RigidBodySet::new();
+        let mut constraints = UserConstraintSet::new();
+        let body1 = bodies.insert(RigidBodyBuilder::dynamic());
+        let body2 = bodies.insert(RigidBodyBuilder::dynamic());
+
+        let support = VerticalSupport { max_impulse: 1.0 };
+        let handle = constraints.insert(body1, body2, support, false);
+        assert_eq!(constraints.len(), 1);
+        assert_eq!(constraints.bodies(handle), Some([body1, body2]));
+        assert_eq!(
+            constraints.attached_bodies(body2).collect::<Vec<_>>(),
+            [body1]
+        );
+        assert!(constraints.to_wake_up.is_empty());
+
+        assert!(constraints.remove(handle, true).is_some());
+        assert!(constraints.is_empty());
+        assert!(constraints.get(handle).is_none());
+        assert_eq!(constraints.attached_constraints(body1).count(), 0);
+        assert_eq!(constraints.to_wake_up, [body1, body2]);
+        assert!(constraints.remove(handle, true).is_none());
+    }
+
+    #[test]
+    fn user_constraints_are_solved_within_their_impulse_bounds() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        let dt = world.integration_parameters.dt;
+        let gravity = 9.81;
+
+        let ground = world.bodies.insert(RigidBodyBuilder::fixed());
+        let supported = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(-Vector::x() * 2.0));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            supported,
+            &mut world.bodies,
+        );
+        let half_supported = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 2.0));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            half_supported,
+            &mut world.bodies,
+        );
+        let half_weight_impulse = 0.5 * world.bodies[half_supported].mass() * gravity * dt;
+
+        let _ = world.user_constraints.insert(
+            supported,
+            ground,
+            VerticalSupport {
+                max_impulse: Real::MAX,
+            },
+            true,
+        );
+        let _ = world.user_constraints.insert(
+            half_supported,
+            ground,
+            VerticalSupport {
+                max_impulse: half_weight_impulse,
+            },
+            true,
+        );
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let time = 60.0 * dt;
+        let supported_vel = world.bodies[supported].linvel().y;
+        let half_supported_vel = world.bodies[half_supported].linvel().y;
+        assert!(supported_vel.abs() < 1.0e-3, "velocity: {supported_vel}");
+        assert!(
+            (half_supported_vel + 0.5 * gravity * time).abs() < 1.0e-2,
+            "velocity: {half_supported_vel}"
+        );
+    }
+}
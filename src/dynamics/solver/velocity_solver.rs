@@ -1,39 +1,123 @@
-use super::{JointConstraintTypes, SolverConstraintsSet};
-use crate::dynamics::solver::solver_body::SolverBody;
+use super::{JointConstraintTypes, SolverConstraintsSet, UserConstraintRows};
+use crate::dynamics::solver::solver_body::SolverBodies;
+#[cfg(feature = "parallel")]
+use crate::dynamics::solver::{contact_constraint::ContactConstraintsColoring, AnyConstraintMut};
 use crate::dynamics::{
     solver::{ContactConstraintTypes, SolverVel},
-    IntegrationParameters, IslandManager, JointGraphEdge, JointIndex, MultibodyJointSet,
-    MultibodyLinkId, RigidBodySet,
+    ContactFrictionMode, IntegrationParameters, IslandManager, JointGraphEdge, JointIndex,
+    MultibodyJointSet, MultibodyLinkId, PositionCorrectionMode, RigidBodySet,
 };
 use crate::geometry::{ContactManifold, ContactManifoldIndex};
-use crate::math::Real;
-use crate::prelude::RigidBodyVelocity;
+use crate::math::{AngularInertia, Isometry, Point, Real};
+use crate::prelude::{RigidBodyDamping, RigidBodyVelocity};
 use crate::utils::SimdAngularInertia;
 use na::DVector;
 
+/// Minimum number of contact constraints solved by each task when an island’s contact
+/// constraints are solved in parallel.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_CONTACT_TASK_SIZE: usize = 64;
+
+/// Minimum number of bodies integrated by each task when the velocities and positions of an
+/// island with parallel contact constraints are integrated in parallel.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_BODY_TASK_SIZE: usize = 256;
+
+/// A resolution stage of the contact constraints.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ContactSolveStage {
+    Warmstart,
+    Restitution,
+    RestitutionWoBias,
+    Friction,
+    /// The normal and friction impulses solved together, see [`ContactFrictionMode::Coupled`].
+    Coupled,
+    CoupledWoBias,
+}
+
+#[cfg(feature = "parallel")]
+impl ContactSolveStage {
+    fn run(
+        self,
+        params: &IntegrationParameters,
+        constraint: &mut AnyConstraintMut<ContactConstraintTypes>,
+        generic_jacobians: &DVector<Real>,
+        solver_vels: &mut [SolverVel<Real>],
+        generic_solver_vels: &mut DVector<Real>,
+    ) {
+        match self {
+            Self::Warmstart => {
+                constraint.warmstart(generic_jacobians, solver_vels, generic_solver_vels)
+            }
+            Self::Restitution => constraint.solve_restitution(
+                generic_jacobians,
+                solver_vels,
+                generic_solver_vels,
+                params.contact_block_solver,
+            ),
+            Self::RestitutionWoBias => {
+                constraint.remove_bias();
+                constraint.solve_restitution(
+                    generic_jacobians,
+                    solver_vels,
+                    generic_solver_vels,
+                    params.contact_block_solver,
+                )
+            }
+            Self::Friction => {
+                constraint.solve_friction(generic_jacobians, solver_vels, generic_solver_vels)
+            }
+            Self::Coupled => {
+                constraint.solve_coupled(generic_jacobians, solver_vels, generic_solver_vels)
+            }
+            Self::CoupledWoBias => {
+                constraint.remove_bias();
+                constraint.solve_coupled(generic_jacobians, solver_vels, generic_solver_vels)
+            }
+        }
+    }
+}
+
 pub(crate) struct VelocitySolver {
-    pub solver_bodies: Vec<SolverBody>,
+    pub solver_bodies: SolverBodies,
     pub solver_vels: Vec<SolverVel<Real>>,
     pub solver_vels_increment: Vec<SolverVel<Real>>,
     pub generic_solver_vels: DVector<Real>,
     pub generic_solver_vels_increment: DVector<Real>,
     pub multibody_roots: Vec<MultibodyLinkId>,
+    /// The number of PGS iterations run by the last call to `Self::solve_constraints`, over
+    /// all its substeps.
+    pub num_pgs_iterations: usize,
+    prev_solver_vels: Vec<SolverVel<Real>>,
+    prev_generic_solver_vels: DVector<Real>,
+    prev_impulses: Vec<Real>,
+    impulses: Vec<Real>,
+    #[cfg(feature = "parallel")]
+    contact_coloring: ContactConstraintsColoring,
 }
 
 impl VelocitySolver {
     pub fn new() -> Self {
         Self {
-            solver_bodies: Vec::new(),
+            solver_bodies: SolverBodies::new(),
             solver_vels: Vec::new(),
             solver_vels_increment: Vec::new(),
             generic_solver_vels: DVector::zeros(0),
             generic_solver_vels_increment: DVector::zeros(0),
             multibody_roots: Vec::new(),
+            num_pgs_iterations: 0,
+            prev_solver_vels: Vec::new(),
+            prev_generic_solver_vels: DVector::zeros(0),
+            prev_impulses: Vec::new(),
+            impulses: Vec::new(),
+            #[cfg(feature = "parallel")]
+            contact_coloring: ContactConstraintsColoring::new(),
         }
     }
 
     pub fn init_constraints(
         &self,
+        params: &IntegrationParameters,
         island_id: usize,
         islands: &IslandManager,
         bodies: &mut RigidBodySet,
@@ -46,6 +130,7 @@ impl VelocitySolver {
         joint_constraints: &mut SolverConstraintsSet<JointConstraintTypes>,
     ) {
         contact_constraints.init(
+            params,
             island_id,
             islands,
             bodies,
@@ -73,11 +158,8 @@ impl VelocitySolver {
         multibodies: &mut MultibodyJointSet,
     ) {
         self.multibody_roots.clear();
-        self.solver_bodies.clear();
-        self.solver_bodies.resize(
-            islands.active_island(island_id).len(),
-            SolverBody::default(),
-        );
+        self.solver_bodies
+            .reset(islands.active_island(island_id).len());
 
         self.solver_vels_increment.clear();
         self.solver_vels_increment
@@ -109,8 +191,7 @@ impl VelocitySolver {
                 let rb = &bodies[*handle];
                 let solver_vel = &mut self.solver_vels[rb.ids.active_set_offset];
                 let solver_vel_incr = &mut self.solver_vels_increment[rb.ids.active_set_offset];
-                let solver_body = &mut self.solver_bodies[rb.ids.active_set_offset];
-                solver_body.copy_from(rb);
+                self.solver_bodies.copy_from(rb.ids.active_set_offset, rb);
 
                 // NOTE: `dvel.angular` is actually storing angular velocity delta multiplied
                 //       by the square root of the inertia tensor:
@@ -158,43 +239,124 @@ impl VelocitySolver {
         multibodies: &mut MultibodyJointSet,
         contact_constraints: &mut SolverConstraintsSet<ContactConstraintTypes>,
         joint_constraints: &mut SolverConstraintsSet<JointConstraintTypes>,
+        user_constraints: &mut UserConstraintRows,
     ) {
+        #[cfg(feature = "parallel")]
+        {
+            #[cfg(not(feature = "simd-is-enabled"))]
+            let num_contact_constraints = contact_constraints.velocity_constraints.len()
+                + contact_constraints.velocity_one_body_constraints.len();
+            #[cfg(feature = "simd-is-enabled")]
+            let num_contact_constraints = contact_constraints.velocity_constraints.len()
+                + contact_constraints.velocity_one_body_constraints.len()
+                + contact_constraints.simd_velocity_constraints.len()
+                + contact_constraints.simd_velocity_one_body_constraints.len();
+
+            if num_contact_constraints >= params.parallel_island_split_size {
+                let _ = self
+                    .contact_coloring
+                    .color(self.solver_vels.len(), contact_constraints);
+            } else {
+                self.contact_coloring.clear();
+            }
+        }
+
+        // With the other position correction modes, the penetrations are corrected after the
+        // velocity resolution by `Self::solve_position_errors`.
+        let (restitution_stage, coupled_stage) = match params.position_correction_mode {
+            PositionCorrectionMode::Baumgarte => {
+                (ContactSolveStage::Restitution, ContactSolveStage::Coupled)
+            }
+            PositionCorrectionMode::SplitImpulse | PositionCorrectionMode::NonlinearGaussSeidel => {
+                (
+                    ContactSolveStage::RestitutionWoBias,
+                    ContactSolveStage::CoupledWoBias,
+                )
+            }
+        };
+        let coupled_friction = params.contact_friction_mode == ContactFrictionMode::Coupled;
+
+        let (num_pgs_iterations, max_residual, max_impulse_delta) = match &params
+            .velocity_convergence
+        {
+            Some(criterion) => (
+                criterion.max_internal_pgs_iterations.max(1),
+                (criterion.normalized_max_residual < Real::MAX)
+                    .then(|| criterion.normalized_max_residual * params.length_unit),
+                (criterion.max_impulse_delta < Real::MAX).then_some(criterion.max_impulse_delta),
+            ),
+            None => (params.num_internal_pgs_iterations, None, None),
+        };
+        self.num_pgs_iterations = 0;
+
         for substep_id in 0..num_substeps {
             let is_last_substep = substep_id == num_substeps - 1;
 
-            for (solver_vels, incr) in self
-                .solver_vels
-                .iter_mut()
-                .zip(self.solver_vels_increment.iter())
-            {
-                solver_vels.linear += incr.linear;
-                solver_vels.angular += incr.angular;
-            }
+            self.integrate_velocity_increments();
 
             self.generic_solver_vels += &self.generic_solver_vels_increment;
 
             /*
              * Update & solve constraints.
              */
-            joint_constraints.update(params, multibodies, &self.solver_bodies);
+            joint_constraints.update(params, substep_id, multibodies, &self.solver_bodies);
             contact_constraints.update(params, substep_id, multibodies, &self.solver_bodies);
+            user_constraints.update(params);
 
             if params.warmstart_coefficient != 0.0 {
-                contact_constraints.warmstart(&mut self.solver_vels, &mut self.generic_solver_vels);
+                user_constraints.warmstart(&mut self.solver_vels);
+                self.solve_contact_constraints(
+                    params,
+                    ContactSolveStage::Warmstart,
+                    contact_constraints,
+                );
             }
 
-            for _ in 0..params.num_internal_pgs_iterations {
+            for _ in 0..num_pgs_iterations {
+                if max_residual.is_some() {
+                    self.prev_solver_vels.clone_from(&self.solver_vels);
+                    self.prev_generic_solver_vels
+                        .clone_from(&self.generic_solver_vels);
+                }
+                if max_impulse_delta.is_some() {
+                    contact_constraints.gather_impulses(&mut self.prev_impulses);
+                }
+
                 joint_constraints.solve(&mut self.solver_vels, &mut self.generic_solver_vels);
-                contact_constraints
-                    .solve_restitution(&mut self.solver_vels, &mut self.generic_solver_vels);
-                contact_constraints
-                    .solve_friction(&mut self.solver_vels, &mut self.generic_solver_vels);
+                user_constraints.solve(&mut self.solver_vels);
+                if coupled_friction {
+                    self.solve_contact_constraints(params, coupled_stage, contact_constraints);
+                } else {
+                    self.solve_contact_constraints(params, restitution_stage, contact_constraints);
+                    self.solve_contact_constraints(
+                        params,
+                        ContactSolveStage::Friction,
+                        contact_constraints,
+                    );
+                }
+                self.num_pgs_iterations += 1;
+
+                let converged = (max_residual.is_some() || max_impulse_delta.is_some())
+                    && match max_residual {
+                        Some(max) => self.max_velocity_change() < max,
+                        None => true,
+                    }
+                    && match max_impulse_delta {
+                        Some(max) => self.max_impulse_change(contact_constraints) < max,
+                        None => true,
+                    };
+                if converged {
+                    break;
+                }
             }
 
             if is_last_substep {
                 for _ in 0..params.num_additional_friction_iterations {
-                    contact_constraints
-                        .solve_friction(&mut self.solver_vels, &mut self.generic_solver_vels);
+                    self.solve_contact_constraints(
+                        params,
+                        ContactSolveStage::Friction,
+                        contact_constraints,
+                    );
                 }
             }
 
@@ -206,18 +368,193 @@ impl VelocitySolver {
             /*
              * Resolution without bias.
              */
-            if params.num_internal_stabilization_iterations > 0 {
-                for _ in 0..params.num_internal_stabilization_iterations {
+            let num_stabilization_iterations = if is_last_substep {
+                params.num_internal_stabilization_iterations
+                    + params.num_additional_stabilization_iterations
+            } else {
+                params.num_internal_stabilization_iterations
+            };
+
+            if num_stabilization_iterations > 0 {
+                let stabilization_stage = if coupled_friction {
+                    ContactSolveStage::CoupledWoBias
+                } else {
+                    ContactSolveStage::RestitutionWoBias
+                };
+
+                for _ in 0..num_stabilization_iterations {
                     joint_constraints
                         .solve_wo_bias(&mut self.solver_vels, &mut self.generic_solver_vels);
-                    contact_constraints.solve_restitution_wo_bias(
-                        &mut self.solver_vels,
-                        &mut self.generic_solver_vels,
+                    user_constraints.solve_wo_bias(&mut self.solver_vels);
+                    self.solve_contact_constraints(
+                        params,
+                        stabilization_stage,
+                        contact_constraints,
                     );
                 }
 
-                contact_constraints
-                    .solve_friction(&mut self.solver_vels, &mut self.generic_solver_vels);
+                if !coupled_friction {
+                    self.solve_contact_constraints(
+                        params,
+                        ContactSolveStage::Friction,
+                        contact_constraints,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The largest change of a solver velocity component since the start of the last PGS
+    /// iteration.
+    fn max_velocity_change(&self) -> Real {
+        let mut max_change: Real = 0.0;
+
+        for (vel, prev) in self.solver_vels.iter().zip(self.prev_solver_vels.iter()) {
+            let change = *vel - *prev;
+            #[cfg(feature = "dim2")]
+            let angular_change = change.angular.abs();
+            #[cfg(feature = "dim3")]
+            let angular_change = change.angular.amax();
+            max_change = max_change.max(change.linear.amax()).max(angular_change);
+        }
+
+        if !self.generic_solver_vels.is_empty() {
+            max_change =
+                max_change.max((&self.generic_solver_vels - &self.prev_generic_solver_vels).amax());
+        }
+
+        max_change
+    }
+
+    /// The largest change of a contact impulse since the start of the last PGS iteration.
+    fn max_impulse_change(
+        &mut self,
+        contact_constraints: &SolverConstraintsSet<ContactConstraintTypes>,
+    ) -> Real {
+        contact_constraints.gather_impulses(&mut self.impulses);
+        self.impulses
+            .iter()
+            .zip(self.prev_impulses.iter())
+            .fold(0.0, |max, (impulse, prev)| max.max((impulse - prev).abs()))
+    }
+
+    /// Corrects the penetrations of the contacts left by the velocity resolution, with the
+    /// [`PositionCorrectionMode::SplitImpulse`] or [`PositionCorrectionMode::NonlinearGaussSeidel`]
+    /// modes.
+    ///
+    /// The contact constraints are solved on pseudo-velocities which only move the bodies, so the
+    /// velocities of the bodies are left untouched. This must be called after the impulses were
+    /// written back since it overwrites them. `params.dt` must be the length of the whole step.
+    /// The links of multibodies aren’t moved by this pass.
+    #[profiling::function]
+    pub fn solve_position_errors(
+        &mut self,
+        params: &IntegrationParameters,
+        multibodies: &MultibodyJointSet,
+        contact_constraints: &mut SolverConstraintsSet<ContactConstraintTypes>,
+    ) {
+        let relinearize = match params.position_correction_mode {
+            PositionCorrectionMode::Baumgarte => return,
+            PositionCorrectionMode::SplitImpulse => false,
+            PositionCorrectionMode::NonlinearGaussSeidel => true,
+        };
+
+        if params.num_position_iterations == 0 {
+            return;
+        }
+
+        let vels = std::mem::take(&mut self.solver_vels);
+        let generic_vels = std::mem::replace(&mut self.generic_solver_vels, DVector::zeros(0));
+
+        for i in 0..params.num_position_iterations {
+            if i == 0 || relinearize {
+                // Evaluate the penetrations at the current positions, at the end of the step.
+                contact_constraints.update(params, 1, multibodies, &self.solver_bodies);
+                contact_constraints.keep_only_position_bias();
+                self.solver_vels = vec![SolverVel::zero(); vels.len()];
+                self.generic_solver_vels = DVector::zeros(generic_vels.len());
+            }
+
+            self.solve_contact_constraints(
+                params,
+                ContactSolveStage::Restitution,
+                contact_constraints,
+            );
+
+            if relinearize || i == params.num_position_iterations - 1 {
+                self.integrate_pseudo_velocities(params.dt);
+            }
+        }
+
+        self.solver_vels = vels;
+        self.generic_solver_vels = generic_vels;
+    }
+
+    /// Moves the solver bodies with the pseudo-velocities stored in `self.solver_vels`, without
+    /// affecting their integrated velocities.
+    fn integrate_pseudo_velocities(&mut self, dt: Real) {
+        let solver_bodies = &mut self.solver_bodies;
+
+        for (i, pseudo_vels) in self.solver_vels.iter().enumerate() {
+            let pseudo_vels = RigidBodyVelocity {
+                linvel: pseudo_vels.linear,
+                angvel: solver_bodies.sqrt_iis[i].transform_vector(pseudo_vels.angular),
+            };
+            let local_com = &solver_bodies.local_coms[i];
+            let new_pos = pseudo_vels.integrate(dt, &solver_bodies.positions[i], local_com);
+            solver_bodies.world_coms[i] = new_pos * local_com;
+            solver_bodies.positions[i] = new_pos;
+        }
+    }
+
+    /// Runs a resolution stage on all the contact constraints.
+    ///
+    /// If the contact constraints were colored, each color is solved in parallel. Otherwise,
+    /// the constraints are solved sequentially.
+    fn solve_contact_constraints(
+        &mut self,
+        params: &IntegrationParameters,
+        stage: ContactSolveStage,
+        contact_constraints: &mut SolverConstraintsSet<ContactConstraintTypes>,
+    ) {
+        #[cfg(feature = "parallel")]
+        if self.contact_coloring.num_colors() > 0 {
+            self.contact_coloring.solve(
+                contact_constraints,
+                &mut self.solver_vels,
+                MIN_PARALLEL_CONTACT_TASK_SIZE,
+                |constraint, jac, solver_vels, generic_solver_vels| {
+                    stage.run(params, constraint, jac, solver_vels, generic_solver_vels)
+                },
+            );
+            return;
+        }
+
+        let solver_vels = &mut self.solver_vels;
+        let generic_solver_vels = &mut self.generic_solver_vels;
+
+        match stage {
+            ContactSolveStage::Warmstart => {
+                contact_constraints.warmstart(solver_vels, generic_solver_vels)
+            }
+            ContactSolveStage::Restitution => contact_constraints.solve_restitution(
+                solver_vels,
+                generic_solver_vels,
+                params.contact_block_solver,
+            ),
+            ContactSolveStage::RestitutionWoBias => contact_constraints.solve_restitution_wo_bias(
+                solver_vels,
+                generic_solver_vels,
+                params.contact_block_solver,
+            ),
+            ContactSolveStage::Friction => {
+                contact_constraints.solve_friction(solver_vels, generic_solver_vels)
+            }
+            ContactSolveStage::Coupled => {
+                contact_constraints.solve_coupled(solver_vels, generic_solver_vels)
+            }
+            ContactSolveStage::CoupledWoBias => {
+                contact_constraints.solve_coupled_wo_bias(solver_vels, generic_solver_vels)
             }
         }
     }
@@ -231,18 +568,59 @@ impl VelocitySolver {
         multibodies: &mut MultibodyJointSet,
     ) {
         // Integrate positions.
-        for (solver_vels, solver_body) in self.solver_vels.iter().zip(self.solver_bodies.iter_mut())
-        {
-            let linvel = solver_vels.linear;
-            let angvel = solver_body.sqrt_ii.transform_vector(solver_vels.angular);
-
-            let mut new_vels = RigidBodyVelocity { linvel, angvel };
-            new_vels = new_vels.apply_damping(params.dt, &solver_body.damping);
-            let new_pos =
-                new_vels.integrate(params.dt, &solver_body.position, &solver_body.local_com);
-            solver_body.integrated_vels += new_vels;
-            solver_body.position = new_pos;
-            solver_body.world_com = new_pos * solver_body.local_com;
+        let solver_bodies = &mut self.solver_bodies;
+
+        // The islands large enough to have their contacts solved in parallel also have their
+        // bodies integrated in parallel.
+        #[cfg(feature = "parallel")]
+        let parallel = self.contact_coloring.num_colors() > 0;
+        #[cfg(not(feature = "parallel"))]
+        let parallel = false;
+
+        if parallel {
+            #[cfg(feature = "parallel")]
+            {
+                use rayon::prelude::*;
+
+                (
+                    &self.solver_vels,
+                    &solver_bodies.sqrt_iis,
+                    &solver_bodies.local_coms,
+                    &solver_bodies.dampings,
+                    &mut solver_bodies.positions,
+                    &mut solver_bodies.integrated_vels,
+                    &mut solver_bodies.world_coms,
+                )
+                    .into_par_iter()
+                    .with_min_len(MIN_PARALLEL_BODY_TASK_SIZE)
+                    .for_each(
+                        |(vels, sqrt_ii, local_com, damping, pos, integrated_vels, com)| {
+                            integrate_body(
+                                params.dt,
+                                vels,
+                                sqrt_ii,
+                                local_com,
+                                damping,
+                                pos,
+                                integrated_vels,
+                                com,
+                            )
+                        },
+                    );
+            }
+        } else {
+            for (i, solver_vels) in self.solver_vels.iter().enumerate() {
+                integrate_body(
+                    params.dt,
+                    solver_vels,
+                    &solver_bodies.sqrt_iis[i],
+                    &solver_bodies.local_coms[i],
+                    &solver_bodies.dampings[i],
+                    &mut solver_bodies.positions[i],
+                    &mut solver_bodies.integrated_vels[i],
+                    &mut solver_bodies.world_coms[i],
+                );
+            }
         }
 
         // Integrate multibody positions.
@@ -273,6 +651,31 @@ impl VelocitySolver {
         }
     }
 
+    /// Adds the velocity increments due to the forces to the solver velocities.
+    fn integrate_velocity_increments(&mut self) {
+        let integrate = |(solver_vels, incr): (&mut SolverVel<Real>, &SolverVel<Real>)| {
+            solver_vels.linear += incr.linear;
+            solver_vels.angular += incr.angular;
+        };
+
+        #[cfg(feature = "parallel")]
+        if self.contact_coloring.num_colors() > 0 {
+            use rayon::prelude::*;
+
+            self.solver_vels
+                .par_iter_mut()
+                .zip(self.solver_vels_increment.par_iter())
+                .with_min_len(MIN_PARALLEL_BODY_TASK_SIZE)
+                .for_each(integrate);
+            return;
+        }
+
+        self.solver_vels
+            .iter_mut()
+            .zip(self.solver_vels_increment.iter())
+            .for_each(integrate);
+    }
+
     pub fn writeback_bodies(
         &mut self,
         params: &IntegrationParameters,
@@ -302,28 +705,322 @@ impl VelocitySolver {
                 }
             } else {
                 let rb = bodies.index_mut_internal(*handle);
-                let solver_body = &self.solver_bodies[rb.ids.active_set_offset];
-                let solver_vels = &self.solver_vels[rb.ids.active_set_offset];
+                let id = rb.ids.active_set_offset;
+                let solver_bodies = &self.solver_bodies;
+                let solver_vels = &self.solver_vels[id];
 
-                let dangvel = solver_body.sqrt_ii.transform_vector(solver_vels.angular);
+                let dangvel = solver_bodies.sqrt_iis[id].transform_vector(solver_vels.angular);
 
                 let mut new_vels = RigidBodyVelocity {
                     linvel: solver_vels.linear,
                     angvel: dangvel,
                 };
-                new_vels = new_vels.apply_damping(params.dt, &solver_body.damping);
+                new_vels = new_vels.apply_damping(params.dt, &solver_bodies.dampings[id]);
 
                 // NOTE: using integrated_vels instead of interpolation is interesting for
                 //       high angular velocities. However, it is a bit inexact due to the
                 //       solver integrating at intermediate sub-steps. Should we just switch
                 //       to interpolation?
                 rb.integrated_vels.linvel =
-                    solver_body.integrated_vels.linvel / num_substeps as Real;
+                    solver_bodies.integrated_vels[id].linvel / num_substeps as Real;
                 rb.integrated_vels.angvel =
-                    solver_body.integrated_vels.angvel / num_substeps as Real;
+                    solver_bodies.integrated_vels[id].angvel / num_substeps as Real;
                 rb.vels = new_vels;
-                rb.pos.next_position = solver_body.position;
+                rb.pos.next_position = solver_bodies.positions[id];
             }
         }
     }
 }
+
+/// Integrates the position of a solver body with the velocity `solver_vels`.
+fn integrate_body(
+    dt: Real,
+    solver_vels: &SolverVel<Real>,
+    sqrt_ii: &AngularInertia<Real>,
+    local_com: &Point<Real>,
+    damping: &RigidBodyDamping,
+    position: &mut Isometry<Real>,
+    integrated_vels: &mut RigidBodyVelocity,
+    world_com: &mut Point<Real>,
+) {
+    let linvel = solver_vels.linear;
+    let angvel = sqrt_ii.transform_vector(solver_vels.angular);
+
+    let mut new_vels = RigidBodyVelocity { linvel, angvel };
+    new_vels = new_vels.apply_damping(dt, damping);
+    let new_pos = new_vels.integrate(dt, position, local_com);
+    *integrated_vels += new_vels;
+    *world_com = new_pos * local_com;
+    *position = new_pos;
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use crate::dynamics::{
+        ContactFrictionMode, IntegrationParameters, PositionCorrectionMode, RigidBodyBuilder,
+        VelocityConvergenceCriterion,
+    };
+    use crate::geometry::ColliderBuilder;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[cfg(feature = "dim2")]
+    fn cube() -> ColliderBuilder {
+        ColliderBuilder::cuboid(0.5, 0.5)
+    }
+
+    #[cfg(feature = "dim3")]
+    fn cube() -> ColliderBuilder {
+        ColliderBuilder::cuboid(0.5, 0.5, 0.5)
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn block_solver_keeps_box_stack_at_rest() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        world.integration_parameters.contact_block_solver = true;
+        world.integration_parameters.num_solver_iterations = NonZeroUsize::MIN;
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let heights = [0.5, 1.5, 2.5, 3.5, 4.5];
+        let boxes = heights.map(|height| {
+            let handle = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * height));
+            let _ = world
+                .colliders
+                .insert_with_parent(cube(), handle, &mut world.bodies);
+            handle
+        });
+        for _ in 0..120 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        for (handle, height) in boxes.into_iter().zip(heights) {
+            let rb = &world.bodies[handle];
+            assert!((rb.translation().y - height).abs() < 5.0e-2);
+            assert!(rb.linvel().norm() < 1.0e-2);
+            assert!(rb.angvel().norm() < 1.0e-2);
+        }
+    }
+
+    #[test]
+    fn contact_impulses_are_summed_over_substeps() {
+        let resting_impulse = |num_solver_iterations| {
+            let mut world = PhysicsWorld {
+                gravity: Vector::y() * -9.81,
+                ..PhysicsWorld::default()
+            };
+            let mut pipeline = PhysicsPipeline::new();
+            world.integration_parameters.num_solver_iterations =
+                NonZeroUsize::new(num_solver_iterations).unwrap();
+
+            let ground = world
+                .colliders
+                .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+            let body = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+            let collider = world
+                .colliders
+                .insert_with_parent(cube(), body, &mut world.bodies);
+            let mass = world.bodies[body].mass();
+            for _ in 0..100 {
+                world.step(&mut pipeline, None, &(), &());
+            }
+
+            let pair = world.narrow_phase.contact_pair(ground, collider).unwrap();
+            pair.total_impulse_magnitude() / (mass * 9.81 * world.integration_parameters.dt)
+        };
+
+        // The contact impulses balance the gravity over the whole step, whatever the
+        // number of substeps.
+        assert!((resting_impulse(1) - 1.0).abs() < 0.05);
+        assert!((resting_impulse(4) - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn position_correction_modes_dont_inject_energy() {
+        for mode in [
+            PositionCorrectionMode::SplitImpulse,
+            PositionCorrectionMode::NonlinearGaussSeidel,
+        ] {
+            let mut world = PhysicsWorld::default();
+            let mut pipeline = PhysicsPipeline::new();
+            world.integration_parameters.position_correction_mode = mode;
+
+            let _ = world
+                .colliders
+                .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+            // A ball starting deep inside the ground.
+            let ball = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.3));
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5),
+                ball,
+                &mut world.bodies,
+            );
+
+            for i in 0..120 {
+                world.step(&mut pipeline, None, &(), &());
+
+                // The ball is pushed out of the ground without gaining any velocity.
+                assert!(
+                    world.bodies[ball].linvel().norm() < 1.0e-3,
+                    "{:?} at step {}",
+                    mode,
+                    i
+                );
+            }
+
+            assert!(world.bodies[ball].translation().y > 0.49, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn converged_islands_exit_their_pgs_iterations_early() {
+        for velocity_convergence in [None, Some(VelocityConvergenceCriterion::default())] {
+            let mut world = PhysicsWorld {
+                gravity: Vector::y() * -9.81,
+                ..PhysicsWorld::default()
+            };
+            let mut pipeline = PhysicsPipeline::new();
+            pipeline.counters.enable();
+            world.integration_parameters = IntegrationParameters {
+                num_internal_pgs_iterations: 4,
+                velocity_convergence,
+                ..IntegrationParameters::default()
+            };
+
+            // A free-falling body has no constraint to converge.
+            let body = world.bodies.insert(RigidBodyBuilder::dynamic());
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5),
+                body,
+                &mut world.bodies,
+            );
+            world.step(&mut pipeline, None, &(), &());
+
+            let num_substeps = world.integration_parameters.num_solver_iterations.get();
+            let expected = if velocity_convergence.is_some() {
+                num_substeps
+            } else {
+                num_substeps * 4
+            };
+            assert_eq!(pipeline.counters.solver.num_pgs_iterations, expected);
+            assert_eq!(pipeline.counters.solver.max_island_pgs_iterations, expected);
+        }
+    }
+
+    #[test]
+    fn converged_contact_impulses_exit_their_pgs_iterations_early() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        pipeline.counters.enable();
+        world.integration_parameters = IntegrationParameters {
+            num_internal_pgs_iterations: 4,
+            velocity_convergence: Some(VelocityConvergenceCriterion {
+                normalized_max_residual: Real::MAX,
+                max_impulse_delta: 1.0e-3,
+                max_internal_pgs_iterations: 4,
+            }),
+            ..IntegrationParameters::default()
+        };
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+
+        // Once the ball rests on the ground, the warmstarted impulses are already converged.
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let num_substeps = world.integration_parameters.num_solver_iterations.get();
+        assert!(pipeline.counters.solver.max_island_pgs_iterations < num_substeps * 4);
+    }
+
+    #[test]
+    fn coupled_friction_decelerates_sliding_bodies_with_the_coulomb_law() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        world.integration_parameters.contact_friction_mode = ContactFrictionMode::Coupled;
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()).friction(0.5));
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(Vector::y() * 0.5)
+                .linvel(Vector::x() * 5.0),
+        );
+        let _ = world
+            .colliders
+            .insert_with_parent(cube().friction(0.5), body, &mut world.bodies);
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The combined friction coefficient is 0.5, so the box decelerates by 0.5 * 9.81 m/s².
+        let expected_speed = 5.0 - 0.5 * 9.81 * 30.0 * world.integration_parameters.dt;
+        let rb = &world.bodies[body];
+        assert!((rb.linvel().x - expected_speed).abs() < expected_speed * 0.1);
+        assert!((rb.translation().y - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn additional_stabilization_iterations_remove_the_bias_velocities() {
+        let separating_velocity = |num_additional_stabilization_iterations| {
+            let mut world = PhysicsWorld::default();
+            let mut pipeline = PhysicsPipeline::new();
+            // With a single substep, the relaxation iterations run right after the penetration
+            // correction.
+            world.integration_parameters = IntegrationParameters {
+                num_solver_iterations: NonZeroUsize::MIN,
+                num_internal_stabilization_iterations: 0,
+                num_additional_stabilization_iterations,
+                ..IntegrationParameters::default()
+            };
+
+            let _ = world
+                .colliders
+                .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+            // The box starts penetrating the ground by 5cm.
+            let body = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.45));
+            let _ = world
+                .colliders
+                .insert_with_parent(cube(), body, &mut world.bodies);
+            world.step(&mut pipeline, None, &(), &());
+            world.bodies[body].linvel().y
+        };
+
+        let without_relaxation = separating_velocity(0);
+        let with_relaxation = separating_velocity(4);
+        assert!(without_relaxation > 0.1);
+        assert!(with_relaxation < without_relaxation * 0.1);
+    }
+}
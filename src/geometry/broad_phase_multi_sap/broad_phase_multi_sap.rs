@@ -71,6 +71,8 @@ use parry::utils::hashmap::HashMap;
 /// - Each `SAPRegion` contains three `SAPAxis`, representing the "classical" SAP algorithm running on this region.
 /// - Each `SAPAxis` maintains a sorted list of `SAPEndpoints` representing the endpoints of the Aabbs intersecting
 ///   the bounds on the `SAPRegion` containing this `SAPAxis`.
+///   The endpoint values are quantized to 16 bits relative to the region bounds, and are stored apart from the
+///   rest of the endpoint data to keep the sweeps cache-friendly. The exact Aabbs are only kept by the `SAPProxy`.
 /// - A set of `SAPProxy` are maintained separately. It contains the Aabbs of all the colliders managed by this
 ///   broad-phase, as well as the Aabbs of all the regions part of this broad-phase.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -371,16 +373,26 @@ impl BroadPhaseMultiSap {
         collider: &Collider,
         next_position: Option<&Isometry<Real>>,
     ) -> bool {
-        let mut aabb = collider.compute_collision_aabb(prediction_distance / 2.0);
+        let prediction = collider.broad_phase_prediction(prediction_distance);
+        let mut aabb = collider.compute_collision_aabb(prediction);
 
         if let Some(next_position) = next_position {
             let next_aabb = collider
                 .shape
                 .compute_aabb(next_position)
-                .loosened(collider.contact_skin() + prediction_distance / 2.0);
+                .loosened(collider.contact_skin() + prediction);
             aabb.merge(&next_aabb);
         }
 
+        // Swept colliders must find the pairs they may have intersected along their motion.
+        if let Some(prev_pos) = collider.prev_pos.filter(|_| collider.is_swept()) {
+            let swept_aabb = collider
+                .shape
+                .compute_aabb(&prev_pos)
+                .loosened(collider.contact_skin() + prediction);
+            aabb.merge(&swept_aabb);
+        }
+
         if aabb.mins.coords.iter().any(|e| !e.is_finite())
             || aabb.maxs.coords.iter().any(|e| !e.is_finite())
         {
@@ -661,7 +673,10 @@ mod test {
     use crate::dynamics::{
         ImpulseJointSet, IslandManager, MultibodyJointSet, RigidBodyBuilder, RigidBodySet,
     };
-    use crate::geometry::{BroadPhase, BroadPhaseMultiSap, ColliderBuilder, ColliderSet};
+    use crate::geometry::{
+        BroadPhase, BroadPhaseMultiSap, BroadPhasePairEvent, ColliderBuilder, ColliderSet,
+    };
+    use crate::math::Vector;
 
     #[test]
     fn test_add_update_remove() {
@@ -699,4 +714,61 @@ mod test {
         // Make sure the proxy handles is recycled properly.
         broad_phase.update(0.0, 0.0, &mut colliders, &bodies, &[coh], &[], &mut events);
     }
+
+    #[test]
+    fn test_large_collider_just_outside_a_subregion() {
+        let mut broad_phase = BroadPhaseMultiSap::new();
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut events = Vec::new();
+        let added_pair = |events: &[BroadPhasePairEvent]| {
+            events
+                .iter()
+                .any(|e| matches!(e, BroadPhasePairEvent::AddPair(_)))
+        };
+
+        // The small ball lies in the region [25, 26] of its layer. The large ball ends 1mm before
+        // that region, which is less than one quantization cell of the regions of its own layer.
+        let small = colliders.insert(
+            ColliderBuilder::ball(0.05).translation(Vector::repeat(0.5) + Vector::x() * 25.0),
+        );
+        let large = colliders.insert(
+            ColliderBuilder::ball(2.5).translation(Vector::repeat(0.5) + Vector::x() * 21.999),
+        );
+        broad_phase.update(
+            0.0,
+            0.0,
+            &mut colliders,
+            &bodies,
+            &[small, large],
+            &[],
+            &mut events,
+        );
+        assert!(!added_pair(&events));
+
+        broad_phase.update(
+            0.0,
+            0.0,
+            &mut colliders,
+            &bodies,
+            &[large],
+            &[],
+            &mut events,
+        );
+        assert!(!added_pair(&events));
+
+        // The large ball must still be part of the small region once it moves into it.
+        let translation = *colliders[large].translation();
+        colliders[large].set_translation(translation + Vector::x() * 0.5);
+        broad_phase.update(
+            0.0,
+            0.0,
+            &mut colliders,
+            &bodies,
+            &[large],
+            &[],
+            &mut events,
+        );
+        assert!(added_pair(&events));
+    }
 }
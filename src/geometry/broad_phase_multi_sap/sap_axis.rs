@@ -1,20 +1,29 @@
-use super::{SAPEndpoint, SAPProxies, NUM_SENTINELS};
+use super::{
+    quantization_tolerance, SAPEndpoint, SAPProxies, SAPProxy, SAPQuantization, NUM_SENTINELS,
+    QUANTIZED_ABOVE_BOUNDS, QUANTIZED_BELOW_BOUNDS, QUANTIZED_END_SENTINEL,
+};
 use crate::geometry::broad_phase_multi_sap::DELETED_AABB_VALUE;
 use crate::geometry::BroadPhaseProxyIndex;
 use crate::math::Real;
 use bit_vec::BitVec;
-use parry::bounding_volume::BoundingVolume;
 use parry::utils::hashmap::HashMap;
-use std::cmp::Ordering;
 
+/// The sorted endpoints of the proxies intersecting a region, along one axis.
+///
+/// The endpoint values are quantized relative to the region bounds (see [`SAPQuantization`])
+/// and stored separately from the rest of the endpoint data. This keeps the values compared
+/// during the sweeps tightly packed in memory.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
 pub struct SAPAxis {
     pub min_bound: Real,
     pub max_bound: Real,
+    /// The quantized value of each endpoint, in increasing order.
+    pub values: Vec<u16>,
+    /// The endpoints, in the same order as `values`.
     pub endpoints: Vec<SAPEndpoint>,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
-    pub new_endpoints: Vec<(SAPEndpoint, usize)>, // Workspace
+    pub new_endpoints: Vec<(u16, SAPEndpoint, usize)>, // Workspace
 }
 
 impl SAPAxis {
@@ -24,6 +33,7 @@ impl SAPAxis {
         Self {
             min_bound,
             max_bound,
+            values: vec![QUANTIZED_BELOW_BOUNDS, QUANTIZED_END_SENTINEL],
             endpoints: vec![SAPEndpoint::start_sentinel(), SAPEndpoint::end_sentinel()],
             new_endpoints: Vec::new(),
         }
@@ -31,15 +41,37 @@ impl SAPAxis {
 
     pub fn clear(&mut self) {
         self.new_endpoints.clear();
+        self.values.clear();
+        self.values.push(QUANTIZED_BELOW_BOUNDS);
+        self.values.push(QUANTIZED_END_SENTINEL);
         self.endpoints.clear();
         self.endpoints.push(SAPEndpoint::start_sentinel());
         self.endpoints.push(SAPEndpoint::end_sentinel());
     }
 
+    /// Keeps only the endpoints (and their values) for which `keep` returns `true`.
+    pub fn retain_endpoints(&mut self, mut keep: impl FnMut(SAPEndpoint) -> bool) {
+        let mut num_kept = 0;
+
+        for i in 0..self.endpoints.len() {
+            let endpoint = self.endpoints[i];
+
+            if keep(endpoint) {
+                self.values[num_kept] = self.values[i];
+                self.endpoints[num_kept] = endpoint;
+                num_kept += 1;
+            }
+        }
+
+        self.values.truncate(num_kept);
+        self.endpoints.truncate(num_kept);
+    }
+
     #[profiling::function]
     pub fn batch_insert(
         &mut self,
         dim: usize,
+        quantization: &SAPQuantization,
         new_proxies: &[BroadPhaseProxyIndex],
         proxies: &SAPProxies,
         reporting: Option<&mut HashMap<(u32, u32), bool>>,
@@ -52,32 +84,39 @@ impl SAPAxis {
 
         for proxy_id in new_proxies {
             let proxy = &proxies[*proxy_id];
+            // NOTE: the proxies of larger layers may lie slightly outside of this axis’ bounds
+            //       (see `quantization_tolerance`). Their endpoints are then clamped by the
+            //       quantization.
+            let tolerance = quantization_tolerance(proxy.layer_depth);
             assert!(
-                proxy.aabb.mins[dim] <= self.max_bound,
+                proxy.aabb.mins[dim] <= self.max_bound + tolerance,
                 "proxy.aabb.mins {} (in {:?}) <= max_bound {}",
                 proxy.aabb.mins[dim],
                 proxy.aabb,
                 self.max_bound
             );
             assert!(
-                proxy.aabb.maxs[dim] >= self.min_bound,
+                proxy.aabb.maxs[dim] >= self.min_bound - tolerance,
                 "proxy.aabb.maxs {} (in {:?}) >= min_bound {}",
                 proxy.aabb.maxs[dim],
                 proxy.aabb,
                 self.min_bound
             );
-            let start_endpoint = SAPEndpoint::start_endpoint(proxy.aabb.mins[dim], *proxy_id);
-            let end_endpoint = SAPEndpoint::end_endpoint(proxy.aabb.maxs[dim], *proxy_id);
+            let start_value = quantization.quantize_min(dim, proxy.aabb.mins[dim]);
+            let end_value = quantization.quantize_max(dim, proxy.aabb.maxs[dim]);
 
-            self.new_endpoints.push((start_endpoint, 0));
-            self.new_endpoints.push((end_endpoint, 0));
+            self.new_endpoints
+                .push((start_value, SAPEndpoint::start_endpoint(*proxy_id), 0));
+            self.new_endpoints
+                .push((end_value, SAPEndpoint::end_endpoint(*proxy_id), 0));
         }
 
-        self.new_endpoints
-            .sort_by(|a, b| a.0.value.partial_cmp(&b.0.value).unwrap_or(Ordering::Equal));
+        self.new_endpoints.sort_by_key(|e| e.0);
 
         let mut curr_existing_index = self.endpoints.len() - NUM_SENTINELS - 1;
         let new_num_endpoints = self.endpoints.len() + self.new_endpoints.len();
+        self.values
+            .resize(new_num_endpoints, QUANTIZED_END_SENTINEL);
         self.endpoints
             .resize(new_num_endpoints, SAPEndpoint::end_sentinel());
         let mut curr_shift_index = new_num_endpoints - NUM_SENTINELS - 1;
@@ -87,43 +126,52 @@ impl SAPAxis {
         // first time we insert endpoints to this axis?
         for new_endpoint in self.new_endpoints.iter_mut().rev() {
             loop {
-                let existing_endpoint = self.endpoints[curr_existing_index];
-                if existing_endpoint.value <= new_endpoint.0.value {
+                let existing_value = self.values[curr_existing_index];
+                if existing_value <= new_endpoint.0 {
                     break;
                 }
 
-                self.endpoints[curr_shift_index] = existing_endpoint;
+                self.values[curr_shift_index] = existing_value;
+                self.endpoints[curr_shift_index] = self.endpoints[curr_existing_index];
 
                 curr_shift_index -= 1;
                 curr_existing_index -= 1;
             }
 
-            self.endpoints[curr_shift_index] = new_endpoint.0;
-            new_endpoint.1 = curr_shift_index;
+            self.values[curr_shift_index] = new_endpoint.0;
+            self.endpoints[curr_shift_index] = new_endpoint.1;
+            new_endpoint.2 = curr_shift_index;
             curr_shift_index -= 1;
         }
 
         // Report pairs using a single mbp pass on each new endpoint.
-        let endpoints_wo_last_sentinel = &self.endpoints[..self.endpoints.len() - 1];
+        let num_endpoints_wo_last_sentinel = self.endpoints.len() - 1;
+        let values_wo_last_sentinel = &self.values[..num_endpoints_wo_last_sentinel];
+        let endpoints_wo_last_sentinel = &self.endpoints[..num_endpoints_wo_last_sentinel];
         if let Some(reporting) = reporting {
-            for (endpoint, endpoint_id) in self.new_endpoints.drain(..).filter(|e| e.0.is_start()) {
+            for (min, endpoint, endpoint_id) in
+                self.new_endpoints.drain(..).filter(|e| e.1.is_start())
+            {
                 let proxy1 = &proxies[endpoint.proxy()];
-                let min = endpoint.value;
-                let max = proxy1.aabb.maxs[dim];
+                let max = quantization.quantize_max(dim, proxy1.aabb.maxs[dim]);
 
-                for endpoint2 in &endpoints_wo_last_sentinel[endpoint_id + 1..] {
+                for (value2, endpoint2) in values_wo_last_sentinel[endpoint_id + 1..]
+                    .iter()
+                    .zip(endpoints_wo_last_sentinel[endpoint_id + 1..].iter())
+                {
                     if endpoint2.proxy() == endpoint.proxy() {
                         continue;
                     }
 
                     let proxy2 = &proxies[endpoint2.proxy()];
 
-                    // NOTE: some pairs with equal aabb.mins[dim] may end up being reported twice.
-                    if (endpoint2.is_start() && endpoint2.value < max)
-                        || (endpoint2.is_end() && proxy2.aabb.mins[dim] <= min)
+                    // NOTE: some pairs with equal quantized mins may end up being reported twice.
+                    if (endpoint2.is_start() && *value2 <= max)
+                        || (endpoint2.is_end()
+                            && quantization.quantize_min(dim, proxy2.aabb.mins[dim]) <= min)
                     {
                         // Report pair.
-                        if proxy1.aabb.intersects(&proxy2.aabb) {
+                        if quantization.intersects(&proxy1.aabb, &proxy2.aabb) {
                             // Report pair.
                             let pair = super::sort2(endpoint.proxy(), endpoint2.proxy());
                             reporting.insert(pair, true);
@@ -136,23 +184,37 @@ impl SAPAxis {
 
     /// Removes from this axis all the endpoints that are out of bounds from this axis.
     ///
+    /// Proxies of larger layers are only deleted once they are out of bounds by more than their
+    /// [`quantization_tolerance`]. Otherwise, the larger layer may still consider them as
+    /// overlapping this region, and would not report them again once they come back inside.
+    ///
     /// Returns the number of deleted proxies as well as the number of proxies deleted
     /// such that `proxy.layer_depth <= layer_depth`.
     pub fn delete_out_of_bounds_proxies(
         &self,
+        dim: usize,
         proxies: &SAPProxies,
         existing_proxies: &mut BitVec,
         layer_depth: i8,
     ) -> (usize, usize) {
         let mut num_subproper_proxies_deleted = 0;
         let mut num_proxies_deleted = 0;
-        for endpoint in &self.endpoints {
-            if endpoint.value < self.min_bound {
+        let is_within_tolerance = |proxy: &SAPProxy, distance: Real| {
+            proxy.layer_depth > layer_depth && distance <= quantization_tolerance(proxy.layer_depth)
+        };
+
+        for (value, endpoint) in self.values.iter().zip(self.endpoints.iter()) {
+            if *value == QUANTIZED_BELOW_BOUNDS {
                 let proxy_id = endpoint.proxy();
                 if endpoint.is_end() && existing_proxies[proxy_id as usize] {
+                    let proxy = &proxies[proxy_id];
+                    if is_within_tolerance(proxy, self.min_bound - proxy.aabb.maxs[dim]) {
+                        continue;
+                    }
+
                     existing_proxies.set(proxy_id as usize, false);
 
-                    if proxies[proxy_id].layer_depth <= layer_depth {
+                    if proxy.layer_depth <= layer_depth {
                         num_subproper_proxies_deleted += 1;
                     }
 
@@ -163,13 +225,18 @@ impl SAPAxis {
             }
         }
 
-        for endpoint in self.endpoints.iter().rev() {
-            if endpoint.value > self.max_bound {
+        for (value, endpoint) in self.values.iter().zip(self.endpoints.iter()).rev() {
+            if *value >= QUANTIZED_ABOVE_BOUNDS {
                 let proxy_id = endpoint.proxy();
                 if endpoint.is_start() && existing_proxies[proxy_id as usize] {
+                    let proxy = &proxies[proxy_id];
+                    if is_within_tolerance(proxy, proxy.aabb.mins[dim] - self.max_bound) {
+                        continue;
+                    }
+
                     existing_proxies.set(proxy_id as usize, false);
 
-                    if proxies[proxy_id].layer_depth <= layer_depth {
+                    if proxy.layer_depth <= layer_depth {
                         num_subproper_proxies_deleted += 1;
                     }
 
@@ -184,8 +251,9 @@ impl SAPAxis {
     }
 
     pub fn delete_out_of_bounds_endpoints(&mut self, existing_proxies: &BitVec) {
-        self.endpoints
-            .retain(|endpt| endpt.is_sentinel() || existing_proxies[endpt.proxy() as usize])
+        self.retain_endpoints(|endpt| {
+            endpt.is_sentinel() || existing_proxies[endpt.proxy() as usize]
+        })
     }
 
     /// Removes from this axis all the endpoints corresponding to a proxy with an Aabb mins/maxs values
@@ -200,7 +268,7 @@ impl SAPAxis {
     ) -> usize {
         let mut num_subproper_proxies_deleted = 0;
 
-        self.endpoints.retain(|endpt| {
+        self.retain_endpoints(|endpt| {
             if !endpt.is_sentinel() {
                 let proxy = &proxies[endpt.proxy()];
 
@@ -226,31 +294,33 @@ impl SAPAxis {
     pub fn update_endpoints(
         &mut self,
         dim: usize,
+        quantization: &SAPQuantization,
         proxies: &SAPProxies,
         reporting: &mut HashMap<(u32, u32), bool>,
     ) {
         let last_endpoint = self.endpoints.len() - NUM_SENTINELS;
         for i in NUM_SENTINELS..last_endpoint {
-            let mut endpoint_i = self.endpoints[i];
+            let endpoint_i = self.endpoints[i];
             let aabb_i = proxies[endpoint_i.proxy()].aabb;
 
-            if endpoint_i.is_start() {
-                endpoint_i.value = aabb_i.mins[dim];
+            let value_i = if endpoint_i.is_start() {
+                quantization.quantize_min(dim, aabb_i.mins[dim])
             } else {
-                endpoint_i.value = aabb_i.maxs[dim];
-            }
+                quantization.quantize_max(dim, aabb_i.maxs[dim])
+            };
 
             let mut j = i;
 
             if endpoint_i.is_start() {
-                while endpoint_i.value < self.endpoints[j - 1].value {
+                while value_i < self.values[j - 1] {
                     let endpoint_j = self.endpoints[j - 1];
+                    self.values[j] = self.values[j - 1];
                     self.endpoints[j] = endpoint_j;
 
                     if endpoint_j.is_end() {
                         // Report start collision.
                         let proxy_j = &proxies[endpoint_j.proxy()];
-                        if aabb_i.intersects(&proxy_j.aabb) {
+                        if quantization.intersects(&aabb_i, &proxy_j.aabb) {
                             let pair = super::sort2(endpoint_i.proxy(), endpoint_j.proxy());
                             reporting.insert(pair, true);
                         }
@@ -259,13 +329,14 @@ impl SAPAxis {
                     j -= 1;
                 }
             } else {
-                while endpoint_i.value < self.endpoints[j - 1].value {
+                while value_i < self.values[j - 1] {
                     let endpoint_j = self.endpoints[j - 1];
+                    self.values[j] = self.values[j - 1];
                     self.endpoints[j] = endpoint_j;
 
                     if endpoint_j.is_start() {
                         // Report end collision.
-                        if !aabb_i.intersects(&proxies[endpoint_j.proxy()].aabb) {
+                        if !quantization.intersects(&aabb_i, &proxies[endpoint_j.proxy()].aabb) {
                             let pair = super::sort2(endpoint_i.proxy(), endpoint_j.proxy());
                             reporting.insert(pair, false);
                         }
@@ -275,6 +346,7 @@ impl SAPAxis {
                 }
             }
 
+            self.values[j] = value_i;
             self.endpoints[j] = endpoint_i;
         }
 
@@ -1,6 +1,6 @@
-use super::{SAPAxis, SAPProxies};
+use super::{SAPAxis, SAPProxies, SAPQuantization};
 use crate::geometry::BroadPhaseProxyIndex;
-use crate::math::DIM;
+use crate::math::{Point, Vector, DIM};
 use bit_vec::BitVec;
 use parry::bounding_volume::Aabb;
 use parry::utils::hashmap::HashMap;
@@ -110,7 +110,7 @@ impl SAPRegion {
 
         for (i, axis) in self.axes.iter_mut().enumerate() {
             let existing_proxies = &mut self.existing_proxies;
-            axis.endpoints.retain(|e| {
+            axis.retain_endpoints(|e| {
                 // NOTE: we use `if let` instead of `unwrap` because no
                 // proxy will be found for the sentinels.
                 if let Some(proxy) = proxies.get(e.proxy()) {
@@ -214,6 +214,14 @@ impl SAPRegion {
         }
     }
 
+    /// The quantization of the endpoint values along the axes of this region.
+    pub fn quantization(&self) -> SAPQuantization {
+        SAPQuantization::new(
+            Point::from(Vector::from_fn(|i, _| self.axes[i].min_bound)),
+            Point::from(Vector::from_fn(|i, _| self.axes[i].max_bound)),
+        )
+    }
+
     #[profiling::function]
     pub fn update(
         &mut self,
@@ -221,15 +229,22 @@ impl SAPRegion {
         layer_depth: i8,
         reporting: &mut HashMap<(u32, u32), bool>,
     ) {
+        let quantization = self.quantization();
+
         if self.update_count > 0 {
             // Update endpoints.
             let mut total_deleted = 0;
             let mut total_deleted_subproper = 0;
 
             for dim in 0..DIM {
-                self.axes[dim].update_endpoints(dim, proxies, reporting);
+                self.axes[dim].update_endpoints(dim, &quantization, proxies, reporting);
                 let (num_deleted, num_deleted_subproper) = self.axes[dim]
-                    .delete_out_of_bounds_proxies(proxies, &mut self.existing_proxies, layer_depth);
+                    .delete_out_of_bounds_proxies(
+                        dim,
+                        proxies,
+                        &mut self.existing_proxies,
+                        layer_depth,
+                    );
                 total_deleted += num_deleted;
                 total_deleted_subproper += num_deleted_subproper;
             }
@@ -247,9 +262,9 @@ impl SAPRegion {
         if !self.to_insert.is_empty() {
             // Insert new proxies.
             for dim in 1..DIM {
-                self.axes[dim].batch_insert(dim, &self.to_insert, proxies, None);
+                self.axes[dim].batch_insert(dim, &quantization, &self.to_insert, proxies, None);
             }
-            self.axes[0].batch_insert(0, &self.to_insert, proxies, Some(reporting));
+            self.axes[0].batch_insert(0, &quantization, &self.to_insert, proxies, Some(reporting));
             self.to_insert.clear();
 
             // In the rare event that all proxies leave this region in the next step, we need an
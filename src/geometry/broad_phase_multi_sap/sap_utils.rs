@@ -1,4 +1,4 @@
-use crate::math::{Point, Real, Vector};
+use crate::math::{Point, Real, Vector, DIM};
 use parry::bounding_volume::Aabb;
 
 #[cfg(feature = "f32")]
@@ -13,6 +13,14 @@ pub(crate) const DELETED_AABB_VALUE: Real = SENTINEL_VALUE / 2.0;
 pub(crate) const MAX_AABB_EXTENT: Real = SENTINEL_VALUE / 4.0;
 pub(crate) const REGION_WIDTH_BASE: Real = 1.0;
 pub(crate) const REGION_WIDTH_POWER_BASIS: Real = 5.0;
+/// Number of cells each axis of a region is divided into for quantizing the endpoints.
+pub(crate) const NUM_QUANTIZATION_CELLS: Real = 65532.0;
+/// Quantized value of the endpoints located before the start of a region, and of the start sentinel.
+pub(crate) const QUANTIZED_BELOW_BOUNDS: u16 = 0;
+/// Quantized value of the endpoints located after the end of a region.
+pub(crate) const QUANTIZED_ABOVE_BOUNDS: u16 = u16::MAX - 1;
+/// Quantized value of the end sentinel.
+pub(crate) const QUANTIZED_END_SENTINEL: u16 = u16::MAX;
 
 pub(crate) fn sort2(a: u32, b: u32) -> (u32, u32) {
     assert_ne!(a, b);
@@ -24,6 +32,76 @@ pub(crate) fn sort2(a: u32, b: u32) -> (u32, u32) {
     }
 }
 
+/// Quantization of coordinates relative to the bounds of a region.
+///
+/// The quantization is conservative: interval lower bounds are rounded down and upper bounds
+/// are rounded up, so the quantized intervals always contain the exact ones.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct SAPQuantization {
+    origin: Point<Real>,
+    inv_cell_width: Vector<Real>,
+}
+
+impl SAPQuantization {
+    pub fn new(mins: Point<Real>, maxs: Point<Real>) -> Self {
+        let inv_cell_width = (maxs - mins).map(|width| {
+            if width > 0.0 {
+                NUM_QUANTIZATION_CELLS / width
+            } else {
+                0.0
+            }
+        });
+
+        Self {
+            origin: mins,
+            inv_cell_width,
+        }
+    }
+
+    fn cell(&self, dim: usize, x: Real, round_up: bool) -> Real {
+        let cell = (x - self.origin[dim]) * self.inv_cell_width[dim];
+
+        if round_up {
+            cell.ceil()
+        } else {
+            cell.floor()
+        }
+    }
+
+    fn quantize(&self, dim: usize, x: Real, round_up: bool) -> u16 {
+        let cell = self.cell(dim, x, round_up);
+
+        if cell < 0.0 {
+            QUANTIZED_BELOW_BOUNDS
+        } else if cell > NUM_QUANTIZATION_CELLS {
+            QUANTIZED_ABOVE_BOUNDS
+        } else {
+            cell as u16 + 1
+        }
+    }
+
+    /// The quantized value of the lower bound `x` of an interval along the axis `dim`.
+    pub fn quantize_min(&self, dim: usize, x: Real) -> u16 {
+        self.quantize(dim, x, false)
+    }
+
+    /// The quantized value of the upper bound `x` of an interval along the axis `dim`.
+    pub fn quantize_max(&self, dim: usize, x: Real) -> u16 {
+        self.quantize(dim, x, true)
+    }
+
+    /// Checks if the quantized versions of two Aabbs intersect.
+    ///
+    /// This is the test consistent with the ordering of the quantized endpoints, and must
+    /// be used instead of the exact Aabbs intersection test when the endpoints are swapped.
+    pub fn intersects(&self, aabb1: &Aabb, aabb2: &Aabb) -> bool {
+        (0..DIM).all(|dim| {
+            self.cell(dim, aabb1.mins[dim], false) <= self.cell(dim, aabb2.maxs[dim], true)
+                && self.cell(dim, aabb2.mins[dim], false) <= self.cell(dim, aabb1.maxs[dim], true)
+        })
+    }
+}
+
 pub(crate) fn clamp_point(point: Point<Real>) -> Point<Real> {
     point.map(|e| na::clamp(e, -MAX_AABB_EXTENT, MAX_AABB_EXTENT))
 }
@@ -50,6 +128,16 @@ pub(crate) fn region_width(depth: i8) -> Real {
     (REGION_WIDTH_BASE * REGION_WIDTH_POWER_BASIS.powi(depth as i32)).min(MAX_AABB_EXTENT)
 }
 
+/// The distance up to which a proxy of the layer at `depth` may lie outside of a smaller region
+/// while still being reported as overlapping it.
+///
+/// The overlaps between the proxies and the smaller regions are found by the quantized sweeps of
+/// a layer at most as large as the proxy’s layer, over-approximating them by up to two cells. One
+/// more cell accounts for the rounding errors of the quantization.
+pub(crate) fn quantization_tolerance(depth: i8) -> Real {
+    3.0 * region_width(depth) / NUM_QUANTIZATION_CELLS
+}
+
 /// Computes the depth of the layer the given [`Aabb`] should be part of.
 ///
 /// The idea here is that an [`Aabb`] should be part of a layer which has
@@ -1,8 +1,11 @@
-use crate::dynamics::{CoefficientCombineRule, MassProperties, RigidBodyHandle};
+use crate::dynamics::{
+    CoefficientCombineRule, ContactSoftness, MassProperties, RigidBodyHandle, RigidBodyVelocity,
+};
 use crate::geometry::{
     ActiveCollisionTypes, BroadPhaseProxyIndex, ColliderBroadPhaseData, ColliderChanges,
     ColliderFlags, ColliderMassProps, ColliderMaterial, ColliderParent, ColliderPosition,
-    ColliderShape, ColliderType, InteractionGroups, MeshConverter, MeshConverterError, SharedShape,
+    ColliderShape, ColliderType, Ellipsoid, HeightField, HeightFieldExt, InteractionGroups,
+    MeshConverter, MeshConverterError, SharedShape, TaperedCapsule, Torus,
 };
 use crate::math::{AngVector, Isometry, Point, Real, Rotation, Vector, DIM};
 use crate::parry::transformation::vhacd::VHACDParameters;
@@ -10,10 +13,13 @@ use crate::pipeline::{ActiveEvents, ActiveHooks};
 use crate::prelude::ColliderEnabled;
 use na::Unit;
 use parry::bounding_volume::{Aabb, BoundingVolume};
+use parry::query::NonlinearRigidMotion;
 use parry::shape::{Shape, TriMeshFlags};
 
+#[cfg(feature = "dim2")]
+use crate::geometry::{convex_partition_polygon, CurveSegment, CurvedPolyline};
 #[cfg(feature = "dim3")]
-use crate::geometry::HeightFieldFlags;
+use crate::geometry::{AnisotropicFriction, HeightFieldFlags, PointQueryWithLocation};
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug)]
@@ -31,6 +37,15 @@ pub struct Collider {
     pub(crate) flags: ColliderFlags,
     pub(crate) bf_data: ColliderBroadPhaseData,
     contact_skin: Real,
+    contact_priority: i8,
+    contact_softness: Option<ContactSoftness>,
+    surface_velocity: RigidBodyVelocity,
+    speculative_margin: Option<Real>,
+    swept: bool,
+    /// The position of this collider before it was moved by the latest substep, if it moved.
+    pub(crate) prev_pos: Option<Isometry<Real>>,
+    /// Sorted ids of the sub-shapes with disabled contacts.
+    disabled_primitives: Vec<u32>,
     contact_force_event_threshold: Real,
     /// User-defined data associated to this collider.
     pub user_data: u128,
@@ -111,6 +126,13 @@ impl Collider {
             contact_force_event_threshold,
             user_data,
             contact_skin,
+            contact_priority,
+            contact_softness,
+            surface_velocity,
+            speculative_margin,
+            swept,
+            prev_pos: _prev_pos, // Only meaningful for the collider that actually moved.
+            disabled_primitives,
         } = other;
 
         if self.parent.is_none() {
@@ -126,6 +148,12 @@ impl Collider {
         self.flags = *flags;
         self.changes = ColliderChanges::all();
         self.contact_skin = *contact_skin;
+        self.contact_priority = *contact_priority;
+        self.contact_softness = *contact_softness;
+        self.surface_velocity = *surface_velocity;
+        self.speculative_margin = *speculative_margin;
+        self.swept = *swept;
+        self.disabled_primitives.clone_from(disabled_primitives);
     }
 
     /// The physics hooks enabled for this collider.
@@ -135,7 +163,10 @@ impl Collider {
 
     /// Sets the physics hooks enabled for this collider.
     pub fn set_active_hooks(&mut self, active_hooks: ActiveHooks) {
-        self.flags.active_hooks = active_hooks;
+        if self.flags.active_hooks != active_hooks {
+            self.changes.insert(ColliderChanges::ACTIVE_HOOKS);
+            self.flags.active_hooks = active_hooks;
+        }
     }
 
     /// The events enabled for this collider.
@@ -155,7 +186,10 @@ impl Collider {
 
     /// Sets the collision types enabled for this collider.
     pub fn set_active_collision_types(&mut self, active_collision_types: ActiveCollisionTypes) {
-        self.flags.active_collision_types = active_collision_types;
+        if self.flags.active_collision_types != active_collision_types {
+            self.changes.insert(ColliderChanges::ACTIVE_COLLISION_TYPES);
+            self.flags.active_collision_types = active_collision_types;
+        }
     }
 
     /// The contact skin of this collider.
@@ -172,7 +206,143 @@ impl Collider {
         self.contact_skin = skin_thickness;
     }
 
+    /// The contact priority of this collider.
+    ///
+    /// See the documentation of [`ColliderBuilder::contact_priority`] for details.
+    pub fn contact_priority(&self) -> i8 {
+        self.contact_priority
+    }
+
+    /// Sets the contact priority of this collider.
+    ///
+    /// See the documentation of [`ColliderBuilder::contact_priority`] for details.
+    pub fn set_contact_priority(&mut self, priority: i8) {
+        self.contact_priority = priority;
+    }
+
+    /// The contact softness of this collider, if it overrides the global one.
+    ///
+    /// See the documentation of [`ColliderBuilder::contact_softness`] for details.
+    pub fn contact_softness(&self) -> Option<ContactSoftness> {
+        self.contact_softness
+    }
+
+    /// Sets the contact softness of this collider.
+    ///
+    /// Set it to `None` to use the global contact softness from the
+    /// [`IntegrationParameters`](crate::dynamics::IntegrationParameters).
+    /// See the documentation of [`ColliderBuilder::contact_softness`] for details.
+    pub fn set_contact_softness(&mut self, softness: Option<ContactSoftness>) {
+        self.contact_softness = softness;
+    }
+
+    /// The velocity of the surface of this collider, in its local-space.
+    ///
+    /// See the documentation of [`ColliderBuilder::surface_velocity`] for details.
+    pub fn surface_velocity(&self) -> RigidBodyVelocity {
+        self.surface_velocity
+    }
+
+    /// Sets the velocity of the surface of this collider, in its local-space.
+    ///
+    /// See the documentation of [`ColliderBuilder::surface_velocity`] for details.
+    pub fn set_surface_velocity(&mut self, velocity: RigidBodyVelocity) {
+        self.surface_velocity = velocity;
+    }
+
+    /// The world-space velocity of the surface of this collider at the given world-space point.
+    ///
+    /// This doesn’t include the velocity of the rigid-body this collider is attached to.
+    pub fn surface_velocity_at_point(&self, point: &Point<Real>) -> Vector<Real> {
+        self.surface_velocity
+            .transformed(&self.pos.rotation)
+            .velocity_at_point(point, &Point::from(self.pos.translation.vector))
+    }
+
+    /// The speculative contact margin of this collider, if it overrides the global one.
+    ///
+    /// See the documentation of [`ColliderBuilder::speculative_margin`] for details.
+    pub fn speculative_margin(&self) -> Option<Real> {
+        self.speculative_margin
+    }
+
+    /// Sets the speculative contact margin of this collider.
+    ///
+    /// Set it to `None` to use the global
+    /// [`IntegrationParameters::prediction_distance`](crate::dynamics::IntegrationParameters::prediction_distance).
+    /// See the documentation of [`ColliderBuilder::speculative_margin`] for details.
+    pub fn set_speculative_margin(&mut self, margin: Option<Real>) {
+        if self.speculative_margin != margin {
+            self.speculative_margin = margin;
+            // The broad-phase AABB depends on the margin.
+            self.changes.insert(ColliderChanges::POSITION);
+        }
+    }
+
+    /// Are the intersections of this collider detected along its whole motion?
+    ///
+    /// See the documentation of [`ColliderBuilder::swept`] for details.
+    pub fn is_swept(&self) -> bool {
+        self.swept
+    }
+
+    /// Sets whether the intersections of this collider are detected along its whole motion.
+    ///
+    /// See the documentation of [`ColliderBuilder::swept`] for details.
+    pub fn set_swept(&mut self, swept: bool) {
+        self.swept = swept;
+    }
+
+    /// The swept motion of this collider during the latest substep, starting at its previous
+    /// position and ending at its current position at the time `1.0`.
+    pub(crate) fn swept_motion(&self) -> NonlinearRigidMotion {
+        let Some(prev_pos) = self.prev_pos else {
+            return NonlinearRigidMotion::constant_position(self.pos.0);
+        };
+
+        let pos = &self.pos.0;
+        let linvel = pos.translation.vector - prev_pos.translation.vector;
+        #[cfg(feature = "dim2")]
+        let angvel = (pos.rotation * prev_pos.rotation.inverse()).angle();
+        #[cfg(feature = "dim3")]
+        let angvel = (pos.rotation * prev_pos.rotation.inverse()).scaled_axis();
+        NonlinearRigidMotion::new(prev_pos, Point::origin(), linvel, angvel)
+    }
+
+    /// The speculative contact margin used for the contacts between two colliders, given the
+    /// global `prediction_distance`.
+    ///
+    /// This is the largest margin of the colliders overriding it, or `prediction_distance` if
+    /// none of them does.
+    pub fn combined_speculative_margin(
+        collider1: &Collider,
+        collider2: &Collider,
+        prediction_distance: Real,
+    ) -> Real {
+        match (collider1.speculative_margin, collider2.speculative_margin) {
+            (Some(margin1), Some(margin2)) => margin1.max(margin2),
+            (margin1, margin2) => margin1.or(margin2).unwrap_or(prediction_distance),
+        }
+    }
+
+    /// The amount the broad-phase AABB of this collider must be enlarged by so that every pair
+    /// within its speculative margin is detected, given the global `prediction_distance`.
+    ///
+    /// Every collider is enlarged by at least half the prediction distance, so a larger margin
+    /// only needs to enlarge its own collider by the remainder.
+    pub(crate) fn broad_phase_prediction(&self, prediction_distance: Real) -> Real {
+        let half_prediction = prediction_distance / 2.0;
+        self.speculative_margin
+            .map(|margin| (margin - half_prediction).max(half_prediction))
+            .unwrap_or(half_prediction)
+    }
+
     /// The friction coefficient of this collider.
+    ///
+    /// In 3D, the friction impulse of each contact is capped by its magnitude (the Coulomb cone
+    /// defined by this coefficient) instead of by each of its tangent components, so sliding
+    /// friction doesn’t depend on the sliding direction. If either collider has an
+    /// [`Self::anisotropic_friction`], it is capped to an elliptical cone instead.
     pub fn friction(&self) -> Real {
         self.material.friction
     }
@@ -182,6 +352,48 @@ impl Collider {
         self.material.friction = coefficient
     }
 
+    /// The rolling friction coefficient of this collider.
+    ///
+    /// See [`ColliderMaterial::rolling_friction`] for details.
+    pub fn rolling_friction(&self) -> Real {
+        self.material.rolling_friction
+    }
+
+    /// Sets the rolling friction coefficient of this collider.
+    pub fn set_rolling_friction(&mut self, coefficient: Real) {
+        self.material.rolling_friction = coefficient
+    }
+
+    /// The twist friction coefficient of this collider.
+    ///
+    /// See [`ColliderMaterial::twist_friction`] for details.
+    #[cfg(feature = "dim3")]
+    pub fn twist_friction(&self) -> Real {
+        self.material.twist_friction
+    }
+
+    /// Sets the twist friction coefficient of this collider.
+    #[cfg(feature = "dim3")]
+    pub fn set_twist_friction(&mut self, coefficient: Real) {
+        self.material.twist_friction = coefficient
+    }
+
+    /// The direction-dependent friction of this collider, if any.
+    ///
+    /// See [`AnisotropicFriction`] for details.
+    #[cfg(feature = "dim3")]
+    pub fn anisotropic_friction(&self) -> Option<&AnisotropicFriction> {
+        self.material.anisotropic_friction.as_ref()
+    }
+
+    /// Sets the direction-dependent friction of this collider.
+    ///
+    /// Set it to `None` to use the isotropic friction coefficient instead.
+    #[cfg(feature = "dim3")]
+    pub fn set_anisotropic_friction(&mut self, friction: Option<AnisotropicFriction>) {
+        self.material.anisotropic_friction = friction;
+    }
+
     /// The combine rule used by this collider to combine its friction
     /// coefficient with the friction coefficient of the other collider it
     /// is in contact with.
@@ -453,6 +665,61 @@ impl Collider {
         &self.shape
     }
 
+    /// Disables the contacts against the given primitives of this collider’s shape, e.g., for
+    /// punching a hole into a trimesh or heightfield terrain after an explosion.
+    ///
+    /// The primitives are identified by the sub-shape ids of the contact manifolds involving
+    /// this collider: the triangle indices of a trimesh, the triangle ids of a heightfield
+    /// (the segment indices in 2D), or the child indices of a compound shape. The shape itself
+    /// isn’t modified: the narrow-phase drops the contacts against disabled
+    /// primitives. Scene queries and intersections with sensors still take them into account.
+    pub fn disable_primitives(&mut self, primitives: impl IntoIterator<Item = u32>) {
+        let num_disabled = self.disabled_primitives.len();
+        self.disabled_primitives.extend(primitives);
+        self.disabled_primitives.sort_unstable();
+        self.disabled_primitives.dedup();
+
+        if self.disabled_primitives.len() != num_disabled {
+            self.changes.insert(ColliderChanges::PRIMITIVES);
+        }
+    }
+
+    /// Re-enables the contacts against the given primitives of this collider’s shape.
+    ///
+    /// See [`Collider::disable_primitives`] for details.
+    pub fn enable_primitives(&mut self, primitives: impl IntoIterator<Item = u32>) {
+        let mut to_enable: Vec<u32> = primitives.into_iter().collect();
+        to_enable.sort_unstable();
+
+        let num_disabled = self.disabled_primitives.len();
+        self.disabled_primitives
+            .retain(|primitive| to_enable.binary_search(primitive).is_err());
+
+        if self.disabled_primitives.len() != num_disabled {
+            self.changes.insert(ColliderChanges::PRIMITIVES);
+        }
+    }
+
+    /// Re-enables the contacts against all the primitives of this collider’s shape.
+    pub fn enable_all_primitives(&mut self) {
+        if !self.disabled_primitives.is_empty() {
+            self.disabled_primitives.clear();
+            self.changes.insert(ColliderChanges::PRIMITIVES);
+        }
+    }
+
+    /// Are the contacts against the given primitive of this collider’s shape enabled?
+    ///
+    /// See [`Collider::disable_primitives`] for details.
+    pub fn is_primitive_enabled(&self, primitive: u32) -> bool {
+        self.disabled_primitives.binary_search(&primitive).is_err()
+    }
+
+    /// The sorted ids of the primitives of this collider’s shape with disabled contacts.
+    pub fn disabled_primitives(&self) -> &[u32] {
+        &self.disabled_primitives
+    }
+
     /// Compute the axis-aligned bounding box of this collider.
     ///
     /// This AABB doesn’t take into account the collider’s contact skin.
@@ -475,6 +742,29 @@ impl Collider {
         self.shape.compute_swept_aabb(&self.pos, next_position)
     }
 
+    /// The world-space normal of this collider’s triangle mesh at the given world-space `point`,
+    /// interpolated from the pseudo-normals of the vertices of the triangle closest to `point`.
+    ///
+    /// Unlike the normal of the triangle itself, this varies smoothly across the mesh, which is
+    /// useful to orient, e.g., decals or ricochets at the hit point of a ray or shape cast on a
+    /// low-poly mesh. Returns `None` if the shape isn’t a triangle mesh, or if it doesn’t have
+    /// pseudo-normals, i.e., it wasn’t built with the [`TriMeshFlags::ORIENTED`] flag.
+    #[cfg(feature = "dim3")]
+    pub fn smooth_normal_at_point(&self, point: &Point<Real>) -> Option<Unit<Vector<Real>>> {
+        let trimesh = self.shape.as_trimesh()?;
+        let pseudo_normals = trimesh.pseudo_normals()?;
+        let local_point = self.pos.0.inverse_transform_point(point);
+        let (_, (triangle_id, location)) =
+            trimesh.project_local_point_and_get_location(&local_point, false);
+        let bcoords = location.barycentric_coordinates()?;
+        let triangle = trimesh.indices()[triangle_id as usize];
+        let local_normal = (0..3)
+            .map(|i| pseudo_normals.vertices_pseudo_normal[triangle[i] as usize] * bcoords[i])
+            .sum::<Vector<Real>>();
+
+        Unit::try_new(self.pos.0 * local_normal, 1.0e-6)
+    }
+
     /// Compute the local-space mass properties of this collider.
     pub fn mass_properties(&self) -> MassProperties {
         self.mprops.mass_properties(&*self.shape)
@@ -499,6 +789,14 @@ pub struct ColliderBuilder {
     pub friction: Real,
     /// The rule used to combine two friction coefficients.
     pub friction_combine_rule: CoefficientCombineRule,
+    /// The rolling friction coefficient of the collider to be built.
+    pub rolling_friction: Real,
+    /// The twist friction coefficient of the collider to be built.
+    #[cfg(feature = "dim3")]
+    pub twist_friction: Real,
+    /// The direction-dependent friction of the collider to be built.
+    #[cfg(feature = "dim3")]
+    pub anisotropic_friction: Option<AnisotropicFriction>,
     /// The restitution coefficient of the collider to be built.
     pub restitution: Real,
     /// The rule used to combine two restitution coefficients.
@@ -525,6 +823,17 @@ pub struct ColliderBuilder {
     pub contact_force_event_threshold: Real,
     /// An extra thickness around the collider shape to keep them further apart when colliding.
     pub contact_skin: Real,
+    /// The order in which the contacts of the collider being built are solved.
+    pub contact_priority: i8,
+    /// The contact softness of the collider being built, overriding the global one if set.
+    pub contact_softness: Option<ContactSoftness>,
+    /// The velocity of the surface of the collider being built, in its local-space.
+    pub surface_velocity: RigidBodyVelocity,
+    /// The speculative contact margin of the collider being built, overriding the global
+    /// prediction distance if set.
+    pub speculative_margin: Option<Real>,
+    /// Are the intersections of the collider being built detected along its whole motion?
+    pub swept: bool,
 }
 
 impl Default for ColliderBuilder {
@@ -540,6 +849,11 @@ impl ColliderBuilder {
             shape,
             mass_properties: ColliderMassProps::default(),
             friction: Self::default_friction(),
+            rolling_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            twist_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            anisotropic_friction: None,
             restitution: 0.0,
             position: Isometry::identity(),
             is_sensor: false,
@@ -554,6 +868,11 @@ impl ColliderBuilder {
             enabled: true,
             contact_force_event_threshold: 0.0,
             contact_skin: 0.0,
+            contact_priority: 0,
+            contact_softness: None,
+            surface_velocity: RigidBodyVelocity::zero(),
+            speculative_margin: None,
+            swept: false,
         }
     }
 
@@ -646,6 +965,44 @@ impl ColliderBuilder {
         Self::new(SharedShape::capsule_z(half_height, radius))
     }
 
+    /// Initialize a new collider builder with an ellipsoid (or ellipse in 2D) shape defined
+    /// by its semi-axes lengths.
+    pub fn ellipsoid(radii: Vector<Real>) -> Self {
+        Self::new(SharedShape::new(Ellipsoid::new(radii)))
+    }
+
+    /// Initialize a new collider builder with a tapered capsule defined from the centers
+    /// and radii of its two end balls.
+    ///
+    /// See also [`ColliderBuilder::tapered_capsule_y`] for a tapered capsule aligned with
+    /// the `y` axis.
+    pub fn tapered_capsule(a: Point<Real>, b: Point<Real>, radius_a: Real, radius_b: Real) -> Self {
+        Self::new(SharedShape::new(TaperedCapsule::new(
+            a, b, radius_a, radius_b,
+        )))
+    }
+
+    /// Initialize a new collider builder with a tapered capsule shape aligned with the `y` axis.
+    ///
+    /// The ball with radius `radius_bottom` is centered at `-half_height` along `y` and the
+    /// ball with radius `radius_top` is centered at `+half_height`.
+    pub fn tapered_capsule_y(half_height: Real, radius_bottom: Real, radius_top: Real) -> Self {
+        Self::new(SharedShape::new(TaperedCapsule::new_y(
+            half_height,
+            radius_bottom,
+            radius_top,
+        )))
+    }
+
+    /// Initialize a new collider builder with a torus (or annulus in 2D) shape defined by its
+    /// major radius (distance from its center to the center of its tube) and minor radius
+    /// (radius of its tube).
+    ///
+    /// In 3D, the axis of revolution of the torus is the `y` axis.
+    pub fn torus(major_radius: Real, minor_radius: Real) -> Self {
+        Self::new(SharedShape::new(Torus::new(major_radius, minor_radius)))
+    }
+
     /// Initialize a new collider builder with a cuboid shape defined by its half-extents.
     #[cfg(feature = "dim3")]
     pub fn cuboid(hx: Real, hy: Real, hz: Real) -> Self {
@@ -684,6 +1041,28 @@ impl ColliderBuilder {
         Self::new(SharedShape::polyline(vertices, indices))
     }
 
+    /// Initializes a collider builder with a 2D chain of straight segments, circular arcs, and
+    /// quadratic Bézier curves.
+    ///
+    /// This is well suited for smooth terrains which would otherwise need a polyline with many
+    /// tiny segments.
+    #[cfg(feature = "dim2")]
+    pub fn curved_polyline(segments: Vec<CurveSegment>) -> Self {
+        Self::new(SharedShape::new(CurvedPolyline::new(segments)))
+    }
+
+    /// Initializes a collider builder with a 2D polyline where each interior vertex is replaced
+    /// by a circular arc of radius `radii[i]`, smoothing the transition between consecutive
+    /// segments.
+    ///
+    /// See [`CurvedPolyline::rounded_polyline`] for details.
+    #[cfg(feature = "dim2")]
+    pub fn rounded_polyline(vertices: &[Point<Real>], radii: &[Real]) -> Self {
+        Self::new(SharedShape::new(CurvedPolyline::rounded_polyline(
+            vertices, radii,
+        )))
+    }
+
     /// Initializes a collider builder with a triangle mesh shape defined by its vertex and index buffers.
     pub fn trimesh(vertices: Vec<Point<Real>>, indices: Vec<[u32; 3]>) -> Self {
         Self::new(SharedShape::trimesh(vertices, indices))
@@ -714,6 +1093,18 @@ impl ColliderBuilder {
         Ok(Self::new(shape).position(pose))
     }
 
+    /// Initializes a collider builder with a single shape built from many triangle mesh pieces,
+    /// each given with its pose relative to the collider.
+    ///
+    /// All the pieces share a single BVH and a single broad-phase proxy. See
+    /// [`MeshConverter::convert_pieces`] for details on how the pieces are combined.
+    pub fn converted_trimesh_pieces(
+        pieces: impl IntoIterator<Item = (Isometry<Real>, Vec<Point<Real>>, Vec<[u32; 3]>)>,
+        converter: MeshConverter,
+    ) -> Result<Self, MeshConverterError> {
+        Ok(Self::new(converter.convert_pieces(pieces)?))
+    }
+
     /// Initializes a collider builder with a compound shape obtained from the decomposition of
     /// the given trimesh (in 3D) or polyline (in 2D) into convex parts.
     pub fn convex_decomposition(vertices: &[Point<Real>], indices: &[[u32; DIM]]) -> Self {
@@ -783,6 +1174,24 @@ impl ColliderBuilder {
         SharedShape::convex_polyline(points).map(Self::new)
     }
 
+    /// Creates a new collider builder that is a compound shape obtained from the decomposition
+    /// of the given simple (possibly concave) polygon into convex polygons.
+    ///
+    /// The polygon must not have holes or self-intersections, and can be given either in
+    /// clockwise or counterclockwise order. Returns `None` if the polygon is degenerate.
+    #[cfg(feature = "dim2")]
+    pub fn polygon(points: &[Point<Real>]) -> Option<Self> {
+        let parts: Vec<_> = convex_partition_polygon(points)
+            .into_iter()
+            .filter_map(|part| {
+                let vertices = part.iter().map(|i| points[*i as usize]).collect();
+                SharedShape::convex_polyline(vertices).map(|shape| (Isometry::identity(), shape))
+            })
+            .collect();
+
+        (!parts.is_empty()).then(|| Self::compound(parts))
+    }
+
     /// Creates a new collider builder that is a round convex polygon formed by the
     /// given polyline assumed to be convex (no convex-hull will be automatically
     /// computed). The polygon shape is dilated by a sphere of radius `border_radius`.
@@ -836,6 +1245,50 @@ impl ColliderBuilder {
         Self::new(SharedShape::heightfield_with_flags(heights, scale, flags))
     }
 
+    /// Initializes a collider builder with a heightfield shape where the height of the sample `i`
+    /// is given by `height(i)`.
+    #[cfg(feature = "dim2")]
+    pub fn heightfield_from_fn(
+        num_samples: usize,
+        scale: Vector<Real>,
+        height: impl FnMut(usize) -> Real,
+    ) -> Self {
+        Self::new(SharedShape::new(HeightField::from_fn(
+            num_samples,
+            scale,
+            height,
+        )))
+    }
+
+    /// Initializes a collider builder with a heightfield shape where the height at the row `i`
+    /// and column `j` is given by `height(i, j)`.
+    #[cfg(feature = "dim3")]
+    pub fn heightfield_from_fn(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self {
+        Self::new(SharedShape::new(HeightField::from_fn(
+            num_rows, num_cols, scale, height,
+        )))
+    }
+
+    /// Initializes a collider builder with a heightfield shape where the height at the row `i`
+    /// and column `j` is given by `height(i, j)`, with the given heightfield flags.
+    #[cfg(feature = "dim3")]
+    pub fn heightfield_from_fn_with_flags(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        flags: HeightFieldFlags,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self {
+        Self::new(SharedShape::new(HeightField::from_fn_with_flags(
+            num_rows, num_cols, scale, flags, height,
+        )))
+    }
+
     /// The default friction coefficient used by the collider builder.
     pub fn default_friction() -> Real {
         0.5
@@ -906,6 +1359,41 @@ impl ColliderBuilder {
         self
     }
 
+    /// Sets the rolling friction coefficient of the collider this builder will build.
+    ///
+    /// Rolling and twist friction coefficients are combined with the friction combine rule.
+    pub fn rolling_friction(mut self, rolling_friction: Real) -> Self {
+        self.rolling_friction = rolling_friction;
+        self
+    }
+
+    /// Sets the twist friction coefficient of the collider this builder will build.
+    #[cfg(feature = "dim3")]
+    pub fn twist_friction(mut self, twist_friction: Real) -> Self {
+        self.twist_friction = twist_friction;
+        self
+    }
+
+    /// Sets a direction-dependent friction for the collider this builder will build.
+    ///
+    /// The friction coefficient is `along` in the direction `local_direction`, expressed in the
+    /// collider’s local-space, and `across` in the orthogonal direction. The isotropic friction
+    /// coefficient of this collider is then ignored. See [`AnisotropicFriction`] for details.
+    #[cfg(feature = "dim3")]
+    pub fn anisotropic_friction(
+        mut self,
+        local_direction: Unit<Vector<Real>>,
+        along: Real,
+        across: Real,
+    ) -> Self {
+        self.anisotropic_friction = Some(AnisotropicFriction {
+            local_direction,
+            along,
+            across,
+        });
+        self
+    }
+
     /// Sets the restitution coefficient of the collider this builder will build.
     pub fn restitution(mut self, restitution: Real) -> Self {
         self.restitution = restitution;
@@ -1013,6 +1501,79 @@ impl ColliderBuilder {
         self
     }
 
+    /// Sets the contact priority of the collider.
+    ///
+    /// Within an island, the contacts are solved by increasing priority, the priority of a contact
+    /// being the greatest priority of the two colliders involved. Since the contacts solved last
+    /// are the most accurate, this can be used to give the best convergence to the
+    /// contacts that matter most, e.g., the feet of a player or the wheels of a vehicle.
+    ///
+    /// Contacts with the same priority are solved in an unspecified order. The islands whose
+    /// contacts are solved in parallel with the `parallel` feature solve each priority with
+    /// colors of its own, after the colors of the lower priorities. Defaults to `0`.
+    pub fn contact_priority(mut self, priority: i8) -> Self {
+        self.contact_priority = priority;
+        self
+    }
+
+    /// Sets the contact softness of the collider, overriding the global
+    /// [`IntegrationParameters::contact_natural_frequency`](crate::dynamics::IntegrationParameters::contact_natural_frequency)
+    /// and [`IntegrationParameters::contact_damping_ratio`](crate::dynamics::IntegrationParameters::contact_damping_ratio)
+    /// for its contacts.
+    ///
+    /// This lets a rubber floor be softer than the steel crates lying on it. If both colliders
+    /// in contact have a contact softness, the softest one, i.e., with the smallest natural
+    /// frequency, is used.
+    pub fn contact_softness(mut self, softness: ContactSoftness) -> Self {
+        self.contact_softness = Some(softness);
+        self
+    }
+
+    /// Sets the velocity of the surface of the collider, in its local-space, with the angular
+    /// velocity applied around its local origin.
+    ///
+    /// The tangential part of this velocity is added to the
+    /// [`SolverContact::tangent_velocity`](crate::geometry::SolverContact::tangent_velocity) of
+    /// all the contacts of the collider, so the bodies touching it are dragged along by friction
+    /// while the collider itself doesn’t move. This simulates conveyor belts, treadmills, or
+    /// turntables without implementing [`PhysicsHooks`](crate::pipeline::PhysicsHooks).
+    /// Defaults to zero.
+    pub fn surface_velocity(mut self, velocity: RigidBodyVelocity) -> Self {
+        self.surface_velocity = velocity;
+        self
+    }
+
+    /// Sets the speculative contact margin of the collider, overriding the global
+    /// [`IntegrationParameters::prediction_distance`](crate::dynamics::IntegrationParameters::prediction_distance)
+    /// for its contacts.
+    ///
+    /// Contacts are generated for the solver as soon as the colliders are closer than this
+    /// margin. Thin fast-moving objects need a large margin not to pass through each other,
+    /// while piles of small debris need a small one to avoid visible gaps between them. If both
+    /// colliders in contact have a speculative margin, the largest one is used.
+    pub fn speculative_margin(mut self, margin: Real) -> Self {
+        self.speculative_margin = Some(margin);
+        self
+    }
+
+    /// Sets whether the intersections of the collider being built are detected along its whole
+    /// motion rather than only at the end of each substep.
+    ///
+    /// Without this, a sensor intersecting a collider only in-between two substeps, e.g., a thin
+    /// trigger plane crossed by a fast projectile, doesn’t detect this intersection. With this,
+    /// the motion of both colliders during the substep is swept with a shape-cast. If an
+    /// intersection is found along the way while the colliders don’t intersect at the end of
+    /// the substep, an intersection-started event is immediately followed by an
+    /// intersection-stopped event.
+    ///
+    /// The broad-phase only accounts for the motion of swept colliders, so this must be enabled
+    /// for the fast-moving collider of the pair, be it the sensor or not. This has no effect on
+    /// contacts between non-sensor colliders, see the rigid-body’s CCD for those.
+    pub fn swept(mut self, swept: bool) -> Self {
+        self.swept = swept;
+        self
+    }
+
     /// Enable or disable the collider after its creation.
     pub fn enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
@@ -1027,6 +1588,11 @@ impl ColliderBuilder {
             restitution: self.restitution,
             friction_combine_rule: self.friction_combine_rule,
             restitution_combine_rule: self.restitution_combine_rule,
+            rolling_friction: self.rolling_friction,
+            #[cfg(feature = "dim3")]
+            twist_friction: self.twist_friction,
+            #[cfg(feature = "dim3")]
+            anisotropic_friction: self.anisotropic_friction,
         };
         let flags = ColliderFlags {
             collision_groups: self.collision_groups,
@@ -1061,6 +1627,13 @@ impl ColliderBuilder {
             coll_type,
             contact_force_event_threshold: self.contact_force_event_threshold,
             contact_skin: self.contact_skin,
+            contact_priority: self.contact_priority,
+            contact_softness: self.contact_softness,
+            surface_velocity: self.surface_velocity,
+            speculative_margin: self.speculative_margin,
+            swept: self.swept,
+            prev_pos: None,
+            disabled_primitives: vec![],
             user_data: self.user_data,
         }
     }
@@ -1071,3 +1644,136 @@ impl From<ColliderBuilder> for Collider {
         val.build()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Collider, ColliderBuilder};
+    use crate::dynamics::RigidBodyBuilder;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn disabled_primitives_are_sorted_and_deduplicated() {
+        let mut collider = ColliderBuilder::ball(1.0).build();
+
+        collider.disable_primitives([5, 2, 5, 7]);
+        assert_eq!(collider.disabled_primitives(), &[2, 5, 7]);
+        assert!(!collider.is_primitive_enabled(5));
+        assert!(collider.is_primitive_enabled(3));
+
+        collider.enable_primitives([7, 2, 4]);
+        assert_eq!(collider.disabled_primitives(), &[5]);
+
+        collider.enable_all_primitives();
+        assert!(collider.disabled_primitives().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn disabled_trimesh_triangles_have_no_contacts() {
+        use na::point;
+
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Two quads side by side, the second one (triangles 2 and 3) being a hole.
+        let vertices = vec![
+            point![-2.0, 0.0, -1.0],
+            point![0.0, 0.0, -1.0],
+            point![0.0, 0.0, 1.0],
+            point![-2.0, 0.0, 1.0],
+            point![2.0, 0.0, -1.0],
+            point![2.0, 0.0, 1.0],
+        ];
+        let indices = vec![[0, 2, 1], [0, 3, 2], [1, 5, 4], [1, 2, 5]];
+        let ground = world
+            .colliders
+            .insert(ColliderBuilder::trimesh(vertices, indices));
+        world.colliders[ground].disable_primitives([2, 3]);
+
+        let ball1 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::new(-1.0, 0.5, 0.0)));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.25),
+            ball1,
+            &mut world.bodies,
+        );
+        let ball2 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::new(1.0, 0.5, 0.0)));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.25),
+            ball2,
+            &mut world.bodies,
+        );
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        assert!(world.bodies[ball1].translation().y > 0.0);
+        assert!(world.bodies[ball2].translation().y < 0.0);
+    }
+
+    #[test]
+    fn speculative_margin_overrides_prediction_distance() {
+        let has_solver_contacts = |margin: Option<Real>| {
+            let mut world = PhysicsWorld::default();
+            let mut pipeline = PhysicsPipeline::new();
+
+            let ground = world
+                .colliders
+                .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+            // The ball is 0.5 above the ground.
+            let mut ball = ColliderBuilder::ball(0.5);
+            ball.speculative_margin = margin;
+            let body = world
+                .bodies
+                .insert(RigidBodyBuilder::dynamic().translation(Vector::y()));
+            let ball = world
+                .colliders
+                .insert_with_parent(ball, body, &mut world.bodies);
+            world.step(&mut pipeline, None, &(), &());
+
+            world
+                .narrow_phase
+                .contact_pair(ground, ball)
+                .map(|pair| pair.has_any_active_contact)
+                .unwrap_or(false)
+        };
+
+        assert!(!has_solver_contacts(None));
+        assert!(!has_solver_contacts(Some(0.4)));
+        assert!(has_solver_contacts(Some(0.6)));
+    }
+
+    #[test]
+    fn combined_speculative_margin_is_the_largest_override() {
+        let with_margin = |margin| {
+            let mut collider = ColliderBuilder::ball(1.0).build();
+            collider.set_speculative_margin(margin);
+            collider
+        };
+        let (none, small, large) = (
+            with_margin(None),
+            with_margin(Some(0.1)),
+            with_margin(Some(0.3)),
+        );
+
+        assert_eq!(
+            Collider::combined_speculative_margin(&none, &none, 0.2),
+            0.2
+        );
+        assert_eq!(
+            Collider::combined_speculative_margin(&none, &small, 0.2),
+            0.1
+        );
+        assert_eq!(
+            Collider::combined_speculative_margin(&large, &small, 0.2),
+            0.3
+        );
+    }
+}
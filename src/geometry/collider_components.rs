@@ -1,5 +1,7 @@
 use crate::dynamics::{CoefficientCombineRule, MassProperties, RigidBodyHandle, RigidBodyType};
 use crate::geometry::{BroadPhaseProxyIndex, InteractionGroups, Shape, SharedShape};
+#[cfg(feature = "dim3")]
+use crate::math::Vector;
 use crate::math::{Isometry, Real};
 use crate::parry::partitioning::IndexedData;
 use crate::pipeline::{ActiveEvents, ActiveHooks};
@@ -67,6 +69,12 @@ bitflags::bitflags! {
         const PARENT_EFFECTIVE_DOMINANCE = 1 << 7; // NF update.
         /// Flag indicating that whether or not the collider is enabled was changed.
         const ENABLED_OR_DISABLED = 1 << 8; // BF & NF updates.
+        /// Flag indicating that some primitives of the collider’s shape were enabled or disabled.
+        const PRIMITIVES = 1 << 9; // => NF update.
+        /// Flag indicating that the active collision types of the collider were modified.
+        const ACTIVE_COLLISION_TYPES = 1 << 10; // => NF update.
+        /// Flag indicating that the active physics hooks of the collider were modified.
+        const ACTIVE_HOOKS = 1 << 11; // => NF update.
     }
 }
 
@@ -276,6 +284,40 @@ pub struct ColliderMaterial {
     pub friction_combine_rule: CoefficientCombineRule,
     /// The rule applied to combine the restitution coefficients of two colliders.
     pub restitution_combine_rule: CoefficientCombineRule,
+    /// The rolling friction coefficient of this collider.
+    ///
+    /// This has the dimension of a length: the torque resisting the rolling of the bodies in
+    /// contact is at most this coefficient times the normal force. Should be `>= 0`.
+    pub rolling_friction: Real,
+    /// The twist friction coefficient of this collider.
+    ///
+    /// This has the dimension of a length: the torque resisting the spinning of the bodies in
+    /// contact around the contact normal is at most this coefficient times the normal force.
+    /// Should be `>= 0`.
+    #[cfg(feature = "dim3")]
+    pub twist_friction: Real,
+    /// The direction-dependent friction of this collider, replacing `friction` if set.
+    #[cfg(feature = "dim3")]
+    pub anisotropic_friction: Option<AnisotropicFriction>,
+}
+
+/// A friction with different coefficients along and across a direction attached to a collider.
+///
+/// The friction force is bounded by an ellipse in the contact plane instead of a disk: its
+/// semi-axes are the `along` coefficient in the direction of the projection of
+/// `local_direction` onto the contact plane, and the `across` coefficient in the orthogonal
+/// direction. This models skis, treads, or brushed materials sliding more easily in one
+/// direction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg(feature = "dim3")]
+pub struct AnisotropicFriction {
+    /// The friction direction, expressed in the collider’s local-space.
+    pub local_direction: na::Unit<Vector<Real>>,
+    /// The friction coefficient along `local_direction`. Should be `>= 0`.
+    pub along: Real,
+    /// The friction coefficient orthogonal to `local_direction`. Should be `>= 0`.
+    pub across: Real,
 }
 
 impl ColliderMaterial {
@@ -296,6 +338,11 @@ impl Default for ColliderMaterial {
             restitution: 0.0,
             friction_combine_rule: CoefficientCombineRule::default(),
             restitution_combine_rule: CoefficientCombineRule::default(),
+            rolling_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            twist_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            anisotropic_friction: None,
         }
     }
 }
@@ -1,9 +1,10 @@
-use crate::dynamics::{RigidBodyHandle, RigidBodySet};
+use crate::dynamics::{ContactSoftness, RigidBodyHandle, RigidBodySet};
 use crate::geometry::{ColliderHandle, ColliderSet, Contact, ContactManifold};
-use crate::math::{Point, Real, TangentImpulse, Vector};
+use crate::math::{Isometry, Point, Real, TangentImpulse, Vector};
 use crate::pipeline::EventHandler;
 use crate::prelude::CollisionEventFlags;
 use parry::query::ContactManifoldsWorkspace;
+use parry::shape::PackedFeatureId;
 
 use super::CollisionEvent;
 
@@ -18,6 +19,39 @@ bitflags::bitflags! {
         /// The constraint solver will take this contact manifold into
         /// account for force computation.
         const COMPUTE_IMPULSES = 0b001;
+        /// Overrides the dominance groups for this contact pair so that the first collider's
+        /// rigid-body dominates the second one.
+        ///
+        /// This only has an effect if both rigid-bodies are dynamic.
+        const FIRST_DOMINATES = 0b010;
+        /// Overrides the dominance groups for this contact pair so that the second collider's
+        /// rigid-body dominates the first one.
+        ///
+        /// This only has an effect if both rigid-bodies are dynamic.
+        const SECOND_DOMINATES = 0b100;
+        /// Overrides the dominance groups for this contact pair so that neither rigid-body
+        /// dominates the other.
+        ///
+        /// This only has an effect if both rigid-bodies are dynamic.
+        const EQUAL_DOMINANCE = 0b1000;
+    }
+}
+
+impl SolverFlags {
+    /// The relative dominance forced by these flags, if any.
+    ///
+    /// If several dominance overrides are set, `FIRST_DOMINATES` takes precedence over
+    /// `SECOND_DOMINATES` which takes precedence over `EQUAL_DOMINANCE`.
+    pub fn relative_dominance_override(&self) -> Option<i16> {
+        if self.contains(SolverFlags::FIRST_DOMINATES) {
+            Some(1)
+        } else if self.contains(SolverFlags::SECOND_DOMINATES) {
+            Some(-1)
+        } else if self.contains(SolverFlags::EQUAL_DOMINANCE) {
+            Some(0)
+        } else {
+            None
+        }
     }
 }
 
@@ -27,6 +61,13 @@ impl Default for SolverFlags {
     }
 }
 
+/// A small piece of user-defined data attached to a contact manifold or to a contact point,
+/// and preserved across timesteps as long as the manifold or the contact point exists.
+///
+/// These are typically written by [`PhysicsHooks::modify_solver_contacts`](crate::pipeline::PhysicsHooks::modify_solver_contacts)
+/// to keep track of some state of the contact, e.g., the wetness or the wear of a surface.
+pub type ContactUserSlots = [u32; 4];
+
 #[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 /// A single contact between two collider.
@@ -34,14 +75,29 @@ pub struct ContactData {
     /// The impulse, along the contact normal, applied by this contact to the first collider's rigid-body.
     ///
     /// The impulse applied to the second collider's rigid-body is given by `-impulse`.
+    ///
+    /// This is the sum of the impulses applied during all the substeps of the last simulation
+    /// step, so `impulse / dt` is the average force applied by this contact during that step.
     pub impulse: Real,
     /// The friction impulse along the vector orthonormal to the contact normal, applied to the first
     /// collider's rigid-body.
+    ///
+    /// Like [`ContactData::impulse`], this is summed over all the substeps of the last simulation
+    /// step.
     pub tangent_impulse: TangentImpulse<Real>,
     /// The impulse retained for warmstarting the next simulation step.
     pub warmstart_impulse: Real,
     /// The friction impulse retained for warmstarting the next simulation step.
     pub warmstart_tangent_impulse: TangentImpulse<Real>,
+    /// User-defined data attached to this contact point.
+    pub user_slots: ContactUserSlots,
+    /// An identifier of this contact point, unique within its contact pair, that persists
+    /// across simulation steps for as long as the contact is tracked by its manifold.
+    ///
+    /// This allows attaching effects (e.g., sounds, particles) to "the same" contact over time,
+    /// even if its index in its manifold changes. It is assigned by the narrow-phase, starting
+    /// at 1, when the contact is first computed. A value of 0 means it wasn’t assigned yet.
+    pub id: u32,
 }
 
 impl Default for ContactData {
@@ -51,10 +107,39 @@ impl Default for ContactData {
             tangent_impulse: na::zero(),
             warmstart_impulse: 0.0,
             warmstart_tangent_impulse: na::zero(),
+            user_slots: [0; 4],
+            id: 0,
         }
     }
 }
 
+/// The warmstart impulses of a single contact, identified by the features of the colliders in
+/// contact rather than by its index in its manifold.
+///
+/// This lets the warmstart impulses be serialized, e.g., with a network snapshot, and restored
+/// with [`NarrowPhase::set_warmstart_impulses`](crate::geometry::NarrowPhase::set_warmstart_impulses)
+/// once the same contacts were computed again.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct WarmstartImpulse {
+    /// The first collider of the contact pair.
+    pub collider1: ColliderHandle,
+    /// The second collider of the contact pair.
+    pub collider2: ColliderHandle,
+    /// The sub-shape of the first collider involved in the contact manifold.
+    pub subshape1: u32,
+    /// The sub-shape of the second collider involved in the contact manifold.
+    pub subshape2: u32,
+    /// The feature of the first collider’s sub-shape involved in the contact.
+    pub feature1: PackedFeatureId,
+    /// The feature of the second collider’s sub-shape involved in the contact.
+    pub feature2: PackedFeatureId,
+    /// The impulse retained for warmstarting the normal constraint.
+    pub impulse: Real,
+    /// The friction impulse retained for warmstarting the friction constraints.
+    pub tangent_impulse: TangentImpulse<Real>,
+}
+
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug)]
 /// The description of all the contacts between a pair of colliders.
@@ -128,6 +213,14 @@ pub struct ContactPair {
     /// Was a `CollisionEvent::Started` emitted for this collider?
     pub(crate) start_event_emitted: bool,
     pub(crate) workspace: Option<ContactManifoldsWorkspace>,
+    /// The relative position of the colliders when the contacts against a fixed heightfield
+    /// were last queried, if they are cached.
+    pub(crate) heightfield_cache_pos12: Option<Isometry<Real>>,
+    /// The identifier given to the next new contact point of this pair.
+    pub(crate) next_contact_id: u32,
+    /// Is this pair currently reporting contact force events because of some
+    /// [`ContactForceEventHysteresis`](crate::pipeline::ContactForceEventHysteresis)?
+    pub(crate) reporting_contact_forces: bool,
 }
 
 impl ContactPair {
@@ -139,6 +232,22 @@ impl ContactPair {
             manifolds: Vec::new(),
             start_event_emitted: false,
             workspace: None,
+            heightfield_cache_pos12: None,
+            next_contact_id: 1,
+            reporting_contact_forces: false,
+        }
+    }
+
+    /// Assigns a new [`ContactData::id`] to the contact points that don’t have one yet.
+    pub(crate) fn assign_contact_ids(&mut self) {
+        for manifold in &mut self.manifolds {
+            for pt in &mut manifold.points {
+                if pt.data.id == 0 {
+                    pt.data.id = self.next_contact_id;
+                    // Skip 0 on overflow since it means “unassigned”.
+                    self.next_contact_id = self.next_contact_id.checked_add(1).unwrap_or(1);
+                }
+            }
         }
     }
 
@@ -147,6 +256,66 @@ impl ContactPair {
         self.manifolds.clear();
         self.has_any_active_contact = false;
         self.workspace = None;
+        self.heightfield_cache_pos12 = None;
+    }
+
+    /// The warmstart impulses of all the contacts of this contact pair.
+    pub fn warmstart_impulses(&self) -> impl Iterator<Item = WarmstartImpulse> + '_ {
+        self.manifolds.iter().flat_map(move |manifold| {
+            manifold.points.iter().map(move |contact| WarmstartImpulse {
+                collider1: self.collider1,
+                collider2: self.collider2,
+                subshape1: manifold.subshape1,
+                subshape2: manifold.subshape2,
+                feature1: contact.fid1,
+                feature2: contact.fid2,
+                impulse: contact.data.warmstart_impulse,
+                tangent_impulse: contact.data.warmstart_tangent_impulse,
+            })
+        })
+    }
+
+    /// Sets the warmstart impulses of the contact of this pair involving the same sub-shapes and
+    /// features as `impulse`.
+    ///
+    /// Returns `false` if no such contact exists, or if `impulse` refers to another pair of
+    /// colliders or to the same colliders in the opposite order.
+    pub fn set_warmstart_impulse(&mut self, impulse: &WarmstartImpulse) -> bool {
+        if impulse.collider1 != self.collider1 || impulse.collider2 != self.collider2 {
+            return false;
+        }
+
+        let contact = self
+            .manifolds
+            .iter_mut()
+            .filter(|m| m.subshape1 == impulse.subshape1 && m.subshape2 == impulse.subshape2)
+            .flat_map(|m| m.points.iter_mut())
+            .find(|c| c.fid1 == impulse.feature1 && c.fid2 == impulse.feature2);
+
+        if let Some(contact) = contact {
+            contact.data.warmstart_impulse = impulse.impulse;
+            contact.data.warmstart_tangent_impulse = impulse.tangent_impulse;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sets to zero the warmstart impulses of all the contacts of this contact pair, so that the
+    /// next step solves them from scratch.
+    pub fn reset_warmstart_impulses(&mut self) {
+        for contact in self.manifolds.iter_mut().flat_map(|m| m.points.iter_mut()) {
+            contact.data.warmstart_impulse = 0.0;
+            contact.data.warmstart_tangent_impulse = na::zero();
+        }
+    }
+
+    /// Multiplies the warmstart impulses of all the contacts of this pair by `scale`.
+    pub(crate) fn scale_warmstart_impulses(&mut self, scale: Real) {
+        for contact in self.manifolds.iter_mut().flat_map(|m| m.points.iter_mut()) {
+            contact.data.warmstart_impulse *= scale;
+            contact.data.warmstart_tangent_impulse *= scale;
+        }
     }
 
     /// The sum of all the impulses applied by contacts on this contact pair.
@@ -282,13 +451,148 @@ pub struct ContactManifoldData {
     pub solver_contacts: Vec<SolverContact>,
     /// The relative dominance of the bodies involved in this contact manifold.
     pub relative_dominance: i16,
+    /// The solver priority of this contact manifold, i.e., the greatest contact priority of the
+    /// two colliders involved.
+    ///
+    /// See [`Collider::contact_priority`](crate::geometry::Collider::contact_priority).
+    pub priority: i8,
+    /// The contact softness overriding the global one for this contact manifold, combined
+    /// from the contact softness of the two colliders involved.
+    ///
+    /// See [`Collider::contact_softness`](crate::geometry::Collider::contact_softness).
+    pub contact_softness: Option<ContactSoftness>,
+    /// The rolling friction coefficient of this contact manifold, combined from the rolling
+    /// friction coefficients of the two colliders involved.
+    pub rolling_friction: Real,
+    /// The twist friction coefficient of this contact manifold, combined from the twist
+    /// friction coefficients of the two colliders involved.
+    #[cfg(feature = "dim3")]
+    pub twist_friction: Real,
+    /// The direction-dependent friction of this contact manifold, if one of the colliders
+    /// involved has an anisotropic friction.
+    ///
+    /// See [`Collider::anisotropic_friction`](crate::geometry::Collider::anisotropic_friction).
+    #[cfg(feature = "dim3")]
+    pub anisotropic_friction: Option<ContactAnisotropicFriction>,
     /// A user-defined piece of data.
     pub user_data: u32,
+    /// Additional user-defined data attached to this contact manifold.
+    pub user_slots: ContactUserSlots,
+    /// The result of the last call to `PhysicsHooks::modify_solver_contacts`, if it was marked
+    /// as persistent.
+    pub(crate) cached_modification: Option<CachedContactModification>,
+}
+
+/// The cosine of the maximum angle the contact normal can deviate from the one it had when
+/// a persistent contact modification was cached, before invalidating that cache.
+const CACHED_MODIFICATION_MIN_NORMAL_COS: Real = 0.999;
+
+/// Bitmask identifying a set of solver contacts by their `contact_id`.
+pub(crate) type SolverContactIdMask = [u64; 4];
+
+pub(crate) fn solver_contact_id_mask(contacts: &[SolverContact]) -> SolverContactIdMask {
+    let mut mask = [0; 4];
+    for contact in contacts {
+        mask[contact.contact_id as usize / 64] |= 1 << (contact.contact_id % 64);
+    }
+    mask
 }
 
-/// A contact seen by the constraints solver for computing forces.
 #[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+struct CachedSolverContact {
+    contact_id: u8,
+    friction: Real,
+    restitution: Real,
+    tangent_velocity: Vector<Real>,
+}
+
+/// A persistent contact modification, re-applied to the solver contacts of a manifold instead
+/// of calling `PhysicsHooks::modify_solver_contacts` again.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub(crate) struct CachedContactModification {
+    subshape1: u32,
+    subshape2: u32,
+    local_n1: Vector<Real>,
+    // The modified normal, expressed in the local-space of the first subshape.
+    modified_local_normal: Vector<Real>,
+    original_contact_ids: SolverContactIdMask,
+    modified_contacts: Vec<CachedSolverContact>,
+    modified_contact_softness: Option<ContactSoftness>,
+}
+
+impl CachedContactModification {
+    /// Records the modification applied by the user to `original_contacts`.
+    pub fn new(
+        manifold: &ContactManifold,
+        world_pos1: &Isometry<Real>,
+        original_contacts: SolverContactIdMask,
+        modified_contacts: &[SolverContact],
+        modified_normal: &Vector<Real>,
+        modified_contact_softness: Option<ContactSoftness>,
+    ) -> Self {
+        Self {
+            subshape1: manifold.subshape1,
+            subshape2: manifold.subshape2,
+            local_n1: manifold.local_n1,
+            modified_local_normal: world_pos1.inverse_transform_vector(modified_normal),
+            original_contact_ids: original_contacts,
+            modified_contacts: modified_contacts
+                .iter()
+                .map(|c| CachedSolverContact {
+                    contact_id: c.contact_id,
+                    friction: c.friction,
+                    restitution: c.restitution,
+                    tangent_velocity: c.tangent_velocity,
+                })
+                .collect(),
+            modified_contact_softness,
+        }
+    }
+
+    /// Can this cached modification be re-applied to the given freshly computed solver contacts?
+    ///
+    /// This is not the case if the set of solver contacts changed, or if the contact normal
+    /// changed too much since the modification was cached.
+    pub fn is_valid_for(&self, manifold: &ContactManifold, contacts: &[SolverContact]) -> bool {
+        self.subshape1 == manifold.subshape1
+            && self.subshape2 == manifold.subshape2
+            && self.local_n1.dot(&manifold.local_n1) >= CACHED_MODIFICATION_MIN_NORMAL_COS
+            && self.original_contact_ids == solver_contact_id_mask(contacts)
+    }
+
+    /// Re-applies this modification to the given freshly computed solver contacts.
+    pub fn apply(
+        &self,
+        world_pos1: &Isometry<Real>,
+        contacts: &mut Vec<SolverContact>,
+        normal: &mut Vector<Real>,
+        contact_softness: &mut Option<ContactSoftness>,
+    ) {
+        let original_contacts = std::mem::take(contacts);
+
+        for cached in &self.modified_contacts {
+            if let Some(original) = original_contacts
+                .iter()
+                .find(|c| c.contact_id == cached.contact_id)
+            {
+                let mut contact = *original;
+                contact.friction = cached.friction;
+                contact.restitution = cached.restitution;
+                contact.tangent_velocity = cached.tangent_velocity;
+                contacts.push(contact);
+            }
+        }
+
+        *normal = world_pos1 * self.modified_local_normal;
+        *contact_softness = self.modified_contact_softness;
+    }
+}
+
+/// A contact seen by the constraints solver for computing forces.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub struct SolverContact {
     /// The index of the manifold contact used to generate this solver contact.
     pub(crate) contact_id: u8,
@@ -315,6 +619,12 @@ pub struct SolverContact {
 }
 
 impl SolverContact {
+    /// The index, in [`ContactManifold::points`], of the contact used to generate this solver
+    /// contact.
+    pub fn contact_id(&self) -> usize {
+        self.contact_id as usize
+    }
+
     /// Should we treat this contact as a bouncy contact?
     /// If `true`, use [`Self::restitution`].
     pub fn is_bouncy(&self) -> bool {
@@ -348,7 +658,16 @@ impl ContactManifoldData {
             normal: Vector::zeros(),
             solver_contacts: Vec::new(),
             relative_dominance: 0,
+            priority: 0,
+            contact_softness: None,
+            rolling_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            twist_friction: 0.0,
+            #[cfg(feature = "dim3")]
+            anisotropic_friction: None,
             user_data: 0,
+            user_slots: [0; 4],
+            cached_modification: None,
         }
     }
 
@@ -358,6 +677,43 @@ impl ContactManifoldData {
     pub fn num_active_contacts(&self) -> usize {
         self.solver_contacts.len()
     }
+
+    /// Whether the contacts of this manifold resist rolling or twisting.
+    #[inline]
+    pub(crate) fn has_angular_friction(&self) -> bool {
+        #[cfg(feature = "dim2")]
+        let twist_friction: Real = 0.0;
+        #[cfg(feature = "dim3")]
+        let twist_friction = self.twist_friction;
+        self.rolling_friction > 0.0 || twist_friction > 0.0
+    }
+
+    /// Whether the contacts of this manifold can only be solved by the non-SIMD constraints.
+    #[inline]
+    pub(crate) fn needs_scalar_constraints(&self) -> bool {
+        #[cfg(feature = "dim2")]
+        let is_anisotropic = false;
+        #[cfg(feature = "dim3")]
+        let is_anisotropic = self.anisotropic_friction.is_some();
+        is_anisotropic || self.has_angular_friction()
+    }
+}
+
+/// The direction-dependent friction of a contact manifold.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg(feature = "dim3")]
+pub struct ContactAnisotropicFriction {
+    /// The world-space friction direction.
+    ///
+    /// Only its projection onto the contact plane matters.
+    pub direction: Vector<Real>,
+    /// The friction coefficient along `direction`, relative to the friction coefficients of
+    /// the solver contacts.
+    pub along_scale: Real,
+    /// The friction coefficient orthogonal to `direction`, relative to the friction
+    /// coefficients of the solver contacts.
+    pub across_scale: Real,
 }
 
 /// Additional methods for the contact manifold.
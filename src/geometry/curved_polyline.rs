@@ -0,0 +1,864 @@
+use crate::dynamics::MassProperties;
+use crate::geometry::shape_utils;
+use crate::math::{Isometry, Point, Real, Vector};
+use na::{RealField, Unit};
+use parry::bounding_volume::{Aabb, BoundingSphere};
+use parry::query::{PointProjection, PointQuery, Ray, RayCast, RayIntersection};
+use parry::shape::{
+    Compound, FeatureId, PolygonalFeature, PolygonalFeatureMap, Segment, Shape, ShapeType,
+    SharedShape, SimdCompositeShape, SupportMap, TypedShape,
+};
+
+/// The maximum angle covered by each convex piece a curve is split into for contact generation.
+fn max_piece_angle() -> Real {
+    Real::pi() / 32.0
+}
+
+/// The number of uniform samples used to initialize the closest-point search on a Bézier curve.
+const NUM_BEZIER_PROJECTION_SAMPLES: usize = 8;
+/// The number of Newton iterations refining the closest-point search on a Bézier curve.
+const NUM_BEZIER_PROJECTION_ITERATIONS: usize = 8;
+
+fn perp(v: &Vector<Real>) -> Vector<Real> {
+    Vector::new(-v.y, v.x)
+}
+
+/// A single element of a [`CurvedPolyline`].
+///
+/// Each element is an infinitely thin curve. Point projections and ray-casts are computed
+/// exactly against the curve itself while contacts are computed against the convex hull
+/// of small pieces of the curve.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum CurveSegment {
+    /// A straight line segment.
+    Line {
+        /// The first endpoint.
+        a: Point<Real>,
+        /// The second endpoint.
+        b: Point<Real>,
+    },
+    /// A circular arc.
+    Arc {
+        /// The center of the circle the arc is part of.
+        center: Point<Real>,
+        /// The radius of the circle the arc is part of.
+        radius: Real,
+        /// The angle (measured from the `x` axis) of the first endpoint of the arc.
+        start_angle: Real,
+        /// The signed angle covered by the arc. The arc is counterclockwise if this is positive.
+        sweep_angle: Real,
+    },
+    /// A quadratic Bézier curve.
+    QuadraticBezier {
+        /// The first endpoint.
+        a: Point<Real>,
+        /// The control point.
+        control: Point<Real>,
+        /// The second endpoint.
+        b: Point<Real>,
+    },
+}
+
+impl CurveSegment {
+    /// Creates a straight line segment.
+    pub fn line(a: Point<Real>, b: Point<Real>) -> Self {
+        Self::Line { a, b }
+    }
+
+    /// Creates a circular arc from its center, radius, start angle, and signed sweep angle.
+    pub fn arc(center: Point<Real>, radius: Real, start_angle: Real, sweep_angle: Real) -> Self {
+        Self::Arc {
+            center,
+            radius,
+            start_angle,
+            sweep_angle,
+        }
+    }
+
+    /// Creates a quadratic Bézier curve.
+    pub fn quadratic_bezier(a: Point<Real>, control: Point<Real>, b: Point<Real>) -> Self {
+        Self::QuadraticBezier { a, control, b }
+    }
+
+    /// The first endpoint of this curve.
+    pub fn start_point(&self) -> Point<Real> {
+        self.point_at(0.0)
+    }
+
+    /// The last endpoint of this curve.
+    pub fn end_point(&self) -> Point<Real> {
+        self.point_at(1.0)
+    }
+
+    /// The point of this curve at the parameter `t` in `[0, 1]`.
+    pub fn point_at(&self, t: Real) -> Point<Real> {
+        match *self {
+            Self::Line { a, b } => a + (b - a) * t,
+            Self::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } => {
+                let (sin, cos) = (start_angle + sweep_angle * t).sin_cos();
+                center + Vector::new(cos, sin) * radius
+            }
+            Self::QuadraticBezier { a, control, b } => {
+                let s = 1.0 - t;
+                Point::from(
+                    a.coords * (s * s) + control.coords * (2.0 * s * t) + b.coords * (t * t),
+                )
+            }
+        }
+    }
+
+    /// The (non-normalized) tangent of this curve at the parameter `t` in `[0, 1]`.
+    pub fn tangent_at(&self, t: Real) -> Vector<Real> {
+        match *self {
+            Self::Line { a, b } => b - a,
+            Self::Arc {
+                radius,
+                start_angle,
+                sweep_angle,
+                ..
+            } => {
+                let (sin, cos) = (start_angle + sweep_angle * t).sin_cos();
+                Vector::new(-sin, cos) * (radius * sweep_angle)
+            }
+            Self::QuadraticBezier { a, control, b } => {
+                ((control - a) * (1.0 - t) + (b - control) * t) * 2.0
+            }
+        }
+    }
+
+    /// Splits this curve into pieces small enough for their convex hulls to be a good
+    /// approximation of the curve.
+    pub fn subdivide(&self) -> Vec<CurveSegment> {
+        match *self {
+            Self::Line { .. } => vec![*self],
+            Self::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } => {
+                let n = (sweep_angle.abs() / max_piece_angle()).ceil().max(1.0) as usize;
+                let piece_sweep = sweep_angle / n as Real;
+                (0..n)
+                    .map(|i| {
+                        Self::arc(
+                            center,
+                            radius,
+                            start_angle + piece_sweep * i as Real,
+                            piece_sweep,
+                        )
+                    })
+                    .collect()
+            }
+            Self::QuadraticBezier { a, control, b } => {
+                let turn = (control - a).angle(&(b - control));
+                let n = (turn / max_piece_angle()).ceil().max(1.0) as usize;
+                (0..n)
+                    .map(|i| {
+                        let t0 = i as Real / n as Real;
+                        let t1 = (i + 1) as Real / n as Real;
+                        // The control point of the sub-curve is the intersection of the
+                        // tangents at its endpoints, which is given by the blossom B(t0, t1).
+                        let ctrl = a.coords * ((1.0 - t0) * (1.0 - t1))
+                            + control.coords * ((1.0 - t0) * t1 + t0 * (1.0 - t1))
+                            + b.coords * (t0 * t1);
+                        Self::quadratic_bezier(self.point_at(t0), ctrl.into(), self.point_at(t1))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Computes the parameter `t` of the point of this curve closest to `pt`.
+    pub fn closest_parameter(&self, pt: &Point<Real>) -> Real {
+        match *self {
+            Self::Line { a, b } => {
+                let ab = b - a;
+                let sq_len = ab.norm_squared();
+                if sq_len > Real::default_epsilon() {
+                    ((pt - a).dot(&ab) / sq_len).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            }
+            Self::Arc {
+                center,
+                start_angle,
+                sweep_angle,
+                ..
+            } => {
+                let dpt = pt - center;
+                if dpt.norm_squared() <= Real::default_epsilon() || sweep_angle == 0.0 {
+                    return 0.0;
+                }
+
+                let angle = dpt.y.atan2(dpt.x);
+                if let Some(t) = Self::arc_parameter(angle, start_angle, sweep_angle) {
+                    t
+                } else if na::distance_squared(pt, &self.start_point())
+                    <= na::distance_squared(pt, &self.end_point())
+                {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+            Self::QuadraticBezier { control, a, b } => {
+                let dist_at = |t: Real| na::distance_squared(pt, &self.point_at(t));
+                let mut best_t = 0.0;
+                let mut best_dist = dist_at(0.0);
+
+                for i in 1..=NUM_BEZIER_PROJECTION_SAMPLES {
+                    let t = i as Real / NUM_BEZIER_PROJECTION_SAMPLES as Real;
+                    let dist = dist_at(t);
+                    if dist < best_dist {
+                        best_dist = dist;
+                        best_t = t;
+                    }
+                }
+
+                // Newton iterations on the derivative of the squared distance.
+                let second_derivative = (a.coords - control.coords * 2.0 + b.coords) * 2.0;
+                let mut t = best_t;
+                for _ in 0..NUM_BEZIER_PROJECTION_ITERATIONS {
+                    let diff = self.point_at(t) - pt;
+                    let tangent = self.tangent_at(t);
+                    let denom = tangent.norm_squared() + diff.dot(&second_derivative);
+                    if denom.abs() <= Real::default_epsilon() {
+                        break;
+                    }
+                    t = (t - diff.dot(&tangent) / denom).clamp(0.0, 1.0);
+                }
+
+                if dist_at(t) < best_dist {
+                    t
+                } else {
+                    best_t
+                }
+            }
+        }
+    }
+
+    /// The parameter of the arc point with the given angle, if it is part of the arc.
+    fn arc_parameter(angle: Real, start_angle: Real, sweep_angle: Real) -> Option<Real> {
+        let rel = (angle - start_angle) * sweep_angle.signum();
+        let rel = rel.rem_euclid(Real::two_pi());
+
+        if rel <= sweep_angle.abs() {
+            Some(rel / sweep_angle.abs())
+        } else {
+            None
+        }
+    }
+
+    fn first_ray_hit(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        hits: impl IntoIterator<Item = (Real, Real)>,
+    ) -> Option<RayIntersection> {
+        let (toi, t) = hits
+            .into_iter()
+            .filter(|(toi, _)| *toi >= 0.0 && *toi <= max_time_of_impact)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))?;
+        let mut normal = perp(&self.tangent_at(t))
+            .try_normalize(Real::default_epsilon())
+            .unwrap_or_else(|| -ray.dir);
+
+        if normal.dot(&ray.dir) > 0.0 {
+            normal = -normal;
+        }
+
+        Some(RayIntersection::new(toi, normal, FeatureId::Unknown))
+    }
+}
+
+impl SupportMap for CurveSegment {
+    fn local_support_point(&self, dir: &Vector<Real>) -> Point<Real> {
+        match *self {
+            Self::Line { a, b } => Segment::new(a, b).local_support_point(dir),
+            Self::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } => {
+                let angle = dir.y.atan2(dir.x);
+                if dir.norm_squared() > Real::default_epsilon()
+                    && Self::arc_parameter(angle, start_angle, sweep_angle).is_some()
+                {
+                    center + dir.normalize() * radius
+                } else {
+                    let (start, end) = (self.start_point(), self.end_point());
+                    if start.coords.dot(dir) >= end.coords.dot(dir) {
+                        start
+                    } else {
+                        end
+                    }
+                }
+            }
+            Self::QuadraticBezier { a, control, b } => {
+                // The projection of the curve on `dir` is a quadratic polynomial of `t`
+                // so its maximum is either at an endpoint or at the root of its derivative.
+                let da = (control - a).dot(dir);
+                let db = (b - control).dot(dir);
+                let mut best = if a.coords.dot(dir) >= b.coords.dot(dir) {
+                    a
+                } else {
+                    b
+                };
+
+                if da != db {
+                    let t = da / (da - db);
+                    if t > 0.0 && t < 1.0 {
+                        let pt = self.point_at(t);
+                        if pt.coords.dot(dir) > best.coords.dot(dir) {
+                            best = pt;
+                        }
+                    }
+                }
+
+                best
+            }
+        }
+    }
+}
+
+impl PolygonalFeatureMap for CurveSegment {
+    fn local_support_feature(&self, dir: &Unit<Vector<Real>>, out_feature: &mut PolygonalFeature) {
+        match *self {
+            Self::Line { a, b } => Segment::new(a, b).local_support_feature(dir, out_feature),
+            _ => shape_utils::set_single_vertex_feature(self.local_support_point(dir), out_feature),
+        }
+    }
+}
+
+impl RayCast for CurveSegment {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        let sq_dir = ray.dir.norm_squared();
+        if sq_dir <= Real::default_epsilon() {
+            return None;
+        }
+
+        match *self {
+            Self::Line { a, b } => {
+                Segment::new(a, b).cast_local_ray_and_get_normal(ray, max_time_of_impact, solid)
+            }
+            Self::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } => {
+                let dorig = ray.origin - center;
+                let b = dorig.dot(&ray.dir);
+                let c = dorig.norm_squared() - radius * radius;
+                let delta = b * b - sq_dir * c;
+                if delta < 0.0 {
+                    return None;
+                }
+
+                let sqrt_delta = delta.sqrt();
+                let hits = [(-b - sqrt_delta) / sq_dir, (-b + sqrt_delta) / sq_dir]
+                    .into_iter()
+                    .filter_map(|toi| {
+                        let dpt = ray.point_at(toi) - center;
+                        Self::arc_parameter(dpt.y.atan2(dpt.x), start_angle, sweep_angle)
+                            .map(|t| (toi, t))
+                    });
+                self.first_ray_hit(ray, max_time_of_impact, hits)
+            }
+            Self::QuadraticBezier { a, control, b } => {
+                // Solve `n · (B(t) - origin) = 0` where `n` is orthogonal to the ray.
+                let n = perp(&ray.dir);
+                let c0 = n.dot(&(a - ray.origin));
+                let c1 = 2.0 * n.dot(&(control - a));
+                let c2 = n.dot(&(a.coords - control.coords * 2.0 + b.coords));
+
+                let mut roots = [None, None];
+                if c2.abs() > Real::default_epsilon() {
+                    let delta = c1 * c1 - 4.0 * c2 * c0;
+                    if delta >= 0.0 {
+                        let sqrt_delta = delta.sqrt();
+                        roots[0] = Some((-c1 - sqrt_delta) / (2.0 * c2));
+                        roots[1] = Some((-c1 + sqrt_delta) / (2.0 * c2));
+                    }
+                } else if c1.abs() > Real::default_epsilon() {
+                    roots[0] = Some(-c0 / c1);
+                }
+
+                let hits = roots
+                    .into_iter()
+                    .flatten()
+                    .filter(|t| (0.0..=1.0).contains(t))
+                    .map(|t| ((self.point_at(t) - ray.origin).dot(&ray.dir) / sq_dir, t));
+                self.first_ray_hit(ray, max_time_of_impact, hits)
+            }
+        }
+    }
+}
+
+impl PointQuery for CurveSegment {
+    fn project_local_point(&self, pt: &Point<Real>, _solid: bool) -> PointProjection {
+        let proj = self.point_at(self.closest_parameter(pt));
+        // Like segments, curves are infinitely thin so points are never inside of them.
+        PointProjection::new(false, proj)
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+impl Shape for CurveSegment {
+    fn compute_local_aabb(&self) -> Aabb {
+        shape_utils::support_map_local_aabb(self)
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        self.compute_local_aabb().bounding_sphere()
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(*self)
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, _num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        match *self {
+            Self::Line { a, b } => Some(Box::new(Self::line(
+                a.coords.component_mul(scale).into(),
+                b.coords.component_mul(scale).into(),
+            ))),
+            Self::QuadraticBezier { a, control, b } => Some(Box::new(Self::quadratic_bezier(
+                a.coords.component_mul(scale).into(),
+                control.coords.component_mul(scale).into(),
+                b.coords.component_mul(scale).into(),
+            ))),
+            Self::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep_angle,
+            } if scale.x == scale.y && scale.x > 0.0 => Some(Box::new(Self::arc(
+                center * scale.x,
+                radius * scale.x,
+                start_angle,
+                sweep_angle,
+            ))),
+            // A non-uniformly scaled arc is no longer a circular arc.
+            Self::Arc { .. } => None,
+        }
+    }
+
+    fn mass_properties(&self, _density: Real) -> MassProperties {
+        MassProperties::zero()
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        0.0
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        max_piece_angle()
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self as &dyn SupportMap)
+    }
+
+    fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, Real)> {
+        Some((self as &dyn PolygonalFeatureMap, 0.0))
+    }
+}
+
+/// A 2D chain of straight segments, circular arcs, and quadratic Bézier curves.
+///
+/// This is well suited for smooth 2D terrains (loops, ramps, etc.) which would otherwise
+/// need a polyline with a large number of tiny segments, causing ghost collisions at
+/// their junctions.
+///
+/// For contact generation, each curve is split into pieces covering a small angle, and
+/// contacts are computed against their convex hull. Point projections and ray-casts are
+/// exact.
+#[derive(Clone)]
+pub struct CurvedPolyline {
+    segments: Vec<CurveSegment>,
+    pieces: Compound,
+}
+
+impl CurvedPolyline {
+    /// Creates a new curved polyline from its segments.
+    ///
+    /// Panics if `segments` is empty.
+    pub fn new(segments: Vec<CurveSegment>) -> Self {
+        assert!(
+            !segments.is_empty(),
+            "A curved polyline must have at least one segment."
+        );
+        let pieces = segments
+            .iter()
+            .flat_map(|segment| segment.subdivide())
+            .map(|piece| (Isometry::identity(), SharedShape::new(piece)))
+            .collect();
+
+        Self {
+            segments,
+            pieces: Compound::new(pieces),
+        }
+    }
+
+    /// The segments this curved polyline is composed of.
+    pub fn segments(&self) -> &[CurveSegment] {
+        &self.segments
+    }
+
+    /// Creates a polyline with rounded corners.
+    ///
+    /// Each interior vertex `vertices[i]` is replaced by a circular arc with radius `radii[i]`
+    /// tangent to both adjacent segments. This provides a smooth transition between
+    /// consecutive segments, preventing objects sliding on the polyline from catching on
+    /// its corners. The radius of the first and last vertices is ignored. If a radius is too
+    /// large to fit between the adjacent segments, it is reduced automatically.
+    ///
+    /// Panics if `vertices` has less than two elements or if `radii` doesn’t have the same
+    /// length as `vertices`.
+    pub fn rounded_polyline(vertices: &[Point<Real>], radii: &[Real]) -> Self {
+        assert!(
+            vertices.len() >= 2,
+            "A rounded polyline must have at least two vertices."
+        );
+        assert_eq!(
+            vertices.len(),
+            radii.len(),
+            "A rounded polyline must have exactly one radius per vertex."
+        );
+
+        let mut segments = vec![];
+        let mut last_point = vertices[0];
+
+        for i in 1..vertices.len() - 1 {
+            let (prev, curr, next) = (vertices[i - 1], vertices[i], vertices[i + 1]);
+            let (Some(dir1), Some(dir2)) = (
+                (curr - prev).try_normalize(Real::default_epsilon()),
+                (next - curr).try_normalize(Real::default_epsilon()),
+            ) else {
+                continue;
+            };
+
+            let turn = dir1.perp(&dir2).atan2(dir1.dot(&dir2));
+            if turn.abs() <= Real::default_epsilon() || radii[i] <= 0.0 {
+                segments.push(CurveSegment::line(last_point, curr));
+                last_point = curr;
+                continue;
+            }
+
+            // Distance between the corner and the tangent points of the fillet, limited so
+            // that consecutive fillets don’t overlap.
+            let half_turn_tan = (turn.abs() / 2.0).tan();
+            let max_tangent_dist =
+                na::distance(&last_point, &curr).min(na::distance(&curr, &next) / 2.0);
+            let tangent_dist = (radii[i] * half_turn_tan).min(max_tangent_dist);
+            let radius = tangent_dist / half_turn_tan;
+
+            let tangent1 = curr - dir1 * tangent_dist;
+            let tangent2 = curr + dir2 * tangent_dist;
+            let center = tangent1 + perp(&dir1) * (radius * turn.signum());
+            let start = tangent1 - center;
+
+            if tangent1 != last_point {
+                segments.push(CurveSegment::line(last_point, tangent1));
+            }
+            segments.push(CurveSegment::arc(
+                center,
+                radius,
+                start.y.atan2(start.x),
+                turn,
+            ));
+            last_point = tangent2;
+        }
+
+        segments.push(CurveSegment::line(last_point, vertices[vertices.len() - 1]));
+        Self::new(segments)
+    }
+
+    /// The small convex pieces the curves are split into for contact generation.
+    pub fn pieces(&self) -> &Compound {
+        &self.pieces
+    }
+}
+
+impl RayCast for CurvedPolyline {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        self.pieces
+            .cast_local_ray_and_get_normal(ray, max_time_of_impact, solid)
+    }
+}
+
+impl PointQuery for CurvedPolyline {
+    fn project_local_point(&self, pt: &Point<Real>, solid: bool) -> PointProjection {
+        self.pieces.project_local_point(pt, solid)
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        self.pieces.project_local_point_and_get_feature(pt)
+    }
+}
+
+impl Shape for CurvedPolyline {
+    fn compute_local_aabb(&self) -> Aabb {
+        self.pieces.compute_local_aabb()
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        self.pieces.compute_local_bounding_sphere()
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                segment
+                    .scale_dyn(scale, num_subdivisions)
+                    .and_then(|s| s.downcast_ref::<CurveSegment>().copied())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Box::new(Self::new(segments)))
+    }
+
+    fn mass_properties(&self, _density: Real) -> MassProperties {
+        MassProperties::zero()
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        0.0
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        max_piece_angle()
+    }
+
+    fn as_composite_shape(&self) -> Option<&dyn SimdCompositeShape> {
+        Some(&self.pieces as &dyn SimdCompositeShape)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{point, vector, RealField};
+    use parry::query::{PointQuery, Ray, RayCast};
+    use parry::shape::SupportMap;
+
+    use super::{max_piece_angle, CurveSegment, CurvedPolyline};
+    use crate::dynamics::{
+        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        RigidBodyBuilder, RigidBodySet,
+    };
+    use crate::geometry::{
+        BroadPhaseMultiSap, ColliderBuilder, ColliderSet, NarrowPhase, SharedShape,
+    };
+    use crate::math::{Real, Vector};
+    use crate::pipeline::PhysicsPipeline;
+
+    #[test]
+    fn arc_point_projection_and_ray_cast_are_exact() {
+        // The upper half of the circle of radius 2 centered at the origin.
+        let arc = CurveSegment::arc(point![0.0, 0.0], 2.0, 0.0, Real::pi());
+
+        let proj = arc.project_local_point(&point![0.0, 5.0], true);
+        assert!(!proj.is_inside);
+        approx::assert_relative_eq!(proj.point, point![0.0, 2.0], epsilon = 1.0e-5);
+        // Points closer to the missing half of the circle project on the nearest endpoint.
+        let proj = arc.project_local_point(&point![1.0, -5.0], true);
+        approx::assert_relative_eq!(proj.point, point![2.0, 0.0], epsilon = 1.0e-5);
+
+        // The ray crosses the missing half of the circle before hitting the arc.
+        let ray = Ray::new(point![0.0, -5.0], Vector::y());
+        let hit = arc
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        approx::assert_relative_eq!(hit.time_of_impact, 7.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(hit.normal, -Vector::y(), epsilon = 1.0e-5);
+        assert!(arc.cast_local_ray(&ray, 6.0, true).is_none());
+    }
+
+    #[test]
+    fn bezier_point_projection_and_ray_cast_are_exact() {
+        // The apex of this curve is at (1, 1).
+        let bezier =
+            CurveSegment::quadratic_bezier(point![0.0, 0.0], point![1.0, 2.0], point![2.0, 0.0]);
+        approx::assert_relative_eq!(bezier.point_at(0.5), point![1.0, 1.0]);
+        approx::assert_relative_eq!(
+            bezier.local_support_point(&Vector::y()),
+            point![1.0, 1.0],
+            epsilon = 1.0e-5
+        );
+
+        let proj = bezier.project_local_point(&point![1.0, 3.0], true);
+        approx::assert_relative_eq!(proj.point, point![1.0, 1.0], epsilon = 1.0e-5);
+
+        let ray = Ray::new(point![1.0, 5.0], -Vector::y());
+        let hit = bezier
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        approx::assert_relative_eq!(hit.time_of_impact, 4.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(hit.normal, Vector::y(), epsilon = 1.0e-5);
+    }
+
+    #[test]
+    fn subdivided_curves_are_continuous() {
+        let curves = [
+            CurveSegment::arc(point![1.0, 0.0], 3.0, 0.5, -Real::pi() * 1.5),
+            CurveSegment::quadratic_bezier(point![0.0, 0.0], point![1.0, 4.0], point![3.0, 0.0]),
+        ];
+
+        for curve in curves {
+            let pieces = curve.subdivide();
+            assert!(pieces.len() > 1);
+            approx::assert_relative_eq!(
+                pieces[0].start_point(),
+                curve.start_point(),
+                epsilon = 1.0e-5
+            );
+            approx::assert_relative_eq!(
+                pieces[pieces.len() - 1].end_point(),
+                curve.end_point(),
+                epsilon = 1.0e-5
+            );
+
+            for (piece, next) in pieces.iter().zip(pieces.iter().skip(1)) {
+                approx::assert_relative_eq!(
+                    piece.end_point(),
+                    next.start_point(),
+                    epsilon = 1.0e-5
+                );
+                let turn = piece.tangent_at(0.0).angle(&piece.tangent_at(1.0));
+                assert!(turn <= max_piece_angle() + 1.0e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn rounded_polyline_corners_are_tangent_arcs() {
+        let polyline = CurvedPolyline::rounded_polyline(
+            &[point![0.0, 0.0], point![2.0, 0.0], point![2.0, 2.0]],
+            &[0.0, 1.0, 0.0],
+        );
+        let segments = polyline.segments();
+        assert_eq!(segments.len(), 3);
+        assert!(matches!(segments[1], CurveSegment::Arc { radius, .. } if radius == 1.0));
+
+        for (segment, next) in segments.iter().zip(segments.iter().skip(1)) {
+            approx::assert_relative_eq!(segment.end_point(), next.start_point(), epsilon = 1.0e-5);
+            approx::assert_relative_eq!(
+                segment.tangent_at(1.0).normalize(),
+                next.tangent_at(0.0).normalize(),
+                epsilon = 1.0e-5
+            );
+        }
+
+        approx::assert_relative_eq!(
+            segments[1].start_point(),
+            point![1.0, 0.0],
+            epsilon = 1.0e-5
+        );
+        approx::assert_relative_eq!(segments[1].end_point(), point![2.0, 1.0], epsilon = 1.0e-5);
+    }
+
+    #[test]
+    fn ball_settles_at_the_bottom_of_a_curved_bowl() {
+        let mut pipeline = PhysicsPipeline::new();
+        let mut islands = IslandManager::new();
+        let mut broad_phase = BroadPhaseMultiSap::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut ccd_solver = CCDSolver::new();
+
+        // The bottom quarter of the circle of radius 5 centered at (0, 5).
+        let bowl = CurvedPolyline::new(vec![CurveSegment::arc(
+            point![0.0, 5.0],
+            5.0,
+            -Real::frac_pi_4() * 3.0,
+            Real::frac_pi_2(),
+        )]);
+        let _ = colliders.insert(ColliderBuilder::new(SharedShape::new(bowl)));
+        let ball = bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![1.0, 1.0])
+                .linear_damping(2.0)
+                .angular_damping(2.0),
+        );
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut bodies);
+
+        for _ in 0..600 {
+            pipeline.step(
+                &(Vector::y() * -9.81),
+                &IntegrationParameters::default(),
+                &mut islands,
+                &mut broad_phase,
+                &mut narrow_phase,
+                &mut bodies,
+                &mut colliders,
+                &mut impulse_joints,
+                &mut multibody_joints,
+                &mut ccd_solver,
+                None,
+                &(),
+                &(),
+            );
+        }
+
+        let pos = bodies[ball].translation();
+        assert!(pos.x.abs() < 0.1);
+        assert!((pos.y - 0.5).abs() < 0.05);
+    }
+}
@@ -0,0 +1,415 @@
+use crate::dynamics::MassProperties;
+use crate::geometry::shape_utils;
+use crate::math::{Isometry, Point, Real, Vector};
+use na::{RealField, Unit};
+use parry::bounding_volume::{Aabb, BoundingSphere};
+use parry::query::details::local_point_projection_on_support_map;
+use parry::query::gjk::VoronoiSimplex;
+use parry::query::{PointProjection, PointQuery, Ray, RayCast, RayIntersection};
+use parry::shape::{
+    FeatureId, PolygonalFeature, PolygonalFeatureMap, Shape, ShapeType, SupportMap, TypedShape,
+};
+
+/// An ellipsoid (or an ellipse in 2D) centered at the origin and aligned with the local axes.
+///
+/// Contacts and time-of-impact computations rely on the support-map of this shape. Ray-casts
+/// and mass-properties are computed analytically.
+///
+/// The boundary of an ellipsoid is smooth so its contact manifolds are made of a single point.
+/// Lying on a flat surface with its shortest axis vertical, it still rests stably since tilting
+/// it raises its center of mass.
+///
+/// This is a custom shape for parry so, even though this struct implements `Serialize` and
+/// `Deserialize`, the serialization of a collider with an ellipsoid shape with `serde` isn’t
+/// supported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct Ellipsoid {
+    /// The semi-axes lengths of the ellipsoid, along each local coordinate axis.
+    pub radii: Vector<Real>,
+}
+
+impl Ellipsoid {
+    /// Creates a new ellipsoid from its semi-axes lengths.
+    pub fn new(radii: Vector<Real>) -> Self {
+        Self { radii }
+    }
+
+    /// The largest semi-axis length of this ellipsoid.
+    pub fn max_radius(&self) -> Real {
+        self.radii.max()
+    }
+
+    /// The smallest semi-axis length of this ellipsoid.
+    pub fn min_radius(&self) -> Real {
+        self.radii.min()
+    }
+
+    /// Computes a scaled version of this ellipsoid.
+    ///
+    /// Unlike most other shapes, an ellipsoid remains an ellipsoid after a non-uniform scaling.
+    pub fn scaled(&self, scale: &Vector<Real>) -> Self {
+        Self::new(self.radii.component_mul(scale).abs())
+    }
+
+    /// The area (in 2D) or volume (in 3D) of this ellipsoid.
+    #[cfg(feature = "dim2")]
+    pub fn volume(&self) -> Real {
+        Real::pi() * self.radii.x * self.radii.y
+    }
+
+    /// The area (in 2D) or volume (in 3D) of this ellipsoid.
+    #[cfg(feature = "dim3")]
+    pub fn volume(&self) -> Real {
+        Real::pi() * 4.0 / 3.0 * self.radii.x * self.radii.y * self.radii.z
+    }
+
+    /// The outward unit normal of the ellipsoid boundary at the given local point.
+    ///
+    /// The point is assumed to lie on the boundary of the ellipsoid.
+    pub fn normal_at_point(&self, pt: &Point<Real>) -> Option<Unit<Vector<Real>>> {
+        let gradient = pt
+            .coords
+            .component_div(&self.radii.component_mul(&self.radii));
+        Unit::try_new(gradient, Real::default_epsilon())
+    }
+}
+
+impl SupportMap for Ellipsoid {
+    fn local_support_point(&self, dir: &Vector<Real>) -> Point<Real> {
+        let scaled_dir = dir.component_mul(&self.radii);
+        let norm = scaled_dir.norm();
+
+        if norm > Real::default_epsilon() {
+            Point::from(scaled_dir.component_mul(&self.radii) / norm)
+        } else {
+            Point::origin()
+        }
+    }
+}
+
+impl PolygonalFeatureMap for Ellipsoid {
+    fn local_support_feature(&self, dir: &Unit<Vector<Real>>, out_feature: &mut PolygonalFeature) {
+        shape_utils::set_single_vertex_feature(self.local_support_point_toward(dir), out_feature);
+    }
+}
+
+impl RayCast for Ellipsoid {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        // Solve the intersection in the space where the ellipsoid is a unit ball.
+        let orig = ray.origin.coords.component_div(&self.radii);
+        let dir = ray.dir.component_div(&self.radii);
+
+        let a = dir.norm_squared();
+        let b = orig.dot(&dir);
+        let c = orig.norm_squared() - 1.0;
+
+        if c <= 0.0 && solid {
+            // The ray starts inside of the ellipsoid.
+            let normal =
+                Unit::try_new(-ray.dir, Real::default_epsilon()).unwrap_or_else(Vector::y_axis);
+            return Some(RayIntersection::new(0.0, *normal, FeatureId::Face(0)));
+        }
+
+        if a <= Real::default_epsilon() || (c > 0.0 && b > 0.0) {
+            return None;
+        }
+
+        let delta = b * b - a * c;
+        if delta < 0.0 {
+            return None;
+        }
+
+        let sqrt_delta = delta.sqrt();
+        let toi = if c > 0.0 {
+            (-b - sqrt_delta) / a
+        } else {
+            // Ray starting inside with a non-solid ellipsoid: hit the exit point.
+            (-b + sqrt_delta) / a
+        };
+
+        if toi > max_time_of_impact || toi < 0.0 {
+            return None;
+        }
+
+        let hit = ray.point_at(toi);
+        let mut normal = self.normal_at_point(&hit)?.into_inner();
+
+        if c <= 0.0 {
+            normal = -normal;
+        }
+
+        Some(RayIntersection::new(toi, normal, FeatureId::Face(0)))
+    }
+}
+
+impl PointQuery for Ellipsoid {
+    fn project_local_point(&self, pt: &Point<Real>, solid: bool) -> PointProjection {
+        local_point_projection_on_support_map(self, &mut VoronoiSimplex::new(), pt, solid)
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Face(0))
+    }
+}
+
+impl Shape for Ellipsoid {
+    fn compute_local_aabb(&self) -> Aabb {
+        Aabb::new(Point::from(-self.radii), Point::from(self.radii))
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(Point::origin(), self.max_radius())
+    }
+
+    fn compute_aabb(&self, position: &Isometry<Real>) -> Aabb {
+        // The half-extent along each world axis is the norm of the corresponding row of
+        // `R * diag(radii)`.
+        let rot = position.rotation.to_rotation_matrix();
+        let mut half_extents = Vector::zeros();
+
+        for i in 0..half_extents.len() {
+            half_extents[i] = rot
+                .matrix()
+                .row(i)
+                .transpose()
+                .component_mul(&self.radii)
+                .norm();
+        }
+
+        let center = Point::from(position.translation.vector);
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(*self)
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, _num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        Some(Box::new(self.scaled(scale)))
+    }
+
+    #[cfg(feature = "dim2")]
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let mass = self.volume() * density;
+        let (a2, b2) = (self.radii.x * self.radii.x, self.radii.y * self.radii.y);
+        MassProperties::new(Point::origin(), mass, mass * (a2 + b2) / 4.0)
+    }
+
+    #[cfg(feature = "dim3")]
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let mass = self.volume() * density;
+        let sq = self.radii.component_mul(&self.radii);
+        let principal_inertia = Vector::new(sq.y + sq.z, sq.x + sq.z, sq.x + sq.y) * (mass / 5.0);
+        MassProperties::new(Point::origin(), mass, principal_inertia)
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.min_radius()
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        Real::frac_pi_2()
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self as &dyn SupportMap)
+    }
+
+    fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, Real)> {
+        Some((self as &dyn PolygonalFeatureMap, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ellipsoid;
+    use crate::dynamics::{MassProperties, RigidBodyBuilder};
+    use crate::geometry::{shape_utils, ColliderBuilder};
+    use crate::math::{Isometry, Point, Real, Translation, Vector};
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+    use na::RealField;
+    use parry::query::{Ray, RayCast};
+    use parry::shape::{Cuboid, Shape};
+
+    fn radii() -> Vector<Real> {
+        let mut radii = Vector::repeat(0.5);
+        radii.x = 2.0;
+        radii.y = 1.0;
+        radii
+    }
+
+    #[test]
+    fn equal_radii_mass_properties_match_ball() {
+        let mprops = Ellipsoid::new(Vector::repeat(0.7)).mass_properties(3.0);
+        let expected = MassProperties::from_ball(3.0, 0.7);
+
+        assert!((mprops.mass() - expected.mass()).abs() < expected.mass() * 1.0e-5);
+        assert_eq!(mprops.local_com, Point::origin());
+        #[cfg(feature = "dim2")]
+        assert!(
+            (mprops.principal_inertia() - expected.principal_inertia()).abs()
+                < expected.principal_inertia() * 1.0e-5
+        );
+        #[cfg(feature = "dim3")]
+        assert!(
+            (mprops.principal_inertia() - expected.principal_inertia()).norm()
+                < expected.principal_inertia().norm() * 1.0e-5
+        );
+    }
+
+    #[test]
+    fn mass_properties_match_sampled_hull() {
+        let ellipsoid = Ellipsoid::new(radii());
+        let mprops = ellipsoid.mass_properties(1.0);
+        let sampled = shape_utils::support_map_mass_properties(&ellipsoid, 1.0);
+
+        assert!((mprops.mass() - ellipsoid.volume()).abs() < ellipsoid.volume() * 1.0e-5);
+        assert!((mprops.mass() - sampled.mass()).abs() < mprops.mass() * 5.0e-2);
+        #[cfg(feature = "dim2")]
+        assert!(
+            (mprops.principal_inertia() - sampled.principal_inertia()).abs()
+                < mprops.principal_inertia() * 5.0e-2
+        );
+        #[cfg(feature = "dim3")]
+        assert!(
+            (mprops.reconstruct_inertia_matrix() - sampled.reconstruct_inertia_matrix()).norm()
+                < mprops.reconstruct_inertia_matrix().norm() * 5.0e-2
+        );
+    }
+
+    #[test]
+    fn ray_casts_hit_the_ellipsoid_surface() {
+        let ellipsoid = Ellipsoid::new(radii());
+
+        // Along the longest axis.
+        let ray = Ray::new(Point::from(Vector::x() * -5.0), Vector::x());
+        let hit = ellipsoid
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        assert!((hit.time_of_impact - 3.0).abs() < 1.0e-5);
+        assert!((hit.normal + Vector::x()).norm() < 1.0e-5);
+
+        // Along a diagonal, the hit point is on the surface and the normal is the gradient
+        // of the implicit equation of the ellipsoid.
+        let ray = Ray::new(Point::from(Vector::repeat(3.0)), -Vector::repeat(1.0));
+        let hit = ellipsoid
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        let pt = ray.point_at(hit.time_of_impact);
+        let scaled = pt.coords.component_div(&ellipsoid.radii);
+        assert!((scaled.norm_squared() - 1.0).abs() < 1.0e-4);
+        let gradient = pt
+            .coords
+            .component_div(&ellipsoid.radii.component_mul(&ellipsoid.radii))
+            .normalize();
+        assert!((hit.normal - gradient).norm() < 1.0e-4);
+
+        // Missing the ellipsoid, and stopping before it.
+        let ray = Ray::new(
+            Point::from(Vector::y() * 1.5 - Vector::x() * 5.0),
+            Vector::x(),
+        );
+        assert!(ellipsoid
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .is_none());
+        let ray = Ray::new(Point::from(Vector::x() * -5.0), Vector::x());
+        assert!(ellipsoid.cast_local_ray(&ray, 2.9, true).is_none());
+
+        // Starting inside of the ellipsoid.
+        let ray = Ray::new(Point::origin(), Vector::y());
+        assert_eq!(ellipsoid.cast_local_ray(&ray, Real::MAX, true), Some(0.0));
+        let exit = ellipsoid.cast_local_ray(&ray, Real::MAX, false).unwrap();
+        assert!((exit - 1.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn contacts_with_ground_follow_the_ellipsoid_orientation() {
+        let ellipsoid = Ellipsoid::new(radii());
+        let mut half_extents = Vector::repeat(10.0);
+        half_extents.y = 0.1;
+        let ground = Cuboid::new(half_extents);
+
+        let contact_depth = |pos: Isometry<Real>, ground_top: Real| {
+            let ground_pos = Isometry::from(Translation::from(Vector::y() * (ground_top - 0.1)));
+            let contact = parry::query::contact(&pos, &ellipsoid, &ground_pos, &ground, 0.0)
+                .unwrap()
+                .unwrap();
+            assert!((contact.normal1.into_inner() + Vector::y()).norm() < 1.0e-3);
+            -contact.dist
+        };
+
+        // Lying on its longest axis, the ellipsoid’s bottom is at `-radii.y`.
+        let depth = contact_depth(Isometry::identity(), -0.9);
+        assert!((depth - 0.1).abs() < 1.0e-3);
+
+        // Standing on its longest axis, the ellipsoid’s bottom is at `-radii.x`.
+        #[cfg(feature = "dim2")]
+        let standing = Isometry::rotation(Real::frac_pi_2());
+        #[cfg(feature = "dim3")]
+        let standing = Isometry::rotation(Vector::z() * Real::frac_pi_2());
+        let depth = contact_depth(standing, -1.9);
+        assert!((depth - 0.1).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn ellipsoid_rests_stably_on_its_longest_axis() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let mut half_extents = Vector::repeat(10.0);
+        half_extents.y = 0.1;
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::cuboid(half_extents).translation(Vector::y() * -0.1));
+
+        // Lying on its longest axis, with its shortest axis vertical, and dropped from slightly
+        // above the ground. Its single contact point keeps it in place.
+        #[cfg(feature = "dim2")]
+        let (rest_pos, height) = (Isometry::identity(), 1.0);
+        #[cfg(feature = "dim3")]
+        let (rest_pos, height) = (Isometry::rotation(Vector::x() * Real::frac_pi_2()), 0.5);
+        let handle = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .position(Translation::from(Vector::y() * (height + 0.05)) * rest_pos),
+        );
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ellipsoid(radii()),
+            handle,
+            &mut world.bodies,
+        );
+
+        for _ in 0..300 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let body = &world.bodies[handle];
+        let expected_pos = Translation::from(Vector::y() * height) * rest_pos;
+        assert!((body.translation() - expected_pos.translation.vector).norm() < 1.0e-2);
+        assert!((body.rotation() * expected_pos.rotation.inverse()).angle() < 1.0e-2);
+        assert!(body.linvel().norm() < 1.0e-2);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::dynamics::{
+    MassProperties, RigidBody, RigidBodyHandle, RigidBodySet, RigidBodyVelocity,
+};
+use crate::geometry::{ColliderHandle, ColliderSet, SharedShape};
+use crate::math::{Point, Real, Vector};
+use na::Unit;
+
+/// The two pieces obtained by [`fracture_compound`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompoundFracture {
+    /// The original rigid-body, keeping the sub-shapes behind the fracture plane.
+    pub retained_body: RigidBodyHandle,
+    /// The original collider, keeping the sub-shapes behind the fracture plane.
+    pub retained_collider: ColliderHandle,
+    /// The new rigid-body, carrying the sub-shapes in front of the fracture plane.
+    pub detached_body: RigidBodyHandle,
+    /// The new collider, made of the sub-shapes in front of the fracture plane.
+    pub detached_collider: ColliderHandle,
+}
+
+/// Splits a compound collider into two rigid-bodies along a fracture plane.
+///
+/// The fracture plane passes through the world-space `impact_point` and has the world-space
+/// `normal`. The sub-shapes of the compound with a center of mass in front of the plane are moved
+/// into a new collider, attached to a new rigid-body. The other sub-shapes are kept by the
+/// original collider and rigid-body, so their handles, user data, and contact pairs are preserved.
+/// The contact points of these pairs are regenerated at the next step since the shape of the
+/// original collider changed.
+///
+/// The new rigid-body and collider are copies of the original ones, except for their shape and
+/// the additional mass properties of the rigid-body which are not duplicated. Both colliders keep
+/// the density of the original collider, and the mass properties of both rigid-bodies are
+/// recomputed immediately. The velocity of each piece is the velocity the original rigid-body had
+/// at the new center of mass of that piece, so the pieces fly apart consistently with the
+/// original motion.
+///
+/// Returns `None`, and leaves everything untouched, if the collider isn’t a compound attached to
+/// a rigid-body, or if all its sub-shapes are on the same side of the plane.
+pub fn fracture_compound(
+    bodies: &mut RigidBodySet,
+    colliders: &mut ColliderSet,
+    collider: ColliderHandle,
+    impact_point: &Point<Real>,
+    normal: &Unit<Vector<Real>>,
+) -> Option<CompoundFracture> {
+    let co = colliders.get(collider)?;
+    let parent = co.parent()?;
+    let rb = bodies.get(parent)?;
+    let compound = co.shape().as_compound()?;
+    let co_pos = co.position();
+
+    let mut retained_parts = vec![];
+    let mut detached_parts = vec![];
+
+    for (part_pos, part) in compound.shapes() {
+        let part_com = (co_pos * part_pos) * part.mass_properties(1.0).local_com;
+
+        if normal.dot(&(part_com - impact_point)) > 0.0 {
+            detached_parts.push((*part_pos, part.clone()));
+        } else {
+            retained_parts.push((*part_pos, part.clone()));
+        }
+    }
+
+    if retained_parts.is_empty() || detached_parts.is_empty() {
+        return None;
+    }
+
+    let density = co.density();
+    let mut detached_co = co.clone();
+    detached_co.set_shape(SharedShape::compound(detached_parts));
+    detached_co.set_density(density);
+
+    let co = colliders.get_mut(collider)?;
+    co.set_shape(SharedShape::compound(retained_parts));
+    co.set_density(density);
+
+    let original_com = *rb.center_of_mass();
+    let original_vels = rb.vels;
+    let mut detached_rb = rb.clone();
+    detached_rb.set_additional_mass_properties(MassProperties::default(), false);
+
+    let detached_body = bodies.insert(detached_rb);
+    let detached_collider = colliders.insert_with_parent(detached_co, detached_body, bodies);
+
+    for handle in [parent, detached_body] {
+        if let Some(rb) = bodies.get_mut(handle) {
+            rb.recompute_mass_properties_from_colliders(colliders);
+            set_velocity_from(rb, &original_vels, &original_com);
+        }
+    }
+
+    Some(CompoundFracture {
+        retained_body: parent,
+        retained_collider: collider,
+        detached_body,
+        detached_collider,
+    })
+}
+
+/// Sets the velocity of `rb` to the velocity `vels` of a rigid-body with the center of mass
+/// `world_com`, evaluated at the center of mass of `rb`.
+fn set_velocity_from(rb: &mut RigidBody, vels: &RigidBodyVelocity, world_com: &Point<Real>) {
+    let linvel = vels.velocity_at_point(rb.center_of_mass(), world_com);
+    rb.set_linvel(linvel, true);
+    rb.set_angvel(vels.angvel, true);
+}
+
+#[cfg(all(feature = "dim3", feature = "f32"))]
+#[cfg(test)]
+mod test {
+    use super::fracture_compound;
+    use crate::prelude::*;
+
+    #[test]
+    fn fractured_pieces_keep_the_original_motion() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let body = bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .linvel(vector![1.0, 0.0, 0.0])
+                .angvel(vector![0.0, 0.0, 2.0]),
+        );
+        let cube = SharedShape::cuboid(0.5, 0.5, 0.5);
+        let collider = colliders.insert_with_parent(
+            ColliderBuilder::compound(vec![
+                (Isometry::translation(-1.0, 0.0, 0.0), cube.clone()),
+                (Isometry::translation(1.0, 0.0, 0.0), cube),
+            ]),
+            body,
+            &mut bodies,
+        );
+        let mass = bodies[body].mass();
+
+        // All the sub-shapes are on the same side of the plane.
+        assert!(fracture_compound(
+            &mut bodies,
+            &mut colliders,
+            collider,
+            &point![2.0, 0.0, 0.0],
+            &Vector::x_axis(),
+        )
+        .is_none());
+
+        let fracture = fracture_compound(
+            &mut bodies,
+            &mut colliders,
+            collider,
+            &Point::origin(),
+            &Vector::x_axis(),
+        )
+        .unwrap();
+
+        assert_eq!(fracture.retained_body, body);
+        assert_eq!(fracture.retained_collider, collider);
+
+        let retained = &bodies[fracture.retained_body];
+        let detached = &bodies[fracture.detached_body];
+        assert!((retained.mass() - mass / 2.0).abs() < 1.0e-5);
+        assert!((detached.mass() - mass / 2.0).abs() < 1.0e-5);
+        assert!((retained.center_of_mass() - point![-1.0, 0.0, 0.0]).norm() < 1.0e-5);
+        assert!((detached.center_of_mass() - point![1.0, 0.0, 0.0]).norm() < 1.0e-5);
+        assert!((retained.linvel() - vector![1.0, -2.0, 0.0]).norm() < 1.0e-5);
+        assert!((detached.linvel() - vector![1.0, 2.0, 0.0]).norm() < 1.0e-5);
+        assert_eq!(*detached.angvel(), vector![0.0, 0.0, 2.0]);
+    }
+}
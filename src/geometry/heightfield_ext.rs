@@ -0,0 +1,124 @@
+use crate::geometry::HeightField;
+use crate::math::{Real, Vector};
+
+#[cfg(feature = "dim3")]
+use crate::geometry::HeightFieldFlags;
+
+/// Extension trait for building heightfields from a sampling closure.
+///
+/// This avoids having to materialize the height matrix (or vector in 2D) manually, which is
+/// convenient for procedurally generated terrains.
+pub trait HeightFieldExt: Sized {
+    /// Builds a heightfield with `num_rows` rows and `num_cols` columns where the height
+    /// at the row `i` and column `j` is given by `height(i, j)`.
+    ///
+    /// The heightfield is then scaled by `scale` (the `x` and `z` components define the
+    /// total size of the heightfield along each horizontal axis).
+    #[cfg(feature = "dim3")]
+    fn from_fn(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self;
+
+    /// Builds a heightfield with `num_rows` rows and `num_cols` columns where the height
+    /// at the row `i` and column `j` is given by `height(i, j)`.
+    ///
+    /// The `flags` can be used to enable the pre-computation of the heightfield’s triangle
+    /// normals used for internal-edges handling, see [`HeightFieldFlags::FIX_INTERNAL_EDGES`].
+    #[cfg(feature = "dim3")]
+    fn from_fn_with_flags(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        flags: HeightFieldFlags,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self;
+
+    /// Builds a heightfield with `num_samples` samples where the height of the sample `i`
+    /// is given by `height(i)`.
+    ///
+    /// The heightfield is then scaled by `scale` (the `x` component defines the total width
+    /// of the heightfield).
+    #[cfg(feature = "dim2")]
+    fn from_fn(num_samples: usize, scale: Vector<Real>, height: impl FnMut(usize) -> Real) -> Self;
+}
+
+impl HeightFieldExt for HeightField {
+    #[cfg(feature = "dim3")]
+    fn from_fn(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self {
+        HeightField::new(na::DMatrix::from_fn(num_rows, num_cols, height), scale)
+    }
+
+    #[cfg(feature = "dim3")]
+    fn from_fn_with_flags(
+        num_rows: usize,
+        num_cols: usize,
+        scale: Vector<Real>,
+        flags: HeightFieldFlags,
+        height: impl FnMut(usize, usize) -> Real,
+    ) -> Self {
+        HeightField::with_flags(
+            na::DMatrix::from_fn(num_rows, num_cols, height),
+            scale,
+            flags,
+        )
+    }
+
+    #[cfg(feature = "dim2")]
+    fn from_fn(
+        num_samples: usize,
+        scale: Vector<Real>,
+        mut height: impl FnMut(usize) -> Real,
+    ) -> Self {
+        HeightField::new(na::DVector::from_fn(num_samples, |i, _| height(i)), scale)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HeightFieldExt;
+    use crate::geometry::HeightField;
+    use crate::math::{Real, Vector};
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn heightfield_from_fn_samples_every_cell() {
+        use crate::geometry::HeightFieldFlags;
+
+        let height = |i: usize, j: usize| (i * 10 + j) as Real;
+        let scale = Vector::new(4.0, 1.0, 6.0);
+        let heightfield = HeightField::from_fn(3, 4, scale, height);
+        let with_flags = HeightField::from_fn_with_flags(
+            3,
+            4,
+            scale,
+            HeightFieldFlags::FIX_INTERNAL_EDGES,
+            height,
+        );
+
+        for hf in [&heightfield, &with_flags] {
+            assert_eq!(hf.nrows(), 2);
+            assert_eq!(hf.ncols(), 3);
+            assert_eq!(*hf.scale(), scale);
+            assert_eq!(hf.heights()[(2, 1)], 21.0);
+        }
+    }
+
+    #[cfg(feature = "dim2")]
+    #[test]
+    fn heightfield_from_fn_samples_every_cell() {
+        let scale = Vector::new(4.0, 1.0);
+        let hf = HeightField::from_fn(5, scale, |i| (i * i) as Real);
+
+        assert_eq!(hf.num_cells(), 4);
+        assert_eq!(*hf.scale(), scale);
+        assert_eq!(hf.heights()[3], 9.0);
+    }
+}
@@ -67,6 +67,18 @@ impl InteractionGroups {
         (self.memberships.bits() & rhs.filter.bits()) != 0
             && (rhs.memberships.bits() & self.filter.bits()) != 0
     }
+
+    /// Check if a pair of objects with the groups memberships `memberships1` and `memberships2`
+    /// is allowed by `self`, used as a filter on pairs rather than on individual objects.
+    ///
+    /// The pair is allowed iff. one of the objects is a member of at least one group of
+    /// `self.memberships`, and the other is a member of at least one group of `self.filter`.
+    #[inline]
+    pub const fn test_pair(self, memberships1: Group, memberships2: Group) -> bool {
+        let (m1, m2) = (memberships1.bits(), memberships2.bits());
+        let (a, b) = (self.memberships.bits(), self.filter.bits());
+        ((m1 & a) != 0 && (m2 & b) != 0) || ((m2 & a) != 0 && (m1 & b) != 0)
+    }
 }
 
 impl Default for InteractionGroups {
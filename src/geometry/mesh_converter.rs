@@ -17,6 +17,9 @@ pub enum MeshConverterError {
     /// The convex hull calculation carried out by the [`MeshConverter::ConvexHull`] failed.
     #[error("convex-hull computation failed")]
     ConvexHullFailed,
+    /// The list of mesh pieces given to [`MeshConverter::convert_pieces`] was empty.
+    #[error("no mesh piece to convert")]
+    NoMeshPiece,
 }
 
 /// Determines how meshes (generally when loaded from a file) are converted into Rapier colliders.
@@ -90,4 +93,70 @@ impl MeshConverter {
         };
         Ok((shape, transform))
     }
+
+    /// Applies the conversion rule described by this [`MeshConverter`] to many mesh pieces, and
+    /// combines the results into a single shape.
+    ///
+    /// Each piece is given by its vertex and index buffers, together with its pose relative to
+    /// the resulting shape. With [`MeshConverter::TriMesh`] and [`MeshConverter::TriMeshWithFlags`],
+    /// all the pieces are merged into a single triangle mesh. With any other conversion rule,
+    /// the converted pieces become the children of a single compound shape (convex decompositions
+    /// are flattened into that compound).
+    ///
+    /// Either way, the pieces end up sharing a single BVH and the resulting collider only needs
+    /// one broad-phase proxy, which is much cheaper than one collider per piece when dealing
+    /// with level geometry made of thousands of small meshes.
+    #[profiling::function]
+    pub fn convert_pieces(
+        &self,
+        pieces: impl IntoIterator<Item = (Isometry<Real>, Vec<Point<Real>>, Vec<[u32; 3]>)>,
+    ) -> Result<SharedShape, MeshConverterError> {
+        match self {
+            MeshConverter::TriMesh | MeshConverter::TriMeshWithFlags(_) => {
+                let mut vertices = vec![];
+                let mut indices = vec![];
+
+                for (pose, piece_vertices, piece_indices) in pieces {
+                    let base_id = vertices.len() as u32;
+                    vertices.extend(piece_vertices.iter().map(|pt| pose * pt));
+                    indices.extend(
+                        piece_indices
+                            .iter()
+                            .map(|idx| [idx[0] + base_id, idx[1] + base_id, idx[2] + base_id]),
+                    );
+                }
+
+                if indices.is_empty() {
+                    return Err(MeshConverterError::NoMeshPiece);
+                }
+
+                Ok(self.convert(vertices, indices)?.0)
+            }
+            _ => {
+                let mut shapes = vec![];
+
+                for (pose, piece_vertices, piece_indices) in pieces {
+                    let (shape, transform) = self.convert(piece_vertices, piece_indices)?;
+                    let pose = pose * transform;
+
+                    if let Some(compound) = shape.as_compound() {
+                        shapes.extend(
+                            compound
+                                .shapes()
+                                .iter()
+                                .map(|(sub_pose, sub_shape)| (pose * sub_pose, sub_shape.clone())),
+                        );
+                    } else {
+                        shapes.push((pose, shape));
+                    }
+                }
+
+                if shapes.is_empty() {
+                    return Err(MeshConverterError::NoMeshPiece);
+                }
+
+                Ok(SharedShape::compound(shapes))
+            }
+        }
+    }
 }
@@ -0,0 +1,227 @@
+use crate::geometry::HeightField;
+use crate::math::{Point, Real};
+use parry::shape::{FeatureId, Polyline, TriMesh};
+
+/// The mesh data a [`FeatureId`] returned by a query on a mesh-like shape refers to.
+///
+/// This is computed by [`MeshFeatureExt::resolve_feature`]. `N` is the number of vertices of the
+/// primitives of the mesh: `3` for triangles, and `2` for segments.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshFeature<const N: usize> {
+    /// The index of the triangle (or segment) the feature belongs to.
+    pub primitive_id: u32,
+    /// The indices of the vertices of the triangle (or segment).
+    pub vertex_ids: [u32; N],
+    /// The position of the first vertex index of the primitive in the flat index buffer of the
+    /// mesh, i.e., `primitive_id * N`.
+    ///
+    /// This matches the index buffer the mesh was built from only if the mesh construction
+    /// didn’t remove any primitive, e.g., with the `TriMeshFlags::DELETE_DEGENERATE_TRIANGLES`
+    /// flag.
+    pub index_buffer_offset: usize,
+    /// The barycentric coordinates, relative to the vertices of the primitive, of the point given
+    /// to [`MeshFeatureExt::resolve_feature`], if any.
+    pub barycentric_coordinates: Option<[Real; N]>,
+    /// Is the feature the back face of the primitive?
+    pub is_backface: bool,
+}
+
+/// Extension trait for mapping the [`FeatureId`]s returned by ray casts and point projections
+/// back to the data of the mesh they were computed on.
+///
+/// Only [`FeatureId::Face`] is supported: it identifies a triangle of a [`TriMesh`] or 3D
+/// [`HeightField`], or a segment of a [`Polyline`] or 2D [`HeightField`]. Faces with an index
+/// greater than the number of primitives identify the back face of the primitive with that
+/// index minus the number of primitives.
+pub trait MeshFeatureExt<const N: usize> {
+    /// Resolves `feature` into the primitive it belongs to.
+    ///
+    /// If `local_point` is given, in the local-space of the shape, the barycentric coordinates
+    /// of its projection on the primitive are computed as well. Returns `None` if the feature
+    /// doesn’t identify a primitive of this shape.
+    fn resolve_feature(
+        &self,
+        feature: FeatureId,
+        local_point: Option<&Point<Real>>,
+    ) -> Option<MeshFeature<N>>;
+}
+
+impl MeshFeatureExt<3> for TriMesh {
+    fn resolve_feature(
+        &self,
+        feature: FeatureId,
+        local_point: Option<&Point<Real>>,
+    ) -> Option<MeshFeature<3>> {
+        let (primitive_id, is_backface) = split_face_id(feature, self.indices().len())?;
+        let vertex_ids = self.indices()[primitive_id as usize];
+        let vertices = vertex_ids.map(|i| self.vertices()[i as usize]);
+
+        Some(MeshFeature {
+            primitive_id,
+            vertex_ids,
+            index_buffer_offset: primitive_id as usize * 3,
+            barycentric_coordinates: local_point.map(|pt| triangle_barycentric(vertices, pt)),
+            is_backface,
+        })
+    }
+}
+
+impl MeshFeatureExt<2> for Polyline {
+    fn resolve_feature(
+        &self,
+        feature: FeatureId,
+        local_point: Option<&Point<Real>>,
+    ) -> Option<MeshFeature<2>> {
+        let (primitive_id, is_backface) = split_face_id(feature, self.indices().len())?;
+        let vertex_ids = self.indices()[primitive_id as usize];
+        let vertices = vertex_ids.map(|i| self.vertices()[i as usize]);
+
+        Some(MeshFeature {
+            primitive_id,
+            vertex_ids,
+            index_buffer_offset: primitive_id as usize * 2,
+            barycentric_coordinates: local_point.map(|pt| segment_barycentric(vertices, pt)),
+            is_backface,
+        })
+    }
+}
+
+/// The vertex ids of the triangles of a heightfield are the indices of its heights, in
+/// column-major order, i.e., as given by [`HeightField::triangles_vids_at`].
+#[cfg(feature = "dim3")]
+impl MeshFeatureExt<3> for HeightField {
+    fn resolve_feature(
+        &self,
+        feature: FeatureId,
+        local_point: Option<&Point<Real>>,
+    ) -> Option<MeshFeature<3>> {
+        let num_triangles = self.num_triangles();
+        let (primitive_id, is_backface) = split_face_id(feature, num_triangles)?;
+
+        // The left triangles of all the cells are numbered first, then the right ones.
+        let half = num_triangles / 2;
+        let left = (primitive_id as usize) < half;
+        let cell_id = if left {
+            primitive_id as usize
+        } else {
+            primitive_id as usize - half
+        };
+        let (i, j) = (cell_id % self.nrows(), cell_id / self.nrows());
+
+        let (left_vids, right_vids) = self.triangles_vids_at(i, j);
+        let (left_tri, right_tri) = self.triangles_at(i, j);
+        let (vertex_ids, triangle) = if left {
+            (left_vids?, left_tri?)
+        } else {
+            (right_vids?, right_tri?)
+        };
+
+        Some(MeshFeature {
+            primitive_id,
+            vertex_ids,
+            index_buffer_offset: primitive_id as usize * 3,
+            barycentric_coordinates: local_point
+                .map(|pt| triangle_barycentric([triangle.a, triangle.b, triangle.c], pt)),
+            is_backface,
+        })
+    }
+}
+
+/// The vertex ids of the segments of a heightfield are the indices of its heights.
+#[cfg(feature = "dim2")]
+impl MeshFeatureExt<2> for HeightField {
+    fn resolve_feature(
+        &self,
+        feature: FeatureId,
+        local_point: Option<&Point<Real>>,
+    ) -> Option<MeshFeature<2>> {
+        let (primitive_id, is_backface) = split_face_id(feature, self.num_cells())?;
+        let segment = self.segment_at(primitive_id as usize)?;
+
+        Some(MeshFeature {
+            primitive_id,
+            vertex_ids: [primitive_id, primitive_id + 1],
+            index_buffer_offset: primitive_id as usize * 2,
+            barycentric_coordinates: local_point
+                .map(|pt| segment_barycentric([segment.a, segment.b], pt)),
+            is_backface,
+        })
+    }
+}
+
+/// Splits a face feature into the index of the primitive and whether it is its back face.
+fn split_face_id(feature: FeatureId, num_primitives: usize) -> Option<(u32, bool)> {
+    let FeatureId::Face(id) = feature else {
+        return None;
+    };
+
+    let num_primitives = num_primitives as u32;
+    if id < num_primitives {
+        Some((id, false))
+    } else if id < num_primitives * 2 {
+        Some((id - num_primitives, true))
+    } else {
+        None
+    }
+}
+
+/// The barycentric coordinates of the projection of `pt` on the segment `[a, b]`.
+fn segment_barycentric([a, b]: [Point<Real>; 2], pt: &Point<Real>) -> [Real; 2] {
+    let ab = b - a;
+    let t = (pt - a).dot(&ab) * crate::utils::inv(ab.norm_squared());
+    let t = t.clamp(0.0, 1.0);
+    [1.0 - t, t]
+}
+
+/// The barycentric coordinates of the projection of `pt` on the plane of the triangle `[a, b, c]`.
+fn triangle_barycentric([a, b, c]: [Point<Real>; 3], pt: &Point<Real>) -> [Real; 3] {
+    let (ab, ac, ap) = (b - a, c - a, pt - a);
+    let (d00, d01, d11) = (ab.dot(&ab), ab.dot(&ac), ac.dot(&ac));
+    let (d20, d21) = (ap.dot(&ab), ap.dot(&ac));
+    let inv_denom = crate::utils::inv(d00 * d11 - d01 * d01);
+    let v = (d11 * d20 - d01 * d21) * inv_denom;
+    let w = (d00 * d21 - d01 * d20) * inv_denom;
+    [1.0 - v - w, v, w]
+}
+
+#[cfg(test)]
+mod test {
+    use super::MeshFeatureExt;
+    use crate::geometry::SharedShape;
+    use crate::math::{Point, Vector};
+    use parry::shape::FeatureId;
+
+    #[test]
+    fn trimesh_faces_resolve_to_their_triangles() {
+        let vertices = vec![
+            Point::origin(),
+            Point::from(Vector::x()),
+            Point::from(Vector::y()),
+            Point::from(Vector::x() + Vector::y()),
+        ];
+        let shape = SharedShape::trimesh(vertices, vec![[0, 1, 2], [2, 1, 3]]);
+        let trimesh = shape.as_trimesh().unwrap();
+
+        let third = 1.0 / 3.0;
+        let centroid = Point::from((Vector::x() + Vector::y()) * (2.0 * third));
+        let feature = trimesh
+            .resolve_feature(FeatureId::Face(1), Some(&centroid))
+            .unwrap();
+        assert_eq!(feature.vertex_ids, [2, 1, 3]);
+        assert_eq!(feature.index_buffer_offset, 3);
+        assert!(!feature.is_backface);
+        for coord in feature.barycentric_coordinates.unwrap() {
+            assert!((coord - third).abs() < 1.0e-5);
+        }
+
+        let backface = trimesh.resolve_feature(FeatureId::Face(3), None).unwrap();
+        assert_eq!(backface.primitive_id, 1);
+        assert!(backface.is_backface);
+        assert!(backface.barycentric_coordinates.is_none());
+
+        assert!(trimesh.resolve_feature(FeatureId::Face(4), None).is_none());
+        assert!(trimesh
+            .resolve_feature(FeatureId::Vertex(0), None)
+            .is_none());
+    }
+}
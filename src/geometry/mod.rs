@@ -5,15 +5,28 @@ pub use self::broad_phase_multi_sap::{BroadPhaseMultiSap, BroadPhasePairEvent, C
 pub use self::collider::{Collider, ColliderBuilder};
 pub use self::collider_components::*;
 pub use self::collider_set::ColliderSet;
+#[cfg(feature = "dim3")]
+pub use self::contact_pair::ContactAnisotropicFriction;
 pub use self::contact_pair::{
-    ContactData, ContactManifoldData, ContactPair, IntersectionPair, SolverContact, SolverFlags,
+    ContactData, ContactManifoldData, ContactPair, ContactUserSlots, IntersectionPair,
+    SolverContact, SolverFlags, WarmstartImpulse,
 };
+#[cfg(feature = "dim2")]
+pub use self::curved_polyline::{CurveSegment, CurvedPolyline};
+pub use self::ellipsoid::Ellipsoid;
+pub use self::fracture::{fracture_compound, CompoundFracture};
+pub use self::heightfield_ext::HeightFieldExt;
 pub use self::interaction_graph::{
     ColliderGraphIndex, InteractionGraph, RigidBodyGraphIndex, TemporaryInteractionIndex,
 };
 pub use self::interaction_groups::{Group, InteractionGroups};
 pub use self::mesh_converter::{MeshConverter, MeshConverterError};
+pub use self::mesh_feature::{MeshFeature, MeshFeatureExt};
 pub use self::narrow_phase::NarrowPhase;
+#[cfg(feature = "dim2")]
+pub use self::polygon_partition::{convex_partition_polygon, triangulate_polygon};
+pub use self::tapered_capsule::TaperedCapsule;
+pub use self::torus::{Torus, TorusSection};
 
 pub use parry::bounding_volume::BoundingVolume;
 pub use parry::query::{PointQuery, PointQueryWithLocation, RayCast, TrackedContact};
@@ -182,6 +195,7 @@ impl ContactForceEvent {
 }
 
 pub(crate) use self::broad_phase::BroadPhaseProxyIndex;
+pub(crate) use self::contact_pair::{solver_contact_id_mask, CachedContactModification};
 pub(crate) use self::narrow_phase::ContactManifoldIndex;
 pub(crate) use parry::partitioning::Qbvh;
 pub use parry::shape::*;
@@ -208,4 +222,15 @@ mod broad_phase;
 mod broad_phase_qbvh;
 mod collider;
 mod collider_set;
+#[cfg(feature = "dim2")]
+mod curved_polyline;
+mod ellipsoid;
+mod fracture;
+mod heightfield_ext;
 mod mesh_converter;
+mod mesh_feature;
+#[cfg(feature = "dim2")]
+mod polygon_partition;
+mod shape_utils;
+mod tapered_capsule;
+mod torus;
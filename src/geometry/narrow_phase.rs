@@ -1,19 +1,23 @@
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::data::graph::EdgeIndex;
+use crate::counters::CollisionDetectionCounters;
+use crate::data::graph::{Edge, EdgeIndex};
 use crate::data::Coarena;
 use crate::dynamics::{
-    CoefficientCombineRule, ImpulseJointSet, IslandManager, RigidBodyDominance, RigidBodySet,
-    RigidBodyType,
+    CoefficientCombineRule, ContactSoftness, ImpulseJointSet, IslandManager, RigidBodyDominance,
+    RigidBodyHandle, RigidBodySet, RigidBodyType,
 };
+#[cfg(feature = "dim3")]
+use crate::geometry::ContactAnisotropicFriction;
 use crate::geometry::{
-    BoundingVolume, BroadPhasePairEvent, ColliderChanges, ColliderGraphIndex, ColliderHandle,
-    ColliderPair, ColliderSet, CollisionEvent, ContactData, ContactManifold, ContactManifoldData,
-    ContactPair, InteractionGraph, IntersectionPair, SolverContact, SolverFlags,
-    TemporaryInteractionIndex,
+    solver_contact_id_mask, BoundingVolume, BroadPhasePairEvent, CachedContactModification,
+    Collider, ColliderChanges, ColliderGraphIndex, ColliderHandle, ColliderPair, ColliderSet,
+    CollisionEvent, ContactData, ContactManifold, ContactManifoldData, ContactPair,
+    ContactUserSlots, InteractionGraph, InteractionGroups, IntersectionPair, ShapeType,
+    SolverContact, SolverFlags, TemporaryInteractionIndex, WarmstartImpulse,
 };
-use crate::math::{Real, Vector};
+use crate::math::{Isometry, Point, Real, Vector};
 use crate::pipeline::{
     ActiveEvents, ActiveHooks, ContactModificationContext, EventHandler, PairFilterContext,
     PhysicsHooks,
@@ -22,6 +26,10 @@ use crate::prelude::{CollisionEventFlags, MultibodyJointSet};
 use parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher};
 use parry::utils::IsometryOpt;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
+#[cfg(feature = "profiler")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -59,6 +67,21 @@ pub struct NarrowPhase {
     contact_graph: InteractionGraph<ColliderHandle, ContactPair>,
     intersection_graph: InteractionGraph<ColliderHandle, IntersectionPair>,
     graph_indices: Coarena<ColliderGraphIndices>,
+    /// Overlapping pairs of colliders that are both fixed, and can’t interact.
+    ///
+    /// These pairs are kept out of the contact and intersection graphs so they don’t cost
+    /// anything at each step. They are re-evaluated if one of their colliders becomes attached
+    /// to a non-fixed rigid-body, or changes the types of interactions it allows.
+    fixed_pairs: Coarena<Vec<ColliderHandle>>,
+    /// Pairs of colliders excluded from contact and intersection computation by the user.
+    excluded_pairs: Coarena<Vec<ColliderHandle>>,
+    event_filter: Option<InteractionGroups>,
+    /// The ratio between [`IntegrationParameters::num_solver_iterations`](crate::dynamics::IntegrationParameters::num_solver_iterations)
+    /// and the number of substeps the warmstart impulses of the contacts were computed with.
+    substep_count_ratio: Real,
+    /// Buffer for the contact user slots exposed to the contact modification hooks.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    contact_user_slots_buffer: Vec<ContactUserSlots>,
 }
 
 pub(crate) type ContactManifoldIndex = usize;
@@ -85,7 +108,184 @@ impl NarrowPhase {
             contact_graph: InteractionGraph::new(),
             intersection_graph: InteractionGraph::new(),
             graph_indices: Coarena::new(),
+            fixed_pairs: Coarena::new(),
+            excluded_pairs: Coarena::new(),
+            event_filter: None,
+            substep_count_ratio: 1.0,
+            contact_user_slots_buffer: Vec::new(),
+        }
+    }
+
+    /// The filter on the pairs of colliders allowed to generate collision and contact force
+    /// events, if any.
+    pub fn event_filter(&self) -> Option<InteractionGroups> {
+        self.event_filter
+    }
+
+    /// Sets a filter on the pairs of colliders allowed to generate collision and contact force
+    /// events at all.
+    ///
+    /// If set, a pair generates events only if it is allowed by the
+    /// [`ActiveEvents`] of its colliders, and by [`InteractionGroups::test_pair`] on the
+    /// memberships of their collision groups. This avoids creating events for pairs the
+    /// application never cares about. The contacts and intersections themselves aren’t affected.
+    ///
+    /// Changing the filter while a pair is in contact may cause its collision stopped event to
+    /// be missing, or to be emitted without a matching started event.
+    pub fn set_event_filter(&mut self, filter: Option<InteractionGroups>) {
+        self.event_filter = filter;
+    }
+
+    /// The events enabled for the pair of colliders `co1` and `co2`.
+    pub(crate) fn pair_active_events(
+        event_filter: Option<InteractionGroups>,
+        co1: &Collider,
+        co2: &Collider,
+    ) -> ActiveEvents {
+        match event_filter {
+            Some(filter)
+                if !filter.test_pair(
+                    co1.flags.collision_groups.memberships,
+                    co2.flags.collision_groups.memberships,
+                ) =>
+            {
+                ActiveEvents::empty()
+            }
+            _ => co1.flags.active_events | co2.flags.active_events,
+        }
+    }
+
+    /// Excludes the pair of colliders `collider1` and `collider2` from contact and intersection
+    /// computation, until [`NarrowPhase::include_pair`] is called.
+    ///
+    /// This is a cheaper alternative to filtering a handful of known pairs with
+    /// [`PhysicsHooks`], e.g., a character and its mount. The pair is excluded regardless of
+    /// the collision groups and hooks of its colliders. Existing contacts or intersections
+    /// between these colliders are removed, with the corresponding collision events, the next
+    /// time either of them is moved or modified. The exclusion is removed automatically when
+    /// either collider is removed.
+    pub fn exclude_pair(&mut self, collider1: ColliderHandle, collider2: ColliderHandle) {
+        let others1 = self
+            .excluded_pairs
+            .ensure_element_exist(collider1.0, Vec::new());
+        if !others1.contains(&collider2) {
+            others1.push(collider2);
+            self.excluded_pairs
+                .ensure_element_exist(collider2.0, Vec::new())
+                .push(collider1);
+        }
+    }
+
+    /// Cancels the exclusion of a pair of colliders set with [`NarrowPhase::exclude_pair`].
+    ///
+    /// The contacts or intersections between these colliders are computed again the next time
+    /// either of them is moved or modified.
+    pub fn include_pair(&mut self, collider1: ColliderHandle, collider2: ColliderHandle) {
+        for (handle, other) in [(collider1, collider2), (collider2, collider1)] {
+            if let Some(others) = self.excluded_pairs.get_mut(handle.0) {
+                if let Some(i) = others.iter().position(|h| *h == other) {
+                    let _ = others.swap_remove(i);
+                }
+            }
+        }
+    }
+
+    /// Is the pair of colliders excluded with [`NarrowPhase::exclude_pair`]?
+    pub fn is_pair_excluded(&self, collider1: ColliderHandle, collider2: ColliderHandle) -> bool {
+        Self::is_excluded(&self.excluded_pairs, collider1, collider2)
+    }
+
+    fn is_excluded(
+        excluded_pairs: &Coarena<Vec<ColliderHandle>>,
+        collider1: ColliderHandle,
+        collider2: ColliderHandle,
+    ) -> bool {
+        excluded_pairs
+            .get(collider1.0)
+            .map(|others| others.contains(&collider2))
+            .unwrap_or(false)
+    }
+
+    /// Sets the collision groups of a collider, and immediately clears the contacts and
+    /// intersections of its pairs filtered out by these new groups.
+    ///
+    /// With [`Collider::set_collision_groups`], the pairs filtered out by the new groups keep
+    /// their contacts until the next timestep. This clears them right away instead, wakes up the
+    /// dynamic bodies that were in contact, and emits the corresponding collision stopped events.
+    /// The pairs stay registered, so their contacts are computed again if the groups change back.
+    ///
+    /// Returns the number of pairs that had contacts or intersections, and were cleared.
+    pub fn set_collision_groups(
+        &mut self,
+        mut islands: Option<&mut IslandManager>,
+        colliders: &mut ColliderSet,
+        bodies: &mut RigidBodySet,
+        handle: ColliderHandle,
+        groups: InteractionGroups,
+        events: &dyn EventHandler,
+    ) -> usize {
+        let Some(co) = colliders.get_mut(handle) else {
+            return 0;
+        };
+        co.set_collision_groups(groups);
+
+        let colliders = &*colliders;
+        let event_filter = self.event_filter;
+        let mut num_cleared = 0;
+
+        for pair in self.contact_pairs_with_mut(handle) {
+            let co1 = &colliders[pair.collider1];
+            let co2 = &colliders[pair.collider2];
+
+            if co1.flags.collision_groups.test(co2.flags.collision_groups) {
+                continue;
+            }
+
+            if pair.has_any_active_contact {
+                num_cleared += 1;
+
+                if let Some(islands) = islands.as_deref_mut() {
+                    for co_parent in [&co1.parent, &co2.parent].into_iter().flatten() {
+                        islands.wake_up(bodies, co_parent.handle, true);
+                    }
+                }
+
+                if Self::pair_active_events(event_filter, co1, co2)
+                    .contains(ActiveEvents::COLLISION_EVENTS)
+                {
+                    pair.emit_stop_event(bodies, colliders, events);
+                }
+            }
+
+            pair.clear();
+        }
+
+        if let Some(id) = self.graph_indices.get(handle.0) {
+            for (handle1, handle2, _, intersection) in self
+                .intersection_graph
+                .interactions_with_mut(id.intersection_graph_index)
+            {
+                let co1 = &colliders[handle1];
+                let co2 = &colliders[handle2];
+
+                if !intersection.intersecting
+                    || co1.flags.collision_groups.test(co2.flags.collision_groups)
+                {
+                    continue;
+                }
+
+                num_cleared += 1;
+                intersection.intersecting = false;
+
+                if Self::pair_active_events(event_filter, co1, co2)
+                    .contains(ActiveEvents::COLLISION_EVENTS)
+                {
+                    intersection.emit_stop_event(bodies, colliders, handle1, handle2, events);
+                }
+            }
         }
+
+        num_cleared
     }
 
     /// The query dispatcher used by this narrow-phase to select the right collision-detection
@@ -139,6 +339,20 @@ impl NarrowPhase {
             .map(|pair| pair.2)
     }
 
+    /// All the contact pairs involving the given collider, mutably.
+    pub(crate) fn contact_pairs_with_mut(
+        &mut self,
+        collider: ColliderHandle,
+    ) -> impl Iterator<Item = &mut ContactPair> {
+        let contact_graph = &mut self.contact_graph;
+        self.graph_indices
+            .get(collider.0)
+            .map(move |id| contact_graph.interactions_with_mut(id.contact_graph_index))
+            .into_iter()
+            .flatten()
+            .map(|pair| pair.3)
+    }
+
     /// All the intersection pairs involving the given collider.
     ///
     /// It is strongly recommended to use the [`NarrowPhase::intersection_pairs_with`]  method instead.
@@ -184,6 +398,13 @@ impl NarrowPhase {
         &self.contact_graph.graph.edges[id.index()].weight
     }
 
+    pub(crate) fn contact_pair_at_index_mut(
+        &mut self,
+        id: TemporaryInteractionIndex,
+    ) -> &mut ContactPair {
+        &mut self.contact_graph.graph.edges[id.index()].weight
+    }
+
     /// The contact pair involving two specific colliders.
     ///
     /// It is strongly recommended to use the [`NarrowPhase::contact_pair`] method instead. This
@@ -217,6 +438,78 @@ impl NarrowPhase {
             .map(|c| c.2)
     }
 
+    /// The contact pair involving two specific colliders, mutably.
+    ///
+    /// This is intended for accessing the [`ContactData`] of the contacts, e.g., to modify their
+    /// warmstart impulses. The contact points themselves are recomputed at the next step.
+    pub fn contact_pair_mut(
+        &mut self,
+        collider1: ColliderHandle,
+        collider2: ColliderHandle,
+    ) -> Option<&mut ContactPair> {
+        let id1 = self.graph_indices.get(collider1.0)?;
+        let id2 = self.graph_indices.get(collider2.0)?;
+        self.contact_graph
+            .interaction_pair_mut(id1.contact_graph_index, id2.contact_graph_index)
+            .map(|c| c.2)
+    }
+
+    /// The warmstart impulses of all the contacts maintained by this narrow-phase.
+    ///
+    /// These can be saved, e.g., with a network snapshot, and given back later to
+    /// [`NarrowPhase::set_warmstart_impulses`].
+    pub fn warmstart_impulses(&self) -> impl Iterator<Item = WarmstartImpulse> + '_ {
+        self.contact_pairs()
+            .flat_map(|pair| pair.warmstart_impulses())
+    }
+
+    /// Sets the warmstart impulses of the contacts matching the given ones.
+    ///
+    /// Each impulse is given to the contact between the same colliders, sub-shapes, and features,
+    /// if it exists. Returns the number of contacts that were modified.
+    pub fn set_warmstart_impulses(
+        &mut self,
+        impulses: impl IntoIterator<Item = WarmstartImpulse>,
+    ) -> usize {
+        impulses
+            .into_iter()
+            .filter(|impulse| {
+                self.contact_pair_mut(impulse.collider1, impulse.collider2)
+                    .map(|pair| pair.set_warmstart_impulse(impulse))
+                    .unwrap_or(false)
+            })
+            .count()
+    }
+
+    /// Sets to zero the warmstart impulses of all the contacts involving the given collider.
+    ///
+    /// This avoids stale impulses from being applied after moving the collider’s rigid-body
+    /// abruptly.
+    pub fn reset_warmstart_impulses_with(&mut self, collider: ColliderHandle) {
+        for pair in self.contact_pairs_with_mut(collider) {
+            pair.reset_warmstart_impulses();
+        }
+    }
+
+    /// Multiplies the warmstart impulses of all the contacts by `scale`.
+    ///
+    /// The warmstart impulses are applied during a single solver substep, so they must be
+    /// rescaled when the substep length changes. See [`IntegrationParameters::transition_to`](crate::dynamics::IntegrationParameters::transition_to).
+    pub fn scale_warmstart_impulses(&mut self, scale: Real) {
+        for pair in self.contact_graph.graph.edges.iter_mut() {
+            pair.weight.scale_warmstart_impulses(scale);
+        }
+    }
+
+    /// Rescales the warmstart impulses of all the contacts if they were computed with another
+    /// ratio between the default number of substeps and the number of substeps actually run.
+    pub(crate) fn set_substep_count_ratio(&mut self, substep_count_ratio: Real) {
+        if substep_count_ratio != self.substep_count_ratio {
+            self.scale_warmstart_impulses(substep_count_ratio / self.substep_count_ratio);
+            self.substep_count_ratio = substep_count_ratio;
+        }
+    }
+
     /// The intersection pair involving two specific colliders.
     ///
     /// It is strongly recommended to use the [`NarrowPhase::intersection_pair`] method instead. This
@@ -285,6 +578,14 @@ impl NarrowPhase {
         let mut contact_id_remap = HashMap::new();
 
         for collider in removed_colliders {
+            let _ = self.take_fixed_pairs(*collider);
+
+            if let Some(others) = self.excluded_pairs.remove(collider.0, Vec::new()) {
+                for other in others {
+                    self.include_pair(*collider, other);
+                }
+            }
+
             // NOTE: if the collider does not have any graph indices currently, there is nothing
             // to remove in the narrow-phase for this collider.
             if let Some(graph_idx) = self
@@ -424,6 +725,7 @@ impl NarrowPhase {
         events: &dyn EventHandler,
     ) {
         let mut pairs_to_remove = vec![];
+        let mut pairs_to_add = vec![];
 
         for handle in modified_colliders {
             // NOTE: we use `get` because the collider may no longer
@@ -434,6 +736,23 @@ impl NarrowPhase {
                     continue;
                 }
 
+                // If the type of the collider’s parent, or the types of interactions the
+                // collider allows, may have changed, the pairs only involving fixed colliders
+                // need to be re-evaluated.
+                let fixed_pairs_changed = co.changes.intersects(
+                    ColliderChanges::PARENT
+                        | ColliderChanges::PARENT_EFFECTIVE_DOMINANCE
+                        | ColliderChanges::ACTIVE_COLLISION_TYPES
+                        | ColliderChanges::ACTIVE_HOOKS
+                        | ColliderChanges::TYPE,
+                );
+
+                if fixed_pairs_changed {
+                    for other in self.take_fixed_pairs(*handle) {
+                        pairs_to_add.push(ColliderPair::new(*handle, other));
+                    }
+                }
+
                 if let Some(gid) = self.graph_indices.get(handle.0) {
                     // For each modified colliders, we need to wake-up the bodies it is in contact with
                     // so that the narrow-phase properly takes into account the change in, e.g.,
@@ -494,6 +813,25 @@ impl NarrowPhase {
                             }
                         }
                     }
+
+                    // Move the pairs that now only involve fixed colliders out of the graphs.
+                    if parent_type_changed && Self::collider_body_type(bodies, co).is_fixed() {
+                        let contact_pairs = self
+                            .contact_graph
+                            .interactions_with(gid.contact_graph_index)
+                            .map(|inter| (inter.0, inter.1));
+                        let intersection_pairs = self
+                            .intersection_graph
+                            .interactions_with(gid.intersection_graph_index)
+                            .map(|inter| (inter.0, inter.1));
+
+                        for (h1, h2) in contact_pairs.chain(intersection_pairs) {
+                            if Self::is_fixed_pair(bodies, &colliders[h1], &colliders[h2]) {
+                                pairs_to_remove
+                                    .push((ColliderPair::new(h1, h2), PairRemovalMode::Auto));
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -512,8 +850,78 @@ impl NarrowPhase {
 
         // Add the paid removed pair to the relevant graph.
         for pair in pairs_to_remove {
-            self.add_pair(colliders, &pair.0);
+            self.add_pair(colliders, bodies, &pair.0);
         }
+
+        for pair in pairs_to_add {
+            self.add_pair(colliders, bodies, &pair);
+        }
+    }
+
+    fn collider_body_type(bodies: &RigidBodySet, co: &Collider) -> RigidBodyType {
+        co.parent
+            .and_then(|co_parent| bodies.get(co_parent.handle))
+            .map(|rb| rb.body_type)
+            .unwrap_or(RigidBodyType::Fixed)
+    }
+
+    /// Checks if both colliders are fixed and can’t interact with each other.
+    fn is_fixed_pair(bodies: &RigidBodySet, co1: &Collider, co2: &Collider) -> bool {
+        let fixed = RigidBodyType::Fixed;
+        Self::collider_body_type(bodies, co1) == fixed
+            && Self::collider_body_type(bodies, co2) == fixed
+            && !co1.flags.active_collision_types.test(fixed, fixed)
+            && !co2.flags.active_collision_types.test(fixed, fixed)
+    }
+
+    fn insert_fixed_pair(&mut self, pair: &ColliderPair) {
+        let others1 = self
+            .fixed_pairs
+            .ensure_element_exist(pair.collider1.0, Vec::new());
+        if !others1.contains(&pair.collider2) {
+            others1.push(pair.collider2);
+            self.fixed_pairs
+                .ensure_element_exist(pair.collider2.0, Vec::new())
+                .push(pair.collider1);
+        }
+    }
+
+    /// Removes the given pair from the fixed pairs, returning `true` if it was there.
+    fn remove_fixed_pair(&mut self, pair: &ColliderPair) -> bool {
+        let mut removed = false;
+
+        for (handle, other) in [
+            (pair.collider1, pair.collider2),
+            (pair.collider2, pair.collider1),
+        ] {
+            if let Some(others) = self.fixed_pairs.get_mut(handle.0) {
+                if let Some(i) = others.iter().position(|h| *h == other) {
+                    let _ = others.swap_remove(i);
+                    removed = true;
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Removes all the fixed pairs involving the given collider, and returns the other
+    /// collider of each of these pairs.
+    fn take_fixed_pairs(&mut self, handle: ColliderHandle) -> Vec<ColliderHandle> {
+        let others = self
+            .fixed_pairs
+            .remove(handle.0, Vec::new())
+            .unwrap_or_default();
+
+        for other in &others {
+            if let Some(others_of_other) = self.fixed_pairs.get_mut(other.0) {
+                if let Some(i) = others_of_other.iter().position(|h| *h == handle) {
+                    let _ = others_of_other.swap_remove(i);
+                }
+            }
+        }
+
+        others
     }
 
     #[profiling::function]
@@ -526,6 +934,11 @@ impl NarrowPhase {
         events: &dyn EventHandler,
         mode: PairRemovalMode,
     ) {
+        if self.remove_fixed_pair(pair) {
+            // This pair wasn’t part of any graph.
+            return;
+        }
+
         if let (Some(co1), Some(co2)) =
             (colliders.get(pair.collider1), colliders.get(pair.collider2))
         {
@@ -545,7 +958,7 @@ impl NarrowPhase {
                     // Emit an intersection lost event if we had an intersection before removing the edge.
                     if let Some(mut intersection) = intersection {
                         if intersection.intersecting
-                            && (co1.flags.active_events | co2.flags.active_events)
+                            && Self::pair_active_events(self.event_filter, co1, co2)
                                 .contains(ActiveEvents::COLLISION_EVENTS)
                         {
                             intersection.emit_stop_event(
@@ -576,7 +989,7 @@ impl NarrowPhase {
                                 }
                             }
 
-                            if (co1.flags.active_events | co2.flags.active_events)
+                            if Self::pair_active_events(self.event_filter, co1, co2)
                                 .contains(ActiveEvents::COLLISION_EVENTS)
                             {
                                 ctct.emit_stop_event(bodies, colliders, events);
@@ -589,7 +1002,7 @@ impl NarrowPhase {
     }
 
     #[profiling::function]
-    fn add_pair(&mut self, colliders: &ColliderSet, pair: &ColliderPair) {
+    fn add_pair(&mut self, colliders: &ColliderSet, bodies: &RigidBodySet, pair: &ColliderPair) {
         if let (Some(co1), Some(co2)) =
             (colliders.get(pair.collider1), colliders.get(pair.collider2))
         {
@@ -599,6 +1012,12 @@ impl NarrowPhase {
                 return;
             }
 
+            if Self::is_fixed_pair(bodies, co1, co2) {
+                // Fixed colliders that can’t interact don’t need to be part of any graph.
+                self.insert_fixed_pair(pair);
+                return;
+            }
+
             // These colliders have no parents - continue.
 
             let (gid1, gid2) = self.graph_indices.ensure_pair_exists(
@@ -676,7 +1095,7 @@ impl NarrowPhase {
         for event in broad_phase_events {
             match event {
                 BroadPhasePairEvent::AddPair(pair) => {
-                    self.add_pair(colliders, pair);
+                    self.add_pair(colliders, bodies, pair);
                 }
                 BroadPhasePairEvent::DeletePair(pair) => {
                     self.remove_pair(
@@ -705,96 +1124,213 @@ impl NarrowPhase {
             return;
         }
 
-        let nodes = &self.intersection_graph.graph.nodes;
         let query_dispatcher = &*self.query_dispatcher;
+        let excluded_pairs = &self.excluded_pairs;
+        let event_filter = self.event_filter;
+
+        // The pairs involving a sensor never compute contact manifolds nor solver flags: they
+        // only track the result of a shape intersection test. So the only remaining cost worth
+        // cutting is the traversal itself. When only a few colliders moved, e.g., in worlds made
+        // mostly of static trigger volumes, visiting only the intersection pairs involving them
+        // is much cheaper than going through all the pairs just to find out most of them don’t
+        // need an update.
+        if modified_colliders.len() * 4 < self.intersection_graph.graph.edges.len() {
+            for handle in modified_colliders {
+                let Some(co) = colliders.get(*handle) else {
+                    continue;
+                };
 
-        // TODO: don't iterate on all the edges.
-        par_iter_mut!(&mut self.intersection_graph.graph.edges).for_each(|edge| {
-            let handle1 = nodes[edge.source().index()].weight;
-            let handle2 = nodes[edge.target().index()].weight;
-            let had_intersection = edge.weight.intersecting;
-            let co1 = &colliders[handle1];
-            let co2 = &colliders[handle2];
+                if !co.changes.needs_narrow_phase_update() {
+                    continue;
+                }
 
-            'emit_events: {
-                if !co1.changes.needs_narrow_phase_update()
-                    && !co2.changes.needs_narrow_phase_update()
+                let Some(id) = self.graph_indices.get(handle.0) else {
+                    continue;
+                };
+
+                for (handle1, handle2, _, pair) in self
+                    .intersection_graph
+                    .interactions_with_mut(id.intersection_graph_index)
                 {
-                    // No update needed for these colliders.
-                    return;
-                }
+                    let other = if handle1 == *handle { handle2 } else { handle1 };
 
-                // TODO: avoid lookup into bodies.
-                let mut rb_type1 = RigidBodyType::Fixed;
-                let mut rb_type2 = RigidBodyType::Fixed;
+                    // If both colliders were modified, only update the pair once.
+                    if colliders[other].changes.needs_narrow_phase_update() && other.0 < handle.0 {
+                        continue;
+                    }
 
-                if let Some(co_parent1) = &co1.parent {
-                    rb_type1 = bodies[co_parent1.handle].body_type;
+                    Self::update_intersection_pair(
+                        query_dispatcher,
+                        bodies,
+                        colliders,
+                        excluded_pairs,
+                        event_filter,
+                        hooks,
+                        events,
+                        handle1,
+                        handle2,
+                        pair,
+                    );
                 }
+            }
 
-                if let Some(co_parent2) = &co2.parent {
-                    rb_type2 = bodies[co_parent2.handle].body_type;
-                }
+            return;
+        }
 
-                // Filter based on the rigid-body types.
-                if !co1.flags.active_collision_types.test(rb_type1, rb_type2)
-                    && !co2.flags.active_collision_types.test(rb_type1, rb_type2)
-                {
-                    edge.weight.intersecting = false;
-                    break 'emit_events;
-                }
+        let nodes = &self.intersection_graph.graph.nodes;
 
-                // Filter based on collision groups.
-                if !co1.flags.collision_groups.test(co2.flags.collision_groups) {
-                    edge.weight.intersecting = false;
-                    break 'emit_events;
-                }
+        par_iter_mut!(&mut self.intersection_graph.graph.edges).for_each(|edge| {
+            let handle1 = nodes[edge.source().index()].weight;
+            let handle2 = nodes[edge.target().index()].weight;
+            Self::update_intersection_pair(
+                query_dispatcher,
+                bodies,
+                colliders,
+                excluded_pairs,
+                event_filter,
+                hooks,
+                events,
+                handle1,
+                handle2,
+                &mut edge.weight,
+            );
+        });
+    }
 
-                let active_hooks = co1.flags.active_hooks | co2.flags.active_hooks;
+    fn update_intersection_pair(
+        query_dispatcher: &dyn PersistentQueryDispatcher<ContactManifoldData, ContactData>,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        excluded_pairs: &Coarena<Vec<ColliderHandle>>,
+        event_filter: Option<InteractionGroups>,
+        hooks: &dyn PhysicsHooks,
+        events: &dyn EventHandler,
+        handle1: ColliderHandle,
+        handle2: ColliderHandle,
+        pair: &mut IntersectionPair,
+    ) {
+        let had_intersection = pair.intersecting;
+        let co1 = &colliders[handle1];
+        let co2 = &colliders[handle2];
+        let mut swept_intersection = false;
 
-                if active_hooks.contains(ActiveHooks::FILTER_INTERSECTION_PAIR) {
-                    let context = PairFilterContext {
-                        bodies,
-                        colliders,
-                        rigid_body1: co1.parent.map(|p| p.handle),
-                        rigid_body2: co2.parent.map(|p| p.handle),
-                        collider1: handle1,
-                        collider2: handle2,
-                    };
+        'emit_events: {
+            if !co1.changes.needs_narrow_phase_update() && !co2.changes.needs_narrow_phase_update()
+            {
+                // No update needed for these colliders.
+                return;
+            }
 
-                    if !hooks.filter_intersection_pair(&context) {
-                        // No intersection allowed.
-                        edge.weight.intersecting = false;
-                        break 'emit_events;
-                    }
-                }
+            // TODO: avoid lookup into bodies.
+            let mut rb_type1 = RigidBodyType::Fixed;
+            let mut rb_type2 = RigidBodyType::Fixed;
 
-                let pos12 = co1.pos.inv_mul(&co2.pos);
-                edge.weight.intersecting = query_dispatcher
-                    .intersection_test(&pos12, &*co1.shape, &*co2.shape)
-                    .unwrap_or(false);
+            if let Some(co_parent1) = &co1.parent {
+                rb_type1 = bodies[co_parent1.handle].body_type;
             }
 
-            let active_events = co1.flags.active_events | co2.flags.active_events;
+            if let Some(co_parent2) = &co2.parent {
+                rb_type2 = bodies[co_parent2.handle].body_type;
+            }
 
-            if active_events.contains(ActiveEvents::COLLISION_EVENTS)
-                && had_intersection != edge.weight.intersecting
+            // Filter based on the rigid-body types.
+            if !co1.flags.active_collision_types.test(rb_type1, rb_type2)
+                && !co2.flags.active_collision_types.test(rb_type1, rb_type2)
             {
-                if edge.weight.intersecting {
-                    edge.weight
-                        .emit_start_event(bodies, colliders, handle1, handle2, events);
+                pair.intersecting = false;
+                break 'emit_events;
+            }
+
+            // Filter based on collision groups.
+            if !co1.flags.collision_groups.test(co2.flags.collision_groups) {
+                pair.intersecting = false;
+                break 'emit_events;
+            }
+
+            if Self::is_excluded(excluded_pairs, handle1, handle2) {
+                pair.intersecting = false;
+                break 'emit_events;
+            }
+
+            let active_hooks = co1.flags.active_hooks | co2.flags.active_hooks;
+
+            if active_hooks.contains(ActiveHooks::FILTER_INTERSECTION_PAIR) {
+                let context = PairFilterContext {
+                    bodies,
+                    colliders,
+                    rigid_body1: co1.parent.map(|p| p.handle),
+                    rigid_body2: co2.parent.map(|p| p.handle),
+                    collider1: handle1,
+                    collider2: handle2,
+                };
+
+                if !hooks.filter_intersection_pair(&context) {
+                    // No intersection allowed.
+                    pair.intersecting = false;
+                    break 'emit_events;
+                }
+            }
+
+            let pos12 = co1.pos.inv_mul(&co2.pos);
+            pair.intersecting = query_dispatcher
+                .intersection_test(&pos12, &*co1.shape, &*co2.shape)
+                .unwrap_or(false);
+
+            swept_intersection = !had_intersection
+                && !pair.intersecting
+                && Self::swept_intersection_test(query_dispatcher, co1, co2);
+        }
+
+        let active_events = Self::pair_active_events(event_filter, co1, co2);
+
+        if active_events.contains(ActiveEvents::COLLISION_EVENTS) {
+            if swept_intersection {
+                // The colliders only intersected in-between the previous and current positions.
+                pair.emit_start_event(bodies, colliders, handle1, handle2, events);
+                pair.emit_stop_event(bodies, colliders, handle1, handle2, events);
+            } else if had_intersection != pair.intersecting {
+                if pair.intersecting {
+                    pair.emit_start_event(bodies, colliders, handle1, handle2, events);
                 } else {
-                    edge.weight
-                        .emit_stop_event(bodies, colliders, handle1, handle2, events);
+                    pair.emit_stop_event(bodies, colliders, handle1, handle2, events);
                 }
             }
-        });
+        }
+    }
+
+    /// Checks if two colliders intersected during the latest substep, if at least one of them is
+    /// swept and moved.
+    fn swept_intersection_test(
+        query_dispatcher: &dyn PersistentQueryDispatcher<ContactManifoldData, ContactData>,
+        co1: &Collider,
+        co2: &Collider,
+    ) -> bool {
+        if !(co1.is_swept() || co2.is_swept()) || (co1.prev_pos.is_none() && co2.prev_pos.is_none())
+        {
+            return false;
+        }
+
+        query_dispatcher
+            .cast_shapes_nonlinear(
+                &co1.swept_motion(),
+                &*co1.shape,
+                &co2.swept_motion(),
+                &*co2.shape,
+                0.0,
+                1.0,
+                true,
+            )
+            .ok()
+            .flatten()
+            .is_some()
     }
 
     #[profiling::function]
     pub(crate) fn compute_contacts(
         &mut self,
         prediction_distance: Real,
+        heightfield_cache_distance: Real,
+        parallel_chunk_size: Option<NonZeroUsize>,
         dt: Real,
         bodies: &RigidBodySet,
         colliders: &ColliderSet,
@@ -803,28 +1339,46 @@ impl NarrowPhase {
         modified_colliders: &[ColliderHandle],
         hooks: &dyn PhysicsHooks,
         events: &dyn EventHandler,
-    ) {
+    ) -> NarrowPhaseWorkload {
+        let workload = NarrowPhaseWorkload::default();
+
         if modified_colliders.is_empty() {
-            return;
+            return workload;
         }
 
         let query_dispatcher = &*self.query_dispatcher;
+        let excluded_pairs = &self.excluded_pairs;
+        let event_filter = self.event_filter;
+
+        #[cfg(not(feature = "parallel"))]
+        let pairs = {
+            let _ = parallel_chunk_size;
+            self.contact_graph.graph.edges.iter_mut()
+        };
+        #[cfg(feature = "parallel")]
+        let pairs = self
+            .contact_graph
+            .graph
+            .edges
+            .par_iter_mut()
+            .with_max_len(parallel_chunk_size.map_or(usize::MAX, NonZeroUsize::get));
 
         // TODO: don't iterate on all the edges.
-        par_iter_mut!(&mut self.contact_graph.graph.edges).for_each(|edge| {
+        let update_pair = |edge: &mut Edge<ContactPair>, contact_user_slots_buffer: &mut Vec<_>| {
             let pair = &mut edge.weight;
             let had_any_active_contact = pair.has_any_active_contact;
             let co1 = &colliders[pair.collider1];
             let co2 = &colliders[pair.collider2];
 
-            'emit_events: {
-                if !co1.changes.needs_narrow_phase_update()
-                    && !co2.changes.needs_narrow_phase_update()
-                {
-                    // No update needed for these colliders.
-                    return;
-                }
+            if !co1.changes.needs_narrow_phase_update() && !co2.changes.needs_narrow_phase_update()
+            {
+                // No update needed for these colliders.
+                return;
+            }
 
+            let pair_start = workload.pair_started();
+
+            'emit_events: {
                 let rb1 = co1.parent.map(|co_parent1| &bodies[co_parent1.handle]);
                 let rb2 = co2.parent.map(|co_parent2| &bodies[co_parent2.handle]);
 
@@ -845,7 +1399,7 @@ impl NarrowPhase {
                     let link1 = multibody_joints.rigid_body_link(co_parent1.handle);
                     let link2 = multibody_joints.rigid_body_link(co_parent2.handle);
 
-                    if let (Some(link1),Some(link2)) = (link1, link2) {
+                    if let (Some(link1), Some(link2)) = (link1, link2) {
                         // If both bodies belong to the same multibody, apply some additional built-in
                         // contact filtering rules.
                         if link1.multibody == link2.multibody {
@@ -884,6 +1438,11 @@ impl NarrowPhase {
                     break 'emit_events;
                 }
 
+                if Self::is_excluded(excluded_pairs, pair.collider1, pair.collider2) {
+                    pair.clear();
+                    break 'emit_events;
+                }
+
                 let active_hooks = co1.flags.active_hooks | co2.flags.active_hooks;
 
                 let mut solver_flags = if active_hooks.contains(ActiveHooks::FILTER_CONTACT_PAIRS) {
@@ -911,48 +1470,103 @@ impl NarrowPhase {
                     solver_flags.remove(SolverFlags::COMPUTE_IMPULSES);
                 }
 
-                if co1.changes.contains(ColliderChanges::SHAPE)
-                    || co2.changes.contains(ColliderChanges::SHAPE)
-                {
+                let shape_changed = co1.changes.contains(ColliderChanges::SHAPE)
+                    || co2.changes.contains(ColliderChanges::SHAPE);
+
+                if shape_changed {
                     // The shape changed so the workspace is no longer valid.
                     pair.workspace = None;
                 }
 
                 let pos12 = co1.pos.inv_mul(&co2.pos);
 
+                let prediction_distance =
+                    Collider::combined_speculative_margin(co1, co2, prediction_distance);
                 let contact_skin_sum = co1.contact_skin() + co2.contact_skin();
-                let soft_ccd_prediction1 = rb1.map(|rb| rb.soft_ccd_prediction()).unwrap_or(0.0);
-                let soft_ccd_prediction2 = rb2.map(|rb| rb.soft_ccd_prediction()).unwrap_or(0.0);
-                let effective_prediction_distance = if soft_ccd_prediction1 > 0.0 || soft_ccd_prediction2 > 0.0 {
+                // NOTE: debris are never affected by soft-CCD.
+                let soft_ccd_prediction1 = rb1
+                    .filter(|rb| !rb.is_debris())
+                    .map(|rb| rb.soft_ccd_prediction())
+                    .unwrap_or(0.0);
+                let soft_ccd_prediction2 = rb2
+                    .filter(|rb| !rb.is_debris())
+                    .map(|rb| rb.soft_ccd_prediction())
+                    .unwrap_or(0.0);
+                let effective_prediction_distance =
+                    if soft_ccd_prediction1 > 0.0 || soft_ccd_prediction2 > 0.0 {
                         let aabb1 = co1.compute_collision_aabb(0.0);
                         let aabb2 = co2.compute_collision_aabb(0.0);
                         let inv_dt = crate::utils::inv(dt);
 
-                        let linvel1 = rb1.map(|rb| rb.linvel()
-                            .cap_magnitude(soft_ccd_prediction1 * inv_dt)).unwrap_or_default();
-                        let linvel2 = rb2.map(|rb| rb.linvel()
-                            .cap_magnitude(soft_ccd_prediction2 * inv_dt)).unwrap_or_default();
+                        let linvel1 = rb1
+                            .map(|rb| rb.linvel().cap_magnitude(soft_ccd_prediction1 * inv_dt))
+                            .unwrap_or_default();
+                        let linvel2 = rb2
+                            .map(|rb| rb.linvel().cap_magnitude(soft_ccd_prediction2 * inv_dt))
+                            .unwrap_or_default();
 
-                        if !aabb1.intersects(&aabb2) && !aabb1.intersects_moving_aabb(&aabb2, linvel2 - linvel1) {
+                        if !aabb1.intersects(&aabb2)
+                            && !aabb1.intersects_moving_aabb(&aabb2, linvel2 - linvel1)
+                        {
                             pair.clear();
                             break 'emit_events;
                         }
 
+                        prediction_distance.max(dt * (linvel1 - linvel2).norm()) + contact_skin_sum
+                    } else {
+                        prediction_distance + contact_skin_sum
+                    };
 
-                    prediction_distance.max(
-                        dt * (linvel1 - linvel2).norm()) + contact_skin_sum
+                // Move the contacts along with the rigid-bodies teleported with preserved contacts.
+                // Rigid-bodies teleported by the same motion have nothing to update.
+                let teleport1 = rb1.and_then(|rb| rb.teleport_motion);
+                let teleport2 = rb2.and_then(|rb| rb.teleport_motion);
+                let teleported_contacts = (teleport1 != teleport2)
+                    .then(|| follow_teleport(&mut pair.manifolds, co1, co2, teleport1, teleport2));
+
+                // Contacts against fixed heightfields are only queried again once the other
+                // collider moved far enough since the last query.
+                let cache_heightfield_contacts = heightfield_cache_distance > 0.0
+                    && !shape_changed
+                    && teleported_contacts.is_none()
+                    && is_fixed_heightfield_pair(co1, co2, rb_type1, rb_type2);
+                let reuse_cached_contacts = cache_heightfield_contacts
+                    && pair.heightfield_cache_pos12.is_some_and(|cached_pos12| {
+                        heightfield_relative_motion(co1, co2, &cached_pos12, &pos12)
+                            < heightfield_cache_distance
+                    });
+
+                if reuse_cached_contacts {
+                    update_cached_contacts(&mut pair.manifolds, &pos12);
                 } else {
-                    prediction_distance + contact_skin_sum
-                };
+                    let query_prediction_distance = if cache_heightfield_contacts {
+                        // Include the contacts the collider may reach before the next query.
+                        effective_prediction_distance + heightfield_cache_distance
+                    } else {
+                        effective_prediction_distance
+                    };
 
-                let _ = query_dispatcher.contact_manifolds(
-                    &pos12,
-                    &*co1.shape,
-                    &*co2.shape,
-                    effective_prediction_distance,
-                    &mut pair.manifolds,
-                    &mut pair.workspace,
-                );
+                    let _ = query_dispatcher.contact_manifolds(
+                        &pos12,
+                        &*co1.shape,
+                        &*co2.shape,
+                        query_prediction_distance,
+                        &mut pair.manifolds,
+                        &mut pair.workspace,
+                    );
+                    pair.heightfield_cache_pos12 = cache_heightfield_contacts.then_some(pos12);
+                }
+
+                if let Some(teleported_contacts) = teleported_contacts {
+                    restore_teleported_contacts(
+                        &mut pair.manifolds,
+                        teleported_contacts,
+                        teleport1.is_some(),
+                        effective_prediction_distance,
+                    );
+                }
+
+                pair.assign_contact_ids();
 
                 let friction = CoefficientCombineRule::combine(
                     co1.material.friction,
@@ -960,6 +1574,22 @@ impl NarrowPhase {
                     co1.material.friction_combine_rule as u8,
                     co2.material.friction_combine_rule as u8,
                 );
+                #[cfg(feature = "dim3")]
+                let (friction, anisotropic_friction) =
+                    combine_anisotropic_friction(co1, co2, friction);
+                let rolling_friction = CoefficientCombineRule::combine(
+                    co1.material.rolling_friction,
+                    co2.material.rolling_friction,
+                    co1.material.friction_combine_rule as u8,
+                    co2.material.friction_combine_rule as u8,
+                );
+                #[cfg(feature = "dim3")]
+                let twist_friction = CoefficientCombineRule::combine(
+                    co1.material.twist_friction,
+                    co2.material.twist_friction,
+                    co1.material.friction_combine_rule as u8,
+                    co2.material.friction_combine_rule as u8,
+                );
                 let restitution = CoefficientCombineRule::combine(
                     co1.material.restitution,
                     co2.material.restitution,
@@ -970,8 +1600,33 @@ impl NarrowPhase {
                 let zero = RigidBodyDominance(0); // The value doesn't matter, it will be MAX because of the effective groups.
                 let dominance1 = rb1.map(|rb| rb.dominance).unwrap_or(zero);
                 let dominance2 = rb2.map(|rb| rb.dominance).unwrap_or(zero);
+                // Only allow overriding the dominance between two dynamic bodies: non-dynamic
+                // bodies must always dominate so they are never affected by contact forces.
+                let is_debris1 = rb1.map(|rb| rb.is_debris()).unwrap_or(false);
+                let is_debris2 = rb2.map(|rb| rb.is_debris()).unwrap_or(false);
+                let relative_dominance = solver_flags
+                    .relative_dominance_override()
+                    .filter(|_| rb_type1.is_dynamic() && rb_type2.is_dynamic())
+                    .unwrap_or_else(|| {
+                        if rb_type1.is_dynamic()
+                            && rb_type2.is_dynamic()
+                            && is_debris1 != is_debris2
+                        {
+                            // Non-debris dynamic bodies always dominate debris.
+                            if is_debris1 {
+                                -1
+                            } else {
+                                1
+                            }
+                        } else {
+                            dominance1.effective_group(&rb_type1)
+                                - dominance2.effective_group(&rb_type2)
+                        }
+                    });
 
                 pair.has_any_active_contact = false;
+                let has_surface_velocity =
+                    !co1.surface_velocity().is_zero() || !co2.surface_velocity().is_zero();
 
                 for manifold in &mut pair.manifolds {
                     let world_pos1 = manifold.subshape_pos1.prepend_to(&co1.pos);
@@ -980,9 +1635,25 @@ impl NarrowPhase {
                     manifold.data.rigid_body1 = co1.parent.map(|p| p.handle);
                     manifold.data.rigid_body2 = co2.parent.map(|p| p.handle);
                     manifold.data.solver_flags = solver_flags;
-                    manifold.data.relative_dominance = dominance1.effective_group(&rb_type1)
-                        - dominance2.effective_group(&rb_type2);
+                    manifold.data.relative_dominance = relative_dominance;
+                    manifold.data.priority = co1.contact_priority().max(co2.contact_priority());
+                    manifold.data.contact_softness =
+                        ContactSoftness::combine(co1.contact_softness(), co2.contact_softness());
+                    manifold.data.rolling_friction = rolling_friction;
+                    #[cfg(feature = "dim3")]
+                    {
+                        manifold.data.twist_friction = twist_friction;
+                        manifold.data.anisotropic_friction = anisotropic_friction;
+                    }
                     manifold.data.normal = world_pos1 * manifold.local_n1;
+                    let normal = manifold.data.normal;
+
+                    // Drop the contacts against the disabled primitives of composite shapes.
+                    if !co1.is_primitive_enabled(manifold.subshape1)
+                        || !co2.is_primitive_enabled(manifold.subshape2)
+                    {
+                        continue;
+                    }
 
                     // Generate solver contacts.
                     for (contact_id, contact) in manifold.points.iter().enumerate() {
@@ -991,14 +1662,20 @@ impl NarrowPhase {
                             break;
                         }
 
-                        let effective_contact_dist = contact.dist - co1.contact_skin() - co2.contact_skin();
+                        let effective_contact_dist =
+                            contact.dist - co1.contact_skin() - co2.contact_skin();
 
                         let keep_solver_contact = effective_contact_dist < prediction_distance || {
                             let world_pt1 = world_pos1 * contact.local_p1;
                             let world_pt2 = world_pos2 * contact.local_p2;
-                            let vel1 = rb1.map(|rb| rb.velocity_at_point(&world_pt1)).unwrap_or_default();
-                            let vel2 = rb2.map(|rb| rb.velocity_at_point(&world_pt2)).unwrap_or_default();
-                            effective_contact_dist + (vel2 - vel1).dot(&manifold.data.normal) * dt < prediction_distance
+                            let vel1 = rb1
+                                .map(|rb| rb.velocity_at_point(&world_pt1))
+                                .unwrap_or_default();
+                            let vel2 = rb2
+                                .map(|rb| rb.velocity_at_point(&world_pt2))
+                                .unwrap_or_default();
+                            effective_contact_dist + (vel2 - vel1).dot(&manifold.data.normal) * dt
+                                < prediction_distance
                         };
 
                         if keep_solver_contact {
@@ -1006,6 +1683,13 @@ impl NarrowPhase {
                             let world_pt1 = world_pos1 * contact.local_p1;
                             let world_pt2 = world_pos2 * contact.local_p2;
                             let effective_point = na::center(&world_pt1, &world_pt2);
+                            let tangent_velocity = if has_surface_velocity {
+                                let vel = co1.surface_velocity_at_point(&effective_point)
+                                    - co2.surface_velocity_at_point(&effective_point);
+                                vel - normal * normal.dot(&vel)
+                            } else {
+                                Vector::zeros()
+                            };
 
                             let solver_contact = SolverContact {
                                 contact_id: contact_id as u8,
@@ -1013,7 +1697,7 @@ impl NarrowPhase {
                                 dist: effective_contact_dist,
                                 friction,
                                 restitution,
-                                tangent_velocity: Vector::zeros(),
+                                tangent_velocity,
                                 is_new: contact.data.impulse == 0.0,
                                 warmstart_impulse: contact.data.warmstart_impulse,
                                 warmstart_tangent_impulse: contact.data.warmstart_tangent_impulse,
@@ -1026,29 +1710,83 @@ impl NarrowPhase {
 
                     // Apply the user-defined contact modification.
                     if active_hooks.contains(ActiveHooks::MODIFY_SOLVER_CONTACTS) {
-                        let mut modifiable_solver_contacts =
-                            std::mem::take(&mut manifold.data.solver_contacts);
-                        let mut modifiable_user_data = manifold.data.user_data;
-                        let mut modifiable_normal = manifold.data.normal;
-
-                        let mut context = ContactModificationContext {
-                            bodies,
-                            colliders,
-                            rigid_body1: co1.parent.map(|p| p.handle),
-                            rigid_body2: co2.parent.map(|p| p.handle),
-                            collider1: pair.collider1,
-                            collider2: pair.collider2,
-                            manifold,
-                            solver_contacts: &mut modifiable_solver_contacts,
-                            normal: &mut modifiable_normal,
-                            user_data: &mut modifiable_user_data,
-                        };
+                        let cached_modification =
+                            manifold.data.cached_modification.take().filter(|cache| {
+                                !shape_changed
+                                    && cache.is_valid_for(manifold, &manifold.data.solver_contacts)
+                            });
+
+                        if let Some(cache) = cached_modification {
+                            // Re-apply the persistent modification without calling the hook.
+                            cache.apply(
+                                &world_pos1,
+                                &mut manifold.data.solver_contacts,
+                                &mut manifold.data.normal,
+                                &mut manifold.data.contact_softness,
+                            );
+                            manifold.data.cached_modification = Some(cache);
+                        } else {
+                            let original_contact_ids =
+                                solver_contact_id_mask(&manifold.data.solver_contacts);
+                            let mut modifiable_solver_contacts =
+                                std::mem::take(&mut manifold.data.solver_contacts);
+                            let mut modifiable_user_data = manifold.data.user_data;
+                            let mut modifiable_manifold_user_slots = manifold.data.user_slots;
+                            contact_user_slots_buffer.clear();
+                            contact_user_slots_buffer.extend(
+                                manifold
+                                    .points
+                                    .iter()
+                                    .map(|contact| contact.data.user_slots),
+                            );
+                            let mut modifiable_normal = manifold.data.normal;
+                            let mut modifiable_contact_softness = manifold.data.contact_softness;
+
+                            let mut context = ContactModificationContext {
+                                bodies,
+                                colliders,
+                                rigid_body1: co1.parent.map(|p| p.handle),
+                                rigid_body2: co2.parent.map(|p| p.handle),
+                                collider1: pair.collider1,
+                                collider2: pair.collider2,
+                                manifold,
+                                solver_contacts: &mut modifiable_solver_contacts,
+                                normal: &mut modifiable_normal,
+                                contact_softness: &mut modifiable_contact_softness,
+                                user_data: &mut modifiable_user_data,
+                                manifold_user_slots: &mut modifiable_manifold_user_slots,
+                                contact_user_slots: contact_user_slots_buffer,
+                                persistent: false,
+                            };
 
-                        hooks.modify_solver_contacts(&mut context);
+                            hooks.modify_solver_contacts(&mut context);
+                            let persistent = context.persistent;
+
+                            if persistent {
+                                manifold.data.cached_modification =
+                                    Some(CachedContactModification::new(
+                                        manifold,
+                                        &world_pos1,
+                                        original_contact_ids,
+                                        &modifiable_solver_contacts,
+                                        &modifiable_normal,
+                                        modifiable_contact_softness,
+                                    ));
+                            }
 
-                        manifold.data.solver_contacts = modifiable_solver_contacts;
-                        manifold.data.normal = modifiable_normal;
-                        manifold.data.user_data = modifiable_user_data;
+                            manifold.data.solver_contacts = modifiable_solver_contacts;
+                            manifold.data.normal = modifiable_normal;
+                            manifold.data.contact_softness = modifiable_contact_softness;
+                            manifold.data.user_data = modifiable_user_data;
+                            manifold.data.user_slots = modifiable_manifold_user_slots;
+                            for (contact, user_slots) in manifold
+                                .points
+                                .iter_mut()
+                                .zip(contact_user_slots_buffer.iter())
+                            {
+                                contact.data.user_slots = *user_slots;
+                            }
+                        }
                     }
 
                     /*
@@ -1082,7 +1820,12 @@ impl NarrowPhase {
                 }
             }
 
-            let active_events = co1.flags.active_events | co2.flags.active_events;
+            let active_events = Self::pair_active_events(event_filter, co1, co2);
+
+            if !pair.has_any_active_contact {
+                // Pairs no longer in contact stop reporting contact force events.
+                pair.reporting_contact_forces = false;
+            }
 
             if pair.has_any_active_contact != had_any_active_contact
                 && active_events.contains(ActiveEvents::COLLISION_EVENTS)
@@ -1093,7 +1836,21 @@ impl NarrowPhase {
                     pair.emit_stop_event(bodies, colliders, events);
                 }
             }
+
+            workload.pair_finished(pair_start);
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            let contact_user_slots_buffer = &mut self.contact_user_slots_buffer;
+            pairs.for_each(|edge| update_pair(edge, contact_user_slots_buffer));
+        }
+        #[cfg(feature = "parallel")]
+        pairs.for_each_init(Vec::new, |contact_user_slots_buffer, edge| {
+            update_pair(edge, contact_user_slots_buffer)
         });
+
+        workload
     }
 
     /// Retrieve all the interactions with at least one contact point, happening between two active bodies.
@@ -1110,6 +1867,8 @@ impl NarrowPhase {
             out_island.clear();
         }
 
+        let mut has_manifold_budgets = false;
+
         // TODO: don't iterate through all the interactions.
         for (pair_id, inter) in self.contact_graph.graph.edges.iter_mut().enumerate() {
             let mut push_pair = false;
@@ -1149,6 +1908,12 @@ impl NarrowPhase {
                         && (!rb_type1.is_dynamic() || !sleeping1)
                         && (!rb_type2.is_dynamic() || !sleeping2)
                     {
+                        has_manifold_budgets = has_manifold_budgets
+                            || [manifold.data.rigid_body1, manifold.data.rigid_body2]
+                                .iter()
+                                .flatten()
+                                .any(|h| bodies[*h].max_solved_contact_manifolds != usize::MAX);
+
                         let island_index = if !rb_type1.is_dynamic() {
                             active_island_id2
                         } else {
@@ -1166,5 +1931,1014 @@ impl NarrowPhase {
                 out_contact_pairs.push(EdgeIndex::new(pair_id as u32));
             }
         }
+
+        if has_manifold_budgets {
+            Self::apply_manifold_budgets(islands, bodies, out_manifolds, out);
+        }
+    }
+
+    /// Removes from the islands’ manifolds the shallowest manifolds involving rigid-bodies with
+    /// more manifolds than their `max_solved_contact_manifolds`.
+    fn apply_manifold_budgets(
+        islands: &IslandManager,
+        bodies: &RigidBodySet,
+        manifolds: &mut [&mut ContactManifold],
+        out: &mut [Vec<ContactManifoldIndex>],
+    ) {
+        let mut manifolds_per_body: HashMap<RigidBodyHandle, Vec<(Real, ContactManifoldIndex)>> =
+            HashMap::default();
+
+        for (manifold_id, manifold) in manifolds.iter().enumerate() {
+            let depth = manifold
+                .data
+                .solver_contacts
+                .iter()
+                .map(|c| -c.dist)
+                .fold(-Real::MAX, Real::max);
+
+            for handle in [manifold.data.rigid_body1, manifold.data.rigid_body2]
+                .into_iter()
+                .flatten()
+            {
+                if bodies[handle].max_solved_contact_manifolds != usize::MAX {
+                    manifolds_per_body
+                        .entry(handle)
+                        .or_default()
+                        .push((depth, manifold_id));
+                }
+            }
+        }
+
+        let mut excluded = vec![false; manifolds.len()];
+
+        for (handle, mut body_manifolds) in manifolds_per_body {
+            let budget = bodies[handle].max_solved_contact_manifolds;
+
+            if body_manifolds.len() > budget {
+                // Keep the deepest manifolds first.
+                body_manifolds.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+
+                for (_, manifold_id) in &body_manifolds[budget..] {
+                    excluded[*manifold_id] = true;
+                }
+            }
+        }
+
+        for (manifold_id, manifold) in manifolds.iter_mut().enumerate() {
+            if excluded[manifold_id] {
+                // The excluded manifolds aren’t solved, so their impulses must not be
+                // reported nor used for warmstarting.
+                for pt in &mut manifold.points {
+                    pt.data = ContactData {
+                        id: pt.data.id,
+                        ..ContactData::default()
+                    };
+                }
+            }
+        }
+
+        for out_island in &mut out[..islands.num_islands()] {
+            out_island.retain(|manifold_id| !excluded[*manifold_id]);
+        }
+    }
+}
+
+/// A contact saved by [`follow_teleport`], with its point expressed in the local-space of the
+/// teleported collider.
+type TeleportedContact = (Point<Real>, ContactData);
+
+/// Moves the contact manifolds of a pair along with its teleported collider.
+///
+/// The contacts are anchored to the first collider if it was teleported, and to the second one
+/// otherwise. The points and normals of the other collider are moved by the teleport motion of
+/// the anchor relative to its own, as if it had been teleported along with the anchor. Returns all
+/// the contacts, with their points expressed in the local-space of the anchor.
+/// The work done by the narrow-phase while computing contacts, shared by all its threads.
+#[derive(Default)]
+pub(crate) struct NarrowPhaseWorkload {
+    updated_pairs: AtomicUsize,
+    #[cfg(feature = "profiler")]
+    max_pair_time_ns: AtomicU64,
+    #[cfg(feature = "profiler")]
+    total_pair_time_ns: AtomicU64,
+}
+
+impl NarrowPhaseWorkload {
+    /// Records the start of the contact computation of a pair, returning its start time.
+    fn pair_started(&self) -> f64 {
+        let _ = self.updated_pairs.fetch_add(1, Ordering::Relaxed);
+
+        Self::now()
+    }
+
+    #[cfg(feature = "profiler")]
+    fn now() -> f64 {
+        instant::now()
+    }
+
+    #[cfg(not(feature = "profiler"))]
+    fn now() -> f64 {
+        0.0
+    }
+
+    /// Records the end of the contact computation of a pair started at `start`.
+    fn pair_finished(&self, start: f64) {
+        #[cfg(feature = "profiler")]
+        {
+            let time_ns = ((Self::now() - start) * 1.0e6) as u64;
+            let _ = self.max_pair_time_ns.fetch_max(time_ns, Ordering::Relaxed);
+            let _ = self
+                .total_pair_time_ns
+                .fetch_add(time_ns, Ordering::Relaxed);
+        }
+        #[cfg(not(feature = "profiler"))]
+        let _ = start;
+    }
+
+    /// Stores this workload into the collision-detection counters.
+    pub fn record(self, counters: &mut CollisionDetectionCounters) {
+        counters.narrow_phase_updated_pairs = self.updated_pairs.into_inner();
+        #[cfg(feature = "profiler")]
+        {
+            counters.narrow_phase_max_pair_time =
+                self.max_pair_time_ns.into_inner() as f64 * 1.0e-6;
+            counters.narrow_phase_total_pair_time =
+                self.total_pair_time_ns.into_inner() as f64 * 1.0e-6;
+        }
+    }
+}
+
+/// Is one of the colliders a heightfield that never moves, and the other not a heightfield?
+fn is_fixed_heightfield_pair(
+    co1: &Collider,
+    co2: &Collider,
+    rb_type1: RigidBodyType,
+    rb_type2: RigidBodyType,
+) -> bool {
+    let is_heightfield1 = co1.shape.shape_type() == ShapeType::HeightField;
+    let is_heightfield2 = co2.shape.shape_type() == ShapeType::HeightField;
+
+    (is_heightfield1 && !is_heightfield2 && rb_type1.is_fixed())
+        || (is_heightfield2 && !is_heightfield1 && rb_type2.is_fixed())
+}
+
+/// An upper bound of the distance travelled by any point of the collider moving relative to the
+/// fixed heightfield since their relative position was `cached_pos12`.
+fn heightfield_relative_motion(
+    co1: &Collider,
+    co2: &Collider,
+    cached_pos12: &Isometry<Real>,
+    pos12: &Isometry<Real>,
+) -> Real {
+    let (moving_co, motion) = if co1.shape.shape_type() == ShapeType::HeightField {
+        (co2, cached_pos12.inv_mul(pos12))
+    } else {
+        (co1, cached_pos12 * pos12.inverse())
+    };
+    let local_aabb = moving_co.shape.compute_local_aabb();
+    let radius = local_aabb
+        .mins
+        .coords
+        .abs()
+        .sup(&local_aabb.maxs.coords.abs())
+        .norm();
+
+    motion.translation.vector.norm() + motion.rotation.angle().abs() * radius
+}
+
+/// Moves the contacts computed at a previous step along with the colliders now at the relative
+/// position `pos12`.
+///
+/// The contact points on the second shape follow its motion, while the contact points on the
+/// first shape slide along the contact normal, which is assumed to not change.
+fn update_cached_contacts(manifolds: &mut [ContactManifold], pos12: &Isometry<Real>) {
+    for manifold in manifolds {
+        let subshape_pos12 = manifold
+            .subshape_pos1
+            .inv_mul(&manifold.subshape_pos2.prepend_to(pos12));
+        manifold.local_n2 = subshape_pos12.inverse_transform_vector(&-manifold.local_n1);
+
+        for contact in &mut manifold.points {
+            let local_p2 = subshape_pos12 * contact.local_p2;
+            contact.dist = (local_p2 - contact.local_p1).dot(&manifold.local_n1);
+            contact.local_p1 = local_p2 - manifold.local_n1 * contact.dist;
+        }
+    }
+}
+
+fn follow_teleport(
+    manifolds: &mut [ContactManifold],
+    co1: &Collider,
+    co2: &Collider,
+    teleport1: Option<Isometry<Real>>,
+    teleport2: Option<Isometry<Real>>,
+) -> Vec<TeleportedContact> {
+    let anchor_is_first = teleport1.is_some();
+    let (anchor_motion, other_motion, other_pos) = if anchor_is_first {
+        (teleport1, teleport2, co2.position())
+    } else {
+        (teleport2, teleport1, co1.position())
+    };
+    let anchor_motion = anchor_motion.unwrap_or_else(Isometry::identity);
+    let other_motion = other_motion.unwrap_or_else(Isometry::identity);
+    let mut saved = vec![];
+
+    for manifold in manifolds {
+        let (anchor_subshape_pos, other_subshape_pos) = if anchor_is_first {
+            (manifold.subshape_pos1, manifold.subshape_pos2)
+        } else {
+            (manifold.subshape_pos2, manifold.subshape_pos1)
+        };
+        let other_world_pos = other_subshape_pos.prepend_to(other_pos);
+        let old_other_world_pos = other_motion.inverse() * other_world_pos;
+        let shift = other_world_pos.inv_mul(&(anchor_motion * old_other_world_pos));
+
+        if anchor_is_first {
+            manifold.local_n2 = shift * manifold.local_n2;
+        } else {
+            manifold.local_n1 = shift * manifold.local_n1;
+        }
+
+        for contact in &mut manifold.points {
+            let (anchor_pt, other_pt) = if anchor_is_first {
+                (contact.local_p1, &mut contact.local_p2)
+            } else {
+                (contact.local_p2, &mut contact.local_p1)
+            };
+            *other_pt = shift * *other_pt;
+            let anchor_pt = anchor_subshape_pos
+                .map(|pos| pos * anchor_pt)
+                .unwrap_or(anchor_pt);
+            saved.push((anchor_pt, contact.data));
+        }
+    }
+
+    saved
+}
+
+/// Gives the impulses of the contacts saved by [`follow_teleport`] to the new contacts closest to
+/// them in the local-space of the teleported collider, if they are no further than `max_dist`.
+fn restore_teleported_contacts(
+    manifolds: &mut [ContactManifold],
+    mut saved: Vec<TeleportedContact>,
+    anchor_is_first: bool,
+    max_dist: Real,
+) {
+    for manifold in manifolds {
+        let anchor_subshape_pos = if anchor_is_first {
+            manifold.subshape_pos1
+        } else {
+            manifold.subshape_pos2
+        };
+
+        for contact in &mut manifold.points {
+            if saved.is_empty() {
+                return;
+            }
+
+            // This contact was already matched by the narrow-phase and kept its impulses.
+            if contact.data.impulse != 0.0 {
+                continue;
+            }
+
+            let anchor_pt = if anchor_is_first {
+                contact.local_p1
+            } else {
+                contact.local_p2
+            };
+            let anchor_pt = anchor_subshape_pos
+                .map(|pos| pos * anchor_pt)
+                .unwrap_or(anchor_pt);
+            let closest = saved
+                .iter()
+                .enumerate()
+                .map(|(i, (pt, _))| (i, na::distance_squared(pt, &anchor_pt)))
+                .filter(|(_, dist)| *dist <= max_dist * max_dist)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            if let Some((i, _)) = closest {
+                contact.data = saved.swap_remove(i).1;
+            }
+        }
+    }
+}
+
+/// Combines the friction of two colliders if at least one of them has an anisotropic friction.
+///
+/// The friction direction is the one of the first anisotropic collider. Returns the largest of
+/// the combined coefficients, to be used as the solver contacts friction, along with the
+/// anisotropic friction of the manifolds relative to it. Returns the isotropic `friction`
+/// unchanged if neither collider is anisotropic.
+#[cfg(feature = "dim3")]
+fn combine_anisotropic_friction(
+    co1: &Collider,
+    co2: &Collider,
+    friction: Real,
+) -> (Real, Option<ContactAnisotropicFriction>) {
+    let (anisotropic, co_anisotropic, co_other) = match (
+        &co1.material.anisotropic_friction,
+        &co2.material.anisotropic_friction,
+    ) {
+        (Some(anisotropic), _) => (anisotropic, co1, co2),
+        (None, Some(anisotropic)) => (anisotropic, co2, co1),
+        (None, None) => return (friction, None),
+    };
+
+    let (other_along, other_across) = co_other
+        .material
+        .anisotropic_friction
+        .map(|other| (other.along, other.across))
+        .unwrap_or((co_other.material.friction, co_other.material.friction));
+    let combine = |coeff1, coeff2| {
+        CoefficientCombineRule::combine(
+            coeff1,
+            coeff2,
+            co_anisotropic.material.friction_combine_rule as u8,
+            co_other.material.friction_combine_rule as u8,
+        )
+    };
+    let along = combine(anisotropic.along, other_along);
+    let across = combine(anisotropic.across, other_across);
+    let friction = along.max(across);
+    let inv_friction = crate::utils::inv(friction);
+
+    (
+        friction,
+        Some(ContactAnisotropicFriction {
+            direction: co_anisotropic.pos.0.rotation * anisotropic.local_direction.into_inner(),
+            along_scale: along * inv_friction,
+            across_scale: across * inv_friction,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "dim3")]
+    use na::vector;
+
+    use super::NarrowPhase;
+    use crate::dynamics::{
+        CCDSolver, ContactSoftness, ImpulseJointSet, IntegrationParameters, IslandManager,
+        MultibodyJointSet, RigidBodyBuilder, RigidBodySet, RigidBodyType, RigidBodyVelocity,
+    };
+    use crate::geometry::{
+        ActiveCollisionTypes, BroadPhaseMultiSap, ColliderBuilder, ColliderSet, Group,
+        InteractionGroups,
+    };
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{ActiveEvents, ChannelEventCollector, PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn fixed_pair_activated_by_body_type_change() {
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut pipeline = PhysicsPipeline::new();
+        let mut bf = BroadPhaseMultiSap::new();
+        let mut nf = NarrowPhase::new();
+        let mut islands = IslandManager::new();
+        let mut bodies = RigidBodySet::new();
+
+        // Two overlapping fixed bodies.
+        let h1 = bodies.insert(RigidBodyBuilder::fixed());
+        let h2 = bodies.insert(RigidBodyBuilder::fixed());
+        let co1 = colliders.insert_with_parent(ColliderBuilder::ball(1.0), h1, &mut bodies);
+        let co2 = colliders.insert_with_parent(ColliderBuilder::ball(1.0), h2, &mut bodies);
+
+        pipeline.step(
+            &Vector::zeros(),
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut bf,
+            &mut nf,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut CCDSolver::new(),
+            None,
+            &(),
+            &(),
+        );
+
+        // Fixed colliders can’t interact so they don’t have any contact pair.
+        assert!(nf.contact_pair(co1, co2).is_none());
+
+        // Once one of them becomes dynamic, the pair must be taken into account.
+        bodies[h2].set_body_type(RigidBodyType::Dynamic, true);
+        pipeline.step(
+            &Vector::zeros(),
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut bf,
+            &mut nf,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut CCDSolver::new(),
+            None,
+            &(),
+            &(),
+        );
+
+        assert!(nf
+            .contact_pair(co1, co2)
+            .map(|pair| pair.has_any_active_contact)
+            .unwrap_or(false));
+    }
+
+    #[test]
+    fn max_solved_contact_manifolds_keeps_the_deepest() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        // A ball penetrating two fixed balls, the first one more deeply than the second one.
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().max_solved_contact_manifolds(1));
+        let co =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::fixed().translation(Vector::x() * -0.9));
+        let co_deep =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::fixed().translation(Vector::x() * 0.99));
+        let co_shallow =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+        world.step(&mut pipeline, None, &(), &());
+
+        let deep_pair = world.narrow_phase.contact_pair(co, co_deep).unwrap();
+        let shallow_pair = world.narrow_phase.contact_pair(co, co_shallow).unwrap();
+        assert!(shallow_pair.has_any_active_contact);
+        assert!(deep_pair.total_impulse_magnitude() > 0.0);
+        assert_eq!(shallow_pair.total_impulse_magnitude(), 0.0);
+    }
+
+    #[test]
+    fn excluded_pair_has_no_contacts() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let co1 = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let ball = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let co2 =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut world.bodies);
+        world.narrow_phase.exclude_pair(co2, co1);
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The ball falls through the ground.
+        assert!(world.narrow_phase.is_pair_excluded(co1, co2));
+        assert!(!world
+            .narrow_phase
+            .contact_pair(co1, co2)
+            .map(|pair| pair.has_any_active_contact)
+            .unwrap_or(false));
+        assert!(world.bodies[ball].translation().y < 0.0);
+    }
+
+    #[test]
+    fn collision_groups_change_clears_contacts_immediately() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let co1 = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let co2 =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+        world.step(&mut pipeline, None, &(), &());
+        assert!(
+            world
+                .narrow_phase
+                .contact_pair(co1, co2)
+                .unwrap()
+                .has_any_active_contact
+        );
+
+        let num_cleared = world.narrow_phase.set_collision_groups(
+            Some(&mut world.islands),
+            &mut world.colliders,
+            &mut world.bodies,
+            co2,
+            InteractionGroups::none(),
+            &(),
+        );
+
+        assert_eq!(num_cleared, 1);
+        assert_eq!(
+            world.colliders[co2].collision_groups(),
+            InteractionGroups::none()
+        );
+        assert!(
+            !world
+                .narrow_phase
+                .contact_pair(co1, co2)
+                .unwrap()
+                .has_any_active_contact
+        );
+    }
+
+    #[test]
+    fn moving_body_through_many_sensors() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Overlapping trigger volumes, so there are many more intersection pairs than
+        // moving colliders.
+        let sensors: Vec<_> = (0..10)
+            .map(|_| {
+                world.colliders.insert(
+                    ColliderBuilder::ball(1.0)
+                        .sensor(true)
+                        .active_collision_types(ActiveCollisionTypes::all()),
+                )
+            })
+            .collect();
+        let body = world.bodies.insert(
+            RigidBodyBuilder::kinematic_velocity_based()
+                .translation(Vector::x() * -3.0)
+                .linvel(Vector::x() * 6.0),
+        );
+        let co =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), body, &mut world.bodies);
+
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        for sensor in &sensors {
+            assert_eq!(
+                world.narrow_phase.intersection_pair(co, *sensor),
+                Some(true)
+            );
+        }
+
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        for sensor in &sensors {
+            assert_ne!(
+                world.narrow_phase.intersection_pair(co, *sensor),
+                Some(true)
+            );
+        }
+    }
+
+    #[test]
+    fn contact_priority_is_the_greatest_collider_priority() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let co1 = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()).contact_priority(-2));
+        // Two balls resting on the ground, the first one having a higher contact priority.
+        let ball1 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let co2 = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5).contact_priority(3),
+            ball1,
+            &mut world.bodies,
+        );
+        let ball2 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 2.0 + Vector::y() * 0.5));
+        let co3 = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            ball2,
+            &mut world.bodies,
+        );
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let pair1 = world.narrow_phase.contact_pair(co1, co2).unwrap();
+        let pair2 = world.narrow_phase.contact_pair(co1, co3).unwrap();
+        assert_eq!(pair1.manifolds[0].data.priority, 3);
+        assert_eq!(pair2.manifolds[0].data.priority, 0);
+        // Solving the contacts out of their default order doesn’t prevent them from resting.
+        assert!((world.bodies[ball1].translation().y - 0.5).abs() < 1.0e-2);
+        assert!((world.bodies[ball2].translation().y - 0.5).abs() < 1.0e-2);
+    }
+
+    #[test]
+    fn soft_colliders_penetrate_deeper() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let softness = ContactSoftness {
+            natural_frequency: 2.0,
+            damping_ratio: 1.0,
+            compliance: 0.0,
+        };
+        let ground = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        // Two balls resting on the ground, only the first one being soft.
+        let ball1 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let co1 = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5).contact_softness(softness),
+            ball1,
+            &mut world.bodies,
+        );
+        let ball2 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 2.0 + Vector::y() * 0.5));
+        let co2 = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            ball2,
+            &mut world.bodies,
+        );
+        for _ in 0..120 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let pair1 = world.narrow_phase.contact_pair(ground, co1).unwrap();
+        let pair2 = world.narrow_phase.contact_pair(ground, co2).unwrap();
+        assert_eq!(pair1.manifolds[0].data.contact_softness, Some(softness));
+        assert_eq!(pair2.manifolds[0].data.contact_softness, None);
+        let (y1, y2) = (
+            world.bodies[ball1].translation().y,
+            world.bodies[ball2].translation().y,
+        );
+        assert!(y1 < y2 - 1.0e-2);
+    }
+
+    #[test]
+    fn warmstart_impulses_can_be_saved_and_restored() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let ground = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        #[cfg(feature = "dim2")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5, 0.5);
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ = world
+            .colliders
+            .insert_with_parent(cube, body, &mut world.bodies);
+        for _ in 0..20 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let nf = &mut world.narrow_phase;
+        let saved: Vec<_> = nf.warmstart_impulses().collect();
+        assert!(saved.iter().any(|impulse| impulse.impulse > 0.0));
+
+        nf.reset_warmstart_impulses_with(ground);
+        assert!(nf
+            .warmstart_impulses()
+            .all(|impulse| impulse.impulse == 0.0));
+
+        assert_eq!(
+            nf.set_warmstart_impulses(saved.iter().copied()),
+            saved.len()
+        );
+        assert_eq!(nf.warmstart_impulses().collect::<Vec<_>>(), saved);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn swept_colliders_detect_tunneling_sensors() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+        let (collision_send, collision_recv) = crossbeam::channel::unbounded();
+        let (force_send, _force_recv) = crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(collision_send, force_send);
+
+        // A thin trigger plane crossed by two fast projectiles within a single step.
+        let trigger = world.colliders.insert(
+            ColliderBuilder::cuboid(0.01, 10.0, 10.0)
+                .sensor(true)
+                .active_events(ActiveEvents::COLLISION_EVENTS),
+        );
+        let mut insert_projectile = |z: Real, swept: bool| {
+            let body = world.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(vector![-1.0, 0.0, z])
+                    .linvel(vector![100.0, 0.0, 0.0]),
+            );
+            let collider = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.1).swept(swept),
+                body,
+                &mut world.bodies,
+            );
+            collider
+        };
+        let swept = insert_projectile(-2.0, true);
+        let _not_swept = insert_projectile(2.0, false);
+
+        for _ in 0..5 {
+            world.step(&mut pipeline, None, &(), &events);
+        }
+
+        let events: Vec<_> = collision_recv.try_iter().collect();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].started() && events[1].stopped());
+
+        for event in events {
+            assert!(event.sensor());
+            let pair = [event.collider1(), event.collider2()];
+            assert!(pair.contains(&trigger) && pair.contains(&swept));
+        }
+        assert!(!world.bodies.iter().any(|(_, rb)| rb.linvel().x < 99.0));
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn heightfield_contacts_are_cached_for_resting_bodies() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let heights = na::DMatrix::zeros(10, 10);
+        let terrain = world.colliders.insert(ColliderBuilder::heightfield(
+            heights,
+            vector![20.0, 1.0, 20.0],
+        ));
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![0.3, 0.6, -0.7])
+                .can_sleep(false),
+        );
+        let cube = world.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+            body,
+            &mut world.bodies,
+        );
+        world
+            .integration_parameters
+            .normalized_heightfield_contact_cache_distance = 0.05;
+
+        for _ in 0..101 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        let cached_pos12 = world
+            .narrow_phase
+            .contact_pair(terrain, cube)
+            .unwrap()
+            .heightfield_cache_pos12;
+        for _ in 0..19 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // Once at rest, the contacts of the cube are no longer queried.
+        let pair = world.narrow_phase.contact_pair(terrain, cube).unwrap();
+        assert!(cached_pos12.is_some());
+        assert_eq!(pair.heightfield_cache_pos12, cached_pos12);
+        assert!(pair.has_any_active_contact);
+        assert!((world.bodies[body].translation().y - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn narrow_phase_counts_updated_pairs() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        for x in [0.0, 2.0] {
+            let body = world.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(Vector::x() * x + Vector::y() * 0.5)
+                    .can_sleep(false),
+            );
+            let _ = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5),
+                body,
+                &mut world.bodies,
+            );
+        }
+
+        world
+            .integration_parameters
+            .parallel_narrow_phase_chunk_size = std::num::NonZeroUsize::new(1);
+        pipeline.counters.enable();
+        for _ in 0..2 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // Only the two pairs between the balls and the ground exist.
+        assert_eq!(pipeline.counters.cd.narrow_phase_updated_pairs, 2);
+    }
+
+    #[test]
+    fn event_filter_restricts_the_pairs_generating_events() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        let (collision_send, collision_recv) = crossbeam::channel::unbounded();
+        let (force_send, force_recv) = crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(collision_send, force_send);
+        let all_events = ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS;
+
+        let ground = world.colliders.insert(
+            ColliderBuilder::halfspace(Vector::y_axis())
+                .collision_groups(InteractionGroups::new(Group::GROUP_1, Group::ALL))
+                .active_events(all_events),
+        );
+        let mut insert_ball = |x: Real, group: Group| {
+            let body = world.bodies.insert(
+                RigidBodyBuilder::dynamic().translation(Vector::x() * x + Vector::y() * 0.6),
+            );
+            let collider = world.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5)
+                    .collision_groups(InteractionGroups::new(group, Group::ALL))
+                    .active_events(all_events),
+                body,
+                &mut world.bodies,
+            );
+            collider
+        };
+        let reported = insert_ball(-2.0, Group::GROUP_2);
+        let _ignored = insert_ball(2.0, Group::GROUP_3);
+
+        // Only the pairs between the ground’s group and the first ball’s group generate events.
+        world
+            .narrow_phase
+            .set_event_filter(Some(InteractionGroups::new(Group::GROUP_1, Group::GROUP_2)));
+
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &events);
+        }
+
+        let collision_events: Vec<_> = collision_recv.try_iter().collect();
+        assert_eq!(collision_events.len(), 1);
+        let pair = [
+            collision_events[0].collider1(),
+            collision_events[0].collider2(),
+        ];
+        assert!(pair.contains(&ground) && pair.contains(&reported));
+
+        let force_events: Vec<_> = force_recv.try_iter().collect();
+        assert!(!force_events.is_empty());
+        for event in force_events {
+            let pair = [event.collider1, event.collider2];
+            assert!(pair.contains(&ground) && pair.contains(&reported));
+        }
+    }
+
+    #[test]
+    fn surface_velocity_drags_the_bodies_along_a_conveyor() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        // A fixed conveyor belt moving its surface along +X.
+        #[cfg(feature = "dim2")]
+        let (belt, crate_shape) = (
+            ColliderBuilder::cuboid(100.0, 0.5),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (belt, crate_shape) = (
+            ColliderBuilder::cuboid(100.0, 0.5, 10.0),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let belt_speed = 2.0;
+        let belt =
+            belt.surface_velocity(RigidBodyVelocity::new(Vector::x() * belt_speed, na::zero()));
+        let belt_handle = world.colliders.insert(belt);
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y()));
+        let _ = world
+            .colliders
+            .insert_with_parent(crate_shape, body, &mut world.bodies);
+        for _ in 0..120 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let linvel = *world.bodies[body].linvel();
+        assert!((linvel.x - belt_speed).abs() < 1.0e-2, "linvel: {linvel}");
+        assert!(world.colliders[belt_handle].translation().x == 0.0);
+    }
+
+    #[test]
+    fn contact_ids_persist_across_steps() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        #[cfg(feature = "dim2")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5, 0.5);
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ = world
+            .colliders
+            .insert_with_parent(cube, body, &mut world.bodies);
+
+        let contact_ids = |nf: &NarrowPhase| {
+            let mut ids: Vec<_> = nf
+                .contact_pairs()
+                .flat_map(|pair| pair.manifolds.iter())
+                .flat_map(|manifold| manifold.points.iter().map(|pt| pt.data.id))
+                .collect();
+            ids.sort_unstable();
+            ids
+        };
+
+        for _ in 0..11 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+        let ids_after_landing = contact_ids(&world.narrow_phase);
+        for _ in 0..49 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        assert!(!ids_after_landing.is_empty());
+        assert!(!ids_after_landing.contains(&0));
+        assert!(ids_after_landing.windows(2).all(|w| w[0] != w[1]));
+        assert_eq!(contact_ids(&world.narrow_phase), ids_after_landing);
+    }
+
+    #[test]
+    fn fixed_pair_activated_by_active_collision_types_change() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+
+        // Two overlapping fixed colliders.
+        let co1 = world.colliders.insert(ColliderBuilder::ball(1.0));
+        let co2 = world.colliders.insert(ColliderBuilder::ball(1.0));
+        world.step(&mut pipeline, None, &(), &());
+        assert!(world.narrow_phase.contact_pair(co1, co2).is_none());
+
+        // Enabling the fixed-fixed collisions must take the pair into account.
+        world.colliders[co2].set_active_collision_types(
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::FIXED_FIXED,
+        );
+        world.step(&mut pipeline, None, &(), &());
+
+        assert!(world
+            .narrow_phase
+            .contact_pair(co1, co2)
+            .map(|pair| pair.has_any_active_contact)
+            .unwrap_or(false));
     }
 }
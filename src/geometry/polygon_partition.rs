@@ -0,0 +1,191 @@
+//! Decomposition of simple 2D polygons into convex parts.
+
+use crate::math::{Point, Real};
+use na::RealField;
+
+/// Tolerance used to decide if three consecutive vertices are collinear.
+fn collinearity_epsilon() -> Real {
+    Real::default_epsilon() * 100.0
+}
+
+fn orientation(a: &Point<Real>, b: &Point<Real>, c: &Point<Real>) -> Real {
+    (b - a).perp(&(c - b))
+}
+
+fn signed_area(points: &[Point<Real>]) -> Real {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let j = (i + 1) % points.len();
+        area += points[i].coords.perp(&points[j].coords);
+    }
+    area / 2.0
+}
+
+fn is_inside_triangle(pt: &Point<Real>, a: &Point<Real>, b: &Point<Real>, c: &Point<Real>) -> bool {
+    orientation(a, b, pt) >= 0.0 && orientation(b, c, pt) >= 0.0 && orientation(c, a, pt) >= 0.0
+}
+
+/// Triangulates a simple polygon (without holes or self-intersections) using ear clipping.
+///
+/// The polygon can be given either in clockwise or counterclockwise order. The returned
+/// triangles are counterclockwise and index the input `points`.
+pub fn triangulate_polygon(points: &[Point<Real>]) -> Vec<[u32; 3]> {
+    let mut remaining: Vec<u32> = (0..points.len() as u32).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let mut ear = None;
+
+        for i in 0..n {
+            let (ia, ib, ic) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            let (a, b, c) = (
+                &points[ia as usize],
+                &points[ib as usize],
+                &points[ic as usize],
+            );
+
+            if orientation(a, b, c) <= collinearity_epsilon() {
+                // Reflex or flat vertex: not an ear.
+                continue;
+            }
+
+            let contains_other_vertex = remaining.iter().any(|&k| {
+                k != ia && k != ib && k != ic && is_inside_triangle(&points[k as usize], a, b, c)
+            });
+
+            if !contains_other_vertex {
+                ear = Some(i);
+                break;
+            }
+        }
+
+        // If no ear was found, the polygon is degenerate (flat or self-intersecting):
+        // remove a vertex anyway to guarantee termination.
+        let i = ear.unwrap_or(0);
+        let (ia, ib, ic) = (
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        );
+
+        if ear.is_some() {
+            triangles.push([ia, ib, ic]);
+        }
+
+        let _ = remaining.remove(i);
+    }
+
+    if remaining.len() == 3 {
+        let (a, b, c) = (
+            &points[remaining[0] as usize],
+            &points[remaining[1] as usize],
+            &points[remaining[2] as usize],
+        );
+        if orientation(a, b, c) > collinearity_epsilon() {
+            triangles.push([remaining[0], remaining[1], remaining[2]]);
+        }
+    }
+
+    triangles
+}
+
+fn is_convex(points: &[Point<Real>], polygon: &[u32]) -> bool {
+    let n = polygon.len();
+    (0..n).all(|i| {
+        let a = &points[polygon[(i + n - 1) % n] as usize];
+        let b = &points[polygon[i] as usize];
+        let c = &points[polygon[(i + 1) % n] as usize];
+        orientation(a, b, c) >= -collinearity_epsilon()
+    })
+}
+
+/// Attempts to merge two counterclockwise polygons sharing an edge.
+fn merge_along_shared_edge(poly1: &[u32], poly2: &[u32]) -> Option<Vec<u32>> {
+    let n1 = poly1.len();
+    let n2 = poly2.len();
+
+    for i in 0..n1 {
+        let (u, v) = (poly1[i], poly1[(i + 1) % n1]);
+        // Because both polygons are counterclockwise, the shared edge is oriented
+        // the other way in the second polygon.
+        if let Some(j) = (0..n2).find(|&j| poly2[j] == v && poly2[(j + 1) % n2] == u) {
+            let mut merged = Vec::with_capacity(n1 + n2 - 2);
+            // poly1 from `v` up to, but excluding, `u`.
+            merged.extend((0..n1 - 1).map(|k| poly1[(i + 1 + k) % n1]));
+            // poly2 from `u` up to, but excluding, `v`.
+            merged.extend((0..n2 - 1).map(|k| poly2[(j + 1 + k) % n2]));
+            return Some(merged);
+        }
+    }
+
+    None
+}
+
+/// Decomposes a simple polygon (without holes or self-intersections) into convex polygons.
+///
+/// The polygon is first triangulated by ear clipping. Then, adjacent parts are merged
+/// as long as the result remains convex (Hertel-Mehlhorn algorithm). This yields at most
+/// four times the minimum number of convex parts.
+///
+/// The polygon can be given either in clockwise or counterclockwise order. Each returned
+/// convex polygon is counterclockwise and indexes the input `points`.
+pub fn convex_partition_polygon(points: &[Point<Real>]) -> Vec<Vec<u32>> {
+    let mut parts: Vec<Vec<u32>> = triangulate_polygon(points)
+        .into_iter()
+        .map(|tri| tri.to_vec())
+        .collect();
+
+    let mut merged_any = true;
+    while merged_any {
+        merged_any = false;
+
+        'outer: for i in 0..parts.len() {
+            for j in i + 1..parts.len() {
+                if let Some(merged) = merge_along_shared_edge(&parts[i], &parts[j]) {
+                    if is_convex(points, &merged) {
+                        parts[i] = merged;
+                        let _ = parts.swap_remove(j);
+                        merged_any = true;
+                        break 'outer;
+                    }
+                }
+            }
+        }
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod test {
+    use super::{convex_partition_polygon, triangulate_polygon};
+    use crate::math::Point;
+
+    #[test]
+    fn partition_concave_polygon() {
+        // An L-shaped polygon, given in clockwise order.
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 2.0),
+            Point::new(1.0, 2.0),
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 1.0),
+            Point::new(2.0, 0.0),
+        ];
+
+        assert_eq!(triangulate_polygon(&points).len(), 4);
+
+        let parts = convex_partition_polygon(&points);
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts.iter().map(|part| part.len()).sum::<usize>(), 8);
+    }
+}
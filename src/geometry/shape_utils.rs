@@ -0,0 +1,151 @@
+//! Helpers shared by the shapes implemented on the Rapier side (instead of parry).
+
+use crate::dynamics::MassProperties;
+use crate::math::{Point, Real, Vector, DIM};
+use na::RealField;
+use parry::bounding_volume::Aabb;
+use parry::shape::{PackedFeatureId, PolygonalFeature, SupportMap};
+
+/// Number of directions sampled when approximating a support-mapped shape by a convex hull.
+#[cfg(feature = "dim2")]
+const NUM_SUPPORT_SAMPLES: usize = 64;
+#[cfg(feature = "dim3")]
+const NUM_SUPPORT_SAMPLES: usize = 256;
+
+/// Samples points on the boundary of a convex shape by evaluating its support map
+/// along a set of directions approximately uniformly distributed on the unit circle/sphere.
+pub(crate) fn sample_support_points(shape: &impl SupportMap) -> Vec<Point<Real>> {
+    (0..NUM_SUPPORT_SAMPLES)
+        .map(|i| shape.local_support_point(&sample_direction(i)))
+        .collect()
+}
+
+#[cfg(feature = "dim2")]
+fn sample_direction(i: usize) -> Vector<Real> {
+    let angle = i as Real * Real::two_pi() / NUM_SUPPORT_SAMPLES as Real;
+    Vector::new(angle.cos(), angle.sin())
+}
+
+#[cfg(feature = "dim3")]
+fn sample_direction(i: usize) -> Vector<Real> {
+    // Fibonacci lattice on the unit sphere.
+    let golden_angle = Real::pi() * (3.0 - (5.0 as Real).sqrt());
+    let y = 1.0 - 2.0 * (i as Real + 0.5) / NUM_SUPPORT_SAMPLES as Real;
+    let r = (1.0 - y * y).max(0.0).sqrt();
+    let phi = golden_angle * i as Real;
+    Vector::new(phi.cos() * r, y, phi.sin() * r)
+}
+
+/// Computes the local AABB of a convex shape by evaluating its support map along each
+/// coordinate axis.
+pub(crate) fn support_map_local_aabb(shape: &impl SupportMap) -> Aabb {
+    let mut mins = Point::origin();
+    let mut maxs = Point::origin();
+
+    for i in 0..DIM {
+        let axis = Vector::ith(i, 1.0);
+        maxs[i] = shape.local_support_point(&axis)[i];
+        mins[i] = shape.local_support_point(&-axis)[i];
+    }
+
+    Aabb::new(mins, maxs)
+}
+
+/// Approximates the mass-properties of a convex support-mapped shape by computing the
+/// mass-properties of the convex hull of points sampled on its boundary.
+pub(crate) fn support_map_mass_properties(
+    shape: &impl SupportMap,
+    density: Real,
+) -> MassProperties {
+    let points = sample_support_points(shape);
+
+    #[cfg(feature = "dim2")]
+    {
+        let hull = parry::transformation::convex_hull(&points);
+        MassProperties::from_convex_polygon(density, &hull)
+    }
+
+    #[cfg(feature = "dim3")]
+    {
+        let (vertices, indices) = parry::transformation::convex_hull(&points);
+        MassProperties::from_convex_polyhedron(density, &vertices, &indices)
+    }
+}
+
+/// Sets `out_feature` to a polygonal feature made of a single vertex.
+///
+/// This is the polygonal feature returned by smooth shapes which don’t have any flat face or
+/// straight edge. The contact manifold computation will then rely on the closest points
+/// between the shapes, like it does for a cone apex.
+pub(crate) fn set_single_vertex_feature(vertex: Point<Real>, out_feature: &mut PolygonalFeature) {
+    out_feature.vertices[0] = vertex;
+    out_feature.vids[0] = PackedFeatureId::vertex(0);
+    out_feature.fid = PackedFeatureId::face(0);
+    out_feature.num_vertices = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::MassProperties;
+    use crate::math::{Point, Real, Vector};
+    use parry::shape::{Ball, Cuboid, PackedFeatureId, PolygonalFeature};
+
+    #[test]
+    fn sampled_support_points_lie_on_the_boundary() {
+        let ball = Ball::new(1.5);
+        let points = super::sample_support_points(&ball);
+        assert_eq!(points.len(), super::NUM_SUPPORT_SAMPLES);
+
+        for pt in &points {
+            approx::assert_relative_eq!(pt.coords.norm(), 1.5, epsilon = 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn support_map_aabb_is_tight() {
+        let half_extents = Vector::repeat(0.5) + Vector::x();
+        let aabb = super::support_map_local_aabb(&Cuboid::new(half_extents));
+        assert_eq!(aabb.mins, Point::from(-half_extents));
+        assert_eq!(aabb.maxs, Point::from(half_extents));
+    }
+
+    #[test]
+    fn support_map_mass_properties_are_exact_for_polytopes() {
+        // The support points of a cuboid are its vertices so their convex hull is exact.
+        let half_extents = Vector::repeat(0.5) + Vector::x();
+        let mprops = super::support_map_mass_properties(&Cuboid::new(half_extents), 2.0);
+        let expected = MassProperties::from_cuboid(2.0, half_extents);
+
+        approx::assert_relative_eq!(mprops.mass(), expected.mass(), max_relative = 1.0e-4);
+        approx::assert_relative_eq!(mprops.local_com, expected.local_com, epsilon = 1.0e-4);
+        // The principal axes of the hull may be permuted, so compare the full inertia tensors.
+        approx::assert_relative_eq!(
+            mprops.reconstruct_inertia_matrix(),
+            expected.reconstruct_inertia_matrix(),
+            epsilon = 1.0e-4,
+            max_relative = 1.0e-4
+        );
+    }
+
+    #[test]
+    fn support_map_mass_properties_approximate_smooth_shapes() {
+        let mprops = super::support_map_mass_properties(&Ball::new(1.0), 1.0);
+        let expected = MassProperties::from_ball(1.0, 1.0);
+        let relative_error: Real = (mprops.mass() - expected.mass()).abs() / expected.mass();
+        // The convex hull of the samples is inscribed in the ball.
+        assert!(mprops.mass() < expected.mass());
+        assert!(relative_error < 5.0e-2);
+    }
+
+    #[test]
+    fn single_vertex_feature() {
+        let mut feature = PolygonalFeature::default();
+        let vertex = Point::origin() + Vector::x();
+        super::set_single_vertex_feature(vertex, &mut feature);
+
+        assert_eq!(feature.num_vertices, 1);
+        assert_eq!(feature.vertices[0], vertex);
+        assert_eq!(feature.vids[0], PackedFeatureId::vertex(0));
+        assert_eq!(feature.fid, PackedFeatureId::face(0));
+    }
+}
@@ -0,0 +1,524 @@
+use crate::dynamics::MassProperties;
+use crate::geometry::shape_utils;
+#[cfg(feature = "dim3")]
+use crate::math::Rotation;
+use crate::math::{Isometry, Point, Real, Vector};
+#[cfg(feature = "dim3")]
+use na::Vector4;
+use na::{RealField, Unit};
+use parry::bounding_volume::{Aabb, BoundingSphere, BoundingVolume};
+use parry::query::details::{
+    local_point_projection_on_support_map, local_ray_intersection_with_support_map_with_params,
+};
+use parry::query::gjk::VoronoiSimplex;
+use parry::query::{PointProjection, PointQuery, Ray, RayCast, RayIntersection};
+use parry::shape::{
+    FeatureId, PolygonalFeature, PolygonalFeatureMap, Segment, Shape, ShapeType, SupportMap,
+    TypedShape,
+};
+
+/// The minimum cosine of the angle between a direction and the normal of a side of a tapered
+/// capsule for the whole side to be the support feature toward that direction.
+const SIDE_FEATURE_MIN_COS: Real = 0.9999;
+
+/// A tapered capsule (sometimes called cone-sphere): the convex hull of two balls with
+/// possibly different radii.
+///
+/// This is a good fit for limbs, tree trunks, or character hitboxes, where a regular
+/// capsule would be too coarse and a compound shape too expensive.
+///
+/// Contacts and time-of-impact computations rely on the support-map of this shape so it
+/// is supported by the default query dispatcher against any other convex shape as well
+/// as composite shapes (trimeshes, heightfields, compounds). Its mass-properties are computed
+/// exactly.
+///
+/// This is a custom shape for parry so, even though this struct implements `Serialize` and
+/// `Deserialize`, the serialization of a collider with a tapered capsule shape with `serde`
+/// isn’t supported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct TaperedCapsule {
+    /// The center of the first ball.
+    pub a: Point<Real>,
+    /// The center of the second ball.
+    pub b: Point<Real>,
+    /// The radius of the ball centered at `a`.
+    pub radius_a: Real,
+    /// The radius of the ball centered at `b`.
+    pub radius_b: Real,
+}
+
+impl TaperedCapsule {
+    /// Creates a new tapered capsule from the centers and radii of its two end balls.
+    pub fn new(a: Point<Real>, b: Point<Real>, radius_a: Real, radius_b: Real) -> Self {
+        Self {
+            a,
+            b,
+            radius_a,
+            radius_b,
+        }
+    }
+
+    /// Creates a new tapered capsule aligned with the `y` axis and centered at the origin.
+    ///
+    /// The ball with radius `radius_bottom` is at `-half_height` and the ball with radius
+    /// `radius_top` is at `+half_height`.
+    pub fn new_y(half_height: Real, radius_bottom: Real, radius_top: Real) -> Self {
+        let mut a = Point::origin();
+        let mut b = Point::origin();
+        a.y = -half_height;
+        b.y = half_height;
+        Self::new(a, b, radius_bottom, radius_top)
+    }
+
+    /// The largest radius of the two end balls.
+    pub fn max_radius(&self) -> Real {
+        self.radius_a.max(self.radius_b)
+    }
+
+    /// The smallest radius of the two end balls.
+    pub fn min_radius(&self) -> Real {
+        self.radius_a.min(self.radius_b)
+    }
+
+    /// Does one of the end balls completely contain the other?
+    ///
+    /// In that case the tapered capsule degenerates into a single ball.
+    pub fn is_degenerate(&self) -> bool {
+        na::distance(&self.a, &self.b) + self.min_radius() <= self.max_radius()
+    }
+
+    /// Computes the tapered capsule resulting from the transformation of `self` by `pos`.
+    pub fn transform_by(&self, pos: &Isometry<Real>) -> Self {
+        Self::new(pos * self.a, pos * self.b, self.radius_a, self.radius_b)
+    }
+
+    /// The sine and cosine locating the points where the sides of this tapered capsule touch
+    /// its end balls.
+    ///
+    /// The sides touch the ball at `a` (resp. `b`) at the points with an offset of
+    /// `radius_a * sin` (resp. `radius_b * sin`) along the axis oriented from `a` to `b`, and
+    /// at a distance of `radius_a * cos` (resp. `radius_b * cos`) from it. This must not be
+    /// called on a degenerate tapered capsule.
+    fn side_sin_cos(&self) -> (Real, Real) {
+        let sin = (self.radius_a - self.radius_b) / na::distance(&self.a, &self.b);
+        (sin, (1.0 - sin * sin).sqrt())
+    }
+
+    /// The end points of the side of this tapered capsule with the outward normal the closest
+    /// to `dir`, if this normal is almost parallel to `dir`.
+    ///
+    /// The sides are the only straight parts of the boundary of the tapered capsule.
+    fn side_toward(&self, dir: &Unit<Vector<Real>>) -> Option<(Point<Real>, Point<Real>)> {
+        if self.is_degenerate() {
+            return None;
+        }
+
+        let axis = (self.b - self.a).normalize();
+        let ortho = Unit::try_new(**dir - axis * axis.dot(dir), Real::default_epsilon())?;
+        let (sin, cos) = self.side_sin_cos();
+        let side_normal = axis * sin + *ortho * cos;
+
+        (side_normal.dot(dir) >= SIDE_FEATURE_MIN_COS).then(|| {
+            (
+                self.a + side_normal * self.radius_a,
+                self.b + side_normal * self.radius_b,
+            )
+        })
+    }
+
+    /// The exact mass-properties of this tapered capsule, assuming it isn’t degenerate.
+    ///
+    /// The tapered capsule is split into the two quadrilaterals between its axis and its sides,
+    /// and the two disk sectors around its ends.
+    #[cfg(feature = "dim2")]
+    fn non_degenerate_mass_properties(&self, density: Real) -> MassProperties {
+        let axis = (self.b - self.a).normalize();
+        let normal = Vector::new(-axis.y, axis.x);
+        let (sin, cos) = self.side_sin_cos();
+        let side_a = |side: Real| self.a + (axis * sin + normal * (cos * side)) * self.radius_a;
+        let side_b = |side: Real| self.b + (axis * sin + normal * (cos * side)) * self.radius_b;
+        // The half-angle of the sector around `b`, that of the sector around `a` being its
+        // supplementary angle.
+        let half_angle_b = sin.acos();
+
+        MassProperties::from_convex_polygon(density, &[self.a, self.b, side_b(1.0), side_a(1.0)])
+            + MassProperties::from_convex_polygon(
+                density,
+                &[self.a, side_a(-1.0), side_b(-1.0), self.b],
+            )
+            + disk_sector_mass_properties(
+                density,
+                self.a,
+                -axis,
+                self.radius_a,
+                Real::pi() - half_angle_b,
+            )
+            + disk_sector_mass_properties(density, self.b, axis, self.radius_b, half_angle_b)
+    }
+
+    /// The exact mass-properties of this tapered capsule, assuming it isn’t degenerate.
+    ///
+    /// The tapered capsule is a solid of revolution made of a ball cap around `a`, a truncated
+    /// cone, and a ball cap around `b`. Its mass-properties are integrated along its axis.
+    #[cfg(feature = "dim3")]
+    fn non_degenerate_mass_properties(&self, density: Real) -> MassProperties {
+        let axis = self.b - self.a;
+        let length = axis.norm();
+        let (r1, r2) = (self.radius_a, self.radius_b);
+        let (sin, cos) = self.side_sin_cos();
+        let cone_start = r1 * sin;
+        let cone_end = length + r2 * sin;
+        let cone_slope = (r2 - r1) * cos / (cone_end - cone_start);
+
+        // The squared radius of the slices of each part, from their offset along the axis.
+        let integrals = integrate_slices(-r1, cone_start, |z| r1 * r1 - z * z)
+            + integrate_slices(cone_start, cone_end, |z| {
+                let radius = r1 * cos + (z - cone_start) * cone_slope;
+                radius * radius
+            })
+            + integrate_slices(cone_end, length + r2, |z| {
+                r2 * r2 - (z - length) * (z - length)
+            });
+
+        let volume = integrals[0];
+        let com_offset = integrals[1] / volume;
+        let axial_inertia = integrals[2] * density;
+        let transverse_inertia = (integrals[3] - volume * com_offset * com_offset) * density;
+        let frame =
+            Rotation::rotation_between(&Vector::y(), &axis).unwrap_or_else(Rotation::identity);
+
+        MassProperties::with_principal_inertia_frame(
+            self.a + axis * (com_offset / length),
+            volume * density,
+            Vector::new(transverse_inertia, axial_inertia, transverse_inertia),
+            frame,
+        )
+    }
+
+    /// Computes a uniformly scaled version of this tapered capsule.
+    pub fn scaled(&self, scale: Real) -> Self {
+        Self::new(
+            self.a * scale,
+            self.b * scale,
+            self.radius_a * scale,
+            self.radius_b * scale,
+        )
+    }
+}
+
+/// The mass-properties of the sector of a disk with the given radius and half-angle, centered
+/// at `center` with its bisector along `bisector`.
+#[cfg(feature = "dim2")]
+fn disk_sector_mass_properties(
+    density: Real,
+    center: Point<Real>,
+    bisector: Vector<Real>,
+    radius: Real,
+    half_angle: Real,
+) -> MassProperties {
+    let mass = half_angle * radius * radius * density;
+    let com_distance = 2.0 * radius * half_angle.sin() / (3.0 * half_angle);
+    let inertia = mass * (radius * radius / 2.0 - com_distance * com_distance);
+    MassProperties::new(center + bisector * com_distance, mass, inertia)
+}
+
+/// Integrates the volume, the first moment along the axis, the axial inertia, and the
+/// transverse inertia (relative to the origin of the axis) of the disk slices of a solid of
+/// revolution between the offsets `start` and `end` along its axis.
+///
+/// The squared radius of the slices must be a polynomial of degree at most 2 of their offset,
+/// e.g., for ball caps and truncated cones, so that the three-points Gauss-Legendre quadrature
+/// used here is exact.
+#[cfg(feature = "dim3")]
+fn integrate_slices(start: Real, end: Real, sq_radius: impl Fn(Real) -> Real) -> Vector4<Real> {
+    let node = (0.6 as Real).sqrt();
+    let half_length = (end - start) / 2.0;
+    let middle = (start + end) / 2.0;
+
+    [(-node, 5.0 / 9.0), (0.0, 8.0 / 9.0), (node, 5.0 / 9.0)]
+        .iter()
+        .map(|(x, weight)| {
+            let z = middle + half_length * x;
+            let sq = sq_radius(z);
+            Vector4::new(sq, sq * z, sq * sq / 2.0, sq * sq / 4.0 + sq * z * z)
+                * (weight * half_length)
+        })
+        .sum::<Vector4<Real>>()
+        * Real::pi()
+}
+
+impl SupportMap for TaperedCapsule {
+    fn local_support_point(&self, dir: &Vector<Real>) -> Point<Real> {
+        if let Some(dir) = Unit::try_new(*dir, Real::default_epsilon()) {
+            self.local_support_point_toward(&dir)
+        } else {
+            self.a
+        }
+    }
+
+    fn local_support_point_toward(&self, dir: &Unit<Vector<Real>>) -> Point<Real> {
+        let support_a = self.a.coords.dot(dir) + self.radius_a;
+        let support_b = self.b.coords.dot(dir) + self.radius_b;
+
+        if support_a >= support_b {
+            self.a + **dir * self.radius_a
+        } else {
+            self.b + **dir * self.radius_b
+        }
+    }
+}
+
+impl PolygonalFeatureMap for TaperedCapsule {
+    fn local_support_feature(&self, dir: &Unit<Vector<Real>>, out_feature: &mut PolygonalFeature) {
+        // Lying on a side, the tapered capsule needs a contact manifold along that whole side,
+        // like a regular capsule, to be stable.
+        if let Some((side_a, side_b)) = self.side_toward(dir) {
+            *out_feature = PolygonalFeature::from(Segment::new(side_a, side_b));
+        } else {
+            let support_pt = self.local_support_point_toward(dir);
+            shape_utils::set_single_vertex_feature(support_pt, out_feature);
+        }
+    }
+}
+
+impl RayCast for TaperedCapsule {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        local_ray_intersection_with_support_map_with_params(
+            self,
+            &mut VoronoiSimplex::new(),
+            ray,
+            max_time_of_impact,
+            solid,
+        )
+    }
+}
+
+impl PointQuery for TaperedCapsule {
+    fn project_local_point(&self, pt: &Point<Real>, solid: bool) -> PointProjection {
+        local_point_projection_on_support_map(self, &mut VoronoiSimplex::new(), pt, solid)
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+impl Shape for TaperedCapsule {
+    fn compute_local_aabb(&self) -> Aabb {
+        let aabb_a = Aabb::new(
+            self.a - Vector::repeat(self.radius_a),
+            self.a + Vector::repeat(self.radius_a),
+        );
+        let aabb_b = Aabb::new(
+            self.b - Vector::repeat(self.radius_b),
+            self.b + Vector::repeat(self.radius_b),
+        );
+        aabb_a.merged(&aabb_b)
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        let sphere_a = BoundingSphere::new(self.a, self.radius_a);
+        let sphere_b = BoundingSphere::new(self.b, self.radius_b);
+        sphere_a.merged(&sphere_b)
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(*self)
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, _num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        // Only uniform scaling preserves the tapered capsule shape.
+        if scale.iter().all(|s| *s == scale[0]) && scale[0] >= 0.0 {
+            Some(Box::new(self.scaled(scale[0])))
+        } else {
+            None
+        }
+    }
+
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        if self.is_degenerate() {
+            // The tapered capsule is just its largest end ball.
+            let center = if self.radius_a >= self.radius_b {
+                self.a
+            } else {
+                self.b
+            };
+            MassProperties {
+                local_com: center,
+                ..MassProperties::from_ball(density, self.max_radius())
+            }
+        } else {
+            self.non_degenerate_mass_properties(density)
+        }
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.min_radius()
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        Real::frac_pi_2()
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self as &dyn SupportMap)
+    }
+
+    fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, Real)> {
+        Some((self as &dyn PolygonalFeatureMap, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TaperedCapsule;
+    use crate::dynamics::MassProperties;
+    use crate::geometry::shape_utils;
+    use crate::math::{Isometry, Point, Real, Translation, Vector};
+    use na::Unit;
+    use parry::shape::{Cuboid, PolygonalFeature, PolygonalFeatureMap, Shape};
+
+    fn assert_mass_properties_eq(mprops: &MassProperties, expected: &MassProperties, eps: Real) {
+        assert!((mprops.mass() - expected.mass()).abs() < expected.mass() * eps);
+        assert!(na::distance(&mprops.local_com, &expected.local_com) < eps);
+        #[cfg(feature = "dim2")]
+        assert!(
+            (mprops.principal_inertia() - expected.principal_inertia()).abs()
+                < expected.principal_inertia() * eps
+        );
+        #[cfg(feature = "dim3")]
+        assert!(
+            (mprops.reconstruct_inertia_matrix() - expected.reconstruct_inertia_matrix()).norm()
+                < expected.reconstruct_inertia_matrix().norm() * eps
+        );
+    }
+
+    #[test]
+    fn equal_radii_mass_properties_match_capsule() {
+        let a = Point::from(Vector::repeat(-0.5));
+        let b = Point::from(Vector::repeat(1.0));
+        let capsule = TaperedCapsule::new(a, b, 0.3, 0.3);
+        let expected = MassProperties::from_capsule(2.0, a, b, 0.3);
+        assert_mass_properties_eq(&capsule.mass_properties(2.0), &expected, 1.0e-4);
+    }
+
+    #[cfg(feature = "dim3")]
+    #[test]
+    fn mass_matches_analytic_volume() {
+        let (length, r1, r2) = (2.0, 0.5, 0.25);
+        let capsule = TaperedCapsule::new_y(length / 2.0, r1, r2);
+
+        // The volume of the two ball caps and of the truncated cone between them.
+        let sin: Real = (r1 - r2) / length;
+        let sq_cos = 1.0 - sin * sin;
+        let cap = |radius: Real, height: Real| {
+            Real::pi() * height * height * (3.0 * radius - height) / 3.0
+        };
+        let cone = Real::pi() / 3.0 * length * sq_cos * sq_cos * (r1 * r1 + r1 * r2 + r2 * r2);
+        let volume = cap(r1, r1 * (1.0 + sin)) + cap(r2, r2 * (1.0 - sin)) + cone;
+
+        let mprops = capsule.mass_properties(1.0);
+        assert!((mprops.mass() - volume).abs() < volume * 1.0e-4);
+        // The center of mass is shifted toward the largest ball.
+        assert!(mprops.local_com.y < 0.0);
+    }
+
+    #[test]
+    fn mass_properties_match_sampled_hull() {
+        let capsule = TaperedCapsule::new(
+            Point::origin(),
+            Point::from(Vector::x() * 2.0 + Vector::y()),
+            0.5,
+            0.2,
+        );
+        let sampled = shape_utils::support_map_mass_properties(&capsule, 1.0);
+        assert_mass_properties_eq(&capsule.mass_properties(1.0), &sampled, 5.0e-2);
+    }
+
+    #[test]
+    fn degenerate_tapered_capsule_is_its_largest_ball() {
+        let b = Point::from(Vector::x() * 0.2);
+        let capsule = TaperedCapsule::new(Point::origin(), b, 0.25, 1.0);
+        assert!(capsule.is_degenerate());
+
+        let expected = MassProperties {
+            local_com: b,
+            ..MassProperties::from_ball(1.0, 1.0)
+        };
+        assert_mass_properties_eq(&capsule.mass_properties(1.0), &expected, 1.0e-5);
+    }
+
+    #[test]
+    fn lying_tapered_capsule_touches_ground_with_its_largest_ball() {
+        let capsule = TaperedCapsule::new(
+            Point::from(-Vector::x()),
+            Point::from(Vector::x()),
+            0.5,
+            0.25,
+        );
+        let mut half_extents = Vector::repeat(10.0);
+        half_extents.y = 0.1;
+        let ground = Cuboid::new(half_extents);
+        // The top of the ground is 0.1 above the bottom of the largest ball.
+        let ground_pos = Isometry::from(Translation::from(Vector::y() * -0.5));
+
+        let contact =
+            parry::query::contact(&Isometry::identity(), &capsule, &ground_pos, &ground, 0.0)
+                .unwrap()
+                .unwrap();
+
+        assert!((contact.dist + 0.1).abs() < 1.0e-3);
+        assert!((contact.normal1.into_inner() + Vector::y()).norm() < 1.0e-3);
+        assert!(
+            na::distance(
+                &contact.point1,
+                &Point::from(-Vector::x() - Vector::y() * 0.5)
+            ) < 1.0e-3
+        );
+    }
+
+    #[test]
+    fn support_feature_is_the_side_facing_the_direction() {
+        let capsule = TaperedCapsule::new(
+            Point::from(-Vector::x()),
+            Point::from(Vector::x()),
+            0.5,
+            0.25,
+        );
+        let (sin, cos) = capsule.side_sin_cos();
+        let mut feature = PolygonalFeature::default();
+
+        // The direction normal to the bottom side of the tapered capsule gives the whole side.
+        let side_normal = Unit::new_normalize(Vector::x() * sin - Vector::y() * cos);
+        capsule.local_support_feature(&side_normal, &mut feature);
+        assert_eq!(feature.num_vertices, 2);
+        assert!(na::distance(&feature.vertices[0], &(capsule.a + *side_normal * 0.5)) < 1.0e-5);
+        assert!(na::distance(&feature.vertices[1], &(capsule.b + *side_normal * 0.25)) < 1.0e-5);
+
+        // Any other direction gives a single point, e.g., the bottom of the largest ball.
+        capsule.local_support_feature(&-Vector::y_axis(), &mut feature);
+        assert_eq!(feature.num_vertices, 1);
+        assert!(na::distance(&feature.vertices[0], &(capsule.a - Vector::y() * 0.5)) < 1.0e-5);
+    }
+}
@@ -0,0 +1,673 @@
+use crate::dynamics::MassProperties;
+use crate::geometry::shape_utils;
+use crate::math::{Isometry, Point, Real, Vector};
+use na::{RealField, Unit};
+use parry::bounding_volume::{Aabb, BoundingSphere};
+use parry::query::details::local_point_projection_on_support_map;
+use parry::query::gjk::VoronoiSimplex;
+use parry::query::{PointProjection, PointQuery, Ray, RayCast, RayIntersection};
+use parry::shape::{
+    Compound, FeatureId, PolygonalFeature, PolygonalFeatureMap, Shape, ShapeType, SharedShape,
+    SimdCompositeShape, SupportMap, TypedShape,
+};
+
+/// A torus (or an annulus in 2D) centered at the origin.
+///
+/// In 3D the torus lies on the `xz` plane, i.e., its axis of revolution is the `y` axis.
+///
+/// Point projections are computed exactly from the signed distance function of the torus, and
+/// ray-casts by solving the quartic equation of its boundary. For contact generation, the torus
+/// is seen as a composite shape made of convex [`TorusSection`]s. Each section is a slice of the
+/// torus between two angles so the contact manifolds only deviate from the exact torus by a
+/// small amount on its inner side (which decreases with the number of subdivisions).
+#[derive(Clone)]
+pub struct Torus {
+    major_radius: Real,
+    minor_radius: Real,
+    num_subdivisions: u32,
+    sections: Compound,
+}
+
+impl Torus {
+    /// The default number of sections the torus is split into for contact generation.
+    pub const DEFAULT_SUBDIVISIONS: u32 = 32;
+
+    /// Creates a new torus from its major radius (distance from the center of the torus to the
+    /// center of its tube) and its minor radius (radius of the tube).
+    ///
+    /// The minor radius must be smaller than the major radius.
+    pub fn new(major_radius: Real, minor_radius: Real) -> Self {
+        Self::with_subdivisions(major_radius, minor_radius, Self::DEFAULT_SUBDIVISIONS)
+    }
+
+    /// Creates a new torus split into `num_subdivisions` sections for contact generation.
+    ///
+    /// The minor radius must be smaller than the major radius.
+    pub fn with_subdivisions(
+        major_radius: Real,
+        minor_radius: Real,
+        num_subdivisions: u32,
+    ) -> Self {
+        assert!(
+            minor_radius < major_radius,
+            "The minor radius of a torus must be smaller than its major radius."
+        );
+        let num_subdivisions = num_subdivisions.max(3);
+        let half_angle = Real::pi() / num_subdivisions as Real;
+        let section = SharedShape::new(TorusSection::new(major_radius, minor_radius, half_angle));
+
+        let parts = (0..num_subdivisions)
+            .map(|i| {
+                let angle = 2.0 * half_angle * i as Real;
+                #[cfg(feature = "dim2")]
+                let pos = Isometry::rotation(angle);
+                #[cfg(feature = "dim3")]
+                let pos = Isometry::rotation(Vector::y() * angle);
+                (pos, section.clone())
+            })
+            .collect();
+
+        Self {
+            major_radius,
+            minor_radius,
+            num_subdivisions,
+            sections: Compound::new(parts),
+        }
+    }
+
+    /// The distance between the center of the torus and the center of its tube.
+    pub fn major_radius(&self) -> Real {
+        self.major_radius
+    }
+
+    /// The radius of the tube of this torus.
+    pub fn minor_radius(&self) -> Real {
+        self.minor_radius
+    }
+
+    /// The number of sections this torus is split into for contact generation.
+    pub fn num_subdivisions(&self) -> u32 {
+        self.num_subdivisions
+    }
+
+    /// The convex sections this torus is split into for contact generation.
+    pub fn sections(&self) -> &Compound {
+        &self.sections
+    }
+
+    /// The area (in 2D) or volume (in 3D) of this torus.
+    #[cfg(feature = "dim2")]
+    pub fn volume(&self) -> Real {
+        Real::pi() * 4.0 * self.major_radius * self.minor_radius
+    }
+
+    /// The area (in 2D) or volume (in 3D) of this torus.
+    #[cfg(feature = "dim3")]
+    pub fn volume(&self) -> Real {
+        2.0 * Real::pi() * Real::pi() * self.major_radius * self.minor_radius * self.minor_radius
+    }
+
+    /// The point of the central circle of the tube that is the closest to `pt`.
+    fn closest_point_on_spine(&self, pt: &Point<Real>) -> Point<Real> {
+        let mut planar = pt.coords;
+        #[cfg(feature = "dim3")]
+        {
+            planar.y = 0.0;
+        }
+
+        let dir = Unit::try_new(planar, Real::default_epsilon()).unwrap_or_else(Vector::x_axis);
+        Point::from(*dir * self.major_radius)
+    }
+
+    /// The signed distance from the local point `pt` to the boundary of this torus.
+    ///
+    /// This is negative if the point is inside of the torus.
+    pub fn signed_distance_to_local_point(&self, pt: &Point<Real>) -> Real {
+        na::distance(pt, &self.closest_point_on_spine(pt)) - self.minor_radius
+    }
+
+    /// The outward normal of the torus boundary the closest to `pt`.
+    fn normal_at_point(&self, pt: &Point<Real>) -> Unit<Vector<Real>> {
+        let spine_pt = self.closest_point_on_spine(pt);
+        Unit::try_new(pt - spine_pt, Real::default_epsilon())
+            .or_else(|| Unit::try_new(spine_pt.coords, Real::default_epsilon()))
+            .unwrap_or_else(Vector::x_axis)
+    }
+}
+
+impl RayCast for Torus {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        let dir_norm = ray.dir.norm();
+        if dir_norm <= Real::default_epsilon() {
+            return None;
+        }
+
+        let start_dist = self.signed_distance_to_local_point(&ray.origin);
+        if start_dist <= 0.0 && solid {
+            let normal = -ray.dir / dir_norm;
+            return Some(RayIntersection::new(0.0, normal, FeatureId::Unknown));
+        }
+
+        // Restrict the search to the bounding-sphere of the torus.
+        let bounding_radius = self.major_radius + self.minor_radius;
+        let b = ray.origin.coords.dot(&ray.dir);
+        let c = ray.origin.coords.norm_squared() - bounding_radius * bounding_radius;
+        let delta = b * b - dir_norm * dir_norm * c;
+        if delta < 0.0 {
+            return None;
+        }
+
+        let exit_toi = ((-b + delta.sqrt()) / (dir_norm * dir_norm)).min(max_time_of_impact);
+        let enter_toi = ((-b - delta.sqrt()) / (dir_norm * dir_norm)).max(0.0);
+        if enter_toi > exit_toi {
+            return None;
+        }
+
+        // Express the ray from its entry point into the bounding-sphere, with a unit direction,
+        // so the coefficients of the polynomial stay well-conditioned.
+        let dir = ray.dir / dir_norm;
+        let origin = ray.point_at(enter_toi).coords;
+        let (mut planar_dir, mut planar_origin) = (dir, origin);
+        #[cfg(feature = "dim3")]
+        {
+            planar_dir.y = 0.0;
+            planar_origin.y = 0.0;
+        }
+
+        // The boundary of the torus is the set of points `q` such that
+        // `(|q|² + R² - r²)² - 4R²(q.x² + q.z²) = 0` (or `4R²|q|²` for the annulus in 2D).
+        // With `q = origin + s * dir`, this is a quartic equation in `s`.
+        let (r1, r2) = (self.major_radius, self.minor_radius);
+        let b = 2.0 * origin.dot(&dir);
+        let c = origin.norm_squared() + r1 * r1 - r2 * r2;
+        let e = planar_dir.norm_squared();
+        let f = 2.0 * planar_origin.dot(&planar_dir);
+        let g = planar_origin.norm_squared();
+        let k = 4.0 * r1 * r1;
+        let quartic = [
+            c * c - k * g,
+            2.0 * b * c - k * f,
+            b * b + 2.0 * c - k * e,
+            2.0 * b,
+            1.0,
+        ];
+
+        // The gradient of the quartic has a norm of `8Rρr` on the boundary, where `ρ >= R - r`
+        // is the distance to the axis, so this matches a distance tolerance of `1.0e-4 * r` for
+        // tangent rays.
+        let tolerance = 8.0 * r1 * (r1 - r2) * r2 * r2 * 1.0e-4;
+        let mut roots = [0.0; 4];
+        let max_s = (exit_toi - enter_toi) * dir_norm;
+        if polynomial_roots(&quartic, 0.0, max_s, tolerance, &mut roots) == 0 {
+            return None;
+        }
+
+        let toi = enter_toi + roots[0] / dir_norm;
+        // If the ray starts inside of the (non-solid) torus, this is the exit point.
+        let sign = if start_dist <= 0.0 { -1.0 } else { 1.0 };
+        let normal = self.normal_at_point(&ray.point_at(toi)).into_inner() * sign;
+        Some(RayIntersection::new(toi, normal, FeatureId::Unknown))
+    }
+}
+
+/// Evaluates the polynomial with the given coefficients, ordered by increasing degree.
+fn eval_polynomial(coeffs: &[Real], t: Real) -> Real {
+    coeffs.iter().rev().fold(0.0, |acc, coeff| acc * t + coeff)
+}
+
+/// Computes the roots in `[min, max]`, in increasing order, of the polynomial of degree at most
+/// 4 with the given coefficients, ordered by increasing degree with a non-zero leading one.
+///
+/// The polynomial is monotonic between two consecutive roots of its derivative so each of these
+/// intervals contains at most one root, found by bisection. The extrema where the polynomial is
+/// smaller than `tolerance` in absolute value are reported as (double) roots too. Returns the
+/// number of roots written into `roots`.
+fn polynomial_roots(
+    coeffs: &[Real],
+    min: Real,
+    max: Real,
+    tolerance: Real,
+    roots: &mut [Real; 4],
+) -> usize {
+    let degree = coeffs.len() - 1;
+    if degree == 1 {
+        let root = -coeffs[0] / coeffs[1];
+        roots[0] = root;
+        return usize::from((min..=max).contains(&root));
+    }
+
+    let mut derivative = [0.0; 4];
+    for (i, coeff) in coeffs.iter().enumerate().skip(1) {
+        derivative[i - 1] = coeff * i as Real;
+    }
+    let mut extrema = [0.0; 4];
+    let num_extrema = polynomial_roots(&derivative[..degree], min, max, 0.0, &mut extrema);
+
+    let mut num_roots = 0;
+    let mut lo = min;
+    let mut val_lo = eval_polynomial(coeffs, lo);
+
+    let bounds = extrema[..num_extrema]
+        .iter()
+        .map(|extremum| (*extremum, true))
+        .chain(std::iter::once((max, false)));
+
+    for (hi, is_extremum) in bounds {
+        let val_hi = eval_polynomial(coeffs, hi);
+
+        let root = if val_lo == 0.0 {
+            Some(lo)
+        } else if val_hi == 0.0 {
+            Some(hi)
+        } else if (val_lo < 0.0) != (val_hi < 0.0) {
+            let (mut a, mut b) = (lo, hi);
+            loop {
+                let mid = (a + b) / 2.0;
+                if mid <= a || mid >= b {
+                    break Some(mid);
+                }
+                if (eval_polynomial(coeffs, mid) < 0.0) == (val_lo < 0.0) {
+                    a = mid;
+                } else {
+                    b = mid;
+                }
+            }
+        } else if is_extremum && val_hi.abs() <= tolerance {
+            Some(hi)
+        } else {
+            None
+        };
+
+        if let Some(root) = root {
+            if num_roots == 0 || root > roots[num_roots - 1] {
+                roots[num_roots] = root;
+                num_roots += 1;
+            }
+        }
+
+        lo = hi;
+        val_lo = val_hi;
+    }
+
+    num_roots
+}
+
+impl PointQuery for Torus {
+    fn project_local_point(&self, pt: &Point<Real>, solid: bool) -> PointProjection {
+        let dist = self.signed_distance_to_local_point(pt);
+
+        if dist <= 0.0 && solid {
+            PointProjection::new(true, *pt)
+        } else {
+            let normal = self.normal_at_point(pt);
+            PointProjection::new(dist <= 0.0, pt - *normal * dist)
+        }
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+impl Shape for Torus {
+    fn compute_local_aabb(&self) -> Aabb {
+        let outer_radius = self.major_radius + self.minor_radius;
+        #[cfg(feature = "dim2")]
+        let half_extents = Vector::repeat(outer_radius);
+        #[cfg(feature = "dim3")]
+        let half_extents = Vector::new(outer_radius, self.minor_radius, outer_radius);
+        Aabb::from_half_extents(Point::origin(), half_extents)
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(Point::origin(), self.major_radius + self.minor_radius)
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(self.clone())
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, _num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        // Only uniform scaling preserves the torus shape.
+        if scale.iter().all(|s| *s == scale[0]) && scale[0] > 0.0 {
+            Some(Box::new(Self::with_subdivisions(
+                self.major_radius * scale[0],
+                self.minor_radius * scale[0],
+                self.num_subdivisions,
+            )))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "dim2")]
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let mass = self.volume() * density;
+        let (r1, r2) = (self.major_radius, self.minor_radius);
+        MassProperties::new(Point::origin(), mass, mass * (r1 * r1 + r2 * r2))
+    }
+
+    #[cfg(feature = "dim3")]
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        let mass = self.volume() * density;
+        let (r1, r2) = (self.major_radius, self.minor_radius);
+        let diametral_inertia = mass * (r1 * r1 / 2.0 + r2 * r2 * 5.0 / 8.0);
+        let axial_inertia = mass * (r1 * r1 + r2 * r2 * 3.0 / 4.0);
+        MassProperties::new(
+            Point::origin(),
+            mass,
+            Vector::new(diametral_inertia, axial_inertia, diametral_inertia),
+        )
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.minor_radius
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        Real::pi() / self.num_subdivisions as Real
+    }
+
+    fn as_composite_shape(&self) -> Option<&dyn SimdCompositeShape> {
+        Some(&self.sections as &dyn SimdCompositeShape)
+    }
+}
+
+/// A convex slice of a [`Torus`] (or of an annulus in 2D).
+///
+/// This is the convex hull of the part of the torus between the angles `-half_angle`
+/// and `half_angle` around its axis of revolution, measured from the `x` axis.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct TorusSection {
+    /// The distance between the center of the torus and the center of its tube.
+    pub major_radius: Real,
+    /// The radius of the tube of the torus.
+    pub minor_radius: Real,
+    /// Half of the angle covered by this section.
+    pub half_angle: Real,
+}
+
+impl TorusSection {
+    /// Creates a new torus section.
+    pub fn new(major_radius: Real, minor_radius: Real, half_angle: Real) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+            half_angle,
+        }
+    }
+
+    fn spine_point(&self, angle: Real) -> Point<Real> {
+        let (sin, cos) = angle.sin_cos();
+        #[cfg(feature = "dim2")]
+        return Point::new(cos, sin) * self.major_radius;
+        #[cfg(feature = "dim3")]
+        return Point::new(cos, 0.0, sin) * self.major_radius;
+    }
+}
+
+impl SupportMap for TorusSection {
+    fn local_support_point(&self, dir: &Vector<Real>) -> Point<Real> {
+        if let Some(dir) = Unit::try_new(*dir, Real::default_epsilon()) {
+            self.local_support_point_toward(&dir)
+        } else {
+            self.spine_point(0.0)
+        }
+    }
+
+    fn local_support_point_toward(&self, dir: &Unit<Vector<Real>>) -> Point<Real> {
+        #[cfg(feature = "dim2")]
+        let (dx, dy) = (dir.x, dir.y);
+        #[cfg(feature = "dim3")]
+        let (dx, dy) = (dir.x, dir.z);
+
+        // The point of the arc with the largest projection on `dir` is the one with the
+        // angle closest to the one of `dir`.
+        let angle = if dx * dx + dy * dy > Real::default_epsilon() {
+            dy.atan2(dx).clamp(-self.half_angle, self.half_angle)
+        } else {
+            0.0
+        };
+
+        self.spine_point(angle) + **dir * self.minor_radius
+    }
+}
+
+impl PolygonalFeatureMap for TorusSection {
+    fn local_support_feature(&self, dir: &Unit<Vector<Real>>, out_feature: &mut PolygonalFeature) {
+        shape_utils::set_single_vertex_feature(self.local_support_point_toward(dir), out_feature);
+    }
+}
+
+impl RayCast for TorusSection {
+    fn cast_local_ray_and_get_normal(
+        &self,
+        ray: &Ray,
+        max_time_of_impact: Real,
+        solid: bool,
+    ) -> Option<RayIntersection> {
+        parry::query::details::local_ray_intersection_with_support_map_with_params(
+            self,
+            &mut VoronoiSimplex::new(),
+            ray,
+            max_time_of_impact,
+            solid,
+        )
+    }
+}
+
+impl PointQuery for TorusSection {
+    fn project_local_point(&self, pt: &Point<Real>, solid: bool) -> PointProjection {
+        local_point_projection_on_support_map(self, &mut VoronoiSimplex::new(), pt, solid)
+    }
+
+    fn project_local_point_and_get_feature(
+        &self,
+        pt: &Point<Real>,
+    ) -> (PointProjection, FeatureId) {
+        (self.project_local_point(pt, false), FeatureId::Unknown)
+    }
+}
+
+impl Shape for TorusSection {
+    fn compute_local_aabb(&self) -> Aabb {
+        shape_utils::support_map_local_aabb(self)
+    }
+
+    fn compute_local_bounding_sphere(&self) -> BoundingSphere {
+        self.compute_local_aabb().bounding_sphere()
+    }
+
+    fn clone_box(&self) -> Box<dyn Shape> {
+        Box::new(*self)
+    }
+
+    fn scale_dyn(&self, scale: &Vector<Real>, _num_subdivisions: u32) -> Option<Box<dyn Shape>> {
+        if scale.iter().all(|s| *s == scale[0]) && scale[0] > 0.0 {
+            Some(Box::new(Self::new(
+                self.major_radius * scale[0],
+                self.minor_radius * scale[0],
+                self.half_angle,
+            )))
+        } else {
+            None
+        }
+    }
+
+    fn mass_properties(&self, density: Real) -> MassProperties {
+        shape_utils::support_map_mass_properties(self, density)
+    }
+
+    fn shape_type(&self) -> ShapeType {
+        ShapeType::Custom
+    }
+
+    fn as_typed_shape(&self) -> TypedShape {
+        TypedShape::Custom(self)
+    }
+
+    fn ccd_thickness(&self) -> Real {
+        self.minor_radius
+    }
+
+    fn ccd_angular_thickness(&self) -> Real {
+        self.half_angle
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn as_support_map(&self) -> Option<&dyn SupportMap> {
+        Some(self as &dyn SupportMap)
+    }
+
+    fn as_polygonal_feature_map(&self) -> Option<(&dyn PolygonalFeatureMap, Real)> {
+        Some((self as &dyn PolygonalFeatureMap, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Torus;
+    use crate::math::{Isometry, Point, Real, Translation, Vector};
+    use parry::query::{Ray, RayCast};
+    use parry::shape::{Ball, Shape};
+
+    #[test]
+    fn mass_properties_match_grid_integration() {
+        let torus = Torus::new(1.0, 0.25);
+        let mprops = torus.mass_properties(2.0);
+        assert_eq!(mprops.local_com, Point::origin());
+
+        // Integrate the volume and inertia of the torus over the cells of a grid covering
+        // its Aabb.
+        let aabb = torus.compute_local_aabb();
+        let num_cells = 100;
+        let cell_size = aabb.extents() / num_cells as Real;
+        let cell_volume = cell_size.product();
+        let mut volume = 0.0;
+        let mut inertia = Vector::zeros();
+
+        #[cfg(feature = "dim2")]
+        let cells = (0..num_cells).flat_map(|i| (0..num_cells).map(move |j| Vector::new(i, j)));
+        #[cfg(feature = "dim3")]
+        let cells = (0..num_cells).flat_map(|i| {
+            (0..num_cells).flat_map(move |j| (0..num_cells).map(move |k| Vector::new(i, j, k)))
+        });
+
+        for cell in cells {
+            let pt = aabb.mins + (cell.cast::<Real>().add_scalar(0.5)).component_mul(&cell_size);
+
+            if torus.signed_distance_to_local_point(&pt) <= 0.0 {
+                volume += cell_volume;
+                #[cfg(feature = "dim2")]
+                {
+                    inertia.x += pt.coords.norm_squared() * cell_volume;
+                }
+                #[cfg(feature = "dim3")]
+                {
+                    let sq = pt.coords.component_mul(&pt.coords);
+                    inertia += Vector::new(sq.y + sq.z, sq.x + sq.z, sq.x + sq.y) * cell_volume;
+                }
+            }
+        }
+
+        assert!((torus.volume() - volume).abs() < volume * 2.0e-2);
+        assert!((mprops.mass() - 2.0 * volume).abs() < 2.0 * volume * 2.0e-2);
+        #[cfg(feature = "dim2")]
+        assert!((mprops.principal_inertia() - 2.0 * inertia.x).abs() < 2.0 * inertia.x * 2.0e-2);
+        #[cfg(feature = "dim3")]
+        assert!(
+            (mprops.principal_inertia() - inertia * 2.0).norm() < inertia.norm() * 2.0 * 2.0e-2
+        );
+    }
+
+    #[test]
+    fn ball_collides_with_the_tube_but_not_the_hole() {
+        let torus = Torus::new(1.0, 0.25);
+        let ball = Ball::new(0.5);
+
+        // A ball resting on the tube, 0.05 deeper than touching it.
+        #[cfg(feature = "dim2")]
+        let ball_center = Vector::x() * 1.7;
+        #[cfg(feature = "dim3")]
+        let ball_center = Vector::x() + Vector::y() * 0.7;
+        let ball_pos = Isometry::from(Translation::from(ball_center));
+
+        let contact = parry::query::contact(&Isometry::identity(), &torus, &ball_pos, &ball, 0.0)
+            .unwrap()
+            .unwrap();
+        let expected_normal = (ball_center - Vector::x()).normalize();
+        assert!((contact.dist + 0.05).abs() < 1.0e-3);
+        assert!((contact.normal1.into_inner() - expected_normal).norm() < 1.0e-3);
+
+        // A ball inside of the hole of the torus doesn’t touch it.
+        let contact = parry::query::contact(
+            &Isometry::identity(),
+            &torus,
+            &Isometry::identity(),
+            &ball,
+            0.0,
+        )
+        .unwrap();
+        assert!(contact.is_none());
+    }
+
+    #[test]
+    fn ray_cast_is_exact_even_for_tangent_rays() {
+        let torus = Torus::new(1.0, 0.25);
+
+        // A ray through the hole of the torus hits the outer side of the tube.
+        let ray = Ray::new(Point::origin() - Vector::x() * 3.0, Vector::x());
+        let hit = torus
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        assert!((hit.time_of_impact - 1.75).abs() < 1.0e-5);
+        assert!((hit.normal + Vector::x()).norm() < 1.0e-5);
+
+        // A non-solid ray starting inside of the tube exits it on its inner side.
+        let ray = Ray::new(Point::origin() - Vector::x(), Vector::x());
+        let hit = torus
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, false)
+            .unwrap();
+        assert!((hit.time_of_impact - 0.25).abs() < 1.0e-5);
+
+        // A ray tangent to the top of the tube touches it, and a slightly higher one misses it.
+        #[cfg(feature = "dim2")]
+        let (top, tangent_toi) = (Point::new(0.0, 1.25), 3.0);
+        #[cfg(feature = "dim3")]
+        let (top, tangent_toi) = (Point::new(-1.0, 0.25, 0.0), 2.0);
+        let ray = Ray::new(top - Vector::x() * tangent_toi, Vector::x());
+        let hit = torus
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .unwrap();
+        assert!((hit.time_of_impact - tangent_toi).abs() < 1.0e-2);
+        assert!((hit.normal - Vector::y()).norm() < 1.0e-2);
+
+        let ray = Ray::new(
+            top + Vector::y() * 0.01 - Vector::x() * tangent_toi,
+            Vector::x(),
+        );
+        assert!(torus
+            .cast_local_ray_and_get_normal(&ray, Real::MAX, true)
+            .is_none());
+    }
+}
@@ -45,6 +45,23 @@ std::compile_error!("The `simd-is-enabled` feature should not be enabled explici
 std::compile_error!(
     "SIMD cannot be enabled when the `enhanced-determinism` feature is also enabled."
 );
+#[cfg(all(
+    any(feature = "simd-wide8", feature = "simd-wide16"),
+    not(feature = "simd-is-enabled")
+))]
+std::compile_error!("The `simd-wide8` and `simd-wide16` features require the `simd-stable` or the `simd-nightly` feature.");
+#[cfg(all(feature = "simd-wide8", feature = "simd-wide16"))]
+std::compile_error!(
+    "The `simd-wide8` and `simd-wide16` features cannot be enabled at the same time."
+);
+#[cfg(all(feature = "simd-stable", feature = "simd-wide16"))]
+std::compile_error!("The `simd-wide16` feature requires the `simd-nightly` feature.");
+#[cfg(all(feature = "simd-stable", feature = "simd-wide8", feature = "f64"))]
+std::compile_error!(
+    "The `simd-wide8` feature requires the `simd-nightly` feature with 64-bits floats."
+);
+#[cfg(all(feature = "simd-wide16", feature = "f64"))]
+std::compile_error!("The `simd-wide16` feature is only supported with 32-bits floats.");
 
 macro_rules! enable_flush_to_zero(
     () => {
@@ -58,8 +75,8 @@ macro_rules! gather(
         {
             #[inline(always)]
             #[allow(dead_code)]
-            fn create_arr<T>(mut callback: impl FnMut(usize) -> T) -> [T; SIMD_WIDTH] {
-                [callback(0usize), callback(1usize), callback(2usize), callback(3usize)]
+            fn create_arr<T>(callback: impl FnMut(usize) -> T) -> [T; SIMD_WIDTH] {
+                std::array::from_fn(callback)
             }
 
             create_arr($callback)
@@ -143,6 +160,59 @@ pub mod utils;
 pub mod math {
     pub use parry::math::*;
 
+    /*
+     * Wide SIMD
+     */
+    // The constraints solver can process more constraints at once than the 4 lanes used by
+    // parry. The lanes are selected at build time: enable the `simd-wide8` or `simd-wide16`
+    // feature when targeting CPUs supporting AVX2 or AVX-512. There is no runtime dispatch
+    // since the whole solver would have to be compiled for each width.
+    #[cfg(feature = "simd-wide16")]
+    pub use self::wide_simd16::{SimdBool, SimdReal, SIMD_LAST_INDEX, SIMD_WIDTH};
+    #[cfg(feature = "simd-wide8")]
+    pub use self::wide_simd8::{SimdBool, SimdReal, SIMD_LAST_INDEX, SIMD_WIDTH};
+
+    #[cfg(feature = "simd-wide8")]
+    mod wide_simd8 {
+        /// The number of lanes of a SIMD number.
+        pub const SIMD_WIDTH: usize = 8;
+        /// SIMD_WIDTH - 1
+        pub const SIMD_LAST_INDEX: usize = 7;
+
+        /// A SIMD float with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-stable", feature = "f32"))]
+        pub type SimdReal = simba::simd::WideF32x8;
+        /// A SIMD bool with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-stable", feature = "f32"))]
+        pub type SimdBool = simba::simd::WideBoolF32x8;
+
+        /// A SIMD float with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-nightly", feature = "f32"))]
+        pub type SimdReal = simba::simd::f32x8;
+        /// A SIMD bool with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-nightly", feature = "f32"))]
+        pub type SimdBool = simba::simd::m32x8;
+
+        /// A SIMD float with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-nightly", feature = "f64"))]
+        pub type SimdReal = simba::simd::f64x8;
+        /// A SIMD bool with SIMD_WIDTH lanes.
+        #[cfg(all(feature = "simd-nightly", feature = "f64"))]
+        pub type SimdBool = simba::simd::m64x8;
+    }
+
+    #[cfg(feature = "simd-wide16")]
+    mod wide_simd16 {
+        /// The number of lanes of a SIMD number.
+        pub const SIMD_WIDTH: usize = 16;
+        /// SIMD_WIDTH - 1
+        pub const SIMD_LAST_INDEX: usize = 15;
+        /// A SIMD float with SIMD_WIDTH lanes.
+        pub type SimdReal = simba::simd::f32x16;
+        /// A SIMD bool with SIMD_WIDTH lanes.
+        pub type SimdBool = simba::simd::m32x16;
+    }
+
     /*
      * 2D
      */
@@ -80,9 +80,11 @@ impl CollisionPipeline {
         }
 
         narrow_phase.register_pairs(None, colliders, bodies, &self.broad_phase_events, events);
-        narrow_phase.compute_contacts(
+        let _ = narrow_phase.compute_contacts(
             prediction_distance,
             0.0,
+            None,
+            0.0,
             bodies,
             colliders,
             &ImpulseJointSet::new(),
@@ -164,6 +166,13 @@ impl CollisionPipeline {
 
         self.clear_modified_colliders(colliders, &mut modified_colliders);
         removed_colliders.clear();
+
+        // The contacts already followed the teleported rigid-bodies.
+        for handle in &modified_bodies {
+            if let Some(rb) = bodies.get_mut_internal(*handle) {
+                rb.teleport_motion = None;
+            }
+        }
     }
 }
 
@@ -1,6 +1,7 @@
-use crate::dynamics::RigidBodySet;
+use crate::dynamics::{RigidBodyHandle, RigidBodySet};
 use crate::geometry::{ColliderSet, CollisionEvent, ContactForceEvent, ContactPair};
 use crate::math::Real;
+use crate::pipeline::{ProjectileHit, QuarantineEvent};
 use crossbeam::channel::Sender;
 
 bitflags::bitflags! {
@@ -23,6 +24,34 @@ impl Default for ActiveEvents {
     }
 }
 
+/// Thresholds with hysteresis for reporting contact force events.
+///
+/// Contact force events start being reported for a contact pair once its total force magnitude
+/// exceeds `start_threshold`, and keep being reported until it drops to or below
+/// `stop_threshold`. This avoids floods of events from pairs resting around a single threshold,
+/// e.g., stacked objects. See [`PhysicsPipeline::contact_force_event_hysteresis`](crate::pipeline::PhysicsPipeline::contact_force_event_hysteresis).
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct ContactForceEventHysteresis {
+    /// The force magnitude a pair must exceed to start reporting contact force events.
+    pub start_threshold: Real,
+    /// The force magnitude at or below which a pair stops reporting contact force events.
+    ///
+    /// This should be smaller than `start_threshold`.
+    pub stop_threshold: Real,
+}
+
+impl ContactForceEventHysteresis {
+    /// Creates thresholds starting the contact force events above `start_threshold` and stopping
+    /// them at or below `stop_threshold`.
+    pub fn new(start_threshold: Real, stop_threshold: Real) -> Self {
+        Self {
+            start_threshold,
+            stop_threshold,
+        }
+    }
+}
+
 /// Trait implemented by structures responsible for handling events generated by the physics engine.
 ///
 /// Implementors of this trait will typically collect these events for future processing.
@@ -67,6 +96,51 @@ pub trait EventHandler: Send + Sync {
         contact_pair: &ContactPair,
         total_force_magnitude: Real,
     );
+
+    /// Handle a quarantine event.
+    ///
+    /// A quarantine event is emitted whenever a dynamic rigid-body becomes unstable while
+    /// [`IntegrationParameters::quarantine_thresholds`](crate::dynamics::IntegrationParameters::quarantine_thresholds)
+    /// is set. The rigid-body has already been put to sleep when this is called.
+    ///
+    /// Does nothing by default.
+    fn handle_quarantine_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _event: QuarantineEvent,
+    ) {
+    }
+
+    /// Handle a debris falling asleep.
+    ///
+    /// This is called whenever a rigid-body with the [`RigidBodyQuality::Debris`] quality
+    /// falls asleep. This is typically used to despawn the debris once it settled.
+    ///
+    /// Does nothing by default.
+    ///
+    /// [`RigidBodyQuality::Debris`]: crate::dynamics::RigidBodyQuality::Debris
+    fn handle_debris_sleep(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _body: RigidBodyHandle,
+    ) {
+    }
+
+    /// Handle a projectile hitting a collider.
+    ///
+    /// This is called by [`ProjectileSet::step`](crate::pipeline::ProjectileSet::step) for each
+    /// projectile hitting a collider, after the projectile was removed from its set.
+    ///
+    /// Does nothing by default.
+    fn handle_projectile_hit(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _hit: ProjectileHit,
+    ) {
+    }
 }
 
 impl EventHandler for () {
@@ -1,12 +1,24 @@
 //! Structure for combining the various physics components to perform an actual simulation.
 
 pub use collision_pipeline::CollisionPipeline;
-pub use event_handler::{ActiveEvents, ChannelEventCollector, EventHandler};
+pub use event_handler::{
+    ActiveEvents, ChannelEventCollector, ContactForceEventHysteresis, EventHandler,
+};
 pub use physics_hooks::{ActiveHooks, ContactModificationContext, PairFilterContext, PhysicsHooks};
-pub use physics_pipeline::PhysicsPipeline;
+pub use physics_pipeline::{PhysicsPipeline, SubstepCount};
+pub use physics_world::PhysicsWorld;
+pub use projectile_set::{Projectile, ProjectileHandle, ProjectileHit, ProjectileSet};
+pub use quarantine::{QuarantineCause, QuarantineEvent, UnstableConstraint};
+#[cfg(feature = "dim3")]
+pub use query_pipeline::WalkableSurfaceOptions;
 pub use query_pipeline::{
-    generators as query_pipeline_generators, QueryFilter, QueryFilterFlags, QueryPipeline,
+    generators as query_pipeline_generators, ChildShapeHit, ColliderFilter, Occluder, Occlusion,
+    PickHit, PickOptions, QueryBudget, QueryFilter, QueryFilterFlags, QueryPipeline,
+    QueryPipelineGeneration, ShapeCastContext, Subscription, SubscriptionHandle,
+    SubscriptionVolume,
 };
+pub use trajectory_predictor::{PredictedTrajectory, TrajectoryObstacles, TrajectoryPredictor};
+pub use validation::{Diagnostic, DiagnosticCode, DiagnosticSubject};
 
 #[cfg(feature = "debug-render")]
 pub use self::debug_render_pipeline::{
@@ -18,8 +30,13 @@ mod collision_pipeline;
 mod event_handler;
 mod physics_hooks;
 mod physics_pipeline;
+mod physics_world;
+mod projectile_set;
+mod quarantine;
 mod query_pipeline;
+mod trajectory_predictor;
 mod user_changes;
+mod validation;
 
 #[cfg(feature = "debug-render")]
 mod debug_render_pipeline;
@@ -1,5 +1,7 @@
-use crate::dynamics::{RigidBodyHandle, RigidBodySet};
-use crate::geometry::{ColliderHandle, ColliderSet, ContactManifold, SolverContact, SolverFlags};
+use crate::dynamics::{ContactSoftness, RigidBodyHandle, RigidBodySet};
+use crate::geometry::{
+    ColliderHandle, ColliderSet, ContactManifold, ContactUserSlots, SolverContact, SolverFlags,
+};
 use crate::math::{Real, Vector};
 use na::ComplexField;
 
@@ -39,13 +41,59 @@ pub struct ContactModificationContext<'a> {
     pub solver_contacts: &'a mut Vec<SolverContact>,
     /// The contact normal that can be modified.
     pub normal: &'a mut Vector<Real>,
+    /// The contact softness of this manifold that can be modified.
+    ///
+    /// It is initialized with the softness combined from the two colliders, and `None` means
+    /// the global [`IntegrationParameters::contact_softness`](crate::dynamics::IntegrationParameters::contact_softness)
+    /// is used. Setting it makes the contacts of this manifold softer or stiffer without
+    /// affecting any other contact involving the same colliders.
+    ///
+    /// The softness is set per manifold rather than per [`SolverContact`]: the contacts of a
+    /// manifold share their constraint regularization, and their normal impulses are solved
+    /// together by the block solver. Since each manifold involves a single pair of subshapes,
+    /// this is enough to give a specific softness to each pair of materials, including the
+    /// materials of the triangles of a mesh.
+    pub contact_softness: &'a mut Option<ContactSoftness>,
     /// User-defined data attached to the manifold.
     // NOTE: we keep this a &'a mut u32 to emphasize the
     // fact that this can be modified.
     pub user_data: &'a mut u32,
+    /// Additional user-defined data attached to the manifold, preserved across timesteps.
+    pub manifold_user_slots: &'a mut ContactUserSlots,
+    /// User-defined data attached to each contact point of the manifold, preserved across
+    /// timesteps as long as the contact point exists.
+    ///
+    /// This is indexed like `manifold.points`, so the slots of a solver contact are found with
+    /// [`SolverContact::contact_id`].
+    pub contact_user_slots: &'a mut [ContactUserSlots],
+    /// Should the modifications applied by this hook be cached and re-applied automatically
+    /// during the next timesteps?
+    ///
+    /// See [`ContactModificationContext::mark_as_persistent`] for details.
+    pub persistent: bool,
 }
 
 impl<'a> ContactModificationContext<'a> {
+    /// Marks the modifications applied to this contact manifold as persistent.
+    ///
+    /// If set, `PhysicsHooks::modify_solver_contacts` won’t be called for this contact manifold
+    /// during the next timesteps. Instead, Rapier will automatically re-apply the same
+    /// modifications to the newly computed solver contacts: the solver contacts removed by the
+    /// hook are removed again, the `friction`, `restitution` and `tangent_velocity` of the
+    /// remaining ones are set back to their modified values together with the contact softness,
+    /// and the modified normal is kept fixed relative to the first collider.
+    ///
+    /// The cache is invalidated, and the hook called again, whenever the manifold changes
+    /// substantially: if the set of contact points changes, if the contact normal rotates
+    /// noticeably, or if the shape of one of the colliders is modified. This can significantly
+    /// reduce the cost of hooks in scenes with a large number of stable contact pairs.
+    ///
+    /// This must not be used with modifications that depend on other state (like
+    /// [`Self::update_as_oneway_platform`]) since the hook won’t be able to observe it.
+    pub fn mark_as_persistent(&mut self) {
+        self.persistent = true;
+    }
+
     /// Helper function to update `self` to emulate a oneway-platform.
     ///
     /// The "oneway" behavior will only allow contacts between two colliders
@@ -183,6 +231,13 @@ pub trait PhysicsHooks: Send + Sync {
     /// will be taken into account by the constraints solver. If this returns
     /// `Some(SolverFlags::empty())` then the constraints solver will ignore these
     /// contacts.
+    ///
+    /// The returned flags can also override the dominance groups of the rigid-bodies for this
+    /// specific pair with `SolverFlags::FIRST_DOMINATES`, `SolverFlags::SECOND_DOMINATES`, or
+    /// `SolverFlags::EQUAL_DOMINANCE`. For example, a player can dominate debris without
+    /// dominating walls, and without modifying the player’s dominance group. The override is
+    /// stored with the contact manifolds of the pair and remains active as long as this filter
+    /// keeps returning it.
     fn filter_contact_pair(&self, _context: &PairFilterContext) -> Option<SolverFlags> {
         Some(SolverFlags::COMPUTE_IMPULSES)
     }
@@ -250,3 +305,118 @@ impl PhysicsHooks for () {
 
     fn modify_solver_contacts(&self, _: &mut ContactModificationContext) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ActiveHooks, ContactModificationContext, PhysicsHooks};
+    use crate::dynamics::{ContactSoftness, RigidBodyBuilder};
+    use crate::geometry::ColliderBuilder;
+    use crate::math::Vector;
+    use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+
+    #[test]
+    fn hooks_can_soften_specific_contact_pairs() {
+        // Softens the contacts of the colliders with a squishy material.
+        struct SquishyHooks;
+        impl PhysicsHooks for SquishyHooks {
+            fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+                let squishy = context.colliders[context.collider1].user_data == 1
+                    || context.colliders[context.collider2].user_data == 1;
+                if squishy {
+                    *context.contact_softness = Some(ContactSoftness {
+                        natural_frequency: 2.0,
+                        damping_ratio: 1.0,
+                        compliance: 0.0,
+                    });
+                }
+            }
+        }
+
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let ball1 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5)
+                .user_data(1)
+                .active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+            ball1,
+            &mut world.bodies,
+        );
+        let ball2 = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 2.0 + Vector::y() * 0.5));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5).active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+            ball2,
+            &mut world.bodies,
+        );
+
+        for _ in 0..120 {
+            world.step(&mut pipeline, None, &SquishyHooks, &());
+        }
+
+        let (y1, y2) = (
+            world.bodies[ball1].translation().y,
+            world.bodies[ball2].translation().y,
+        );
+        assert!(y1 < y2 - 1.0e-2);
+    }
+
+    #[test]
+    fn hooks_user_slots_persist_across_steps() {
+        // Counts the number of steps each manifold and contact point existed for.
+        struct CountingHooks;
+        impl PhysicsHooks for CountingHooks {
+            fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
+                context.manifold_user_slots[0] += 1;
+                for solver_contact in context.solver_contacts.iter() {
+                    context.contact_user_slots[solver_contact.contact_id()][0] += 1;
+                }
+            }
+        }
+
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        #[cfg(feature = "dim2")]
+        let box_shape = ColliderBuilder::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let box_shape = ColliderBuilder::cuboid(0.5, 0.5, 0.5);
+        let ground_co = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let body = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let box_co = world.colliders.insert_with_parent(
+            box_shape.active_hooks(ActiveHooks::MODIFY_SOLVER_CONTACTS),
+            body,
+            &mut world.bodies,
+        );
+
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &CountingHooks, &());
+        }
+
+        let pair = world.narrow_phase.contact_pair(ground_co, box_co).unwrap();
+        let manifold = &pair.manifolds[0];
+        assert_eq!(manifold.data.user_slots[0], 10);
+        assert!(!manifold.points.is_empty());
+        assert!(manifold
+            .points
+            .iter()
+            .all(|contact| contact.data.user_slots[0] == 10));
+    }
+}
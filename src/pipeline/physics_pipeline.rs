@@ -2,26 +2,83 @@
 
 use crate::counters::Counters;
 // #[cfg(not(feature = "parallel"))]
+use super::quarantine::QuarantineStage;
 use crate::dynamics::IslandSolver;
 #[cfg(feature = "parallel")]
 use crate::dynamics::JointGraphEdge;
 use crate::dynamics::{
-    CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
-    RigidBodyChanges, RigidBodyHandle, RigidBodyPosition, RigidBodyType,
+    CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet, RigidBody,
+    RigidBodyChanges, RigidBodyHandle, RigidBodyType, UserConstraintHandle, UserConstraintSet,
 };
 use crate::geometry::{
-    BroadPhase, BroadPhasePairEvent, ColliderChanges, ColliderHandle, ColliderPair,
+    BoundingVolume, BroadPhase, BroadPhasePairEvent, ColliderChanges, ColliderHandle, ColliderPair,
     ContactManifoldIndex, NarrowPhase, TemporaryInteractionIndex,
 };
 use crate::math::{Real, Vector};
-use crate::pipeline::{EventHandler, PhysicsHooks, QueryPipeline};
+use crate::pipeline::{
+    ActiveEvents, ContactForceEventHysteresis, EventHandler, PhysicsHooks, QueryPipeline,
+};
+use parry::utils::hashmap::HashMap;
+use std::num::NonZeroUsize;
 use {crate::dynamics::RigidBodySet, crate::geometry::ColliderSet};
 
+/// The number of solver substeps run by [`PhysicsPipeline::step`], replacing
+/// [`IntegrationParameters::num_solver_iterations`] (see [`PhysicsPipeline::substep_count`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SubstepCount {
+    /// Run exactly this number of substeps.
+    Fixed(NonZeroUsize),
+    /// Run enough substeps for the fastest awake dynamic rigid-body to move by at most
+    /// `max_displacement` during each of them, without going below
+    /// [`IntegrationParameters::num_solver_iterations`] nor above `max_substeps`.
+    Velocity {
+        /// The largest distance a rigid-body should travel during a single substep.
+        max_displacement: Real,
+        /// The largest number of substeps this can result in.
+        max_substeps: NonZeroUsize,
+    },
+}
+
+impl SubstepCount {
+    /// The number of substeps actually run for the given parameters and rigid-bodies.
+    pub fn resolve(
+        &self,
+        integration_parameters: &IntegrationParameters,
+        islands: &IslandManager,
+        bodies: &RigidBodySet,
+    ) -> NonZeroUsize {
+        match *self {
+            SubstepCount::Fixed(num_substeps) => num_substeps,
+            SubstepCount::Velocity {
+                max_displacement,
+                max_substeps,
+            } => {
+                let max_speed = islands
+                    .active_dynamic_bodies()
+                    .iter()
+                    .filter_map(|handle| bodies.get(*handle))
+                    .fold(0.0, |max: Real, rb| max.max(rb.linvel().norm()));
+                let num_substeps = if max_displacement > 0.0 {
+                    (max_speed * integration_parameters.dt / max_displacement).ceil() as usize
+                } else {
+                    max_substeps.get()
+                };
+                NonZeroUsize::new(num_substeps)
+                    .unwrap_or(NonZeroUsize::MIN)
+                    .max(integration_parameters.num_solver_iterations)
+                    .min(max_substeps)
+            }
+        }
+    }
+}
+
 /// The physics pipeline, responsible for stepping the whole physics simulation.
 ///
-/// This structure only contains temporary data buffers. It can be dropped and replaced by a fresh
-/// copy at any time. For performance reasons it is recommended to reuse the same physics pipeline
-/// instance to benefit from the cached data.
+/// Besides the settings configured on it (the contact force event thresholds and the
+/// substep count override), this structure only contains temporary data buffers and caches.
+/// It can be dropped and replaced by a fresh copy at any time, as long as these settings are
+/// configured again on the new copy. For performance reasons it is recommended to reuse the
+/// same physics pipeline instance to benefit from the cached data.
 ///
 /// Rapier relies on a time-stepping scheme. Its force computations
 /// uses two solvers:
@@ -31,11 +88,32 @@ use {crate::dynamics::RigidBodySet, crate::geometry::ColliderSet};
 pub struct PhysicsPipeline {
     /// Counters used for benchmarking only.
     pub counters: Counters,
+    /// Global thresholds with hysteresis applied to the contact force events of the pairs without
+    /// thresholds set with [`PhysicsPipeline::set_pair_contact_force_event_hysteresis`].
+    ///
+    /// If `None` (the default), the contact force events are only filtered by the
+    /// [`Collider::contact_force_event_threshold`](crate::geometry::Collider::contact_force_event_threshold)
+    /// of the colliders involved.
+    pub contact_force_event_hysteresis: Option<ContactForceEventHysteresis>,
+    pair_contact_force_event_hysteresis: HashMap<ColliderPair, ContactForceEventHysteresis>,
+    /// The number of solver substeps of the next steps, overriding
+    /// [`IntegrationParameters::num_solver_iterations`] (default: `None`).
+    ///
+    /// This can be changed before any step, e.g., to only run more substeps during the frames
+    /// with fast impacts. The contact warmstart impulses are rescaled whenever the resulting
+    /// substep length changes. The additional solver iterations of the rigid-bodies, and the
+    /// single substep of debris islands, still apply.
+    pub substep_count: Option<SubstepCount>,
     contact_pair_indices: Vec<TemporaryInteractionIndex>,
     manifold_indices: Vec<Vec<ContactManifoldIndex>>,
     joint_constraint_indices: Vec<Vec<ContactManifoldIndex>>,
+    user_constraint_handles: Vec<Vec<UserConstraintHandle>>,
     broadphase_collider_pairs: Vec<ColliderPair>,
     broad_phase_events: Vec<BroadPhasePairEvent>,
+    bodies_with_modified_colliders: Vec<RigidBodyHandle>,
+    /// The inverse masses, before the user changes, of the rigid-bodies with mass-properties
+    /// being recomputed.
+    prev_inv_masses: HashMap<RigidBodyHandle, Real>,
     solvers: Vec<IslandSolver>,
 }
 
@@ -56,15 +134,58 @@ impl PhysicsPipeline {
     pub fn new() -> PhysicsPipeline {
         PhysicsPipeline {
             counters: Counters::new(true),
+            contact_force_event_hysteresis: None,
+            pair_contact_force_event_hysteresis: HashMap::default(),
+            substep_count: None,
             solvers: vec![],
             contact_pair_indices: vec![],
             manifold_indices: vec![],
             joint_constraint_indices: vec![],
+            user_constraint_handles: vec![],
             broadphase_collider_pairs: vec![],
             broad_phase_events: vec![],
+            bodies_with_modified_colliders: vec![],
+            prev_inv_masses: HashMap::default(),
         }
     }
 
+    /// Sets the thresholds with hysteresis applied to the contact force events between two
+    /// colliders, overriding [`PhysicsPipeline::contact_force_event_hysteresis`] for this pair.
+    ///
+    /// Setting `None` removes the pair-level thresholds.
+    pub fn set_pair_contact_force_event_hysteresis(
+        &mut self,
+        collider1: ColliderHandle,
+        collider2: ColliderHandle,
+        hysteresis: Option<ContactForceEventHysteresis>,
+    ) {
+        let pair = ColliderPair::new(collider1, collider2);
+        let _ = self
+            .pair_contact_force_event_hysteresis
+            .remove(&pair.swap());
+
+        if let Some(hysteresis) = hysteresis {
+            let _ = self
+                .pair_contact_force_event_hysteresis
+                .insert(pair, hysteresis);
+        } else {
+            let _ = self.pair_contact_force_event_hysteresis.remove(&pair);
+        }
+    }
+
+    /// The pair-level thresholds with hysteresis applied to the contact force events between two
+    /// colliders, if any.
+    pub fn pair_contact_force_event_hysteresis(
+        &self,
+        collider1: ColliderHandle,
+        collider2: ColliderHandle,
+    ) -> Option<&ContactForceEventHysteresis> {
+        let pair = ColliderPair::new(collider1, collider2);
+        self.pair_contact_force_event_hysteresis
+            .get(&pair)
+            .or_else(|| self.pair_contact_force_event_hysteresis.get(&pair.swap()))
+    }
+
     fn clear_modified_colliders(
         &mut self,
         colliders: &mut ColliderSet,
@@ -73,6 +194,7 @@ impl PhysicsPipeline {
         for handle in modified_colliders.drain(..) {
             if let Some(co) = colliders.get_mut_internal(handle) {
                 co.changes = ColliderChanges::empty();
+                co.prev_pos = None;
             }
         }
     }
@@ -85,10 +207,106 @@ impl PhysicsPipeline {
         for handle in modified_bodies.drain(..) {
             if let Some(rb) = bodies.get_mut_internal(handle) {
                 rb.changes = RigidBodyChanges::empty();
+                rb.teleport_motion = None;
+            }
+        }
+    }
+
+    /// Rescales the warmstart impulses of the contacts involving the rigid-bodies with modified
+    /// masses, proportionally to the change of the inverse mass of each contact pair.
+    ///
+    /// Otherwise, the impulses warmstarting the next step were computed for the previous masses,
+    /// which makes the bodies jolt, e.g., when a resting body gets heavier.
+    fn rescale_warmstart_impulses_of_modified_masses(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        narrow_phase: &mut NarrowPhase,
+    ) {
+        let key = |handle: &RigidBodyHandle| handle.into_raw_parts();
+        let inv_masses = |handle: Option<RigidBodyHandle>| {
+            let Some(handle) = handle else {
+                return (0.0, 0.0);
+            };
+            let new_inv_mass = bodies.get(handle).map(solver_inv_mass).unwrap_or(0.0);
+            let prev_inv_mass = self
+                .prev_inv_masses
+                .get(&handle)
+                .copied()
+                .unwrap_or(new_inv_mass);
+            (prev_inv_mass, new_inv_mass)
+        };
+
+        for (handle, prev_inv_mass) in &self.prev_inv_masses {
+            let Some(rb) = bodies.get(*handle) else {
+                continue;
+            };
+            let new_inv_mass = solver_inv_mass(rb);
+            if new_inv_mass == *prev_inv_mass {
+                continue;
+            }
+
+            for co_handle in rb.colliders() {
+                for pair in narrow_phase.contact_pairs_with_mut(*co_handle) {
+                    let other = if pair.collider1 == *co_handle {
+                        pair.collider2
+                    } else {
+                        pair.collider1
+                    };
+                    let other_body = colliders.get(other).and_then(|co| co.parent());
+
+                    // Pairs between two modified bodies are only rescaled once.
+                    if other_body
+                        .filter(|h| self.prev_inv_masses.contains_key(h) && key(h) < key(handle))
+                        .is_some()
+                    {
+                        continue;
+                    }
+
+                    let (prev_other, new_other) = inv_masses(other_body);
+                    let new_sum = new_inv_mass + new_other;
+                    if new_sum > 0.0 {
+                        pair.scale_warmstart_impulses((prev_inv_mass + prev_other) / new_sum);
+                    }
+                }
             }
         }
     }
 
+    /// Updates the [`RigidBody::bounding_aabb`](crate::dynamics::RigidBody::bounding_aabb) of the
+    /// rigid-bodies with modified colliders.
+    fn update_bodies_aabbs(
+        &mut self,
+        prediction_distance: Real,
+        bodies: &mut RigidBodySet,
+        colliders: &ColliderSet,
+        modified_colliders: &[ColliderHandle],
+    ) {
+        self.bodies_with_modified_colliders.clear();
+        self.bodies_with_modified_colliders.extend(
+            modified_colliders
+                .iter()
+                .filter_map(|handle| colliders.get(*handle)?.parent())
+                .filter(|handle| bodies.contains(*handle)),
+        );
+        self.bodies_with_modified_colliders
+            .sort_unstable_by_key(|handle| handle.into_raw_parts());
+        self.bodies_with_modified_colliders.dedup();
+
+        for handle in &self.bodies_with_modified_colliders {
+            let rb = bodies.index_mut_internal(*handle);
+            let aabb = rb
+                .colliders
+                .0
+                .iter()
+                .filter_map(|co_handle| colliders.get(*co_handle))
+                .filter(|co| co.is_enabled())
+                .map(|co| co.compute_collision_aabb(co.broad_phase_prediction(prediction_distance)))
+                .reduce(|aabb1, aabb2| aabb1.merged(&aabb2));
+            rb.bounding_aabb = aabb;
+        }
+    }
+
     fn detect_collisions(
         &mut self,
         integration_parameters: &IntegrationParameters,
@@ -120,6 +338,12 @@ impl PhysicsPipeline {
             removed_colliders,
             &mut self.broad_phase_events,
         );
+        self.update_bodies_aabbs(
+            integration_parameters.prediction_distance(),
+            bodies,
+            colliders,
+            modified_colliders,
+        );
 
         self.counters.cd.broad_phase_time.pause();
         self.counters.cd.narrow_phase_time.resume();
@@ -142,8 +366,10 @@ impl PhysicsPipeline {
             &self.broad_phase_events,
             events,
         );
-        narrow_phase.compute_contacts(
+        let workload = narrow_phase.compute_contacts(
             integration_parameters.prediction_distance(),
+            integration_parameters.heightfield_contact_cache_distance(),
+            integration_parameters.parallel_narrow_phase_chunk_size,
             integration_parameters.dt,
             bodies,
             colliders,
@@ -153,6 +379,9 @@ impl PhysicsPipeline {
             hooks,
             events,
         );
+        if self.counters.enabled() {
+            workload.record(&mut self.counters.cd);
+        }
         narrow_phase.compute_intersections(bodies, colliders, modified_colliders, hooks, events);
 
         self.counters.cd.narrow_phase_time.pause();
@@ -169,6 +398,7 @@ impl PhysicsPipeline {
         colliders: &mut ColliderSet,
         impulse_joints: &mut ImpulseJointSet,
         multibody_joints: &mut MultibodyJointSet,
+        user_constraints: &mut UserConstraintSet,
         events: &dyn EventHandler,
     ) {
         self.counters.stages.island_construction_time.resume();
@@ -180,9 +410,18 @@ impl PhysicsPipeline {
             narrow_phase,
             impulse_joints,
             multibody_joints,
+            user_constraints,
             integration_parameters.min_island_size,
         );
 
+        if integration_parameters.deterministic_constraint_ordering {
+            islands.sort_active_islands(bodies);
+        }
+
+        for handle in islands.debris_fallen_asleep.drain(..) {
+            events.handle_debris_sleep(bodies, colliders, handle);
+        }
+
         if self.manifold_indices.len() < islands.num_islands() {
             self.manifold_indices
                 .resize(islands.num_islands(), Vec::new());
@@ -193,6 +432,11 @@ impl PhysicsPipeline {
                 .resize(islands.num_islands(), Vec::new());
         }
 
+        if self.user_constraint_handles.len() < islands.num_islands() {
+            self.user_constraint_handles
+                .resize(islands.num_islands(), Vec::new());
+        }
+
         let mut manifolds = Vec::new();
         narrow_phase.select_active_contacts(
             islands,
@@ -206,6 +450,30 @@ impl PhysicsPipeline {
             bodies,
             &mut self.joint_constraint_indices,
         );
+        user_constraints.select_active_constraints(
+            islands,
+            bodies,
+            &mut self.user_constraint_handles,
+        );
+
+        if integration_parameters.deterministic_constraint_ordering {
+            let joints = &impulse_joints.joint_graph().graph.edges;
+            for island_id in 0..islands.num_islands() {
+                // NOTE: the sort is stable so the manifolds between the same subshapes of the
+                //       same bodies are kept in the narrow-phase order.
+                self.manifold_indices[island_id].sort_by_key(|i| {
+                    let manifold = &manifolds[*i];
+                    (
+                        manifold.data.rigid_body1.map(|h| h.0),
+                        manifold.data.rigid_body2.map(|h| h.0),
+                        manifold.subshape1,
+                        manifold.subshape2,
+                    )
+                });
+                self.joint_constraint_indices[island_id]
+                    .sort_unstable_by_key(|i| joints[*i].weight.handle.0);
+            }
+        }
         self.counters.stages.island_construction_time.pause();
 
         self.counters.stages.update_time.resume();
@@ -214,9 +482,18 @@ impl PhysicsPipeline {
             //       the multibody dynamics update) since it depends on dt?
             let rb = bodies.index_mut_internal(*handle);
             rb.mprops.update_world_mass_properties(&rb.pos.position);
+            #[cfg(feature = "dim3")]
+            if rb.gyroscopic_forces_enabled {
+                rb.vels.angvel = rb.vels.angvel_with_gyroscopic_forces(
+                    &rb.mprops,
+                    &rb.pos.position.rotation,
+                    integration_parameters.dt,
+                );
+            }
             let effective_mass = rb.mprops.effective_mass();
             rb.forces
                 .compute_effective_force_and_torque(gravity, &effective_mass);
+            rb.forces.consume_step_impulses(integration_parameters.dt);
         }
         self.counters.stages.update_time.pause();
 
@@ -242,6 +519,8 @@ impl PhysicsPipeline {
                     impulse_joints.joints_mut(),
                     &self.joint_constraint_indices[island_id],
                     multibody_joints,
+                    user_constraints,
+                    &self.user_constraint_handles[island_id],
                 )
             }
         }
@@ -253,7 +532,34 @@ impl PhysicsPipeline {
             use std::sync::atomic::Ordering;
 
             let num_islands = islands.num_islands();
-            let solvers = &mut self.solvers[..num_islands];
+
+            // Group consecutive islands into batches with enough bodies to make each task
+            // worth scheduling. Very large islands are additionally split by their solver.
+            let batch_size = integration_parameters
+                .parallel_island_batch_size
+                .map(|size| size.get())
+                .unwrap_or_else(|| {
+                    let num_tasks = rayon::current_num_threads() * 4;
+                    (islands.active_dynamic_bodies().len() / num_tasks).max(1)
+                });
+            let mut batches = Vec::new();
+            let mut remaining_solvers = &mut self.solvers[..num_islands];
+            let mut batch_start = 0;
+            let mut batch_len = 0;
+
+            for island_id in 0..num_islands {
+                batch_len += islands.active_island(island_id).len();
+
+                if batch_len >= batch_size || island_id == num_islands - 1 {
+                    let (batch, rest) = std::mem::take(&mut remaining_solvers)
+                        .split_at_mut(island_id + 1 - batch_start);
+                    batches.push((batch_start, batch));
+                    remaining_solvers = rest;
+                    batch_start = island_id + 1;
+                    batch_len = 0;
+                }
+            }
+
             let bodies = &std::sync::atomic::AtomicPtr::new(bodies as *mut _);
             let manifolds = &std::sync::atomic::AtomicPtr::new(&mut manifolds as *mut _);
             let impulse_joints =
@@ -261,61 +567,111 @@ impl PhysicsPipeline {
             let multibody_joints = &std::sync::atomic::AtomicPtr::new(multibody_joints as *mut _);
             let manifold_indices = &self.manifold_indices[..];
             let joint_constraint_indices = &self.joint_constraint_indices[..];
+            let user_constraints = &*user_constraints;
+            let user_constraint_handles = &self.user_constraint_handles[..];
 
-            // PERF: right now, we are only doing islands-based parallelism.
-            //       Intra-island parallelism (that hasn’t been ported to the new
-            //       solver yet) will be supported in the future.
             self.counters.solver.velocity_resolution_time.resume();
             rayon::scope(|_scope| {
                 enable_flush_to_zero!();
 
-                solvers
-                    .par_iter_mut()
-                    .enumerate()
-                    .for_each(|(island_id, solver)| {
-                        let bodies: &mut RigidBodySet =
-                            unsafe { &mut *bodies.load(Ordering::Relaxed) };
-                        let manifolds: &mut Vec<&mut ContactManifold> =
-                            unsafe { &mut *manifolds.load(Ordering::Relaxed) };
-                        let impulse_joints: &mut Vec<JointGraphEdge> =
-                            unsafe { &mut *impulse_joints.load(Ordering::Relaxed) };
-                        let multibody_joints: &mut MultibodyJointSet =
-                            unsafe { &mut *multibody_joints.load(Ordering::Relaxed) };
-
-                        let mut counters = Counters::new(false);
-                        solver.init_and_solve(
-                            island_id,
-                            &mut counters,
-                            integration_parameters,
-                            islands,
-                            bodies,
-                            &mut manifolds[..],
-                            &manifold_indices[island_id],
-                            impulse_joints,
-                            &joint_constraint_indices[island_id],
-                            multibody_joints,
-                        )
+                batches
+                    .into_par_iter()
+                    .for_each(|(first_island_id, batch)| {
+                        for (i, solver) in batch.iter_mut().enumerate() {
+                            let island_id = first_island_id + i;
+                            let bodies: &mut RigidBodySet =
+                                unsafe { &mut *bodies.load(Ordering::Relaxed) };
+                            let manifolds: &mut Vec<&mut ContactManifold> =
+                                unsafe { &mut *manifolds.load(Ordering::Relaxed) };
+                            let impulse_joints: &mut Vec<JointGraphEdge> =
+                                unsafe { &mut *impulse_joints.load(Ordering::Relaxed) };
+                            let multibody_joints: &mut MultibodyJointSet =
+                                unsafe { &mut *multibody_joints.load(Ordering::Relaxed) };
+
+                            let mut counters = Counters::new(false);
+                            solver.init_and_solve(
+                                island_id,
+                                &mut counters,
+                                integration_parameters,
+                                islands,
+                                bodies,
+                                &mut manifolds[..],
+                                &manifold_indices[island_id],
+                                impulse_joints,
+                                &joint_constraint_indices[island_id],
+                                multibody_joints,
+                                user_constraints,
+                                &user_constraint_handles[island_id],
+                            )
+                        }
                     });
             });
             self.counters.solver.velocity_resolution_time.pause();
         }
 
+        if !user_constraints.is_empty() {
+            for solver in &self.solvers[..islands.num_islands()] {
+                solver.writeback_user_constraint_impulses(user_constraints);
+            }
+        }
+
+        if self.counters.enabled {
+            for solver in &self.solvers[..islands.num_islands()] {
+                let num_pgs_iterations = solver.num_pgs_iterations();
+                let counters = &mut self.counters.solver;
+                counters.num_pgs_iterations += num_pgs_iterations;
+                counters.max_island_pgs_iterations =
+                    counters.max_island_pgs_iterations.max(num_pgs_iterations);
+            }
+        }
+
         // Generate contact force events if needed.
         let inv_dt = crate::utils::inv(integration_parameters.dt);
+        let event_filter = narrow_phase.event_filter();
+
         for pair_id in self.contact_pair_indices.drain(..) {
-            let pair = narrow_phase.contact_pair_at_index(pair_id);
+            let pair = narrow_phase.contact_pair_at_index_mut(pair_id);
             let co1 = &colliders[pair.collider1];
             let co2 = &colliders[pair.collider2];
+
+            if !NarrowPhase::pair_active_events(event_filter, co1, co2)
+                .contains(ActiveEvents::CONTACT_FORCE_EVENTS)
+            {
+                pair.reporting_contact_forces = false;
+                continue;
+            }
+
             let threshold = co1
                 .effective_contact_force_event_threshold()
                 .min(co2.effective_contact_force_event_threshold());
 
             if threshold < Real::MAX {
                 let total_magnitude = pair.total_impulse_magnitude() * inv_dt;
-
-                // NOTE: the strict inequality is important here, so we don’t
+                let key = ColliderPair::new(pair.collider1, pair.collider2);
+                let hysteresis = self
+                    .pair_contact_force_event_hysteresis
+                    .get(&key)
+                    .or_else(|| self.pair_contact_force_event_hysteresis.get(&key.swap()))
+                    .or(self.contact_force_event_hysteresis.as_ref());
+
+                // NOTE: the strict inequalities are important here, so we don’t
                 //       trigger an event if the force is 0.0 and the threshold is 0.0.
-                if total_magnitude > threshold {
+                let report = match hysteresis {
+                    None => {
+                        pair.reporting_contact_forces = false;
+                        total_magnitude > threshold
+                    }
+                    Some(hysteresis) => {
+                        pair.reporting_contact_forces = if pair.reporting_contact_forces {
+                            total_magnitude > hysteresis.stop_threshold
+                        } else {
+                            total_magnitude > threshold.max(hysteresis.start_threshold)
+                        };
+                        pair.reporting_contact_forces
+                    }
+                };
+
+                if report {
                     events.handle_contact_force_event(
                         integration_parameters.dt,
                         bodies,
@@ -324,6 +680,8 @@ impl PhysicsPipeline {
                         total_magnitude,
                     );
                 }
+            } else {
+                pair.reporting_contact_forces = false;
             }
         }
 
@@ -356,6 +714,8 @@ impl PhysicsPipeline {
 
     fn advance_to_final_positions(
         &mut self,
+        integration_parameters: &IntegrationParameters,
+        is_last_substep: bool,
         islands: &IslandManager,
         bodies: &mut RigidBodySet,
         colliders: &mut ColliderSet,
@@ -364,7 +724,29 @@ impl PhysicsPipeline {
         // Set the rigid-bodies and kinematic bodies to their final position.
         for handle in islands.iter_active_bodies() {
             let rb = bodies.index_mut_internal(handle);
-            rb.pos.position = rb.pos.next_position;
+
+            if rb.is_kinematic() && rb.vels.is_zero() {
+                // The kinematic body didn’t move. Flagging its colliders as modified would
+                // wake up the bodies touching them, preventing them from ever falling asleep.
+                continue;
+            }
+
+            // Keep track of the previous positions for the swept intersection tests.
+            for handle in &rb.colliders.0 {
+                let co = colliders.index_mut_internal(*handle);
+                co.prev_pos = Some(co.pos.0);
+            }
+
+            if rb.body_type == RigidBodyType::KinematicPositionBased && !is_last_substep {
+                // Stop at this substep’s share of the motion, the target stays in `next_position`.
+                rb.pos.position = rb.vels.integrate(
+                    integration_parameters.dt,
+                    &rb.pos.position,
+                    &rb.mprops.local_mprops.local_com,
+                );
+            } else {
+                rb.pos.position = rb.pos.next_position;
+            }
             rb.colliders
                 .update_positions(colliders, modified_colliders, &rb.pos.position);
         }
@@ -373,6 +755,7 @@ impl PhysicsPipeline {
     fn interpolate_kinematic_velocities(
         &mut self,
         integration_parameters: &IntegrationParameters,
+        time_to_kinematic_targets: Real,
         islands: &IslandManager,
         bodies: &mut RigidBodySet,
     ) {
@@ -381,23 +764,27 @@ impl PhysicsPipeline {
         // located before the island computation because we test the velocity
         // there to determine if this kinematic body should wake-up dynamic
         // bodies it is touching.
+        //
+        // If the timestep is split into several CCD substeps, position-based kinematic bodies
+        // move toward their target at the velocity reaching it at the end of the last substep.
         for handle in islands.active_kinematic_bodies() {
             let rb = bodies.index_mut_internal(*handle);
 
             match rb.body_type {
                 RigidBodyType::KinematicPositionBased => {
                     rb.vels = rb.pos.interpolate_velocity(
-                        integration_parameters.inv_dt(),
+                        crate::utils::inv(time_to_kinematic_targets),
                         &rb.mprops.local_mprops.local_com,
                     );
                 }
                 RigidBodyType::KinematicVelocityBased => {
-                    let new_pos = rb.vels.integrate(
+                    // Only set the next position: the solver substeps move the body from its
+                    // current position with its velocity.
+                    rb.pos.next_position = rb.vels.integrate(
                         integration_parameters.dt,
                         &rb.pos.position,
                         &rb.mprops.local_mprops.local_com,
                     );
-                    rb.pos = RigidBodyPosition::from(new_pos);
                 }
                 _ => {}
             }
@@ -405,6 +792,8 @@ impl PhysicsPipeline {
     }
 
     /// Executes one timestep of the physics simulation.
+    ///
+    /// This is the same as [`Self::step_with_user_constraints`] without any user constraint.
     pub fn step(
         &mut self,
         gravity: &Vector<Real>,
@@ -417,6 +806,43 @@ impl PhysicsPipeline {
         impulse_joints: &mut ImpulseJointSet,
         multibody_joints: &mut MultibodyJointSet,
         ccd_solver: &mut CCDSolver,
+        query_pipeline: Option<&mut QueryPipeline>,
+        hooks: &dyn PhysicsHooks,
+        events: &dyn EventHandler,
+    ) {
+        self.step_with_user_constraints(
+            gravity,
+            integration_parameters,
+            islands,
+            broad_phase,
+            narrow_phase,
+            bodies,
+            colliders,
+            impulse_joints,
+            multibody_joints,
+            &mut UserConstraintSet::new(),
+            ccd_solver,
+            query_pipeline,
+            hooks,
+            events,
+        );
+    }
+
+    /// Executes one timestep of the physics simulation, solving the given user constraints
+    /// together with the contacts and joints.
+    pub fn step_with_user_constraints(
+        &mut self,
+        gravity: &Vector<Real>,
+        integration_parameters: &IntegrationParameters,
+        islands: &mut IslandManager,
+        broad_phase: &mut dyn BroadPhase,
+        narrow_phase: &mut NarrowPhase,
+        bodies: &mut RigidBodySet,
+        colliders: &mut ColliderSet,
+        impulse_joints: &mut ImpulseJointSet,
+        multibody_joints: &mut MultibodyJointSet,
+        user_constraints: &mut UserConstraintSet,
+        ccd_solver: &mut CCDSolver,
         mut query_pipeline: Option<&mut QueryPipeline>,
         hooks: &dyn PhysicsHooks,
         events: &dyn EventHandler,
@@ -439,6 +865,11 @@ impl PhysicsPipeline {
         for handle in impulse_joints_iterator {
             islands.wake_up(bodies, handle.0, true);
         }
+        for handle in user_constraints.to_wake_up.drain(..) {
+            if bodies.contains(handle) {
+                islands.wake_up(bodies, handle, true);
+            }
+        }
 
         // Apply modifications.
         let mut modified_colliders = colliders.take_modified();
@@ -451,6 +882,16 @@ impl PhysicsPipeline {
         );
 
         let mut modified_bodies = bodies.take_modified();
+        self.prev_inv_masses.clear();
+        self.prev_inv_masses
+            .extend(modified_bodies.iter().filter_map(|handle| {
+                let rb = bodies.get(*handle)?;
+                rb.changes
+                    .intersects(
+                        RigidBodyChanges::LOCAL_MASS_PROPERTIES | RigidBodyChanges::COLLIDERS,
+                    )
+                    .then(|| (*handle, solver_inv_mass(rb)))
+            }));
         super::user_changes::handle_user_changes_to_rigid_bodies(
             Some(islands),
             bodies,
@@ -460,6 +901,7 @@ impl PhysicsPipeline {
             &modified_bodies,
             &mut modified_colliders,
         );
+        self.rescale_warmstart_impulses_of_modified_masses(bodies, colliders, narrow_phase);
 
         // Disabled colliders are treated as if they were removed.
         // NOTE: this must be called here, after handle_user_changes_to_rigid_bodies to take into
@@ -510,8 +952,21 @@ impl PhysicsPipeline {
         self.counters.stages.user_changes.pause();
 
         let mut remaining_time = integration_parameters.dt;
+        let base_num_substeps = integration_parameters.num_solver_iterations;
         let mut integration_parameters = *integration_parameters;
 
+        if let Some(substep_count) = &self.substep_count {
+            integration_parameters.num_solver_iterations =
+                substep_count.resolve(&integration_parameters, islands, bodies);
+        }
+
+        // The warmstart impulses are rescaled for the current substep length, like
+        // in `IntegrationParameters::transition_to`.
+        narrow_phase.set_substep_count_ratio(
+            base_num_substeps.get() as Real
+                / integration_parameters.num_solver_iterations.get() as Real,
+        );
+
         let (ccd_is_enabled, mut remaining_substeps) =
             if integration_parameters.max_ccd_substeps == 0 {
                 (false, 1)
@@ -520,6 +975,8 @@ impl PhysicsPipeline {
             };
 
         while remaining_substeps > 0 {
+            let time_to_kinematic_targets = remaining_time;
+
             // If there are more than one CCD substep, we need to split
             // the timestep into multiple intervals. First, estimate the
             // size of the time slice we will integrate for this substep.
@@ -579,7 +1036,12 @@ impl PhysicsPipeline {
 
             self.counters.ccd.num_substeps += 1;
 
-            self.interpolate_kinematic_velocities(&integration_parameters, islands, bodies);
+            self.interpolate_kinematic_velocities(
+                &integration_parameters,
+                time_to_kinematic_targets,
+                islands,
+                bodies,
+            );
             self.build_islands_and_solve_velocity_constraints(
                 gravity,
                 &integration_parameters,
@@ -589,6 +1051,7 @@ impl PhysicsPipeline {
                 colliders,
                 impulse_joints,
                 multibody_joints,
+                user_constraints,
                 events,
             );
 
@@ -616,7 +1079,38 @@ impl PhysicsPipeline {
             }
 
             self.counters.stages.update_time.resume();
-            self.advance_to_final_positions(islands, bodies, colliders, &mut modified_colliders);
+            self.quarantine_unstable_bodies(
+                QuarantineStage::Velocities,
+                &integration_parameters,
+                islands,
+                narrow_phase,
+                bodies,
+                colliders,
+                impulse_joints,
+                multibody_joints,
+                &mut modified_colliders,
+                events,
+            );
+            self.advance_to_final_positions(
+                &integration_parameters,
+                remaining_substeps == 0,
+                islands,
+                bodies,
+                colliders,
+                &mut modified_colliders,
+            );
+            self.quarantine_unstable_bodies(
+                QuarantineStage::Positions,
+                &integration_parameters,
+                islands,
+                narrow_phase,
+                bodies,
+                colliders,
+                impulse_joints,
+                multibody_joints,
+                &mut modified_colliders,
+                events,
+            );
             self.counters.stages.update_time.pause();
 
             self.detect_collisions(
@@ -666,17 +1160,30 @@ impl PhysicsPipeline {
     }
 }
 
+/// The inverse mass of a rigid-body as seen by the contact constraints.
+fn solver_inv_mass(rb: &RigidBody) -> Real {
+    if rb.is_dynamic() {
+        rb.mprops.local_mprops.inv_mass
+    } else {
+        0.0
+    }
+}
+
 #[cfg(test)]
 mod test {
     use na::point;
+    use std::num::NonZeroUsize;
 
     use crate::dynamics::{
-        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, RigidBodyBuilder,
-        RigidBodySet,
+        CCDSolver, FixedJointBuilder, ImpulseJointSet, IntegrationParameters, IslandManager,
+        RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
     };
     use crate::geometry::{BroadPhaseMultiSap, ColliderBuilder, ColliderSet, NarrowPhase};
-    use crate::math::Vector;
-    use crate::pipeline::PhysicsPipeline;
+    use crate::math::{Real, Vector};
+    use crate::pipeline::{
+        ActiveEvents, ChannelEventCollector, ContactForceEventHysteresis, PhysicsPipeline,
+        PhysicsWorld, SubstepCount,
+    };
     use crate::prelude::{MultibodyJointSet, RevoluteJointBuilder, RigidBodyType};
 
     #[test]
@@ -1011,4 +1518,291 @@ mod test {
             assert!(rotation.w.is_finite());
         }
     }
+
+    #[test]
+    fn bodies_on_a_stopped_kinematic_body_can_sleep() {
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut pipeline = PhysicsPipeline::new();
+        let mut bf = BroadPhaseMultiSap::new();
+        let mut nf = NarrowPhase::new();
+        let mut islands = IslandManager::new();
+        let mut bodies = RigidBodySet::new();
+
+        // A ball resting on top of a motionless kinematic ball.
+        let platform = bodies.insert(RigidBodyBuilder::kinematic_position_based());
+        let ball = bodies.insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 1.5));
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(1.0), platform, &mut bodies);
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut bodies);
+
+        let gravity = Vector::y() * -9.81;
+
+        for _ in 0..300 {
+            pipeline.step(
+                &gravity,
+                &IntegrationParameters::default(),
+                &mut islands,
+                &mut bf,
+                &mut nf,
+                &mut bodies,
+                &mut colliders,
+                &mut impulse_joints,
+                &mut multibody_joints,
+                &mut CCDSolver::new(),
+                None,
+                &(),
+                &(),
+            );
+        }
+
+        assert!(bodies[ball].is_sleeping());
+
+        // Moving the kinematic body again must wake up the ball.
+        bodies[platform].set_next_kinematic_translation(Vector::y() * 0.01);
+        pipeline.step(
+            &gravity,
+            &IntegrationParameters::default(),
+            &mut islands,
+            &mut bf,
+            &mut nf,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut CCDSolver::new(),
+            None,
+            &(),
+            &(),
+        );
+
+        assert!(!bodies[ball].is_sleeping());
+    }
+
+    #[test]
+    fn contact_force_event_hysteresis_filters_resting_pairs() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        let (collision_send, _collision_recv) = crossbeam::channel::unbounded();
+        let (force_send, force_recv) = crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(collision_send, force_send);
+
+        // A ball resting on the ground.
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        let ball = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 0.5));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5).active_events(ActiveEvents::CONTACT_FORCE_EVENTS),
+            ball,
+            &mut world.bodies,
+        );
+        let weight = world.bodies[ball].mass() * 9.81;
+
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &events);
+        }
+
+        assert!(force_recv.try_iter().count() > 0);
+
+        // The resting force never exceeds the start threshold.
+        pipeline.contact_force_event_hysteresis = Some(ContactForceEventHysteresis::new(
+            weight * 10.0,
+            weight * 0.5,
+        ));
+
+        for _ in 0..30 {
+            world.step(&mut pipeline, None, &(), &events);
+        }
+
+        assert_eq!(force_recv.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn deterministic_ordering_sorts_the_islands_by_handle() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+        world
+            .integration_parameters
+            .deterministic_constraint_ordering = true;
+
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        // A row of touching balls, joined in reverse order.
+        let balls: Vec<_> = (0..8)
+            .map(|i| {
+                let ball = world
+                    .bodies
+                    .insert(RigidBodyBuilder::dynamic().translation(Vector::x() * i as Real));
+                let _ = world.colliders.insert_with_parent(
+                    ColliderBuilder::ball(0.5),
+                    ball,
+                    &mut world.bodies,
+                );
+                ball
+            })
+            .collect();
+        for pair in balls.windows(2).rev() {
+            let _ = world
+                .impulse_joints
+                .insert(pair[1], pair[0], FixedJointBuilder::new(), true);
+        }
+
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+
+            for island_id in 0..world.islands.num_islands() {
+                let island = world.islands.active_island(island_id);
+                assert!(island.windows(2).all(|w| w[0].0 < w[1].0));
+                for (offset, handle) in island.iter().enumerate() {
+                    assert_eq!(world.bodies[*handle].ids.active_set_offset, offset);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn velocity_based_kinematic_bodies_dont_overshoot_during_substeps() {
+        let mut world = PhysicsWorld::default();
+        let mut pipeline = PhysicsPipeline::new();
+        assert!(world.integration_parameters.num_solver_iterations.get() > 1);
+
+        // A kinematic ball pushing up a dynamic ball resting on it.
+        let platform = world
+            .bodies
+            .insert(RigidBodyBuilder::kinematic_velocity_based().linvel(Vector::y() * 1.0));
+        let _ = world.colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5),
+            platform,
+            &mut world.bodies,
+        );
+        let ball = world
+            .bodies
+            .insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 1.0));
+        let _ =
+            world
+                .colliders
+                .insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut world.bodies);
+
+        for _ in 0..10 {
+            world.step(&mut pipeline, None, &(), &());
+
+            // The ball must be pushed to the platform’s end-of-step position, not beyond it.
+            let gap = world.bodies[ball].translation().y - world.bodies[platform].translation().y;
+            assert!((gap - 1.0).abs() < 5.0e-3, "gap: {gap}");
+        }
+    }
+
+    fn resting_box(world: &mut PhysicsWorld) -> RigidBodyHandle {
+        let _ = world
+            .colliders
+            .insert(ColliderBuilder::halfspace(Vector::y_axis()));
+        #[cfg(feature = "dim2")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let cube = ColliderBuilder::cuboid(0.5, 0.5, 0.5);
+        let body = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(Vector::y() * 0.5)
+                .can_sleep(false),
+        );
+        let _ = world
+            .colliders
+            .insert_with_parent(cube, body, &mut world.bodies);
+        body
+    }
+
+    #[test]
+    fn mass_changes_rescale_the_warmstart_impulses() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let body = resting_box(&mut world);
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        let mass = world.bodies[body].mass();
+        world.bodies[body].set_additional_mass(mass * 9.0, true);
+        world.step(&mut pipeline, None, &(), &());
+
+        // The warmstart impulses were scaled for the new mass, so the body doesn’t sink.
+        assert!(world.bodies[body].linvel().norm() < 0.05);
+    }
+
+    #[test]
+    fn substep_count_overrides_the_solver_iterations() {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        let mut pipeline = PhysicsPipeline::new();
+
+        let body = resting_box(&mut world);
+        let projectile = world.bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(Vector::y() * 100.0)
+                .linvel(Vector::x() * 57.0)
+                .gravity_scale(0.0)
+                .can_sleep(false),
+        );
+        for _ in 0..60 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        for _ in 0..2 {
+            // The substep count the warmstart impulses were computed with is stored in the
+            // narrow-phase, so using another pipeline doesn’t rescale them again.
+            pipeline = PhysicsPipeline::new();
+            pipeline.substep_count = Some(SubstepCount::Fixed(NonZeroUsize::new(16).unwrap()));
+            world.step(&mut pipeline, None, &(), &());
+
+            // The warmstart impulses were scaled for the shorter substeps.
+            assert!(world.bodies[body].linvel().norm() < 0.05);
+        }
+
+        for _ in 0..18 {
+            world.step(&mut pipeline, None, &(), &());
+        }
+
+        // The projectile moves by 95cm per step, so it needs 10 substeps to move by less than
+        // 10cm per substep.
+        let velocity = SubstepCount::Velocity {
+            max_displacement: 0.1,
+            max_substeps: NonZeroUsize::new(32).unwrap(),
+        };
+        let params = &world.integration_parameters;
+        assert_eq!(
+            velocity
+                .resolve(params, &world.islands, &world.bodies)
+                .get(),
+            10
+        );
+        world.bodies[projectile].set_linvel(Vector::zeros(), true);
+        assert_eq!(
+            velocity
+                .resolve(params, &world.islands, &world.bodies)
+                .get(),
+            4
+        );
+        world.bodies[projectile].set_linvel(Vector::x() * 6000.0, true);
+        assert_eq!(
+            velocity
+                .resolve(params, &world.islands, &world.bodies)
+                .get(),
+            32
+        );
+    }
 }
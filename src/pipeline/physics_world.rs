@@ -0,0 +1,238 @@
+use crate::dynamics::{
+    CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+    RigidBodySet, UserConstraintSet,
+};
+use crate::geometry::{ColliderSet, DefaultBroadPhase, NarrowPhase};
+use crate::math::{Real, Vector};
+use crate::pipeline::{EventHandler, PhysicsHooks, PhysicsPipeline, QueryPipeline};
+
+#[cfg(doc)]
+use crate::geometry::Collider;
+
+/// An owned copy of all the structures needed to step a physics simulation.
+///
+/// This is typically created with [`PhysicsWorld::clone_physics_state`] from the structures
+/// of an existing simulation, and then stepped independently to predict what would happen
+/// if, e.g., a force was applied to a body, without affecting the original simulation.
+///
+/// Cloning a simulation is cheap compared to serializing it: the collider shapes are
+/// reference-counted so they are shared with the original colliders until they are modified
+/// through [`Collider::shape_mut`], and all the other structures are plain arenas copied as-is.
+#[derive(Clone, Default)]
+pub struct PhysicsWorld {
+    /// The gravity applied to the dynamic bodies.
+    pub gravity: Vector<Real>,
+    /// The parameters of each timestep.
+    pub integration_parameters: IntegrationParameters,
+    /// The island manager.
+    pub islands: IslandManager,
+    /// The broad-phase.
+    pub broad_phase: DefaultBroadPhase,
+    /// The narrow-phase.
+    pub narrow_phase: NarrowPhase,
+    /// The rigid-bodies.
+    pub bodies: RigidBodySet,
+    /// The colliders.
+    pub colliders: ColliderSet,
+    /// The impulse joints.
+    pub impulse_joints: ImpulseJointSet,
+    /// The multibody joints.
+    pub multibody_joints: MultibodyJointSet,
+    /// The user-defined constraints.
+    pub user_constraints: UserConstraintSet,
+    /// The CCD solver.
+    pub ccd_solver: CCDSolver,
+}
+
+impl PhysicsWorld {
+    /// Copies the state of a simulation so it can be stepped independently from the original.
+    ///
+    /// The arguments are the same as the ones given to
+    /// [`PhysicsPipeline::step_with_user_constraints`]. The cloned world keeps the contact and
+    /// warmstarting information of the original simulation so stepping both yields the same
+    /// results.
+    pub fn clone_physics_state(
+        gravity: &Vector<Real>,
+        integration_parameters: &IntegrationParameters,
+        islands: &IslandManager,
+        broad_phase: &DefaultBroadPhase,
+        narrow_phase: &NarrowPhase,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        impulse_joints: &ImpulseJointSet,
+        multibody_joints: &MultibodyJointSet,
+        user_constraints: &UserConstraintSet,
+        ccd_solver: &CCDSolver,
+    ) -> Self {
+        Self {
+            gravity: *gravity,
+            integration_parameters: *integration_parameters,
+            islands: islands.clone(),
+            broad_phase: broad_phase.clone(),
+            narrow_phase: narrow_phase.clone(),
+            bodies: bodies.clone(),
+            colliders: colliders.clone(),
+            impulse_joints: impulse_joints.clone(),
+            multibody_joints: multibody_joints.clone(),
+            user_constraints: user_constraints.clone(),
+            ccd_solver: ccd_solver.clone(),
+        }
+    }
+
+    /// Executes one timestep of this world with the given physics pipeline.
+    ///
+    /// All the state kept from one step to the next is stored in the world: the `pipeline`
+    /// only contains its settings, temporary buffers, and caches that are checked against their
+    /// inputs before being reused. So the same pipeline can be used to step several worlds one
+    /// after the other, with the same results as one pipeline per world with the same settings.
+    pub fn step(
+        &mut self,
+        pipeline: &mut PhysicsPipeline,
+        query_pipeline: Option<&mut QueryPipeline>,
+        hooks: &dyn PhysicsHooks,
+        events: &dyn EventHandler,
+    ) {
+        pipeline.step_with_user_constraints(
+            &self.gravity,
+            &self.integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.user_constraints,
+            &mut self.ccd_solver,
+            query_pipeline,
+            hooks,
+            events,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PhysicsWorld;
+    use crate::dynamics::{
+        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        RigidBodyBuilder, RigidBodySet, UserConstraintSet,
+    };
+    use crate::geometry::{ColliderBuilder, ColliderSet, DefaultBroadPhase, NarrowPhase};
+    use crate::math::{Real, Vector};
+    use crate::pipeline::PhysicsPipeline;
+
+    #[test]
+    fn cloned_world_steps_independently() {
+        let mut islands = IslandManager::new();
+        let mut broad_phase = DefaultBroadPhase::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut user_constraints = UserConstraintSet::new();
+        let mut ccd_solver = CCDSolver::new();
+        let mut pipeline = PhysicsPipeline::new();
+        let gravity = Vector::y() * -9.81;
+        let params = IntegrationParameters::default();
+
+        let handle = bodies.insert(RigidBodyBuilder::dynamic());
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(0.5), handle, &mut bodies);
+
+        pipeline.step_with_user_constraints(
+            &gravity,
+            &params,
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut user_constraints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+
+        let mut world = PhysicsWorld::clone_physics_state(
+            &gravity,
+            &params,
+            &islands,
+            &broad_phase,
+            &narrow_phase,
+            &bodies,
+            &colliders,
+            &impulse_joints,
+            &multibody_joints,
+            &user_constraints,
+            &ccd_solver,
+        );
+
+        // Push the copy upward: the original body must not be affected.
+        world.bodies[handle].apply_impulse(Vector::y() * 100.0, true);
+        world.step(&mut pipeline, None, &(), &());
+
+        assert!(world.bodies[handle].translation().y > 0.0);
+        assert!(bodies[handle].linvel().y < 0.0);
+    }
+
+    fn falling_boxes(heights: &[Real]) -> PhysicsWorld {
+        let mut world = PhysicsWorld {
+            gravity: Vector::y() * -9.81,
+            ..PhysicsWorld::default()
+        };
+        #[cfg(feature = "dim2")]
+        let (ground, cube) = (
+            ColliderBuilder::cuboid(10.0, 0.5),
+            ColliderBuilder::cuboid(0.5, 0.5),
+        );
+        #[cfg(feature = "dim3")]
+        let (ground, cube) = (
+            ColliderBuilder::cuboid(10.0, 0.5, 10.0),
+            ColliderBuilder::cuboid(0.5, 0.5, 0.5),
+        );
+        let _ = world
+            .colliders
+            .insert(ground.translation(-Vector::y() * 0.5));
+
+        for (i, height) in heights.iter().enumerate() {
+            let handle = world.bodies.insert(
+                RigidBodyBuilder::dynamic()
+                    .translation(Vector::y() * *height + Vector::x() * (i as Real * 0.1)),
+            );
+            let _ = world
+                .colliders
+                .insert_with_parent(cube.clone(), handle, &mut world.bodies);
+        }
+
+        world
+    }
+
+    #[test]
+    fn worlds_stepped_by_one_pipeline_match_separate_pipelines() {
+        let mut shared_pipeline = PhysicsPipeline::new();
+        let mut pipelines = [PhysicsPipeline::new(), PhysicsPipeline::new()];
+        let mut shared_worlds = [falling_boxes(&[0.5, 1.5, 2.5]), falling_boxes(&[3.0, 5.0])];
+        let mut worlds = shared_worlds.clone();
+
+        for _ in 0..120 {
+            for (shared_world, (world, pipeline)) in shared_worlds
+                .iter_mut()
+                .zip(worlds.iter_mut().zip(pipelines.iter_mut()))
+            {
+                shared_world.step(&mut shared_pipeline, None, &(), &());
+                world.step(pipeline, None, &(), &());
+            }
+        }
+
+        for (shared_world, world) in shared_worlds.iter().zip(worlds.iter()) {
+            for (handle, rb) in world.bodies.iter() {
+                assert_eq!(shared_world.bodies[handle].position(), rb.position());
+                assert_eq!(shared_world.bodies[handle].linvel(), rb.linvel());
+            }
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use crate::data::Arena;
+use crate::dynamics::{RigidBodyHandle, RigidBodySet};
+use crate::geometry::{ColliderHandle, ColliderSet, InteractionGroups, Ray};
+use crate::math::{Point, Real, Vector};
+use crate::pipeline::{EventHandler, QueryFilter, QueryFilterFlags, QueryPipeline};
+use std::ops::{Index, IndexMut};
+
+/// The unique handle of a projectile added to a [`ProjectileSet`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[repr(transparent)]
+pub struct ProjectileHandle(pub crate::data::arena::Index);
+
+impl ProjectileHandle {
+    /// Converts this handle into its (index, generation) components.
+    pub fn into_raw_parts(self) -> (u32, u32) {
+        self.0.into_raw_parts()
+    }
+
+    /// Reconstructs an handle from its (index, generation) components.
+    pub fn from_raw_parts(id: u32, generation: u32) -> Self {
+        Self(crate::data::arena::Index::from_raw_parts(id, generation))
+    }
+
+    /// An always-invalid projectile handle.
+    pub fn invalid() -> Self {
+        Self(crate::data::arena::Index::from_raw_parts(
+            crate::INVALID_U32,
+            crate::INVALID_U32,
+        ))
+    }
+}
+
+/// A point-mass moving ballistically, e.g., a bullet.
+///
+/// Projectiles don’t rotate, aren’t affected by contacts and aren’t seen by the physics
+/// pipeline or by the scene queries. They are much cheaper than rigid-bodies: they are only
+/// integrated and ray-cast against the colliders by [`ProjectileSet::step`], which removes them
+/// at their first hit.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Projectile {
+    /// The world-space position of the projectile.
+    pub position: Point<Real>,
+    /// The linear velocity of the projectile.
+    pub linvel: Vector<Real>,
+    /// The mass of the projectile.
+    ///
+    /// At its impact, the momentum of the projectile is transferred to the dynamic rigid-body
+    /// it hits, if any. Set it to zero for projectiles that don’t push what they hit.
+    pub mass: Real,
+    /// The multiplier applied to the gravity affecting this projectile.
+    pub gravity_scale: Real,
+    /// The drag coefficient proportional to the speed of the projectile.
+    pub linear_drag: Real,
+    /// The drag coefficient proportional to the squared speed of the projectile, modeling the
+    /// air resistance at high speeds.
+    pub quadratic_drag: Real,
+    /// The remaining time before this projectile is removed without hitting anything.
+    pub lifetime: Real,
+    /// The collision groups of this projectile, filtering the colliders it can hit.
+    pub collision_groups: InteractionGroups,
+    /// Flags excluding some types of colliders from the colliders the projectile can hit.
+    pub filter_flags: QueryFilterFlags,
+    /// A rigid-body whose colliders are never hit by this projectile, typically its shooter.
+    pub exclude_rigid_body: Option<RigidBodyHandle>,
+    /// User-defined data associated to this projectile.
+    pub user_data: u128,
+}
+
+impl Projectile {
+    /// Creates a projectile at the given position with the given velocity.
+    ///
+    /// The projectile has no mass and no drag, is fully affected by gravity, never expires, and
+    /// hits all the colliders except sensors.
+    pub fn new(position: Point<Real>, linvel: Vector<Real>) -> Self {
+        Self {
+            position,
+            linvel,
+            mass: 0.0,
+            gravity_scale: 1.0,
+            linear_drag: 0.0,
+            quadratic_drag: 0.0,
+            lifetime: Real::INFINITY,
+            collision_groups: InteractionGroups::all(),
+            filter_flags: QueryFilterFlags::EXCLUDE_SENSORS,
+            exclude_rigid_body: None,
+            user_data: 0,
+        }
+    }
+
+    /// Sets the mass of this projectile.
+    pub fn mass(mut self, mass: Real) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Sets the gravity multiplier of this projectile.
+    pub fn gravity_scale(mut self, gravity_scale: Real) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    /// Sets the linear and quadratic drag coefficients of this projectile.
+    pub fn drag(mut self, linear_drag: Real, quadratic_drag: Real) -> Self {
+        self.linear_drag = linear_drag;
+        self.quadratic_drag = quadratic_drag;
+        self
+    }
+
+    /// Sets the time after which this projectile is removed if it didn’t hit anything.
+    pub fn lifetime(mut self, lifetime: Real) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Sets the collision groups of this projectile.
+    pub fn collision_groups(mut self, groups: InteractionGroups) -> Self {
+        self.collision_groups = groups;
+        self
+    }
+
+    /// Sets the flags excluding some types of colliders from the colliders this projectile can
+    /// hit.
+    pub fn filter_flags(mut self, flags: QueryFilterFlags) -> Self {
+        self.filter_flags = flags;
+        self
+    }
+
+    /// Prevents this projectile from hitting the colliders of the given rigid-body.
+    pub fn exclude_rigid_body(mut self, rigid_body: RigidBodyHandle) -> Self {
+        self.exclude_rigid_body = Some(rigid_body);
+        self
+    }
+
+    /// Sets the user-data of this projectile.
+    pub fn user_data(mut self, user_data: u128) -> Self {
+        self.user_data = user_data;
+        self
+    }
+
+    /// Integrates the velocity of this projectile over `dt`, returning its displacement.
+    fn integrate_velocity(&mut self, dt: Real, gravity: &Vector<Real>) -> Vector<Real> {
+        // The drag is integrated implicitly so large coefficients don’t make it explode.
+        let drag = self.linear_drag + self.quadratic_drag * self.linvel.norm();
+        self.linvel = (self.linvel + gravity * (self.gravity_scale * dt)) / (1.0 + drag * dt);
+        self.linvel * dt
+    }
+}
+
+/// A projectile of a [`ProjectileSet`] hitting a collider.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProjectileHit {
+    /// The projectile that hit the collider. It has already been removed from its set.
+    pub projectile: ProjectileHandle,
+    /// The collider hit by the projectile.
+    pub collider: ColliderHandle,
+    /// The world-space impact point.
+    pub point: Point<Real>,
+    /// The world-space normal of the collider at the impact point.
+    pub normal: Vector<Real>,
+    /// The velocity of the projectile at the impact.
+    pub linvel: Vector<Real>,
+    /// The mass of the projectile.
+    pub mass: Real,
+    /// The user-data of the projectile.
+    pub user_data: u128,
+}
+
+/// A set of projectiles, stepped in batch.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug)]
+pub struct ProjectileSet {
+    projectiles: Arena<Projectile>,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    hits: Vec<ProjectileHit>,
+}
+
+impl ProjectileSet {
+    /// Create a new empty set of projectiles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of projectiles on this set.
+    pub fn len(&self) -> usize {
+        self.projectiles.len()
+    }
+
+    /// `true` if there are no projectiles in this set.
+    pub fn is_empty(&self) -> bool {
+        self.projectiles.is_empty()
+    }
+
+    /// Is the given projectile handle valid?
+    pub fn contains(&self, handle: ProjectileHandle) -> bool {
+        self.projectiles.contains(handle.0)
+    }
+
+    /// Insert a projectile into this set and retrieve its handle.
+    pub fn insert(&mut self, projectile: Projectile) -> ProjectileHandle {
+        ProjectileHandle(self.projectiles.insert(projectile))
+    }
+
+    /// Removes a projectile from this set.
+    pub fn remove(&mut self, handle: ProjectileHandle) -> Option<Projectile> {
+        self.projectiles.remove(handle.0)
+    }
+
+    /// Gets the projectile with the given handle.
+    pub fn get(&self, handle: ProjectileHandle) -> Option<&Projectile> {
+        self.projectiles.get(handle.0)
+    }
+
+    /// Gets a mutable reference to the projectile with the given handle.
+    pub fn get_mut(&mut self, handle: ProjectileHandle) -> Option<&mut Projectile> {
+        self.projectiles.get_mut(handle.0)
+    }
+
+    /// Iterates through all the projectiles on this set.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (ProjectileHandle, &Projectile)> {
+        self.projectiles
+            .iter()
+            .map(|(h, p)| (ProjectileHandle(h), p))
+    }
+
+    /// Iterates mutably through all the projectiles on this set.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (ProjectileHandle, &mut Projectile)> {
+        self.projectiles
+            .iter_mut()
+            .map(|(h, p)| (ProjectileHandle(h), p))
+    }
+
+    /// Advances all the projectiles by the time `dt`.
+    ///
+    /// Each projectile is ray-cast along its motion against the colliders of the `queries`,
+    /// which must be up-to-date, so fast projectiles never pass through thin colliders. A
+    /// projectile hitting a collider is removed from this set, its momentum is applied to the
+    /// dynamic rigid-body it hit, and [`EventHandler::handle_projectile_hit`] is called. The
+    /// projectiles that exceeded their lifetime are removed silently.
+    pub fn step(
+        &mut self,
+        dt: Real,
+        gravity: &Vector<Real>,
+        queries: &QueryPipeline,
+        bodies: &mut RigidBodySet,
+        colliders: &ColliderSet,
+        events: &dyn EventHandler,
+    ) {
+        let hits = &mut self.hits;
+        hits.clear();
+
+        self.projectiles.retain(|handle, projectile| {
+            let displacement = projectile.integrate_velocity(dt, gravity);
+            let filter = QueryFilter {
+                flags: projectile.filter_flags,
+                groups: Some(projectile.collision_groups),
+                exclude_rigid_body: projectile.exclude_rigid_body,
+                ..QueryFilter::default()
+            };
+            let ray = Ray::new(projectile.position, displacement);
+
+            if let Some((collider, hit)) =
+                queries.cast_ray_and_get_normal(bodies, colliders, &ray, 1.0, true, filter)
+            {
+                hits.push(ProjectileHit {
+                    projectile: ProjectileHandle(handle),
+                    collider,
+                    point: ray.point_at(hit.time_of_impact),
+                    normal: hit.normal,
+                    linvel: projectile.linvel,
+                    mass: projectile.mass,
+                    user_data: projectile.user_data,
+                });
+                return false;
+            }
+
+            projectile.position += displacement;
+            projectile.lifetime -= dt;
+            projectile.lifetime > 0.0
+        });
+
+        for hit in &self.hits {
+            if hit.mass > 0.0 {
+                if let Some(rb) = colliders[hit.collider]
+                    .parent()
+                    .and_then(|parent| bodies.get_mut(parent))
+                {
+                    if rb.is_dynamic() {
+                        rb.apply_impulse_at_point(hit.linvel * hit.mass, hit.point, true);
+                    }
+                }
+            }
+
+            events.handle_projectile_hit(bodies, colliders, *hit);
+        }
+    }
+}
+
+impl Index<ProjectileHandle> for ProjectileSet {
+    type Output = Projectile;
+
+    fn index(&self, index: ProjectileHandle) -> &Projectile {
+        &self.projectiles[index.0]
+    }
+}
+
+impl IndexMut<ProjectileHandle> for ProjectileSet {
+    fn index_mut(&mut self, index: ProjectileHandle) -> &mut Projectile {
+        &mut self.projectiles[index.0]
+    }
+}
+
+#[cfg(all(feature = "dim3", feature = "f32"))]
+#[cfg(test)]
+mod test {
+    use super::{Projectile, ProjectileHit, ProjectileSet};
+    use crate::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct HitCollector(Mutex<Vec<ProjectileHit>>);
+
+    impl EventHandler for HitCollector {
+        fn handle_collision_event(
+            &self,
+            _bodies: &RigidBodySet,
+            _colliders: &ColliderSet,
+            _event: CollisionEvent,
+            _contact_pair: Option<&ContactPair>,
+        ) {
+        }
+
+        fn handle_contact_force_event(
+            &self,
+            _dt: Real,
+            _bodies: &RigidBodySet,
+            _colliders: &ColliderSet,
+            _contact_pair: &ContactPair,
+            _total_force_magnitude: Real,
+        ) {
+        }
+
+        fn handle_projectile_hit(
+            &self,
+            _bodies: &RigidBodySet,
+            _colliders: &ColliderSet,
+            hit: ProjectileHit,
+        ) {
+            self.0.lock().unwrap().push(hit);
+        }
+    }
+
+    #[test]
+    fn fast_projectiles_hit_thin_colliders() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut queries = QueryPipeline::new();
+        let mut projectiles = ProjectileSet::new();
+        let events = HitCollector::default();
+
+        // A thin dynamic wall, and a projectile crossing it within a single step.
+        let wall = bodies.insert(RigidBodyBuilder::dynamic().translation(vector![10.0, 0.0, 0.0]));
+        let wall_collider = colliders.insert_with_parent(
+            ColliderBuilder::cuboid(0.01, 5.0, 5.0),
+            wall,
+            &mut bodies,
+        );
+        queries.update(&colliders);
+
+        let bullet = projectiles.insert(
+            Projectile::new(Point::origin(), vector![300.0, 0.0, 0.0])
+                .mass(0.1)
+                .gravity_scale(0.0)
+                .user_data(42),
+        );
+        let expiring = projectiles
+            .insert(Projectile::new(point![0.0, 10.0, 0.0], vector![0.0, 1.0, 0.0]).lifetime(0.05));
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..4 {
+            projectiles.step(
+                dt,
+                &(Vector::y() * -9.81),
+                &queries,
+                &mut bodies,
+                &colliders,
+                &events,
+            );
+        }
+
+        let hits = events.0.into_inner().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].projectile, bullet);
+        assert_eq!(hits[0].collider, wall_collider);
+        assert_eq!(hits[0].user_data, 42);
+        assert!((hits[0].point.x - 9.99).abs() < 1.0e-3);
+        assert!((hits[0].normal - vector![-1.0, 0.0, 0.0]).norm() < 1.0e-5);
+        assert!(!projectiles.contains(bullet));
+        assert!(!projectiles.contains(expiring));
+
+        // The bullet’s momentum was transferred to the wall.
+        let momentum = bodies[wall].linvel() * bodies[wall].mass();
+        assert!((momentum - vector![30.0, 0.0, 0.0]).norm() < 1.0e-3);
+    }
+}
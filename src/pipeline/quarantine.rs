@@ -0,0 +1,331 @@
+use crate::dynamics::{
+    ImpulseJointHandle, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+    QuarantineThresholds, RigidBodyHandle, RigidBodySet,
+};
+use crate::geometry::{ColliderHandle, ColliderSet, ContactData, NarrowPhase};
+use crate::math::{Isometry, Real, SpacialVector};
+use crate::pipeline::{EventHandler, PhysicsPipeline};
+use crate::utils::SimdDot;
+
+/// The reason why a rigid-body was quarantined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum QuarantineCause {
+    /// The velocity of the rigid-body contained NaN or infinite values.
+    NonFiniteVelocity,
+    /// The next position of the rigid-body contained NaN or infinite values.
+    NonFinitePosition,
+    /// The velocity of the rigid-body exceeded the [`QuarantineThresholds`].
+    ExcessiveVelocity,
+    /// The next position of the rigid-body exceeded
+    /// [`QuarantineThresholds::normalized_max_distance_from_origin`].
+    ExcessiveDistanceFromOrigin,
+}
+
+/// A constraint that produced a NaN or infinite impulse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum UnstableConstraint {
+    /// A contact between two colliders.
+    Contact(ColliderHandle, ColliderHandle),
+    /// An impulse joint.
+    ImpulseJoint(ImpulseJointHandle),
+}
+
+/// Event emitted when a rigid-body is quarantined because it became unstable.
+///
+/// See [`QuarantineThresholds`] for details.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct QuarantineEvent {
+    /// The quarantined rigid-body.
+    pub body: RigidBodyHandle,
+    /// Why the rigid-body was quarantined.
+    pub cause: QuarantineCause,
+    /// A constraint attached to this rigid-body that produced a NaN or infinite impulse.
+    ///
+    /// This is `None` if no such constraint was found, e.g., if the instability originates from
+    /// a NaN force or velocity set by the user, or from a constraint that remained finite but
+    /// failed to converge.
+    pub constraint: Option<UnstableConstraint>,
+}
+
+fn is_finite_impulse(data: &ContactData) -> bool {
+    data.impulse.is_finite() && data.tangent_impulse.iter().all(|e| e.is_finite())
+}
+
+fn is_finite_joint_impulse(impulses: &SpacialVector<Real>) -> bool {
+    impulses.iter().all(|e| e.is_finite())
+}
+
+/// When the stability of the rigid-bodies is checked during a CCD substep.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum QuarantineStage {
+    /// After the velocity solver and the CCD motion clamping, before the bodies are moved. The
+    /// velocities and next positions are checked.
+    Velocities,
+    /// After the bodies were moved to their final position. The final positions are checked.
+    Positions,
+}
+
+fn check_velocity(
+    thresholds: &QuarantineThresholds,
+    length_unit: Real,
+    sq_linvel: Real,
+    sq_angvel: Real,
+) -> Option<QuarantineCause> {
+    let max_linvel = thresholds.normalized_max_linear_velocity * length_unit;
+
+    if !sq_linvel.is_finite() || !sq_angvel.is_finite() {
+        Some(QuarantineCause::NonFiniteVelocity)
+    } else if sq_linvel > max_linvel * max_linvel
+        || sq_angvel > thresholds.max_angular_velocity * thresholds.max_angular_velocity
+    {
+        Some(QuarantineCause::ExcessiveVelocity)
+    } else {
+        None
+    }
+}
+
+fn check_position(
+    thresholds: &QuarantineThresholds,
+    length_unit: Real,
+    position: &Isometry<Real>,
+) -> Option<QuarantineCause> {
+    let max_distance = thresholds.normalized_max_distance_from_origin * length_unit;
+    let sq_distance = position.translation.vector.norm_squared();
+
+    if !sq_distance.is_finite()
+        || !position
+            .rotation
+            .to_homogeneous()
+            .iter()
+            .all(|e| e.is_finite())
+    {
+        Some(QuarantineCause::NonFinitePosition)
+    } else if sq_distance > max_distance * max_distance {
+        Some(QuarantineCause::ExcessiveDistanceFromOrigin)
+    } else {
+        None
+    }
+}
+
+impl PhysicsPipeline {
+    /// Quarantines the active dynamic bodies exceeding the
+    /// [`IntegrationParameters::quarantine_thresholds`], if they are set.
+    ///
+    /// This is called twice per CCD substep: at [`QuarantineStage::Velocities`] before the bodies
+    /// are moved to their next position, and at [`QuarantineStage::Positions`] right after. A
+    /// quarantined body is moved back to its last position that passed these checks.
+    pub(super) fn quarantine_unstable_bodies(
+        &mut self,
+        stage: QuarantineStage,
+        integration_parameters: &IntegrationParameters,
+        islands: &IslandManager,
+        narrow_phase: &mut NarrowPhase,
+        bodies: &mut RigidBodySet,
+        colliders: &mut ColliderSet,
+        impulse_joints: &mut ImpulseJointSet,
+        multibody_joints: &MultibodyJointSet,
+        modified_colliders: &mut Vec<ColliderHandle>,
+        events: &dyn EventHandler,
+    ) {
+        let Some(thresholds) = integration_parameters.quarantine_thresholds else {
+            return;
+        };
+
+        let length_unit = integration_parameters.length_unit;
+        let mut quarantined = vec![];
+
+        for handle in islands.active_dynamic_bodies() {
+            if multibody_joints.rigid_body_link(*handle).is_some() {
+                continue;
+            }
+
+            let rb = bodies.index_mut_internal(*handle);
+            let position_cause = check_position(&thresholds, length_unit, &rb.pos.position);
+
+            if position_cause.is_none() {
+                rb.stable_position = Some(rb.pos.position);
+            }
+
+            let cause = match stage {
+                QuarantineStage::Velocities => check_velocity(
+                    &thresholds,
+                    length_unit,
+                    rb.vels.linvel.norm_squared(),
+                    rb.vels.angvel.gdot(rb.vels.angvel),
+                )
+                .or_else(|| check_position(&thresholds, length_unit, &rb.pos.next_position)),
+                QuarantineStage::Positions => position_cause,
+            };
+
+            if let Some(cause) = cause {
+                quarantined.push((*handle, cause));
+            }
+        }
+
+        for (handle, cause) in quarantined {
+            let rb = bodies.index_mut_internal(handle);
+            // A body that never had a stable position, e.g., because it was inserted with a NaN
+            // pose, is moved back to the origin.
+            let stable_position = rb.stable_position.unwrap_or_else(Isometry::identity);
+            rb.pos.position = stable_position;
+            rb.pos.next_position = stable_position;
+            rb.mprops.update_world_mass_properties(&stable_position);
+
+            if stage == QuarantineStage::Positions {
+                // The colliders already followed the body to its unstable position.
+                rb.colliders
+                    .update_positions(colliders, modified_colliders, &stable_position);
+            }
+
+            rb.reset_forces(false);
+            rb.reset_torques(false);
+            rb.sleep();
+
+            // Find the constraints with non-finite impulses, and reset them so they don’t
+            // contaminate the next step through warmstarting.
+            let mut constraint = None;
+
+            for co_handle in rb.colliders.0.iter() {
+                for pair in narrow_phase.contact_pairs_with_mut(*co_handle) {
+                    for manifold in &mut pair.manifolds {
+                        for pt in &mut manifold.points {
+                            if !is_finite_impulse(&pt.data) {
+                                constraint.get_or_insert(UnstableConstraint::Contact(
+                                    pair.collider1,
+                                    pair.collider2,
+                                ));
+                                pt.data = ContactData {
+                                    id: pt.data.id,
+                                    ..ContactData::default()
+                                };
+                            }
+                        }
+                    }
+                }
+            }
+
+            impulse_joints.map_attached_joints_mut(handle, |_, _, joint_handle, joint| {
+                if !is_finite_joint_impulse(&joint.impulses) {
+                    constraint.get_or_insert(UnstableConstraint::ImpulseJoint(joint_handle));
+                    joint.impulses = na::zero();
+                }
+            });
+
+            events.handle_quarantine_event(
+                bodies,
+                colliders,
+                QuarantineEvent {
+                    body: handle,
+                    cause,
+                    constraint,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::{
+        CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        QuarantineThresholds, RigidBodyBuilder, RigidBodySet,
+    };
+    use crate::geometry::{ColliderBuilder, ColliderSet, DefaultBroadPhase, NarrowPhase};
+    use crate::math::{Real, Vector};
+    use crate::pipeline::PhysicsPipeline;
+
+    #[test]
+    fn non_finite_velocity_is_quarantined() {
+        let mut islands = IslandManager::new();
+        let mut broad_phase = DefaultBroadPhase::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut ccd_solver = CCDSolver::new();
+        let mut pipeline = PhysicsPipeline::new();
+        let params = IntegrationParameters {
+            quarantine_thresholds: Some(QuarantineThresholds::default()),
+            ..IntegrationParameters::default()
+        };
+
+        let handle = bodies.insert(RigidBodyBuilder::dynamic().linvel(Vector::x() * Real::NAN));
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(0.5), handle, &mut bodies);
+
+        pipeline.step(
+            &(Vector::y() * -9.81),
+            &params,
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &(),
+        );
+
+        let rb = &bodies[handle];
+        assert!(rb.is_sleeping());
+        assert_eq!(rb.linvel().norm(), 0.0);
+        assert_eq!(*rb.translation(), Vector::zeros());
+    }
+
+    #[test]
+    fn non_finite_position_is_restored_to_last_stable_position() {
+        let mut islands = IslandManager::new();
+        let mut broad_phase = DefaultBroadPhase::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut ccd_solver = CCDSolver::new();
+        let mut pipeline = PhysicsPipeline::new();
+        let params = IntegrationParameters {
+            quarantine_thresholds: Some(QuarantineThresholds::default()),
+            ..IntegrationParameters::default()
+        };
+
+        let handle = bodies.insert(RigidBodyBuilder::dynamic().translation(Vector::y() * 5.0));
+        let collider =
+            colliders.insert_with_parent(ColliderBuilder::ball(0.5), handle, &mut bodies);
+
+        let mut step = |bodies: &mut RigidBodySet, colliders: &mut ColliderSet| {
+            pipeline.step(
+                &(Vector::y() * -9.81),
+                &params,
+                &mut islands,
+                &mut broad_phase,
+                &mut narrow_phase,
+                bodies,
+                colliders,
+                &mut impulse_joints,
+                &mut multibody_joints,
+                &mut ccd_solver,
+                None,
+                &(),
+                &(),
+            );
+        };
+
+        step(&mut bodies, &mut colliders);
+        let stable_position = *bodies[handle].position();
+        assert!(!bodies[handle].is_sleeping());
+
+        bodies[handle].set_translation(Vector::x() * Real::NAN, true);
+        step(&mut bodies, &mut colliders);
+
+        let rb = &bodies[handle];
+        assert!(rb.is_sleeping());
+        assert_eq!(*rb.position(), stable_position);
+        assert_eq!(*colliders[collider].position(), stable_position);
+    }
+}
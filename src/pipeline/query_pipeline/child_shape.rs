@@ -0,0 +1,180 @@
+use crate::dynamics::RigidBodySet;
+use crate::geometry::{
+    Aabb, BoundingVolume, Collider, ColliderHandle, ColliderSet, PointProjection, Ray,
+    RayIntersection,
+};
+use crate::math::{Isometry, Point, Real};
+use crate::pipeline::{QueryFilter, QueryPipeline};
+use parry::query::visitors::{BoundingVolumeIntersectionsVisitor, RayIntersectionsVisitor};
+use parry::query::{PointQuery, RayCast};
+use parry::shape::SimdCompositeShape;
+
+/// The child of a composite shape hit by a scene query.
+///
+/// The children of a compound shape are its sub-shapes, and the children of a triangle mesh,
+/// polyline, or heightfield are its triangles or segments.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChildShapeHit {
+    /// The index of the child within its composite shape, e.g., its index in
+    /// [`Compound::shapes`](parry::shape::Compound::shapes), or the index of a triangle.
+    pub child_id: u32,
+    /// The world-space pose of the child shape.
+    ///
+    /// This is the pose of the collider for the triangles and segments of meshes, since their
+    /// vertices are expressed in the local-space of the collider.
+    pub child_pose: Isometry<Real>,
+}
+
+impl ChildShapeHit {
+    fn new(collider: &Collider, child_id: u32, child_pose: Option<&Isometry<Real>>) -> Self {
+        Self {
+            child_id,
+            child_pose: child_pose
+                .map(|pose| collider.pos.0 * pose)
+                .unwrap_or(collider.pos.0),
+        }
+    }
+}
+
+impl QueryPipeline {
+    /// Find the closest intersection between a ray and a set of colliders, with the child shape
+    /// hit if the collider has a composite shape.
+    ///
+    /// The child is `None` if the collider hit doesn’t have a composite shape. The parameters are
+    /// the same as for [`Self::cast_ray_and_get_normal`].
+    pub fn cast_ray_and_get_child(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        ray: &Ray,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, RayIntersection, Option<ChildShapeHit>)> {
+        let (handle, hit) =
+            self.cast_ray_and_get_normal(bodies, colliders, ray, max_toi, solid, filter)?;
+        let co = &colliders[handle];
+        let child = co.shape.as_composite_shape().and_then(|composite| {
+            let local_ray = ray.inverse_transform_by(&co.pos);
+            let mut closest: Option<(Real, ChildShapeHit)> = None;
+            let mut leaf_callback = |child_id: &u32| {
+                composite.map_part_at(*child_id, &mut |pose, shape, _| {
+                    let child_ray = pose
+                        .map(|pose| local_ray.inverse_transform_by(pose))
+                        .unwrap_or(local_ray);
+                    if let Some(toi) = shape.cast_local_ray(&child_ray, max_toi, solid) {
+                        if closest.map(|(best, _)| toi < best).unwrap_or(true) {
+                            closest = Some((toi, ChildShapeHit::new(co, *child_id, pose)));
+                        }
+                    }
+                });
+                true
+            };
+
+            let mut visitor = RayIntersectionsVisitor::new(&local_ray, max_toi, &mut leaf_callback);
+            composite.qbvh().traverse_depth_first(&mut visitor);
+            closest.map(|(_, child)| child)
+        });
+
+        Some((handle, hit, child))
+    }
+
+    /// Find the projection of a point on the closest collider, with the child shape the point
+    /// is projected on if the collider has a composite shape.
+    ///
+    /// The child is `None` if the closest collider doesn’t have a composite shape. The
+    /// parameters are the same as for [`Self::project_point`].
+    pub fn project_point_and_get_child(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        point: &Point<Real>,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, PointProjection, Option<ChildShapeHit>)> {
+        let (handle, proj) = self.project_point(bodies, colliders, point, solid, filter)?;
+        let co = &colliders[handle];
+        let child = co.shape.as_composite_shape().and_then(|composite| {
+            // The projection lies on the child the point is projected on, so only the children
+            // with a bounding box containing the projection need to be tested.
+            let local_point = co.pos.inverse_transform_point(point);
+            let local_proj = co.pos.inverse_transform_point(&proj.point);
+            let proj_aabb = Aabb::new(local_proj, local_proj).loosened(1.0e-3);
+            let mut closest: Option<(Real, ChildShapeHit)> = None;
+            let mut leaf_callback = |child_id: &u32| {
+                composite.map_part_at(*child_id, &mut |pose, shape, _| {
+                    let child_point = pose
+                        .map(|pose| pose.inverse_transform_point(&local_point))
+                        .unwrap_or(local_point);
+                    let dist = shape.distance_to_local_point(&child_point, solid);
+                    if closest.map(|(best, _)| dist < best).unwrap_or(true) {
+                        closest = Some((dist, ChildShapeHit::new(co, *child_id, pose)));
+                    }
+                });
+                true
+            };
+
+            let mut visitor =
+                BoundingVolumeIntersectionsVisitor::new(&proj_aabb, &mut leaf_callback);
+            composite.qbvh().traverse_depth_first(&mut visitor);
+            closest.map(|(_, child)| child)
+        });
+
+        Some((handle, proj, child))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::geometry::{ColliderBuilder, ColliderSet, Ray, SharedShape};
+    use crate::math::{Isometry, Point, Real, Translation, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline};
+    use crate::prelude::RigidBodySet;
+
+    #[test]
+    fn ray_casts_report_the_compound_child_hit() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let children = vec![
+            (
+                Isometry::from(Translation::from(-Vector::x() * 2.0)),
+                SharedShape::ball(0.5),
+            ),
+            (
+                Isometry::from(Translation::from(Vector::x() * 2.0)),
+                SharedShape::ball(0.5),
+            ),
+        ];
+        let handle = colliders.insert(ColliderBuilder::compound(children));
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let dir = -Vector::y();
+        let origin = Point::from(Vector::x() * 2.0 + Vector::y() * 5.0);
+        let (hit_handle, _, child) = query_pipeline
+            .cast_ray_and_get_child(
+                &bodies,
+                &colliders,
+                &Ray::new(origin, dir),
+                Real::MAX,
+                true,
+                QueryFilter::default(),
+            )
+            .unwrap();
+        let child = child.unwrap();
+        assert_eq!(hit_handle, handle);
+        assert_eq!(child.child_id, 1);
+        assert!((child.child_pose.translation.vector - Vector::x() * 2.0).norm() < 1.0e-5);
+
+        let (_, _, child) = query_pipeline
+            .project_point_and_get_child(
+                &bodies,
+                &colliders,
+                &Point::from(-Vector::x() * 3.0),
+                true,
+                QueryFilter::default(),
+            )
+            .unwrap();
+        assert_eq!(child.unwrap().child_id, 0);
+    }
+}
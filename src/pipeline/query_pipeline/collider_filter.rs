@@ -0,0 +1,191 @@
+use crate::dynamics::{RigidBodyHandle, RigidBodySet};
+use crate::geometry::{Collider, ColliderHandle, InteractionGroups};
+use crate::pipeline::{QueryFilter, QueryFilterFlags};
+use parry::utils::hashmap::HashMap;
+use std::sync::Arc;
+
+type ColliderPredicate = Arc<dyn Fn(ColliderHandle, &Collider) -> bool + Send + Sync>;
+
+/// An owned filter describing what colliders should be included or excluded from scene queries.
+///
+/// Unlike a [`QueryFilter`], which borrows its predicate, a `ColliderFilter` owns everything it
+/// needs so it can be built once, stored (e.g. in a game entity), cloned cheaply, and reused
+/// across many queries and character controller moves. Filters can be combined with
+/// [`ColliderFilter::and`]: a collider passes the combined filter only if it passes both.
+///
+/// It is given to a query through [`QueryFilter::from`] or [`QueryFilter::collider_filter`], the
+/// latter allowing to add per-query settings like a [`QueryBudget`](crate::pipeline::QueryBudget):
+/// ```ignore
+/// let filter = ColliderFilter::new()
+///     .flags(QueryFilterFlags::EXCLUDE_SENSORS)
+///     .exclude_rigid_body(player)
+///     .user_data_predicate(|user_data| user_data != DEBRIS);
+/// query_pipeline.cast_ray(&bodies, &colliders, &ray, max_toi, true, (&filter).into());
+/// ```
+#[derive(Clone, Default)]
+pub struct ColliderFilter {
+    /// Flags indicating what particular type of colliders should be excluded.
+    pub flags: QueryFilterFlags,
+    /// Only the colliders with collision groups compatible with all of these are included.
+    pub groups: Vec<InteractionGroups>,
+    /// The colliders excluded, used as a set.
+    pub excluded_colliders: HashMap<ColliderHandle, ()>,
+    /// The rigid-bodies whose colliders are excluded, used as a set.
+    pub excluded_rigid_bodies: HashMap<RigidBodyHandle, ()>,
+    predicates: Vec<ColliderPredicate>,
+}
+
+impl ColliderFilter {
+    /// A filter that doesn’t exclude any collider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Excludes from the queries the whole sets of colliders identified by `flags`, in addition to
+    /// the ones already excluded by [`Self::flags`].
+    pub fn flags(mut self, flags: QueryFilterFlags) -> Self {
+        self.flags |= flags;
+        self
+    }
+
+    /// Only includes the colliders with collision groups compatible with `groups`, in addition to
+    /// the groups already required.
+    pub fn groups(mut self, groups: InteractionGroups) -> Self {
+        self.groups.push(groups);
+        self
+    }
+
+    /// Excludes the given collider from the queries.
+    pub fn exclude_collider(mut self, collider: ColliderHandle) -> Self {
+        let _ = self.excluded_colliders.insert(collider, ());
+        self
+    }
+
+    /// Excludes all the given colliders from the queries.
+    pub fn exclude_colliders(
+        mut self,
+        colliders: impl IntoIterator<Item = ColliderHandle>,
+    ) -> Self {
+        self.excluded_colliders
+            .extend(colliders.into_iter().map(|handle| (handle, ())));
+        self
+    }
+
+    /// Excludes all the colliders attached to the given rigid-body from the queries.
+    pub fn exclude_rigid_body(mut self, rigid_body: RigidBodyHandle) -> Self {
+        let _ = self.excluded_rigid_bodies.insert(rigid_body, ());
+        self
+    }
+
+    /// Excludes all the colliders attached to any of the given rigid-bodies from the queries.
+    pub fn exclude_rigid_bodies(
+        mut self,
+        rigid_bodies: impl IntoIterator<Item = RigidBodyHandle>,
+    ) -> Self {
+        self.excluded_rigid_bodies
+            .extend(rigid_bodies.into_iter().map(|handle| (handle, ())));
+        self
+    }
+
+    /// Excludes the colliders for which `predicate` returns `false`.
+    pub fn predicate(
+        mut self,
+        predicate: impl Fn(ColliderHandle, &Collider) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    /// Excludes the colliders for which `predicate`, called on their
+    /// [`Collider::user_data`], returns `false`.
+    pub fn user_data_predicate(
+        self,
+        predicate: impl Fn(u128) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicate(move |_, co| predicate(co.user_data))
+    }
+
+    /// Combines this filter with `other`: the result only includes the colliders included by
+    /// both filters.
+    pub fn and(mut self, other: &ColliderFilter) -> Self {
+        self.flags |= other.flags;
+        self.groups.extend_from_slice(&other.groups);
+        self.excluded_colliders
+            .extend(other.excluded_colliders.keys().map(|handle| (*handle, ())));
+        self.excluded_rigid_bodies.extend(
+            other
+                .excluded_rigid_bodies
+                .keys()
+                .map(|handle| (*handle, ())),
+        );
+        self.predicates.extend_from_slice(&other.predicates);
+        self
+    }
+
+    /// Applies this filter to a collider to determine if it has to be included in a scene
+    /// query (`true`) or not (`false`).
+    #[inline]
+    pub fn test(&self, bodies: &RigidBodySet, handle: ColliderHandle, collider: &Collider) -> bool {
+        !self.excluded_colliders.contains_key(&handle)
+            && collider
+                .parent
+                .map(|p| !self.excluded_rigid_bodies.contains_key(&p.handle))
+                .unwrap_or(true)
+            && self
+                .groups
+                .iter()
+                .all(|groups| collider.flags.collision_groups.test(*groups))
+            && self.flags.test(bodies, collider)
+            && self.predicates.iter().all(|f| f(handle, collider))
+    }
+}
+
+impl<'a> From<&'a ColliderFilter> for QueryFilter<'a> {
+    fn from(filter: &'a ColliderFilter) -> Self {
+        QueryFilter::new().collider_filter(filter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ColliderFilter;
+    use crate::dynamics::{RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::{ColliderBuilder, ColliderSet, Group, InteractionGroups};
+    use crate::pipeline::{QueryFilter, QueryFilterFlags};
+
+    #[test]
+    fn combined_filters_include_colliders_passing_both() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let player = bodies.insert(RigidBodyBuilder::dynamic());
+        let player_co =
+            colliders.insert_with_parent(ColliderBuilder::ball(0.5), player, &mut bodies);
+        let sensor = colliders.insert(ColliderBuilder::ball(0.5).sensor(true));
+        let debris = colliders.insert(ColliderBuilder::ball(0.5).user_data(42));
+        let other_group = colliders.insert(
+            ColliderBuilder::ball(0.5)
+                .collision_groups(InteractionGroups::new(Group::GROUP_2, Group::GROUP_2)),
+        );
+        let wall = colliders.insert(ColliderBuilder::ball(0.5));
+
+        let base = ColliderFilter::new()
+            .flags(QueryFilterFlags::EXCLUDE_SENSORS)
+            .groups(InteractionGroups::new(Group::GROUP_1, Group::GROUP_1));
+        let filter = base
+            .clone()
+            .exclude_rigid_body(player)
+            .and(&ColliderFilter::new().user_data_predicate(|user_data| user_data != 42));
+
+        let included = |filter: QueryFilter| {
+            [player_co, sensor, debris, other_group, wall]
+                .map(|handle| filter.test(&bodies, handle, &colliders[handle]))
+        };
+
+        assert_eq!(included((&base).into()), [true, false, true, false, true]);
+        assert_eq!(
+            included((&filter).into()),
+            [false, false, false, false, true]
+        );
+    }
+}
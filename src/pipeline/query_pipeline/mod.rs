@@ -1,8 +1,30 @@
+mod child_shape;
+mod collider_filter;
 pub mod generators;
-
+mod occlusion;
+mod pick;
+mod query_budget;
+mod shape_cast_context;
+mod subscriptions;
+#[cfg(feature = "dim3")]
+mod walkable_surfaces;
+
+pub use self::child_shape::ChildShapeHit;
+pub use self::collider_filter::ColliderFilter;
+pub use self::occlusion::{Occluder, Occlusion};
+pub use self::pick::{PickHit, PickOptions};
+pub use self::query_budget::QueryBudget;
+pub use self::shape_cast_context::ShapeCastContext;
+pub use self::subscriptions::{Subscription, SubscriptionHandle, SubscriptionVolume};
+#[cfg(feature = "dim3")]
+pub use self::walkable_surfaces::WalkableSurfaceOptions;
+
+use crate::data::arena::Arena;
+use crate::data::Coarena;
 use crate::dynamics::RigidBodyHandle;
 use crate::geometry::{
-    Aabb, Collider, ColliderHandle, InteractionGroups, PointProjection, Qbvh, Ray, RayIntersection,
+    Aabb, BoundingVolume, Collider, ColliderHandle, ContactManifold, InteractionGroups,
+    PointProjection, Qbvh, Ray, RayIntersection,
 };
 use crate::math::{Isometry, Point, Real, Vector};
 use crate::{dynamics::RigidBodySet, geometry::ColliderSet};
@@ -16,7 +38,10 @@ use parry::query::details::{
 use parry::query::visitors::{
     BoundingVolumeIntersectionsVisitor, PointIntersectionsVisitor, RayIntersectionsVisitor,
 };
-use parry::query::{DefaultQueryDispatcher, NonlinearRigidMotion, QueryDispatcher, ShapeCastHit};
+use parry::query::{
+    DefaultQueryDispatcher, NonlinearRigidMotion, PersistentQueryDispatcher, QueryDispatcher,
+    ShapeCastHit,
+};
 use parry::shape::{FeatureId, Shape, TypedSimdCompositeShape};
 use std::sync::Arc;
 
@@ -33,8 +58,21 @@ pub struct QueryPipeline {
     dilation_factor: Real,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     workspace: QbvhUpdateWorkspace,
+    generation: QueryPipelineGeneration,
+    subscriptions: Arena<Subscription>,
+    // Stored apart from the colliders so the queries can test them without loading the colliders.
+    proxy_user_flags: Coarena<u32>,
 }
 
+/// Identifies the state of the world a [`QueryPipeline`] was last updated with.
+///
+/// The generation changes every time the query pipeline is updated. Systems caching query
+/// results across frames can store the generation current at the time of the query, and
+/// compare it with [`QueryPipeline::generation`] later to detect stale results.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QueryPipelineGeneration(pub u64);
+
 struct QueryPipelineAsCompositeShape<'a> {
     query_pipeline: &'a QueryPipeline,
     bodies: &'a RigidBodySet,
@@ -43,6 +81,7 @@ struct QueryPipelineAsCompositeShape<'a> {
 }
 
 bitflags::bitflags! {
+    #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
     #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
     /// Flags for excluding whole sets of colliders from a scene query.
     pub struct QueryFilterFlags: u32 {
@@ -117,6 +156,16 @@ pub struct QueryFilter<'a> {
     /// If set, any collider for which this closure returns false will be excluded from the scene query.
     #[allow(clippy::type_complexity)] // Type doesn’t look really complex?
     pub predicate: Option<&'a dyn Fn(ColliderHandle, &Collider) -> bool>,
+    /// If set, the number of candidate colliders tested by the scene query is bounded by this budget.
+    pub budget: Option<&'a QueryBudget>,
+    /// If set, any collider excluded by this reusable filter will be excluded from the scene query.
+    pub collider_filter: Option<&'a ColliderFilter>,
+    /// If set, only the colliders with [`QueryPipeline::proxy_user_flags`] sharing at least one bit
+    /// with this mask are included in the scene query.
+    ///
+    /// This is tested by the queries of the [`QueryPipeline`] before accessing the colliders, but
+    /// ignored by [`QueryFilter::test`].
+    pub proxy_user_flags: Option<u32>,
 }
 
 impl<'a> QueryFilter<'a> {
@@ -133,6 +182,12 @@ impl<'a> QueryFilter<'a> {
                 .unwrap_or(true)
             && self.flags.test(bodies, collider)
             && self.predicate.map(|f| f(handle, collider)).unwrap_or(true)
+            && self
+                .collider_filter
+                .map(|f| f.test(bodies, handle, collider))
+                .unwrap_or(true)
+            // NOTE: check the budget last so only colliders actually tested consume it.
+            && self.budget.map(|budget| budget.consume()).unwrap_or(true)
     }
 }
 
@@ -227,6 +282,25 @@ impl<'a> QueryFilter<'a> {
         self.predicate = Some(predicate);
         self
     }
+
+    /// Set the budget bounding the number of candidate colliders tested by the scene query.
+    pub fn budget(mut self, budget: &'a QueryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set the reusable filter applied on top of the other settings of this query filter.
+    pub fn collider_filter(mut self, filter: &'a ColliderFilter) -> Self {
+        self.collider_filter = Some(filter);
+        self
+    }
+
+    /// Only include the colliders with [`QueryPipeline::proxy_user_flags`] sharing at least one
+    /// bit with `mask`.
+    pub fn proxy_user_flags(mut self, mask: u32) -> Self {
+        self.proxy_user_flags = Some(mask);
+        self
+    }
 }
 
 impl<'a> TypedSimdCompositeShape for QueryPipelineAsCompositeShape<'a> {
@@ -243,6 +317,13 @@ impl<'a> TypedSimdCompositeShape for QueryPipelineAsCompositeShape<'a> {
             Option<&Self::PartNormalConstraints>,
         ),
     ) {
+        if !self
+            .query_pipeline
+            .test_proxy_user_flags(shape_id, &self.filter)
+        {
+            return;
+        }
+
         if let Some(co) = self.colliders.get(shape_id) {
             if self.filter.test(self.bodies, shape_id, co) {
                 f(Some(&co.pos), &*co.shape, None)
@@ -302,9 +383,68 @@ impl QueryPipeline {
             qbvh: Qbvh::new(),
             dilation_factor: 0.01,
             workspace: QbvhUpdateWorkspace::default(),
+            generation: QueryPipelineGeneration::default(),
+            subscriptions: Arena::new(),
+            proxy_user_flags: Coarena::new(),
         }
     }
 
+    /// The current generation of this query pipeline.
+    ///
+    /// This is incremented by each call to [`QueryPipeline::update`],
+    /// [`QueryPipeline::update_incremental`], or [`QueryPipeline::update_with_generator`].
+    /// All the scene queries performed between two updates refer to the same generation.
+    pub fn generation(&self) -> QueryPipelineGeneration {
+        self.generation
+    }
+
+    /// Runs a scene query and returns its result together with the generation of the
+    /// state of the world it refers to.
+    ///
+    /// The returned generation can be compared with [`QueryPipeline::generation`] later to
+    /// check whether the cached result still refers to the current state of the world.
+    pub fn with_generation<T>(
+        &self,
+        query: impl FnOnce(&Self) -> T,
+    ) -> (T, QueryPipelineGeneration) {
+        (query(self), self.generation)
+    }
+
+    fn increment_generation(&mut self) {
+        self.generation.0 = self.generation.0.wrapping_add(1);
+    }
+
+    /// The user-defined bitmask of the given collider, tested by the queries with a
+    /// [`QueryFilter::proxy_user_flags`] mask before accessing the collider.
+    ///
+    /// This is `0` for the colliders without flags set.
+    pub fn proxy_user_flags(&self, collider: ColliderHandle) -> u32 {
+        self.proxy_user_flags.get(collider.0).copied().unwrap_or(0)
+    }
+
+    /// Sets the user-defined bitmask of the given collider.
+    ///
+    /// The flags are stored by this query pipeline, in a compact array indexed by collider
+    /// handles, so queries only including a few colliders (e.g. cover markers) can discard all
+    /// the others without loading them from the [`ColliderSet`]. They are reset when the collider
+    /// is given as removed to [`QueryPipeline::update_incremental`].
+    pub fn set_proxy_user_flags(&mut self, collider: ColliderHandle, flags: u32) {
+        self.proxy_user_flags.insert(collider.0, flags);
+    }
+
+    /// Tests the proxy user flags of the given collider against the mask of `filter`.
+    #[inline]
+    pub(crate) fn test_proxy_user_flags(
+        &self,
+        handle: ColliderHandle,
+        filter: &QueryFilter,
+    ) -> bool {
+        filter
+            .proxy_user_flags
+            .map(|mask| self.proxy_user_flags(handle) & mask != 0)
+            .unwrap_or(true)
+    }
+
     /// The query dispatcher used by this query pipeline for running scene queries.
     pub fn query_dispatcher(&self) -> &dyn QueryDispatcher {
         &*self.query_dispatcher
@@ -326,6 +466,7 @@ impl QueryPipeline {
         // a collider was added/modified and then removed during the same frame).
         for removed in removed_colliders {
             self.qbvh.remove(*removed);
+            let _ = self.proxy_user_flags.remove(removed.0, 0);
         }
 
         for modified in modified_colliders {
@@ -335,12 +476,16 @@ impl QueryPipeline {
             }
         }
 
+        self.update_subscriptions_incremental(colliders, modified_colliders, removed_colliders);
+
         if refit_and_rebalance {
             let _ = self.qbvh.refit(0.0, &mut self.workspace, |handle| {
                 colliders[*handle].compute_aabb()
             });
             self.qbvh.rebalance(0.0, &mut self.workspace);
         }
+
+        self.increment_generation();
     }
 
     /// Update the acceleration structure on the query pipeline.
@@ -356,7 +501,8 @@ impl QueryPipeline {
     /// See [`generators`] for available generators.
     #[profiling::function]
     pub fn update_with_generator(&mut self, mode: impl QbvhDataGenerator<ColliderHandle>) {
-        self.qbvh.clear_and_rebuild(mode, self.dilation_factor);
+        self.rebuild_with_generator_and_update_subscriptions(mode);
+        self.increment_generation();
     }
 
     /// Find the closest intersection between a ray and a set of colliders.
@@ -419,6 +565,42 @@ impl QueryPipeline {
         self.qbvh.traverse_best_first(&mut visitor).map(|h| h.1)
     }
 
+    /// Find the closest intersection between a ray and a set of colliders, with a smooth normal
+    /// at the hit point in addition to the normal of the shape.
+    ///
+    /// On triangle meshes with pseudo-normals, the smooth normal is interpolated from the normals
+    /// of the vertices of the triangle hit, see [`Collider::smooth_normal_at_point`]. It is
+    /// oriented toward the same side as the normal of the triangle. On any other shape, the
+    /// smooth normal is equal to [`RayIntersection::normal`].
+    ///
+    /// The parameters are the same as for [`Self::cast_ray_and_get_normal`].
+    #[cfg(feature = "dim3")]
+    #[profiling::function]
+    pub fn cast_ray_and_get_smooth_normal(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        ray: &Ray,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, RayIntersection, Vector<Real>)> {
+        let (handle, hit) =
+            self.cast_ray_and_get_normal(bodies, colliders, ray, max_toi, solid, filter)?;
+        let smooth_normal = colliders[handle]
+            .smooth_normal_at_point(&ray.point_at(hit.time_of_impact))
+            .map(|normal| {
+                if normal.dot(&hit.normal) < 0.0 {
+                    -normal.into_inner()
+                } else {
+                    normal.into_inner()
+                }
+            })
+            .unwrap_or(hit.normal);
+
+        Some((handle, hit, smooth_normal))
+    }
+
     /// Find the all intersections between a ray and a set of colliders and passes them to a callback.
     ///
     /// # Parameters
@@ -445,6 +627,10 @@ impl QueryPipeline {
         mut callback: impl FnMut(ColliderHandle, RayIntersection) -> bool,
     ) {
         let mut leaf_callback = &mut |handle: &ColliderHandle| {
+            if !self.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
             if let Some(co) = colliders.get(*handle) {
                 if filter.test(bodies, *handle, co) {
                     if let Some(hit) = co
@@ -463,6 +649,84 @@ impl QueryPipeline {
         self.qbvh.traverse_depth_first(&mut visitor);
     }
 
+    /// Find the closest intersection between a ray and a set of colliders, within the given
+    /// world-space distance from the ray origin.
+    ///
+    /// Unlike [`QueryPipeline::cast_ray`], the direction of the ray doesn’t need to be
+    /// normalized: it is normalized automatically and the returned value is the world-space
+    /// distance between the ray origin and the hit point. Returns `None` if the ray direction
+    /// is zero.
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `ray`: the ray to cast.
+    /// * `max_distance`: the maximum distance, in world units, from the ray origin at which a hit
+    ///   can be reported. Use `Real::MAX` for an unbounded ray.
+    /// * `solid`: if this is `true` an impact at distance 0.0 (i.e. at the ray origin) is returned if
+    ///            it starts inside of a shape. If this `false` then the ray will hit the shape's boundary
+    ///            even if its starts inside of it.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    pub fn cast_ray_within_distance(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        ray: &Ray,
+        max_distance: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, Real)> {
+        let ray = normalized_ray(ray)?;
+        self.cast_ray(bodies, colliders, &ray, max_distance, solid, filter)
+    }
+
+    /// Find the closest intersection between a ray and a set of colliders, within the given
+    /// world-space distance from the ray origin.
+    ///
+    /// This is the same as [`QueryPipeline::cast_ray_within_distance`] except that the normal
+    /// at the hit point is computed too. The `time_of_impact` of the returned intersection is
+    /// the world-space distance between the ray origin and the hit point.
+    pub fn cast_ray_and_get_normal_within_distance(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        ray: &Ray,
+        max_distance: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, RayIntersection)> {
+        let ray = normalized_ray(ray)?;
+        self.cast_ray_and_get_normal(bodies, colliders, &ray, max_distance, solid, filter)
+    }
+
+    /// Find the all intersections between a ray and a set of colliders, within the given
+    /// world-space distance from the ray origin, and passes them to a callback.
+    ///
+    /// This is the same as [`QueryPipeline::intersections_with_ray`] except that the ray direction
+    /// is normalized automatically. The `time_of_impact` of each intersection given to the
+    /// callback is the world-space distance between the ray origin and the hit point.
+    pub fn intersections_with_ray_within_distance<'a>(
+        &self,
+        bodies: &'a RigidBodySet,
+        colliders: &'a ColliderSet,
+        ray: &Ray,
+        max_distance: Real,
+        solid: bool,
+        filter: QueryFilter,
+        callback: impl FnMut(ColliderHandle, RayIntersection) -> bool,
+    ) {
+        if let Some(ray) = normalized_ray(ray) {
+            self.intersections_with_ray(
+                bodies,
+                colliders,
+                &ray,
+                max_distance,
+                solid,
+                filter,
+                callback,
+            )
+        }
+    }
+
     /// Gets the handle of up to one collider intersecting the given shape.
     ///
     /// # Parameters
@@ -543,6 +807,10 @@ impl QueryPipeline {
         mut callback: impl FnMut(ColliderHandle) -> bool,
     ) {
         let mut leaf_callback = &mut |handle: &ColliderHandle| {
+            if !self.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
             if let Some(co) = colliders.get(*handle) {
                 if filter.test(bodies, *handle, co) && co.shape.contains_point(&co.pos, point) {
                     return callback(*handle);
@@ -637,6 +905,239 @@ impl QueryPipeline {
         self.qbvh.traverse_best_first(&mut visitor).map(|h| h.1)
     }
 
+    /// Casts a shape along a direction and retrieve the first collider it hits within the given
+    /// world-space distance.
+    ///
+    /// This is the same as [`QueryPipeline::cast_shape`] except that `shape_dir` is normalized
+    /// automatically, the maximum travel is given in world units by `max_distance` (the
+    /// `max_time_of_impact` of the given `options` is ignored), and the `time_of_impact` of the
+    /// returned hit is the world-space distance traveled by the shape before the impact.
+    /// Returns `None` if the direction is zero.
+    pub fn cast_shape_within_distance(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        shape_pos: &Isometry<Real>,
+        shape_dir: &Vector<Real>,
+        shape: &dyn Shape,
+        max_distance: Real,
+        options: ShapeCastOptions,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, ShapeCastHit)> {
+        let shape_dir = shape_dir.try_normalize(0.0)?;
+        let options = ShapeCastOptions {
+            max_time_of_impact: max_distance,
+            ..options
+        };
+        self.cast_shape(
+            bodies, colliders, shape_pos, &shape_dir, shape, options, filter,
+        )
+    }
+
+    /// Casts a shape at a constant linear velocity and retrieve the first collider it hits,
+    /// reusing the candidate colliders cached in `context` by previous casts when possible.
+    ///
+    /// This gives the same result as [`QueryPipeline::cast_shape`], but is faster when casting
+    /// the same shape repeatedly in the same region of space (for example for a character
+    /// controller sweeping its shape several times per frame). See [`ShapeCastContext`] for
+    /// details on the caching strategy.
+    ///
+    /// If `options.max_time_of_impact` is unbounded, the context is not used and this falls
+    /// back to [`QueryPipeline::cast_shape`].
+    #[profiling::function]
+    pub fn cast_shape_with_context(
+        &self,
+        context: &mut ShapeCastContext,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        shape_pos: &Isometry<Real>,
+        shape_vel: &Vector<Real>,
+        shape: &dyn Shape,
+        options: ShapeCastOptions,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, ShapeCastHit)> {
+        let displacement = shape_vel * options.max_time_of_impact;
+        if !displacement.iter().all(|x| x.is_finite()) {
+            return self.cast_shape(
+                bodies, colliders, shape_pos, shape_vel, shape, options, filter,
+            );
+        }
+
+        let start_aabb = shape.compute_aabb(shape_pos);
+        let end_aabb = Aabb::new(
+            start_aabb.mins + displacement,
+            start_aabb.maxs + displacement,
+        );
+        let swept_aabb = start_aabb
+            .merged(&end_aabb)
+            .loosened(options.target_distance);
+
+        if !context.is_valid_for(&swept_aabb, self.generation) {
+            let region = swept_aabb.loosened(context.margin);
+            context.candidates.clear();
+            self.colliders_with_aabb_intersecting_aabb(&region, |handle| {
+                context.candidates.push(*handle);
+                true
+            });
+            context.region = Some(region);
+            context.generation = self.generation;
+        }
+
+        let mut best_hit: Option<(ColliderHandle, ShapeCastHit)> = None;
+
+        for handle in &context.candidates {
+            if !self.test_proxy_user_flags(*handle, &filter) {
+                continue;
+            }
+
+            let Some(co) = colliders.get(*handle) else {
+                continue;
+            };
+
+            if !filter.test(bodies, *handle, co) {
+                continue;
+            }
+
+            let max_time_of_impact = best_hit
+                .as_ref()
+                .map(|(_, hit)| hit.time_of_impact)
+                .unwrap_or(options.max_time_of_impact);
+            let pos12 = co.pos.inv_mul(shape_pos);
+            let local_vel12 = co.pos.inverse_transform_vector(shape_vel);
+            let local_options = ShapeCastOptions {
+                max_time_of_impact,
+                ..options
+            };
+
+            if let Ok(Some(hit)) = self.query_dispatcher.cast_shapes(
+                &pos12,
+                &local_vel12,
+                &*co.shape,
+                shape,
+                local_options,
+            ) {
+                if best_hit
+                    .as_ref()
+                    .map(|(_, best)| hit.time_of_impact < best.time_of_impact)
+                    .unwrap_or(true)
+                {
+                    best_hit = Some((*handle, hit.transform1_by(&co.pos)));
+                }
+            }
+        }
+
+        best_hit
+    }
+
+    /// Casts a shape at a constant linear velocity against colliders that are moving too, and
+    /// retrieve the first collider it hits.
+    ///
+    /// This is similar to [`QueryPipeline::cast_shape`] except that each collider attached to a
+    /// moving rigid-body is assumed to move at the constant linear velocity of its rigid-body
+    /// (evaluated at the collider’s position) during the cast. The returned time-of-impact is
+    /// the earliest time at which the cast shape and one of the colliders touch, taking both
+    /// motions into account. This is useful for projectile interception or catch prediction.
+    ///
+    /// The angular velocities of the rigid-bodies are ignored. In the resulting hit, witness
+    /// and normal 1 refer to the world collider at the time of impact, and are in world space.
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `shape_pos` - The initial position of the shape to cast.
+    /// * `shape_vel` - The constant velocity of the shape to cast (i.e. the cast direction).
+    /// * `shape` - The shape to cast.
+    /// * `options` - Options controlling the shape-cast, including its maximum time-of-impact.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    #[profiling::function]
+    pub fn cast_shape_against_moving(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        shape_pos: &Isometry<Real>,
+        shape_vel: &Vector<Real>,
+        shape: &dyn Shape,
+        options: ShapeCastOptions,
+        filter: QueryFilter,
+    ) -> Option<(ColliderHandle, ShapeCastHit)> {
+        let is_moving = |co: &Collider| {
+            co.parent
+                .and_then(|p| bodies.get(p.handle))
+                .map(|rb| !rb.is_fixed() && *rb.linvel() != Vector::zeros())
+                .unwrap_or(false)
+        };
+
+        // First, cast against the non-moving colliders, leveraging the acceleration structure.
+        let static_predicate = |handle: ColliderHandle, co: &Collider| {
+            !is_moving(co) && filter.predicate.map(|f| f(handle, co)).unwrap_or(true)
+        };
+        let static_filter = QueryFilter {
+            predicate: Some(&static_predicate),
+            ..filter
+        };
+        let mut best_hit = self.cast_shape(
+            bodies,
+            colliders,
+            shape_pos,
+            shape_vel,
+            shape,
+            options,
+            static_filter,
+        );
+
+        // Then, cast against each moving collider using the relative velocity.
+        for (_, rb) in bodies.iter() {
+            if rb.is_fixed() || *rb.linvel() == Vector::zeros() {
+                continue;
+            }
+
+            for handle in rb.colliders() {
+                if !self.test_proxy_user_flags(*handle, &filter) {
+                    continue;
+                }
+
+                let Some(co) = colliders.get(*handle) else {
+                    continue;
+                };
+
+                if !co.is_enabled() || !filter.test(bodies, *handle, co) {
+                    continue;
+                }
+
+                let max_time_of_impact = best_hit
+                    .as_ref()
+                    .map(|(_, hit)| hit.time_of_impact)
+                    .unwrap_or(options.max_time_of_impact);
+                let target_vel = rb.velocity_at_point(&Point::from(co.pos.translation.vector));
+                let pos12 = co.pos.inv_mul(shape_pos);
+                let local_vel12 = co.pos.inverse_transform_vector(&(shape_vel - target_vel));
+                let local_options = ShapeCastOptions {
+                    max_time_of_impact,
+                    ..options
+                };
+
+                if let Ok(Some(hit)) = self.query_dispatcher.cast_shapes(
+                    &pos12,
+                    &local_vel12,
+                    &*co.shape,
+                    shape,
+                    local_options,
+                ) {
+                    if best_hit
+                        .as_ref()
+                        .map(|(_, best)| hit.time_of_impact < best.time_of_impact)
+                        .unwrap_or(true)
+                    {
+                        let mut hit = hit.transform1_by(&co.pos);
+                        hit.witness1 += target_vel * hit.time_of_impact;
+                        best_hit = Some((*handle, hit));
+                    }
+                }
+            }
+        }
+
+        best_hit
+    }
+
     /// Casts a shape with an arbitrary continuous motion and retrieve the first collider it hits.
     ///
     /// In the resulting `TOI`, witness and normal 1 refer to the world collider, and are in world
@@ -706,6 +1207,10 @@ impl QueryPipeline {
         let inv_shape_pos = shape_pos.inverse();
 
         let mut leaf_callback = &mut |handle: &ColliderHandle| {
+            if !self.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
             if let Some(co) = colliders.get(*handle) {
                 if filter.test(bodies, *handle, co) {
                     let pos12 = inv_shape_pos * co.pos.as_ref();
@@ -724,4 +1229,120 @@ impl QueryPipeline {
 
         self.qbvh.traverse_depth_first(&mut visitor);
     }
+
+    /// Computes the contact manifolds between the given shape and all the colliders it is
+    /// touching (or closer than `prediction` from), and passes them to a callback.
+    ///
+    /// Unlike [`QueryPipeline::intersections_with_shape`] this yields the full contact
+    /// manifolds (with possibly several contact points, their normals and penetration depths)
+    /// for each collider, which is useful for implementing custom character or vehicle
+    /// solvers on top of the scene queries.
+    ///
+    /// For each manifold, the first shape is the given `shape` and the second shape is the
+    /// collider’s shape. Contact points and normals are expressed in their respective local
+    /// spaces (see [`ContactManifold::local_n1`] and [`ContactManifold::subshape_pos1`]).
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `shape_pos` - The position of the shape to test.
+    /// * `shape` - The shape to test.
+    /// * `prediction` - Contacts with a distance smaller than this value are reported too,
+    ///   even if the shapes do not touch.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    /// * `callback` - A function called with the handle of each collider with at least one
+    ///   contact with `shape`, and the corresponding contact manifolds. If this returns
+    ///   `false`, this method exits early.
+    #[profiling::function]
+    pub fn contact_manifolds_with_shape(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        shape_pos: &Isometry<Real>,
+        shape: &dyn Shape,
+        prediction: Real,
+        filter: QueryFilter,
+        mut callback: impl FnMut(ColliderHandle, &[ContactManifold]) -> bool,
+    ) {
+        // TODO: allow custom dispatchers.
+        let dispatcher = DefaultQueryDispatcher;
+        let mut manifolds: Vec<ContactManifold> = vec![];
+
+        let mut leaf_callback = &mut |handle: &ColliderHandle| {
+            if !self.test_proxy_user_flags(*handle, &filter) {
+                return true;
+            }
+
+            if let Some(co) = colliders.get(*handle) {
+                if filter.test(bodies, *handle, co) {
+                    manifolds.clear();
+                    let pos12 = shape_pos.inv_mul(&co.pos);
+                    let _ = dispatcher.contact_manifolds(
+                        &pos12,
+                        shape,
+                        &*co.shape,
+                        prediction,
+                        &mut manifolds,
+                        &mut None,
+                    );
+
+                    manifolds.retain(|manifold| !manifold.points.is_empty());
+
+                    if !manifolds.is_empty() {
+                        return callback(*handle, &manifolds);
+                    }
+                }
+            }
+
+            true
+        };
+
+        let shape_aabb = shape.compute_aabb(shape_pos).loosened(prediction);
+        let mut visitor = BoundingVolumeIntersectionsVisitor::new(&shape_aabb, &mut leaf_callback);
+
+        self.qbvh.traverse_depth_first(&mut visitor);
+    }
+}
+
+/// Returns a copy of `ray` with a unit direction, or `None` if its direction is zero.
+fn normalized_ray(ray: &Ray) -> Option<Ray> {
+    ray.dir
+        .try_normalize(0.0)
+        .map(|dir| Ray::new(ray.origin, dir))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{QueryFilter, QueryPipeline};
+    use crate::dynamics::RigidBodySet;
+    use crate::geometry::{ColliderBuilder, ColliderSet, Ray};
+    use crate::math::{Point, Real, Vector};
+
+    #[test]
+    fn queries_skip_colliders_without_matching_proxy_user_flags() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let near = colliders.insert(ColliderBuilder::ball(0.5).translation(Vector::x() * 2.0));
+        let far = colliders.insert(ColliderBuilder::ball(0.5).translation(Vector::x() * 4.0));
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        const COVER: u32 = 1 << 3;
+        query_pipeline.set_proxy_user_flags(far, COVER);
+
+        let ray = Ray::new(Point::origin(), Vector::x());
+        let hit = |filter| {
+            query_pipeline
+                .cast_ray(&bodies, &colliders, &ray, Real::MAX, true, filter)
+                .map(|(handle, _)| handle)
+        };
+        assert_eq!(hit(QueryFilter::default()), Some(near));
+        assert_eq!(
+            hit(QueryFilter::default().proxy_user_flags(COVER)),
+            Some(far)
+        );
+        assert_eq!(hit(QueryFilter::default().proxy_user_flags(!COVER)), None);
+
+        query_pipeline.update_incremental(&colliders, &[], &[far], true);
+        assert_eq!(query_pipeline.proxy_user_flags(far), 0);
+    }
 }
@@ -0,0 +1,157 @@
+use crate::dynamics::RigidBodySet;
+use crate::geometry::{ColliderHandle, ColliderSet, Ray};
+use crate::math::{Point, Real};
+use crate::pipeline::{QueryFilter, QueryPipeline};
+
+/// A collider crossed by the segment of an occlusion query.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Occluder {
+    /// The crossed collider.
+    pub collider: ColliderHandle,
+    /// The distance from the start of the segment to the point where it enters the collider.
+    pub entry_distance: Real,
+    /// The length of the part of the segment inside of the collider.
+    ///
+    /// This is the distance between the first point where the segment enters the collider and
+    /// the last point where it exits it. For non-convex shapes, this includes the gaps between
+    /// the parts of the shape crossed by the segment.
+    pub thickness: Real,
+}
+
+/// The result of [`QueryPipeline::occlusion_between`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Occlusion {
+    /// The sum of the thicknesses of all the occluders.
+    pub total_thickness: Real,
+    /// All the colliders crossed by the segment, sorted by increasing entry distance.
+    pub occluders: Vec<Occluder>,
+}
+
+impl QueryPipeline {
+    /// Computes the colliders crossed by the segment between `start` and `end`, and the
+    /// thickness of each of them.
+    ///
+    /// This is intended for computing the occlusion or obstruction of a sound traveling between
+    /// a source and a listener, e.g., by attenuating the sound depending on the
+    /// material and thickness of each occluder. The material of each occluder isn’t tracked
+    /// by Rapier and can be retrieved from the occluding collider, e.g., through its
+    /// [`Collider::user_data`](crate::geometry::Collider::user_data).
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `start`: the start of the segment, e.g., the sound source.
+    /// * `end`: the end of the segment, e.g., the listener.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this
+    ///   scene query. Sensors are typically excluded with [`QueryFilter::exclude_sensors`].
+    pub fn occlusion_between(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        start: &Point<Real>,
+        end: &Point<Real>,
+        filter: QueryFilter,
+    ) -> Occlusion {
+        let mut result = Occlusion::default();
+        let length = na::distance(start, end);
+
+        if length == 0.0 {
+            return result;
+        }
+
+        let dir = (end - start) / length;
+        let ray = Ray::new(*start, dir);
+        let reverse_ray = Ray::new(*end, -dir);
+
+        self.intersections_with_ray(
+            bodies,
+            colliders,
+            &ray,
+            length,
+            true,
+            filter,
+            |handle, hit| {
+                let entry_distance = hit.time_of_impact;
+                // Cast the ray back from the end of the segment to find where it exits the collider.
+                let exit_distance = colliders[handle]
+                    .shape()
+                    .cast_ray(
+                        colliders[handle].position(),
+                        &reverse_ray,
+                        length - entry_distance,
+                        true,
+                    )
+                    .map(|toi| length - toi)
+                    .unwrap_or(length);
+                let thickness = (exit_distance - entry_distance).max(0.0);
+
+                result.total_thickness += thickness;
+                result.occluders.push(Occluder {
+                    collider: handle,
+                    entry_distance,
+                    thickness,
+                });
+                true
+            },
+        );
+
+        result
+            .occluders
+            .sort_by(|a, b| a.entry_distance.total_cmp(&b.entry_distance));
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Occlusion;
+    use crate::dynamics::RigidBodySet;
+    use crate::geometry::{ColliderBuilder, ColliderSet};
+    use crate::math::{Point, Real, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline};
+
+    #[test]
+    fn occluders_are_sorted_with_their_thickness() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let far = colliders.insert(ColliderBuilder::ball(1.0).translation(Vector::x() * 6.0));
+        let near = colliders.insert(ColliderBuilder::ball(0.5).translation(Vector::x() * 3.0));
+        colliders.insert(
+            ColliderBuilder::ball(0.5)
+                .translation(Vector::x() * 4.5)
+                .sensor(true),
+        );
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let occlusion = |end: Point<Real>| {
+            query_pipeline.occlusion_between(
+                &bodies,
+                &colliders,
+                &Point::origin(),
+                &end,
+                QueryFilter::default().exclude_sensors(),
+            )
+        };
+
+        let result = occlusion(Point::from(Vector::x() * 10.0));
+        let occluders: Vec<_> = result
+            .occluders
+            .iter()
+            .map(|o| (o.collider, o.entry_distance, o.thickness))
+            .collect();
+        assert_eq!(occluders.len(), 2);
+        assert_eq!((occluders[0].0, occluders[1].0), (near, far));
+        approx::assert_relative_eq!(occluders[0].1, 2.5, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(occluders[0].2, 1.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(occluders[1].1, 5.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(occluders[1].2, 2.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(result.total_thickness, 3.0, epsilon = 1.0e-5);
+
+        // Segments ending inside of a collider only account for the part they cross.
+        let result = occlusion(Point::from(Vector::x() * 6.0));
+        approx::assert_relative_eq!(result.occluders[1].thickness, 1.0, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(result.total_thickness, 2.0, epsilon = 1.0e-5);
+
+        assert_eq!(occlusion(Point::origin()), Occlusion::default());
+    }
+}
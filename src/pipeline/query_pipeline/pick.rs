@@ -0,0 +1,205 @@
+use crate::dynamics::{MouseJointBuilder, RigidBodyHandle, RigidBodySet};
+use crate::geometry::{Collider, ColliderHandle, ColliderSet, Ray};
+use crate::math::{Isometry, Point, Real};
+use crate::pipeline::{QueryFilter, QueryFilterFlags, QueryPipeline};
+use na::RealField;
+
+/// Settings of the mouse joint suggested by [`QueryPipeline::pick`].
+///
+/// These are independent from the mass of the picked body: the actual spring constants and max
+/// force are scaled by the picked body’s mass.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickOptions {
+    /// The oscillation frequency, in Hertz, of the grabbed point around the target
+    /// (default: `5.0`).
+    pub frequency: Real,
+    /// The damping ratio of the grabbed point’s motion, `1.0` being critically damped
+    /// (default: `1.0`).
+    pub damping_ratio: Real,
+    /// The maximum acceleration the mouse joint can apply to the picked body (default: `1000.0`).
+    pub max_acceleration: Real,
+}
+
+impl Default for PickOptions {
+    fn default() -> Self {
+        Self {
+            frequency: 5.0,
+            damping_ratio: 1.0,
+            max_acceleration: 1000.0,
+        }
+    }
+}
+
+/// The result of [`QueryPipeline::pick`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickHit {
+    /// The picked collider.
+    pub collider: ColliderHandle,
+    /// The dynamic rigid-body the picked collider is attached to.
+    pub body: RigidBodyHandle,
+    /// The time of impact of the ray on the picked collider.
+    pub time_of_impact: Real,
+    /// The picked point, in world-space.
+    pub world_point: Point<Real>,
+    /// The picked point, in the local-space of the picked rigid-body.
+    pub local_grab_point: Point<Real>,
+    /// The suggested stiffness of the mouse joint dragging the picked body.
+    pub stiffness: Real,
+    /// The suggested damping of the mouse joint dragging the picked body.
+    pub damping: Real,
+    /// The suggested max force of the mouse joint dragging the picked body.
+    pub max_force: Real,
+}
+
+impl PickHit {
+    /// A mouse joint dragging the picked body with the suggested parameters.
+    ///
+    /// The first rigid-body this joint is attached to holds the target. Its current position
+    /// `target_body_pos` is needed to initialize the target to the picked point.
+    pub fn mouse_joint(&self, target_body_pos: &Isometry<Real>) -> MouseJointBuilder {
+        MouseJointBuilder::new(self.stiffness, self.damping, self.max_force)
+            .local_target(target_body_pos.inverse_transform_point(&self.world_point))
+            .local_grab_point(self.local_grab_point)
+    }
+}
+
+impl QueryPipeline {
+    /// Finds the dynamic rigid-body hit first by a ray, e.g., cast from the mouse cursor, and
+    /// computes the parameters for dragging it with a [`MouseJoint`](crate::dynamics::MouseJoint).
+    ///
+    /// Only solid colliders attached to a dynamic rigid-body with a non-zero mass can be picked,
+    /// in addition to the rules of `filter`.
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `ray`: the ray to cast.
+    /// * `max_toi`: the maximum time-of-impact that can be reported by this cast. This effectively
+    ///   limits the length of the ray to `ray.dir.norm() * max_toi`. Use `Real::MAX` for an unbounded ray.
+    /// * `options`: the settings of the suggested mouse joint.
+    /// * `filter`: set of rules used to determine which collider can be picked.
+    pub fn pick(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        ray: &Ray,
+        max_toi: Real,
+        options: &PickOptions,
+        filter: QueryFilter,
+    ) -> Option<PickHit> {
+        let user_predicate = filter.predicate;
+        let predicate = |handle: ColliderHandle, co: &Collider| {
+            co.parent()
+                .map(|parent| bodies[parent].mass() > 0.0)
+                .unwrap_or(false)
+                && user_predicate.map(|f| f(handle, co)).unwrap_or(true)
+        };
+        let filter = QueryFilter {
+            flags: filter.flags
+                | QueryFilterFlags::ONLY_DYNAMIC
+                | QueryFilterFlags::EXCLUDE_SENSORS,
+            predicate: Some(&predicate),
+            ..filter
+        };
+
+        let (collider, time_of_impact) =
+            self.cast_ray(bodies, colliders, ray, max_toi, true, filter)?;
+        let body = colliders[collider].parent()?;
+        let rb = &bodies[body];
+        let world_point = ray.point_at(time_of_impact);
+
+        let mass = rb.mass();
+        let angular_frequency = Real::two_pi() * options.frequency;
+
+        Some(PickHit {
+            collider,
+            body,
+            time_of_impact,
+            world_point,
+            local_grab_point: rb.position().inverse_transform_point(&world_point),
+            stiffness: mass * angular_frequency * angular_frequency,
+            damping: 2.0 * mass * options.damping_ratio * angular_frequency,
+            max_force: mass * options.max_acceleration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PickOptions;
+    use crate::dynamics::{RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::{ColliderBuilder, ColliderSet, Ray};
+    use crate::math::{Isometry, Point, Real, Translation, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline};
+    use na::RealField;
+
+    #[test]
+    fn pick_skips_fixed_massless_and_sensor_colliders() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        colliders.insert(ColliderBuilder::ball(0.5).translation(Vector::x() * 2.0));
+
+        let picked = bodies.insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 5.0));
+        let picked_co =
+            colliders.insert_with_parent(ColliderBuilder::ball(0.5), picked, &mut bodies);
+        colliders.insert_with_parent(
+            ColliderBuilder::ball(0.5)
+                .translation(Vector::x() * -2.0)
+                .sensor(true),
+            picked,
+            &mut bodies,
+        );
+
+        let massless = bodies.insert(RigidBodyBuilder::dynamic().translation(Vector::x() * 4.0));
+        colliders.insert_with_parent(
+            ColliderBuilder::ball(0.1).density(0.0),
+            massless,
+            &mut bodies,
+        );
+
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let ray = Ray::new(Point::origin(), Vector::x());
+        let options = PickOptions::default();
+        let hit = query_pipeline
+            .pick(
+                &bodies,
+                &colliders,
+                &ray,
+                Real::MAX,
+                &options,
+                QueryFilter::default(),
+            )
+            .unwrap();
+
+        let mass = bodies[picked].mass();
+        let angular_frequency = Real::two_pi() * options.frequency;
+        assert_eq!((hit.collider, hit.body), (picked_co, picked));
+        approx::assert_relative_eq!(hit.time_of_impact, 4.5, epsilon = 1.0e-5);
+        approx::assert_relative_eq!(
+            hit.local_grab_point,
+            Point::from(-Vector::x() * 0.5),
+            epsilon = 1.0e-5
+        );
+        approx::assert_relative_eq!(
+            hit.stiffness,
+            mass * angular_frequency * angular_frequency,
+            max_relative = 1.0e-5
+        );
+        approx::assert_relative_eq!(
+            hit.damping,
+            2.0 * mass * angular_frequency,
+            max_relative = 1.0e-5
+        );
+        approx::assert_relative_eq!(hit.max_force, mass * 1000.0, max_relative = 1.0e-5);
+
+        let target_body_pos = Isometry::from(Translation::from(Vector::y() * 3.0));
+        let joint = hit.mouse_joint(&target_body_pos).build();
+        approx::assert_relative_eq!(
+            target_body_pos * joint.local_target(),
+            hit.world_point,
+            epsilon = 1.0e-5
+        );
+        assert_eq!(joint.local_grab_point(), hit.local_grab_point);
+    }
+}
@@ -0,0 +1,130 @@
+use std::cell::Cell;
+
+#[cfg(doc)]
+use crate::pipeline::{QueryFilter, QueryPipeline};
+
+/// A bound on the number of candidate colliders a scene query is allowed to test.
+///
+/// A budget is attached to a scene query with [`QueryFilter::budget`]. Every candidate collider
+/// found by traversing the [`QueryPipeline`] consumes one unit of the budget before being tested
+/// against the query. Once the budget is exhausted, the remaining candidates are skipped and
+/// the query returns the best result found so far.
+///
+/// Use [`QueryBudget::is_exhausted`] after the query to know whether its result is partial.
+/// This lets systems running thousands of queries per frame trade accuracy for a bounded cost.
+#[derive(Clone, Debug)]
+pub struct QueryBudget {
+    max_candidates: usize,
+    candidates_visited: Cell<usize>,
+    exhausted: Cell<bool>,
+}
+
+impl QueryBudget {
+    /// Creates a budget allowing up to `max_candidates` colliders to be tested.
+    pub fn new(max_candidates: usize) -> Self {
+        Self {
+            max_candidates,
+            candidates_visited: Cell::new(0),
+            exhausted: Cell::new(false),
+        }
+    }
+
+    /// The maximum number of candidate colliders that can be tested.
+    pub fn max_candidates(&self) -> usize {
+        self.max_candidates
+    }
+
+    /// The number of candidate colliders tested so far.
+    pub fn candidates_visited(&self) -> usize {
+        self.candidates_visited.get()
+    }
+
+    /// Did a query skip some candidates because this budget was exhausted?
+    ///
+    /// If `true`, the result of the query is partial: a closer hit (or another intersection)
+    /// may have been missed.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.get()
+    }
+
+    /// Resets this budget so it can be reused for another query.
+    pub fn reset(&self) {
+        self.candidates_visited.set(0);
+        self.exhausted.set(false);
+    }
+
+    /// Consumes one unit of this budget, returning `false` if it was already exhausted.
+    #[inline]
+    pub(crate) fn consume(&self) -> bool {
+        let visited = self.candidates_visited.get();
+        if visited >= self.max_candidates {
+            self.exhausted.set(true);
+            false
+        } else {
+            self.candidates_visited.set(visited + 1);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QueryBudget;
+    use crate::dynamics::RigidBodySet;
+    use crate::geometry::{ColliderBuilder, ColliderSet, Ray};
+    use crate::math::{Point, Real, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline};
+
+    #[test]
+    fn budget_is_exhausted_after_max_candidates() {
+        let budget = QueryBudget::new(2);
+        assert!(budget.consume());
+        assert!(budget.consume());
+        assert!(!budget.is_exhausted());
+        assert!(!budget.consume());
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.candidates_visited(), 2);
+
+        budget.reset();
+        assert!(!budget.is_exhausted());
+        assert_eq!(budget.candidates_visited(), 0);
+        assert_eq!(budget.max_candidates(), 2);
+    }
+
+    #[test]
+    fn exhausted_budget_skips_the_remaining_candidates() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        for i in 1..=3 {
+            colliders.insert(ColliderBuilder::ball(0.5).translation(Vector::x() * i as Real * 2.0));
+        }
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let ray = Ray::new(Point::origin(), Vector::x());
+        let num_hits = |filter: QueryFilter| {
+            let mut num_hits = 0;
+            query_pipeline.intersections_with_ray(
+                &bodies,
+                &colliders,
+                &ray,
+                Real::MAX,
+                true,
+                filter,
+                |_, _| {
+                    num_hits += 1;
+                    true
+                },
+            );
+            num_hits
+        };
+
+        let budget = QueryBudget::new(2);
+        assert_eq!(num_hits(QueryFilter::default().budget(&budget)), 2);
+        assert!(budget.is_exhausted());
+
+        let budget = QueryBudget::new(3);
+        assert_eq!(num_hits(QueryFilter::default().budget(&budget)), 3);
+        assert!(!budget.is_exhausted());
+    }
+}
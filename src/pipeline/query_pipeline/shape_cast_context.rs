@@ -0,0 +1,149 @@
+use crate::geometry::{Aabb, BoundingVolume, ColliderHandle};
+use crate::math::Real;
+use crate::pipeline::QueryPipelineGeneration;
+
+#[cfg(doc)]
+use crate::pipeline::QueryPipeline;
+
+/// A reusable context for repeatedly casting a shape in the same region of space.
+///
+/// This is meant to be passed to [`QueryPipeline::cast_shape_with_context`] by systems
+/// casting the same shape many times, like character controllers. The context caches the
+/// colliders potentially hit in a region enclosing the last cast (enlarged by a margin), so
+/// that subsequent casts contained in that region skip the traversal of the query pipeline’s
+/// acceleration structure.
+///
+/// The cached candidates are automatically discarded whenever the query pipeline is updated
+/// (see [`QueryPipeline::generation`]) or when a cast leaves the cached region.
+#[derive(Clone, Debug, Default)]
+pub struct ShapeCastContext {
+    pub(super) margin: Real,
+    pub(super) region: Option<Aabb>,
+    pub(super) generation: QueryPipelineGeneration,
+    pub(super) candidates: Vec<ColliderHandle>,
+}
+
+impl ShapeCastContext {
+    /// Creates an empty shape-cast context.
+    ///
+    /// The `margin` is the distance by which the region enclosing a cast is enlarged when
+    /// caching its candidate colliders. Larger margins allow the shape to travel farther
+    /// before the cache needs to be refreshed, at the cost of more candidates to test.
+    pub fn new(margin: Real) -> Self {
+        Self {
+            margin,
+            ..Default::default()
+        }
+    }
+
+    /// The margin by which the cached region is enlarged.
+    pub fn margin(&self) -> Real {
+        self.margin
+    }
+
+    /// The colliders that were potentially hit in the cached region.
+    pub fn candidates(&self) -> &[ColliderHandle] {
+        &self.candidates
+    }
+
+    /// Discards the cached candidates, forcing the next cast to traverse the query pipeline.
+    ///
+    /// There is generally no need to call this manually since the cache is discarded
+    /// automatically when the query pipeline is updated.
+    pub fn invalidate(&mut self) {
+        self.region = None;
+        self.candidates.clear();
+    }
+
+    pub(super) fn is_valid_for(&self, region: &Aabb, generation: QueryPipelineGeneration) -> bool {
+        self.generation == generation
+            && self
+                .region
+                .map(|cached| cached.contains(region))
+                .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ShapeCastContext;
+    use crate::dynamics::RigidBodySet;
+    use crate::geometry::{Aabb, Ball, BoundingVolume, ColliderBuilder, ColliderSet};
+    use crate::math::{Isometry, Point, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline, QueryPipelineGeneration};
+    use parry::query::details::ShapeCastOptions;
+
+    #[test]
+    fn context_matches_uncached_casts_and_refreshes_on_update() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let far = colliders.insert(ColliderBuilder::ball(1.0).translation(Vector::x() * 5.0));
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let shape = Ball::new(0.5);
+        let options = ShapeCastOptions {
+            max_time_of_impact: 10.0,
+            ..Default::default()
+        };
+        let mut context = ShapeCastContext::new(1.0);
+        let cast = |query_pipeline: &QueryPipeline,
+                    colliders: &ColliderSet,
+                    context: &mut ShapeCastContext| {
+            let cached = query_pipeline.cast_shape_with_context(
+                context,
+                &bodies,
+                colliders,
+                &Isometry::identity(),
+                &Vector::x(),
+                &shape,
+                options,
+                QueryFilter::default(),
+            );
+            let uncached = query_pipeline.cast_shape(
+                &bodies,
+                colliders,
+                &Isometry::identity(),
+                &Vector::x(),
+                &shape,
+                options,
+                QueryFilter::default(),
+            );
+            assert_eq!(
+                cached.map(|(handle, hit)| (handle, hit.time_of_impact)),
+                uncached.map(|(handle, hit)| (handle, hit.time_of_impact))
+            );
+            cached.map(|(handle, _)| handle)
+        };
+
+        assert_eq!(cast(&query_pipeline, &colliders, &mut context), Some(far));
+        assert_eq!(context.candidates(), &[far]);
+
+        // The cached candidates are discarded once the query pipeline is updated.
+        let near = colliders.insert(ColliderBuilder::ball(1.0).translation(Vector::x() * 2.5));
+        query_pipeline.update(&colliders);
+        assert_eq!(cast(&query_pipeline, &colliders, &mut context), Some(near));
+        assert_eq!(context.candidates().len(), 2);
+
+        context.invalidate();
+        assert!(context.candidates().is_empty());
+        assert_eq!(context.margin(), 1.0);
+    }
+
+    #[test]
+    fn context_is_only_valid_within_its_region_and_generation() {
+        let mut context = ShapeCastContext::new(0.5);
+        let region = Aabb::new(
+            Point::from(Vector::repeat(-1.0)),
+            Point::from(Vector::repeat(1.0)),
+        );
+        let inner = region.tightened(0.5);
+        let generation = context.generation;
+        assert!(!context.is_valid_for(&inner, generation));
+
+        context.region = Some(region);
+        assert!(context.is_valid_for(&inner, generation));
+        assert!(!context.is_valid_for(&region.loosened(0.1), generation));
+        assert!(!context.is_valid_for(&inner, QueryPipelineGeneration(generation.0 + 1)));
+    }
+}
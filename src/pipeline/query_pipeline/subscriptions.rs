@@ -0,0 +1,376 @@
+use crate::data::arena::Index;
+use crate::geometry::{Aabb, BoundingVolume, ColliderHandle, ColliderSet};
+use crate::math::{Point, Real, Vector};
+use crate::pipeline::QueryPipeline;
+use parry::partitioning::QbvhDataGenerator;
+use std::collections::HashSet;
+
+/// The unique identifier of a subscription added to a [`QueryPipeline`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[repr(transparent)]
+pub struct SubscriptionHandle(pub Index);
+
+impl SubscriptionHandle {
+    /// Converts this handle into its (index, generation) components.
+    pub fn into_raw_parts(self) -> (u32, u32) {
+        self.0.into_raw_parts()
+    }
+
+    /// Reconstructs an handle from its (index, generation) components.
+    pub fn from_raw_parts(id: u32, generation: u32) -> Self {
+        Self(Index::from_raw_parts(id, generation))
+    }
+
+    /// An always-invalid subscription handle.
+    pub fn invalid() -> Self {
+        Self(Index::from_raw_parts(
+            crate::INVALID_U32,
+            crate::INVALID_U32,
+        ))
+    }
+}
+
+/// The region of space watched by a [`Subscription`].
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SubscriptionVolume {
+    /// A world-space axis-aligned box.
+    Aabb(Aabb),
+    /// A world-space ball.
+    Ball {
+        /// The center of the ball.
+        center: Point<Real>,
+        /// The radius of the ball.
+        radius: Real,
+    },
+}
+
+impl SubscriptionVolume {
+    /// The smallest [`Aabb`] containing this volume.
+    pub fn aabb(&self) -> Aabb {
+        match self {
+            Self::Aabb(aabb) => *aabb,
+            Self::Ball { center, radius } => Aabb::new(
+                center - Vector::repeat(*radius),
+                center + Vector::repeat(*radius),
+            ),
+        }
+    }
+
+    /// Does this volume intersect the given [`Aabb`]?
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        match self {
+            Self::Aabb(volume) => volume.intersects(aabb),
+            Self::Ball { center, radius } => {
+                let closest = center.sup(&aabb.mins).inf(&aabb.maxs);
+                na::distance_squared(&closest, center) <= radius * radius
+            }
+        }
+    }
+}
+
+/// A persistent query tracking the colliders overlapping a region of space, e.g., the area of
+/// interest of a network client.
+///
+/// A collider is inside the subscription if its [`Aabb`] intersects the subscription’s volume.
+/// The colliders entering or leaving the subscription are accumulated each time the
+/// [`QueryPipeline`] is updated, until [`QueryPipeline::clear_subscription_changes`] is called.
+/// A collider entering and leaving the subscription between two clears isn’t reported at all.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    volume: SubscriptionVolume,
+    inside: HashSet<ColliderHandle>,
+    entered: Vec<ColliderHandle>,
+    left: Vec<ColliderHandle>,
+}
+
+impl Subscription {
+    fn new(volume: SubscriptionVolume) -> Self {
+        Self {
+            volume,
+            inside: HashSet::new(),
+            entered: vec![],
+            left: vec![],
+        }
+    }
+
+    /// The region of space watched by this subscription.
+    pub fn volume(&self) -> &SubscriptionVolume {
+        &self.volume
+    }
+
+    /// All the colliders currently inside this subscription, in no particular order.
+    pub fn inside(&self) -> impl ExactSizeIterator<Item = ColliderHandle> + '_ {
+        self.inside.iter().copied()
+    }
+
+    /// Is the given collider currently inside this subscription?
+    pub fn contains(&self, collider: ColliderHandle) -> bool {
+        self.inside.contains(&collider)
+    }
+
+    /// The colliders that entered this subscription since the last clear, in the order they
+    /// entered it.
+    pub fn entered(&self) -> &[ColliderHandle] {
+        &self.entered
+    }
+
+    /// The colliders that left this subscription since the last clear, in the order they left
+    /// it. This includes the colliders removed from the collider set.
+    pub fn left(&self) -> &[ColliderHandle] {
+        &self.left
+    }
+
+    fn set_inside(&mut self, collider: ColliderHandle, inside: bool) {
+        let (changed, undone, done) = if inside {
+            (
+                self.inside.insert(collider),
+                &mut self.left,
+                &mut self.entered,
+            )
+        } else {
+            (
+                self.inside.remove(&collider),
+                &mut self.entered,
+                &mut self.left,
+            )
+        };
+
+        if changed {
+            // A change undoing the previous one since the last clear cancels it out.
+            if let Some(i) = undone.iter().position(|h| *h == collider) {
+                let _ = undone.remove(i);
+            } else {
+                done.push(collider);
+            }
+        }
+    }
+
+    fn replace_inside(&mut self, new_inside: HashSet<ColliderHandle>) {
+        let exited: Vec<_> = self.inside.difference(&new_inside).copied().collect();
+
+        for collider in exited {
+            self.set_inside(collider, false);
+        }
+
+        for collider in new_inside {
+            self.set_inside(collider, true);
+        }
+    }
+}
+
+/// Generator forwarding the AABBs of another generator while recording them.
+struct RecordingGenerator<'a, G> {
+    generator: G,
+    aabbs: &'a mut Vec<(ColliderHandle, Aabb)>,
+}
+
+impl<'a, G: QbvhDataGenerator<ColliderHandle>> QbvhDataGenerator<ColliderHandle>
+    for RecordingGenerator<'a, G>
+{
+    fn size_hint(&self) -> usize {
+        self.generator.size_hint()
+    }
+
+    #[inline(always)]
+    fn for_each(&mut self, mut f: impl FnMut(ColliderHandle, Aabb)) {
+        self.generator.for_each(|handle, aabb| {
+            self.aabbs.push((handle, aabb));
+            f(handle, aabb)
+        })
+    }
+}
+
+impl QueryPipeline {
+    /// Starts tracking the colliders overlapping the given volume.
+    ///
+    /// The colliders initially inside the volume are reported as entering the subscription.
+    /// They are found with the acceleration structure of this query pipeline, so it must be up
+    /// to date with `colliders`.
+    pub fn add_subscription(
+        &mut self,
+        colliders: &ColliderSet,
+        volume: SubscriptionVolume,
+    ) -> SubscriptionHandle {
+        let handle = SubscriptionHandle(self.subscriptions.insert(Subscription::new(volume)));
+        self.set_subscription_volume(colliders, handle, volume);
+        handle
+    }
+
+    /// Stops tracking the colliders of a subscription.
+    pub fn remove_subscription(&mut self, handle: SubscriptionHandle) -> Option<Subscription> {
+        self.subscriptions.remove(handle.0)
+    }
+
+    /// Moves or resizes the volume of a subscription, e.g., when the network client it belongs
+    /// to moves.
+    ///
+    /// The colliders entering or leaving the subscription because of this change are reported
+    /// the same way as the ones moving across its volume. Nothing happens if the subscription
+    /// doesn’t exist.
+    pub fn set_subscription_volume(
+        &mut self,
+        colliders: &ColliderSet,
+        handle: SubscriptionHandle,
+        volume: SubscriptionVolume,
+    ) {
+        if !self.subscriptions.contains(handle.0) {
+            return;
+        }
+
+        let mut new_inside = HashSet::new();
+        self.colliders_with_aabb_intersecting_aabb(&volume.aabb(), |collider| {
+            if let Some(co) = colliders.get(*collider) {
+                if co.is_enabled() && volume.intersects_aabb(&co.compute_aabb()) {
+                    let _ = new_inside.insert(*collider);
+                }
+            }
+            true
+        });
+
+        if let Some(subscription) = self.subscriptions.get_mut(handle.0) {
+            subscription.volume = volume;
+            subscription.replace_inside(new_inside);
+        }
+    }
+
+    /// The subscription identified by the given handle.
+    pub fn subscription(&self, handle: SubscriptionHandle) -> Option<&Subscription> {
+        self.subscriptions.get(handle.0)
+    }
+
+    /// Iterates through all the subscriptions of this query pipeline.
+    pub fn subscriptions(&self) -> impl Iterator<Item = (SubscriptionHandle, &Subscription)> {
+        self.subscriptions
+            .iter()
+            .map(|(handle, subscription)| (SubscriptionHandle(handle), subscription))
+    }
+
+    /// Forgets the colliders that entered or left all the subscriptions, typically after they
+    /// were sent to their network clients.
+    pub fn clear_subscription_changes(&mut self) {
+        for (_, subscription) in self.subscriptions.iter_mut() {
+            subscription.entered.clear();
+            subscription.left.clear();
+        }
+    }
+
+    /// Updates the subscriptions after the given colliders were modified or removed.
+    ///
+    /// Each modified collider is tested against every subscription.
+    pub(super) fn update_subscriptions_incremental(
+        &mut self,
+        colliders: &ColliderSet,
+        modified_colliders: &[ColliderHandle],
+        removed_colliders: &[ColliderHandle],
+    ) {
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        for removed in removed_colliders {
+            for (_, subscription) in self.subscriptions.iter_mut() {
+                subscription.set_inside(*removed, false);
+            }
+        }
+
+        for modified in modified_colliders {
+            // The collider may have been removed after being modified.
+            let aabb = colliders
+                .get(*modified)
+                .map(|co| co.is_enabled().then(|| co.compute_aabb()));
+            let Some(aabb) = aabb else {
+                continue;
+            };
+
+            for (_, subscription) in self.subscriptions.iter_mut() {
+                let inside = aabb
+                    .map(|aabb| subscription.volume.intersects_aabb(&aabb))
+                    .unwrap_or(false);
+                subscription.set_inside(*modified, inside);
+            }
+        }
+    }
+
+    /// Rebuilds the acceleration structure with the given generator, and updates the
+    /// subscriptions using the AABBs it generates.
+    pub(super) fn rebuild_with_generator_and_update_subscriptions(
+        &mut self,
+        generator: impl QbvhDataGenerator<ColliderHandle>,
+    ) {
+        if self.subscriptions.is_empty() {
+            self.qbvh.clear_and_rebuild(generator, self.dilation_factor);
+            return;
+        }
+
+        let mut aabbs = vec![];
+        let recorder = RecordingGenerator {
+            generator,
+            aabbs: &mut aabbs,
+        };
+        self.qbvh.clear_and_rebuild(recorder, self.dilation_factor);
+
+        for (_, subscription) in self.subscriptions.iter_mut() {
+            let new_inside = aabbs
+                .iter()
+                .filter(|(_, aabb)| subscription.volume.intersects_aabb(aabb))
+                .map(|(handle, _)| *handle)
+                .collect();
+            subscription.replace_inside(new_inside);
+        }
+    }
+}
+
+#[cfg(all(feature = "dim3", feature = "f32"))]
+#[cfg(test)]
+mod test {
+    use super::SubscriptionVolume;
+    use crate::prelude::*;
+
+    #[test]
+    fn subscriptions_report_entering_and_leaving_colliders() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut islands = IslandManager::new();
+        let mut queries = QueryPipeline::new();
+
+        let inside = colliders.insert(ColliderBuilder::ball(0.5));
+        let outside =
+            colliders.insert(ColliderBuilder::ball(0.5).translation(vector![10.0, 0.0, 0.0]));
+        queries.update(&colliders);
+
+        let subscription = queries.add_subscription(
+            &colliders,
+            SubscriptionVolume::Ball {
+                center: point![2.0, 0.0, 0.0],
+                radius: 2.0,
+            },
+        );
+        assert_eq!(
+            queries.subscription(subscription).unwrap().entered(),
+            &[inside]
+        );
+        queries.clear_subscription_changes();
+
+        // Move the second ball into the subscription, and remove the first one.
+        colliders[outside].set_translation(vector![3.0, 0.0, 0.0]);
+        let _ = colliders.remove(inside, &mut islands, &mut bodies, true);
+        queries.update_incremental(&colliders, &[outside], &[inside], true);
+
+        let sub = queries.subscription(subscription).unwrap();
+        assert_eq!(sub.entered(), &[outside]);
+        assert_eq!(sub.left(), &[inside]);
+        assert!(sub.contains(outside) && !sub.contains(inside));
+
+        // Moving the subscription away is reported too, and cancels the unread entering.
+        let volume =
+            SubscriptionVolume::Aabb(Aabb::new(point![-10.0, -1.0, -1.0], point![-8.0, 1.0, 1.0]));
+        queries.set_subscription_volume(&colliders, subscription, volume);
+        let sub = queries.subscription(subscription).unwrap();
+        assert!(sub.entered().is_empty());
+        assert_eq!(sub.left(), &[inside]);
+        assert_eq!(sub.inside().len(), 0);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::dynamics::RigidBodySet;
+use crate::geometry::{ColliderSet, Ray, Shape, Triangle, TypedShape};
+use crate::math::{Isometry, Point, Real, UnitVector, Vector};
+use crate::pipeline::{QueryFilter, QueryPipeline};
+use na::RealField;
+
+/// Criteria for selecting the walkable triangles extracted by
+/// [`QueryPipeline::walkable_triangles`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WalkableSurfaceOptions {
+    /// The up direction (default: `+Y`).
+    pub up: UnitVector<Real>,
+    /// The maximum angle, in radians, between the normal of a walkable triangle and the up
+    /// direction (default: `π / 4`).
+    pub max_slope_angle: Real,
+    /// The minimum free height above the center of a walkable triangle (default: `2.0`).
+    pub min_clearance: Real,
+}
+
+impl Default for WalkableSurfaceOptions {
+    fn default() -> Self {
+        Self {
+            up: Vector::y_axis(),
+            max_slope_angle: Real::frac_pi_4(),
+            min_clearance: 2.0,
+        }
+    }
+}
+
+fn append_world_triangles(
+    shape: &dyn Shape,
+    pos: &Isometry<Real>,
+    up: &UnitVector<Real>,
+    out: &mut Vec<Triangle>,
+) {
+    fn push_indexed(
+        (vertices, indices): (Vec<Point<Real>>, Vec<[u32; 3]>),
+        pos: &Isometry<Real>,
+        out: &mut Vec<Triangle>,
+    ) {
+        out.extend(indices.iter().map(|idx| {
+            Triangle::new(
+                vertices[idx[0] as usize],
+                vertices[idx[1] as usize],
+                vertices[idx[2] as usize],
+            )
+            .transformed(pos)
+        }))
+    }
+
+    match shape.as_typed_shape() {
+        TypedShape::Cuboid(s) => push_indexed(s.to_trimesh(), pos, out),
+        TypedShape::ConvexPolyhedron(s) => push_indexed(s.to_trimesh(), pos, out),
+        TypedShape::Triangle(s) => out.push(s.transformed(pos)),
+        // The winding of trimeshes and heightfields is arbitrary, so their triangles are
+        // flipped to face upward.
+        TypedShape::TriMesh(s) => out.extend(
+            s.triangles()
+                .map(|tri| face_upward(tri.transformed(pos), up)),
+        ),
+        TypedShape::HeightField(s) => out.extend(
+            s.triangles()
+                .map(|tri| face_upward(tri.transformed(pos), up)),
+        ),
+        TypedShape::Compound(s) => {
+            for (sub_pos, sub_shape) in s.shapes() {
+                append_world_triangles(&**sub_shape, &(pos * sub_pos), up, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn face_upward(tri: Triangle, up: &UnitVector<Real>) -> Triangle {
+    if tri.scaled_normal().dot(&**up) < 0.0 {
+        Triangle::new(tri.a, tri.c, tri.b)
+    } else {
+        tri
+    }
+}
+
+impl QueryPipeline {
+    /// Extracts the world-space triangles of the colliders the characters can walk on, e.g., as
+    /// the input of a navigation mesh generator.
+    ///
+    /// A triangle is walkable if the angle between its normal and `options.up` is smaller than
+    /// `options.max_slope_angle`, and if a ray cast upward from its center doesn’t hit any
+    /// collider within `options.min_clearance`. The query pipeline must be up-to-date
+    /// for this clearance test.
+    ///
+    /// Only the triangles of cuboids, convex polyhedra, triangles, trimeshes, heightfields,
+    /// and compounds of these shapes are extracted. Since only the center of each triangle is
+    /// tested for clearance, large triangles partially covered by an obstacle may be reported
+    /// as walkable.
+    ///
+    /// # Parameters
+    /// * `colliders` - The set of colliders taking part in this pipeline.
+    /// * `options`: the criteria for selecting the walkable triangles.
+    /// * `filter`: set of rules used to determine which collider is taken into account, both as
+    ///   a walkable surface and as an obstacle for the clearance test.
+    pub fn walkable_triangles(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        options: &WalkableSurfaceOptions,
+        filter: QueryFilter,
+    ) -> Vec<Triangle> {
+        let min_cos_slope = options.max_slope_angle.cos();
+        // Offset the clearance rays so they don’t hit the surface they start from.
+        let ray_offset = options.min_clearance * 1.0e-3;
+        let mut triangles = vec![];
+        let mut result = vec![];
+
+        for (handle, co) in colliders.iter() {
+            if !co.is_enabled()
+                || !self.test_proxy_user_flags(handle, &filter)
+                || !filter.test(bodies, handle, co)
+            {
+                continue;
+            }
+
+            triangles.clear();
+            append_world_triangles(co.shape(), co.position(), &options.up, &mut triangles);
+
+            for tri in &triangles {
+                let Some(normal) = tri.normal() else {
+                    continue; // Degenerate triangle.
+                };
+
+                if normal.dot(&*options.up) < min_cos_slope {
+                    continue;
+                }
+
+                let ray = Ray::new(tri.center() + *options.up * ray_offset, *options.up);
+                let blocked = self
+                    .cast_ray(
+                        bodies,
+                        colliders,
+                        &ray,
+                        options.min_clearance - ray_offset,
+                        true,
+                        filter,
+                    )
+                    .is_some();
+
+                if !blocked {
+                    result.push(*tri);
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WalkableSurfaceOptions;
+    use crate::dynamics::RigidBodySet;
+    use crate::geometry::{ColliderBuilder, ColliderSet, Triangle};
+    use crate::math::{Point, Real, Vector};
+    use crate::pipeline::{QueryFilter, QueryPipeline};
+    use na::point;
+
+    /// A horizontal triangle facing upward, with its first vertex at `origin`.
+    fn flat_triangle(origin: Point<Real>) -> Triangle {
+        Triangle::new(origin, origin + Vector::z(), origin + Vector::x())
+    }
+
+    #[test]
+    fn walkable_triangles_are_flat_enough_and_unobstructed() {
+        let bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let walkable = flat_triangle(point![0.0, 0.0, 0.0]);
+        let facing_down = flat_triangle(point![5.0, 0.0, 0.0]);
+        let steep = Triangle::new(
+            point![10.0, 0.0, 0.0],
+            point![10.0, 0.0, 1.0],
+            point![11.0, 2.0, 0.0],
+        );
+        let blocked = flat_triangle(point![15.0, 0.0, 0.0]);
+        let high_ceiling = flat_triangle(point![20.0, 0.0, 0.0]);
+        // Trimeshes are flipped to face upward regardless of their winding.
+        let flipped = flat_triangle(point![25.0, 0.0, 0.0]);
+
+        for tri in [walkable, steep, blocked, high_ceiling] {
+            colliders.insert(ColliderBuilder::triangle(tri.a, tri.b, tri.c));
+        }
+        colliders.insert(ColliderBuilder::triangle(
+            facing_down.a,
+            facing_down.c,
+            facing_down.b,
+        ));
+        colliders.insert(ColliderBuilder::trimesh(
+            vec![flipped.a, flipped.b, flipped.c],
+            vec![[0, 2, 1]],
+        ));
+        colliders
+            .insert(ColliderBuilder::ball(0.3).translation(blocked.center().coords + Vector::y()));
+        colliders.insert(
+            ColliderBuilder::ball(0.3)
+                .translation(high_ceiling.center().coords + Vector::y() * 3.0),
+        );
+
+        let mut query_pipeline = QueryPipeline::new();
+        query_pipeline.update(&colliders);
+
+        let result = query_pipeline.walkable_triangles(
+            &bodies,
+            &colliders,
+            &WalkableSurfaceOptions::default(),
+            QueryFilter::default(),
+        );
+        let mut centers: Vec<_> = result.iter().map(|tri| tri.center()).collect();
+        centers.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        assert_eq!(centers.len(), 3);
+        for (center, expected) in centers.iter().zip([walkable, high_ceiling, flipped]) {
+            approx::assert_relative_eq!(*center, expected.center(), epsilon = 1.0e-5);
+        }
+        assert!(result
+            .iter()
+            .all(|tri| tri.scaled_normal().dot(&Vector::y()) > 0.0));
+    }
+}
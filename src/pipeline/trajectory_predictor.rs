@@ -0,0 +1,211 @@
+use crate::dynamics::{
+    IntegrationParameters, RigidBody, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
+};
+use crate::geometry::{Collider, ColliderBuilder, ColliderHandle, ColliderSet, SharedShape};
+use crate::math::{AngVector, Isometry, Real, Vector};
+use crate::pipeline::{PhysicsPipeline, PhysicsWorld};
+use parry::mass_properties::MassProperties;
+
+/// The colliders of the world a predicted trajectory can collide with.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TrajectoryObstacles {
+    /// The predicted body doesn’t collide with anything.
+    None,
+    /// The predicted body only collides with the colliders without parent or attached to a
+    /// fixed rigid-body.
+    #[default]
+    Fixed,
+    /// The predicted body collides with all the other colliders, as if they were fixed at their
+    /// current position.
+    All,
+}
+
+/// The result of a trajectory prediction.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PredictedTrajectory {
+    /// The position of the predicted body at the end of each simulated step.
+    pub positions: Vec<Isometry<Real>>,
+    /// The index, in `positions`, of the first step at the end of which the predicted body
+    /// touches an obstacle.
+    pub first_contact: Option<usize>,
+}
+
+/// Predicts the trajectory of a single body by simulating it in isolation from the rest of
+/// the world, e.g., for drawing the arc of a throw or placing a landing marker.
+///
+/// The predicted body is simulated alone in a separate world containing a copy of the
+/// obstacles selected by [`TrajectoryPredictor::obstacles`], so the prediction never
+/// affects the original simulation. The other dynamic bodies don’t move during the prediction.
+pub struct TrajectoryPredictor {
+    /// The gravity applied to the predicted body.
+    pub gravity: Vector<Real>,
+    /// The parameters of each simulated step.
+    pub integration_parameters: IntegrationParameters,
+    /// The number of steps simulated by each prediction.
+    pub num_steps: usize,
+    /// The colliders the predicted body can collide with.
+    pub obstacles: TrajectoryObstacles,
+    pipeline: PhysicsPipeline,
+}
+
+impl TrajectoryPredictor {
+    /// Creates a trajectory predictor simulating `num_steps` steps against the fixed colliders.
+    pub fn new(
+        gravity: Vector<Real>,
+        integration_parameters: IntegrationParameters,
+        num_steps: usize,
+    ) -> Self {
+        Self {
+            gravity,
+            integration_parameters,
+            num_steps,
+            obstacles: TrajectoryObstacles::Fixed,
+            pipeline: PhysicsPipeline::new(),
+        }
+    }
+
+    /// Sets the colliders the predicted body can collide with.
+    pub fn with_obstacles(mut self, obstacles: TrajectoryObstacles) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
+
+    /// Predicts the trajectory of the rigid-body `handle`, starting from its current state.
+    pub fn predict_body(
+        &mut self,
+        handle: RigidBodyHandle,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+    ) -> PredictedTrajectory {
+        let rb = &bodies[handle];
+        let body_colliders: Vec<Collider> = rb
+            .colliders()
+            .iter()
+            .map(|co_handle| colliders[*co_handle].clone())
+            .collect();
+
+        self.predict(rb.clone(), body_colliders, Some(handle), bodies, colliders)
+    }
+
+    /// Predicts the trajectory of a standalone shape with the given mass properties, initial
+    /// position, and initial velocities.
+    pub fn predict_shape(
+        &mut self,
+        shape: SharedShape,
+        mass_properties: MassProperties,
+        position: Isometry<Real>,
+        linvel: Vector<Real>,
+        angvel: AngVector<Real>,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+    ) -> PredictedTrajectory {
+        let rb = RigidBodyBuilder::dynamic()
+            .position(position)
+            .linvel(linvel)
+            .angvel(angvel)
+            .additional_mass_properties(mass_properties)
+            .build();
+        let collider = ColliderBuilder::new(shape).density(0.0).build();
+
+        self.predict(rb, vec![collider], None, bodies, colliders)
+    }
+
+    fn predict(
+        &mut self,
+        rb: RigidBody,
+        body_colliders: Vec<Collider>,
+        excluded_body: Option<RigidBodyHandle>,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+    ) -> PredictedTrajectory {
+        let mut world = PhysicsWorld {
+            gravity: self.gravity,
+            integration_parameters: self.integration_parameters,
+            ..PhysicsWorld::default()
+        };
+
+        for (_, co) in colliders.iter() {
+            if co.is_sensor() || !co.is_enabled() {
+                continue;
+            }
+
+            let is_obstacle = match (self.obstacles, co.parent()) {
+                (TrajectoryObstacles::None, _) => false,
+                (_, None) => true,
+                (TrajectoryObstacles::Fixed, Some(parent)) => bodies[parent].is_fixed(),
+                (TrajectoryObstacles::All, Some(parent)) => Some(parent) != excluded_body,
+            };
+
+            if is_obstacle {
+                // NOTE: `ColliderSet::insert` detaches the copy from its parent, keeping
+                //       its current world-space position.
+                let _ = world.colliders.insert(co.clone());
+            }
+        }
+
+        let handle = world.bodies.insert(rb);
+        // The predicted body may be a copy of a sleeping body.
+        world.bodies[handle].wake_up(true);
+        let predicted_colliders: Vec<ColliderHandle> = body_colliders
+            .into_iter()
+            .map(|co| {
+                world
+                    .colliders
+                    .insert_with_parent(co, handle, &mut world.bodies)
+            })
+            .collect();
+
+        let mut result = PredictedTrajectory {
+            positions: Vec::with_capacity(self.num_steps),
+            first_contact: None,
+        };
+
+        for step in 0..self.num_steps {
+            world.step(&mut self.pipeline, None, &(), &());
+            result.positions.push(*world.bodies[handle].position());
+
+            if result.first_contact.is_none()
+                && predicted_colliders.iter().any(|co| {
+                    world
+                        .narrow_phase
+                        .contact_pairs_with(*co)
+                        .any(|pair| pair.has_any_active_contact)
+                })
+            {
+                result.first_contact = Some(step);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TrajectoryPredictor;
+    use crate::dynamics::{IntegrationParameters, RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::{ColliderBuilder, ColliderSet};
+    use crate::math::Vector;
+
+    #[test]
+    fn predicted_body_lands_on_fixed_ground() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let ground = bodies.insert(RigidBodyBuilder::fixed().translation(Vector::y() * -10.0));
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(5.0), ground, &mut bodies);
+        let ball = bodies.insert(RigidBodyBuilder::dynamic());
+        let _ = colliders.insert_with_parent(ColliderBuilder::ball(0.5), ball, &mut bodies);
+
+        let mut predictor =
+            TrajectoryPredictor::new(Vector::y() * -9.81, IntegrationParameters::default(), 120);
+        let trajectory = predictor.predict_body(ball, &bodies, &colliders);
+
+        assert_eq!(trajectory.positions.len(), 120);
+        assert!(trajectory.first_contact.is_some());
+        // The ball stops on top of the ground.
+        assert!(trajectory.positions.last().unwrap().translation.y > -5.0 - 0.1);
+        // The original ball didn’t move.
+        assert_eq!(bodies[ball].translation().y, 0.0);
+    }
+}
@@ -106,7 +106,24 @@ pub(crate) fn handle_user_changes_to_rigid_bodies(
                                     islands.active_kinematic_set.push(*handle);
                                 }
                             }
-                            RigidBodyType::Fixed => {}
+                            RigidBodyType::Fixed => {
+                                // Fixed bodies never belong to any active set.
+                                if islands.active_kinematic_set.get(ids.active_set_id)
+                                    == Some(handle)
+                                {
+                                    islands.active_kinematic_set.swap_remove(ids.active_set_id);
+                                    final_action = Some(FinalAction::UpdateActiveKinematicSetId(
+                                        ids.active_set_id,
+                                    ));
+                                } else if islands.active_dynamic_set.get(ids.active_set_id)
+                                    == Some(handle)
+                                {
+                                    islands.active_dynamic_set.swap_remove(ids.active_set_id);
+                                    final_action = Some(FinalAction::UpdateActiveDynamicSetId(
+                                        ids.active_set_id,
+                                    ));
+                                }
+                            }
                         }
                     }
 
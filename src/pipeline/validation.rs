@@ -0,0 +1,247 @@
+use crate::dynamics::{
+    ImpulseJointHandle, ImpulseJointSet, IntegrationParameters, JointAxesMask, RigidBodyHandle,
+    RigidBodySet,
+};
+use crate::geometry::{ColliderHandle, ColliderSet};
+use crate::math::{Isometry, Real};
+use crate::pipeline::{ActiveEvents, PhysicsPipeline};
+
+/// Distance from the origin, before scaling by [`IntegrationParameters::length_unit`], beyond
+/// which a collider is reported with [`DiagnosticCode::FarFromOrigin`].
+const FAR_FROM_ORIGIN_DISTANCE: Real = 1.0e5;
+
+/// The kind of issue detected by [`PhysicsPipeline::validate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// A dynamic rigid-body has a zero mass, typically because all its colliders are disabled
+    /// or have a zero density, and no additional mass was set.
+    ZeroMassDynamicBody,
+    /// A trimesh collider contains triangles with a zero area.
+    DegenerateTriangles,
+    /// A sensor collider doesn’t enable [`ActiveEvents::COLLISION_EVENTS`], so no intersection
+    /// event will ever be emitted for it.
+    SensorWithoutCollisionEvents,
+    /// A rigid-body or collider position contains NaN or infinite values.
+    NonFinitePosition,
+    /// A collider is so far from the origin that floating-point precision issues are likely.
+    FarFromOrigin,
+    /// A joint with coupled linear axes (e.g. a rope or a spring joint) has coincident anchors,
+    /// so the direction it acts along is undefined.
+    CoincidentJointAnchors,
+}
+
+impl DiagnosticCode {
+    /// A stable, machine-readable name for this code.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ZeroMassDynamicBody => "zero_mass_dynamic_body",
+            Self::DegenerateTriangles => "degenerate_triangles",
+            Self::SensorWithoutCollisionEvents => "sensor_without_collision_events",
+            Self::NonFinitePosition => "non_finite_position",
+            Self::FarFromOrigin => "far_from_origin",
+            Self::CoincidentJointAnchors => "coincident_joint_anchors",
+        }
+    }
+}
+
+/// The object a [`Diagnostic`] refers to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DiagnosticSubject {
+    /// A rigid-body.
+    RigidBody(RigidBodyHandle),
+    /// A collider.
+    Collider(ColliderHandle),
+    /// An impulse joint.
+    ImpulseJoint(ImpulseJointHandle),
+}
+
+/// An issue detected by [`PhysicsPipeline::validate`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The kind of issue.
+    pub code: DiagnosticCode,
+    /// The object the issue was detected on.
+    pub subject: DiagnosticSubject,
+    /// A human-readable description of the issue.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(code: DiagnosticCode, subject: DiagnosticSubject, message: String) -> Self {
+        Self {
+            code,
+            subject,
+            message,
+        }
+    }
+}
+
+fn is_finite(pos: &Isometry<Real>) -> bool {
+    pos.to_homogeneous().iter().all(|e| e.is_finite())
+}
+
+impl PhysicsPipeline {
+    /// Checks the given sets for common authoring errors.
+    ///
+    /// This doesn’t modify anything and is meant to be called occasionally, e.g., after loading
+    /// a scene, since it iterates through all the bodies, colliders, and joints. An empty result
+    /// doesn’t guarantee the scene is free of issues.
+    pub fn validate(
+        &self,
+        integration_parameters: &IntegrationParameters,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        impulse_joints: &ImpulseJointSet,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for (handle, rb) in bodies.iter() {
+            let subject = DiagnosticSubject::RigidBody(handle);
+
+            if !is_finite(rb.position()) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::NonFinitePosition,
+                    subject,
+                    format!("the position of the rigid-body {:?} isn’t finite", handle),
+                ));
+            }
+
+            if rb.is_dynamic() && rb.mass() == 0.0 {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::ZeroMassDynamicBody,
+                    subject,
+                    format!(
+                            "the dynamic rigid-body {:?} has a zero mass ({} enabled collider(s))",
+                            handle,
+                            rb.colliders()
+                                .iter()
+                                .filter(|co| colliders.get(**co).map(|co| co.is_enabled())
+                                    == Some(true))
+                                .count()
+                        ),
+                ));
+            }
+        }
+
+        let far_from_origin = FAR_FROM_ORIGIN_DISTANCE * integration_parameters.length_unit;
+
+        for (handle, co) in colliders.iter() {
+            let subject = DiagnosticSubject::Collider(handle);
+
+            if !is_finite(co.position()) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::NonFinitePosition,
+                    subject,
+                    format!("the position of the collider {:?} isn’t finite", handle),
+                ));
+            } else if co.translation().norm() > far_from_origin {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::FarFromOrigin,
+                    subject,
+                    format!(
+                        "the collider {:?} is {} units away from the origin",
+                        handle,
+                        co.translation().norm()
+                    ),
+                ));
+            }
+
+            if co.is_sensor() && !co.active_events().contains(ActiveEvents::COLLISION_EVENTS) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticCode::SensorWithoutCollisionEvents,
+                    subject,
+                    format!(
+                        "the sensor collider {:?} doesn’t enable ActiveEvents::COLLISION_EVENTS",
+                        handle
+                    ),
+                ));
+            }
+
+            if let Some(trimesh) = co.shape().as_trimesh() {
+                let num_degenerate = trimesh
+                    .triangles()
+                    .filter(|tri| {
+                        let longest_edge = (tri.b - tri.a)
+                            .norm_squared()
+                            .max((tri.c - tri.b).norm_squared())
+                            .max((tri.a - tri.c).norm_squared());
+                        tri.area() <= Real::EPSILON * longest_edge
+                    })
+                    .count();
+
+                if num_degenerate > 0 {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticCode::DegenerateTriangles,
+                        subject,
+                        format!(
+                            "{} of the {} triangles of the trimesh collider {:?} are degenerate",
+                            num_degenerate,
+                            trimesh.num_triangles(),
+                            handle
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (handle, joint) in impulse_joints.iter() {
+            if !joint.data.coupled_axes.intersects(JointAxesMask::LIN_AXES) {
+                continue;
+            }
+
+            if let (Some(rb1), Some(rb2)) = (bodies.get(joint.body1), bodies.get(joint.body2)) {
+                let anchor1 = rb1.position() * joint.data.local_anchor1();
+                let anchor2 = rb2.position() * joint.data.local_anchor2();
+
+                if (anchor2 - anchor1).norm() <= Real::EPSILON * integration_parameters.length_unit
+                {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticCode::CoincidentJointAnchors,
+                        DiagnosticSubject::ImpulseJoint(handle),
+                        format!(
+                            "the anchors of the joint {:?} with coupled linear axes coincide",
+                            handle
+                        ),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DiagnosticCode;
+    use crate::dynamics::{ImpulseJointSet, IntegrationParameters, RigidBodyBuilder, RigidBodySet};
+    use crate::geometry::{ColliderBuilder, ColliderSet};
+    use crate::pipeline::PhysicsPipeline;
+
+    #[test]
+    fn validate_reports_zero_mass_and_silent_sensors() {
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        let rb = bodies.insert(RigidBodyBuilder::dynamic());
+        let _ = colliders.insert_with_parent(
+            ColliderBuilder::ball(1.0).density(0.0).sensor(true),
+            rb,
+            &mut bodies,
+        );
+
+        let codes: Vec<_> = PhysicsPipeline::new()
+            .validate(
+                &IntegrationParameters::default(),
+                &bodies,
+                &colliders,
+                &ImpulseJointSet::new(),
+            )
+            .into_iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+
+        assert!(codes.contains(&DiagnosticCode::ZeroMassDynamicBody));
+        assert!(codes.contains(&DiagnosticCode::SensorWithoutCollisionEvents));
+    }
+}
@@ -213,7 +213,7 @@ impl Harness {
             let physics = &mut self.physics;
             let event_handler = &self.event_handler;
             self.state.thread_pool.install(|| {
-                physics.pipeline.step(
+                physics.pipeline.step_with_user_constraints(
                     &physics.gravity,
                     &physics.integration_parameters,
                     &mut physics.islands,
@@ -223,6 +223,7 @@ impl Harness {
                     &mut physics.colliders,
                     &mut physics.impulse_joints,
                     &mut physics.multibody_joints,
+                    &mut physics.user_constraints,
                     &mut physics.ccd_solver,
                     Some(&mut physics.query_pipeline),
                     &*physics.hooks,
@@ -232,7 +233,7 @@ impl Harness {
         }
 
         #[cfg(not(feature = "parallel"))]
-        self.physics.pipeline.step(
+        self.physics.pipeline.step_with_user_constraints(
             &self.physics.gravity,
             &self.physics.integration_parameters,
             &mut self.physics.islands,
@@ -242,6 +243,7 @@ impl Harness {
             &mut self.physics.colliders,
             &mut self.physics.impulse_joints,
             &mut self.physics.multibody_joints,
+            &mut self.physics.user_constraints,
             &mut self.physics.ccd_solver,
             Some(&mut self.physics.query_pipeline),
             &*self.physics.hooks,
@@ -1,7 +1,7 @@
 use crossbeam::channel::Receiver;
 use rapier::dynamics::{
     CCDSolver, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
-    RigidBodySet,
+    RigidBodySet, UserConstraintSet,
 };
 use rapier::geometry::{
     ColliderSet, CollisionEvent, ContactForceEvent, DefaultBroadPhase, NarrowPhase,
@@ -95,6 +95,7 @@ pub struct PhysicsState {
     pub colliders: ColliderSet,
     pub impulse_joints: ImpulseJointSet,
     pub multibody_joints: MultibodyJointSet,
+    pub user_constraints: UserConstraintSet,
     pub ccd_solver: CCDSolver,
     pub pipeline: PhysicsPipeline,
     pub query_pipeline: QueryPipeline,
@@ -119,6 +120,7 @@ impl PhysicsState {
             colliders: ColliderSet::new(),
             impulse_joints: ImpulseJointSet::new(),
             multibody_joints: MultibodyJointSet::new(),
+            user_constraints: UserConstraintSet::new(),
             ccd_solver: CCDSolver::new(),
             pipeline: PhysicsPipeline::new(),
             query_pipeline: QueryPipeline::new(),
@@ -202,10 +202,21 @@ pub fn update_ui(
                 )
                 .text("max internal stabilization iters."),
             );
+            ui.add(
+                Slider::new(
+                    &mut integration_parameters.num_additional_stabilization_iterations,
+                    0..=100,
+                )
+                .text("num additional stabilization iters."),
+            );
             ui.add(
                 Slider::new(&mut integration_parameters.warmstart_coefficient, 0.0..=1.0)
                     .text("warmstart coefficient"),
             );
+            ui.checkbox(
+                &mut integration_parameters.contact_block_solver,
+                "contact block solver",
+            );
 
             let mut substep_params = *integration_parameters;
             substep_params.dt /= substep_params.num_solver_iterations.get() as Real;